@@ -5,6 +5,7 @@ Provides generic commands for property manipulation and operator calls.
 """
 
 import bpy
+import json
 from typing import Dict, Any, Callable
 from .resolver import resolve_path, get_property, set_property, to_json_value
 
@@ -41,6 +42,30 @@ def cmd_property_get(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
         return {"success": False, "error": str(e)}
 
 
+@register_command("property.get_batch")
+def cmd_property_get_batch(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Get several property values, possibly from different objects.
+
+    Args:
+        target: Unused
+        params:
+            reads: List of {"target": "objects['Cube']", "path": "location"}
+
+    Returns:
+        {"success": True, "data": [{"value": ...} or {"error": "..."}, ...]}
+        in the order of `reads`
+    """
+    results = []
+    for read in params.get("reads", []):
+        try:
+            value = get_property(read.get("target", ""), read.get("path", ""))
+            results.append({"value": value})
+        except Exception as e:
+            results.append({"error": str(e)})
+    return {"success": True, "data": results}
+
+
 @register_command("property.set")
 def cmd_property_set(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """
@@ -122,183 +147,1857 @@ def cmd_property_set_batch(target: str, params: Dict[str, Any]) -> Dict[str, Any
         params:
             properties: List of {"path": "...", "value": ...}
 
-    Returns:
-        {"success": True, "count": N} or {"success": False, "error": "..."}
-    """
+    Returns:
+        {"success": True, "count": N} or {"success": False, "error": "..."}
+    """
+    try:
+        properties = params.get("properties", [])
+        if not properties:
+            return {"success": False, "error": "No properties to set"}
+
+        # Single undo push for all changes
+        bpy.ops.ed.undo_push(message=f"Blendmate: Batch update {target}")
+
+        count = 0
+        errors = []
+
+        for prop in properties:
+            path = prop.get("path")
+            value = prop.get("value")
+            if path is not None and value is not None:
+                try:
+                    set_property(target, path, value)
+                    count += 1
+                except Exception as e:
+                    errors.append(f"{path}: {e}")
+
+        if errors:
+            return {
+                "success": True,
+                "count": count,
+                "warnings": errors
+            }
+        return {"success": True, "count": count}
+
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("batch.execute")
+def cmd_batch_execute(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Execute a group of commands as one unit (single undo step).
+
+    If a step fails, property changes made by the earlier steps are
+    reverted so the group is applied all-or-nothing. Operator steps
+    cannot be reverted and are reported in the error instead.
+
+    Args:
+        target: Unused
+        params:
+            label: Undo step label
+            steps: List of {"action": "property.set" | "operator.call",
+                            "target": "...", "params": {...}}
+
+    Returns:
+        {"success": True, "data": {"count": N}} or {"success": False, "error": "..."}
+    """
+    steps = params.get("steps", [])
+    if not steps:
+        return {"success": False, "error": "No steps to execute"}
+
+    for step in steps:
+        if step.get("action") not in ("property.set", "operator.call"):
+            return {"success": False, "error": f"Invalid batch step action: {step.get('action')}"}
+
+    try:
+        bpy.ops.ed.undo_push(message=params.get("label", "Blendmate: Batch"))
+    except Exception:
+        pass  # Undo push might fail in some contexts, continue anyway
+
+    applied = []  # (target, path, previous value) for rollback
+    operators_run = []
+
+    for index, step in enumerate(steps):
+        action = step["action"]
+        step_target = step.get("target", "")
+        step_params = step.get("params", {})
+
+        try:
+            if action == "property.set":
+                path = step_params.get("path")
+                if not path:
+                    raise ValueError("Property path is required")
+                previous = get_property(step_target, path)
+                set_property(step_target, path, step_params.get("value"))
+                applied.append((step_target, path, previous))
+            else:
+                result = cmd_operator_call(step_target, step_params)
+                if not result.get("success"):
+                    raise RuntimeError(result.get("error", "Operator failed"))
+                operators_run.append(step_target)
+        except Exception as e:
+            # Roll back in reverse order
+            for prev_target, prev_path, prev_value in reversed(applied):
+                try:
+                    set_property(prev_target, prev_path, prev_value)
+                except Exception:
+                    pass
+
+            error = f"Step {index} ({action} {step_target}) failed: {e}"
+            if operators_run:
+                error += f". Operators already run (not reverted): {', '.join(operators_run)}"
+            return {"success": False, "error": error}
+
+    return {"success": True, "data": {"count": len(steps)}}
+
+
+# Operators behind each quick action; they act on the selected objects
+_QUICK_ACTIONS = {
+    "apply_transforms": lambda: bpy.ops.object.transform_apply(location=True, rotation=True, scale=True),
+    "clear_custom_normals": lambda: bpy.ops.mesh.customdata_custom_splitnormals_clear(),
+    "convert_to_mesh": lambda: bpy.ops.object.convert(target='MESH'),
+}
+
+
+@register_command("objects.quick_action")
+def cmd_objects_quick_action(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Run a quick action on several objects as one undo step.
+
+    Objects are processed one at a time, each selected and active on its
+    own, and the run stops at the first failure. Operators can't be
+    reverted step by step, so a partial run is reported rather than an
+    error: one undo in Blender reverts everything the run changed.
+
+    Args:
+        target: Unused
+        params:
+            action: "apply_transforms", "clear_custom_normals" or "convert_to_mesh"
+            objects: Object names
+            label: Undo step label
+
+    Returns:
+        {"success": True, "data": {"applied": [...], "failed": {"name", "error"} | None,
+                                   "not_run": [...]}}
+    """
+    run = _QUICK_ACTIONS.get(params.get("action"))
+    if run is None:
+        return {"success": False, "error": f"Invalid quick action: {params.get('action')}"}
+    names = params.get("objects", [])
+    if not names:
+        return {"success": False, "error": "No objects to process"}
+    if bpy.context.mode != 'OBJECT':
+        return {"success": False, "error": "Quick actions need Object Mode (invalid context)"}
+    missing = [name for name in names if name not in bpy.data.objects]
+    if missing:
+        return {"success": False, "error": f"Objects not found: {', '.join(missing)}"}
+
+    view_layer = bpy.context.view_layer
+    selected = [obj.name for obj in bpy.context.selected_objects]
+    active = view_layer.objects.active.name if view_layer.objects.active else None
+
+    try:
+        bpy.ops.ed.undo_push(message="Before " + params.get("label", "Blendmate: Quick action"))
+    except Exception:
+        pass
+
+    applied = []
+    failed = None
+    try:
+        for index, name in enumerate(names):
+            obj = bpy.data.objects[name]
+            try:
+                bpy.ops.object.select_all(action='DESELECT')
+                obj.select_set(True)
+                view_layer.objects.active = obj
+                result = run()
+                if 'FINISHED' not in result:
+                    raise RuntimeError(f"operator returned {', '.join(result)}")
+                # Converting can replace the object
+                applied.append(view_layer.objects.active.name if view_layer.objects.active else name)
+            except Exception as e:
+                failed = {"name": name, "error": str(e)}
+                break
+    finally:
+        # Restore the selection, minus objects that no longer exist
+        try:
+            bpy.ops.object.select_all(action='DESELECT')
+            for name in selected:
+                obj = bpy.data.objects.get(name)
+                if obj:
+                    obj.select_set(True)
+            view_layer.objects.active = bpy.data.objects.get(active) if active else None
+        except Exception:
+            pass
+
+    if applied:
+        try:
+            bpy.ops.ed.undo_push(message=params.get("label", "Blendmate: Quick action"))
+        except Exception:
+            pass
+
+    done = len(applied) + (1 if failed else 0)
+    return {
+        "success": True,
+        "data": {"applied": applied, "failed": failed, "not_run": names[done:]},
+    }
+
+
+@register_command("object.select")
+def cmd_object_select(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Select/deselect an object.
+
+    Args:
+        target: Object name (not full path), e.g. "Cube"
+        params:
+            mode: "set" (replace selection), "add", "remove", "toggle"
+            active: If True, also make this the active object
+
+    Returns:
+        {"success": True} or {"success": False, "error": "..."}
+    """
+    try:
+        obj = bpy.data.objects.get(target)
+        if not obj:
+            return {"success": False, "error": f"Object '{target}' not found"}
+
+        mode = params.get("mode", "set")
+        make_active = params.get("active", True)
+
+        if mode == "set":
+            # Deselect all, then select this one
+            bpy.ops.object.select_all(action='DESELECT')
+            obj.select_set(True)
+        elif mode == "add":
+            obj.select_set(True)
+        elif mode == "remove":
+            obj.select_set(False)
+        elif mode == "toggle":
+            obj.select_set(not obj.select_get())
+        else:
+            return {"success": False, "error": f"Unknown mode: {mode}"}
+
+        if make_active and obj.select_get():
+            bpy.context.view_layer.objects.active = obj
+
+        return {"success": True}
+
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("object.rename")
+def cmd_object_rename(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Rename an object.
+
+    Args:
+        target: Current object name
+        params:
+            name: New name
+
+    Returns:
+        {"success": True, "data": {"name": "final_name"}}
+    """
+    try:
+        obj = bpy.data.objects.get(target)
+        if not obj:
+            return {"success": False, "error": f"Object '{target}' not found"}
+
+        new_name = params.get("name")
+        if not new_name:
+            return {"success": False, "error": "Missing 'name' parameter"}
+
+        bpy.ops.ed.undo_push(message=f"Blendmate: Rename {target} to {new_name}")
+        obj.name = new_name
+
+        # Return the final name (Blender may add suffix if name exists)
+        return {"success": True, "data": {"name": obj.name}}
+
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("collection.sync")
+def cmd_collection_sync(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Make a collection contain exactly the given objects.
+
+    The collection is created under the scene collection when missing.
+    Objects are only linked to or unlinked from this collection; their
+    other collections are left alone.
+
+    Args:
+        target: Collection name
+        params:
+            objects: Object names the collection should contain
+
+    Returns:
+        {"success": True, "data": {"name", "linked", "unlinked", "missing"}}
+    """
+    try:
+        if not target:
+            return {"success": False, "error": "Missing collection name"}
+        wanted = set(params.get("objects", []))
+
+        bpy.ops.ed.undo_push(message=f"Blendmate: Sync collection {target}")
+        collection = bpy.data.collections.get(target)
+        if collection is None:
+            collection = bpy.data.collections.new(target)
+            bpy.context.scene.collection.children.link(collection)
+
+        unlinked = []
+        for obj in list(collection.objects):
+            if obj.name not in wanted:
+                collection.objects.unlink(obj)
+                unlinked.append(obj.name)
+
+        linked = []
+        missing = []
+        for name in sorted(wanted):
+            obj = bpy.data.objects.get(name)
+            if obj is None:
+                missing.append(name)
+            elif collection.objects.get(name) is None:
+                collection.objects.link(obj)
+                linked.append(name)
+
+        return {
+            "success": True,
+            "data": {
+                "name": collection.name,
+                "linked": linked,
+                "unlinked": unlinked,
+                "missing": missing,
+            },
+        }
+
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("operator.call")
+def cmd_operator_call(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Call a Blender operator.
+
+    Args:
+        target: Operator path, e.g. "object.duplicate" or "mesh.primitive_cube_add"
+        params: Operator arguments
+
+    Returns:
+        {"success": True, "data": {"result": "FINISHED"}}
+
+    Security: Only allows operators from a whitelist of safe categories.
+    """
+    try:
+        # Parse operator path
+        parts = target.split(".")
+        if len(parts) != 2:
+            return {"success": False, "error": f"Invalid operator path: {target}"}
+
+        category, name = parts
+
+        # Whitelist of safe operator categories
+        SAFE_CATEGORIES = {
+            'object', 'mesh', 'curve', 'surface', 'armature', 'lattice',
+            'transform', 'view3d', 'node', 'material', 'texture',
+            'uv', 'paint', 'sculpt', 'gpencil', 'anim', 'action',
+            'marker', 'pose', 'constraint', 'screen', 'wm',
+        }
+
+        # Blacklist of dangerous operators
+        BLOCKED_OPERATORS = {
+            'wm.quit_blender', 'wm.save_mainfile', 'wm.open_mainfile',
+            'wm.read_factory_settings', 'wm.recover_auto_save',
+            'wm.recover_last_session', 'preferences.addon_install',
+            'preferences.addon_remove', 'script.python_file_run',
+            'script.execute_preset', 'text.run_script',
+        }
+
+        full_name = f"{category}.{name}"
+
+        if full_name in BLOCKED_OPERATORS:
+            return {"success": False, "error": f"Operator '{full_name}' is blocked for security"}
+
+        if category not in SAFE_CATEGORIES:
+            return {"success": False, "error": f"Operator category '{category}' is not allowed"}
+
+        # Get the operator
+        if not hasattr(bpy.ops, category):
+            return {"success": False, "error": f"Unknown operator category: {category}"}
+
+        op_category = getattr(bpy.ops, category)
+        if not hasattr(op_category, name):
+            return {"success": False, "error": f"Unknown operator: {full_name}"}
+
+        op = getattr(op_category, name)
+
+        # Push undo before operator
+        bpy.ops.ed.undo_push(message=f"Blendmate: {full_name}")
+
+        # Call the operator
+        result = op(**params)
+
+        return {"success": True, "data": {"result": str(result)}}
+
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("render.get_settings")
+def cmd_render_get_settings(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Get render settings of a scene.
+
+    Args:
+        target: Scene name (empty = active scene)
+
+    Returns:
+        {"success": True, "data": {"engine": ..., "filepath": ..., ...}}
+    """
+    try:
+        scene = bpy.data.scenes.get(target) if target else bpy.context.scene
+        if not scene:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+
+        render = scene.render
+        return {"success": True, "data": {
+            "scene": scene.name,
+            "engine": render.engine,
+            "filepath": render.filepath,
+            "file_format": render.image_settings.file_format,
+            "resolution_x": render.resolution_x,
+            "resolution_y": render.resolution_y,
+            "resolution_percentage": render.resolution_percentage,
+            "use_border": render.use_border,
+            "fps": render.fps,
+            "frame_start": scene.frame_start,
+            "frame_end": scene.frame_end,
+            "frame_step": scene.frame_step,
+        }}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+def _cycles_preferences():
+    addon = bpy.context.preferences.addons.get("cycles")
+    if not addon:
+        raise ValueError("Cycles add-on is not enabled")
+    return addon.preferences
+
+
+def _render_devices_info() -> Dict[str, Any]:
+    prefs = _cycles_preferences()
+    prefs.refresh_devices()
+    scene = bpy.context.scene
+    return {
+        "compute_device_type": prefs.compute_device_type,
+        "available_types": [item[0] for item in prefs.get_device_types(bpy.context)],
+        "devices": [
+            {"name": d.name, "type": d.type, "id": d.id, "use": d.use}
+            for d in prefs.get_devices_for_type(prefs.compute_device_type)
+        ],
+        "scene_device": scene.cycles.device if scene and hasattr(scene, "cycles") else None,
+    }
+
+
+@register_command("render.get_devices")
+def cmd_render_get_devices(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Get the Cycles compute device type and devices.
+
+    Returns:
+        {"success": True, "data": {"compute_device_type": "OPTIX", "devices": [...], ...}}
+    """
+    try:
+        return {"success": True, "data": _render_devices_info()}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("render.set_devices")
+def cmd_render_set_devices(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Apply a device profile to the Cycles preferences and the scene.
+
+    Args:
+        target: Unused
+        params:
+            compute_device_type: "CUDA", "OPTIX", "HIP", "METAL", "ONEAPI" or "NONE"
+                (omit to keep the current one)
+            use_cpu: Enable the CPU alongside GPUs
+            gpus: Indices of the GPUs to enable, in device order (null = all)
+
+    Returns:
+        {"success": True, "data": {...same as render.get_devices}}
+    """
+    try:
+        prefs = _cycles_preferences()
+        bpy.ops.ed.undo_push(message="Blendmate: Set render devices")
+        if params.get("compute_device_type"):
+            prefs.compute_device_type = params["compute_device_type"]
+        prefs.refresh_devices()
+
+        wanted = params.get("gpus")
+        gpu_index = 0
+        any_gpu = False
+        for device in prefs.get_devices_for_type(prefs.compute_device_type):
+            if device.type == 'CPU':
+                device.use = bool(params.get("use_cpu", False))
+            else:
+                device.use = wanted is None or gpu_index in wanted
+                any_gpu = any_gpu or device.use
+                gpu_index += 1
+
+        if hasattr(bpy.context.scene, "cycles"):
+            bpy.context.scene.cycles.device = 'GPU' if any_gpu else 'CPU'
+
+        return {"success": True, "data": _render_devices_info()}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+def _enum_items(owner, prop: str) -> list:
+    return [item.identifier for item in owner.bl_rna.properties[prop].enum_items]
+
+
+@register_command("color.get_settings")
+def cmd_color_get_settings(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Get the scene's color management settings and the active OCIO config.
+
+    Returns:
+        {"success": True, "data": {"display_device": ..., "view_transform": ...,
+         "available_view_transforms": [...], "ocio_env": ..., ...}}
+    """
+    import os
+
+    try:
+        scene = bpy.context.scene
+        display = scene.display_settings
+        view = scene.view_settings
+        bundled = os.path.join(bpy.utils.resource_path('LOCAL'), "datafiles", "colormanagement", "config.ocio")
+
+        return {"success": True, "data": {
+            "display_device": display.display_device,
+            "view_transform": view.view_transform,
+            "look": view.look,
+            "exposure": view.exposure,
+            "gamma": view.gamma,
+            "sequencer_colorspace": scene.sequencer_colorspace_settings.name,
+            "available_displays": _enum_items(display, "display_device"),
+            "available_view_transforms": _enum_items(view, "view_transform"),
+            "available_looks": _enum_items(view, "look"),
+            "ocio_env": os.environ.get("OCIO"),
+            "bundled_config": bundled,
+            "blender_version": list(bpy.app.version),
+        }}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("color.set_settings")
+def cmd_color_set_settings(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Set scene color management settings. Omitted keys are left alone.
+
+    Args:
+        target: Unused
+        params: display_device, view_transform, look, exposure, gamma,
+            sequencer_colorspace
+
+    Returns:
+        {"success": True}
+    """
+    try:
+        scene = bpy.context.scene
+        display = scene.display_settings
+        view = scene.view_settings
+
+        for key, owner, allowed in (
+            ("display_device", display, _enum_items(display, "display_device")),
+            ("view_transform", view, _enum_items(view, "view_transform")),
+        ):
+            if params.get(key) and params[key] not in allowed:
+                return {"success": False, "error": f"'{params[key]}' is not available for {key} in this OCIO config"}
+
+        bpy.ops.ed.undo_push(message="Blendmate: Set color management")
+        if params.get("display_device"):
+            display.display_device = params["display_device"]
+        # The view transform must be set before the look, which depends on it
+        if params.get("view_transform"):
+            view.view_transform = params["view_transform"]
+        if params.get("look"):
+            view.look = params["look"]
+        if params.get("exposure") is not None:
+            view.exposure = params["exposure"]
+        if params.get("gamma") is not None:
+            view.gamma = params["gamma"]
+        if params.get("sequencer_colorspace"):
+            scene.sequencer_colorspace_settings.name = params["sequencer_colorspace"]
+
+        return {"success": True}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+# bpy.data collections that can hold linked or overridden datablocks
+ID_COLLECTIONS = (
+    'objects', 'collections', 'meshes', 'materials', 'node_groups',
+    'armatures', 'actions', 'cameras', 'lights', 'curves', 'images',
+    'textures', 'worlds', 'scenes', 'grease_pencils', 'particles',
+)
+
+
+@register_command("library.get_links")
+def cmd_library_get_links(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Get the libraries linked into the open file and its library overrides.
+
+    Returns:
+        {"success": True, "data": {"libraries": [...], "overrides": [...]}}
+    """
+    import os
+    from .. import connection
+
+    try:
+        libraries = {}
+        for lib in bpy.data.libraries:
+            abspath = bpy.path.abspath(lib.filepath)
+            exists = os.path.exists(abspath)
+            libraries[lib.name] = {
+                "name": lib.name,
+                "filepath": lib.filepath,
+                "abspath": abspath,
+                "exists": exists,
+                "mtime": os.path.getmtime(abspath) if exists else None,
+                "parent": lib.parent.name if lib.parent else None,
+                "version": list(lib.version) if hasattr(lib, "version") else None,
+                "linked_ids": {},
+                "missing_ids": 0,
+            }
+
+        overrides = []
+        for collection_name in ID_COLLECTIONS:
+            for id_block in getattr(bpy.data, collection_name, []):
+                if id_block.library and id_block.library.name in libraries:
+                    entry = libraries[id_block.library.name]
+                    entry["linked_ids"][collection_name] = entry["linked_ids"].get(collection_name, 0) + 1
+                    if getattr(id_block, "is_missing", False):
+                        entry["missing_ids"] += 1
+
+                override = getattr(id_block, "override_library", None)
+                if override and override.reference:
+                    reference = override.reference
+                    overrides.append({
+                        "name": id_block.name,
+                        "type": collection_name,
+                        "reference": reference.name,
+                        "reference_library": reference.library.name if reference.library else None,
+                    })
+
+        return {"success": True, "data": {
+            "filepath": bpy.data.filepath,
+            "blender_version": list(bpy.app.version),
+            "loaded_at": connection._file_loaded_at,
+            "libraries": list(libraries.values()),
+            "overrides": overrides,
+        }}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("library.relink")
+def cmd_library_relink(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Point libraries at a moved or renamed file and reload them.
+
+    Args:
+        target: Unused
+        params:
+            libraries: Names of the libraries to relink
+            filepath: New absolute path; stored relative to the open file
+                where the old path was relative
+
+    Returns:
+        {"success": True, "data": {"libraries": [{"name", "filepath", "exists", "missing_ids", "error"}]}}
+    """
+    import os
+
+    try:
+        names = params.get("libraries", [])
+        new_path = params.get("filepath")
+        if not names or not new_path:
+            return {"success": False, "error": "libraries and filepath are required"}
+        if not os.path.exists(new_path):
+            return {"success": False, "error": f"File not found: {new_path}"}
+
+        results = []
+        for name in names:
+            lib = bpy.data.libraries.get(name)
+            if lib is None:
+                results.append({"name": name, "error": "Library not found"})
+                continue
+            relative = lib.filepath.startswith("//") and bool(bpy.data.filepath)
+            try:
+                lib.filepath = bpy.path.relpath(new_path) if relative else new_path
+                lib.reload()
+            except (RuntimeError, ValueError) as e:
+                results.append({"name": name, "filepath": lib.filepath, "error": str(e)})
+                continue
+            results.append({"name": lib.name, "filepath": lib.filepath, "error": None})
+
+        # Counted after every reload, as reloading one library can resolve
+        # datablocks that come through another
+        missing = {}
+        for collection_name in ID_COLLECTIONS:
+            for id_block in getattr(bpy.data, collection_name, []):
+                if id_block.library and getattr(id_block, "is_missing", False):
+                    missing[id_block.library.name] = missing.get(id_block.library.name, 0) + 1
+        for result in results:
+            lib = bpy.data.libraries.get(result["name"])
+            result["exists"] = lib is not None and os.path.exists(bpy.path.abspath(lib.filepath))
+            result["missing_ids"] = missing.get(result["name"], 0)
+
+        return {"success": True, "data": {"libraries": results}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("text.get")
+def cmd_text_get(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Get the content of a text datablock.
+
+    Args:
+        target: Unused
+        params:
+            name: Text datablock name
+
+    Returns:
+        {"success": True, "data": {"name": ..., "content": ..., "filepath": ..., "is_modified": bool}}
+    """
+    try:
+        name = params.get("name")
+        text = bpy.data.texts.get(name) if name else None
+        if text is None:
+            return {"success": False, "error": f"Text not found: {name}"}
+        return {"success": True, "data": {
+            "name": text.name,
+            "content": text.as_string(),
+            "filepath": text.filepath,
+            "is_modified": text.is_modified,
+        }}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("text.set")
+def cmd_text_set(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Replace the content of a text datablock, creating it if needed.
+
+    Args:
+        target: Unused
+        params:
+            name: Text datablock name
+            content: New content
+
+    Returns:
+        {"success": True, "data": {"name": ..., "lines": int, "created": bool}}
+    """
+    try:
+        name = params.get("name")
+        content = params.get("content")
+        if not name or content is None:
+            return {"success": False, "error": "name and content are required"}
+        text = bpy.data.texts.get(name)
+        created = text is None
+        if created:
+            text = bpy.data.texts.new(name)
+        # Keep the cursor where it was, as far as the new content allows
+        line = text.current_line_index
+        text.from_string(content)
+        text.current_line_index = min(line, len(text.lines) - 1)
+        return {"success": True, "data": {"name": text.name, "lines": len(text.lines), "created": created}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+# Namespaces of console sessions by name, kept until reset or the add-on
+# is reloaded
+_console_namespaces = {}
+
+# Characters of printed output returned per line; the rest is cut
+CONSOLE_OUTPUT_LIMIT = 64 * 1024
+
+
+def _console_namespace(session):
+    """Namespace of a console session, set up like Blender's Python console."""
+    namespace = _console_namespaces.get(session)
+    if namespace is None:
+        namespace = {"__name__": "__console__", "bpy": bpy, "C": bpy.context, "D": bpy.data}
+        exec("from mathutils import *\nfrom math import *", namespace)
+        _console_namespaces[session] = namespace
+    return namespace
+
+
+def _find_console():
+    """First (window, area, region) showing a Python console, or None."""
+    for window in bpy.context.window_manager.windows:
+        for area in window.screen.areas:
+            if area.type != 'CONSOLE':
+                continue
+            for region in area.regions:
+                if region.type == 'WINDOW':
+                    return window, area, region
+    return None
+
+
+@register_command("console.exec")
+def cmd_console_exec(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Run one line of Python in a console session.
+
+    An expression is evaluated and its repr returned, as in Blender's
+    console; other statements are executed. Names defined by earlier lines
+    of the same session stay defined. Operators run with a Python console
+    area as context when one is open.
+
+    Args:
+        target: Unused
+        params:
+            line: Python source, usually one line
+            session: Session name (default: "default")
+
+    Returns:
+        {"success": True, "data": {"result": repr or None, "output": printed text,
+                                   "error": traceback or None}}
+    """
+    import contextlib
+    import io
+    import sys
+    import traceback
+
+    line = params.get("line", "")
+    if not line.strip():
+        return {"success": True, "data": {"result": None, "output": "", "error": None}}
+    namespace = _console_namespace(params.get("session") or "default")
+
+    output = io.StringIO()
+    result = None
+    error = None
+    try:
+        try:
+            code = compile(line, "<console>", "eval")
+            is_expression = True
+        except SyntaxError:
+            code = compile(line, "<console>", "exec")
+            is_expression = False
+
+        found = _find_console()
+        override = (bpy.context.temp_override(window=found[0], area=found[1], region=found[2])
+                    if found else contextlib.nullcontext())
+        with override, contextlib.redirect_stdout(output), contextlib.redirect_stderr(output):
+            value = eval(code, namespace)
+        if is_expression and value is not None:
+            namespace["_"] = value
+            result = repr(value)
+    except Exception:
+        # Drop the frames of this handler, keep the ones of the line
+        kind, exc, tb = sys.exc_info()
+        error = "".join(traceback.format_exception_only(kind, exc))
+        while tb is not None and tb.tb_frame.f_code.co_filename != "<console>":
+            tb = tb.tb_next
+        if tb is not None:
+            error = "Traceback (most recent call last):\n" + "".join(traceback.format_tb(tb)) + error
+
+    text = output.getvalue()
+    if len(text) > CONSOLE_OUTPUT_LIMIT:
+        text = text[:CONSOLE_OUTPUT_LIMIT] + "\n[output cut]"
+    return {"success": True, "data": {"result": result, "output": text, "error": error}}
+
+
+@register_command("console.reset")
+def cmd_console_reset(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Forget the names defined in a console session.
+
+    Args:
+        target: Unused
+        params:
+            session: Session name (default: "default")
+
+    Returns:
+        {"success": True, "data": {"existed": bool}}
+    """
+    existed = _console_namespaces.pop(params.get("session") or "default", None) is not None
+    return {"success": True, "data": {"existed": existed}}
+
+
+# bpy.data collections that can be marked as assets
+ASSET_ID_TYPES = ('actions', 'objects', 'collections', 'materials', 'node_groups', 'worlds')
+
+
+@register_command("assets.list")
+def cmd_assets_list(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    List configured asset libraries and the assets they contain.
+
+    Library .blend files are read with assets_only=True, which lists asset
+    names without linking anything into the open file.
+
+    Args:
+        target: Unused
+        params:
+            max_files: Stop scanning after this many .blend files (default 500)
+
+    Returns:
+        {"success": True, "data": {"filepath": ..., "libraries": [...], "assets": [...]}}
+    """
+    import os
+
+    try:
+        max_files = params.get("max_files", 500)
+        libraries = []
+        assets = []
+
+        # Assets marked in the open file carry catalog ids
+        for id_type in ASSET_ID_TYPES:
+            for id_block in getattr(bpy.data, id_type):
+                if id_block.asset_data and not id_block.library:
+                    assets.append({
+                        "name": id_block.name,
+                        "id_type": id_type,
+                        "library": None,
+                        "filepath": bpy.data.filepath,
+                        "catalog_id": id_block.asset_data.catalog_id,
+                    })
+
+        scanned = 0
+        for lib in bpy.context.preferences.filepaths.asset_libraries:
+            root = bpy.path.abspath(lib.path)
+            libraries.append({"name": lib.name, "path": root})
+
+            for dirpath, _dirnames, filenames in os.walk(root):
+                for filename in filenames:
+                    if not filename.endswith(".blend") or scanned >= max_files:
+                        continue
+                    scanned += 1
+                    filepath = os.path.join(dirpath, filename)
+                    try:
+                        with bpy.data.libraries.load(filepath, assets_only=True) as (data_from, _data_to):
+                            for id_type in ASSET_ID_TYPES:
+                                for name in getattr(data_from, id_type):
+                                    assets.append({
+                                        "name": name,
+                                        "id_type": id_type,
+                                        "library": lib.name,
+                                        "filepath": filepath,
+                                        "catalog_id": None,
+                                    })
+                    except Exception as e:
+                        print(f"[Blendmate] Cannot read assets from {filepath}: {e}")
+
+        return {"success": True, "data": {
+            "filepath": bpy.data.filepath,
+            "libraries": libraries,
+            "assets": assets,
+            "truncated": scanned >= max_files,
+        }}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+def _load_asset(filepath: str, id_type: str, name: str, link: bool = False):
+    """Append (or link) a datablock from a .blend, or return it if it is local."""
+    if not filepath or filepath == bpy.data.filepath:
+        id_block = getattr(bpy.data, id_type).get(name)
+        if not id_block:
+            raise ValueError(f"Asset '{name}' not found in {id_type}")
+        return id_block, False
+
+    with bpy.data.libraries.load(filepath, link=link) as (data_from, data_to):
+        if name not in getattr(data_from, id_type):
+            raise ValueError(f"Asset '{name}' not found in {filepath}")
+        setattr(data_to, id_type, [name])
+
+    loaded = getattr(data_to, id_type)
+    if not loaded or loaded[0] is None:
+        raise ValueError(f"Failed to load '{name}' from {filepath}")
+    return loaded[0], True
+
+
+@register_command("assets.append")
+def cmd_assets_append(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Append or link an asset into the open file.
+
+    Objects are added to the active collection, collections to the scene.
+
+    Args:
+        target: Unused
+        params:
+            filepath: .blend containing the asset
+            id_type: bpy.data collection name, e.g. "objects"
+            name: Asset name
+            link: Link instead of append (default False)
+
+    Returns:
+        {"success": True, "data": {"name": "final_name"}}
+    """
+    try:
+        id_type = params.get("id_type")
+        if id_type not in ASSET_ID_TYPES:
+            return {"success": False, "error": f"Invalid asset type: {id_type}"}
+
+        bpy.ops.ed.undo_push(message=f"Blendmate: Append {params.get('name')}")
+        id_block, _ = _load_asset(params.get("filepath", ""), id_type, params.get("name", ""), params.get("link", False))
+
+        if id_type == 'objects':
+            bpy.context.collection.objects.link(id_block)
+        elif id_type == 'collections':
+            bpy.context.scene.collection.children.link(id_block)
+
+        return {"success": True, "data": {"name": id_block.name}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("assets.apply_pose")
+def cmd_assets_apply_pose(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Apply a pose asset (action) to an armature.
+
+    Args:
+        target: Armature object name
+        params:
+            filepath: .blend containing the pose asset (empty = open file)
+            name: Action name
+
+    Returns:
+        {"success": True}
+    """
+    try:
+        obj = bpy.data.objects.get(target)
+        if not obj:
+            return {"success": False, "error": f"Object '{target}' not found"}
+        if obj.type != 'ARMATURE':
+            return {"success": False, "error": f"Object '{target}' is not an armature (invalid context)"}
+
+        bpy.ops.ed.undo_push(message=f"Blendmate: Apply pose {params.get('name')}")
+        action, appended = _load_asset(params.get("filepath", ""), 'actions', params.get("name", ""))
+        obj.pose.apply_pose_from_action(action)
+
+        # Don't leave appended pose actions behind in the file
+        if appended and action.users == 0:
+            bpy.data.actions.remove(action)
+
+        return {"success": True}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+def _point_cache_directory(point_cache) -> str:
+    """Directory a point cache reads from and writes to."""
+    import os
+
+    if point_cache.use_external:
+        return bpy.path.abspath(point_cache.filepath)
+    stem = os.path.splitext(os.path.basename(bpy.data.filepath))[0]
+    return bpy.path.abspath(f"//blendcache_{stem}")
+
+
+def _find_cache(obj, source: str):
+    """Resolve a cache source name (modifier name or "particles:<name>")."""
+    if source.startswith("particles:"):
+        psys = obj.particle_systems.get(source[len("particles:"):])
+        return psys.point_cache if psys else None
+    modifier = obj.modifiers.get(source)
+    if not modifier:
+        return None
+    if modifier.type == 'FLUID':
+        return modifier.domain_settings
+    return getattr(modifier, "point_cache", None)
+
+
+@register_command("caches.list")
+def cmd_caches_list(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    List the simulation caches referenced by the open file.
+
+    Covers fluid domains, cloth/soft body/dynamic paint point caches,
+    particle systems and the rigid body world.
+
+    Returns:
+        {"success": True, "data": {"caches": [{"object", "source", "kind",
+         "directory", "baked", "use_disk_cache", "frame_start", "frame_end"}]}}
+    """
+    try:
+        caches = []
+
+        def add_point_cache(obj_name, source, kind, point_cache):
+            caches.append({
+                "object": obj_name,
+                "source": source,
+                "kind": kind,
+                "directory": _point_cache_directory(point_cache),
+                "baked": point_cache.is_baked,
+                "use_disk_cache": point_cache.use_disk_cache or point_cache.use_external,
+                "frame_start": point_cache.frame_start,
+                "frame_end": point_cache.frame_end,
+            })
+
+        for obj in bpy.data.objects:
+            for modifier in obj.modifiers:
+                if modifier.type == 'FLUID' and modifier.fluid_type == 'DOMAIN':
+                    domain = modifier.domain_settings
+                    caches.append({
+                        "object": obj.name,
+                        "source": modifier.name,
+                        "kind": "fluid",
+                        "directory": bpy.path.abspath(domain.cache_directory),
+                        "baked": bool(domain.has_cache_baked_data or domain.has_cache_baked_mesh),
+                        "use_disk_cache": True,
+                        "frame_start": domain.cache_frame_start,
+                        "frame_end": domain.cache_frame_end,
+                    })
+                elif modifier.type in ('CLOTH', 'SOFT_BODY'):
+                    add_point_cache(obj.name, modifier.name, modifier.type.lower(), modifier.point_cache)
+                elif modifier.type == 'DYNAMIC_PAINT' and modifier.canvas_settings:
+                    for surface in modifier.canvas_settings.canvas_surfaces:
+                        add_point_cache(obj.name, modifier.name, "dynamic_paint", surface.point_cache)
+
+            for psys in obj.particle_systems:
+                add_point_cache(obj.name, f"particles:{psys.name}", "particles", psys.point_cache)
+
+        for scene in bpy.data.scenes:
+            world = scene.rigidbody_world
+            if world and world.point_cache:
+                add_point_cache(scene.name, "rigidbody", "rigid_body", world.point_cache)
+
+        return {"success": True, "data": {"caches": caches}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("caches.set_directory")
+def cmd_caches_set_directory(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Point a cache at another directory.
+
+    Args:
+        target: Object name
+        params:
+            source: Modifier name or "particles:<system name>"
+            directory: New cache directory
+
+    Returns:
+        {"success": True}
+    """
+    try:
+        obj = bpy.data.objects.get(target)
+        if not obj:
+            return {"success": False, "error": f"Object '{target}' not found"}
+        cache = _find_cache(obj, params.get("source", ""))
+        if cache is None:
+            return {"success": False, "error": f"No cache '{params.get('source')}' on '{target}'"}
+
+        bpy.ops.ed.undo_push(message="Blendmate: Relocate cache")
+        directory = params.get("directory", "")
+        if hasattr(cache, "cache_directory"):
+            cache.cache_directory = directory
+        else:
+            cache.use_external = True
+            cache.filepath = directory
+
+        return {"success": True}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+# Shot metadata (status, explicit end, order) is kept as JSON in this scene
+# property, keyed by marker name. The markers themselves are the shots.
+SHOTS_PROPERTY = "blendmate_shots"
+
+
+def _shot_meta(scene) -> Dict[str, Any]:
+    try:
+        return json.loads(scene.get(SHOTS_PROPERTY, "{}"))
+    except (TypeError, ValueError):
+        return {}
+
+
+def _set_shot_meta(scene, meta: Dict[str, Any]):
+    scene[SHOTS_PROPERTY] = json.dumps(meta)
+
+
+def _scene_shots(scene) -> list:
+    meta = _shot_meta(scene)
+    review = _review_markers(scene)
+    markers = sorted(
+        (m for m in scene.timeline_markers if (m.name, m.frame) not in review),
+        key=lambda m: m.frame,
+    )
+    shots = []
+    for index, marker in enumerate(markers):
+        info = meta.get(marker.name, {})
+        if info.get("end") is not None:
+            end = info["end"]
+        elif index + 1 < len(markers):
+            end = markers[index + 1].frame - 1
+        else:
+            end = scene.frame_end
+        shots.append({
+            "scene": scene.name,
+            "name": marker.name,
+            "start": marker.frame,
+            "end": max(end, marker.frame),
+            "camera": marker.camera.name if marker.camera else None,
+            "status": info.get("status", "todo"),
+            "order": info.get("order"),
+        })
+    return shots
+
+
+def _shot_scene(target: str):
+    return bpy.data.scenes.get(target) if target else bpy.context.scene
+
+
+@register_command("shots.list")
+def cmd_shots_list(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    List shots: one per timeline marker, running until the next marker.
+
+    Args:
+        target: Scene name, or empty for every scene
+
+    Returns:
+        {"success": True, "data": {"shots": [...], "scene", "frame"}}
+    """
+    try:
+        scenes = [bpy.data.scenes.get(target)] if target else list(bpy.data.scenes)
+        if None in scenes:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        shots = []
+        for scene in scenes:
+            shots.extend(_scene_shots(scene))
+        return {
+            "success": True,
+            "data": {
+                "shots": shots,
+                "scene": bpy.context.scene.name,
+                "frame": bpy.context.scene.frame_current,
+            },
+        }
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("shots.set")
+def cmd_shots_set(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Create or update a shot's marker and metadata.
+
+    Args:
+        target: Scene name, or empty for the current scene
+        params:
+            name: Shot (marker) name
+            rename_from: Existing shot to rename (optional)
+            start: First frame
+            end: Last frame (optional, default: until the next marker)
+            camera: Camera object bound to the marker (optional)
+            status: Production status (optional)
+
+    Returns:
+        {"success": True, "data": {"shots": [...]}} for the scene
+    """
+    try:
+        scene = _shot_scene(target)
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        name = params.get("name", "")
+        if not name:
+            return {"success": False, "error": "Missing shot name"}
+        old_name = params.get("rename_from") or name
+        camera = None
+        if params.get("camera"):
+            camera = bpy.data.objects.get(params["camera"])
+            if camera is None or camera.type != 'CAMERA':
+                return {"success": False, "error": f"Camera '{params['camera']}' not found"}
+
+        markers = scene.timeline_markers
+        if old_name != name and markers.get(name) is not None:
+            return {"success": False, "error": f"Shot '{name}' already exists"}
+
+        bpy.ops.ed.undo_push(message=f"Blendmate: Set shot {name}")
+        marker = markers.get(old_name)
+        if marker is None:
+            marker = markers.new(name, frame=int(params.get("start", scene.frame_current)))
+        marker.name = name
+        if "start" in params:
+            marker.frame = int(params["start"])
+        marker.camera = camera
+
+        meta = _shot_meta(scene)
+        info = meta.pop(old_name, {})
+        info["end"] = params.get("end")
+        if params.get("status"):
+            info["status"] = params["status"]
+        meta[name] = info
+        _set_shot_meta(scene, meta)
+
+        return {"success": True, "data": {"shots": _scene_shots(scene)}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("shots.delete")
+def cmd_shots_delete(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Remove a shot's marker and metadata.
+
+    Args:
+        target: Scene name, or empty for the current scene
+        params:
+            name: Shot name
+
+    Returns:
+        {"success": True}
+    """
+    try:
+        scene = _shot_scene(target)
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        name = params.get("name", "")
+        marker = scene.timeline_markers.get(name)
+        if marker is None:
+            return {"success": False, "error": f"Shot '{name}' not found"}
+
+        bpy.ops.ed.undo_push(message=f"Blendmate: Delete shot {name}")
+        scene.timeline_markers.remove(marker)
+        meta = _shot_meta(scene)
+        meta.pop(name, None)
+        _set_shot_meta(scene, meta)
+        return {"success": True}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("shots.reorder")
+def cmd_shots_reorder(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Set the storyboard order of a scene's shots. Frames are not moved.
+
+    Args:
+        target: Scene name, or empty for the current scene
+        params:
+            names: Shot names in the new order; others keep no order
+
+    Returns:
+        {"success": True}
+    """
+    try:
+        scene = _shot_scene(target)
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        names = params.get("names", [])
+        meta = _shot_meta(scene)
+        for info in meta.values():
+            info.pop("order", None)
+        for order, name in enumerate(names):
+            if scene.timeline_markers.get(name) is None:
+                return {"success": False, "error": f"Shot '{name}' not found"}
+            meta.setdefault(name, {})["order"] = order
+
+        bpy.ops.ed.undo_push(message="Blendmate: Reorder shots")
+        _set_shot_meta(scene, meta)
+        return {"success": True}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("shots.jump")
+def cmd_shots_jump(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Show a shot: switch to its scene, go to its first frame and make its
+    camera active.
+
+    Args:
+        target: Scene name, or empty for the current scene
+        params:
+            name: Shot name
+            preview_range: Limit playback to the shot (default false)
+
+    Returns:
+        {"success": True}
+    """
+    try:
+        scene = _shot_scene(target)
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        shot = next((s for s in _scene_shots(scene) if s["name"] == params.get("name")), None)
+        if shot is None:
+            return {"success": False, "error": f"Shot '{params.get('name')}' not found"}
+
+        window = bpy.context.window or (bpy.context.window_manager.windows[0] if bpy.context.window_manager.windows else None)
+        if window is not None and window.scene != scene:
+            window.scene = scene
+        scene.frame_set(shot["start"])
+        if shot["camera"]:
+            scene.camera = bpy.data.objects[shot["camera"]]
+        if params.get("preview_range"):
+            scene.use_preview_range = True
+            scene.frame_preview_start = shot["start"]
+            scene.frame_preview_end = shot["end"]
+        return {"success": True}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+# Markers that carry review notes, as [name, frame] pairs in this scene
+# property. They are left out of the shot list.
+REVIEW_PROPERTY = "blendmate_review_markers"
+
+
+def _review_markers(scene) -> set:
     try:
-        properties = params.get("properties", [])
-        if not properties:
-            return {"success": False, "error": "No properties to set"}
+        return {(name, frame) for name, frame in json.loads(scene.get(REVIEW_PROPERTY, "[]"))}
+    except (TypeError, ValueError):
+        return set()
 
-        # Single undo push for all changes
-        bpy.ops.ed.undo_push(message=f"Blendmate: Batch update {target}")
 
-        count = 0
-        errors = []
+def _all_markers() -> list:
+    return [
+        {"scene": scene.name, "name": marker.name, "frame": marker.frame}
+        for scene in bpy.data.scenes
+        for marker in scene.timeline_markers
+    ]
 
-        for prop in properties:
-            path = prop.get("path")
-            value = prop.get("value")
-            if path is not None and value is not None:
-                try:
-                    set_property(target, path, value)
-                    count += 1
-                except Exception as e:
-                    errors.append(f"{path}: {e}")
 
-        if errors:
-            return {
-                "success": True,
-                "count": count,
-                "warnings": errors
-            }
-        return {"success": True, "count": count}
+@register_command("review.list_markers")
+def cmd_review_list_markers(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    List the timeline markers of every scene, for syncing review notes.
 
+    Returns:
+        {"success": True, "data": {"filepath", "markers": [{scene, name, frame}]}}
+    """
+    try:
+        return {"success": True, "data": {"filepath": bpy.data.filepath, "markers": _all_markers()}}
     except Exception as e:
         return {"success": False, "error": str(e)}
 
 
-@register_command("object.select")
-def cmd_object_select(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+@register_command("review.apply")
+def cmd_review_apply(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """
-    Select/deselect an object.
+    Create, change and remove the markers of review notes in one undo step.
 
     Args:
-        target: Object name (not full path), e.g. "Cube"
         params:
-            mode: "set" (replace selection), "add", "remove", "toggle"
-            active: If True, also make this the active object
+            changes: [{op: "create"|"update"|"delete", scene, name, frame,
+                      old_name, old_frame}]; updates and deletes find the
+                      marker by its old name and frame
+            linked: {scene: [[name, frame], ...]} markers that carry notes
+                    once the changes are made
 
     Returns:
-        {"success": True} or {"success": False, "error": "..."}
+        {"success": True, "data": {"markers": [...]}} of every scene
     """
     try:
-        obj = bpy.data.objects.get(target)
-        if not obj:
-            return {"success": False, "error": f"Object '{target}' not found"}
+        changes = params.get("changes", [])
+        for change in changes:
+            if bpy.data.scenes.get(change.get("scene", "")) is None:
+                return {"success": False, "error": f"Scene '{change.get('scene')}' not found"}
+
+        bpy.ops.ed.undo_push(message="Blendmate: Sync review markers")
+        for change in changes:
+            markers = bpy.data.scenes[change["scene"]].timeline_markers
+            if change["op"] == "create":
+                markers.new(change["name"], frame=int(change["frame"]))
+                continue
+            marker = next(
+                (m for m in markers if m.name == change["old_name"] and m.frame == change["old_frame"]),
+                None,
+            )
+            if marker is None:
+                continue
+            if change["op"] == "delete":
+                markers.remove(marker)
+            else:
+                marker.name = change["name"]
+                marker.frame = int(change["frame"])
+
+        linked = params.get("linked", {})
+        for scene in bpy.data.scenes:
+            if scene.name in linked or REVIEW_PROPERTY in scene:
+                scene[REVIEW_PROPERTY] = json.dumps(linked.get(scene.name, []))
+        return {"success": True, "data": {"markers": _all_markers()}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
 
-        mode = params.get("mode", "set")
-        make_active = params.get("active", True)
 
-        if mode == "set":
-            # Deselect all, then select this one
-            bpy.ops.object.select_all(action='DESELECT')
-            obj.select_set(True)
-        elif mode == "add":
-            obj.select_set(True)
-        elif mode == "remove":
-            obj.select_set(False)
-        elif mode == "toggle":
-            obj.select_set(not obj.select_get())
-        else:
-            return {"success": False, "error": f"Unknown mode: {mode}"}
+def _playing_window():
+    """A window whose screen can play animation, and whether it is playing."""
+    for window in bpy.context.window_manager.windows:
+        if window.screen is not None:
+            return window, window.screen.is_animation_playing
+    return None, False
 
-        if make_active and obj.select_get():
-            bpy.context.view_layer.objects.active = obj
 
-        return {"success": True}
+@register_command("timeline.get_state")
+def cmd_timeline_get_state(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Read the playhead and frame rate of a scene.
+
+    Args:
+        target: Scene name, or empty for the current scene
 
+    Returns:
+        {"success": True, "data": {"frame", "subframe", "fps", "frame_start",
+        "frame_end", "playing"}}
+    """
+    try:
+        scene = bpy.data.scenes.get(target) if target else bpy.context.scene
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        _, playing = _playing_window()
+        preview = scene.use_preview_range
+        return {
+            "success": True,
+            "data": {
+                "frame": scene.frame_current,
+                "subframe": scene.frame_subframe,
+                "fps": scene.render.fps / scene.render.fps_base,
+                "frame_start": scene.frame_preview_start if preview else scene.frame_start,
+                "frame_end": scene.frame_preview_end if preview else scene.frame_end,
+                "playing": playing,
+            },
+        }
     except Exception as e:
         return {"success": False, "error": str(e)}
 
 
-@register_command("object.rename")
-def cmd_object_rename(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+@register_command("timeline.set")
+def cmd_timeline_set(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """
-    Rename an object.
+    Move the playhead and start or stop playback.
 
     Args:
-        target: Current object name
+        target: Scene name, or empty for the current scene
         params:
-            name: New name
+            frame: Frame to go to, may be fractional (optional)
+            play: True to play, False to stop where it is (optional)
 
     Returns:
-        {"success": True, "data": {"name": "final_name"}}
+        {"success": True, "data": {"frame", "playing"}}
     """
     try:
-        obj = bpy.data.objects.get(target)
-        if not obj:
-            return {"success": False, "error": f"Object '{target}' not found"}
+        scene = bpy.data.scenes.get(target) if target else bpy.context.scene
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        if params.get("frame") is not None:
+            frame = float(params["frame"])
+            whole = int(frame // 1)
+            scene.frame_set(whole, subframe=frame - whole)
+
+        window, playing = _playing_window()
+        play = params.get("play")
+        if play is not None and bool(play) != playing:
+            if window is None:
+                return {"success": False, "error": "No window to play in"}
+            with bpy.context.temp_override(window=window, screen=window.screen):
+                if play:
+                    bpy.ops.screen.animation_play()
+                else:
+                    bpy.ops.screen.animation_cancel(restore_frame=False)
+            playing = bool(play)
+        return {"success": True, "data": {"frame": scene.frame_current, "playing": playing}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
 
-        new_name = params.get("name")
-        if not new_name:
-            return {"success": False, "error": "Missing 'name' parameter"}
 
-        bpy.ops.ed.undo_push(message=f"Blendmate: Rename {target} to {new_name}")
-        obj.name = new_name
+@register_command("audio.list_strips")
+def cmd_audio_list_strips(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    List the scene's sound strips with their timing, and its audio options.
 
-        # Return the final name (Blender may add suffix if name exists)
-        return {"success": True, "data": {"name": obj.name}}
+    Args:
+        target: Scene name, or empty for the current scene
 
+    Returns:
+        {"success": True, "data": {"fps", "strips": [...], "options": {...}}}
+    """
+    try:
+        scene = bpy.data.scenes.get(target) if target else bpy.context.scene
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+
+        strips = []
+        editor = scene.sequence_editor
+        # Renamed to strips_all in Blender 4.4
+        if editor is None:
+            all_strips = []
+        elif hasattr(editor, "strips_all"):
+            all_strips = editor.strips_all
+        else:
+            all_strips = editor.sequences_all
+        for strip in all_strips:
+            if strip.type != 'SOUND' or strip.sound is None:
+                continue
+            strips.append({
+                "name": strip.name,
+                "filepath": bpy.path.abspath(strip.sound.filepath, library=strip.sound.library),
+                "channel": strip.channel,
+                # Frame the sound's first sample plays at, before trimming
+                "frame_start": strip.frame_start,
+                "frame_final_start": strip.frame_final_start,
+                "frame_final_end": strip.frame_final_end,
+                "volume": strip.volume,
+                "mute": strip.mute,
+            })
+        strips.sort(key=lambda s: (s["channel"], s["frame_final_start"]))
+
+        return {
+            "success": True,
+            "data": {
+                "fps": scene.render.fps / (scene.render.fps_base or 1.0),
+                "strips": strips,
+                "options": {
+                    "scrub": scene.use_audio_scrub,
+                    "sync_mode": scene.sync_mode,
+                    "mute": scene.use_audio,
+                },
+            },
+        }
     except Exception as e:
         return {"success": False, "error": str(e)}
 
 
-@register_command("operator.call")
-def cmd_operator_call(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+@register_command("audio.set_options")
+def cmd_audio_set_options(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """
-    Call a Blender operator.
+    Change audio scrubbing, playback sync and muting.
 
     Args:
-        target: Operator path, e.g. "object.duplicate" or "mesh.primitive_cube_add"
-        params: Operator arguments
+        target: Scene name, or empty for the current scene
+        params:
+            scrub: Play audio while scrubbing (optional)
+            sync_mode: AUDIO_SYNC, FRAME_DROP or NONE (optional)
+            mute: Mute all audio (optional)
 
     Returns:
-        {"success": True, "data": {"result": "FINISHED"}}
-
-    Security: Only allows operators from a whitelist of safe categories.
+        {"success": True}
     """
     try:
-        # Parse operator path
-        parts = target.split(".")
-        if len(parts) != 2:
-            return {"success": False, "error": f"Invalid operator path: {target}"}
+        scene = bpy.data.scenes.get(target) if target else bpy.context.scene
+        if scene is None:
+            return {"success": False, "error": f"Scene '{target}' not found"}
+        sync_mode = params.get("sync_mode")
+        if sync_mode is not None and sync_mode not in ('AUDIO_SYNC', 'FRAME_DROP', 'NONE'):
+            return {"success": False, "error": f"Unknown sync mode '{sync_mode}'"}
+
+        if params.get("scrub") is not None:
+            scene.use_audio_scrub = bool(params["scrub"])
+        if sync_mode is not None:
+            scene.sync_mode = sync_mode
+        if params.get("mute") is not None:
+            # Despite its name, use_audio mutes the scene
+            scene.use_audio = bool(params["mute"])
+        return {"success": True}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
 
-        category, name = parts
 
-        # Whitelist of safe operator categories
-        SAFE_CATEGORIES = {
-            'object', 'mesh', 'curve', 'surface', 'armature', 'lattice',
-            'transform', 'view3d', 'node', 'material', 'texture',
-            'uv', 'paint', 'sculpt', 'gpencil', 'anim', 'action',
-            'marker', 'pose', 'constraint', 'screen', 'wm',
-        }
+@register_command("reference.push")
+def cmd_reference_push(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Show a reference image in the scene, as an image empty or a camera
+    background.
 
-        # Blacklist of dangerous operators
-        BLOCKED_OPERATORS = {
-            'wm.quit_blender', 'wm.save_mainfile', 'wm.open_mainfile',
-            'wm.read_factory_settings', 'wm.recover_auto_save',
-            'wm.recover_last_session', 'preferences.addon_install',
-            'preferences.addon_remove', 'script.python_file_run',
-            'script.execute_preset', 'text.run_script',
-        }
+    Args:
+        params:
+            name: Image name
+            filepath: Image file on this machine, or
+            data: Base64 image data, packed into the .blend (remote sessions)
+            extension: File extension of `data`, e.g. ".png"
+            mode: "empty" or "camera_background"
+            camera: Camera object for "camera_background" (default: scene camera)
+            opacity: 0-1 (default: 1 for empties, 0.5 for backgrounds)
 
-        full_name = f"{category}.{name}"
+    Returns:
+        {"success": True, "data": {"image", "object"}}
+    """
+    import base64
+    import os
 
-        if full_name in BLOCKED_OPERATORS:
-            return {"success": False, "error": f"Operator '{full_name}' is blocked for security"}
+    try:
+        mode = params.get("mode", "empty")
+        if mode not in ("empty", "camera_background"):
+            return {"success": False, "error": f"Unknown mode '{mode}'"}
+        scene = bpy.context.scene
+        camera = None
+        if mode == "camera_background":
+            camera = bpy.data.objects.get(params["camera"]) if params.get("camera") else scene.camera
+            if camera is None or camera.type != 'CAMERA':
+                return {"success": False, "error": "No camera to put the background on"}
+
+        bpy.ops.ed.undo_push(message="Blendmate: Add reference image")
+        if params.get("data"):
+            fd, path = _mkstemp(params.get("extension", ".png"))
+            with os.fdopen(fd, "wb") as file:
+                file.write(base64.b64decode(params["data"]))
+            try:
+                image = bpy.data.images.load(path)
+                image.pack()
+            finally:
+                os.remove(path)
+        else:
+            image = bpy.data.images.load(params.get("filepath", ""), check_existing=True)
+        if params.get("name"):
+            image.name = params["name"]
+
+        if mode == "empty":
+            obj = bpy.data.objects.new(image.name, None)
+            obj.empty_display_type = 'IMAGE'
+            obj.empty_display_size = 5.0
+            obj.data = image
+            obj.location = scene.cursor.location
+            opacity = float(params.get("opacity", 1.0))
+            if opacity < 1.0:
+                obj.use_empty_image_alpha = True
+                obj.color[3] = opacity
+            scene.collection.objects.link(obj)
+        else:
+            obj = camera
+            camera.data.show_background_images = True
+            background = camera.data.background_images.new()
+            background.image = image
+            background.alpha = float(params.get("opacity", 0.5))
 
-        if category not in SAFE_CATEGORIES:
-            return {"success": False, "error": f"Operator category '{category}' is not allowed"}
+        return {"success": True, "data": {"image": image.name, "object": obj.name}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
 
-        # Get the operator
-        if not hasattr(bpy.ops, category):
-            return {"success": False, "error": f"Unknown operator category: {category}"}
 
-        op_category = getattr(bpy.ops, category)
-        if not hasattr(op_category, name):
-            return {"success": False, "error": f"Unknown operator: {full_name}"}
+@register_command("palette.apply")
+def cmd_palette_apply(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Set a material's base color or a paint brush's color.
 
-        op = getattr(op_category, name)
+    Args:
+        target: Material or brush name (default: the active object's active
+            material, or the brush of the current paint mode)
+        params:
+            kind: "material" or "brush"
+            linear: [r, g, b] scene linear, used for materials
+            srgb: [r, g, b] display encoded, used for brushes
 
-        # Push undo before operator
-        bpy.ops.ed.undo_push(message=f"Blendmate: {full_name}")
+    Returns:
+        {"success": True, "data": {"kind", "name"}}
+    """
+    try:
+        kind = params.get("kind", "material")
+        if kind == "material":
+            if target:
+                material = bpy.data.materials.get(target)
+            else:
+                obj = bpy.context.active_object
+                material = obj.active_material if obj else None
+            if material is None:
+                return {"success": False, "error": f"Material not found: {target or '(active)'}"}
+            color = tuple(params.get("linear", (0.8, 0.8, 0.8))[:3]) + (1.0,)
+
+            bpy.ops.ed.undo_push(message="Blendmate: Set material color")
+            material.diffuse_color = color
+            if material.use_nodes and material.node_tree:
+                for node in material.node_tree.nodes:
+                    if node.type == 'BSDF_PRINCIPLED':
+                        node.inputs["Base Color"].default_value = color
+            return {"success": True, "data": {"kind": kind, "name": material.name}}
+
+        if kind == "brush":
+            tool_settings = bpy.context.tool_settings
+            paint = {
+                'PAINT_TEXTURE': getattr(tool_settings, "image_paint", None),
+                'PAINT_VERTEX': getattr(tool_settings, "vertex_paint", None),
+                'SCULPT': getattr(tool_settings, "sculpt", None),
+                'PAINT_GPENCIL': getattr(tool_settings, "gpencil_paint", None),
+                'PAINT_GREASE_PENCIL': getattr(tool_settings, "gpencil_paint", None),
+            }.get(bpy.context.mode)
+            brush = bpy.data.brushes.get(target) if target else (paint.brush if paint else None)
+            if brush is None:
+                return {"success": False, "error": f"Brush not found: {target or '(no paint mode active)'}"}
+            color = tuple(params.get("srgb", (1.0, 1.0, 1.0))[:3])
+
+            bpy.ops.ed.undo_push(message="Blendmate: Set brush color")
+            brush.color = color
+            # With unified color on, painting uses the shared color instead
+            unified = getattr(paint, "unified_paint_settings", None) or getattr(
+                tool_settings, "unified_paint_settings", None
+            )
+            if unified is not None and getattr(unified, "use_unified_color", False):
+                unified.color = color
+            return {"success": True, "data": {"kind": kind, "name": brush.name}}
+
+        return {"success": False, "error": f"Unknown kind '{kind}'"}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
 
-        # Call the operator
-        result = op(**params)
 
-        return {"success": True, "data": {"result": str(result)}}
+@register_command("licenses.used")
+def cmd_licenses_used(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    List what the open file uses from outside itself, for license checks:
+    linked and appended assets, fonts, images and sounds.
 
+    Appended datablocks are only known when Blender kept a weak reference
+    to the file they came from.
+
+    Returns:
+        {"success": True, "data": {"filepath", "assets": [...], "files": [...]}}
+    """
+    try:
+        assets = []
+        for id_type in ASSET_ID_TYPES:
+            for id_block in getattr(bpy.data, id_type):
+                if id_block.library:
+                    assets.append({
+                        "filepath": bpy.path.abspath(id_block.library.filepath),
+                        "id_type": id_type,
+                        "name": id_block.name,
+                        "appended": False,
+                    })
+                    continue
+                weak = getattr(id_block, "library_weak_reference", None)
+                if weak and weak.filepath:
+                    assets.append({
+                        "filepath": bpy.path.abspath(weak.filepath),
+                        "id_type": id_type,
+                        # The stored name starts with the two letter ID code
+                        "name": weak.id_name[2:],
+                        "appended": True,
+                    })
+
+        files = []
+        for kind, collection in (("font", bpy.data.fonts), ("image", bpy.data.images), ("sound", bpy.data.sounds)):
+            for id_block in collection:
+                filepath = getattr(id_block, "filepath", "")
+                if not filepath or filepath == "<builtin>":
+                    continue
+                library = id_block.library
+                files.append({
+                    "kind": kind,
+                    "name": id_block.name,
+                    "filepath": bpy.path.abspath(filepath, library=library),
+                    "packed": bool(getattr(id_block, "packed_file", None)),
+                })
+
+        return {"success": True, "data": {"filepath": bpy.data.filepath, "assets": assets, "files": files}}
     except Exception as e:
         return {"success": False, "error": str(e)}
 
@@ -739,13 +2438,164 @@ def _get_primitive_meshes() -> list:
     ]
 
 
+@register_command("echo")
+def cmd_echo(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Return the request unchanged; used by the app's conformance checks.
+
+    Returns:
+        {"success": True, "data": {"target": target, "params": params}}
+    """
+    return {"success": True, "data": {"target": target, "params": params}}
+
+
+@register_command("viewport.capture")
+def cmd_viewport_capture(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Capture the first 3D viewport as a JPEG preview frame.
+
+    Args:
+        params:
+            width: Frame width in pixels, never upscaled; the height keeps
+                the viewport's aspect (default: 640)
+            quality: JPEG quality 1-100 (default: 80)
+
+    Returns:
+        {"success": True, "data": {"image": "<base64 JPEG>", "width": 640, "height": 360, "quality": 80}}
+        or {"success": False, "error": "..."}
+    """
+    import base64
+    import os
+
+    width = max(16, int(params.get("width", 640)))
+    quality = min(100, max(1, int(params.get("quality", 80))))
+
+    found = _find_view3d()
+    if not found:
+        return {"success": False, "error": "No 3D viewport to capture"}
+    window, area, region = found
+
+    fd, png_path = _mkstemp(".png")
+    os.close(fd)
+    jpg_path = png_path[:-4] + ".jpg"
+    image = None
+    try:
+        with bpy.context.temp_override(window=window, area=area, region=region):
+            bpy.ops.screen.screenshot_area(filepath=png_path)
+
+        image = bpy.data.images.load(png_path)
+        source_width, source_height = image.size
+        if width < source_width:
+            height = max(1, round(source_height * width / source_width))
+            image.scale(width, height)
+        else:
+            width, height = source_width, source_height
+
+        image.file_format = 'JPEG'
+        image.save(filepath=jpg_path, quality=quality)
+        with open(jpg_path, "rb") as f:
+            data = base64.b64encode(f.read()).decode("ascii")
+
+        return {"success": True, "data": {
+            "image": data,
+            "width": width,
+            "height": height,
+            "quality": quality,
+        }}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+    finally:
+        if image is not None:
+            bpy.data.images.remove(image)
+        for path in (png_path, jpg_path):
+            if os.path.exists(path):
+                os.remove(path)
+
+
+def _find_view3d():
+    """First (window, area, region) showing a 3D viewport, or None."""
+    for window in bpy.context.window_manager.windows:
+        for area in window.screen.areas:
+            if area.type != 'VIEW_3D':
+                continue
+            for region in area.regions:
+                if region.type == 'WINDOW':
+                    return window, area, region
+    return None
+
+
+# Temporary directory the app cleans up when the session ends; None until
+# the app sends one
+_session_temp_dir = None
+
+
+def _mkstemp(suffix: str):
+    """tempfile.mkstemp in the session's temporary directory, if there is one."""
+    import os
+    import tempfile
+
+    directory = _session_temp_dir if _session_temp_dir and os.path.isdir(_session_temp_dir) else None
+    return tempfile.mkstemp(suffix=suffix, dir=directory)
+
+
+@register_command("session.set_temp_dir")
+def cmd_session_set_temp_dir(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Keep this session's temporary files in a directory the app manages.
+
+    Args:
+        params:
+            path: Directory on this machine. Ignored when it doesn't exist,
+                e.g. when the app runs on another machine.
+
+    Returns:
+        {"success": True, "data": {"path": "/path" or None}}
+    """
+    global _session_temp_dir
+    import os
+
+    try:
+        path = params.get("path")
+        _session_temp_dir = path if path and os.path.isdir(path) else None
+        return {"success": True, "data": {"path": _session_temp_dir}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+@register_command("events.set_throttle")
+def cmd_events_set_throttle(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Change how long high-frequency events are coalesced before sending.
+
+    Args:
+        params:
+            interval: Window in seconds (default: 0.1)
+
+    Returns:
+        {"success": True, "data": {"interval": 0.3}} or {"success": False, "error": "..."}
+    """
+    try:
+        from .. import throttle
+
+        interval = throttle.set_interval(params.get("interval", throttle.DEFAULT_INTERVAL))
+        return {"success": True, "data": {"interval": interval}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
 @register_command("addon.reload")
 def cmd_addon_reload(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """
     Reload the Blendmate addon.
 
-    This safely disables and re-enables the addon, reloading all Python modules.
-    Useful for development when addon code has changed.
+    By default this safely disables and re-enables the addon, reloading all
+    Python modules. Useful for development when addon code has changed.
+
+    Args:
+        params:
+            keep_connection: Reload every module except the connection, so
+                the WebSocket and protocol session stay up (default: False).
+                Changes to __init__.py or connection.py need a full reload.
 
     Returns:
         {"success": True} or {"success": False, "error": "..."}
@@ -753,13 +2603,14 @@ def cmd_addon_reload(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
     import sys
 
     try:
-        # Get the addon module name
-        package_name = __package__.split('.')[0] if __package__ and '.' in __package__ else __package__
-        if not package_name:
-            package_name = "blendmate-addon"
-
+        package_name = _addon_package()
         print(f"[Blendmate] Reloading addon: {package_name}")
 
+        if params.get("keep_connection"):
+            reloaded = _reload_keeping_connection(package_name)
+            print("[Blendmate] Addon reloaded successfully (connection kept)")
+            return {"success": True, "data": {"reloaded": True, "modules": reloaded}}
+
         # Try to unregister first
         try:
             if package_name in sys.modules:
@@ -784,6 +2635,93 @@ def cmd_addon_reload(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
         return {"success": False, "error": str(e)}
 
 
+@register_command("addon.write_files")
+def cmd_addon_write_files(target: str, params: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Overwrite source files of the installed addon, e.g. before a reload.
+
+    Args:
+        params:
+            files: [{"path": "commands/handlers.py", "content": "..."}],
+                paths relative to the addon directory
+
+    Returns:
+        {"success": True, "data": {"directory": "...", "written": [...]}}
+        or {"success": False, "error": "..."}
+    """
+    import os
+    import sys
+
+    try:
+        root = os.path.dirname(os.path.abspath(sys.modules[_addon_package()].__file__))
+        files = params.get("files", [])
+
+        # Check every path before writing anything
+        targets = []
+        for entry in files:
+            relative = os.path.normpath(entry["path"])
+            if os.path.isabs(relative) or relative == ".." or relative.startswith(".." + os.sep):
+                return {"success": False, "error": f"Path is outside the addon: {entry['path']}"}
+            targets.append((relative, entry.get("content", "")))
+
+        written = []
+        for relative, content in targets:
+            path = os.path.join(root, relative)
+            os.makedirs(os.path.dirname(path), exist_ok=True)
+            with open(path, "w", encoding="utf-8", newline="") as f:
+                f.write(content)
+            written.append(relative)
+
+        return {"success": True, "data": {"directory": root, "written": written}}
+    except Exception as e:
+        return {"success": False, "error": str(e)}
+
+
+def _addon_package() -> str:
+    """Top-level package name the addon is installed under."""
+    package_name = __package__.split('.')[0] if __package__ and '.' in __package__ else __package__
+    return package_name or "blendmate-addon"
+
+
+def _reload_keeping_connection(package_name: str) -> list:
+    """
+    Unregister, reload and register every addon module except connection.
+
+    The connection module owns the WebSocket thread, message queues and
+    protocol version, so leaving it loaded keeps the session alive.
+    """
+    import importlib
+    import sys
+
+    root = sys.modules[package_name]
+    names = [name for name in root.modules if name != "connection"]
+
+    for name in reversed(names):
+        module = sys.modules.get(f"{package_name}.{name}")
+        if module and hasattr(module, "unregister"):
+            try:
+                module.unregister()
+            except Exception as e:
+                print(f"[Blendmate] Unregister warning ({name}): {e}")
+
+    # Subpackage modules first, so packages re-import the new code
+    for sub_name in ("commands.resolver", "commands.handlers"):
+        full_name = f"{package_name}.{sub_name}"
+        if full_name in sys.modules:
+            importlib.reload(sys.modules[full_name])
+
+    for name in names:
+        full_name = f"{package_name}.{name}"
+        if full_name in sys.modules:
+            module = importlib.reload(sys.modules[full_name])
+        else:
+            module = importlib.import_module(full_name)
+        if hasattr(module, "register"):
+            module.register()
+
+    return names
+
+
 def handle_command(action: str, target: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """
     Handle a command by dispatching to the appropriate handler.
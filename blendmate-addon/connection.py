@@ -16,6 +16,9 @@ try:
 except ImportError:
     _protocol_available = False
 
+from . import health
+from . import reports
+
 # Global connection state
 # Use module-level caching to preserve state across reloads
 # IMPORTANT: Get old module reference FIRST before any state access
@@ -43,6 +46,10 @@ else:
 
 _last_node_id = None
 
+# When the open .blend was loaded (updated by handlers.on_load_post);
+# used to tell whether linked libraries changed on disk since then
+_file_loaded_at = _old_module._file_loaded_at if _old_module and hasattr(_old_module, '_file_loaded_at') else time.time()
+
 # Also preserve queues across reloads to avoid losing messages
 if _old_module and hasattr(_old_module, '_message_queue'):
     _message_queue = _old_module._message_queue
@@ -104,6 +111,19 @@ def send_to_blendmate(data):
         else:
             _message_queue.put(data)
 
+def fire_trigger(name, payload=None):
+    """
+    Send a custom.trigger event with a user-chosen name and JSON payload.
+
+    Lets scripts signal the app without protocol changes. The app drops
+    triggers with invalid names, oversized payloads, or that fire too often.
+    """
+    if not _protocol_available:
+        info("Custom triggers need protocol support")
+        return
+    event = protocol.create_event("custom.trigger", protocol.event_custom_trigger(name, payload))
+    _message_queue.put(event)
+
 # ============== Scene Introspection ==============
 
 def get_object_info(obj):
@@ -172,6 +192,8 @@ def get_object_info(obj):
                 "edges": len(mesh.edges),
                 "polygons": len(mesh.polygons),
                 "materials": len(mesh.materials),
+                "has_uv": len(mesh.uv_layers) > 0,
+                "has_custom_normals": mesh.has_custom_normals,
             }
             # Geometry is NOT included here - use get_geometry action for heavy data
             # This keeps get_scene fast and lightweight
@@ -696,7 +718,8 @@ def handle_request(request_data):
 
         # Command handlers (property.set, property.get, operator.call, etc.)
         elif _commands_available and action in COMMAND_HANDLERS:
-            result = dispatch_command(action, target, params)
+            with reports.capture(action, target) as outcome:
+                result = outcome["result"] = dispatch_command(action, target, params)
             if result.get("success"):
                 return make_response(data=result.get("data"), warnings=result.get("warnings"))
             else:
@@ -795,6 +818,22 @@ def is_ws_connected():
 
 _heartbeat_interval = 5.0  # seconds
 
+def _operator_state():
+    """Last finished operator and the running modal operators."""
+    operator = None
+    modal = []
+    try:
+        window_manager = bpy.context.window_manager
+        if len(window_manager.operators):
+            operator = window_manager.operators[-1].bl_idname
+        for window in window_manager.windows:
+            # Window.modal_operators is not in every supported Blender version
+            for op in getattr(window, "modal_operators", ()):
+                modal.append(op.bl_idname)
+    except:
+        pass
+    return operator, modal
+
 def send_heartbeat():
     """Send periodic heartbeat with basic status."""
     if not _should_run.is_set():
@@ -813,10 +852,12 @@ def send_heartbeat():
                 pass
 
             filepath = bpy.data.filepath or "(unsaved)"
+            operator, modal = _operator_state()
+            metrics = health.collect()
 
             if is_protocol_v1() and _protocol_available:
                 # Native protocol format
-                heartbeat = protocol.create_heartbeat(active_obj, mode, filepath)
+                heartbeat = protocol.create_heartbeat(active_obj, mode, filepath, operator, modal, metrics)
                 _message_queue.put(heartbeat)
             else:
                 # Legacy format
@@ -825,6 +866,9 @@ def send_heartbeat():
                     "active_object": active_obj,
                     "mode": mode,
                     "filepath": filepath,
+                    "operator": operator,
+                    "modal": modal,
+                    "health": metrics,
                 })
         except Exception as e:
             info(f"Heartbeat error: {e}")
@@ -59,9 +59,16 @@ def _register_app_handlers():
     handlers_to_register = [
         (bpy.app.handlers.save_post, handlers.on_save_post),
         (bpy.app.handlers.load_post, handlers.on_load_post),
+        (bpy.app.handlers.depsgraph_update_pre, handlers.on_depsgraph_update_pre),
         (bpy.app.handlers.depsgraph_update_post, handlers.on_depsgraph_update),
+        (bpy.app.handlers.frame_change_pre, handlers.on_frame_change_pre),
         (bpy.app.handlers.frame_change_post, handlers.on_frame_change),
     ]
+    # Not in every supported Blender version
+    if hasattr(bpy.app.handlers, "animation_playback_post"):
+        handlers_to_register.append((bpy.app.handlers.animation_playback_post, handlers.on_playback_post))
+    if hasattr(bpy.app.handlers, "object_bake_pre"):
+        handlers_to_register.append((bpy.app.handlers.object_bake_pre, handlers.on_bake_pre))
     
     for handler_list, handler_func in handlers_to_register:
         if handler_func not in handler_list:
@@ -1,6 +1,9 @@
+import time
 import bpy
 from . import connection
 from . import throttle
+from . import profiler
+from . import health
 
 # Protocol import
 try:
@@ -15,8 +18,15 @@ def _use_v1():
     return connection.is_protocol_v1() and _protocol_available
 
 
+@bpy.app.handlers.persistent
+def on_depsgraph_update_pre(scene, *args):
+    health.depsgraph_started()
+
+
 @bpy.app.handlers.persistent
 def on_depsgraph_update(scene, depsgraph):
+    health.depsgraph_finished()
+
     # Optimization: Only check nodes if we have a connection
     # and only if something in the scene actually changed nodes-related
     current_node_id = connection.get_active_gn_node()
@@ -79,8 +89,15 @@ def on_depsgraph_update(scene, depsgraph):
             )
 
 
+@bpy.app.handlers.persistent
+def on_frame_change_pre(scene, *args):
+    profiler.frame_started()
+
+
 @bpy.app.handlers.persistent
 def on_frame_change(scene, *args):
+    profiler.frame_finished(scene, args[0] if args else None)
+
     # Throttle frame change events to avoid high-frequency spam during playback
     if _use_v1():
         body = protocol.event_timeline_frame_changed(scene.frame_current)
@@ -116,6 +133,7 @@ def on_save_post(scene, *args):
 @bpy.app.handlers.persistent
 def on_load_post(scene, *args):
     connection.info("File Loaded")
+    connection._file_loaded_at = time.time()
     filepath = bpy.data.filepath or "(unsaved)"
     blender_version = ".".join(str(v) for v in bpy.app.version[:3])
     addon_version = "1.0.0"
@@ -134,5 +152,25 @@ def on_load_post(scene, *args):
         connection.send_to_blendmate({"type": "event", "event": "load_post", "filename": filepath})
 
 
+@bpy.app.handlers.persistent
+def on_playback_post(scene, *args):
+    profiler.flush(scene)
+
+
+@bpy.app.handlers.persistent
+def on_bake_pre(*args):
+    # The bake blocks the main thread, so send the event now rather than
+    # leaving it for the queue timer
+    if _use_v1():
+        event = protocol.create_event(
+            "event.operator.blocking",
+            protocol.event_operator_blocking("object.bake"),
+        )
+        connection._message_queue.put(event)
+    else:
+        connection.send_to_blendmate({"type": "event", "event": "operator_blocking", "operator": "object.bake"})
+    connection.process_queue()
+
+
 # Registration is now handled by events.registry module
 # This file only contains the handler functions themselves
@@ -0,0 +1,147 @@
+"""
+Blender-side health metrics carried by the heartbeat.
+
+depsgraph_update_pre and depsgraph_update_post bracket an evaluation, so the
+time between them is what the update cost. The slowest evaluation since the
+previous heartbeat is reported with the process and system memory, so the
+app can warn before Blender runs into swap.
+
+Blender doesn't expose how much memory the undo stack holds to Python; the
+undo step count and memory limit are sent instead, as unlimited undo on a
+heavy scene is a common way to run out of memory.
+"""
+
+import sys
+import time
+import bpy
+
+_eval_started = None
+_eval_max_ms = None
+_eval_count = 0
+
+
+def depsgraph_started():
+    """Call from depsgraph_update_pre."""
+    global _eval_started
+    _eval_started = time.perf_counter()
+
+
+def depsgraph_finished():
+    """Call from depsgraph_update_post."""
+    global _eval_started, _eval_max_ms, _eval_count
+    if _eval_started is None:
+        return
+    elapsed = (time.perf_counter() - _eval_started) * 1000.0
+    _eval_started = None
+    _eval_count += 1
+    if _eval_max_ms is None or elapsed > _eval_max_ms:
+        _eval_max_ms = elapsed
+
+
+def _read_kb_fields(path, names):
+    """Values of `Name: 123 kB` lines in a /proc file, in bytes"""
+    values = {}
+    try:
+        with open(path) as f:
+            for line in f:
+                name, _, rest = line.partition(":")
+                if name in names:
+                    values[name] = int(rest.split()[0]) * 1024
+    except (OSError, ValueError, IndexError):
+        pass
+    return values
+
+
+def _windows_memory():
+    import ctypes
+    from ctypes import wintypes
+
+    class ProcessMemoryCounters(ctypes.Structure):
+        _fields_ = [
+            ("cb", wintypes.DWORD),
+            ("PageFaultCount", wintypes.DWORD),
+            ("PeakWorkingSetSize", ctypes.c_size_t),
+            ("WorkingSetSize", ctypes.c_size_t),
+            ("QuotaPeakPagedPoolUsage", ctypes.c_size_t),
+            ("QuotaPagedPoolUsage", ctypes.c_size_t),
+            ("QuotaPeakNonPagedPoolUsage", ctypes.c_size_t),
+            ("QuotaNonPagedPoolUsage", ctypes.c_size_t),
+            ("PagefileUsage", ctypes.c_size_t),
+            ("PeakPagefileUsage", ctypes.c_size_t),
+        ]
+
+    class MemoryStatusEx(ctypes.Structure):
+        _fields_ = [
+            ("dwLength", wintypes.DWORD),
+            ("dwMemoryLoad", wintypes.DWORD),
+            ("ullTotalPhys", ctypes.c_ulonglong),
+            ("ullAvailPhys", ctypes.c_ulonglong),
+            ("ullTotalPageFile", ctypes.c_ulonglong),
+            ("ullAvailPageFile", ctypes.c_ulonglong),
+            ("ullTotalVirtual", ctypes.c_ulonglong),
+            ("ullAvailVirtual", ctypes.c_ulonglong),
+            ("ullAvailExtendedVirtual", ctypes.c_ulonglong),
+        ]
+
+    metrics = {}
+    counters = ProcessMemoryCounters()
+    counters.cb = ctypes.sizeof(counters)
+    process = ctypes.windll.kernel32.GetCurrentProcess()
+    if ctypes.windll.psapi.GetProcessMemoryInfo(process, ctypes.byref(counters), counters.cb):
+        metrics["memory_bytes"] = counters.WorkingSetSize
+        metrics["peak_memory_bytes"] = counters.PeakWorkingSetSize
+    status = MemoryStatusEx()
+    status.dwLength = ctypes.sizeof(status)
+    if ctypes.windll.kernel32.GlobalMemoryStatusEx(ctypes.byref(status)):
+        metrics["system_total_bytes"] = status.ullTotalPhys
+        metrics["system_available_bytes"] = status.ullAvailPhys
+        # The page file includes physical memory
+        metrics["swap_used_bytes"] = max(
+            0,
+            (status.ullTotalPageFile - status.ullAvailPageFile) - (status.ullTotalPhys - status.ullAvailPhys),
+        )
+    return metrics
+
+
+def _memory():
+    """Process and system memory in bytes; missing where the platform doesn't tell"""
+    if sys.platform.startswith("linux"):
+        process = _read_kb_fields("/proc/self/status", ("VmRSS", "VmHWM"))
+        system = _read_kb_fields("/proc/meminfo", ("MemTotal", "MemAvailable", "SwapTotal", "SwapFree"))
+        metrics = {
+            "memory_bytes": process.get("VmRSS"),
+            "peak_memory_bytes": process.get("VmHWM"),
+            "system_total_bytes": system.get("MemTotal"),
+            "system_available_bytes": system.get("MemAvailable"),
+        }
+        if "SwapTotal" in system and "SwapFree" in system:
+            metrics["swap_used_bytes"] = system["SwapTotal"] - system["SwapFree"]
+        return metrics
+    if sys.platform == "win32":
+        try:
+            return _windows_memory()
+        except Exception:
+            return {}
+    try:
+        import resource
+        # Bytes on macOS; only the peak is available without extra modules
+        return {"peak_memory_bytes": resource.getrusage(resource.RUSAGE_SELF).ru_maxrss}
+    except Exception:
+        return {}
+
+
+def collect():
+    """Metrics for the next heartbeat; resets the depsgraph timing."""
+    global _eval_max_ms, _eval_count
+    metrics = {key: value for key, value in _memory().items() if value is not None}
+    metrics["depsgraph_ms"] = _eval_max_ms
+    metrics["depsgraph_evals"] = _eval_count
+    _eval_max_ms = None
+    _eval_count = 0
+    try:
+        edit = bpy.context.preferences.edit
+        metrics["undo_steps"] = edit.undo_steps
+        metrics["undo_memory_limit_mb"] = edit.undo_memory_limit
+    except Exception:
+        pass
+    return metrics
@@ -37,14 +37,37 @@ class BLENDMATE_OT_reload(bpy.types.Operator):
         self.report({'INFO'}, f"Blendmate Reloaded safely")
         return {'FINISHED'}
 
+class BLENDMATE_OT_fire_trigger(bpy.types.Operator):
+    bl_idname = "blendmate.fire_trigger"
+    bl_label = "Fire Blendmate Trigger"
+    bl_description = "Send a custom trigger with a JSON payload to the Blendmate app"
+    bl_options = {'INTERNAL'}
+
+    name: bpy.props.StringProperty(name="Name")
+    payload: bpy.props.StringProperty(name="Payload", description="JSON value", default="null")
+
+    def execute(self, context):
+        import json
+        from . import connection
+
+        try:
+            payload = json.loads(self.payload) if self.payload else None
+        except ValueError as e:
+            self.report({'ERROR'}, f"Payload is not valid JSON: {e}")
+            return {'CANCELLED'}
+
+        connection.fire_trigger(self.name, payload)
+        return {'FINISHED'}
+
 classes = (
     # BLENDMATE_OT_reload removed for stability
+    BLENDMATE_OT_fire_trigger,
 )
 
 def register():
-    # No classes to register currently
-    pass
+    for cls in classes:
+        bpy.utils.register_class(cls)
 
 def unregister():
-    # No classes to unregister currently
-    pass
+    for cls in reversed(classes):
+        bpy.utils.unregister_class(cls)
@@ -0,0 +1,95 @@
+"""
+Frame-time sampling during animation playback.
+
+frame_change_pre and frame_change_post bracket the depsgraph evaluation of a
+frame, so the time between them is what the frame cost to evaluate. Samples
+are only taken while playback runs (not when scrubbing or rendering) and are
+sent to the app in batches as event.playback.frame_times.
+"""
+
+import time
+import bpy
+
+from . import connection
+
+try:
+    from . import protocol
+    _protocol_available = True
+except ImportError:
+    _protocol_available = False
+
+# Samples per batch, and the longest a sample waits before it is sent
+BATCH_SIZE = 24
+BATCH_SECONDS = 1.0
+
+# Objects listed per sample; the rest are counted only
+MAX_OBJECTS = 32
+
+_frame_started = None
+_last_frame_at = None
+_samples = []
+_batch_started = None
+_fps = 0.0
+
+
+def _is_playing():
+    screen = bpy.context.screen
+    return bool(screen and screen.is_animation_playing)
+
+
+def frame_started():
+    """Call from frame_change_pre."""
+    global _frame_started
+    _frame_started = time.perf_counter() if _is_playing() else None
+
+
+def frame_finished(scene, depsgraph):
+    """Call from frame_change_post; records one sample while playing."""
+    global _frame_started, _last_frame_at, _batch_started, _fps
+    now = time.perf_counter()
+    if _frame_started is None:
+        _last_frame_at = None
+        return
+
+    objects = []
+    evaluated = 0
+    if depsgraph is not None:
+        for update in depsgraph.updates:
+            if isinstance(update.id, bpy.types.Object):
+                evaluated += 1
+                if len(objects) < MAX_OBJECTS:
+                    objects.append(update.id.name)
+
+    _samples.append({
+        "frame": scene.frame_current,
+        "eval_ms": (now - _frame_started) * 1000.0,
+        # Wall time since the previous frame, including drawing
+        "interval_ms": (now - _last_frame_at) * 1000.0 if _last_frame_at is not None else None,
+        "objects": objects,
+        "evaluated": evaluated,
+    })
+    _frame_started = None
+    _last_frame_at = now
+    _fps = scene.render.fps / (scene.render.fps_base or 1.0)
+    if _batch_started is None:
+        _batch_started = now
+    if len(_samples) >= BATCH_SIZE or now - _batch_started >= BATCH_SECONDS:
+        flush()
+
+
+def flush(*args):
+    """Send the samples taken so far. Also an animation_playback_post handler."""
+    global _batch_started, _last_frame_at
+    _batch_started = None
+    if args:
+        # Playback stopped; the next frame starts a new interval
+        _last_frame_at = None
+    if not _samples or not _protocol_available or not connection.is_protocol_v1():
+        _samples.clear()
+        return
+    event = protocol.create_event(
+        "event.playback.frame_times",
+        protocol.event_playback_frame_times(list(_samples), _fps),
+    )
+    _samples.clear()
+    connection._message_queue.put(event)
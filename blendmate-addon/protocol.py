@@ -62,6 +62,10 @@ EVENT_TYPE_MAP = {
 
     # Context events (GN node)
     "context": "event.node.active_changed",
+
+    # Operator events
+    "operator_blocking": "event.operator.blocking",
+    "operator_report": "event.operator.report",
 }
 
 # Reverse map for legacy support
@@ -277,6 +281,70 @@ def event_timeline_frame_changed(
     }
 
 
+def event_playback_frame_times(
+    samples: List[Dict[str, Any]],
+    fps: float,
+) -> Dict[str, Any]:
+    """
+    Create body for event.playback.frame_times
+
+    Emitted: In batches while animation plays back
+    Cache impact: None
+    """
+    return {
+        "samples": samples,
+        "fps": fps,
+    }
+
+
+def event_operator_blocking(
+    operator: str,
+) -> Dict[str, Any]:
+    """
+    Create body for event.operator.blocking
+
+    Emitted: Right before an operation that blocks Blender's main thread
+    Cache impact: None
+    """
+    return {
+        "operator": operator,
+    }
+
+
+def event_operator_report(
+    action: str,
+    target: str,
+    reports: List[Dict[str, Any]],
+) -> Dict[str, Any]:
+    """
+    Create body for event.operator.report
+
+    Emitted: After a command the app sent raised warning or error reports
+    Cache impact: None
+    """
+    return {
+        "action": action,
+        "target": target,
+        "reports": reports,
+    }
+
+
+def event_custom_trigger(
+    name: str,
+    payload: Any = None,
+) -> Dict[str, Any]:
+    """
+    Create body for custom.trigger
+
+    Emitted: When a user script calls fire_trigger() or blendmate.fire_trigger
+    Cache impact: None
+    """
+    return {
+        "name": name,
+        "payload": payload,
+    }
+
+
 def event_node_active_changed(
     node_id: str,
     node_tree: Optional[str] = None,
@@ -317,11 +385,17 @@ def create_heartbeat(
     active_object: Optional[str],
     mode: Optional[str],
     filepath: str,
+    operator: Optional[str] = None,
+    modal: Optional[List[str]] = None,
+    health: Optional[Dict[str, Any]] = None,
 ) -> Dict[str, Any]:
     """
     Create heartbeat envelope.
 
     Heartbeats are lightweight keep-alive messages with basic context.
+    `operator` is the last finished operator and `modal` the running modal
+    operators, so the app can tell what Blender was doing if they stop.
+    `health` holds memory and evaluation metrics (see health.py).
     """
     return create_envelope(
         msg_type="heartbeat",
@@ -329,6 +403,9 @@ def create_heartbeat(
             "active_object": active_object,
             "mode": mode,
             "filepath": filepath,
+            "operator": operator,
+            "modal": modal or [],
+            "health": health or {},
         },
     )
 
@@ -0,0 +1,84 @@
+"""
+Blender reports raised by what the add-on runs for the app.
+
+Blender has no Python API for the window manager's report list. bpy.ops
+calls print their warning and info reports to sys.stdout as
+"<Type>: <message>" and raise error reports as RuntimeError, so commands
+are run with stdout copied into a buffer, and the report lines in it and in
+the command's error are sent to the app as one event.
+"""
+
+import contextlib
+import io
+import re
+import sys
+
+from . import protocol
+
+# Report type names as Blender prints them; info, operator and property
+# reports are left out as noise
+_REPORT_LINE = re.compile(
+    r"^(?P<type>(?:Invalid Input |Invalid Context |Out Of Memory )?Error|Warning)\s*:\s*(?P<message>.+)$"
+)
+
+# Reports sent per command; a loop over many objects can raise one each
+MAX_REPORTS = 50
+
+
+class _Tee(io.TextIOBase):
+    """Writes to the real stream and keeps a copy."""
+
+    def __init__(self, stream):
+        self.stream = stream
+        self.copy = io.StringIO()
+
+    def write(self, text):
+        self.copy.write(text)
+        return self.stream.write(text)
+
+    def flush(self):
+        self.stream.flush()
+
+
+def parse(text):
+    """Report dicts of the report lines in `text`."""
+    reports = []
+    for line in (text or "").splitlines():
+        match = _REPORT_LINE.match(line.strip())
+        if match:
+            level = "warning" if match.group("type") == "Warning" else "error"
+            reports.append({"level": level, "type": match.group("type"), "message": match.group("message").strip()})
+    return reports
+
+
+@contextlib.contextmanager
+def capture(action, target):
+    """
+    Run a command with its reports captured. Yields a dict to put the
+    command's result in, so reports in its error are caught as well.
+    """
+    tee = _Tee(sys.stdout)
+    outcome = {}
+    sys.stdout = tee
+    try:
+        yield outcome
+    finally:
+        sys.stdout = tee.stream
+        result = outcome.get("result") or {}
+        reports = parse(tee.copy.getvalue())
+        if not result.get("success", True):
+            reports.extend(parse(str(result.get("error", ""))))
+        if reports:
+            send(action, target, reports[:MAX_REPORTS])
+
+
+def send(action, target, reports):
+    """Send reports raised by `action` on `target` to the app."""
+    # Imported here, as the connection module imports this one
+    from . import connection
+
+    body = protocol.event_operator_report(action, target, reports)
+    if connection.is_protocol_v1():
+        connection._message_queue.put(protocol.create_event("event.operator.report", body))
+    else:
+        connection.send_to_blendmate({"type": "event", "event": "operator_report", **body})
@@ -1,7 +1,8 @@
 """
 Minimal debounce/throttle layer for high-frequency Blender events.
 
-Coalesces events within a 100ms window and flushes via bpy.app.timers.
+Coalesces events within a 100ms window (lengthened by the app when power is
+constrained) and flushes via bpy.app.timers.
 Supports protocol v1 envelope format with batch tracking.
 """
 
@@ -35,10 +36,26 @@ _dirty_reasons: Dict[str, Set[str]] = {}
 _coalesced_data: Dict[str, List[Any]] = {}  # For merging arrays across events
 _event_count: Dict[str, int] = {}  # Count of coalesced events per type
 _new_type_map: Dict[str, str] = {}  # Maps old event types to new protocol types
-_throttle_interval = 0.1  # Fixed 100ms
+DEFAULT_INTERVAL = 0.1  # 100ms
+_throttle_interval = DEFAULT_INTERVAL
 _send_function = None
 
 
+def set_interval(seconds: float) -> float:
+    """
+    Change the coalescing window.
+
+    Args:
+        seconds: New window, at least 10ms
+
+    Returns:
+        float: The window now in use
+    """
+    global _throttle_interval
+    _throttle_interval = max(0.01, float(seconds))
+    return _throttle_interval
+
+
 def throttle_event(
     event_type: str,
     event_data: Dict[str, Any],
@@ -46,7 +63,7 @@ def throttle_event(
     new_type: Optional[str] = None,
 ):
     """
-    Queue an event for throttled delivery (100ms window).
+    Queue an event for throttled delivery (100ms window by default).
 
     Args:
         event_type: Event type key for coalescing (e.g., "depsgraph_update")
@@ -0,0 +1,104 @@
+## Connection
+
+blender-not-connected = Blender není připojen
+blender-disconnected = Blender se odpojil
+
+## Disk space guard
+
+disk-below-reserve = Na { $path } zbývá jen { $free }, méně než rezerva { $reserve }
+disk-frames-left = Na { $path } je místo jen na { $frames } dalších snímků
+disk-jobs-need = Úlohy renderu potřebují na { $path } ještě asi { $needed }, volné je { $free }
+disk-fills-in = { $path } se zaplní asi za { $minutes } min
+
+## Network shares
+
+share-no-response = Žádná odpověď do { $secs } s
+share-empty = Prázdné; sdílená složka možná není připojená
+share-still-waiting = Stále se čeká na předchozí kontrolu
+shares-unreachable = Sdílené složky projektu nejsou dostupné: { $shares }
+
+## Blender health
+
+health-swapping = Systém přesunul { $grown } do swapu; Blender může zamrznout
+health-memory-low = Zbývá jen { $free } z { $total } paměti
+health-memory-running-out = Tímto tempem dojde paměť asi za { $seconds } sekund
+
+## Quick actions
+
+quick-action-rollback = { $applied ->
+    [one] Jeden objekt byl změněn
+    [few] { $applied } objekty byly změněny
+   *[other] Změněno { $applied } objektů
+}, než { $name } selhal; { $remaining } zůstalo beze změny. Jedním krokem zpět v Blenderu vrátíte celý běh, nebo opravte { $name } a zobrazte náhled akce znovu pro zbytek.
+
+## Screen reader announcements
+
+a11y-blender-connected = Blender připojen
+a11y-blender-disconnected = Blender odpojen
+a11y-job-done = { $kind ->
+    [render] Render dokončen
+    [export] Export dokončen
+    [upgrade] Aktualizace souboru dokončena
+    [benchmark] Benchmark dokončen
+    [unit_audit] Kontrola jednotek dokončena
+    [relink] Přepojení knihoven dokončeno
+   *[other] Úloha dokončena
+}
+a11y-job-failed = { $kind ->
+    [render] Render selhal
+    [export] Export selhal
+    [upgrade] Aktualizace souboru selhala
+    [benchmark] Benchmark selhal
+    [unit_audit] Kontrola jednotek selhala
+    [relink] Přepojení knihoven selhalo
+   *[other] Úloha selhala
+}
+a11y-job-cancelled = { $kind ->
+    [render] Render zrušen
+    [export] Export zrušen
+   *[other] Úloha zrušena
+}
+a11y-disk-low = Úlohy renderu jsou pozastaveny. { $problem }
+a11y-share-lost = Sdílená složka { $path } není dostupná. Úlohy čekají.
+a11y-share-restored = Sdílená složka { $path } je opět dostupná
+a11y-delivery-done = Webové výstupy projektu { $project } jsou hotové: { $count ->
+    [one] jeden soubor
+    [few] { $count } soubory
+   *[other] { $count } souborů
+}
+a11y-delivery-failed = Webové výstupy projektu { $project } jsou hotové, ale { $failed ->
+    [one] jeden soubor selhal
+    [few] { $failed } soubory selhaly
+   *[other] { $failed } souborů selhalo
+}
+
+## Session report
+
+report-title = Přehled práce
+report-project = Projekt
+report-tags = Štítky
+report-period = Období
+report-generated = Vytvořeno
+report-digest = Aktivita
+report-commands = Odeslané příkazy
+report-action = Příkaz
+report-count = Počet
+report-no-commands = V tomto období nebyly odeslány žádné příkazy.
+report-render = Renderování
+report-jobs = úloh
+report-done = hotovo
+report-failed = selhalo
+report-frames = snímků
+report-render-time = čas renderu
+report-per-frame = na snímek
+report-job = Úloha
+report-status = Stav
+report-started = Začátek
+report-duration = Trvání
+report-validation = Kontrola scény
+report-passed = prošla
+report-not-passed = neprošla
+report-no-findings = Žádné nálezy.
+report-validation-unavailable = Scéna nebyla zkontrolována.
+report-renders = Rendery
+report-timelapse = Timelapse
@@ -0,0 +1,104 @@
+# Strings the backend shows to users. Keys are stable; the frontend can
+# fetch this catalog with `get_catalog`.
+
+## Connection
+
+blender-not-connected = Blender is not connected
+blender-disconnected = Blender disconnected
+
+## Disk space guard
+
+disk-below-reserve = Only { $free } free on { $path }, below the { $reserve } reserve
+disk-frames-left = Room for only { $frames } more frames on { $path }
+disk-jobs-need = Render jobs need about { $needed } more on { $path }, { $free } free
+disk-fills-in = { $path } fills in about { $minutes } minutes
+
+## Network shares
+
+share-no-response = No response in { $secs }s
+share-empty = Empty; the share may not be mounted
+share-still-waiting = Still waiting on the last check
+shares-unreachable = Project shares are unreachable: { $shares }
+
+## Blender health
+
+health-swapping = The system moved { $grown } to swap; Blender may freeze
+health-memory-low = Only { $free } of { $total } memory left
+health-memory-running-out = Memory runs out in about { $seconds } seconds at this rate
+
+## Quick actions
+
+quick-action-rollback = { $applied ->
+    [one] One object was changed
+   *[other] { $applied } objects were changed
+} before { $name } failed, and { $remaining } were left as they were. Undo once in Blender to revert the whole run, or fix { $name } and preview the action again for the rest.
+
+## Screen reader announcements
+
+a11y-blender-connected = Blender connected
+a11y-blender-disconnected = Blender disconnected
+a11y-job-done = { $kind ->
+    [render] Render finished
+    [export] Export finished
+    [upgrade] File upgrade finished
+    [benchmark] Benchmark finished
+    [unit_audit] Unit audit finished
+    [relink] Library relink finished
+   *[other] Job finished
+}
+a11y-job-failed = { $kind ->
+    [render] Render failed
+    [export] Export failed
+    [upgrade] File upgrade failed
+    [benchmark] Benchmark failed
+    [unit_audit] Unit audit failed
+    [relink] Library relink failed
+   *[other] Job failed
+}
+a11y-job-cancelled = { $kind ->
+    [render] Render cancelled
+    [export] Export cancelled
+   *[other] Job cancelled
+}
+a11y-disk-low = Render jobs paused. { $problem }
+a11y-share-lost = Network share { $path } is unreachable. Jobs are on hold.
+a11y-share-restored = Network share { $path } is back
+a11y-delivery-done = Web delivery for { $project } is ready: { $count ->
+    [one] one file
+   *[other] { $count } files
+}
+a11y-delivery-failed = Web delivery for { $project } finished with { $failed ->
+    [one] one failed file
+   *[other] { $failed } failed files
+}
+
+## Session report
+
+report-title = Session report
+report-project = Project
+report-tags = Tags
+report-period = Period
+report-generated = Generated
+report-digest = Activity
+report-commands = Commands sent
+report-action = Command
+report-count = Times
+report-no-commands = No commands were sent in this period.
+report-render = Rendering
+report-jobs = jobs
+report-done = done
+report-failed = failed
+report-frames = frames
+report-render-time = render time
+report-per-frame = per frame
+report-job = Job
+report-status = Status
+report-started = Started
+report-duration = Duration
+report-validation = Scene validation
+report-passed = passed
+report-not-passed = did not pass
+report-no-findings = No findings.
+report-validation-unavailable = The scene was not validated.
+report-renders = Renders
+report-timelapse = Timelapse
@@ -0,0 +1,104 @@
+"""
+Headless benchmark worker: render one frame of a scene and time it.
+
+Run by the app's worker pool as:
+    blender -b file.blend --python-expr <this script> -- '<json args>'
+
+Args:
+    url: Download the scene from here instead of opening a file (optional)
+    cache_dir: Where downloaded scenes are kept between runs
+    engine: Render engine to use, e.g. CYCLES or BLENDER_EEVEE_NEXT (optional)
+    frame: Frame to render (optional, default: the scene's current frame)
+    repeats: Timed renders (default 1)
+    warmup: Untimed render first, e.g. to compile shaders (default false)
+
+Reports {"result": {...}} with the timings of each render in seconds,
+the Blender version and the devices Cycles used.
+"""
+
+import hashlib
+import json
+import os
+import sys
+import tempfile
+import time
+import urllib.request
+
+import bpy
+
+
+def report(**message):
+    print("BLENDMATE:" + json.dumps(message), flush=True)
+
+
+def download(url, cache_dir):
+    """Fetch `url` once into `cache_dir`, keyed by the URL."""
+    os.makedirs(cache_dir, exist_ok=True)
+    name = hashlib.sha1(url.encode()).hexdigest()[:16] + ".blend"
+    path = os.path.join(cache_dir, name)
+    if not os.path.exists(path):
+        partial = path + ".part"
+        urllib.request.urlretrieve(url, partial)
+        os.replace(partial, path)
+    return path
+
+
+def enabled_devices():
+    try:
+        prefs = bpy.context.preferences.addons["cycles"].preferences
+        return [device.name for device in prefs.devices if device.use]
+    except KeyError:
+        return []
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+
+    load_seconds = 0.0
+    if args.get("url"):
+        cache_dir = args.get("cache_dir") or os.path.join(tempfile.gettempdir(), "blendmate-benchmarks")
+        started = time.perf_counter()
+        path = download(args["url"], cache_dir)
+        bpy.ops.wm.open_mainfile(filepath=path)
+        load_seconds = time.perf_counter() - started
+        # Opening the file replaced the device setup applied to the startup scene
+        if args.get("devices") and bpy.context.scene.render.engine == 'CYCLES':
+            prefs = bpy.context.preferences.addons["cycles"].preferences
+            any_gpu = any(d.use and d.type != 'CPU' for d in prefs.devices)
+            bpy.context.scene.cycles.device = 'GPU' if any_gpu else 'CPU'
+
+    scene = bpy.context.scene
+    render = scene.render
+    if args.get("engine"):
+        render.engine = args["engine"]
+    if args.get("frame") is not None:
+        scene.frame_set(args["frame"])
+
+    render.filepath = os.path.join(tempfile.mkdtemp(prefix="blendmate-bench-"), "frame")
+    repeats = max(1, int(args.get("repeats", 1)))
+    steps = repeats + (1 if args.get("warmup") else 0)
+
+    if args.get("warmup"):
+        bpy.ops.render.render(write_still=False)
+        report(progress=1 / steps)
+
+    timings = []
+    for _ in range(repeats):
+        started = time.perf_counter()
+        bpy.ops.render.render(write_still=False)
+        timings.append(time.perf_counter() - started)
+        report(progress=(len(timings) + (1 if args.get("warmup") else 0)) / steps)
+
+    report(result={
+        "render_seconds": timings,
+        "load_seconds": load_seconds,
+        "engine": render.engine,
+        "samples": scene.cycles.samples if render.engine == 'CYCLES' else None,
+        "resolution": [render.resolution_x, render.resolution_y, render.resolution_percentage],
+        "blender_version": bpy.app.version_string,
+        "devices": enabled_devices(),
+        "cycles_device": scene.cycles.device if render.engine == 'CYCLES' else None,
+    })
+
+
+main()
@@ -0,0 +1,106 @@
+"""
+Headless denoise worker for one landed frame.
+
+Run by the app's worker pool as:
+    blender -b --python-expr <this script> -- '<json args>'
+
+Args:
+    input: Frame to denoise
+    output: Where the denoised frame goes; its extension picks the format
+
+The frame goes through Blender's compositor with a Denoise node (OIDN).
+Multilayer EXRs with denoising data passes guide it with their normal and
+albedo.
+"""
+
+import json
+import os
+import sys
+
+import bpy
+
+FORMATS = {
+    ".exr": "OPEN_EXR",
+    ".png": "PNG",
+    ".jpg": "JPEG",
+    ".jpeg": "JPEG",
+    ".tif": "TIFF",
+    ".tiff": "TIFF",
+    ".webp": "WEBP",
+    ".bmp": "BMP",
+    ".tga": "TARGA",
+}
+
+
+def report(**message):
+    print("BLENDMATE:" + json.dumps(message), flush=True)
+
+
+def output_socket(scene):
+    """A fresh compositor tree and the socket that ends up in the render"""
+    if bpy.app.version >= (5, 0, 0):
+        tree = bpy.data.node_groups.new("Denoise", "CompositorNodeTree")
+        scene.compositing_node_group = tree
+        tree.interface.new_socket("Image", in_out="OUTPUT", socket_type="NodeSocketColor")
+        return tree, tree.nodes.new("NodeGroupOutput").inputs[0]
+    scene.use_nodes = True
+    tree = scene.node_tree
+    tree.nodes.clear()
+    return tree, tree.nodes.new("CompositorNodeComposite").inputs["Image"]
+
+
+def find(sockets, *names):
+    return next((socket for name in names for socket in sockets if socket.name == name), None)
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+    source, target = args["input"], args["output"]
+    file_format = FORMATS.get(os.path.splitext(target)[1].lower())
+    if file_format is None:
+        report(error=f"Can't write {target}: unsupported format")
+        return
+
+    image = bpy.data.images.load(source)
+    scene = bpy.context.scene
+    render = scene.render
+    render.engine = "BLENDER_WORKBENCH"
+    render.resolution_x, render.resolution_y = image.size
+    render.resolution_percentage = 100
+    render.use_compositing = True
+    render.use_sequencer = False
+    render.filepath = target
+    render.image_settings.file_format = file_format
+    if file_format == "OPEN_EXR":
+        render.image_settings.color_depth = "32"
+    else:
+        scene.view_settings.view_transform = "Standard"
+    report(progress=0.2)
+
+    tree, output = output_socket(scene)
+    node = tree.nodes.new("CompositorNodeImage")
+    node.image = image
+    denoise = tree.nodes.new("CompositorNodeDenoise")
+    if hasattr(denoise, "prefilter"):
+        denoise.prefilter = "ACCURATE"
+        denoise.use_hdr = True
+
+    color = find(node.outputs, "Combined", "Image")
+    normal = find(node.outputs, "Denoising Normal")
+    albedo = find(node.outputs, "Denoising Albedo")
+    tree.links.new(color, denoise.inputs["Image"])
+    if normal and albedo:
+        tree.links.new(normal, denoise.inputs["Normal"])
+        tree.links.new(albedo, denoise.inputs["Albedo"])
+    tree.links.new(denoise.outputs["Image"], output)
+
+    bpy.ops.render.render(write_still=True)
+    if not os.path.isfile(target):
+        report(error=f"Blender wrote no frame to {target}")
+        return
+    report(output=target)
+    report(result={"guided": bool(normal and albedo)})
+    report(progress=1.0)
+
+
+main()
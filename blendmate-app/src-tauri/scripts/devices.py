@@ -0,0 +1,45 @@
+"""
+Headless device setup, run by the worker pool before a job script.
+
+Reads "devices" from the job's JSON args (a device profile: compute_device_type,
+use_cpu, gpus) and applies it to the Cycles preferences and every scene.
+"""
+
+import json
+import sys
+
+import bpy
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+    profile = args.get("devices")
+    if not profile:
+        return
+
+    prefs = bpy.context.preferences.addons["cycles"].preferences
+    if profile.get("compute_device_type"):
+        prefs.compute_device_type = profile["compute_device_type"]
+    prefs.refresh_devices()
+
+    wanted = profile.get("gpus")
+    gpu_index = 0
+    any_gpu = False
+    enabled = []
+    for device in prefs.get_devices_for_type(prefs.compute_device_type):
+        if device.type == 'CPU':
+            device.use = bool(profile.get("use_cpu", False))
+        else:
+            device.use = wanted is None or gpu_index in wanted
+            any_gpu = any_gpu or device.use
+            gpu_index += 1
+        if device.use:
+            enabled.append(device.name)
+
+    for scene in bpy.data.scenes:
+        scene.cycles.device = 'GPU' if any_gpu else 'CPU'
+
+    print(f"Render devices: {', '.join(enabled) or 'none'}", flush=True)
+
+
+main()
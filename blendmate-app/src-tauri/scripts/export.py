@@ -0,0 +1,84 @@
+"""
+Headless export worker.
+
+Run by the app's worker pool as:
+    blender -b file.blend --python-expr <this script> -- '<json args>'
+
+Args:
+    format: "fbx" | "gltf" | "usd" | "obj"
+    options: Extra keyword arguments for the exporter operator
+    output_dir: Directory the exports are written to
+    collections: Collection names to export one by one (empty = whole scene)
+    name: What the exported files are named after, unique within the job
+          (defaults to the .blend file's name)
+"""
+
+import json
+import os
+import sys
+
+import bpy
+
+
+def report(**message):
+    print("BLENDMATE:" + json.dumps(message), flush=True)
+
+
+def extension(fmt, options):
+    if fmt == "gltf":
+        return ".gltf" if options.get("export_format") == "GLTF_SEPARATE" else ".glb"
+    return {"fbx": ".fbx", "usd": ".usdc", "obj": ".obj"}[fmt]
+
+
+def export(fmt, filepath, options):
+    if fmt == "fbx":
+        bpy.ops.export_scene.fbx(filepath=filepath, use_selection=True, **options)
+    elif fmt == "gltf":
+        bpy.ops.export_scene.gltf(filepath=filepath, use_selection=True, **options)
+    elif fmt == "usd":
+        bpy.ops.wm.usd_export(filepath=filepath, selected_objects_only=True, **options)
+    elif fmt == "obj":
+        bpy.ops.wm.obj_export(filepath=filepath, export_selected_objects=True, **options)
+    else:
+        raise ValueError(f"Unknown export format: {fmt}")
+
+
+def select_only(objects):
+    for obj in bpy.context.view_layer.objects:
+        obj.select_set(obj in objects)
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+    fmt = args["format"]
+    options = args.get("options", {})
+    output_dir = args["output_dir"]
+    os.makedirs(output_dir, exist_ok=True)
+
+    stem = args.get("name") or os.path.splitext(os.path.basename(bpy.data.filepath))[0] or "untitled"
+    targets = args.get("collections") or [None]
+
+    for index, name in enumerate(targets):
+        if name is None:
+            objects = set(bpy.context.view_layer.objects)
+            filename = stem
+        else:
+            collection = bpy.data.collections.get(name)
+            if collection is None:
+                report(error=f"Collection '{name}' not found")
+                report(progress=(index + 1) / len(targets))
+                continue
+            objects = set(collection.all_objects)
+            filename = f"{stem}_{bpy.path.clean_name(name)}"
+
+        filepath = os.path.join(output_dir, filename + extension(fmt, options))
+        try:
+            select_only(objects)
+            export(fmt, filepath, options)
+            report(output=filepath)
+        except Exception as e:
+            report(error=f"{name or stem}: {e}")
+        report(progress=(index + 1) / len(targets))
+
+
+main()
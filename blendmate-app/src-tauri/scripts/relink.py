@@ -0,0 +1,83 @@
+"""
+Headless library relink for one .blend file.
+
+Run by the app's worker pool as:
+    blender -b file.blend --python-expr <this script> -- '<json args>'
+
+Args:
+    old_path: Absolute path of the library file before it moved
+    new_path: Absolute path it moved to
+    backup: Keep the original as <file>.bak (default true)
+
+Libraries whose path resolves to old_path are pointed at new_path, kept
+relative where they were, and reloaded. The file is saved only when every
+relinked library resolves all its datablocks; otherwise it is left as it
+was and the item reports an error.
+
+Reports a result of {"relinked": [...], "missing_ids": int, "saved": bool}.
+"""
+
+import json
+import os
+import shutil
+import sys
+
+import bpy
+
+# bpy.data collections linked datablocks can be in
+ID_COLLECTIONS = (
+    "actions", "armatures", "brushes", "cameras", "collections", "curves", "fonts",
+    "grease_pencils", "images", "lattices", "lights", "materials", "meshes", "metaballs",
+    "movieclips", "node_groups", "objects", "particles", "scenes", "sounds", "texts",
+    "textures", "volumes", "worlds",
+)
+
+
+def report(**message):
+    print("BLENDMATE:" + json.dumps(message), flush=True)
+
+
+def normalize(path):
+    return os.path.normcase(os.path.normpath(path))
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+    old_path = normalize(args["old_path"])
+    new_path = args["new_path"]
+    source = bpy.data.filepath
+
+    relinked = []
+    for lib in bpy.data.libraries:
+        if normalize(bpy.path.abspath(lib.filepath)) != old_path:
+            continue
+        lib.filepath = bpy.path.relpath(new_path) if lib.filepath.startswith("//") else new_path
+        lib.reload()
+        relinked.append(lib.name)
+    report(progress=0.5)
+
+    missing = 0
+    for collection_name in ID_COLLECTIONS:
+        for id_block in getattr(bpy.data, collection_name, []):
+            if id_block.library and id_block.library.name in relinked and getattr(id_block, "is_missing", False):
+                missing += 1
+
+    saved = False
+    if missing:
+        report(error=f"{missing} linked datablocks are still missing; the file was not saved")
+    elif relinked:
+        if args.get("backup", True):
+            shutil.copy2(source, source + ".bak")
+        bpy.ops.wm.save_as_mainfile(filepath=source)
+        report(output=source)
+        saved = True
+
+    report(result={
+        "relinked": relinked,
+        "missing_ids": missing,
+        "saved": saved,
+    })
+    report(progress=1.0)
+
+
+main()
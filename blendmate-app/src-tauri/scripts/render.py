@@ -0,0 +1,78 @@
+"""
+Headless render worker for one chunk of a render job.
+
+Run by the app's worker pool as:
+    blender -b file.blend --python-expr <this script> -- '<json args>'
+
+Args:
+    frames: Frames to render, in order
+    output: Output path overriding the file's render.filepath (optional)
+    split: The job is split into several chunks
+    scene: Scene to render (optional, default: the file's active scene)
+    camera: Camera object to render through (optional)
+
+Every frame is written to the same image sequence, so chunks rendered by
+different workers merge into one directory.
+"""
+
+import json
+import sys
+
+import bpy
+
+
+def report(**message):
+    print("BLENDMATE:" + json.dumps(message), flush=True)
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+    frames = args["frames"]
+    scene = bpy.data.scenes.get(args["scene"]) if args.get("scene") else bpy.context.scene
+    if scene is None:
+        report(error=f"Scene '{args['scene']}' not found")
+        return
+    render = scene.render
+
+    if args.get("camera"):
+        camera = bpy.data.objects.get(args["camera"])
+        if camera is None:
+            report(error=f"Camera '{args['camera']}' not found")
+            return
+        scene.camera = camera
+
+    if args.get("output"):
+        render.filepath = args["output"]
+
+    if render.is_movie_format:
+        if args.get("split"):
+            report(error="Movie output can't be split into chunks; render to an image sequence")
+            return
+        scene.frame_start = min(frames)
+        scene.frame_end = max(frames)
+        scene.frame_step = frames[1] - frames[0] if len(frames) > 1 else 1
+        bpy.ops.render.render(animation=True, scene=scene.name)
+        report(output=bpy.path.abspath(render.frame_path(frame=scene.frame_start)))
+        report(result={"rendered": frames})
+        return
+
+    base = render.filepath
+    rendered = []
+    for index, frame in enumerate(frames):
+        scene.frame_set(frame)
+        path = render.frame_path(frame=frame)
+        render.filepath = path
+        try:
+            bpy.ops.render.render(write_still=True, scene=scene.name)
+            rendered.append(frame)
+            report(output=bpy.path.abspath(path))
+        except Exception as e:
+            report(error=f"Frame {frame}: {e}")
+        finally:
+            render.filepath = base
+        report(progress=(index + 1) / len(frames))
+
+    report(result={"rendered": rendered})
+
+
+main()
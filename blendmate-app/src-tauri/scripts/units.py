@@ -0,0 +1,84 @@
+"""
+Headless unit and scale report for one .blend file.
+
+Run by the app's worker pool as:
+    blender -b file.blend --python-expr <this script> -- '<json args>'
+
+Args:
+    max_scale_ratio: Report objects whose largest scale axis is more than
+        this many times their smallest
+    max_objects: Most objects reported per file
+
+Reports a result of {"scenes": [...], "objects": [...], "truncated": bool}.
+"""
+
+import json
+import sys
+
+import bpy
+
+
+def report(**message):
+    print("BLENDMATE:" + json.dumps(message), flush=True)
+
+
+def scale_issue(obj, max_ratio):
+    scale = [abs(value) for value in obj.scale]
+    if min(scale) == 0.0:
+        return "zero", None
+    ratio = max(scale) / min(scale)
+    if ratio > max_ratio:
+        return "non_uniform", ratio
+    if any(value < 0.0 for value in obj.scale):
+        return "negative", ratio
+    return None, ratio
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+    max_ratio = float(args.get("max_scale_ratio", 10.0))
+    max_objects = int(args.get("max_objects", 200))
+
+    scenes = []
+    for scene in bpy.data.scenes:
+        units = scene.unit_settings
+        scenes.append({
+            "name": scene.name,
+            "system": units.system,
+            "scale_length": units.scale_length,
+            "length_unit": units.length_unit,
+            "mass_unit": units.mass_unit,
+            "system_rotation": units.system_rotation,
+        })
+    report(progress=0.3)
+
+    objects = []
+    truncated = False
+    for obj in bpy.data.objects:
+        if obj.library is not None:
+            continue
+        reason, ratio = scale_issue(obj, max_ratio)
+        if reason is None:
+            continue
+        if len(objects) >= max_objects:
+            truncated = True
+            break
+        objects.append({
+            "name": obj.name,
+            "type": obj.type,
+            "scenes": [scene.name for scene in obj.users_scene],
+            "scale": list(obj.scale),
+            "ratio": ratio,
+            "reason": reason,
+        })
+
+    report(result={
+        "scenes": scenes,
+        "objects": objects,
+        "truncated": truncated,
+        "blender_version": bpy.app.version_string,
+    })
+    report(progress=1.0)
+
+
+main()
@@ -0,0 +1,56 @@
+"""
+Headless re-save worker for version upgrades/downgrades.
+
+Run by the app's worker pool as:
+    blender -b file.blend --python-expr <this script> -- '<json args>'
+
+Args:
+    fixup_script: Optional .py file run after loading, before saving
+    output_dir: Save the copy here; empty saves in place
+    backup: When saving in place, keep the original as <file>.bak
+    compress: Save compressed
+"""
+
+import json
+import os
+import shutil
+import sys
+
+import bpy
+
+
+def report(**message):
+    print("BLENDMATE:" + json.dumps(message), flush=True)
+
+
+def main():
+    args = json.loads(sys.argv[sys.argv.index("--") + 1])
+    source = bpy.data.filepath
+    file_version = list(bpy.data.version)
+
+    fixup = args.get("fixup_script")
+    if fixup:
+        with open(fixup, encoding="utf-8") as f:
+            code = compile(f.read(), fixup, "exec")
+        exec(code, {"__name__": "__main__", "__file__": fixup})
+    report(progress=0.5)
+
+    output_dir = args.get("output_dir")
+    if output_dir:
+        os.makedirs(output_dir, exist_ok=True)
+        target = os.path.join(output_dir, os.path.basename(source))
+    else:
+        target = source
+        if args.get("backup", True):
+            shutil.copy2(source, source + ".bak")
+
+    bpy.ops.wm.save_as_mainfile(filepath=target, compress=args.get("compress", False))
+    report(output=target)
+    report(result={
+        "file_version": file_version,
+        "saved_version": list(bpy.app.version),
+    })
+    report(progress=1.0)
+
+
+main()
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+
+/// Announcements kept for `list_announcements`
+const HISTORY: usize = 50;
+
+/// Announcements waiting for their turn; the oldest polite ones are
+/// dropped beyond this
+const MAX_PENDING: usize = 5;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct A11ySettings {
+    pub enabled: bool,
+    /// Least time between two announcements; ones in between wait
+    pub min_gap_ms: u64,
+    /// The same text in the same category within this long is dropped
+    pub repeat_window_secs: u64,
+}
+
+impl Default for A11ySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_gap_ms: 2000,
+            repeat_window_secs: 10,
+        }
+    }
+}
+
+/// How urgently a screen reader should speak, as in `aria-live`
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Politeness {
+    /// After the user is idle
+    Polite,
+    /// Right away, interrupting; for failures and lost connections
+    Assertive,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Announcement {
+    /// What it is about, e.g. `connection` or `job`. A newer announcement
+    /// replaces a waiting one of the same category.
+    pub category: &'static str,
+    pub text: String,
+    pub politeness: Politeness,
+    pub at: String,
+}
+
+#[derive(Default)]
+struct Queue {
+    pending: VecDeque<Announcement>,
+    last_sent: Option<Instant>,
+    /// Recently sent text per category, for dropping repeats
+    recent: Vec<(&'static str, String, Instant)>,
+    history: VecDeque<Announcement>,
+    flushing: bool,
+}
+
+/// Plain-language announcements of important state changes, emitted as
+/// `a11y:announce` at most once per `min_gap_ms`
+#[derive(Default)]
+pub struct Announcer {
+    queue: Mutex<Queue>,
+}
+
+impl Announcer {
+    /// Queue `announcement`; returns whether a flush needs scheduling
+    fn push(&self, announcement: Announcement, config: &A11ySettings) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        let window = Duration::from_secs(config.repeat_window_secs);
+        queue.recent.retain(|(_, _, at)| at.elapsed() < window);
+        let repeated = queue
+            .recent
+            .iter()
+            .any(|(category, text, _)| *category == announcement.category && *text == announcement.text);
+        if repeated {
+            return false;
+        }
+
+        queue.pending.retain(|waiting| waiting.category != announcement.category);
+        if announcement.politeness == Politeness::Assertive {
+            let first_polite = queue
+                .pending
+                .iter()
+                .position(|waiting| waiting.politeness == Politeness::Polite)
+                .unwrap_or(queue.pending.len());
+            queue.pending.insert(first_polite, announcement);
+        } else {
+            queue.pending.push_back(announcement);
+        }
+        while queue.pending.len() > MAX_PENDING {
+            match queue.pending.iter().position(|waiting| waiting.politeness == Politeness::Polite) {
+                Some(index) => queue.pending.remove(index),
+                None => queue.pending.pop_front(),
+            };
+        }
+        !std::mem::replace(&mut queue.flushing, true)
+    }
+
+    /// The next announcement if its turn has come, else how long to wait
+    fn next(&self, gap: Duration) -> Result<Option<Announcement>, Duration> {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(wait) = queue.last_sent.map(|last| gap.saturating_sub(last.elapsed())) {
+            if !wait.is_zero() && !queue.pending.is_empty() {
+                return Err(wait);
+            }
+        }
+        let Some(announcement) = queue.pending.pop_front() else {
+            queue.flushing = false;
+            return Ok(None);
+        };
+        let now = Instant::now();
+        queue.last_sent = Some(now);
+        queue.recent.push((announcement.category, announcement.text.clone(), now));
+        queue.history.push_back(announcement.clone());
+        if queue.history.len() > HISTORY {
+            queue.history.pop_front();
+        }
+        Ok(Some(announcement))
+    }
+}
+
+/// Emit waiting announcements one per gap until none are left
+fn flush<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let gap = Duration::from_millis(app.state::<SettingsStore>().get().a11y.min_gap_ms);
+            match app.state::<Announcer>().next(gap) {
+                Err(wait) => tokio::time::sleep(wait).await,
+                Ok(None) => break,
+                Ok(Some(announcement)) => {
+                    if let Err(err) = app.emit("a11y:announce", &announcement) {
+                        eprintln!("Failed to emit a11y:announce: {err}");
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Announce `text`, already in the user's language (see `i18n::t`), to
+/// screen readers
+pub fn announce<R: Runtime>(app: &AppHandle<R>, category: &'static str, politeness: Politeness, text: String) {
+    let config = app.state::<SettingsStore>().get().a11y;
+    if !config.enabled {
+        return;
+    }
+    let announcement = Announcement {
+        category,
+        text,
+        politeness,
+        at: chrono::Local::now().to_rfc3339(),
+    };
+    if app.state::<Announcer>().push(announcement, &config) {
+        flush(app.clone());
+    }
+}
+
+/// Announcements sent recently, oldest first, e.g. for a log a screen
+/// reader user can review
+#[tauri::command]
+pub fn list_announcements(announcer: State<'_, Announcer>) -> Vec<Announcement> {
+    announcer.queue.lock().unwrap().history.iter().cloned().collect()
+}
+
+#[tauri::command]
+pub fn get_a11y_settings(settings: State<'_, SettingsStore>) -> A11ySettings {
+    settings.get().a11y
+}
+
+#[tauri::command]
+pub fn set_a11y_settings(a11y: A11ySettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.a11y = a11y)?;
+    Ok(())
+}
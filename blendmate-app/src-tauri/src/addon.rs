@@ -0,0 +1,354 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::watch;
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+use crate::workers;
+use crate::AppState;
+
+/// Directories of the add-on source that are never pushed
+const SKIPPED_DIRS: &[&str] = &["vendor", "docs", "tests", "__pycache__"];
+
+/// File types pushed to Blender
+const PUSHED_EXTENSIONS: &[&str] = &["py", "toml"];
+
+/// Modules that own the connection; changing them needs a full reload
+const CONNECTION_FILES: &[&str] = &["__init__.py", "connection.py"];
+
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often dev mode checks the source directory
+const POLL_INTERVAL: Duration = Duration::from_millis(750);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AddonReload {
+    /// Add-on directory in the Blender installation
+    pub directory: String,
+    pub written: Vec<String>,
+    /// Modules that were reloaded
+    pub modules: Vec<String>,
+}
+
+/// Add-on sources bundled with the app (the `addon` resource directory)
+fn bundled_addon_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to find app resources: {}", e))?
+        .join("addon");
+    if !dir.join("__init__.py").is_file() {
+        return Err(format!("No bundled add-on in {}", dir.display()));
+    }
+    Ok(dir)
+}
+
+/// Push add-on files to the connected Blender and reload its modules
+/// without dropping the connection.
+async fn push_and_reload(state: &AppState, dir: &Path, files: &[PathBuf]) -> Result<AddonReload, CommandError> {
+    let written = push_files(state, dir, files).await?;
+    let reloaded = reload(state, true).await?;
+
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .map(|list| list.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+    Ok(AddonReload {
+        directory: written.get("directory").and_then(Value::as_str).unwrap_or_default().to_string(),
+        written: strings(written.get("written")),
+        modules: strings(reloaded.get("modules")),
+    })
+}
+
+/// Write `files` (relative to `dir`) into the installed add-on
+async fn push_files(state: &AppState, dir: &Path, files: &[PathBuf]) -> Result<Value, CommandError> {
+    let mut entries = Vec::new();
+    for relative in files {
+        let content = fs::read_to_string(dir.join(relative))
+            .map_err(|e| CommandError::Io(format!("Failed to read {}: {}", relative.display(), e)))?;
+        // The add-on expects forward slashes on every platform
+        let path = relative.to_string_lossy().replace('\\', "/");
+        entries.push(json!({ "path": path, "content": content }));
+    }
+    state
+        .bridge
+        .request_with_timeout("addon.write_files", "", json!({ "files": entries }), RELOAD_TIMEOUT)
+        .await
+}
+
+async fn reload(state: &AppState, keep_connection: bool) -> Result<Value, CommandError> {
+    let params = json!({ "keep_connection": keep_connection });
+    state
+        .bridge
+        .request_with_timeout("addon.reload", "", params, RELOAD_TIMEOUT)
+        .await
+}
+
+/// Whether a path relative to the add-on root is one that gets pushed
+fn is_pushed(relative: &Path) -> bool {
+    let skipped = relative
+        .components()
+        .any(|part| SKIPPED_DIRS.contains(&part.as_os_str().to_string_lossy().as_ref()));
+    let extension = relative.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    !skipped && PUSHED_EXTENSIONS.contains(&extension)
+}
+
+/// Pushed files under `dir`, relative to it
+fn collect_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let entries = fs::read_dir(dir.join(&relative))
+            .map_err(|e| format!("Failed to read {}: {}", dir.join(&relative).display(), e))?;
+        for entry in entries.flatten() {
+            let path = relative.join(entry.file_name());
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                if !SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                    pending.push(path);
+                }
+            } else if is_pushed(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Update the connected Blender's add-on from the copy bundled with the app
+/// and reload it, keeping the session. `session` is the peer address from
+/// `connection:quality`; when given, it must still be the connected one.
+#[tauri::command]
+pub async fn reload_addon<R: Runtime>(
+    session: Option<String>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+) -> Result<AddonReload, CommandError> {
+    state.session.check_peer(session.as_deref())?;
+
+    let dir = bundled_addon_dir(&app)?;
+    let files = collect_files(&dir)?;
+    push_and_reload(&state, &dir, &files).await
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AddonDevSettings {
+    /// Watch `source_dir` and hot-reload the connected Blender on changes
+    pub enabled: bool,
+    /// Add-on checkout (the directory containing `__init__.py`)
+    pub source_dir: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DevStage {
+    Stopped,
+    Watching,
+    /// Changes are waiting for Blender to connect
+    Waiting,
+    Syncing,
+    Reloaded,
+    Failed,
+}
+
+/// Dev mode state, emitted as `addon:dev`
+#[derive(Serialize, Clone, Debug)]
+pub struct DevStatus {
+    pub stage: DevStage,
+    pub source_dir: Option<String>,
+    /// Files in the last (or pending) sync
+    pub changed: Vec<String>,
+    /// The last sync replaced the connection modules and reconnected
+    pub full_reload: bool,
+    pub error: Option<String>,
+    pub at: String,
+}
+
+impl Default for DevStatus {
+    fn default() -> Self {
+        Self {
+            stage: DevStage::Stopped,
+            source_dir: None,
+            changed: Vec::new(),
+            full_reload: false,
+            error: None,
+            at: chrono::Local::now().to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AddonDevState {
+    stop: Mutex<Option<watch::Sender<bool>>>,
+    status: Mutex<DevStatus>,
+}
+
+impl AddonDevState {
+    fn report<R: Runtime>(&self, app: &AppHandle<R>, change: impl FnOnce(&mut DevStatus)) {
+        let status = {
+            let mut status = self.status.lock().unwrap();
+            change(&mut status);
+            status.at = chrono::Local::now().to_rfc3339();
+            status.clone()
+        };
+        if let Err(err) = app.emit("addon:dev", &status) {
+            eprintln!("Failed to emit addon:dev: {err}");
+        }
+    }
+}
+
+/// (Re)start or stop dev mode from the saved settings.
+pub fn apply_dev_settings<R: Runtime>(app: &AppHandle<R>) -> Result<(), CommandError> {
+    let config = app.state::<SettingsStore>().get().addon_dev;
+    let dev = app.state::<AddonDevState>();
+    if let Some(stop) = dev.stop.lock().unwrap().take() {
+        stop.send_replace(true);
+    }
+
+    let source_dir = match (config.enabled, config.source_dir) {
+        (true, Some(dir)) => PathBuf::from(dir),
+        (true, None) => {
+            return Err(CommandError::InvalidInput(
+                "Dev mode needs an add-on source directory".to_string(),
+            ))
+        }
+        (false, _) => {
+            dev.report(app, |status| *status = DevStatus::default());
+            return Ok(());
+        }
+    };
+    if !source_dir.join("__init__.py").is_file() {
+        return Err(CommandError::InvalidInput(format!(
+            "{} is not an add-on directory",
+            source_dir.display()
+        )));
+    }
+
+    let (stop, stopped) = watch::channel(false);
+    *dev.stop.lock().unwrap() = Some(stop);
+    dev.report(app, |status| {
+        *status = DevStatus {
+            stage: DevStage::Watching,
+            source_dir: Some(source_dir.display().to_string()),
+            ..DevStatus::default()
+        }
+    });
+    tauri::async_runtime::spawn(watch_source(app.clone(), source_dir, stopped));
+    Ok(())
+}
+
+/// Poll `dir` and push changed files once they have settled. Everything is
+/// pushed when dev mode starts, and pending changes wait for Blender to
+/// connect.
+async fn watch_source<R: Runtime>(app: AppHandle<R>, dir: PathBuf, mut stopped: watch::Receiver<bool>) {
+    let dev = app.state::<AddonDevState>();
+    let mut synced: HashMap<PathBuf, SystemTime> = HashMap::new();
+    let mut previous = snapshot(&dir);
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = workers::cancelled(&mut stopped) => break,
+        }
+
+        // Editors often write a file more than once; wait until a poll sees no change
+        let current = snapshot(&dir);
+        if current != previous {
+            previous = current;
+            continue;
+        }
+
+        let changed: BTreeSet<PathBuf> = current
+            .iter()
+            .filter(|(path, modified)| synced.get(*path) != Some(*modified))
+            .map(|(path, _)| path.clone())
+            .collect();
+        if changed.is_empty() {
+            continue;
+        }
+        let names: Vec<String> = changed.iter().map(|path| path.display().to_string()).collect();
+
+        let state = app.state::<AppState>();
+        if state.ws_sender.lock().await.is_none() {
+            if dev.status.lock().unwrap().stage != DevStage::Waiting {
+                dev.report(&app, |status| {
+                    status.stage = DevStage::Waiting;
+                    status.changed = names;
+                });
+            }
+            continue;
+        }
+
+        let full_reload = changed
+            .iter()
+            .any(|path| CONNECTION_FILES.iter().any(|name| path == Path::new(name)));
+        dev.report(&app, |status| {
+            status.stage = DevStage::Syncing;
+            status.changed = names.clone();
+            status.full_reload = full_reload;
+            status.error = None;
+        });
+
+        let files: Vec<PathBuf> = changed.into_iter().collect();
+        let result = match push_files(&state, &dir, &files).await {
+            Ok(_) => match reload(&state, !full_reload).await {
+                // A full reload drops the connection before the response arrives
+                Err(_) if full_reload => Ok(()),
+                result => result.map(|_| ()),
+            },
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(()) => {
+                synced.extend(files.iter().filter_map(|path| Some((path.clone(), *current.get(path)?))));
+                dev.report(&app, |status| status.stage = DevStage::Reloaded);
+            }
+            Err(error) => dev.report(&app, |status| {
+                status.stage = DevStage::Failed;
+                status.error = Some(error.to_string());
+            }),
+        }
+    }
+}
+
+/// Modification time of every pushed file under `dir`
+fn snapshot(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    collect_files(dir)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(dir.join(&path)).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_addon_dev_settings(settings: State<'_, SettingsStore>) -> AddonDevSettings {
+    settings.get().addon_dev
+}
+
+/// Save dev mode settings and start or stop watching
+#[tauri::command]
+pub fn set_addon_dev_settings<R: Runtime>(
+    config: AddonDevSettings,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), CommandError> {
+    settings.update(|s| s.addon_dev = config)?;
+    apply_dev_settings(&app)
+}
+
+#[tauri::command]
+pub fn get_addon_dev_status(dev: State<'_, AddonDevState>) -> DevStatus {
+    dev.status.lock().unwrap().clone()
+}
@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime, State};
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::storage::SharedStorage;
+use crate::tasks::{TaskHandle, TaskKind, TaskManager};
+use crate::AppState;
+
+/// Scanning library .blend files is slow on big libraries
+const SCAN_TIMEOUT: Duration = Duration::from_secs(120);
+
+const CATALOG_FILE: &str = "blender_assets.cats.txt";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssetLibrary {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssetCatalog {
+    pub id: String,
+    /// Slash separated path, e.g. `characters/ellie`
+    pub path: String,
+    pub simple_name: String,
+    pub library: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssetEntry {
+    pub name: String,
+    /// `bpy.data` collection, e.g. `actions` or `objects`
+    pub id_type: String,
+    /// Asset library name; `None` for assets of the file they were found in
+    pub library: Option<String>,
+    pub filepath: String,
+    pub catalog_id: Option<String>,
+}
+
+impl AssetEntry {
+    /// Pose assets are actions marked as assets
+    pub fn is_pose(&self) -> bool {
+        self.id_type == "actions"
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct AssetIndex {
+    pub libraries: Vec<AssetLibrary>,
+    pub catalogs: Vec<AssetCatalog>,
+    pub assets: Vec<AssetEntry>,
+    pub refreshed_at: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct AssetQuery {
+    pub library: Option<String>,
+    /// Catalog path prefix, e.g. `characters` matches `characters/ellie`
+    pub catalog: Option<String>,
+    pub id_type: Option<String>,
+    pub poses_only: bool,
+    pub text: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AssetIndexSummary {
+    pub libraries: usize,
+    pub catalogs: usize,
+    pub assets: usize,
+    pub truncated: bool,
+}
+
+/// Asset index, cached in storage as `caches` / `asset_index`.
+pub struct AssetState {
+    storage: SharedStorage,
+    index: Mutex<AssetIndex>,
+}
+
+impl AssetState {
+    /// `legacy` is the `asset_index.json` of earlier versions, imported once
+    pub fn load(storage: SharedStorage, legacy: &Path) -> Self {
+        storage.import_file(legacy, "caches", "asset_index");
+        let index = storage.get_json("caches", "asset_index").unwrap_or_default();
+        Self {
+            storage,
+            index: Mutex::new(index),
+        }
+    }
+
+    /// Asset libraries from the last refresh
+    pub async fn libraries(&self) -> Vec<AssetLibrary> {
+        self.index.lock().await.libraries.clone()
+    }
+
+    fn save(&self, index: &AssetIndex) -> Result<(), CommandError> {
+        self.storage.put_json("caches", "asset_index", index)
+    }
+}
+
+/// Rescan the connected Blender's asset libraries and merge them into the
+/// index. Runs as a cancellable task.
+#[tauri::command]
+pub async fn refresh_asset_index<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    assets: State<'_, AssetState>,
+    tasks: State<'_, TaskManager>,
+) -> Result<AssetIndexSummary, CommandError> {
+    let task = tasks.start(&app, TaskKind::AssetIndex, "Refresh asset index");
+    let result = refresh(&state, &assets, &task).await;
+    task.finish(&result);
+    result
+}
+
+async fn refresh<R: Runtime>(
+    state: &AppState,
+    assets: &AssetState,
+    task: &TaskHandle<R>,
+) -> Result<AssetIndexSummary, CommandError> {
+    task.progress(None, Some("Scanning asset libraries".to_string()));
+    let scan = task
+        .run(state.bridge.request_with_timeout("assets.list", "", json!({}), SCAN_TIMEOUT))
+        .await?;
+    task.progress(None, Some("Reading catalogs".to_string()));
+
+    let libraries: Vec<AssetLibrary> = parse_field(&scan, "libraries")?;
+    let found: Vec<AssetEntry> = parse_field(&scan, "assets")?;
+    let truncated = scan.get("truncated").and_then(Value::as_bool).unwrap_or(false);
+
+    let catalogs: Vec<AssetCatalog> = libraries.iter().flat_map(read_catalogs).collect();
+
+    let filepath = scan.get("filepath").and_then(Value::as_str).unwrap_or("");
+
+    let mut index = assets.index.lock().await;
+    merge(&mut index, filepath, libraries, catalogs, found);
+    index.refreshed_at = Some(chrono::Local::now().to_rfc3339());
+    assets.save(&index)?;
+
+    Ok(AssetIndexSummary {
+        libraries: index.libraries.len(),
+        catalogs: index.catalogs.len(),
+        assets: index.assets.len(),
+        truncated,
+    })
+}
+
+/// Browse the asset index
+#[tauri::command]
+pub async fn get_asset_index(
+    query: Option<AssetQuery>,
+    assets: State<'_, AssetState>,
+) -> Result<AssetIndex, CommandError> {
+    let query = query.unwrap_or_default();
+    let index = assets.index.lock().await;
+
+    // Catalog prefix matches resolve to ids first
+    let catalog_ids: Option<HashSet<&str>> = query.catalog.as_deref().map(|prefix| {
+        index
+            .catalogs
+            .iter()
+            .filter(|c| c.path == prefix || c.path.starts_with(&format!("{}/", prefix)))
+            .map(|c| c.id.as_str())
+            .collect()
+    });
+    let text = query.text.as_deref().map(str::to_lowercase);
+
+    let matching = index
+        .assets
+        .iter()
+        .filter(|a| query.library.is_none() || a.library == query.library)
+        .filter(|a| query.id_type.as_ref().is_none_or(|t| &a.id_type == t))
+        .filter(|a| !query.poses_only || a.is_pose())
+        .filter(|a| {
+            catalog_ids
+                .as_ref()
+                .is_none_or(|ids| a.catalog_id.as_deref().is_some_and(|id| ids.contains(id)))
+        })
+        .filter(|a| text.as_ref().is_none_or(|t| a.name.to_lowercase().contains(t)))
+        .cloned()
+        .collect();
+
+    Ok(AssetIndex {
+        libraries: index.libraries.clone(),
+        catalogs: index.catalogs.clone(),
+        assets: matching,
+        refreshed_at: index.refreshed_at.clone(),
+    })
+}
+
+/// Append (or link) an indexed asset into the live session
+#[tauri::command]
+pub async fn append_asset(
+    filepath: String,
+    id_type: String,
+    name: String,
+    link: Option<bool>,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<String, CommandError> {
+    let filepath = projects::map_path(&settings, &filepath);
+    let params = json!({"filepath": filepath, "id_type": id_type, "name": name, "link": link.unwrap_or(false)});
+    let result = state.bridge.request("assets.append", "", params.clone()).await?;
+    history.record(&settings, "assets.append", "", &params);
+    state.mirror.invalidate().await;
+
+    Ok(result.get("name").and_then(Value::as_str).unwrap_or(&name).to_string())
+}
+
+/// Apply a pose asset to an armature in the live session
+#[tauri::command]
+pub async fn apply_pose(
+    filepath: String,
+    name: String,
+    object: String,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<(), CommandError> {
+    let filepath = projects::map_path(&settings, &filepath);
+    let params = json!({"filepath": filepath, "name": name});
+    state.bridge.request("assets.apply_pose", &object, params.clone()).await?;
+    history.record(&settings, "assets.apply_pose", &object, &params);
+    Ok(())
+}
+
+/// Replace everything that came from the scanned sources, keep the rest
+/// (libraries not configured in this Blender, assets of other files).
+fn merge(
+    index: &mut AssetIndex,
+    filepath: &str,
+    libraries: Vec<AssetLibrary>,
+    catalogs: Vec<AssetCatalog>,
+    found: Vec<AssetEntry>,
+) {
+    let scanned_libraries: HashSet<String> = libraries.iter().map(|l| l.name.clone()).collect();
+
+    index.libraries.retain(|l| !scanned_libraries.contains(&l.name));
+    index.libraries.extend(libraries);
+
+    index.catalogs.retain(|c| !scanned_libraries.contains(&c.library));
+    index.catalogs.extend(catalogs);
+
+    index.assets.retain(|a| match &a.library {
+        Some(library) => !scanned_libraries.contains(library),
+        None => a.filepath != filepath,
+    });
+    index.assets.extend(found);
+}
+
+/// Parse a library's `blender_assets.cats.txt` (`uuid:path:simple name` lines)
+fn read_catalogs(library: &AssetLibrary) -> Vec<AssetCatalog> {
+    let Ok(text) = fs::read_to_string(Path::new(&library.path).join(CATALOG_FILE)) else {
+        return Vec::new();
+    };
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("VERSION"))
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ':');
+            let id = parts.next()?.trim();
+            let path = parts.next()?.trim();
+            let simple_name = parts.next().unwrap_or("").trim();
+            Some(AssetCatalog {
+                id: id.to_string(),
+                path: path.to_string(),
+                simple_name: simple_name.to_string(),
+                library: library.name.clone(),
+            })
+        })
+        .collect()
+}
+
+fn parse_field<T: serde::de::DeserializeOwned>(value: &Value, key: &str) -> Result<T, String> {
+    serde_json::from_value(value.get(key).cloned().unwrap_or(Value::Array(Vec::new())))
+        .map_err(|e| format!("Invalid '{}' in asset scan: {}", key, e))
+}
@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Resolution of a decoded waveform; frame peaks are built from these bins
+const BINS_PER_SECOND: f64 = 200.0;
+
+/// Most peaks per frame a caller may ask for
+const MAX_RESOLUTION: u32 = 16;
+
+/// A sound strip as the add-on reports it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SoundStrip {
+    pub name: String,
+    pub filepath: String,
+    pub channel: i64,
+    /// Frame the first sample plays at, before trimming
+    pub frame_start: f64,
+    pub frame_final_start: i64,
+    pub frame_final_end: i64,
+    pub volume: f64,
+    pub mute: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct AudioOptions {
+    /// Play audio while scrubbing the timeline
+    pub scrub: Option<bool>,
+    /// `AUDIO_SYNC`, `FRAME_DROP` or `NONE`
+    pub sync_mode: Option<String>,
+    pub mute: Option<bool>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AudioStrips {
+    pub fps: f64,
+    pub strips: Vec<SoundStrip>,
+    pub options: AudioOptions,
+}
+
+/// Peaks of one strip over the requested frames
+#[derive(Serialize, Debug)]
+pub struct StripPeaks {
+    pub name: String,
+    pub channel: i64,
+    pub mute: bool,
+    /// `[min, max]` pairs, `resolution` per frame; silent outside the strip
+    pub peaks: Vec<[f32; 2]>,
+    /// Why the sound couldn't be decoded
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AudioPeaks {
+    pub fps: f64,
+    pub frame_start: i64,
+    pub frame_end: i64,
+    pub resolution: u32,
+    pub strips: Vec<StripPeaks>,
+}
+
+/// Min and max sample of every bin, all channels mixed
+struct Waveform {
+    bins: Vec<[f32; 2]>,
+}
+
+impl Waveform {
+    /// Min and max over `[start, end)` seconds of the sound
+    fn peak(&self, start: f64, end: f64) -> [f32; 2] {
+        let first = (start * BINS_PER_SECOND).floor().max(0.0) as usize;
+        let last = ((end * BINS_PER_SECOND).ceil().max(0.0) as usize).min(self.bins.len());
+        self.bins
+            .get(first..last)
+            .unwrap_or_default()
+            .iter()
+            .fold([0.0, 0.0], |[min, max], [lo, hi]| [min.min(*lo), max.max(*hi)])
+    }
+}
+
+/// Decoded waveforms by file, reused until the file changes
+#[derive(Default)]
+pub struct AudioState {
+    waveforms: Mutex<HashMap<PathBuf, (SystemTime, Arc<Waveform>)>>,
+}
+
+impl AudioState {
+    fn waveform(&self, path: &Path) -> Result<Arc<Waveform>, String> {
+        let modified = fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+        if let Some((at, waveform)) = self.waveforms.lock().unwrap().get(path) {
+            if *at == modified {
+                return Ok(waveform.clone());
+            }
+        }
+        let waveform = Arc::new(decode(path)?);
+        self.waveforms
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), (modified, waveform.clone()));
+        Ok(waveform)
+    }
+}
+
+fn decode(path: &Path) -> Result<Waveform, String> {
+    let file = File::open(path).map_err(|e| format!("Can't open {}: {}", path.display(), e))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("Unsupported audio file {}: {}", path.display(), e))?;
+    let mut format = probed.format;
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| format!("No audio track in {}", path.display()))?;
+    let track_id = track.id;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        .ok_or_else(|| format!("Unknown sample rate in {}", path.display()))?;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported codec in {}: {}", path.display(), e))?;
+
+    let per_bin = ((sample_rate as f64 / BINS_PER_SECOND).round() as usize).max(1);
+    let mut bins = Vec::new();
+    let mut current = [0.0f32, 0.0f32];
+    let mut in_bin = 0;
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet; keep going with the next one
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(e) => return Err(format!("Failed to decode {}: {}", path.display(), e)),
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        // The buffer counts interleaved samples, the decoder frames
+        if buffer.as_ref().is_none_or(|b| b.capacity() < decoded.capacity() * channels) {
+            buffer = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+        }
+        let samples = buffer.as_mut().unwrap();
+        samples.copy_interleaved_ref(decoded);
+
+        for frame in samples.samples().chunks(channels) {
+            for sample in frame {
+                current[0] = current[0].min(*sample);
+                current[1] = current[1].max(*sample);
+            }
+            in_bin += 1;
+            if in_bin == per_bin {
+                bins.push(current);
+                current = [0.0, 0.0];
+                in_bin = 0;
+            }
+        }
+    }
+    if in_bin > 0 {
+        bins.push(current);
+    }
+    Ok(Waveform { bins })
+}
+
+async fn list_strips(state: &AppState, scene: Option<&str>) -> Result<AudioStrips, CommandError> {
+    let data = state.bridge.request("audio.list_strips", scene.unwrap_or(""), json!({})).await?;
+    let strips = serde_json::from_value(data.get("strips").cloned().unwrap_or(Value::Null))
+        .map_err(|e| format!("Invalid strip list from Blender: {}", e))?;
+    let options = serde_json::from_value(data.get("options").cloned().unwrap_or(Value::Null)).unwrap_or_default();
+    Ok(AudioStrips {
+        fps: data.get("fps").and_then(Value::as_f64).unwrap_or(24.0),
+        strips,
+        options,
+    })
+}
+
+/// Sound strips of a scene (default: the current one) with its audio options
+#[tauri::command]
+pub async fn list_audio_strips(scene: Option<String>, state: State<'_, AppState>) -> Result<AudioStrips, CommandError> {
+    list_strips(&state, scene.as_deref()).await
+}
+
+/// Waveform peaks of every sound strip for `frame_start..=frame_end`, at
+/// `resolution` peaks per frame, for drawing under the timeline. Sound
+/// files are decoded once and kept until they change on disk.
+#[tauri::command]
+pub async fn get_audio_peaks<R: Runtime>(
+    frame_start: i64,
+    frame_end: i64,
+    resolution: Option<u32>,
+    scene: Option<String>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+) -> Result<AudioPeaks, CommandError> {
+    if frame_end < frame_start {
+        return Err(CommandError::InvalidInput("Frame end is before frame start".to_string()));
+    }
+    let resolution = resolution.unwrap_or(1).clamp(1, MAX_RESOLUTION);
+    let listed = list_strips(&state, scene.as_deref()).await?;
+    let fps = listed.fps;
+    let paths: Vec<PathBuf> = listed
+        .strips
+        .iter()
+        .map(|strip| PathBuf::from(projects::map_path(&settings, &strip.filepath)))
+        .collect();
+
+    // Decoding is slow and blocking; keep it off the async runtime
+    let strips = tauri::async_runtime::spawn_blocking(move || {
+        let audio = app.state::<AudioState>();
+        let steps = (frame_end - frame_start + 1) as usize * resolution as usize;
+        listed
+            .strips
+            .into_iter()
+            .zip(paths)
+            .map(|(strip, path)| {
+                let (peaks, error) = match audio.waveform(&path) {
+                    Ok(waveform) => {
+                        let step = 1.0 / (fps * resolution as f64);
+                        let peaks = (0..steps)
+                            .map(|index| {
+                                let frame = frame_start as f64 + index as f64 / resolution as f64;
+                                if frame < strip.frame_final_start as f64 || frame >= strip.frame_final_end as f64 {
+                                    return [0.0, 0.0];
+                                }
+                                let start = (frame - strip.frame_start) / fps;
+                                let [min, max] = waveform.peak(start, start + step);
+                                let volume = strip.volume as f32;
+                                [min * volume, max * volume]
+                            })
+                            .collect();
+                        (peaks, None)
+                    }
+                    Err(err) => (vec![[0.0, 0.0]; steps], Some(err)),
+                };
+                StripPeaks {
+                    name: strip.name,
+                    channel: strip.channel,
+                    mute: strip.mute,
+                    peaks,
+                    error,
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Waveform task failed: {}", e))?;
+
+    Ok(AudioPeaks {
+        fps,
+        frame_start,
+        frame_end,
+        resolution,
+        strips,
+    })
+}
+
+/// Change audio scrubbing, playback sync and muting; unset options are
+/// left alone
+#[tauri::command]
+pub async fn set_audio_options(
+    options: AudioOptions,
+    scene: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
+    let params = serde_json::to_value(&options).map_err(|e| format!("Failed to serialize options: {}", e))?;
+    state
+        .bridge
+        .request("audio.set_options", scene.as_deref().unwrap_or(""), params)
+        .await?;
+    Ok(())
+}
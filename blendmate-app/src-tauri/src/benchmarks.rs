@@ -0,0 +1,341 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
+use std::sync::Mutex;
+
+use crate::devices;
+use crate::errors::CommandError;
+use crate::journal::JobRequest;
+use crate::settings::SettingsStore;
+use crate::storage::SharedStorage;
+use crate::workers::{self, Job, JobStatus, WorkItem, WorkerPool};
+
+const BENCHMARK_SCRIPT: &str = include_str!("../scripts/benchmark.py");
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BenchmarkScene {
+    /// Local .blend file
+    pub path: Option<String>,
+    /// Downloaded once by the worker and cached between runs
+    pub url: Option<String>,
+    /// Shown in results; defaults to the file name
+    pub name: Option<String>,
+}
+
+impl BenchmarkScene {
+    fn name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let source = self.path.as_deref().or(self.url.as_deref()).unwrap_or_default();
+        source
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(source)
+            .trim_end_matches(".blend")
+            .to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BenchmarkRequest {
+    pub scenes: Vec<BenchmarkScene>,
+    /// Render engines to compare; empty uses each scene's own
+    #[serde(default)]
+    pub engines: Vec<String>,
+    /// Device profiles to compare; empty keeps Blender's device setup
+    #[serde(default)]
+    pub device_profiles: Vec<String>,
+    /// Install name or Blender path; defaults to the worker setting
+    pub blender: Option<String>,
+    /// Timed renders per combination
+    #[serde(default = "default_repeats")]
+    pub repeats: u32,
+    /// Render once untimed first, e.g. to compile shaders and kernels
+    #[serde(default)]
+    pub warmup: bool,
+    pub frame: Option<i64>,
+    /// Stored with the results, e.g. "driver 550.54"
+    #[serde(default)]
+    pub note: String,
+}
+
+fn default_repeats() -> u32 {
+    1
+}
+
+/// One scene rendered with one engine and device profile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BenchmarkResult {
+    pub job: String,
+    pub scene: String,
+    /// Engine the render used, as Blender reports it
+    pub engine: Option<String>,
+    pub device_profile: Option<String>,
+    /// Cycles devices that were enabled
+    pub devices: Vec<String>,
+    pub blender: String,
+    pub blender_version: Option<String>,
+    pub render_seconds: Vec<f64>,
+    pub median_seconds: Option<f64>,
+    pub load_seconds: Option<f64>,
+    pub note: String,
+    pub error: Option<String>,
+    pub finished_at: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BenchmarkComparison {
+    pub scene: String,
+    pub engine: Option<String>,
+    pub device_profile: Option<String>,
+    pub baseline_seconds: Option<f64>,
+    pub candidate_seconds: Option<f64>,
+    /// Candidate time relative to the baseline; -0.1 is 10% faster
+    pub change: Option<f64>,
+}
+
+/// Benchmark results, written to storage as `metrics` / `benchmarks`
+/// after every finished job.
+pub struct BenchmarkStore {
+    storage: SharedStorage,
+    results: Mutex<Vec<BenchmarkResult>>,
+}
+
+impl BenchmarkStore {
+    /// `legacy` is the `benchmarks.json` of earlier versions, imported once
+    pub fn load(storage: SharedStorage, legacy: &Path) -> Self {
+        storage.import_file(legacy, "metrics", "benchmarks");
+        let results = storage.get_json("metrics", "benchmarks").unwrap_or_default();
+        Self {
+            storage,
+            results: Mutex::new(results),
+        }
+    }
+
+    fn record(&self, results: Vec<BenchmarkResult>) {
+        let mut all = self.results.lock().unwrap();
+        all.extend(results);
+        if let Err(err) = self.save(&all) {
+            eprintln!("Failed to save benchmarks: {err}");
+        }
+    }
+
+    fn save(&self, results: &[BenchmarkResult]) -> Result<(), CommandError> {
+        self.storage.put_json("metrics", "benchmarks", results)
+    }
+}
+
+/// Scene, engine and device profile of each item, in item order
+fn combinations(request: &BenchmarkRequest) -> Vec<(&BenchmarkScene, Option<&String>, Option<&String>)> {
+    let engines: Vec<Option<&String>> = if request.engines.is_empty() {
+        vec![None]
+    } else {
+        request.engines.iter().map(Some).collect()
+    };
+    let profiles: Vec<Option<&String>> = if request.device_profiles.is_empty() {
+        vec![None]
+    } else {
+        request.device_profiles.iter().map(Some).collect()
+    };
+
+    let mut combinations = Vec::new();
+    for scene in &request.scenes {
+        for engine in &engines {
+            for profile in &profiles {
+                combinations.push((scene, *engine, *profile));
+            }
+        }
+    }
+    combinations
+}
+
+/// One item per scene, engine and device profile
+pub fn work(settings: &SettingsStore, request: &BenchmarkRequest) -> Result<Vec<WorkItem>, CommandError> {
+    let cache_dir = std::env::temp_dir().join("blendmate-benchmarks");
+    combinations(request)
+        .into_iter()
+        .map(|(scene, engine, profile)| {
+            let devices = profile.map(|name| devices::find_profile(settings, name)).transpose()?;
+            let mut label = scene.name();
+            for part in [engine, profile].into_iter().flatten() {
+                label = format!("{} · {}", label, part);
+            }
+            Ok(WorkItem {
+                label,
+                blend_file: scene.path.as_ref().map(PathBuf::from),
+                script: BENCHMARK_SCRIPT,
+                args: json!({
+                    "url": scene.url,
+                    "cache_dir": cache_dir,
+                    "engine": engine,
+                    "frame": request.frame,
+                    "repeats": request.repeats,
+                    "warmup": request.warmup,
+                }),
+                devices,
+                priority: 0,
+                env: BTreeMap::new(),
+            })
+        })
+        .collect()
+}
+
+/// Store the results of a finished benchmark job; returns the job summary.
+pub fn record<R: Runtime>(app: &AppHandle<R>, request: &BenchmarkRequest, job: &Job) -> Value {
+    let finished_at = job.finished_at.clone().unwrap_or_default();
+    let results: Vec<BenchmarkResult> = combinations(request)
+        .into_iter()
+        .zip(&job.items)
+        .filter(|(_, item)| item.status != JobStatus::Cancelled)
+        .map(|((scene, _, profile), item)| {
+            let result = item.result.clone().unwrap_or(Value::Null);
+            let render_seconds: Vec<f64> = result
+                .get("render_seconds")
+                .and_then(Value::as_array)
+                .map(|times| times.iter().filter_map(Value::as_f64).collect())
+                .unwrap_or_default();
+            let error = match item.status {
+                JobStatus::Done => None,
+                _ => Some(item.errors.last().cloned().unwrap_or_else(|| "Benchmark failed".to_string())),
+            };
+            BenchmarkResult {
+                job: job.id.clone(),
+                scene: scene.name(),
+                engine: result.get("engine").and_then(Value::as_str).map(str::to_string),
+                device_profile: profile.cloned(),
+                devices: result
+                    .get("devices")
+                    .and_then(Value::as_array)
+                    .map(|names| names.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                    .unwrap_or_default(),
+                blender: job.blender.clone(),
+                blender_version: result.get("blender_version").and_then(Value::as_str).map(str::to_string),
+                median_seconds: median(&render_seconds),
+                render_seconds,
+                load_seconds: result.get("load_seconds").and_then(Value::as_f64),
+                note: request.note.clone(),
+                error,
+                finished_at: finished_at.clone(),
+            }
+        })
+        .collect();
+
+    let summary = json!({
+        "results": results.len(),
+        "failed": results.iter().filter(|r| r.error.is_some()).count(),
+    });
+    app.state::<BenchmarkStore>().record(results);
+    summary
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    Some(if sorted.len() % 2 == 0 {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    })
+}
+
+/// Queue a benchmark on the worker pool. Results are stored when the job
+/// finishes and listed by `list_benchmark_results`.
+#[tauri::command]
+pub async fn start_benchmark_job<R: Runtime>(
+    request: BenchmarkRequest,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+) -> Result<String, CommandError> {
+    if request.scenes.is_empty() {
+        return Err(CommandError::InvalidInput("No benchmark scenes selected".to_string()));
+    }
+    if let Some(scene) = request.scenes.iter().find(|s| s.path.is_none() == s.url.is_none()) {
+        return Err(CommandError::InvalidInput(format!(
+            "Scene '{}' needs either a path or a URL",
+            scene.name()
+        )));
+    }
+    if let Some(path) = request.scenes.iter().filter_map(|s| s.path.as_ref()).find(|p| !Path::new(p).is_file()) {
+        return Err(CommandError::NotFound(format!("Benchmark scene not found: {}", path)));
+    }
+
+    let id = pool.submit(JobRequest::Benchmark(request.clone()), &settings).await?;
+    let request = JobRequest::Benchmark(request);
+    workers::spawn_job(app, id.clone(), move |app, job| request.finished(app, job));
+    Ok(id)
+}
+
+/// Stored results, oldest first, optionally for one scene only
+#[tauri::command]
+pub fn list_benchmark_results(scene: Option<String>, store: State<'_, BenchmarkStore>) -> Vec<BenchmarkResult> {
+    let results = store.results.lock().unwrap();
+    results
+        .iter()
+        .filter(|r| scene.as_ref().is_none_or(|scene| &r.scene == scene))
+        .cloned()
+        .collect()
+}
+
+/// Median render times of two benchmark jobs side by side, matched by
+/// scene, engine and device profile
+#[tauri::command]
+pub fn compare_benchmark_runs(
+    baseline: String,
+    candidate: String,
+    store: State<'_, BenchmarkStore>,
+) -> Result<Vec<BenchmarkComparison>, CommandError> {
+    let results = store.results.lock().unwrap();
+    let of_job = |job: &str| -> BTreeMap<(String, Option<String>, Option<String>), Option<f64>> {
+        results
+            .iter()
+            .filter(|r| r.job == job)
+            .map(|r| ((r.scene.clone(), r.engine.clone(), r.device_profile.clone()), r.median_seconds))
+            .collect()
+    };
+    let before = of_job(&baseline);
+    let after = of_job(&candidate);
+    for (job, found) in [(&baseline, &before), (&candidate, &after)] {
+        if found.is_empty() {
+            return Err(CommandError::NotFound(format!("No benchmark results for job '{}'", job)));
+        }
+    }
+
+    let mut keys: Vec<_> = before.keys().chain(after.keys()).cloned().collect();
+    keys.sort();
+    keys.dedup();
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            let baseline_seconds = before.get(&key).copied().flatten();
+            let candidate_seconds = after.get(&key).copied().flatten();
+            let change = baseline_seconds
+                .zip(candidate_seconds)
+                .filter(|(base, _)| *base > 0.0)
+                .map(|(base, new)| new / base - 1.0);
+            let (scene, engine, device_profile) = key;
+            BenchmarkComparison {
+                scene,
+                engine,
+                device_profile,
+                baseline_seconds,
+                candidate_seconds,
+                change,
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn clear_benchmark_results(store: State<'_, BenchmarkStore>) -> Result<(), CommandError> {
+    let mut results = store.results.lock().unwrap();
+    results.clear();
+    store.storage.delete("metrics", "benchmarks")
+}
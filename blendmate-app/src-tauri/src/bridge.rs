@@ -0,0 +1,247 @@
+use futures_util::SinkExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Mutex};
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+use crate::command_queue::{CommandQueue, FRONTEND_HOLD};
+use crate::errors::CommandError;
+use crate::faults::{Direction, FaultInjector};
+use crate::protocol;
+
+pub type WsConnection = Arc<Mutex<Option<futures_util::stream::SplitSink<WebSocketStream<tokio::net::TcpStream>, Message>>>>;
+
+/// A single property assignment inside a grouped `batch.execute` request.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PropertyChange {
+    pub target: String,
+    pub path: String,
+    pub value: Value,
+}
+
+type PendingReply = oneshot::Sender<Result<Value, CommandError>>;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Requests issued by the backend itself (not relayed from the frontend).
+///
+/// Request ids use the `bm-` prefix so they never collide with the
+/// frontend's `req-`/`cmd-` ids; responses to them are consumed here and
+/// are not forwarded as `ws:message`.
+pub struct BlenderBridge {
+    ws_sender: WsConnection,
+    pending: Mutex<HashMap<String, PendingReply>>,
+    next_id: AtomicU64,
+    faults: FaultInjector,
+    commands: CommandQueue,
+}
+
+impl BlenderBridge {
+    pub fn new(ws_sender: WsConnection) -> Self {
+        Self {
+            ws_sender,
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            faults: FaultInjector::default(),
+            commands: CommandQueue::default(),
+        }
+    }
+
+    /// Send a request to the add-on and wait for its response data.
+    pub async fn request(&self, action: &str, target: &str, params: Value) -> Result<Value, CommandError> {
+        self.request_with_timeout(action, target, params, DEFAULT_TIMEOUT).await
+    }
+
+    pub async fn request_with_timeout(
+        &self,
+        action: &str,
+        target: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, CommandError> {
+        let id = format!("bm-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        // Waiting for an earlier mutation counts toward the timeout
+        let started = Instant::now();
+        if tokio::time::timeout(timeout, self.commands.admit(&id, action, timeout)).await.is_err() {
+            return Err(CommandError::Timeout(format!("Request '{}' timed out waiting for its turn", action)));
+        }
+        let timeout = timeout.saturating_sub(started.elapsed());
+        let message = json!({
+            "type": "request",
+            "id": id,
+            "action": action,
+            "target": target,
+            "params": params,
+        });
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending.lock().await.insert(id.clone(), reply_tx);
+
+        if let Err(err) = self.send_raw(message.to_string()).await {
+            self.pending.lock().await.remove(&id);
+            self.commands.finished(&id);
+            return Err(err);
+        }
+
+        match tokio::time::timeout(timeout, reply_rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(CommandError::NotConnected(format!(
+                "Connection closed before '{}' completed",
+                action
+            ))),
+            Err(_) => {
+                self.pending.lock().await.remove(&id);
+                self.commands.finished(&id);
+                Err(CommandError::Timeout(format!("Request '{}' timed out", action)))
+            }
+        }
+    }
+
+    /// Run property changes as one undo step; the add-on reverts the whole
+    /// group if any step fails.
+    pub async fn execute_batch(&self, changes: &[PropertyChange], label: &str) -> Result<Value, CommandError> {
+        let steps: Vec<Value> = changes
+            .iter()
+            .map(|change| {
+                json!({
+                    "action": "property.set",
+                    "target": change.target,
+                    "params": {"path": change.path, "value": change.value},
+                })
+            })
+            .collect();
+
+        self.request("batch.execute", "", json!({"label": label, "steps": steps}))
+            .await
+    }
+
+    /// Send a frame to the add-on. Frames from the frontend go through
+    /// here too, so fault injection covers all outgoing traffic.
+    pub async fn send_raw(&self, message: String) -> Result<(), CommandError> {
+        if self.ws_sender.lock().await.is_none() {
+            return Err(CommandError::not_connected());
+        }
+        for frame in self.faults.apply(Direction::Outgoing, message).await {
+            let mut sender_guard = self.ws_sender.lock().await;
+            let Some(sender) = sender_guard.as_mut() else {
+                return Err(CommandError::not_connected());
+            };
+            sender
+                .send(Message::Text(frame))
+                .await
+                .map_err(|e| CommandError::NotConnected(format!("Failed to send: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Send a frame from the frontend. Requests wait for their turn in the
+    /// command queue; responses reach the frontend as `ws:message`.
+    pub async fn send_frame(&self, message: String) -> Result<(), CommandError> {
+        let request = serde_json::from_str::<Value>(&message)
+            .ok()
+            .filter(|value| protocol::message_type(value).as_deref() == Some("request"));
+        let Some((id, action)) = request.as_ref().and_then(|request| {
+            let id = request.get("id")?.as_str()?;
+            let action = protocol::message_body(request).get("action")?.as_str()?;
+            Some((id, action))
+        }) else {
+            return self.send_raw(message).await;
+        };
+        self.commands.admit(id, action, FRONTEND_HOLD).await?;
+        let result = self.send_raw(message).await;
+        if result.is_err() {
+            self.commands.finished(id);
+        }
+        result
+    }
+
+    pub fn faults(&self) -> &FaultInjector {
+        &self.faults
+    }
+
+    pub fn commands(&self) -> &CommandQueue {
+        &self.commands
+    }
+
+    /// Backend requests still waiting for a response
+    pub async fn pending_count(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    /// Resolve a pending backend request from an incoming message.
+    ///
+    /// Returns `true` when the message was a response to one of our own
+    /// requests and should not be forwarded to the frontend.
+    pub async fn handle_incoming(&self, value: &Value) -> bool {
+        if value.get("type").and_then(Value::as_str) != Some("response") {
+            return false;
+        }
+
+        // Protocol v1 responses carry `reply_to`, legacy responses carry `id`
+        let request_id = value
+            .get("reply_to")
+            .or_else(|| value.get("id"))
+            .and_then(Value::as_str);
+        if let Some(request_id) = request_id {
+            self.commands.finished(request_id);
+        }
+        let Some(request_id) = request_id.filter(|id| id.starts_with("bm-")) else {
+            return false;
+        };
+
+        let Some(reply_tx) = self.pending.lock().await.remove(request_id) else {
+            return true;
+        };
+        let _ = reply_tx.send(parse_response(value));
+        true
+    }
+
+    /// Fail all in-flight and queued requests, e.g. when the add-on
+    /// disconnects.
+    pub async fn fail_pending(&self, reason: &str) {
+        self.commands.reset();
+        for (_, reply_tx) in self.pending.lock().await.drain() {
+            let _ = reply_tx.send(Err(CommandError::NotConnected(reason.to_string())));
+        }
+    }
+}
+
+fn parse_response(value: &Value) -> Result<Value, CommandError> {
+    // Protocol v1 envelope: {"body": {"ok": bool, "data": ..., "error": {...}}}
+    if let Some(body) = value.get("body") {
+        if body.get("ok").and_then(Value::as_bool) == Some(true) {
+            return Ok(body.get("data").cloned().unwrap_or(Value::Null));
+        }
+        let message = body
+            .pointer("/error/message")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown error");
+        return Err(CommandError::Blender {
+            message: message.to_string(),
+            details: body.get("error").filter(|error| error.is_object()).cloned(),
+        });
+    }
+
+    // Legacy: {"ok": true, "data": ...} or {"error": "..."}
+    if let Some(error) = value.get("error") {
+        return Err(CommandError::Blender {
+            message: error.as_str().map(str::to_string).unwrap_or_else(|| error.to_string()),
+            details: None,
+        });
+    }
+    Ok(value.get("data").cloned().unwrap_or(Value::Null))
+}
+
+/// Build a resolver path like `objects['Cube']`, quoting names the add-on
+/// resolver would otherwise misparse.
+pub fn datablock_path(collection: &str, name: &str) -> String {
+    if name.contains('\'') {
+        format!("{}[\"{}\"]", collection, name)
+    } else {
+        format!("{}['{}']", collection, name)
+    }
+}
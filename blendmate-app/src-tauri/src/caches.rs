@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::AppState;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SimCache {
+    /// Object name, or scene name for the rigid body world
+    pub object: String,
+    /// Modifier name, `particles:<system>` or `rigidbody`
+    pub source: String,
+    pub kind: String,
+    pub directory: String,
+    pub baked: bool,
+    pub use_disk_cache: bool,
+    pub frame_start: i64,
+    pub frame_end: i64,
+    #[serde(default)]
+    pub exists: bool,
+    #[serde(default)]
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub file_count: u64,
+    /// Other caches write to the same directory (default `blendcache_*`)
+    #[serde(default)]
+    pub shared: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct CacheReport {
+    pub caches: Vec<SimCache>,
+    /// Disk usage of all cache directories, each counted once
+    pub total_bytes: u64,
+}
+
+/// Last listing; delete and relocate only act on directories it contains.
+#[derive(Default)]
+pub struct CacheState {
+    known: Mutex<Vec<SimCache>>,
+}
+
+impl CacheState {
+    /// Directories of the caches in the last listing
+    pub async fn directories(&self) -> Vec<String> {
+        self.known.lock().await.iter().map(|cache| cache.directory.clone()).collect()
+    }
+}
+
+/// Simulation caches of the open file with their size on disk
+#[tauri::command]
+pub async fn list_caches(
+    state: State<'_, AppState>,
+    caches: State<'_, CacheState>,
+) -> Result<CacheReport, CommandError> {
+    let listing = state.bridge.request("caches.list", "", json!({})).await?;
+    let mut found: Vec<SimCache> = serde_json::from_value(listing.get("caches").cloned().unwrap_or_default())
+        .map_err(|e| format!("Invalid cache listing: {}", e))?;
+
+    let directories: Vec<String> = found.iter().map(|c| c.directory.clone()).collect();
+    let sizes = tokio::task::spawn_blocking(move || {
+        directories
+            .iter()
+            .map(|dir| dir_usage(Path::new(dir)))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut seen = HashSet::new();
+    let mut total_bytes = 0;
+    for (index, usage) in sizes.into_iter().enumerate() {
+        let shared = directories_shared(&found, index);
+        let cache = &mut found[index];
+        cache.shared = shared;
+        if let Some((size, files)) = usage {
+            cache.exists = true;
+            cache.size_bytes = size;
+            cache.file_count = files;
+            if seen.insert(cache.directory.clone()) {
+                total_bytes += size;
+            }
+        }
+    }
+
+    *caches.known.lock().await = found.clone();
+    Ok(CacheReport {
+        caches: found,
+        total_bytes,
+    })
+}
+
+/// Delete a cache's files on disk. Shared directories need `force`.
+#[tauri::command]
+pub async fn delete_cache(
+    object: String,
+    source: String,
+    force: Option<bool>,
+    caches: State<'_, CacheState>,
+) -> Result<u64, CommandError> {
+    let cache = find_known(&caches, &object, &source).await?;
+    if cache.shared && !force.unwrap_or(false) {
+        return Err(CommandError::InvalidInput(format!(
+            "{} is shared with other caches; pass force to delete it anyway",
+            cache.directory
+        )));
+    }
+
+    let directory = PathBuf::from(&cache.directory);
+    check_cache_directory(&directory)?;
+    tokio::task::spawn_blocking(move || fs::remove_dir_all(&directory))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| CommandError::Io(format!("Failed to delete {}: {}", cache.directory, e)))?;
+
+    Ok(cache.size_bytes)
+}
+
+/// Point a cache at a new directory, optionally moving its files there
+#[tauri::command]
+pub async fn relocate_cache(
+    object: String,
+    source: String,
+    directory: String,
+    move_files: Option<bool>,
+    state: State<'_, AppState>,
+    caches: State<'_, CacheState>,
+) -> Result<(), CommandError> {
+    let cache = find_known(&caches, &object, &source).await?;
+
+    if move_files.unwrap_or(true) && cache.exists {
+        if cache.shared {
+            return Err(CommandError::InvalidInput(format!(
+                "{} is shared with other caches; relocate without moving files",
+                cache.directory
+            )));
+        }
+        let from = PathBuf::from(&cache.directory);
+        let to = PathBuf::from(&directory);
+        check_cache_directory(&from)?;
+        tokio::task::spawn_blocking(move || move_dir(&from, &to))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| CommandError::Io(format!("Failed to move cache: {}", e)))?;
+    }
+
+    // Blender wants a trailing separator on cache directories
+    let mut directory = directory;
+    if !directory.ends_with(std::path::MAIN_SEPARATOR) {
+        directory.push(std::path::MAIN_SEPARATOR);
+    }
+    state
+        .bridge
+        .request("caches.set_directory", &object, json!({"source": source, "directory": directory}))
+        .await?;
+    Ok(())
+}
+
+async fn find_known(caches: &CacheState, object: &str, source: &str) -> Result<SimCache, CommandError> {
+    caches
+        .known
+        .lock()
+        .await
+        .iter()
+        .find(|c| c.object == object && c.source == source)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown cache {} / {}; list caches first", object, source)))
+}
+
+fn directories_shared(caches: &[SimCache], index: usize) -> bool {
+    let directory = &caches[index].directory;
+    caches
+        .iter()
+        .enumerate()
+        .any(|(other, cache)| other != index && &cache.directory == directory)
+}
+
+/// Refuse directories that clearly aren't caches (project folders).
+fn check_cache_directory(directory: &Path) -> Result<(), String> {
+    let entries = fs::read_dir(directory).map_err(|e| format!("Cannot read {}: {}", directory.display(), e))?;
+    for entry in entries.flatten() {
+        if entry.path().extension().is_some_and(|ext| ext == "blend") {
+            return Err(format!("{} contains .blend files; not touching it", directory.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Total size and file count below `directory`; `None` when it doesn't exist
+fn dir_usage(directory: &Path) -> Option<(u64, u64)> {
+    if !directory.is_dir() {
+        return None;
+    }
+    let mut size = 0;
+    let mut files = 0;
+    let mut pending = vec![directory.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                size += metadata.len();
+                files += 1;
+            }
+        }
+    }
+    Some((size, files))
+}
+
+/// Move a directory's contents, copying when a rename can't cross devices
+fn move_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if fs::rename(entry.path(), &target).is_err() {
+            if entry.metadata()?.is_dir() {
+                move_dir(&entry.path(), &target)?;
+            } else {
+                fs::copy(entry.path(), &target)?;
+                fs::remove_file(entry.path())?;
+            }
+        }
+    }
+    fs::remove_dir_all(from)
+}
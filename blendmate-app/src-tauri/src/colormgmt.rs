@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Project color management standard; unset fields are not checked.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct ColorStandard {
+    pub display_device: Option<String>,
+    pub view_transform: Option<String>,
+    pub look: Option<String>,
+    pub exposure: Option<f64>,
+    pub gamma: Option<f64>,
+    pub sequencer_colorspace: Option<String>,
+    /// Expected `OCIO` environment value (config path)
+    pub ocio_config: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ColorMismatch {
+    pub field: String,
+    pub expected: Value,
+    pub actual: Value,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ColorInspection {
+    /// `color.get_settings` result from the connected Blender
+    pub blender: Value,
+    /// `OCIO` in the app's environment, inherited by headless workers
+    pub app_ocio_env: Option<String>,
+    pub standard: ColorStandard,
+    pub mismatches: Vec<ColorMismatch>,
+}
+
+/// Compare the connected Blender's color management with the project standard
+#[tauri::command]
+pub async fn inspect_color_management(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+) -> Result<ColorInspection, CommandError> {
+    let blender = state.bridge.request("color.get_settings", "", json!({})).await?;
+    let standard = settings.get().color_standard;
+    let app_ocio_env = std::env::var("OCIO").ok().filter(|v| !v.is_empty());
+
+    let mut mismatches = Vec::new();
+    let mut compare = |field: &str, expected: Option<Value>| {
+        let Some(expected) = expected else {
+            return;
+        };
+        let actual = blender.get(field).cloned().unwrap_or(Value::Null);
+        if !values_match(&expected, &actual) {
+            mismatches.push(ColorMismatch {
+                field: field.to_string(),
+                message: format!("Expected {} {}, Blender has {}", field, expected, actual),
+                expected,
+                actual,
+            });
+        }
+    };
+
+    compare("display_device", standard.display_device.clone().map(Value::from));
+    compare("view_transform", standard.view_transform.clone().map(Value::from));
+    compare("look", standard.look.clone().map(Value::from));
+    compare("exposure", standard.exposure.map(Value::from));
+    compare("gamma", standard.gamma.map(Value::from));
+    compare("sequencer_colorspace", standard.sequencer_colorspace.clone().map(Value::from));
+
+    // The standard's view transform may not exist at all in this config (AgX before 4.0)
+    if let Some(view) = &standard.view_transform {
+        let available = blender.get("available_view_transforms").and_then(Value::as_array);
+        if available.is_some_and(|list| !list.iter().any(|v| v.as_str() == Some(view))) {
+            mismatches.push(ColorMismatch {
+                field: "available_view_transforms".to_string(),
+                expected: Value::from(view.as_str()),
+                actual: blender.get("available_view_transforms").cloned().unwrap_or_default(),
+                message: format!("View transform '{}' is not available in Blender's OCIO config", view),
+            });
+        }
+    }
+
+    let blender_ocio = blender.get("ocio_env").and_then(Value::as_str).map(str::to_string);
+    if standard.ocio_config.is_some() && standard.ocio_config != blender_ocio {
+        mismatches.push(ColorMismatch {
+            field: "ocio_env".to_string(),
+            expected: json!(standard.ocio_config),
+            actual: json!(blender_ocio),
+            message: "Blender is not running with the project OCIO config".to_string(),
+        });
+    }
+    if app_ocio_env != blender_ocio {
+        mismatches.push(ColorMismatch {
+            field: "app_ocio_env".to_string(),
+            expected: json!(blender_ocio),
+            actual: json!(app_ocio_env),
+            message: "Headless jobs launched by the app would use a different OCIO config".to_string(),
+        });
+    }
+
+    Ok(ColorInspection {
+        blender,
+        app_ocio_env,
+        standard,
+        mismatches,
+    })
+}
+
+#[tauri::command]
+pub fn get_color_standard(settings: State<'_, SettingsStore>) -> ColorStandard {
+    settings.get().color_standard
+}
+
+#[tauri::command]
+pub fn set_color_standard(standard: ColorStandard, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.color_standard = standard)?;
+    Ok(())
+}
+
+/// Apply the project standard to the connected Blender's scene. The OCIO
+/// config itself can only change with a Blender restart.
+#[tauri::command]
+pub async fn apply_color_standard(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<(), CommandError> {
+    let standard = settings.get().color_standard;
+    let params = serde_json::to_value(&standard).map_err(|e| e.to_string())?;
+    state.bridge.request("color.set_settings", "", params.clone()).await?;
+    history.record(&settings, "color.set_settings", "", &params);
+    Ok(())
+}
+
+fn values_match(expected: &Value, actual: &Value) -> bool {
+    match (expected.as_f64(), actual.as_f64()) {
+        (Some(a), Some(b)) => (a - b).abs() < 1e-4,
+        _ => expected == actual,
+    }
+}
@@ -0,0 +1,298 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::Notify;
+
+use crate::errors::CommandError;
+use crate::AppState;
+
+/// How long a frontend request may hold the mutation slot without a
+/// response; backend requests hold it for their own timeout
+pub const FRONTEND_HOLD: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandClass {
+    /// Reads state; runs alongside anything
+    Query,
+    /// Changes Blender's state; one at a time, in the order sent
+    Mutation,
+    /// Cancels or probes; never waits, so a cancel isn't stuck behind
+    /// what it cancels
+    Control,
+}
+
+/// Class of an add-on action by its name: `get_*` and `*.list` style
+/// actions are queries, cancels and pings are control, the rest mutate
+pub fn classify(action: &str) -> CommandClass {
+    let verb = action.rsplit('.').next().unwrap_or(action);
+    if matches!(action, "ping" | "echo" | "protocol.upgrade" | "events.set_throttle") || verb.contains("cancel") {
+        CommandClass::Control
+    } else if verb.starts_with("get")
+        || verb.starts_with("list")
+        || matches!(action, "licenses.used" | "viewport.capture")
+    {
+        CommandClass::Query
+    } else {
+        CommandClass::Mutation
+    }
+}
+
+/// Emitted as `command:ack` when a request is queued, when its place in
+/// line changes and when it is sent
+#[derive(Serialize, Clone, Debug)]
+pub struct CommandAck {
+    pub id: String,
+    pub action: String,
+    pub class: CommandClass,
+    /// Mutations ahead of this one, counting the running one; 0 once sent
+    pub position: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct QueuedCommand {
+    pub id: String,
+    pub action: String,
+    pub since: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommandQueueStatus {
+    /// Mutation sent and waiting for its response
+    pub running: Option<QueuedCommand>,
+    pub queued: Vec<QueuedCommand>,
+}
+
+struct Running {
+    command: QueuedCommand,
+    /// Given up on after this, so a lost response doesn't stall the queue
+    expires: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Bumped on every disconnect; waiters from an older session fail
+    session: u64,
+    running: Option<Running>,
+    queued: VecDeque<QueuedCommand>,
+}
+
+type AckListener = Box<dyn Fn(&CommandAck) + Send + Sync>;
+
+/// Orders requests to the add-on within a connection. Mutations are sent
+/// one at a time and each waits for the previous one's response; queries
+/// and control requests are sent straight away.
+#[derive(Default)]
+pub struct CommandQueue {
+    inner: Mutex<Inner>,
+    changed: Notify,
+    listener: Mutex<Option<AckListener>>,
+}
+
+/// Gives up a place in line when the waiting request is dropped, e.g.
+/// because it timed out
+struct Waiting<'a> {
+    queue: &'a CommandQueue,
+    id: &'a str,
+    admitted: bool,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if self.admitted {
+            return;
+        }
+        self.queue.inner.lock().unwrap().queued.retain(|command| command.id != self.id);
+        self.queue.changed.notify_waiters();
+        self.queue.announce_positions();
+    }
+}
+
+impl CommandQueue {
+    pub fn set_listener(&self, listener: impl Fn(&CommandAck) + Send + Sync + 'static) {
+        *self.listener.lock().unwrap() = Some(Box::new(listener));
+    }
+
+    fn ack(&self, ack: CommandAck) {
+        if let Some(listener) = self.listener.lock().unwrap().as_ref() {
+            listener(&ack);
+        }
+    }
+
+    fn announce_positions(&self) {
+        let acks: Vec<CommandAck> = {
+            let inner = self.inner.lock().unwrap();
+            let ahead = usize::from(inner.running.is_some());
+            inner
+                .queued
+                .iter()
+                .enumerate()
+                .map(|(index, command)| CommandAck {
+                    id: command.id.clone(),
+                    action: command.action.clone(),
+                    class: CommandClass::Mutation,
+                    position: ahead + index,
+                })
+                .collect()
+        };
+        for ack in acks {
+            self.ack(ack);
+        }
+    }
+
+    /// Wait until request `id` may be sent. A mutation holds the slot until
+    /// `finished(id)` or for `hold` at most. Dropping the future gives up
+    /// the place in line.
+    pub async fn admit(&self, id: &str, action: &str, hold: Duration) -> Result<(), CommandError> {
+        let class = classify(action);
+        if class != CommandClass::Mutation {
+            self.ack(CommandAck {
+                id: id.to_string(),
+                action: action.to_string(),
+                class,
+                position: 0,
+            });
+            return Ok(());
+        }
+
+        let session = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.queued.push_back(QueuedCommand {
+                id: id.to_string(),
+                action: action.to_string(),
+                since: chrono::Local::now().to_rfc3339(),
+            });
+            inner.session
+        };
+        let mut waiting = Waiting {
+            queue: self,
+            id,
+            admitted: false,
+        };
+        self.announce_positions();
+
+        loop {
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let deadline = {
+                let mut inner = self.inner.lock().unwrap();
+                if inner.session != session {
+                    return Err(CommandError::not_connected());
+                }
+                if inner.running.as_ref().is_some_and(|running| running.expires <= Instant::now()) {
+                    if let Some(running) = inner.running.take() {
+                        eprintln!("No response to {} in time, sending the next command", running.command.action);
+                    }
+                    self.changed.notify_waiters();
+                }
+                match inner.running.as_ref().map(|running| running.expires) {
+                    Some(expires) => Some(expires),
+                    None if inner.queued.front().is_some_and(|command| command.id == id) => {
+                        let command = inner.queued.pop_front().expect("front checked above");
+                        inner.running = Some(Running {
+                            command,
+                            expires: Instant::now() + hold,
+                        });
+                        waiting.admitted = true;
+                        drop(inner);
+                        self.ack(CommandAck {
+                            id: id.to_string(),
+                            action: action.to_string(),
+                            class,
+                            position: 0,
+                        });
+                        self.announce_positions();
+                        return Ok(());
+                    }
+                    // Another request is next and was woken as well
+                    None => None,
+                }
+            };
+            match deadline {
+                Some(deadline) => {
+                    tokio::select! {
+                        _ = &mut notified => {}
+                        _ = tokio::time::sleep_until(deadline.into()) => {}
+                    }
+                }
+                None => notified.await,
+            }
+        }
+    }
+
+    /// Request `id` got its response or will never get one; the next
+    /// mutation may go
+    pub fn finished(&self, id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.running.as_ref().is_some_and(|running| running.command.id == id) {
+            inner.running = None;
+            drop(inner);
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Start a new session: queued requests fail and nothing is running
+    pub fn reset(&self) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.session += 1;
+            inner.running = None;
+            inner.queued.clear();
+        }
+        self.changed.notify_waiters();
+    }
+
+    pub fn status(&self) -> CommandQueueStatus {
+        let inner = self.inner.lock().unwrap();
+        CommandQueueStatus {
+            running: inner.running.as_ref().map(|running| running.command.clone()),
+            queued: inner.queued.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Emit the command queue's acks as `command:ack`
+pub fn forward_acks<R: Runtime>(app: AppHandle<R>) {
+    let bridge = app.state::<AppState>().bridge.clone();
+    bridge.commands().set_listener(move |ack| {
+        if let Err(err) = app.emit("command:ack", ack) {
+            eprintln!("Failed to emit command:ack: {err}");
+        }
+    });
+}
+
+/// The mutation waiting for Blender and those queued behind it
+#[tauri::command]
+pub fn get_command_queue(state: State<'_, AppState>) -> CommandQueueStatus {
+    state.bridge.commands().status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_are_queries() {
+        for action in ["scene.get", "get_scene", "objects.list", "list_cameras", "licenses.used", "viewport.capture"] {
+            assert_eq!(classify(action), CommandClass::Query, "{action}");
+        }
+    }
+
+    #[test]
+    fn cancels_and_probes_are_control() {
+        for action in ["ping", "echo", "protocol.upgrade", "events.set_throttle", "render.cancel", "cancel_bake"] {
+            assert_eq!(classify(action), CommandClass::Control, "{action}");
+        }
+    }
+
+    #[test]
+    fn everything_else_mutates() {
+        for action in ["operator.call", "property.set", "objects.rename", "target_list.add"] {
+            assert_eq!(classify(action), CommandClass::Mutation, "{action}");
+        }
+    }
+}
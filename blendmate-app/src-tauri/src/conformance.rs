@@ -0,0 +1,162 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::tasks::{TaskHandle, TaskKind, TaskManager};
+use crate::AppState;
+
+/// Size of the large-payload round trip
+const LARGE_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+const STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConformanceStep {
+    pub name: String,
+    pub passed: bool,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConformanceReport {
+    pub session: String,
+    pub passed: bool,
+    pub steps: Vec<ConformanceStep>,
+    pub started_at: String,
+}
+
+/// Run the protocol conformance sequence against the connected add-on.
+///
+/// This is the acceptance test for add-on releases. The operator step runs
+/// `wm.redraw_timer`, which changes nothing in the scene. Runs as a task;
+/// cancelling it skips the remaining steps.
+#[tauri::command]
+pub async fn run_conformance<R: Runtime>(
+    session: Option<String>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    tasks: State<'_, TaskManager>,
+) -> Result<ConformanceReport, CommandError> {
+    let peer = state.session.check_peer(session.as_deref())?;
+    let task = tasks.start(&app, TaskKind::Diagnostics, "Protocol conformance");
+    let started_at = chrono::Local::now().to_rfc3339();
+    let mut steps = Vec::new();
+
+    steps.push(step(&task, "hello", || async {
+        let data = request(&state, "ping", "", json!({})).await?;
+        expect(data.get("pong") == Some(&json!(true)), "ping did not answer pong")?;
+        let caps = request(&state, "get_capabilities", "", json!({})).await?;
+        expect(caps.get("operators").is_some_and(Value::is_object), "get_capabilities has no operators")?;
+        Ok("ping and capabilities answered".to_string())
+    })
+    .await);
+
+    steps.push(step(&task, "echo", || async {
+        let params = json!({"text": "Blendmate ✓ ěščř", "number": 1.5, "nested": {"list": [1, null, true]}});
+        let data = request(&state, "echo", "objects['Cube']", params.clone()).await?;
+        expect(data.get("params") == Some(&params), "echoed params differ")?;
+        expect(data.get("target") == Some(&json!("objects['Cube']")), "echoed target differs")?;
+        Ok("params round-tripped unchanged".to_string())
+    })
+    .await);
+
+    steps.push(step(&task, "operator run", || async {
+        let params = json!({"type": "DRAW", "iterations": 1});
+        let data = request(&state, "operator.call", "wm.redraw_timer", params).await?;
+        let result = data.get("result").and_then(Value::as_str).unwrap_or_default();
+        expect(result.contains("FINISHED"), &format!("operator returned {}", result))?;
+        let unknown = request(&state, "operator.call", "object.blendmate_missing", json!({})).await;
+        expect(unknown.is_err(), "unknown operator was not reported")?;
+        Ok("operator ran, unknown operator rejected".to_string())
+    })
+    .await);
+
+    steps.push(step(&task, "exec refused", || async {
+        let result = request(&state, "operator.call", "script.python_file_run", json!({"filepath": ""})).await;
+        match result {
+            Err(error) => Ok(format!("refused: {}", error)),
+            Ok(_) => Err("script execution was allowed".to_string()),
+        }
+    })
+    .await);
+
+    steps.push(step(&task, "large payload", || async {
+        let text = "x".repeat(LARGE_PAYLOAD_BYTES);
+        let data = request(&state, "echo", "", json!({"text": text})).await?;
+        let len = data.pointer("/params/text").and_then(Value::as_str).map_or(0, str::len);
+        expect(len == LARGE_PAYLOAD_BYTES, &format!("echoed {} of {} bytes", len, LARGE_PAYLOAD_BYTES))?;
+        Ok(format!("{} KiB round-tripped", LARGE_PAYLOAD_BYTES / 1024))
+    })
+    .await);
+
+    steps.push(step(&task, "unknown action", || async {
+        match request(&state, "blendmate.no_such_action", "", json!({})).await {
+            Err(error) => Ok(format!("rejected: {}", error)),
+            Ok(_) => Err("unknown action succeeded".to_string()),
+        }
+    })
+    .await);
+
+    steps.push(step(&task, "malformed frame", || async {
+        state.bridge.send_raw("{\"type\": \"request\", not json".to_string()).await?;
+        request(&state, "ping", "", json!({}))
+            .await
+            .map_err(|e| format!("add-on stopped answering: {}", e))?;
+        Ok("ignored; still answering".to_string())
+    })
+    .await);
+
+    let result = if task.is_cancelled() {
+        Err(CommandError::Cancelled("Conformance run was cancelled".to_string()))
+    } else {
+        Ok(ConformanceReport {
+            session: peer,
+            passed: steps.iter().all(|step| step.passed),
+            steps,
+            started_at,
+        })
+    };
+    task.finish(&result);
+    result
+}
+
+async fn request(state: &AppState, action: &str, target: &str, params: Value) -> Result<Value, String> {
+    state
+        .bridge
+        .request_with_timeout(action, target, params, STEP_TIMEOUT)
+        .await
+        .map_err(String::from)
+}
+
+fn expect(condition: bool, message: &str) -> Result<(), String> {
+    if condition {
+        Ok(())
+    } else {
+        Err(message.to_string())
+    }
+}
+
+/// Run one step, or skip it once the task is cancelled
+async fn step<R, F, Fut>(task: &TaskHandle<R>, name: &str, run: F) -> ConformanceStep
+where
+    R: Runtime,
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<String, String>>,
+{
+    let started = Instant::now();
+    let result = if task.is_cancelled() {
+        Err("Cancelled".to_string())
+    } else {
+        task.progress(None, Some(format!("Running '{}'", name)));
+        run().await
+    };
+    ConformanceStep {
+        name: name.to_string(),
+        passed: result.is_ok(),
+        message: result.unwrap_or_else(|error| error),
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
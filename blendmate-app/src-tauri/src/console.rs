@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::AppState;
+
+/// Lines kept per session; the oldest are dropped
+const HISTORY_LIMIT: usize = 500;
+
+/// A line can loop or bake; it holds the mutation queue for this long at most
+const EXEC_TIMEOUT: Duration = Duration::from_secs(60);
+
+const DEFAULT_SESSION: &str = "default";
+
+/// One line run in a console session
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConsoleEntry {
+    pub line: String,
+    /// `repr` of an expression's value; unset for statements and for
+    /// expressions that are `None`
+    pub result: Option<String>,
+    /// What the line printed
+    pub output: String,
+    /// Traceback when the line raised
+    pub error: Option<String>,
+    pub at: String,
+    pub duration_ms: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConsoleHistory {
+    pub session: String,
+    /// Oldest first
+    pub entries: Vec<ConsoleEntry>,
+    /// Distinct lines for up-arrow recall, newest first
+    pub recall: Vec<String>,
+}
+
+/// Lines run per console session. Blender keeps each session's names
+/// between lines; the history is kept here, in memory.
+#[derive(Default)]
+pub struct ConsoleState {
+    sessions: Mutex<HashMap<String, VecDeque<ConsoleEntry>>>,
+}
+
+impl ConsoleState {
+    fn push(&self, session: &str, entry: ConsoleEntry) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let entries = sessions.entry(session.to_string()).or_default();
+        entries.push_back(entry);
+        while entries.len() > HISTORY_LIMIT {
+            entries.pop_front();
+        }
+    }
+}
+
+fn session_name(session: Option<String>) -> String {
+    session.filter(|name| !name.is_empty()).unwrap_or_else(|| DEFAULT_SESSION.to_string())
+}
+
+/// Run one line of Python in Blender, the way its Python console would:
+/// an expression returns its `repr`, and names defined by earlier lines of
+/// the session are still there. Errors raised by the line come back in the
+/// entry rather than as a failure.
+#[tauri::command]
+pub async fn console_exec(
+    line: String,
+    session: Option<String>,
+    state: State<'_, AppState>,
+    console: State<'_, ConsoleState>,
+) -> Result<ConsoleEntry, CommandError> {
+    let session = session_name(session);
+    let started = Instant::now();
+    let reply = state
+        .bridge
+        .request_with_timeout("console.exec", "", json!({ "line": line, "session": session }), EXEC_TIMEOUT)
+        .await?;
+    let text = |key: &str| reply.get(key).and_then(Value::as_str).map(str::to_string);
+    let entry = ConsoleEntry {
+        result: text("result"),
+        output: text("output").unwrap_or_default(),
+        error: text("error"),
+        at: chrono::Local::now().to_rfc3339(),
+        duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+        line,
+    };
+    if !entry.line.trim().is_empty() {
+        console.push(&session, entry.clone());
+    }
+    Ok(entry)
+}
+
+/// History of a console session. With `prefix`, recall holds only the lines
+/// starting with it, as when typing before pressing the up arrow.
+#[tauri::command]
+pub fn get_console_history(
+    session: Option<String>,
+    prefix: Option<String>,
+    console: State<'_, ConsoleState>,
+) -> ConsoleHistory {
+    let session = session_name(session);
+    let entries: Vec<ConsoleEntry> = console
+        .sessions
+        .lock()
+        .unwrap()
+        .get(&session)
+        .map(|entries| entries.iter().cloned().collect())
+        .unwrap_or_default();
+    let prefix = prefix.unwrap_or_default();
+    let mut recall: Vec<String> = Vec::new();
+    for entry in entries.iter().rev() {
+        if entry.line.starts_with(&prefix) && !recall.contains(&entry.line) {
+            recall.push(entry.line.clone());
+        }
+    }
+    ConsoleHistory {
+        session,
+        entries,
+        recall,
+    }
+}
+
+/// Forget a session's history and, with `names`, the names it defined in
+/// Blender
+#[tauri::command]
+pub async fn clear_console(
+    session: Option<String>,
+    names: Option<bool>,
+    state: State<'_, AppState>,
+    console: State<'_, ConsoleState>,
+) -> Result<(), CommandError> {
+    let session = session_name(session);
+    console.sessions.lock().unwrap().remove(&session);
+    if names.unwrap_or(false) {
+        state.bridge.request("console.reset", "", json!({ "session": session })).await?;
+    }
+    Ok(())
+}
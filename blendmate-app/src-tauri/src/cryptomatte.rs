@@ -0,0 +1,297 @@
+use base64::Engine;
+use openexr::prelude::{read_all_flat_layers_from_file, FlatSamples};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::errors::CommandError;
+use crate::exr::{self as header, CryptomatteLayer};
+
+const DEFAULT_WIDTH: u32 = 1280;
+
+/// Highlight blended over the picked object
+const HIGHLIGHT: [f32; 3] = [1.0, 0.55, 0.1];
+
+/// How much of the highlight shows through at full coverage
+const HIGHLIGHT_OPACITY: f32 = 0.5;
+
+/// Brightness kept outside the matte, so the object stands out
+const SURROUNDING: f32 = 0.45;
+
+/// A preview with one object highlighted through its cryptomatte
+#[derive(Serialize, Clone, Debug)]
+pub struct MatteOverlay {
+    pub path: String,
+    pub frame: Option<i64>,
+    pub object: String,
+    /// Cryptomatte layer the matte came from, e.g. `ViewLayer.CryptoObject`
+    pub layer: String,
+    /// Base64 JPEG
+    pub image: String,
+    pub width: u32,
+    pub height: u32,
+    /// Share of the frame the object covers, 0 to 1
+    pub coverage: f64,
+    /// Bounds of the matte in frame pixels as `[x, y, width, height]`;
+    /// `None` when the object isn't visible in this frame
+    pub bounds: Option<[u32; 4]>,
+}
+
+/// MurmurHash3 (32 bit, seed 0), which cryptomatte hashes names with
+fn murmur3(bytes: &[u8]) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let mix = |k: u32| k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    let mut hash = 0u32;
+    let mut chunks = bytes.chunks_exact(4);
+    for chunk in &mut chunks {
+        hash ^= mix(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
+        hash = hash.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let k = tail.iter().enumerate().fold(0u32, |k, (i, byte)| k | u32::from(*byte) << (8 * i));
+        hash ^= mix(k);
+    }
+    hash ^= bytes.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
+
+/// The float id of `name`: its hash with an exponent that is neither zero
+/// nor all ones, so it's never a denormal, infinity or NaN
+fn matte_id(name: &str) -> f32 {
+    let mut hash = murmur3(name.as_bytes());
+    let exponent = (hash >> 23) & 0xff;
+    if exponent == 0 || exponent == 0xff {
+        hash ^= 1 << 23;
+    }
+    f32::from_bits(hash)
+}
+
+/// The cryptomatte layer `wanted` names, by full name or pass name such as
+/// `CryptoMaterial`; object mattes by default
+fn pick_layer<'a>(layers: &'a [CryptomatteLayer], wanted: Option<&str>) -> Option<&'a CryptomatteLayer> {
+    let name = |layer: &&CryptomatteLayer| layer.name.clone().unwrap_or_default();
+    match wanted {
+        Some(wanted) => layers.iter().find(|layer| {
+            let name = name(layer);
+            name == wanted || name.rsplit('.').next() == Some(wanted)
+        }),
+        None => layers.iter().find(|layer| name(layer).ends_with("CryptoObject")).or(layers.first()),
+    }
+}
+
+/// The id of `object` in `layer`, from the manifest when the file has one
+fn object_id(path: &Path, layer: &CryptomatteLayer, object: &str) -> Result<f32, CommandError> {
+    let manifest = header::cryptomatte_manifest(path, &layer.key)?;
+    if manifest.is_empty() {
+        return Ok(matte_id(object));
+    }
+    let hash = manifest.get(object).and_then(|hash| hash.as_str()).ok_or_else(|| {
+        CommandError::NotFound(format!(
+            "'{}' is not in cryptomatte layer {}",
+            object,
+            layer.name.as_deref().unwrap_or(&layer.key)
+        ))
+    })?;
+    u32::from_str_radix(hash, 16)
+        .map(f32::from_bits)
+        .map_err(|_| CommandError::InvalidInput(format!("Invalid cryptomatte hash '{}' for '{}'", hash, object)))
+}
+
+fn floats(samples: &FlatSamples) -> Cow<'_, [f32]> {
+    match samples {
+        FlatSamples::F32(values) => Cow::Borrowed(values),
+        other => Cow::Owned(other.values_as_f32().collect()),
+    }
+}
+
+fn srgb(linear: f32) -> f32 {
+    let linear = linear.clamp(0.0, 1.0);
+    if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Coverage of `id` per pixel, summed over the ranks of `layer`
+fn matte(channels: &HashMap<String, &FlatSamples>, layer: &str, id: f32, pixels: usize) -> Vec<f32> {
+    let mut matte = vec![0.0f32; pixels];
+    for rank in 0.. {
+        let channel = |suffix: &str| channels.get(&format!("{}{:02}.{}", layer, rank, suffix)).map(|s| floats(s));
+        let (Some(r), Some(g), Some(b), Some(a)) = (channel("R"), channel("G"), channel("B"), channel("A")) else {
+            break;
+        };
+        for (ids, coverage) in [(r, g), (b, a)] {
+            for (pixel, (sample, amount)) in ids.iter().zip(coverage.iter()).enumerate().take(pixels) {
+                if sample.to_bits() == id.to_bits() {
+                    matte[pixel] += amount;
+                }
+            }
+        }
+    }
+    matte.iter_mut().for_each(|value| *value = value.clamp(0.0, 1.0));
+    matte
+}
+
+/// Highlight `object` on a frame of an EXR with cryptomatte passes. The
+/// overlay goes on `preview`, a JPEG of the same shot scaled to any size,
+/// or on the frame's own combined pass.
+fn composite(
+    path: &Path,
+    object: &str,
+    layer: Option<&str>,
+    preview: Option<&Path>,
+    width: u32,
+) -> Result<MatteOverlay, CommandError> {
+    let metadata = header::read(path)?;
+    let crypto = pick_layer(&metadata.cryptomatte, layer).ok_or_else(|| {
+        CommandError::NotFound(format!("{} has no cryptomatte layer {}", path.display(), layer.unwrap_or_default()))
+    })?;
+    let crypto_name = crypto.name.clone().unwrap_or_else(|| crypto.key.clone());
+    let id = object_id(path, crypto, object)?;
+
+    let pixels = read_all_flat_layers_from_file(path)
+        .map_err(|e| CommandError::InvalidInput(format!("Can't read pixels of {}: {}", path.display(), e)))?;
+    let part = pixels
+        .layer_data
+        .iter()
+        .find(|part| {
+            let prefix = part.attributes.layer_name.as_ref().map(|name| format!("{}.", name)).unwrap_or_default();
+            part.channel_data.list.iter().any(|channel| {
+                format!("{}{}", prefix, channel.name).starts_with(&format!("{}00.", crypto_name))
+            })
+        })
+        .ok_or_else(|| CommandError::NotFound(format!("{} has no channels for {}", path.display(), crypto_name)))?;
+    let prefix = part.attributes.layer_name.as_ref().map(|name| format!("{}.", name)).unwrap_or_default();
+    let channels: HashMap<String, &FlatSamples> = part
+        .channel_data
+        .list
+        .iter()
+        .map(|channel| (format!("{}{}", prefix, channel.name), &channel.sample_data))
+        .collect();
+    let (frame_width, frame_height) = (part.size.0, part.size.1);
+    let matte = matte(&channels, &crypto_name, id, frame_width * frame_height);
+
+    let mut covered = 0.0f64;
+    let mut bounds: Option<[usize; 4]> = None;
+    for (pixel, value) in matte.iter().enumerate().filter(|(_, value)| **value > 0.0) {
+        covered += f64::from(*value);
+        let (x, y) = (pixel % frame_width, pixel / frame_width);
+        let found = bounds.get_or_insert([x, y, x, y]);
+        *found = [found[0].min(x), found[1].min(y), found[2].max(x), found[3].max(y)];
+    }
+
+    let base = match preview {
+        Some(preview) => image::open(preview)
+            .map_err(|e| CommandError::InvalidInput(format!("Can't read preview {}: {}", preview.display(), e)))?
+            .to_rgb8(),
+        None => {
+            // The combined pass of the view layer the cryptomatte belongs to
+            let view = crypto_name.rsplit_once('.').map(|(view, _)| format!("{}.Combined.", view));
+            let rgb: Vec<Option<Cow<[f32]>>> = ["R", "G", "B"]
+                .iter()
+                .map(|c| {
+                    view.as_ref()
+                        .and_then(|view| channels.get(&format!("{}{}", view, c)))
+                        .or_else(|| channels.get(*c))
+                        .map(|samples| floats(samples))
+                })
+                .collect();
+            image::RgbImage::from_fn(frame_width as u32, frame_height as u32, |x, y| {
+                let pixel = y as usize * frame_width + x as usize;
+                image::Rgb([0, 1, 2].map(|c| {
+                    let value = rgb[c].as_ref().and_then(|values| values.get(pixel)).copied().unwrap_or_default();
+                    (srgb(value) * 255.0).round() as u8
+                }))
+            })
+        }
+    };
+
+    let (base_width, base_height) = base.dimensions();
+    let overlay = image::RgbImage::from_fn(base_width, base_height, |x, y| {
+        let matte_x = (x as usize * frame_width / base_width.max(1) as usize).min(frame_width.saturating_sub(1));
+        let matte_y = (y as usize * frame_height / base_height.max(1) as usize).min(frame_height.saturating_sub(1));
+        let amount = matte.get(matte_y * frame_width + matte_x).copied().unwrap_or_default();
+        let source = base.get_pixel(x, y).0;
+        image::Rgb([0, 1, 2].map(|c| {
+            let value = f32::from(source[c]) / 255.0;
+            let highlighted = value + (HIGHLIGHT[c] - value) * HIGHLIGHT_OPACITY;
+            let dimmed = value * SURROUNDING;
+            ((dimmed + (highlighted - dimmed) * amount) * 255.0).round() as u8
+        }))
+    });
+    let overlay = image::DynamicImage::ImageRgb8(overlay);
+    let overlay = if base_width > width {
+        overlay.resize(width, u32::MAX, image::imageops::FilterType::Triangle)
+    } else {
+        overlay
+    };
+    let mut bytes = Vec::new();
+    overlay
+        .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Jpeg)
+        .map_err(|e| CommandError::Failed(format!("Failed to encode overlay: {}", e)))?;
+
+    Ok(MatteOverlay {
+        path: metadata.path,
+        frame: metadata.frame,
+        object: object.to_string(),
+        layer: crypto_name,
+        image: base64::engine::general_purpose::STANDARD.encode(bytes),
+        width: overlay.width(),
+        height: overlay.height(),
+        coverage: covered / (frame_width * frame_height).max(1) as f64,
+        bounds: bounds.map(|[x0, y0, x1, y1]| [x0, y0, x1 - x0 + 1, y1 - y0 + 1].map(|value| value as u32)),
+    })
+}
+
+/// Names that can be picked in a frame's cryptomatte layer, from its
+/// manifest; empty when the manifest is in a sidecar file
+#[tauri::command]
+pub async fn list_matte_names(path: String, layer: Option<String>) -> Result<Vec<String>, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&path);
+        let metadata = header::read(path)?;
+        let Some(crypto) = pick_layer(&metadata.cryptomatte, layer.as_deref()) else {
+            return Ok(Vec::new());
+        };
+        let manifest = header::cryptomatte_manifest(path, &crypto.key)?;
+        let mut names: Vec<String> = manifest.into_iter().map(|(name, _)| name).collect();
+        names.sort();
+        Ok(names)
+    })
+    .await
+    .map_err(|e| CommandError::Failed(format!("Cryptomatte read failed: {}", e)))?
+}
+
+/// A review preview of an EXR frame with `object` highlighted, so a note
+/// can be matched to what it's about. `layer` picks a cryptomatte layer
+/// such as `CryptoMaterial` instead of object mattes; `preview` overlays a
+/// JPEG of the frame instead of the EXR's combined pass.
+#[tauri::command]
+pub async fn composite_matte_preview(
+    path: String,
+    object: String,
+    layer: Option<String>,
+    preview: Option<String>,
+    width: Option<u32>,
+) -> Result<MatteOverlay, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        composite(
+            Path::new(&path),
+            &object,
+            layer.as_deref(),
+            preview.as_deref().map(Path::new),
+            width.unwrap_or(DEFAULT_WIDTH).max(1),
+        )
+    })
+    .await
+    .map_err(|e| CommandError::Failed(format!("Cryptomatte composite failed: {}", e)))?
+}
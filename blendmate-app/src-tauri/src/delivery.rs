@@ -0,0 +1,548 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::a11y::{self, Politeness};
+use crate::errors::CommandError;
+use crate::ffmpeg;
+use crate::i18n;
+use crate::postprocess::{self, PostOperation};
+use crate::projects::{self, ProjectProfile};
+use crate::settings::SettingsStore;
+
+/// How often the watched folder is scanned
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// A delivery is complete once nothing new has landed for this long, so a
+/// render writing a frame every few seconds makes one delivery
+const QUIET_PERIOD: Duration = Duration::from_secs(60);
+
+/// Written to the output folder, next to the derivatives
+const MANIFEST: &str = "manifest.json";
+
+/// Output folder inside the watched one when none is set; it isn't watched
+const DEFAULT_OUTPUT: &str = "web";
+
+const FRAME_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "exr", "tif", "tiff", "webp", "bmp", "tga"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "mkv", "webm", "avi"];
+
+/// What to make of each render
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DeliveryPreset {
+    /// Height limit of the H.264 proxy made of each video; 0 makes none
+    pub proxy_height: u32,
+    /// x264 quality of proxies, lower is better
+    pub proxy_crf: u32,
+    /// Width limit of stills made of frames, and of posters
+    pub still_width: u32,
+    /// `jpg`, `webp` or `png`
+    pub still_format: String,
+    /// Second of each video its poster frame is taken from; unset makes none
+    pub poster_at_secs: Option<f64>,
+}
+
+impl Default for DeliveryPreset {
+    fn default() -> Self {
+        Self {
+            proxy_height: 1080,
+            proxy_crf: 23,
+            still_width: 1920,
+            still_format: "jpg".to_string(),
+            poster_at_secs: Some(1.0),
+        }
+    }
+}
+
+/// Delivery setup of a project, kept in its profile. Paths go through the
+/// project's path mappings, so one profile works on every platform.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeliveryConfig {
+    /// Folder renders land in, watched with its subfolders
+    pub watch_dir: String,
+    /// Where derivatives and the manifest go; defaults to `web` inside the
+    /// watched folder
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    #[serde(default)]
+    pub preset: DeliveryPreset,
+    /// Run on each landed frame, in order, before its derivatives are
+    /// made; videos are delivered as they land
+    #[serde(default)]
+    pub post_process: Vec<PostOperation>,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    Frame,
+    Video,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivativeKind {
+    /// The frame after post-processing, in its own format
+    Processed,
+    Proxy,
+    Still,
+    Poster,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Derivative {
+    pub kind: DerivativeKind,
+    /// Relative to the output folder, `/` separated
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeliveryEntry {
+    /// Relative to the watched folder, `/` separated
+    pub source: String,
+    pub kind: SourceKind,
+    /// Size and modification time (Unix seconds) the source was processed
+    /// at; a source that changes is processed again
+    pub size: u64,
+    pub modified: u64,
+    pub derivatives: Vec<Derivative>,
+    pub processed_at: String,
+    pub error: Option<String>,
+}
+
+/// `manifest.json` of an output folder, by source
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct DeliveryManifest {
+    pub project: String,
+    pub updated_at: String,
+    pub entries: BTreeMap<String, DeliveryEntry>,
+}
+
+/// Emitted as `delivery:complete` once a delivery has gone quiet
+#[derive(Serialize, Clone, Debug)]
+pub struct DeliverySummary {
+    pub project: String,
+    pub output_dir: String,
+    pub manifest: String,
+    pub delivered: usize,
+    pub failed: usize,
+}
+
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct DeliveryStatus {
+    /// Project being watched; `None` while the active project has no
+    /// delivery set up or it is paused
+    pub project: Option<String>,
+    pub watch_dir: Option<String>,
+    pub output_dir: Option<String>,
+    /// Renders that landed and aren't processed yet
+    pub pending: usize,
+    /// Source being processed
+    pub processing: Option<String>,
+    /// Sources processed in the delivery under way
+    pub delivered: usize,
+    pub failed: usize,
+    /// Why the folder can't be watched, e.g. it doesn't exist
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct DeliveryState {
+    status: Mutex<DeliveryStatus>,
+    /// Set by `retry_delivery`; failed sources are processed again
+    retry: AtomicBool,
+}
+
+impl DeliveryState {
+    fn report<R: Runtime>(&self, app: &AppHandle<R>, change: impl FnOnce(&mut DeliveryStatus)) {
+        let status = {
+            let mut status = self.status.lock().unwrap();
+            change(&mut status);
+            status.clone()
+        };
+        if let Err(err) = app.emit("delivery:status", &status) {
+            eprintln!("Failed to emit delivery:status: {err}");
+        }
+    }
+}
+
+/// Folders of the active project's delivery on this machine
+#[derive(Clone, PartialEq)]
+struct Target {
+    project: String,
+    watch_dir: PathBuf,
+    output_dir: PathBuf,
+}
+
+fn target(profile: &ProjectProfile, config: &DeliveryConfig) -> Target {
+    let watch_dir = PathBuf::from(profile.map_path(&config.watch_dir));
+    let output_dir = match &config.output_dir {
+        Some(dir) => PathBuf::from(profile.map_path(dir)),
+        None => watch_dir.join(DEFAULT_OUTPUT),
+    };
+    Target {
+        project: profile.name.clone(),
+        watch_dir,
+        output_dir,
+    }
+}
+
+/// The active project's delivery, unless it has none or it is paused
+fn active(settings: &SettingsStore) -> Option<(Target, DeliveryPreset, Vec<PostOperation>)> {
+    let profile = projects::active(settings)?;
+    let config = profile.delivery.clone().filter(|config| !config.paused)?;
+    let post_process = config
+        .post_process
+        .iter()
+        .map(|operation| operation.with_paths(|path| profile.map_path(path)))
+        .collect();
+    Some((target(&profile, &config), config.preset, post_process))
+}
+
+fn kind_of(path: &Path) -> Option<SourceKind> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    if FRAME_EXTENSIONS.contains(&extension.as_str()) {
+        Some(SourceKind::Frame)
+    } else if VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+        Some(SourceKind::Video)
+    } else {
+        None
+    }
+}
+
+/// Renders below `dir` by relative path, with their size and modification
+/// time. The output folder and hidden files are skipped.
+fn scan(dir: &Path, output: &Path) -> HashMap<PathBuf, (u64, u64)> {
+    let mut files = HashMap::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let Ok(entries) = fs::read_dir(dir.join(&relative)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = relative.join(entry.file_name());
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                if entry.path() != output {
+                    pending.push(path);
+                }
+            } else if metadata.len() > 0 && kind_of(&path).is_some() {
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |since| since.as_secs());
+                files.insert(path, (metadata.len(), modified));
+            }
+        }
+    }
+    files
+}
+
+fn slashed(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// `relative` with its extension replaced by `suffix`, e.g.
+/// `shot/frame.0001.exr` to `shot/frame.0001.jpg`
+fn derived(relative: &Path, suffix: &str) -> PathBuf {
+    let stem = relative.file_stem().unwrap_or_default().to_string_lossy();
+    relative.with_file_name(format!("{}{}", stem, suffix))
+}
+
+pub fn load_manifest(output_dir: &Path) -> DeliveryManifest {
+    fs::read_to_string(output_dir.join(MANIFEST))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(output_dir: &Path, manifest: &DeliveryManifest) -> Result<(), CommandError> {
+    let text = serde_json::to_string_pretty(manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    let path = output_dir.join(MANIFEST);
+    fs::write(&path, text).map_err(|e| CommandError::Io(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Make the derivatives of one render into the output folder. Frames are
+/// post-processed first and their stills made of the processed frame.
+async fn derive<R: Runtime>(
+    app: &AppHandle<R>,
+    target: &Target,
+    preset: &DeliveryPreset,
+    post_process: &[PostOperation],
+    relative: &Path,
+    kind: SourceKind,
+) -> Result<Vec<Derivative>, CommandError> {
+    let settings = app.state::<SettingsStore>();
+    let format = preset.still_format.trim_start_matches('.').to_lowercase();
+    if !matches!(format.as_str(), "jpg" | "jpeg" | "webp" | "png") {
+        return Err(CommandError::InvalidInput(format!("Unsupported still format '{}'", format)));
+    }
+    let input = target.watch_dir.join(relative);
+    if let Some(parent) = target.output_dir.join(relative).parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| CommandError::Io(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+
+    let mut made = Vec::new();
+    match kind {
+        SourceKind::Frame => {
+            let mut frame = input;
+            if !post_process.is_empty() {
+                let extension = relative.extension().unwrap_or_default().to_string_lossy();
+                let processed = derived(relative, &format!(".post.{}", extension));
+                let output = target.output_dir.join(&processed);
+                postprocess::process(app, &slashed(relative), post_process, &frame, &output).await?;
+                made.push((DerivativeKind::Processed, processed));
+                frame = output;
+            }
+            let still = derived(relative, &format!(".{}", format));
+            ffmpeg::extract_still(&settings, &frame, None, preset.still_width, &target.output_dir.join(&still)).await?;
+            made.push((DerivativeKind::Still, still));
+        }
+        SourceKind::Video => {
+            if preset.proxy_height > 0 {
+                let proxy = derived(relative, ".proxy.mp4");
+                let output = target.output_dir.join(&proxy);
+                ffmpeg::encode_proxy(&settings, &input, preset.proxy_height, preset.proxy_crf, &output).await?;
+                made.push((DerivativeKind::Proxy, proxy));
+            }
+            if let Some(at) = preset.poster_at_secs {
+                let poster = derived(relative, &format!(".poster.{}", format));
+                let output = target.output_dir.join(&poster);
+                // Videos shorter than the poster time get their first frame
+                let result = ffmpeg::extract_still(&settings, &input, Some(at), preset.still_width, &output).await;
+                if result.is_err() && at > 0.0 {
+                    ffmpeg::extract_still(&settings, &input, Some(0.0), preset.still_width, &output).await?;
+                } else {
+                    result?;
+                }
+                made.push((DerivativeKind::Poster, poster));
+            }
+        }
+    }
+
+    Ok(made
+        .into_iter()
+        .map(|(kind, path)| Derivative {
+            kind,
+            bytes: fs::metadata(target.output_dir.join(&path)).map_or(0, |m| m.len()),
+            path: slashed(&path),
+        })
+        .collect())
+}
+
+fn emit<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, event: &str, payload: &S) {
+    if let Err(err) = app.emit(event, payload) {
+        eprintln!("Failed to emit {event}: {err}");
+    }
+}
+
+fn complete<R: Runtime>(app: &AppHandle<R>, target: &Target, delivered: usize, failed: usize) {
+    let summary = DeliverySummary {
+        project: target.project.clone(),
+        output_dir: target.output_dir.display().to_string(),
+        manifest: target.output_dir.join(MANIFEST).display().to_string(),
+        delivered,
+        failed,
+    };
+    emit(app, "delivery:complete", &summary);
+    let project = target.project.as_str().into();
+    if failed > 0 {
+        let text = i18n::t("a11y-delivery-failed", &[("project", project), ("failed", failed.into())]);
+        a11y::announce(app, "delivery", Politeness::Assertive, text);
+    } else {
+        let text = i18n::t("a11y-delivery-done", &[("project", project), ("count", delivered.into())]);
+        a11y::announce(app, "delivery", Politeness::Polite, text);
+    }
+}
+
+/// Watch the active project's delivery folder. Renders are processed once
+/// their size and time stop changing between two scans, and again when
+/// they change. Emits `delivery:status` as it goes, `delivery:processed`
+/// with the manifest entry of each render and `delivery:complete` once
+/// nothing new has landed for a minute.
+pub fn start_watcher<R: Runtime>(app: AppHandle<R>) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let delivery = app.state::<DeliveryState>();
+        let mut watched: Option<Target> = None;
+        let mut manifest = DeliveryManifest::default();
+        let mut previous: HashMap<PathBuf, (u64, u64)> = HashMap::new();
+        let mut last_activity = Instant::now();
+        let (mut delivered, mut failed) = (0, 0);
+
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let settings = app.state::<SettingsStore>();
+            let current = active(&settings);
+
+            if current.as_ref().map(|(target, _, _)| target) != watched.as_ref() {
+                if let Some(target) = watched.as_ref().filter(|_| delivered + failed > 0) {
+                    complete(&app, target, delivered, failed);
+                }
+                watched = current.as_ref().map(|(target, _, _)| target.clone());
+                manifest = watched.as_ref().map(|target| load_manifest(&target.output_dir)).unwrap_or_default();
+                previous.clear();
+                (delivered, failed) = (0, 0);
+                delivery.report(&app, |status| {
+                    *status = DeliveryStatus {
+                        project: watched.as_ref().map(|target| target.project.clone()),
+                        watch_dir: watched.as_ref().map(|target| target.watch_dir.display().to_string()),
+                        output_dir: watched.as_ref().map(|target| target.output_dir.display().to_string()),
+                        ..DeliveryStatus::default()
+                    }
+                });
+            }
+            let Some((target, preset, post_process)) = current else {
+                continue;
+            };
+
+            if !target.watch_dir.is_dir() {
+                let error = format!("Folder not found: {}", target.watch_dir.display());
+                if delivery.status.lock().unwrap().error.as_ref() != Some(&error) {
+                    delivery.report(&app, |status| status.error = Some(error));
+                }
+                continue;
+            }
+            if let Err(e) = fs::create_dir_all(&target.output_dir) {
+                let error = format!("Failed to create {}: {}", target.output_dir.display(), e);
+                if delivery.status.lock().unwrap().error.as_ref() != Some(&error) {
+                    delivery.report(&app, |status| status.error = Some(error));
+                }
+                continue;
+            }
+            if delivery.retry.swap(false, Ordering::Relaxed) {
+                manifest.entries.retain(|_, entry| entry.error.is_none());
+            }
+
+            let files = scan(&target.watch_dir, &target.output_dir);
+            let mut waiting: Vec<&PathBuf> = files
+                .iter()
+                .filter(|(path, (size, modified))| {
+                    manifest
+                        .entries
+                        .get(&slashed(path))
+                        .is_none_or(|entry| entry.size != *size || entry.modified != *modified)
+                })
+                .map(|(path, _)| path)
+                .collect();
+            waiting.sort();
+            if !waiting.is_empty() {
+                last_activity = Instant::now();
+            }
+            let pending = waiting.len();
+            let reported = {
+                let status = delivery.status.lock().unwrap();
+                status.pending == pending && status.error.is_none()
+            };
+            if !reported {
+                delivery.report(&app, |status| {
+                    status.pending = pending;
+                    status.error = None;
+                });
+            }
+
+            // Files still being written show a new size or time on every scan
+            let settled: Vec<PathBuf> = waiting
+                .into_iter()
+                .filter(|path| previous.get(*path) == files.get(*path))
+                .cloned()
+                .collect();
+            for (index, relative) in settled.iter().enumerate() {
+                let Some(kind) = kind_of(relative) else {
+                    continue;
+                };
+                let source = slashed(relative);
+                delivery.report(&app, |status| status.processing = Some(source.clone()));
+                let result = derive(&app, &target, &preset, &post_process, relative, kind).await;
+                let (size, modified) = files[relative];
+                let entry = DeliveryEntry {
+                    source: source.clone(),
+                    kind,
+                    size,
+                    modified,
+                    processed_at: chrono::Local::now().to_rfc3339(),
+                    error: result.as_ref().err().map(ToString::to_string),
+                    derivatives: result.unwrap_or_default(),
+                };
+                if entry.error.is_some() {
+                    failed += 1;
+                } else {
+                    delivered += 1;
+                }
+                emit(&app, "delivery:processed", &entry);
+                manifest.entries.insert(source, entry);
+                manifest.project = target.project.clone();
+                manifest.updated_at = chrono::Local::now().to_rfc3339();
+                if let Err(err) = save_manifest(&target.output_dir, &manifest) {
+                    eprintln!("Failed to save delivery manifest: {err}");
+                }
+                delivery.report(&app, |status| {
+                    status.processing = None;
+                    status.pending = pending - index - 1;
+                    status.delivered = delivered;
+                    status.failed = failed;
+                });
+                last_activity = Instant::now();
+            }
+            previous = files;
+
+            if delivered + failed > 0 && last_activity.elapsed() >= QUIET_PERIOD {
+                complete(&app, &target, delivered, failed);
+                (delivered, failed) = (0, 0);
+                delivery.report(&app, |status| {
+                    status.delivered = 0;
+                    status.failed = 0;
+                });
+            }
+        }
+    })
+}
+
+#[tauri::command]
+pub fn get_delivery_status(delivery: State<'_, DeliveryState>) -> DeliveryStatus {
+    delivery.status.lock().unwrap().clone()
+}
+
+/// Manifest of a project's delivery, by default the active project's
+#[tauri::command]
+pub fn get_delivery_manifest(
+    project: Option<String>,
+    settings: State<'_, SettingsStore>,
+) -> Result<DeliveryManifest, CommandError> {
+    let name = projects::key(&settings, project);
+    let profile = settings
+        .get()
+        .projects
+        .profiles
+        .into_iter()
+        .find(|profile| profile.name == name)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown project '{}'", name)))?;
+    let config = profile
+        .delivery
+        .as_ref()
+        .ok_or_else(|| CommandError::NotFound(format!("Project '{}' has no delivery set up", name)))?;
+    Ok(load_manifest(&target(&profile, config).output_dir))
+}
+
+/// Process the renders that failed again on the next scan, e.g. once
+/// ffmpeg is installed
+#[tauri::command]
+pub fn retry_delivery(delivery: State<'_, DeliveryState>) {
+    delivery.retry.store(true, Ordering::Relaxed);
+}
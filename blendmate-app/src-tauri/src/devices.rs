@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Cycles device setup. User profiles are stored in settings and take
+/// precedence over built-in profiles with the same name.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeviceProfile {
+    pub name: String,
+    /// `CUDA`, `OPTIX`, `HIP`, `METAL`, `ONEAPI` or `NONE`; `None` keeps
+    /// whatever Blender has selected
+    #[serde(default)]
+    pub compute_device_type: Option<String>,
+    #[serde(default)]
+    pub use_cpu: bool,
+    /// GPUs to enable by position in Blender's device list; `None` = all
+    #[serde(default)]
+    pub gpus: Option<Vec<usize>>,
+}
+
+fn builtin_profiles() -> Vec<DeviceProfile> {
+    let profile = |name: &str, use_cpu: bool, gpus: Option<Vec<usize>>| DeviceProfile {
+        name: name.to_string(),
+        compute_device_type: None,
+        use_cpu,
+        gpus,
+    };
+
+    vec![
+        profile("gpu_only", false, None),
+        profile("cpu_gpu", true, None),
+        profile("second_gpu_only", false, Some(vec![1])),
+        profile("cpu_only", true, Some(Vec::new())),
+    ]
+}
+
+fn all_profiles(settings: &SettingsStore) -> Vec<DeviceProfile> {
+    let mut profiles = settings.get().device_profiles;
+    for builtin in builtin_profiles() {
+        if !profiles.iter().any(|p| p.name == builtin.name) {
+            profiles.push(builtin);
+        }
+    }
+    profiles
+}
+
+/// Look up a device profile by name (for live sessions and job launches)
+pub fn find_profile(settings: &SettingsStore, name: &str) -> Result<DeviceProfile, CommandError> {
+    all_profiles(settings)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown device profile '{}'", name)))
+}
+
+/// Cycles compute device type and devices of the connected Blender
+#[tauri::command]
+pub async fn get_render_devices(state: State<'_, AppState>) -> Result<Value, CommandError> {
+    state.bridge.request("render.get_devices", "", json!({})).await
+}
+
+/// Apply device settings to the connected Blender
+#[tauri::command]
+pub async fn set_render_devices(
+    devices: DeviceProfile,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<Value, CommandError> {
+    let params = serde_json::to_value(&devices).map_err(|e| e.to_string())?;
+    let result = state.bridge.request("render.set_devices", "", params.clone()).await?;
+    history.record(&settings, "render.set_devices", "", &params);
+    Ok(result)
+}
+
+/// Apply a saved device profile to the connected Blender
+#[tauri::command]
+pub async fn apply_device_profile(
+    name: String,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<Value, CommandError> {
+    let profile = find_profile(&settings, &name)?;
+    set_render_devices(profile, state, settings, history).await
+}
+
+/// List built-in and user device profiles
+#[tauri::command]
+pub fn list_device_profiles(settings: State<'_, SettingsStore>) -> Vec<DeviceProfile> {
+    all_profiles(&settings)
+}
+
+/// Create or replace a user device profile
+#[tauri::command]
+pub fn save_device_profile(profile: DeviceProfile, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    if profile.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Profile name is required".to_string()));
+    }
+    settings.update(|s| {
+        s.device_profiles.retain(|p| p.name != profile.name);
+        s.device_profiles.push(profile);
+    })?;
+    Ok(())
+}
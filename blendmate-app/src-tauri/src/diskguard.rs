@@ -0,0 +1,398 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::a11y::{self, Politeness};
+use crate::caches::CacheState;
+use crate::errors::CommandError;
+use crate::i18n;
+use crate::journal::JobRequest;
+use crate::render_queue::{self, RenderRequest};
+use crate::settings::SettingsStore;
+use crate::workers::WorkerPool;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Warn when a volume is predicted to fill within this long at the rate its
+/// free space is dropping
+const WARN_WITHIN: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DiskGuardSettings {
+    /// Check free space and pause jobs at all
+    pub enabled: bool,
+    /// Free space to keep on every watched volume, in MiB
+    pub reserve_mb: u64,
+    /// Pause once the space above the reserve holds fewer than this many
+    /// more frames of a running render
+    pub headroom_frames: u64,
+    /// Watched besides render outputs and the open file's caches
+    pub paths: Vec<String>,
+    pub check_interval_secs: u64,
+}
+
+impl Default for DiskGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reserve_mb: 2048,
+            headroom_frames: 10,
+            paths: Vec::new(),
+            check_interval_secs: 15,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct VolumeStatus {
+    /// First watched directory on the volume
+    pub path: String,
+    /// What writes there: job ids, `cache` or `watched`
+    pub users: Vec<String>,
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+    /// Largest average frame size of the render jobs on the volume
+    pub frame_bytes: Option<u64>,
+    /// What unfinished render jobs are expected to write yet
+    pub needed_bytes: u64,
+    /// Frames that still fit above the reserve
+    pub frames_left: Option<u64>,
+    /// At the rate free space dropped since the last check
+    pub secs_to_full: Option<f64>,
+    pub problem: Option<String>,
+    /// The problem holds the worker pool
+    pub pauses_jobs: bool,
+}
+
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct DiskStatus {
+    pub volumes: Vec<VolumeStatus>,
+    pub jobs_paused: bool,
+    pub checked_at: Option<String>,
+}
+
+#[derive(Default)]
+pub struct DiskGuard {
+    status: Mutex<DiskStatus>,
+}
+
+impl DiskGuard {
+    pub fn current(&self) -> DiskStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// Something that writes to a directory
+struct Writer {
+    label: String,
+    directory: PathBuf,
+    is_job: bool,
+    /// Sizes of the frames rendered so far
+    frame_sizes: Vec<u64>,
+    /// Frames still to render
+    remaining: u64,
+}
+
+fn average(sizes: &[u64]) -> Option<u64> {
+    (!sizes.is_empty()).then(|| sizes.iter().sum::<u64>() / sizes.len() as u64)
+}
+
+fn megabytes(bytes: u64) -> String {
+    if bytes >= 1024 * MIB {
+        format!("{:.1} GiB", bytes as f64 / (1024 * MIB) as f64)
+    } else {
+        format!("{} MiB", bytes / MIB)
+    }
+}
+
+fn below_reserve(available: u64, path: &str, reserve: u64) -> String {
+    i18n::t(
+        "disk-below-reserve",
+        &[
+            ("free", megabytes(available).into()),
+            ("path", path.into()),
+            ("reserve", megabytes(reserve).into()),
+        ],
+    )
+}
+
+/// Nearest existing directory at or above `path`, which is on the volume
+/// the path will be written to
+fn existing_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|dir| dir.is_dir()).map(Path::to_path_buf)
+}
+
+/// Where a render writes: its output override, resolved against the file
+/// for `//` paths, else next to the file until frames report their paths
+fn output_dir(request: &RenderRequest) -> PathBuf {
+    let file_dir = Path::new(&request.filepath).parent().unwrap_or(Path::new("")).to_path_buf();
+    match request.output.as_deref() {
+        Some(output) => match output.strip_prefix("//") {
+            Some(relative) => file_dir.join(relative),
+            None => PathBuf::from(output),
+        },
+        None => file_dir,
+    }
+}
+
+/// Identifies the volume a directory is on
+#[cfg(unix)]
+fn volume_id(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|metadata| metadata.dev().to_string())
+}
+
+/// The drive or UNC share prefix
+#[cfg(not(unix))]
+fn volume_id(path: &Path) -> Option<String> {
+    path.components()
+        .next()
+        .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+}
+
+fn render_writer(id: &str, request: &RenderRequest, outputs: &[String]) -> Writer {
+    let frame_sizes: Vec<u64> = outputs
+        .iter()
+        .filter_map(|output| fs::metadata(output).ok())
+        .map(|metadata| metadata.len())
+        .collect();
+    let directory = outputs
+        .last()
+        .and_then(|output| Path::new(output).parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| output_dir(request));
+    Writer {
+        label: id.to_string(),
+        directory,
+        is_job: true,
+        frame_sizes,
+        remaining: render_queue::frames(request).len().saturating_sub(outputs.len()) as u64,
+    }
+}
+
+/// Refuse a render whose output volume is already below the reserve
+pub fn require_space(config: &DiskGuardSettings, request: &RenderRequest) -> Result<(), CommandError> {
+    if !config.enabled {
+        return Ok(());
+    }
+    let Some(directory) = existing_dir(&output_dir(request)) else {
+        return Ok(());
+    };
+    let available = fs2::available_space(&directory)
+        .map_err(|e| CommandError::Io(format!("Failed to read free space on {}: {}", directory.display(), e)))?;
+    let reserve = config.reserve_mb * MIB;
+    if available < reserve {
+        return Err(CommandError::InvalidInput(below_reserve(
+            available,
+            &directory.display().to_string(),
+            reserve,
+        )));
+    }
+    Ok(())
+}
+
+/// Group writers by volume and judge each one. While `paused`, a volume
+/// needs twice the frame headroom to release the pool, so it doesn't
+/// flip back and forth around the threshold.
+fn check(
+    config: &DiskGuardSettings,
+    writers: Vec<Writer>,
+    paused: bool,
+    last: &mut HashMap<String, (Instant, u64)>,
+) -> Vec<VolumeStatus> {
+    let mut volumes: BTreeMap<String, Vec<Writer>> = BTreeMap::new();
+    for writer in writers {
+        let Some(directory) = existing_dir(&writer.directory) else {
+            continue;
+        };
+        let Some(id) = volume_id(&directory) else {
+            continue;
+        };
+        volumes.entry(id).or_default().push(Writer { directory, ..writer });
+    }
+
+    let reserve = config.reserve_mb * MIB;
+    let headroom = if paused { config.headroom_frames * 2 } else { config.headroom_frames };
+    let now = Instant::now();
+    let mut seen = HashMap::new();
+    let mut statuses = Vec::new();
+    for (id, writers) in volumes {
+        let directory = &writers[0].directory;
+        let (Ok(available), Ok(total)) = (fs2::available_space(directory), fs2::total_space(directory)) else {
+            continue;
+        };
+        let secs_to_full = last.get(&id).and_then(|(at, before)| {
+            let rate = before.saturating_sub(available) as f64 / now.duration_since(*at).as_secs_f64();
+            (rate > 0.0).then(|| available as f64 / rate)
+        });
+        seen.insert(id, (now, available));
+
+        // Each job's own average, falling back to the volume's for jobs
+        // that haven't written a frame yet
+        let all: Vec<u64> = writers.iter().flat_map(|w| w.frame_sizes.iter().copied()).collect();
+        let volume_average = average(&all);
+        let frame_bytes = writers.iter().filter_map(|w| average(&w.frame_sizes)).max();
+        let needed_bytes = writers
+            .iter()
+            .map(|w| average(&w.frame_sizes).or(volume_average).unwrap_or(0) * w.remaining)
+            .sum();
+        let rendering = writers.iter().any(|w| w.remaining > 0);
+        let above_reserve = available.saturating_sub(reserve);
+        let frames_left = frame_bytes.filter(|_| rendering).map(|size| above_reserve / size.max(1));
+        let has_jobs = writers.iter().any(|w| w.is_job);
+        let path = directory.display().to_string();
+
+        let (problem, pauses_jobs) = if available < reserve {
+            (Some(below_reserve(available, &path, reserve)), has_jobs)
+        } else if let Some(frames) = frames_left.filter(|frames| *frames < headroom) {
+            let problem = i18n::t("disk-frames-left", &[("frames", frames.into()), ("path", path.as_str().into())]);
+            (Some(problem), true)
+        } else if needed_bytes > above_reserve {
+            let problem = i18n::t(
+                "disk-jobs-need",
+                &[
+                    ("needed", megabytes(needed_bytes).into()),
+                    ("path", path.as_str().into()),
+                    ("free", megabytes(available).into()),
+                ],
+            );
+            (Some(problem), false)
+        } else if let Some(secs) = secs_to_full.filter(|secs| *secs < WARN_WITHIN.as_secs_f64()) {
+            let minutes = (secs / 60.0).round() as u64;
+            let problem = i18n::t("disk-fills-in", &[("path", path.as_str().into()), ("minutes", minutes.into())]);
+            (Some(problem), false)
+        } else {
+            (None, false)
+        };
+
+        statuses.push(VolumeStatus {
+            path,
+            users: writers.iter().map(|w| w.label.clone()).collect(),
+            available_bytes: available,
+            total_bytes: total,
+            frame_bytes,
+            needed_bytes,
+            frames_left,
+            secs_to_full,
+            problem,
+            pauses_jobs,
+        });
+    }
+    *last = seen;
+    statuses
+}
+
+/// Check the volumes unfinished render jobs and the open file's caches
+/// write to every `check_interval_secs`. Emits `disk:status` after every
+/// check, and `disk:low` when the worker pool is paused to keep a volume
+/// from filling.
+pub fn start_monitor<R: Runtime>(app: AppHandle<R>) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let mut paused = false;
+        let mut last = HashMap::new();
+
+        loop {
+            let config = app.state::<SettingsStore>().get().disk_guard;
+            let volumes = if config.enabled {
+                let renders: Vec<(String, RenderRequest, Vec<String>)> = app
+                    .state::<WorkerPool>()
+                    .active()
+                    .await
+                    .into_iter()
+                    .filter_map(|(job, request)| match request {
+                        JobRequest::Render(request) => {
+                            let outputs = job.items.iter().flat_map(|item| item.outputs.clone()).collect();
+                            Some((job.id, request, outputs))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let caches = app.state::<CacheState>().directories().await;
+                let config = config.clone();
+                let mut previous = std::mem::take(&mut last);
+                let result = tauri::async_runtime::spawn_blocking(move || {
+                    let mut writers: Vec<Writer> = renders
+                        .iter()
+                        .map(|(id, request, outputs)| render_writer(id, request, outputs))
+                        .collect();
+                    for (label, directories) in [("cache", caches), ("watched", config.paths.clone())] {
+                        writers.extend(directories.into_iter().map(|directory| Writer {
+                            label: label.to_string(),
+                            directory: PathBuf::from(directory),
+                            is_job: false,
+                            frame_sizes: Vec::new(),
+                            remaining: 0,
+                        }));
+                    }
+                    let volumes = check(&config, writers, paused, &mut previous);
+                    (volumes, previous)
+                })
+                .await;
+                match result {
+                    Ok((volumes, previous)) => {
+                        last = previous;
+                        volumes
+                    }
+                    Err(err) => {
+                        eprintln!("Disk space check failed: {err}");
+                        Vec::new()
+                    }
+                }
+            } else {
+                Vec::new()
+            };
+
+            let was_paused = paused;
+            paused = volumes.iter().any(|volume| volume.pauses_jobs);
+            if paused != was_paused {
+                app.state::<WorkerPool>().set_paused("disk", paused);
+            }
+
+            let status = DiskStatus {
+                volumes,
+                jobs_paused: paused,
+                checked_at: Some(chrono::Local::now().to_rfc3339()),
+            };
+            *app.state::<DiskGuard>().status.lock().unwrap() = status.clone();
+            if paused && !was_paused {
+                if let Err(err) = app.emit("disk:low", &status) {
+                    eprintln!("Failed to emit disk:low: {err}");
+                }
+                let problem = status.volumes.iter().find(|v| v.pauses_jobs).and_then(|v| v.problem.clone());
+                let text = i18n::t("a11y-disk-low", &[("problem", problem.unwrap_or_default().into())]);
+                a11y::announce(&app, "disk", Politeness::Assertive, text);
+            }
+            if let Err(err) = app.emit("disk:status", &status) {
+                eprintln!("Failed to emit disk:status: {err}");
+            }
+
+            tokio::time::sleep(Duration::from_secs(config.check_interval_secs.max(1))).await;
+        }
+    })
+}
+
+/// Free space on watched volumes as of the last check
+#[tauri::command]
+pub fn get_disk_status(guard: State<'_, DiskGuard>) -> DiskStatus {
+    guard.current()
+}
+
+#[tauri::command]
+pub fn get_disk_guard_settings(settings: State<'_, SettingsStore>) -> DiskGuardSettings {
+    settings.get().disk_guard
+}
+
+/// Takes effect at the next check
+#[tauri::command]
+pub fn set_disk_guard_settings(
+    disk_guard: DiskGuardSettings,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), CommandError> {
+    settings.update(|s| s.disk_guard = disk_guard)?;
+    Ok(())
+}
@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::process::Command;
+use tokio::sync::watch;
+
+use crate::errors::CommandError;
+use crate::notes::NoteStore;
+use crate::safemode;
+use crate::settings::SettingsStore;
+use crate::workers;
+use crate::AppState;
+
+/// How often open files are checked for saves
+const POLL_INTERVAL: Duration = Duration::from_millis(750);
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Editor program, e.g. `code`. Unset uses `$VISUAL` or `$EDITOR`, and
+    /// then the system's default app for the file type. Terminal editors
+    /// need a terminal program here, e.g. `kitty` with `nvim {file}`.
+    pub program: Option<String>,
+    /// `{file}` is replaced by the file to edit; it is appended when no
+    /// argument has it
+    pub args: Vec<String>,
+}
+
+/// What to edit
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EditTarget {
+    /// A file on disk, opened where it is
+    Path { path: String },
+    /// A text datablock of the open .blend
+    Text { name: String },
+    /// A note from the notes store
+    Note { name: String },
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EditSession {
+    pub id: String,
+    pub target: EditTarget,
+    /// File the editor has open
+    pub file: String,
+    pub started_at: String,
+    /// When a save was last synced back
+    pub synced_at: Option<String>,
+    /// Why the last save couldn't be synced; it is retried until it is
+    pub error: Option<String>,
+}
+
+struct OpenFile {
+    session: EditSession,
+    stop: watch::Sender<bool>,
+}
+
+/// Text datablocks and notes open in an external editor, each a temp file
+/// watched for saves
+#[derive(Default)]
+pub struct EditorState {
+    open: Mutex<HashMap<String, OpenFile>>,
+    next_id: AtomicU64,
+}
+
+impl EditorState {
+    fn report<R: Runtime>(&self, app: &AppHandle<R>, id: &str, change: impl FnOnce(&mut EditSession)) {
+        let session = {
+            let mut open = self.open.lock().unwrap();
+            let Some(file) = open.get_mut(id) else {
+                return;
+            };
+            change(&mut file.session);
+            file.session.clone()
+        };
+        if let Err(err) = app.emit("editor:synced", &session) {
+            eprintln!("Failed to emit editor:synced: {err}");
+        }
+    }
+}
+
+/// File name for a datablock or note name, keeping its extension
+fn file_name(id: &str, name: &str, default_extension: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    if Path::new(&safe).extension().is_some() {
+        format!("{}-{}", id, safe)
+    } else {
+        format!("{}-{}.{}", id, safe, default_extension)
+    }
+}
+
+/// Start the configured editor on `file` without waiting for it
+fn launch(config: &EditorSettings, file: &Path) -> Result<(), CommandError> {
+    let (program, mut args) = match config.program.clone().filter(|program| !program.is_empty()) {
+        Some(program) => (program, config.args.clone()),
+        None => {
+            // `$EDITOR` may carry its own arguments, e.g. `code --wait`
+            let Some(editor) = std::env::var("VISUAL").or_else(|_| std::env::var("EDITOR")).ok() else {
+                return tauri_plugin_opener::open_path(file, None::<&str>)
+                    .map_err(|e| CommandError::Failed(format!("Failed to open {}: {}", file.display(), e)));
+            };
+            let mut words = editor.split_whitespace().map(str::to_string);
+            let program = words.next().unwrap_or_default();
+            (program, words.chain(config.args.iter().cloned()).collect())
+        }
+    };
+
+    let path = file.to_string_lossy();
+    if !args.iter().any(|arg| arg.contains("{file}")) {
+        args.push("{file}".to_string());
+    }
+    let args: Vec<String> = args.iter().map(|arg| arg.replace("{file}", &path)).collect();
+    let mut child = Command::new(&program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| CommandError::Failed(format!("Failed to start '{}': {}", program, e)))?;
+    // Reaped in the background; editors that fork exit straight away
+    tauri::async_runtime::spawn(async move {
+        let _ = child.wait().await;
+    });
+    Ok(())
+}
+
+/// Content of a text datablock or note, to write into the temp file
+async fn read_target<R: Runtime>(app: &AppHandle<R>, target: &EditTarget) -> Result<String, CommandError> {
+    match target {
+        EditTarget::Path { .. } => Ok(String::new()),
+        EditTarget::Text { name } => {
+            let state = app.state::<AppState>();
+            let text = state.bridge.request("text.get", "", json!({ "name": name })).await?;
+            Ok(text.get("content").and_then(Value::as_str).unwrap_or_default().to_string())
+        }
+        EditTarget::Note { name } => {
+            // A note that doesn't exist yet starts empty and is created on the first save
+            let notes = safemode::subsystem::<NoteStore, R>(app, "notes")?;
+            Ok(notes.get(name).map(|note| note.text).unwrap_or_default())
+        }
+    }
+}
+
+async fn write_target<R: Runtime>(app: &AppHandle<R>, target: &EditTarget, content: String) -> Result<(), CommandError> {
+    match target {
+        EditTarget::Path { .. } => Ok(()),
+        EditTarget::Text { name } => {
+            let state = app.state::<AppState>();
+            if state.ws_sender.lock().await.is_none() {
+                return Err(CommandError::not_connected());
+            }
+            state
+                .bridge
+                .request("text.set", "", json!({ "name": name, "content": content }))
+                .await
+                .map(|_| ())
+        }
+        EditTarget::Note { name } => {
+            let notes = safemode::subsystem::<NoteStore, R>(app, "notes")?;
+            notes.save(name, content).map(|_| ())
+        }
+    }
+}
+
+/// Poll the temp file and sync each save back once the file has settled.
+/// A failed sync is retried on the next poll, e.g. once Blender connects.
+async fn watch_file<R: Runtime>(app: AppHandle<R>, id: String, file: PathBuf, mut stopped: watch::Receiver<bool>) {
+    let editor = app.state::<EditorState>();
+    let modified = |file: &Path| fs::metadata(file).and_then(|m| m.modified()).ok();
+    let mut synced: Option<SystemTime> = modified(&file);
+    let mut previous = synced;
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = workers::cancelled(&mut stopped) => break,
+        }
+
+        // Editors often write a file more than once; wait until a poll sees no change
+        let current = modified(&file);
+        if current != previous {
+            previous = current;
+            continue;
+        }
+        if current.is_none() || current == synced {
+            continue;
+        }
+
+        let Some(target) = editor.open.lock().unwrap().get(&id).map(|open| open.session.target.clone()) else {
+            break;
+        };
+        let result = match fs::read_to_string(&file) {
+            Ok(content) => write_target(&app, &target, content).await,
+            Err(e) => Err(CommandError::Io(format!("Failed to read {}: {}", file.display(), e))),
+        };
+        match result {
+            Ok(()) => {
+                synced = current;
+                editor.report(&app, &id, |session| {
+                    session.synced_at = Some(chrono::Local::now().to_rfc3339());
+                    session.error = None;
+                });
+            }
+            Err(error) => {
+                let error = error.to_string();
+                let changed = editor
+                    .open
+                    .lock()
+                    .unwrap()
+                    .get(&id)
+                    .is_some_and(|open| open.session.error.as_ref() != Some(&error));
+                if changed {
+                    editor.report(&app, &id, |session| session.error = Some(error));
+                }
+            }
+        }
+    }
+}
+
+/// Open a file, text datablock or note in the configured editor. Text
+/// datablocks and notes are written to a temp file, and every save of it
+/// is synced back until `close_in_editor`. Opening the same one again
+/// brings up its file without exporting it again.
+#[tauri::command]
+pub async fn open_in_editor<R: Runtime>(
+    target: EditTarget,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    editor: State<'_, EditorState>,
+) -> Result<EditSession, CommandError> {
+    let config = settings.get().editor;
+    if let EditTarget::Path { path } = &target {
+        if !Path::new(path).is_file() {
+            return Err(CommandError::NotFound(format!("File not found: {}", path)));
+        }
+        launch(&config, Path::new(path))?;
+        return Ok(EditSession {
+            id: String::new(),
+            target: target.clone(),
+            file: path.clone(),
+            started_at: chrono::Local::now().to_rfc3339(),
+            synced_at: None,
+            error: None,
+        });
+    }
+
+    let existing = editor
+        .open
+        .lock()
+        .unwrap()
+        .values()
+        .find(|open| open.session.target == target)
+        .map(|open| open.session.clone());
+    if let Some(session) = existing {
+        launch(&config, Path::new(&session.file))?;
+        return Ok(session);
+    }
+
+    let content = read_target(&app, &target).await?;
+    let id = format!("edit-{}", editor.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+    let name = match &target {
+        EditTarget::Text { name } => file_name(&id, name, "py"),
+        EditTarget::Note { name } => file_name(&id, name, "md"),
+        EditTarget::Path { .. } => unreachable!("paths are opened above"),
+    };
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| CommandError::Io(format!("Failed to find the cache directory: {}", e)))?
+        .join("editor");
+    fs::create_dir_all(&dir).map_err(|e| CommandError::Io(format!("Failed to create {}: {}", dir.display(), e)))?;
+    let file = dir.join(name);
+    fs::write(&file, content).map_err(|e| CommandError::Io(format!("Failed to write {}: {}", file.display(), e)))?;
+    launch(&config, &file)?;
+
+    let session = EditSession {
+        id: id.clone(),
+        target,
+        file: file.display().to_string(),
+        started_at: chrono::Local::now().to_rfc3339(),
+        synced_at: None,
+        error: None,
+    };
+    let (stop, stopped) = watch::channel(false);
+    editor.open.lock().unwrap().insert(
+        id.clone(),
+        OpenFile {
+            session: session.clone(),
+            stop,
+        },
+    );
+    tauri::async_runtime::spawn(watch_file(app.clone(), id, file, stopped));
+    Ok(session)
+}
+
+#[tauri::command]
+pub fn list_editor_sessions(editor: State<'_, EditorState>) -> Vec<EditSession> {
+    editor.open.lock().unwrap().values().map(|open| open.session.clone()).collect()
+}
+
+/// Stop syncing an edit session and delete its temp file. Saves after the
+/// last poll are not synced.
+#[tauri::command]
+pub fn close_in_editor(id: String, editor: State<'_, EditorState>) -> Result<(), CommandError> {
+    let open = editor
+        .open
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .ok_or_else(|| CommandError::NotFound(format!("No edit session {}", id)))?;
+    open.stop.send_replace(true);
+    if let Err(err) = fs::remove_file(&open.session.file) {
+        eprintln!("Failed to remove {}: {err}", open.session.file);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_editor_settings(settings: State<'_, SettingsStore>) -> EditorSettings {
+    settings.get().editor
+}
+
+#[tauri::command]
+pub fn set_editor_settings(editor: EditorSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.editor = editor)?;
+    Ok(())
+}
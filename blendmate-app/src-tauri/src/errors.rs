@@ -0,0 +1,123 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+use crate::i18n;
+
+/// Error returned by every Tauri command.
+///
+/// The frontend receives `{code, message, details, retryable}`; `code` is
+/// stable and listed in the command manifest, `message` is for display.
+#[derive(Debug, Clone)]
+pub enum CommandError {
+    /// Blender is not connected, or disconnected before answering
+    NotConnected(String),
+    /// Blender did not answer in time
+    Timeout(String),
+    /// The add-on handled the request and reported an error. `details` is
+    /// its error object when it sent one.
+    Blender { message: String, details: Option<Value> },
+    /// Arguments the command cannot act on
+    InvalidInput(String),
+    NotFound(String),
+    /// Reading or writing a file failed
+    Io(String),
+    /// Not available in this build or configuration
+    Unsupported(String),
+    /// Stopped through `cancel_task`
+    Cancelled(String),
+    Failed(String),
+}
+
+/// Every `code` a `CommandError` can carry
+pub const ERROR_CODES: &[&str] = &[
+    "not_connected",
+    "timeout",
+    "blender",
+    "invalid_input",
+    "not_found",
+    "io",
+    "unsupported",
+    "cancelled",
+    "failed",
+];
+
+impl CommandError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::NotConnected(_) => "not_connected",
+            CommandError::Timeout(_) => "timeout",
+            CommandError::Blender { .. } => "blender",
+            CommandError::InvalidInput(_) => "invalid_input",
+            CommandError::NotFound(_) => "not_found",
+            CommandError::Io(_) => "io",
+            CommandError::Unsupported(_) => "unsupported",
+            CommandError::Cancelled(_) => "cancelled",
+            CommandError::Failed(_) => "failed",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CommandError::NotConnected(message)
+            | CommandError::Timeout(message)
+            | CommandError::Blender { message, .. }
+            | CommandError::InvalidInput(message)
+            | CommandError::NotFound(message)
+            | CommandError::Io(message)
+            | CommandError::Unsupported(message)
+            | CommandError::Cancelled(message)
+            | CommandError::Failed(message) => message,
+        }
+    }
+
+    pub fn details(&self) -> Option<&Value> {
+        match self {
+            CommandError::Blender { details, .. } => details.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the same call can succeed when repeated unchanged, e.g.
+    /// after Blender reconnects
+    pub fn retryable(&self) -> bool {
+        matches!(self, CommandError::NotConnected(_) | CommandError::Timeout(_))
+    }
+
+    pub fn not_connected() -> Self {
+        CommandError::NotConnected(i18n::t("blender-not-connected", &[]))
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("CommandError", 4)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", self.message())?;
+        error.serialize_field("details", &self.details())?;
+        error.serialize_field("retryable", &self.retryable())?;
+        error.end()
+    }
+}
+
+/// Internal helpers report plain messages; they surface as `failed`
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Failed(message)
+    }
+}
+
+/// Lets helpers that report plain messages call into the bridge with `?`
+impl From<CommandError> for String {
+    fn from(error: CommandError) -> Self {
+        error.message().to_string()
+    }
+}
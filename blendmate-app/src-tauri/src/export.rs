@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use crate::errors::CommandError;
+use crate::journal::JobRequest;
+use crate::settings::SettingsStore;
+use crate::validation;
+use crate::workers::{self, Job, WorkItem, WorkerPool};
+use crate::AppState;
+
+const EXPORT_SCRIPT: &str = include_str!("../scripts/export.py");
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Fbx,
+    Gltf,
+    Usd,
+    Obj,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportSource {
+    pub filepath: String,
+    /// Export these collections one file each; empty exports the whole scene
+    #[serde(default)]
+    pub collections: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportRequest {
+    pub sources: Vec<ExportSource>,
+    pub format: ExportFormat,
+    /// Extra keyword arguments for the exporter operator
+    #[serde(default)]
+    pub options: Map<String, Value>,
+    pub output_dir: String,
+    /// Validate the open file first when it is one of the sources
+    pub validation_profile: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ManifestEntry<'a> {
+    source: &'a str,
+    status: workers::JobStatus,
+    outputs: &'a [String],
+    errors: &'a [String],
+}
+
+/// Queue exports of one or more .blend files on the worker pool.
+///
+/// Returns the job id; progress arrives as `job:progress` and an
+/// `export-<timestamp>.json` manifest is written next to the exports.
+#[tauri::command]
+pub async fn start_export_job(
+    request: ExportRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+) -> Result<String, CommandError> {
+    let id = submit_export(&state, &settings, &pool, &request).await?;
+    workers::spawn_job(app, id.clone(), move |_, job| {
+        finish_export(&request, job);
+        None
+    });
+    Ok(id)
+}
+
+/// Validate and queue an export job without running it.
+pub async fn submit_export(
+    state: &AppState,
+    settings: &SettingsStore,
+    pool: &WorkerPool,
+    request: &ExportRequest,
+) -> Result<String, CommandError> {
+    if request.sources.is_empty() {
+        return Err(CommandError::InvalidInput("No files selected for export".to_string()));
+    }
+    fs::create_dir_all(&request.output_dir)
+        .map_err(|e| CommandError::Io(format!("Failed to create output directory: {}", e)))?;
+
+    if let Some(profile) = &request.validation_profile {
+        let files: Vec<&str> = request.sources.iter().map(|s| s.filepath.as_str()).collect();
+        validation::require_passing_if_open(state, settings, profile, &files).await?;
+    }
+
+    pool.submit(JobRequest::Export(request.clone()), settings).await
+}
+
+/// One item per source file
+pub fn work(request: &ExportRequest) -> Vec<WorkItem> {
+    let names = output_names(&request.sources);
+    request
+        .sources
+        .iter()
+        .zip(names)
+        .map(|(source, name)| WorkItem {
+            label: source.filepath.clone(),
+            blend_file: Some(PathBuf::from(&source.filepath)),
+            script: EXPORT_SCRIPT,
+            args: json!({
+                "format": request.format,
+                "options": request.options,
+                "output_dir": request.output_dir,
+                "collections": source.collections,
+                "name": name,
+            }),
+            devices: None,
+            priority: 0,
+            env: BTreeMap::new(),
+        })
+        .collect()
+}
+
+/// The name each source's exports start with: its file stem, with a
+/// `_2`, `_3`, ... suffix when an earlier source has the same stem, so
+/// `a/scene.blend` and `b/scene.blend` don't overwrite each other. Compared
+/// without case, as the output directory may be on a case-insensitive disk.
+fn output_names(sources: &[ExportSource]) -> Vec<String> {
+    let mut taken = HashSet::new();
+    sources
+        .iter()
+        .map(|source| {
+            let stem = Path::new(&source.filepath)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .filter(|stem| !stem.is_empty())
+                .unwrap_or_else(|| "untitled".to_string());
+            let mut name = stem.clone();
+            let mut counter = 2;
+            while !taken.insert(name.to_lowercase()) {
+                name = format!("{}_{}", stem, counter);
+                counter += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+/// Write the manifest of a finished export job.
+pub fn finish_export(request: &ExportRequest, job: &Job) {
+    if let Err(err) = write_manifest(Path::new(&request.output_dir), request.format, job) {
+        eprintln!("Failed to write export manifest: {err}");
+    }
+}
+
+fn write_manifest(output_dir: &Path, format: ExportFormat, job: &Job) -> Result<(), String> {
+    let files: Vec<ManifestEntry> = job
+        .items
+        .iter()
+        .map(|item| ManifestEntry {
+            source: &item.label,
+            status: item.status,
+            outputs: &item.outputs,
+            errors: &item.errors,
+        })
+        .collect();
+
+    let manifest = json!({
+        "job": job.id,
+        "format": format,
+        "status": job.status,
+        "created_at": job.created_at,
+        "finished_at": job.finished_at,
+        "files": files,
+    });
+    let name = format!("export-{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let text = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::write(output_dir.join(name), text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(filepath: &str) -> ExportSource {
+        ExportSource {
+            filepath: filepath.to_string(),
+            collections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn output_names_differ_for_sources_with_the_same_stem() {
+        let sources = [
+            source("/shots/a/scene.blend"),
+            source("/shots/b/scene.blend"),
+            source("/shots/c/Scene.blend"),
+            source("/shots/prop.blend"),
+        ];
+        assert_eq!(output_names(&sources), ["scene", "scene_2", "Scene_3", "prop"]);
+    }
+}
@@ -0,0 +1,493 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::storage::SharedStorage;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+
+/// Version flag of files with several parts, each with its own header
+const MULTIPART: u32 = 0x1000;
+
+/// Attribute and type names are at most this long, even with long names
+const MAX_NAME: usize = 256;
+
+/// Largest attribute read; cryptomatte manifests can be megabytes
+const MAX_ATTRIBUTE: usize = 64 * 1024 * 1024;
+
+const COMPRESSION: [&str; 10] = ["none", "rle", "zips", "zip", "piz", "pxr24", "b44", "b44a", "dwaa", "dwab"];
+
+const PIXEL_TYPES: [&str; 3] = ["uint", "half", "float"];
+
+/// A render pass and its channels, e.g. `Normal` with `X`, `Y` and `Z`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExrPass {
+    pub name: String,
+    pub channels: Vec<String>,
+    /// `half`, `float` or `uint`, of the first channel
+    pub pixel_type: String,
+}
+
+/// Passes of one view layer. Single-layer files have one unnamed layer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExrLayer {
+    pub name: String,
+    pub passes: Vec<ExrPass>,
+}
+
+/// A cryptomatte layer as declared in the header
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CryptomatteLayer {
+    /// Seven-character key of its `cryptomatte/<key>/…` attributes
+    pub key: String,
+    pub name: Option<String>,
+    pub hash: Option<String>,
+    pub conversion: Option<String>,
+    /// Names in the manifest
+    pub manifest_entries: usize,
+}
+
+/// What the header of a rendered EXR says
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExrMetadata {
+    pub path: String,
+    pub frame: Option<i64>,
+    pub size_bytes: u64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub compression: Option<String>,
+    pub layers: Vec<ExrLayer>,
+    /// Blender's `RenderTime` stamp, e.g. `00:12.34`
+    pub render_time: Option<String>,
+    /// `cycles.*` attributes, such as samples and timings per view layer
+    pub cycles: BTreeMap<String, String>,
+    pub cryptomatte: Vec<CryptomatteLayer>,
+    /// Denoising normal and albedo passes are there
+    pub denoising_data: bool,
+    /// Other attributes with a type that has a JSON form
+    pub attributes: BTreeMap<String, Value>,
+    pub read_at: String,
+}
+
+struct Header {
+    reader: BufReader<File>,
+}
+
+impl Header {
+    fn byte(&mut self) -> std::io::Result<u8> {
+        let mut byte = [0u8; 1];
+        self.reader.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn bytes(&mut self, count: usize) -> std::io::Result<Vec<u8>> {
+        let mut bytes = vec![0u8; count];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// A null-terminated name; empty at the end of a header
+    fn name(&mut self) -> Result<String, String> {
+        let mut name = Vec::new();
+        loop {
+            match self.byte().map_err(|e| e.to_string())? {
+                0 => return Ok(String::from_utf8_lossy(&name).into_owned()),
+                byte if name.len() < MAX_NAME => name.push(byte),
+                _ => return Err("attribute name too long".to_string()),
+            }
+        }
+    }
+
+    /// The attributes of one header, by name, with their type
+    fn attributes(&mut self) -> Result<Vec<(String, String, Vec<u8>)>, String> {
+        let mut attributes = Vec::new();
+        loop {
+            let name = self.name()?;
+            if name.is_empty() {
+                return Ok(attributes);
+            }
+            let kind = self.name()?;
+            let size = i32::from_le_bytes(self.bytes(4).map_err(|e| e.to_string())?.try_into().unwrap_or_default());
+            let size = usize::try_from(size).ok().filter(|size| *size <= MAX_ATTRIBUTE);
+            let size = size.ok_or_else(|| format!("attribute '{}' has an invalid size", name))?;
+            attributes.push((name, kind, self.bytes(size).map_err(|e| e.to_string())?));
+        }
+    }
+}
+
+fn le_i32(bytes: &[u8], offset: usize) -> Option<i32> {
+    Some(i32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn le_f32(bytes: &[u8], offset: usize) -> Option<f32> {
+    Some(f32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// JSON form of the attribute types worth showing
+fn attribute_value(kind: &str, bytes: &[u8]) -> Option<Value> {
+    match kind {
+        "string" => Some(Value::String(text(bytes))),
+        "int" => le_i32(bytes, 0).map(Value::from),
+        "float" => le_f32(bytes, 0).map(Value::from),
+        "double" => Some(Value::from(f64::from_le_bytes(bytes.get(0..8)?.try_into().ok()?))),
+        "box2i" => Some(json!([le_i32(bytes, 0)?, le_i32(bytes, 4)?, le_i32(bytes, 8)?, le_i32(bytes, 12)?])),
+        "v2i" => Some(json!([le_i32(bytes, 0)?, le_i32(bytes, 4)?])),
+        "v2f" => Some(json!([le_f32(bytes, 0)?, le_f32(bytes, 4)?])),
+        "rational" => {
+            let denominator = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?);
+            (denominator != 0).then(|| Value::from(f64::from(le_i32(bytes, 0)?) / f64::from(denominator)))
+        }
+        "stringvector" => {
+            let mut strings = Vec::new();
+            let mut offset = 0;
+            while offset < bytes.len() {
+                let length = usize::try_from(le_i32(bytes, offset)?).ok()?;
+                strings.push(Value::String(text(bytes.get(offset + 4..offset + 4 + length)?)));
+                offset += 4 + length;
+            }
+            Some(Value::Array(strings))
+        }
+        _ => None,
+    }
+}
+
+/// Channel names and pixel types of a `chlist` attribute
+fn channels(bytes: &[u8]) -> Vec<(String, &'static str)> {
+    let mut channels = Vec::new();
+    let mut rest = bytes;
+    while let Some(end) = rest.iter().position(|byte| *byte == 0).filter(|end| *end > 0) {
+        let name = text(&rest[..end]);
+        // Pixel type, linear flag, three reserved bytes and the sampling
+        let Some(fields) = rest.get(end + 1..end + 17) else {
+            break;
+        };
+        let pixel_type = le_i32(fields, 0)
+            .and_then(|index| PIXEL_TYPES.get(usize::try_from(index).ok()?).copied())
+            .unwrap_or("unknown");
+        channels.push((name, pixel_type));
+        rest = &rest[end + 17..];
+    }
+    channels
+}
+
+/// Group `ViewLayer.Pass.Channel` names into layers and passes. Names
+/// without a layer, like the `R`, `G`, `B` of single-layer files, go to an
+/// unnamed layer and pass.
+fn group(channels: Vec<(String, &'static str)>) -> Vec<ExrLayer> {
+    let mut layers: Vec<ExrLayer> = Vec::new();
+    for (name, pixel_type) in channels {
+        let mut parts = name.rsplitn(3, '.');
+        let channel = parts.next().unwrap_or_default().to_string();
+        let pass = parts.next().unwrap_or_default().to_string();
+        let layer = parts.next().unwrap_or_default().to_string();
+        let index = match layers.iter().position(|known| known.name == layer) {
+            Some(index) => index,
+            None => {
+                layers.push(ExrLayer {
+                    name: layer,
+                    passes: Vec::new(),
+                });
+                layers.len() - 1
+            }
+        };
+        let passes = &mut layers[index].passes;
+        match passes.iter_mut().find(|known| known.name == pass) {
+            Some(known) => known.channels.push(channel),
+            None => passes.push(ExrPass {
+                name: pass,
+                channels: vec![channel],
+                pixel_type: pixel_type.to_string(),
+            }),
+        }
+    }
+    layers
+}
+
+/// Trailing digits of the file stem, as in `shot_0042.exr`
+fn frame_from_name(path: &Path) -> Option<i64> {
+    let stem = path.file_stem()?.to_str()?;
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    stem[stem.len() - digits..].parse().ok()
+}
+
+/// Attributes of every header of the EXR at `path`, and the file size
+fn read_attributes(path: &Path) -> Result<(Vec<(String, String, Vec<u8>)>, u64), CommandError> {
+    let invalid = |reason: String| {
+        CommandError::InvalidInput(format!("{} is not a valid OpenEXR file: {}", path.display(), reason))
+    };
+    let file = File::open(path).map_err(|e| CommandError::Io(format!("Failed to open {}: {}", path.display(), e)))?;
+    let size_bytes = file.metadata().map(|metadata| metadata.len()).unwrap_or_default();
+    let mut header = Header {
+        reader: BufReader::new(file),
+    };
+    let start = header.bytes(8).map_err(|e| invalid(e.to_string()))?;
+    if start[..4] != MAGIC {
+        return Err(invalid("wrong magic number".to_string()));
+    }
+    let flags = u32::from_le_bytes([start[4], start[5], start[6], start[7]]);
+
+    let mut attributes = Vec::new();
+    loop {
+        let part = header.attributes().map_err(invalid)?;
+        if part.is_empty() {
+            break;
+        }
+        attributes.extend(part);
+        if flags & MULTIPART == 0 {
+            break;
+        }
+    }
+    Ok((attributes, size_bytes))
+}
+
+/// Read the header of the EXR at `path`; pixels are not read
+pub fn read(path: &Path) -> Result<ExrMetadata, CommandError> {
+    let (attributes, size_bytes) = read_attributes(path)?;
+    let mut metadata = ExrMetadata {
+        path: path.to_string_lossy().into_owned(),
+        frame: None,
+        size_bytes,
+        width: None,
+        height: None,
+        compression: None,
+        layers: Vec::new(),
+        render_time: None,
+        cycles: BTreeMap::new(),
+        cryptomatte: Vec::new(),
+        denoising_data: false,
+        attributes: BTreeMap::new(),
+        read_at: chrono::Local::now().to_rfc3339(),
+    };
+    let mut all_channels = Vec::new();
+    let mut cryptomatte: BTreeMap<String, CryptomatteLayer> = BTreeMap::new();
+    for (name, kind, bytes) in attributes {
+        match (name.as_str(), kind.as_str()) {
+            ("channels", "chlist") => all_channels.extend(channels(&bytes)),
+            ("compression", _) => {
+                let compression = bytes.first().and_then(|index| COMPRESSION.get(usize::from(*index)));
+                metadata.compression = metadata.compression.or(compression.map(|name| name.to_string()));
+            }
+            ("dataWindow", "box2i") if metadata.width.is_none() => {
+                if let (Some(x_min), Some(y_min), Some(x_max), Some(y_max)) =
+                    (le_i32(&bytes, 0), le_i32(&bytes, 4), le_i32(&bytes, 8), le_i32(&bytes, 12))
+                {
+                    metadata.width = Some(x_max - x_min + 1);
+                    metadata.height = Some(y_max - y_min + 1);
+                }
+            }
+            ("RenderTime", "string") => metadata.render_time = Some(text(&bytes)),
+            ("Frame", "string") => metadata.frame = text(&bytes).trim().parse().ok(),
+            (cycles, "string") if cycles.starts_with("cycles.") => {
+                metadata.cycles.insert(name.clone(), text(&bytes));
+            }
+            (crypto, _) if crypto.starts_with("cryptomatte/") => {
+                let mut parts = crypto.splitn(3, '/').skip(1);
+                let (Some(key), Some(field)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                let layer = cryptomatte.entry(key.to_string()).or_insert_with(|| CryptomatteLayer {
+                    key: key.to_string(),
+                    name: None,
+                    hash: None,
+                    conversion: None,
+                    manifest_entries: 0,
+                });
+                match field {
+                    "name" => layer.name = Some(text(&bytes)),
+                    "hash" => layer.hash = Some(text(&bytes)),
+                    "conversion" => layer.conversion = Some(text(&bytes)),
+                    "manifest" => {
+                        layer.manifest_entries = serde_json::from_slice::<serde_json::Map<String, Value>>(&bytes)
+                            .map(|manifest| manifest.len())
+                            .unwrap_or_default()
+                    }
+                    _ => {}
+                }
+            }
+            _ => {
+                if let Some(value) = attribute_value(&kind, &bytes) {
+                    metadata.attributes.insert(name, value);
+                }
+            }
+        }
+    }
+    metadata.frame = metadata.frame.or_else(|| frame_from_name(path));
+    metadata.layers = group(all_channels);
+    metadata.cryptomatte = cryptomatte.into_values().collect();
+    let passes: Vec<&str> = metadata
+        .layers
+        .iter()
+        .flat_map(|layer| layer.passes.iter().map(|pass| pass.name.as_str()))
+        .collect();
+    metadata.denoising_data = passes.contains(&"Denoising Normal") && passes.contains(&"Denoising Albedo");
+    Ok(metadata)
+}
+
+/// Names and hashes in the manifest of the cryptomatte layer `key`; empty
+/// when the manifest is in a sidecar file
+pub fn cryptomatte_manifest(path: &Path, key: &str) -> Result<serde_json::Map<String, Value>, CommandError> {
+    let name = format!("cryptomatte/{}/manifest", key);
+    let (attributes, _) = read_attributes(path)?;
+    Ok(attributes
+        .into_iter()
+        .find(|(attribute, _, _)| *attribute == name)
+        .and_then(|(_, _, bytes)| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default())
+}
+
+/// Whether `metadata` has the pass `expected`. Besides pass names, or
+/// `Layer.Pass` for one view layer, `cryptomatte` and `denoising` match
+/// any cryptomatte layer and the denoising data passes.
+fn has_pass(metadata: &ExrMetadata, expected: &str) -> bool {
+    match expected.to_lowercase().as_str() {
+        "cryptomatte" => !metadata.cryptomatte.is_empty(),
+        "denoising" | "denoising data" => metadata.denoising_data,
+        _ => {
+            let (layer, pass) = match expected.rsplit_once('.') {
+                Some((layer, pass)) => (Some(layer), pass),
+                None => (None, expected),
+            };
+            metadata
+                .layers
+                .iter()
+                .filter(|known| layer.is_none_or(|layer| known.name.eq_ignore_ascii_case(layer)))
+                .any(|known| known.passes.iter().any(|known| known.name.eq_ignore_ascii_case(pass)))
+        }
+    }
+}
+
+/// EXR headers of rendered frames, one document per job in the `renders`
+/// namespace of the storage backend
+pub struct ExrStore {
+    storage: SharedStorage,
+    jobs: Mutex<HashMap<String, Vec<ExrMetadata>>>,
+}
+
+impl ExrStore {
+    pub fn new(storage: SharedStorage) -> Self {
+        Self {
+            storage,
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn frames(&self, job: &str) -> Vec<ExrMetadata> {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.entry(job.to_string())
+            .or_insert_with(|| self.storage.get_json("renders", job).unwrap_or_default())
+            .clone()
+    }
+
+    fn record(&self, job: &str, metadata: ExrMetadata) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let frames = jobs
+            .entry(job.to_string())
+            .or_insert_with(|| self.storage.get_json("renders", job).unwrap_or_default());
+        // A frame rendered again replaces the earlier one
+        frames.retain(|frame| frame.path != metadata.path);
+        frames.push(metadata);
+        frames.sort_by(|a, b| a.frame.cmp(&b.frame).then_with(|| a.path.cmp(&b.path)));
+        if let Err(err) = self.storage.put_json("renders", job, frames) {
+            eprintln!("Failed to save render metadata of {job}: {err}");
+        }
+    }
+}
+
+/// Payload of `render:metadata`
+#[derive(Serialize, Clone, Debug)]
+struct FrameMetadata<'a> {
+    job: &'a str,
+    metadata: &'a ExrMetadata,
+}
+
+/// Read and store the header of a frame a render job wrote, when it is an
+/// EXR. Runs in the background; unreadable files are logged.
+pub fn ingest<R: Runtime>(app: &AppHandle<R>, job: &str, output: &str) {
+    let path = Path::new(output);
+    if !path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("exr")) {
+        return;
+    }
+    let (app, job, path) = (app.clone(), job.to_string(), path.to_path_buf());
+    tauri::async_runtime::spawn_blocking(move || {
+        let metadata = match read(&path) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                eprintln!("Failed to read EXR header of {}: {err}", path.display());
+                return;
+            }
+        };
+        let payload = FrameMetadata {
+            job: &job,
+            metadata: &metadata,
+        };
+        if let Err(err) = app.emit("render:metadata", &payload) {
+            eprintln!("Failed to emit render:metadata: {err}");
+        }
+        app.state::<ExrStore>().record(&job, metadata);
+    });
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MissingPasses {
+    pub path: String,
+    pub frame: Option<i64>,
+    pub passes: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PassCheck {
+    pub job: String,
+    /// Frames with a stored header
+    pub checked: usize,
+    /// Frames that have every expected pass
+    pub complete: usize,
+    pub missing: Vec<MissingPasses>,
+}
+
+/// EXR headers of the frames a render job wrote, by frame
+#[tauri::command]
+pub fn get_render_metadata(job: String, store: State<'_, ExrStore>) -> Vec<ExrMetadata> {
+    store.frames(&job)
+}
+
+/// Header of any EXR file, without storing it
+#[tauri::command]
+pub async fn read_exr_metadata(path: String) -> Result<ExrMetadata, CommandError> {
+    tauri::async_runtime::spawn_blocking(move || read(Path::new(&path)))
+        .await
+        .map_err(|e| CommandError::Failed(format!("EXR read failed: {}", e)))?
+}
+
+/// Frames of a render job that lack any of `passes`, e.g. `["Normal",
+/// "cryptomatte", "denoising"]`
+#[tauri::command]
+pub fn check_render_passes(job: String, passes: Vec<String>, store: State<'_, ExrStore>) -> PassCheck {
+    let frames = store.frames(&job);
+    let missing: Vec<MissingPasses> = frames
+        .iter()
+        .filter_map(|frame| {
+            let absent: Vec<String> = passes.iter().filter(|pass| !has_pass(frame, pass)).cloned().collect();
+            (!absent.is_empty()).then(|| MissingPasses {
+                path: frame.path.clone(),
+                frame: frame.frame,
+                passes: absent,
+            })
+        })
+        .collect();
+    PassCheck {
+        job,
+        checked: frames.len(),
+        complete: frames.len() - missing.len(),
+        missing,
+    }
+}
@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::AppState;
+
+/// Chances, from 0 to 1, that a frame in one direction is tampered with.
+/// Each fault is rolled independently, so a frame can be both delayed and
+/// duplicated.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct FaultRates {
+    pub drop: f64,
+    pub delay: f64,
+    pub duplicate: f64,
+    /// Corrupted frames are cut in half, so they no longer parse
+    pub corrupt: f64,
+    /// How long a delayed frame is held back
+    pub delay_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct FaultConfig {
+    pub enabled: bool,
+    /// App to Blender
+    pub outgoing: FaultRates,
+    /// Blender to app
+    pub incoming: FaultRates,
+}
+
+#[derive(Serialize, Clone, Copy, Default, Debug)]
+pub struct FaultCounts {
+    pub dropped: u64,
+    pub delayed: u64,
+    pub duplicated: u64,
+    pub corrupted: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FaultStatus {
+    pub config: FaultConfig,
+    pub outgoing: FaultCounts,
+    pub incoming: FaultCounts,
+}
+
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+struct Faults {
+    config: FaultConfig,
+    outgoing: FaultCounts,
+    incoming: FaultCounts,
+    rng: u64,
+}
+
+/// Tampers with frames on the Blender connection to exercise reconnects,
+/// gap detection and request timeouts. Only debug builds can enable it,
+/// and it always starts disabled.
+pub struct FaultInjector {
+    faults: Mutex<Faults>,
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self {
+            faults: Mutex::new(Faults {
+                config: FaultConfig::default(),
+                outgoing: FaultCounts::default(),
+                incoming: FaultCounts::default(),
+                rng: seed | 1,
+            }),
+        }
+    }
+}
+
+impl FaultInjector {
+    /// Frames to pass on in place of `frame`: none when dropped, two when
+    /// duplicated. Delayed frames are held back here, which also holds back
+    /// the frames behind them.
+    pub async fn apply(&self, direction: Direction, frame: String) -> Vec<String> {
+        let (delay, frames) = {
+            let mut faults = self.faults.lock().unwrap();
+            if !faults.config.enabled {
+                return vec![frame];
+            }
+            faults.roll(direction, frame)
+        };
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        frames
+    }
+
+    pub fn status(&self) -> FaultStatus {
+        let faults = self.faults.lock().unwrap();
+        FaultStatus {
+            config: faults.config.clone(),
+            outgoing: faults.outgoing,
+            incoming: faults.incoming,
+        }
+    }
+
+    fn configure(&self, config: FaultConfig) {
+        let mut faults = self.faults.lock().unwrap();
+        faults.config = config;
+        faults.outgoing = FaultCounts::default();
+        faults.incoming = FaultCounts::default();
+    }
+}
+
+impl Faults {
+    fn roll(&mut self, direction: Direction, mut frame: String) -> (Option<Duration>, Vec<String>) {
+        let rates = match direction {
+            Direction::Outgoing => self.config.outgoing.clone(),
+            Direction::Incoming => self.config.incoming.clone(),
+        };
+        let mut counts = FaultCounts::default();
+
+        if self.chance(rates.drop) {
+            counts.dropped += 1;
+            self.count(direction, counts);
+            return (None, Vec::new());
+        }
+
+        let delay = self.chance(rates.delay).then(|| {
+            counts.delayed += 1;
+            Duration::from_millis(rates.delay_ms)
+        });
+        if self.chance(rates.corrupt) {
+            counts.corrupted += 1;
+            let mut cut = frame.len() / 2;
+            while !frame.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            frame.truncate(cut);
+        }
+        let frames = if self.chance(rates.duplicate) {
+            counts.duplicated += 1;
+            vec![frame.clone(), frame]
+        } else {
+            vec![frame]
+        };
+
+        self.count(direction, counts);
+        (delay, frames)
+    }
+
+    fn count(&mut self, direction: Direction, added: FaultCounts) {
+        let counts = match direction {
+            Direction::Outgoing => &mut self.outgoing,
+            Direction::Incoming => &mut self.incoming,
+        };
+        counts.dropped += added.dropped;
+        counts.delayed += added.delayed;
+        counts.duplicated += added.duplicated;
+        counts.corrupted += added.corrupted;
+    }
+
+    /// xorshift64; good enough to spread faults, not for anything else
+    fn chance(&mut self, rate: f64) -> bool {
+        if rate <= 0.0 {
+            return false;
+        }
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        ((self.rng >> 11) as f64 / (1u64 << 53) as f64) < rate
+    }
+}
+
+fn check_rates(rates: &FaultRates) -> Result<(), CommandError> {
+    let all = [rates.drop, rates.delay, rates.duplicate, rates.corrupt];
+    if all.iter().any(|rate| !(0.0..=1.0).contains(rate)) {
+        return Err(CommandError::InvalidInput("Fault rates must be between 0 and 1".to_string()));
+    }
+    Ok(())
+}
+
+/// Configure fault injection on the Blender connection. Counts restart
+/// from zero. Not exposed in the UI; call it from the devtools console.
+#[tauri::command]
+pub fn set_fault_injection(config: FaultConfig, state: State<'_, AppState>) -> Result<FaultStatus, CommandError> {
+    if !cfg!(debug_assertions) {
+        return Err(CommandError::Unsupported(
+            "Fault injection is only available in debug builds".to_string(),
+        ));
+    }
+    check_rates(&config.outgoing)?;
+    check_rates(&config.incoming)?;
+    state.bridge.faults().configure(config);
+    Ok(state.bridge.faults().status())
+}
+
+#[tauri::command]
+pub fn get_fault_injection(state: State<'_, AppState>) -> FaultStatus {
+    state.bridge.faults().status()
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+use crate::surfaces::SurfaceState;
+use crate::timesync::TimeSync;
+use crate::workers::WorkerPool;
+use crate::{delivery, diskguard, shares, surfaces, sync, timesync};
+
+/// A subsystem that runs in the background and can be switched off
+struct Feature {
+    id: &'static str,
+    description: &'static str,
+    /// Off unless switched on
+    experimental: bool,
+}
+
+const FEATURES: &[Feature] = &[
+    Feature {
+        id: "disk_guard",
+        description: "Pause renders before their output volume fills up",
+        experimental: false,
+    },
+    Feature {
+        id: "share_monitor",
+        description: "Hold jobs while network shares they need are unreachable",
+        experimental: false,
+    },
+    Feature {
+        id: "web_delivery",
+        description: "Encode renders dropped into project watch folders for the web",
+        experimental: false,
+    },
+    Feature {
+        id: "settings_sync",
+        description: "Keep settings in step with a synced file",
+        experimental: false,
+    },
+    Feature {
+        id: "time_sync",
+        description: "Discipline playback to MIDI timecode or NTP",
+        experimental: true,
+    },
+    Feature {
+        id: "control_surfaces",
+        description: "Run actions from MIDI controllers, Stream Decks and global hotkeys",
+        experimental: true,
+    },
+];
+
+/// Switched features by id; a feature not listed keeps its default
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct FeatureFlags(pub BTreeMap<String, bool>);
+
+impl FeatureFlags {
+    fn enabled(&self, feature: &Feature) -> bool {
+        self.0.get(feature.id).copied().unwrap_or(!feature.experimental)
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FeatureStatus {
+    pub id: String,
+    pub description: String,
+    pub experimental: bool,
+    pub enabled: bool,
+    pub running: bool,
+    pub started_at: Option<String>,
+}
+
+struct RunningFeature {
+    task: JoinHandle<()>,
+    started_at: String,
+}
+
+fn spawn<R: Runtime>(id: &str, app: AppHandle<R>) -> JoinHandle<()> {
+    match id {
+        "disk_guard" => diskguard::start_monitor(app),
+        "share_monitor" => shares::start_monitor(app),
+        "web_delivery" => delivery::start_watcher(app),
+        "settings_sync" => sync::start_watcher(app),
+        "time_sync" => timesync::start(app),
+        "control_surfaces" => surfaces::start(app),
+        _ => unreachable!("unknown feature {id}"),
+    }
+}
+
+/// Release what a stopped feature held outside its task, so jobs it paused
+/// don't stay paused
+fn stopped<R: Runtime>(id: &str, app: &AppHandle<R>) {
+    match id {
+        "disk_guard" => app.state::<WorkerPool>().set_paused("disk", false),
+        "share_monitor" => app.state::<WorkerPool>().set_paused("shares", false),
+        "time_sync" => app.state::<TimeSync>().close(),
+        "control_surfaces" => app.state::<SurfaceState>().close(),
+        _ => {}
+    }
+}
+
+/// Starts the enabled features and stops the others when flags change
+#[derive(Default)]
+pub struct FeatureRegistry {
+    running: Mutex<HashMap<&'static str, RunningFeature>>,
+    /// Set once the `features` subsystem starts; until then, e.g. in safe
+    /// mode, flags are only saved, as features need the worker pool
+    started: AtomicBool,
+}
+
+impl FeatureRegistry {
+    /// Start and stop features to match the flags in the settings
+    pub async fn apply<R: Runtime>(&self, app: &AppHandle<R>) {
+        let flags = app.state::<SettingsStore>().get().features;
+        let mut running = self.running.lock().await;
+        for feature in FEATURES {
+            let enabled = flags.enabled(feature);
+            if enabled && !running.contains_key(feature.id) {
+                running.insert(
+                    feature.id,
+                    RunningFeature {
+                        task: spawn(feature.id, app.clone()),
+                        started_at: chrono::Local::now().to_rfc3339(),
+                    },
+                );
+            } else if !enabled {
+                if let Some(stopping) = running.remove(feature.id) {
+                    stopping.task.abort();
+                    let _ = stopping.task.await;
+                    stopped(feature.id, app);
+                }
+            }
+        }
+    }
+
+    async fn statuses(&self, flags: &FeatureFlags) -> Vec<FeatureStatus> {
+        let running = self.running.lock().await;
+        FEATURES
+            .iter()
+            .map(|feature| {
+                let started_at = running.get(feature.id).map(|running| running.started_at.clone());
+                FeatureStatus {
+                    id: feature.id.to_string(),
+                    description: feature.description.to_string(),
+                    experimental: feature.experimental,
+                    enabled: flags.enabled(feature),
+                    running: started_at.is_some(),
+                    started_at,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Start the enabled features in the background.
+pub fn start<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let registry = app.state::<FeatureRegistry>();
+        registry.started.store(true, Ordering::SeqCst);
+        registry.apply(&app).await;
+    });
+}
+
+#[tauri::command]
+pub async fn list_features(
+    settings: State<'_, SettingsStore>,
+    registry: State<'_, FeatureRegistry>,
+) -> Result<Vec<FeatureStatus>, CommandError> {
+    Ok(registry.statuses(&settings.get().features).await)
+}
+
+/// Switch a feature on or off and start or stop it right away. Emits
+/// `features:changed` with every feature's status.
+#[tauri::command]
+pub async fn set_feature_enabled<R: Runtime>(
+    id: String,
+    enabled: bool,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    registry: State<'_, FeatureRegistry>,
+) -> Result<Vec<FeatureStatus>, CommandError> {
+    if !FEATURES.iter().any(|feature| feature.id == id) {
+        return Err(CommandError::NotFound(format!("Unknown feature '{}'", id)));
+    }
+    let flags = settings.update(|s| {
+        s.features.0.insert(id, enabled);
+    })?;
+    if registry.started.load(Ordering::SeqCst) {
+        registry.apply(&app).await;
+    }
+    let statuses = registry.statuses(&flags.features).await;
+    if let Err(err) = app.emit("features:changed", &statuses) {
+        eprintln!("Failed to emit features:changed: {err}");
+    }
+    Ok(statuses)
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use tauri::State;
+use tokio::process::Command;
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+
+/// Lines of ffmpeg's output kept in an error
+const ERROR_LINES: usize = 8;
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct FfmpegSettings {
+    /// ffmpeg executable; defaults to `ffmpeg` on the PATH
+    pub path: Option<String>,
+}
+
+fn binary(settings: &SettingsStore) -> String {
+    settings.get().ffmpeg.path.unwrap_or_else(|| "ffmpeg".to_string())
+}
+
+/// Encode a numbered JPEG sequence (`pattern` like `frame_%06d.jpg`) into
+/// an H.264 video of `width` x `height`. Frames of another size are
+/// scaled to fit and padded.
+pub async fn encode_sequence(
+    settings: &SettingsStore,
+    pattern: &Path,
+    fps: f64,
+    width: u32,
+    height: u32,
+    output: &Path,
+) -> Result<(), CommandError> {
+    // H.264 needs even dimensions
+    let (width, height) = (width.max(2) & !1, height.max(2) & !1);
+    let filter = format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+        w = width,
+        h = height
+    );
+    let mut command = ffmpeg(settings);
+    command
+        .arg("-framerate")
+        .arg(fps.to_string())
+        .arg("-i")
+        .arg(pattern)
+        .args(["-vf", &filter, "-c:v", "libx264", "-pix_fmt", "yuv420p"])
+        .arg(output);
+    run(command).await
+}
+
+/// Re-encode a video as an H.264 MP4 at most `max_height` tall, with the
+/// index up front so browsers can start playing it while it loads
+pub async fn encode_proxy(
+    settings: &SettingsStore,
+    input: &Path,
+    max_height: u32,
+    crf: u32,
+    output: &Path,
+) -> Result<(), CommandError> {
+    let filter = format!("scale=-2:'min({},ih)':flags=lanczos", max_height.max(2) & !1);
+    let mut command = ffmpeg(settings);
+    command
+        .arg("-i")
+        .arg(input)
+        .args(["-vf", &filter, "-c:v", "libx264", "-preset", "medium", "-crf"])
+        .arg(crf.to_string())
+        .args(["-pix_fmt", "yuv420p", "-c:a", "aac", "-b:a", "160k", "-movflags", "+faststart"])
+        .arg(output);
+    run(command).await
+}
+
+/// Write one frame of `input`, an image or the frame of a video at
+/// `at_secs`, scaled down to at most `max_width` wide. The format follows
+/// the extension of `output`.
+pub async fn extract_still(
+    settings: &SettingsStore,
+    input: &Path,
+    at_secs: Option<f64>,
+    max_width: u32,
+    output: &Path,
+) -> Result<(), CommandError> {
+    let filter = format!("scale='min({},iw)':-2:flags=lanczos", max_width.max(2) & !1);
+    let mut command = ffmpeg(settings);
+    if let Some(at) = at_secs {
+        command.arg("-ss").arg(format!("{:.3}", at.max(0.0)));
+    }
+    command
+        .arg("-i")
+        .arg(input)
+        .args(["-vf", &filter, "-frames:v", "1", "-update", "1"])
+        .arg(output);
+    run(command).await?;
+    // Seeking past the end of a video writes nothing and still succeeds
+    if !output.is_file() {
+        return Err(CommandError::Failed(format!("ffmpeg wrote no frame of {}", input.display())));
+    }
+    Ok(())
+}
+
+/// Overlay the image `mark` on the image `input` at `x`, `y` (ffmpeg
+/// `overlay` expressions such as `W-w-24`), faded to `opacity`. The format
+/// follows the extension of `output`.
+pub async fn overlay_image(
+    settings: &SettingsStore,
+    input: &Path,
+    mark: &Path,
+    x: &str,
+    y: &str,
+    opacity: f64,
+    output: &Path,
+) -> Result<(), CommandError> {
+    let filter = format!(
+        "[1]format=rgba,colorchannelmixer=aa={:.3}[mark];[0][mark]overlay={}:{}",
+        opacity.clamp(0.0, 1.0),
+        x,
+        y
+    );
+    let mut command = ffmpeg(settings);
+    command
+        .arg("-i")
+        .arg(input)
+        .arg("-i")
+        .arg(mark)
+        .args(["-filter_complex", &filter, "-frames:v", "1", "-update", "1"])
+        .arg(output);
+    run(command).await
+}
+
+/// ffmpeg set to overwrite outputs and print errors only
+fn ffmpeg(settings: &SettingsStore) -> Command {
+    let mut command = Command::new(binary(settings));
+    command
+        .args(["-y", "-hide_banner", "-loglevel", "error"])
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+    command
+}
+
+/// Run an ffmpeg command; errors carry the last lines it printed
+async fn run(mut command: Command) -> Result<(), CommandError> {
+    let result = command
+        .output()
+        .await
+        .map_err(|e| CommandError::Unsupported(format!("Can't run ffmpeg: {}", e)))?;
+
+    if result.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    let lines: Vec<&str> = stderr.lines().rev().take(ERROR_LINES).collect();
+    let tail: Vec<&str> = lines.into_iter().rev().collect();
+    Err(CommandError::Failed(format!("ffmpeg failed: {}", tail.join("\n"))))
+}
+
+/// Version line of the configured ffmpeg, to check it can be run
+#[tauri::command]
+pub async fn check_ffmpeg(settings: State<'_, SettingsStore>) -> Result<String, CommandError> {
+    let output = Command::new(binary(&settings))
+        .arg("-version")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| CommandError::Unsupported(format!("Can't run ffmpeg: {}", e)))?;
+    Ok(String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or_default().to_string())
+}
+
+#[tauri::command]
+pub fn get_ffmpeg_settings(settings: State<'_, SettingsStore>) -> FfmpegSettings {
+    settings.get().ffmpeg
+}
+
+#[tauri::command]
+pub fn set_ffmpeg_settings(ffmpeg: FfmpegSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.ffmpeg = ffmpeg)?;
+    Ok(())
+}
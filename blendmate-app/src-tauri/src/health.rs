@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::a11y::{self, Politeness};
+use crate::errors::CommandError;
+use crate::i18n;
+use crate::protocol;
+use crate::settings::SettingsStore;
+
+/// Samples kept, an hour at one heartbeat every 5 seconds
+const MAX_SAMPLES: usize = 720;
+
+/// How far back the rate free memory drops at is measured over
+const TREND_WINDOW: Duration = Duration::from_secs(60);
+
+/// Swap growth between two heartbeats that counts as swapping
+const SWAP_GROWTH_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct HealthSettings {
+    /// Warn when less than this share of system memory is available, 0-100
+    pub warn_available_percent: u8,
+    /// Warn when free memory is predicted to run out within this long
+    pub warn_within_secs: u64,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            warn_available_percent: 10,
+            warn_within_secs: 120,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryPressure {
+    #[default]
+    Normal,
+    /// Little memory left, or running out soon at the current rate
+    High,
+    /// The system is moving memory to swap; Blender may freeze
+    Swapping,
+}
+
+/// Metrics from the add-on's heartbeat. Fields a platform doesn't report
+/// are missing.
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+struct Metrics {
+    memory_bytes: Option<u64>,
+    peak_memory_bytes: Option<u64>,
+    system_total_bytes: Option<u64>,
+    system_available_bytes: Option<u64>,
+    swap_used_bytes: Option<u64>,
+    depsgraph_ms: Option<f64>,
+    depsgraph_evals: u32,
+    undo_steps: Option<u32>,
+    undo_memory_limit_mb: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct HealthSample {
+    pub at: String,
+    /// Resident memory of the Blender process
+    pub memory_bytes: Option<u64>,
+    pub peak_memory_bytes: Option<u64>,
+    pub system_total_bytes: Option<u64>,
+    pub system_available_bytes: Option<u64>,
+    pub swap_used_bytes: Option<u64>,
+    /// Slowest depsgraph evaluation since the previous heartbeat
+    pub depsgraph_ms: Option<f64>,
+    pub depsgraph_evals: u32,
+    /// Blender doesn't report what the undo stack holds, only its limits;
+    /// a limit of 0 is unlimited
+    pub undo_steps: Option<u32>,
+    pub undo_memory_limit_mb: Option<u64>,
+    /// At the rate available memory dropped over the last minute
+    pub secs_to_exhausted: Option<f64>,
+    pub pressure: MemoryPressure,
+    pub warning: Option<String>,
+}
+
+#[derive(Default)]
+struct Samples {
+    samples: VecDeque<(Instant, HealthSample)>,
+}
+
+/// Blender health from heartbeats, emitted as `blender:health` samples
+#[derive(Default)]
+pub struct HealthMonitor {
+    samples: Mutex<Samples>,
+}
+
+fn gigabytes(bytes: u64) -> String {
+    format!("{:.1} GiB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+fn assess(
+    metrics: &Metrics,
+    previous: Option<&HealthSample>,
+    secs_to_exhausted: Option<f64>,
+    config: &HealthSettings,
+) -> (MemoryPressure, Option<String>) {
+    let swap_growth = metrics
+        .swap_used_bytes
+        .zip(previous.and_then(|sample| sample.swap_used_bytes))
+        .map(|(now, before)| now.saturating_sub(before))
+        .unwrap_or(0);
+    if swap_growth >= SWAP_GROWTH_BYTES {
+        return (
+            MemoryPressure::Swapping,
+            Some(i18n::t("health-swapping", &[("grown", gigabytes(swap_growth).into())])),
+        );
+    }
+    if let Some((available, total)) = metrics.system_available_bytes.zip(metrics.system_total_bytes) {
+        if total > 0 && available * 100 < total * u64::from(config.warn_available_percent) {
+            let text = i18n::t(
+                "health-memory-low",
+                &[("free", gigabytes(available).into()), ("total", gigabytes(total).into())],
+            );
+            return (MemoryPressure::High, Some(text));
+        }
+    }
+    if let Some(secs) = secs_to_exhausted.filter(|secs| *secs < config.warn_within_secs as f64) {
+        let text = i18n::t("health-memory-running-out", &[("seconds", (secs.round() as u64).into())]);
+        return (MemoryPressure::High, Some(text));
+    }
+    (MemoryPressure::Normal, None)
+}
+
+impl HealthMonitor {
+    /// Record the metrics a heartbeat carries; returns the sample and the
+    /// pressure before it
+    fn record(&self, metrics: Metrics, config: &HealthSettings) -> (HealthSample, MemoryPressure) {
+        let mut samples = self.samples.lock().unwrap();
+        let now = Instant::now();
+        let previous = samples.samples.back().map(|(_, sample)| sample);
+        let before = previous.map(|sample| sample.pressure).unwrap_or_default();
+        // The oldest sample within the window, for a rate that isn't thrown
+        // off by a single heartbeat
+        let secs_to_exhausted = samples
+            .samples
+            .iter()
+            .find(|(at, _)| now.duration_since(*at) <= TREND_WINDOW)
+            .and_then(|(at, sample)| Some((*at, sample.system_available_bytes?)))
+            .zip(metrics.system_available_bytes)
+            .and_then(|((at, before), available)| {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                let rate = before.saturating_sub(available) as f64 / elapsed;
+                (elapsed > 0.0 && rate > 0.0).then(|| available as f64 / rate)
+            });
+        let (pressure, warning) = assess(&metrics, previous, secs_to_exhausted, config);
+        let sample = HealthSample {
+            at: chrono::Local::now().to_rfc3339(),
+            memory_bytes: metrics.memory_bytes,
+            peak_memory_bytes: metrics.peak_memory_bytes,
+            system_total_bytes: metrics.system_total_bytes,
+            system_available_bytes: metrics.system_available_bytes,
+            swap_used_bytes: metrics.swap_used_bytes,
+            depsgraph_ms: metrics.depsgraph_ms,
+            depsgraph_evals: metrics.depsgraph_evals,
+            undo_steps: metrics.undo_steps,
+            undo_memory_limit_mb: metrics.undo_memory_limit_mb,
+            secs_to_exhausted,
+            pressure,
+            warning,
+        };
+        samples.samples.push_back((now, sample.clone()));
+        if samples.samples.len() > MAX_SAMPLES {
+            samples.samples.pop_front();
+        }
+        (sample, before)
+    }
+
+    /// Take the metrics out of a heartbeat and emit them. Heartbeats from
+    /// add-ons without metrics are ignored.
+    pub fn observe<R: Runtime>(&self, app: &AppHandle<R>, message: &Value) {
+        if protocol::message_type(message).as_deref() != Some("heartbeat") {
+            return;
+        }
+        let Some(health) = protocol::message_body(message).get("health").filter(|h| h.is_object()) else {
+            return;
+        };
+        let metrics: Metrics = match serde_json::from_value(health.clone()) {
+            Ok(metrics) => metrics,
+            Err(err) => {
+                eprintln!("Invalid health metrics: {err}");
+                return;
+            }
+        };
+        let config = app.state::<SettingsStore>().get().health;
+        let (sample, before) = self.record(metrics, &config);
+        if sample.pressure != MemoryPressure::Normal && before == MemoryPressure::Normal {
+            if let Some(warning) = &sample.warning {
+                a11y::announce(app, "health", Politeness::Assertive, warning.clone());
+            }
+        }
+        if let Err(err) = app.emit("blender:health", &sample) {
+            eprintln!("Failed to emit blender:health: {err}");
+        }
+    }
+}
+
+/// Health samples, oldest first; the newest `limit` when given, to fill a
+/// chart that `blender:health` events then extend
+#[tauri::command]
+pub fn get_blender_health(limit: Option<usize>, health: State<'_, HealthMonitor>) -> Vec<HealthSample> {
+    let samples = health.samples.lock().unwrap();
+    let skip = limit.map_or(0, |limit| samples.samples.len().saturating_sub(limit));
+    samples.samples.iter().skip(skip).map(|(_, sample)| sample.clone()).collect()
+}
+
+#[tauri::command]
+pub fn get_health_settings(settings: State<'_, SettingsStore>) -> HealthSettings {
+    settings.get().health
+}
+
+#[tauri::command]
+pub fn set_health_settings(health: HealthSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.health = health)?;
+    Ok(())
+}
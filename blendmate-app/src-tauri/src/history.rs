@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::storage::SharedStorage;
+use crate::tagging::{self, Tags};
+use crate::AppState;
+
+/// Entries kept per project; older ones are dropped
+const MAX_ENTRIES: usize = 1000;
+
+/// Requests that change the scene and are worth repeating. Reads, scene
+/// queries and the backend's own housekeeping are not recorded.
+pub const RECORDED_ACTIONS: [&str; 11] = [
+    "operator.call",
+    "property.set",
+    "property.set_batch",
+    "batch.execute",
+    "object.rename",
+    "assets.append",
+    "assets.apply_pose",
+    "palette.apply",
+    "render.set_devices",
+    "color.set_settings",
+    "shots.jump",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub action: String,
+    /// Operator name for `operator.call`, else the request's target
+    pub target: String,
+    pub params: Value,
+    pub at: String,
+    /// Tags of the file open when it was sent
+    #[serde(default, skip_serializing_if = "Tags::is_empty")]
+    pub tags: Tags,
+}
+
+/// A request pinned under a name, to run again from the command palette
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Favorite {
+    pub name: String,
+    pub action: String,
+    pub target: String,
+    pub params: Value,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct ProjectHistory {
+    /// Oldest first
+    entries: Vec<HistoryEntry>,
+    favorites: Vec<Favorite>,
+    next_id: u64,
+}
+
+/// Storage namespace; each project's history is one document
+const NAMESPACE: &str = "history";
+
+/// Requests the user sent to Blender, per project, written to storage
+/// after every change.
+pub struct HistoryStore {
+    storage: SharedStorage,
+    /// Tags of the open file, added to new entries
+    tags: Mutex<Tags>,
+}
+
+impl HistoryStore {
+    /// `legacy` is the `history.json` of earlier versions, imported once
+    pub fn load(storage: SharedStorage, legacy: &Path) -> Self {
+        if let Some(projects) = fs::read_to_string(legacy)
+            .ok()
+            .and_then(|text| serde_json::from_str::<HashMap<String, ProjectHistory>>(&text).ok())
+        {
+            let imported = projects.iter().all(|(project, history)| {
+                matches!(storage.get(NAMESPACE, project), Ok(Some(_)))
+                    || storage
+                        .put_json(NAMESPACE, project, history)
+                        .map_err(|err| eprintln!("Failed to import history of {project}: {err}"))
+                        .is_ok()
+            });
+            if imported {
+                if let Err(err) = fs::rename(legacy, legacy.with_extension("json.migrated")) {
+                    eprintln!("Failed to rename {}: {err}", legacy.display());
+                }
+            }
+        }
+        Self {
+            storage,
+            tags: Mutex::new(Tags::new()),
+        }
+    }
+
+    fn update<T>(
+        &self,
+        project: String,
+        change: impl FnOnce(&mut ProjectHistory) -> Result<T, CommandError>,
+    ) -> Result<T, CommandError> {
+        self.storage.update_json(NAMESPACE, &project, change)
+    }
+
+    fn read<T>(&self, project: &str, read: impl FnOnce(&ProjectHistory) -> T) -> T {
+        read(&self.storage.get_json(NAMESPACE, project).unwrap_or_default())
+    }
+
+    pub fn set_tags(&self, tags: Tags) {
+        *self.tags.lock().unwrap() = tags;
+    }
+
+    /// Add a request to the active project's history if its action is one
+    /// that is recorded. Failures are logged; recording never blocks the
+    /// request itself.
+    pub fn record(&self, settings: &SettingsStore, action: &str, target: &str, params: &Value) {
+        if !RECORDED_ACTIONS.contains(&action) {
+            return;
+        }
+        let tags = self.tags.lock().unwrap().clone();
+        let result = self.update(projects::key(settings, None), |history| {
+            history.next_id += 1;
+            history.entries.push(HistoryEntry {
+                id: history.next_id,
+                action: action.to_string(),
+                target: target.to_string(),
+                params: params.clone(),
+                at: chrono::Local::now().to_rfc3339(),
+                tags,
+            });
+            let excess = history.entries.len().saturating_sub(MAX_ENTRIES);
+            history.entries.drain(..excess);
+            Ok(())
+        });
+        if let Err(err) = result {
+            eprintln!("Failed to record history: {err}");
+        }
+    }
+
+    /// Id of the newest entry of `project`, 0 when it has none
+    pub fn last_id(&self, project: &str) -> u64 {
+        self.read(project, |history| history.next_id)
+    }
+
+    /// Every entry of `project`, oldest first
+    pub fn entries(&self, project: &str) -> Vec<HistoryEntry> {
+        self.read(project, |history| history.entries.clone())
+    }
+
+    /// Entries of `project` newer than `after`, oldest first
+    pub fn since(&self, project: &str, after: u64) -> Vec<HistoryEntry> {
+        self.read(project, |history| {
+            history.entries.iter().filter(|entry| entry.id > after).cloned().collect()
+        })
+    }
+
+    /// Record a raw protocol message the frontend sent, if it is a request
+    pub fn record_message(&self, settings: &SettingsStore, message: &str) {
+        let Ok(value) = serde_json::from_str::<Value>(message) else {
+            return;
+        };
+        if value.get("type").and_then(Value::as_str) != Some("request") {
+            return;
+        }
+        let action = value.get("action").and_then(Value::as_str).unwrap_or_default();
+        let target = value.get("target").and_then(Value::as_str).unwrap_or_default();
+        let params = value.get("params").cloned().unwrap_or(Value::Object(Default::default()));
+        self.record(settings, action, target, &params);
+    }
+}
+
+/// Send a request again and record it as the newest entry
+async fn replay(
+    state: &AppState,
+    history: &HistoryStore,
+    settings: &SettingsStore,
+    action: &str,
+    target: &str,
+    params: Value,
+) -> Result<Value, CommandError> {
+    let result = state.bridge.request(action, target, params.clone()).await?;
+    history.record(settings, action, target, &params);
+    Ok(result)
+}
+
+/// History of a project (default: the active one), newest first. `query`
+/// matches the action, target and parameters, ignoring case, and `tags`
+/// keeps entries with all of the given tags.
+#[tauri::command]
+pub fn list_history(
+    query: Option<String>,
+    action: Option<String>,
+    tags: Option<Tags>,
+    limit: Option<usize>,
+    project: Option<String>,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Vec<HistoryEntry> {
+    let query = query.map(|query| query.to_lowercase());
+    history.read(&projects::key(&settings, project), |project| {
+        project
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| action.as_ref().is_none_or(|action| &entry.action == action))
+            .filter(|entry| tagging::matches(&entry.tags, tags.as_ref()))
+            .filter(|entry| {
+                query.as_ref().is_none_or(|query| {
+                    let text = format!("{} {} {}", entry.action, entry.target, entry.params).to_lowercase();
+                    text.contains(query.as_str())
+                })
+            })
+            .take(limit.unwrap_or(100))
+            .cloned()
+            .collect()
+    })
+}
+
+/// Run the newest entry of the active project's history again
+#[tauri::command]
+pub async fn repeat_last(
+    state: State<'_, AppState>,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Value, CommandError> {
+    let last = history
+        .read(&projects::key(&settings, None), |project| project.entries.last().cloned())
+        .ok_or_else(|| CommandError::NotFound("Nothing to repeat".to_string()))?;
+    replay(&state, &history, &settings, &last.action, &last.target, last.params).await
+}
+
+/// Run a history entry of the active project again
+#[tauri::command]
+pub async fn repeat_history(
+    id: u64,
+    state: State<'_, AppState>,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Value, CommandError> {
+    let entry = history
+        .read(&projects::key(&settings, None), |project| {
+            project.entries.iter().find(|entry| entry.id == id).cloned()
+        })
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown history entry {}", id)))?;
+    replay(&state, &history, &settings, &entry.action, &entry.target, entry.params).await
+}
+
+/// Parameters `action` was last sent with on `target`, e.g. an operator's
+/// last settings, to prefill a form
+#[tauri::command]
+pub fn recall_params(
+    action: String,
+    target: String,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Option<Value> {
+    history.read(&projects::key(&settings, None), |project| {
+        project
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.action == action && entry.target == target)
+            .map(|entry| entry.params.clone())
+    })
+}
+
+#[tauri::command]
+pub fn list_favorites(
+    project: Option<String>,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Vec<Favorite> {
+    history.read(&projects::key(&settings, project), |project| project.favorites.clone())
+}
+
+/// Pin a history entry as a favorite, replacing one with the same name
+#[tauri::command]
+pub fn pin_favorite(
+    id: u64,
+    name: String,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<Favorite>, CommandError> {
+    if name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Favorite name is required".to_string()));
+    }
+    history.update(projects::key(&settings, None), |project| {
+        let entry = project
+            .entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| CommandError::NotFound(format!("Unknown history entry {}", id)))?;
+        let favorite = Favorite {
+            name,
+            action: entry.action.clone(),
+            target: entry.target.clone(),
+            params: entry.params.clone(),
+        };
+        project.favorites.retain(|f| f.name != favorite.name);
+        project.favorites.push(favorite);
+        Ok(project.favorites.clone())
+    })
+}
+
+#[tauri::command]
+pub fn unpin_favorite(
+    name: String,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<Favorite>, CommandError> {
+    history.update(projects::key(&settings, None), |project| {
+        let before = project.favorites.len();
+        project.favorites.retain(|f| f.name != name);
+        if project.favorites.len() == before {
+            return Err(CommandError::NotFound(format!("Unknown favorite '{}'", name)));
+        }
+        Ok(project.favorites.clone())
+    })
+}
+
+#[tauri::command]
+pub async fn run_favorite(
+    name: String,
+    state: State<'_, AppState>,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Value, CommandError> {
+    let favorite = history
+        .read(&projects::key(&settings, None), |project| {
+            project.favorites.iter().find(|f| f.name == name).cloned()
+        })
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown favorite '{}'", name)))?;
+    replay(&state, &history, &settings, &favorite.action, &favorite.target, favorite.params).await
+}
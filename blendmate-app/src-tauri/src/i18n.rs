@@ -0,0 +1,248 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+use tauri::State;
+use unic_langid::LanguageIdentifier;
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+
+/// Locale every other falls back to. Its catalog is built in, so backend
+/// messages stay readable when the resources are missing.
+const FALLBACK_LOCALE: &str = "en";
+
+const FALLBACK_CATALOG: &str = include_str!("../locales/en.ftl");
+
+/// Catalogs loaded at startup. Messages are formatted from code without
+/// access to app state, e.g. errors deep inside a helper, so the catalog is
+/// global rather than managed.
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+struct Locale {
+    bundle: FluentBundle<FluentResource>,
+    source: String,
+}
+
+struct Catalog {
+    locales: BTreeMap<String, Locale>,
+    current: RwLock<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CatalogSource {
+    pub locale: String,
+    /// Fluent (`.ftl`) text of the catalog
+    pub source: String,
+}
+
+fn parse(locale: &str, source: String) -> Option<Locale> {
+    let id: LanguageIdentifier = match locale.parse() {
+        Ok(id) => id,
+        Err(err) => {
+            eprintln!("Invalid locale {locale}: {err}");
+            return None;
+        }
+    };
+    let resource = FluentResource::try_new(source.clone()).unwrap_or_else(|(resource, errors)| {
+        eprintln!("Errors in the {locale} catalog: {errors:?}");
+        resource
+    });
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Messages end up in logs and native notifications as well as the UI,
+    // where bidi isolation marks show up as stray characters
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        eprintln!("Errors in the {locale} catalog: {errors:?}");
+    }
+    Some(Locale { bundle, source })
+}
+
+/// `cs` for `cs_CZ.UTF-8` or `cs-CZ`
+fn language(tag: &str) -> String {
+    tag.split(['_', '-', '.']).next().unwrap_or_default().to_lowercase()
+}
+
+/// The system language, from the usual environment variables
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|value| language(&value))
+        .find(|language| !language.is_empty() && language != "c" && language != "posix")
+}
+
+impl Catalog {
+    fn load(dir: Option<&Path>) -> Self {
+        let mut locales = BTreeMap::new();
+        for path in dir.and_then(|dir| fs::read_dir(dir).ok()).into_iter().flatten().flatten() {
+            let path = path.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("ftl") {
+                continue;
+            }
+            let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string) else {
+                continue;
+            };
+            match fs::read_to_string(&path) {
+                Ok(source) => {
+                    if let Some(parsed) = parse(&locale, source) {
+                        locales.insert(locale, parsed);
+                    }
+                }
+                Err(err) => eprintln!("Failed to read {}: {err}", path.display()),
+            }
+        }
+        if !locales.contains_key(FALLBACK_LOCALE) {
+            if let Some(parsed) = parse(FALLBACK_LOCALE, FALLBACK_CATALOG.to_string()) {
+                locales.insert(FALLBACK_LOCALE.to_string(), parsed);
+            }
+        }
+        Self {
+            locales,
+            current: RwLock::new(FALLBACK_LOCALE.to_string()),
+        }
+    }
+
+    /// `locale` if there is a catalog for it or its language, else the
+    /// fallback
+    fn resolve(&self, locale: &str) -> String {
+        if self.locales.contains_key(locale) {
+            return locale.to_string();
+        }
+        let language = language(locale);
+        if self.locales.contains_key(&language) {
+            language
+        } else {
+            FALLBACK_LOCALE.to_string()
+        }
+    }
+
+    fn format(&self, locale: &str, key: &str, args: Option<&FluentArgs>) -> Option<String> {
+        let bundle = &self.locales.get(locale)?.bundle;
+        let pattern = bundle.get_message(key)?.value()?;
+        let mut errors = Vec::new();
+        let text = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            eprintln!("Errors formatting {key} in {locale}: {errors:?}");
+        }
+        Some(text.into_owned())
+    }
+
+    /// `key` in `locale`, else in the fallback locale, else the key itself
+    fn translate(&self, locale: &str, key: &str, args: Option<&FluentArgs>) -> String {
+        self.format(locale, key, args)
+            .or_else(|| self.format(FALLBACK_LOCALE, key, args))
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| Catalog::load(None))
+}
+
+/// Load the catalogs in `dir` and pick the configured locale, or the
+/// system's when none is configured. Call once during setup.
+pub fn init(dir: Option<&Path>, configured: Option<String>) {
+    let catalog = CATALOG.get_or_init(|| Catalog::load(dir));
+    let locale = configured.or_else(system_locale).unwrap_or_else(|| FALLBACK_LOCALE.to_string());
+    *catalog.current.write().unwrap() = catalog.resolve(&locale);
+}
+
+/// A backend message in the current locale. `args` fill the message's
+/// `{ $name }` placeables.
+pub fn t(key: &str, args: &[(&str, FluentValue)]) -> String {
+    let catalog = catalog();
+    let locale = catalog.current.read().unwrap().clone();
+    if args.is_empty() {
+        return catalog.translate(&locale, key, None);
+    }
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    catalog.translate(&locale, key, Some(&fluent_args))
+}
+
+fn fluent_value(value: &Value) -> FluentValue<'static> {
+    match value {
+        Value::Number(number) => number.as_f64().map(FluentValue::from).unwrap_or(FluentValue::None),
+        Value::String(text) => FluentValue::from(text.clone()),
+        Value::Null => FluentValue::None,
+        other => FluentValue::from(other.to_string()),
+    }
+}
+
+/// Locales with a catalog
+#[tauri::command]
+pub fn list_locales() -> Vec<String> {
+    catalog().locales.keys().cloned().collect()
+}
+
+#[tauri::command]
+pub fn get_locale() -> String {
+    catalog().current.read().unwrap().clone()
+}
+
+/// Use `locale` for backend messages from now on, or the system's when
+/// `None`. Returns the locale used, which falls back to its language or
+/// to English when there is no catalog for it.
+#[tauri::command]
+pub fn set_locale(locale: Option<String>, settings: State<'_, SettingsStore>) -> Result<String, CommandError> {
+    settings.update(|s| s.locale = locale.clone())?;
+    Ok(use_locale(locale))
+}
+
+/// Switch backend messages to `locale`, or the system's when `None`, and
+/// return the locale resolved to
+pub fn use_locale(locale: Option<String>) -> String {
+    let catalog = catalog();
+    let resolved = catalog.resolve(
+        &locale
+            .or_else(system_locale)
+            .unwrap_or_else(|| FALLBACK_LOCALE.to_string()),
+    );
+    *catalog.current.write().unwrap() = resolved.clone();
+    resolved
+}
+
+/// `key` formatted with `args`, in `locale` or the current locale
+#[tauri::command]
+pub fn translate(key: String, args: Option<HashMap<String, Value>>, locale: Option<String>) -> String {
+    let catalog = catalog();
+    let locale = match locale {
+        Some(locale) => catalog.resolve(&locale),
+        None => catalog.current.read().unwrap().clone(),
+    };
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args.iter().flatten() {
+        fluent_args.set(name.clone(), fluent_value(value));
+    }
+    catalog.translate(&locale, &key, Some(&fluent_args))
+}
+
+/// The catalog of `locale` (default: the current one) and, unless it is
+/// the fallback, the fallback catalog after it, for the frontend to format
+/// messages itself
+#[tauri::command]
+pub fn get_catalog(locale: Option<String>) -> Vec<CatalogSource> {
+    let catalog = catalog();
+    let locale = match locale {
+        Some(locale) => catalog.resolve(&locale),
+        None => catalog.current.read().unwrap().clone(),
+    };
+    let mut chain = vec![locale];
+    if chain[0] != FALLBACK_LOCALE {
+        chain.push(FALLBACK_LOCALE.to_string());
+    }
+    chain
+        .into_iter()
+        .filter_map(|locale| {
+            let source = catalog.locales.get(&locale)?.source.clone();
+            Some(CatalogSource { locale, source })
+        })
+        .collect()
+}
@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use tauri::{AppHandle, Runtime};
+
+use crate::benchmarks::{self, BenchmarkRequest};
+use crate::errors::CommandError;
+use crate::export::{self, ExportRequest};
+use crate::postprocess::{self, DenoiseRequest};
+use crate::projects;
+use crate::relink::{self, RelinkRequest};
+use crate::render_queue::{self, RenderRequest};
+use crate::settings::SettingsStore;
+use crate::storage::SharedStorage;
+use crate::units::{self, UnitAuditRequest};
+use crate::upgrade::{self, UpgradeRequest};
+use crate::workers::{self, Job, JobKind, WorkItem};
+
+/// Finished jobs kept in the journal; older ones are dropped as jobs finish.
+const MAX_FINISHED: usize = 100;
+
+/// What a job was submitted with, kept so its work can be rebuilt after a
+/// restart
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", content = "request", rename_all = "snake_case")]
+pub enum JobRequest {
+    Export(ExportRequest),
+    Upgrade(UpgradeRequest),
+    Render(RenderRequest),
+    Benchmark(BenchmarkRequest),
+    UnitAudit(UnitAuditRequest),
+    Relink(RelinkRequest),
+    Denoise(DenoiseRequest),
+}
+
+impl JobRequest {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            JobRequest::Export(_) => JobKind::Export,
+            JobRequest::Upgrade(_) => JobKind::Upgrade,
+            JobRequest::Render(_) => JobKind::Render,
+            JobRequest::Benchmark(_) => JobKind::Benchmark,
+            JobRequest::UnitAudit(_) => JobKind::UnitAudit,
+            JobRequest::Relink(_) => JobKind::Relink,
+            JobRequest::Denoise(_) => JobKind::Denoise,
+        }
+    }
+
+    /// Blender binary and work items, in the same order every time. The
+    /// active project's environment and path mappings are applied.
+    pub fn work(&self, settings: &SettingsStore) -> Result<(String, Vec<WorkItem>), CommandError> {
+        let workers = settings.get().workers;
+        let (blender, mut work) = match self {
+            JobRequest::Export(request) => (workers.blender_path, export::work(request)),
+            JobRequest::Upgrade(request) => (
+                workers::resolve_blender(&workers, request.blender.as_deref()),
+                upgrade::work(request),
+            ),
+            JobRequest::Render(request) => (
+                workers::resolve_blender(&workers, request.blender.as_deref()),
+                render_queue::work(settings, request)?,
+            ),
+            JobRequest::Benchmark(request) => (
+                workers::resolve_blender(&workers, request.blender.as_deref()),
+                benchmarks::work(settings, request)?,
+            ),
+            JobRequest::UnitAudit(request) => (
+                workers::resolve_blender(&workers, request.blender.as_deref()),
+                units::work(request),
+            ),
+            JobRequest::Relink(request) => (
+                workers::resolve_blender(&workers, request.blender.as_deref()),
+                relink::work(request),
+            ),
+            JobRequest::Denoise(request) => (
+                workers::resolve_blender(&workers, request.blender.as_deref()),
+                postprocess::work(request),
+            ),
+        };
+
+        if let Some(project) = projects::active(settings) {
+            for item in &mut work {
+                item.blend_file = item
+                    .blend_file
+                    .take()
+                    .map(|file| PathBuf::from(project.map_path(&file.to_string_lossy())));
+                item.env = project.env.clone();
+            }
+        }
+        Ok((blender, work))
+    }
+
+    /// Finish a job the way its start command does; returns the summary to
+    /// attach.
+    pub fn finished<R: Runtime>(&self, app: &AppHandle<R>, job: &Job) -> Option<Value> {
+        match self {
+            JobRequest::Export(request) => {
+                export::finish_export(request, job);
+                None
+            }
+            JobRequest::Upgrade(_) => None,
+            JobRequest::Render(request) => Some(render_queue::summarize(request, job)),
+            JobRequest::Benchmark(request) => Some(benchmarks::record(app, request, job)),
+            JobRequest::UnitAudit(request) => Some(units::summarize(request, job)),
+            JobRequest::Relink(request) => Some(relink::summarize(request, job)),
+            JobRequest::Denoise(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub job: Job,
+    pub request: JobRequest,
+    /// Started on its own rather than as a playlist step
+    #[serde(default)]
+    pub detached: bool,
+}
+
+/// Storage namespace; each job is one document, plus `index`
+const NAMESPACE: &str = "jobs";
+
+#[derive(Serialize, Deserialize, Clone)]
+struct IndexEntry {
+    id: String,
+    finished: bool,
+}
+
+/// Worker pool jobs in the `jobs` namespace of the storage backend, one
+/// document per job. Writes go to a thread of their own, so the pool never
+/// waits on the database.
+pub struct JobJournal {
+    storage: SharedStorage,
+    writes: mpsc::Sender<JournalEntry>,
+}
+
+impl JobJournal {
+    /// `legacy` is the `jobs.json` of earlier versions, imported once
+    pub fn open(storage: SharedStorage, legacy: &Path) -> Self {
+        let index: Vec<IndexEntry> = storage.get_json(NAMESPACE, "index").unwrap_or_default();
+        let (writes, pending) = mpsc::channel();
+        let journal = Self {
+            storage: storage.clone(),
+            writes,
+        };
+        if index.is_empty() {
+            journal.import(legacy);
+        }
+        std::thread::spawn(move || write_loop(storage, pending));
+        journal
+    }
+
+    fn import(&self, legacy: &Path) {
+        #[derive(Deserialize)]
+        struct LegacyFile {
+            jobs: Vec<JournalEntry>,
+        }
+        let Ok(text) = fs::read_to_string(legacy) else {
+            return;
+        };
+        match serde_json::from_str::<LegacyFile>(&text) {
+            Ok(file) => {
+                let mut index = Vec::new();
+                for entry in file.jobs {
+                    write_entry(&self.storage, &mut index, entry);
+                }
+                if let Err(err) = fs::rename(legacy, legacy.with_extension("json.migrated")) {
+                    eprintln!("Failed to rename {}: {err}", legacy.display());
+                }
+            }
+            Err(err) => eprintln!("Failed to import job journal {}: {err}", legacy.display()),
+        }
+    }
+
+    /// Jobs from the last session, oldest first
+    pub fn load(&self) -> Vec<JournalEntry> {
+        let index: Vec<IndexEntry> = self.storage.get_json(NAMESPACE, "index").unwrap_or_default();
+        let mut jobs: Vec<JournalEntry> = index
+            .iter()
+            .filter_map(|entry| self.storage.get_json(NAMESPACE, &entry.id))
+            .collect();
+        jobs.sort_by(|a, b| a.job.created_at.cmp(&b.job.created_at));
+        jobs
+    }
+
+    /// Queue a job's current state to be written. Item logs are left out,
+    /// since they are only for watching a running job.
+    pub fn write(&self, mut entry: JournalEntry) {
+        for item in &mut entry.job.items {
+            item.log.clear();
+        }
+        if self.writes.send(entry).is_err() {
+            eprintln!("Job journal writer stopped");
+        }
+    }
+}
+
+/// Write queued jobs until the journal is dropped. Jobs queued while a
+/// write was running are written once, in their latest state.
+fn write_loop(storage: SharedStorage, pending: mpsc::Receiver<JournalEntry>) {
+    let mut index: Vec<IndexEntry> = storage.get_json(NAMESPACE, "index").unwrap_or_default();
+    while let Ok(first) = pending.recv() {
+        let mut latest: Vec<JournalEntry> = vec![first];
+        for entry in pending.try_iter() {
+            latest.retain(|queued| queued.job.id != entry.job.id);
+            latest.push(entry);
+        }
+        for entry in latest {
+            write_entry(&storage, &mut index, entry);
+        }
+    }
+}
+
+/// Store one job and update the index, dropping the oldest finished jobs
+/// beyond MAX_FINISHED
+fn write_entry(storage: &SharedStorage, index: &mut Vec<IndexEntry>, entry: JournalEntry) {
+    let id = entry.job.id.clone();
+    let finished = entry.job.status.is_finished();
+    if let Err(err) = storage.put_json(NAMESPACE, &id, &entry) {
+        eprintln!("Failed to write job {id} to the journal: {err}");
+        return;
+    }
+    match index.iter().position(|known| known.id == id) {
+        Some(at) if index[at].finished == finished => return,
+        Some(at) => index[at].finished = finished,
+        None => index.push(IndexEntry { id, finished }),
+    }
+
+    let mut excess = index.iter().filter(|known| known.finished).count().saturating_sub(MAX_FINISHED);
+    index.retain(|known| {
+        if excess > 0 && known.finished {
+            excess -= 1;
+            if let Err(err) = storage.delete(NAMESPACE, &known.id) {
+                eprintln!("Failed to drop job {} from the journal: {err}", known.id);
+            }
+            return false;
+        }
+        true
+    });
+    if let Err(err) = storage.put_json(NAMESPACE, "index", &*index) {
+        eprintln!("Failed to write the job journal index: {err}");
+    }
+}
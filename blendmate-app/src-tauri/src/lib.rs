@@ -1,24 +1,110 @@
-use futures_util::{SinkExt, StreamExt};
+mod a11y;
+mod addon;
+mod assets;
+mod audio;
+mod benchmarks;
+mod bridge;
+mod caches;
+mod colormgmt;
+mod command_queue;
+mod conformance;
+mod console;
+mod cryptomatte;
+mod delivery;
+mod devices;
+mod diskguard;
+mod editor;
+mod errors;
+mod export;
+mod exr;
+mod faults;
+mod features;
+mod ffmpeg;
+mod health;
+mod history;
+mod i18n;
+mod journal;
+mod library;
+mod licenses;
+mod listeners;
+mod lookdev;
+mod macros;
+mod manifest;
+mod mirror;
+mod mode;
+mod naming;
+mod network;
+mod notes;
+mod outliner;
+mod palettes;
+mod placement;
+mod playlist;
+mod postprocess;
+mod power;
+mod preview;
+mod profiler;
+mod projects;
+mod protocol;
+mod quality;
+mod quick_actions;
+mod references;
+mod relink;
+mod render_logs;
+mod render_queue;
+mod report;
+mod review;
+mod safemode;
+mod scaffold;
+mod scheduler;
+mod scratch;
+mod search;
+mod secrets;
+mod settings;
+mod shares;
+mod shots;
+mod smart;
+mod snapshot;
+mod storage;
+mod surfaces;
+mod sync;
+mod tagging;
+mod tasks;
+mod timelapse;
+mod timesync;
+mod trackers;
+mod triggers;
+mod units;
+mod upgrade;
+mod validation;
+mod warnings;
+mod watch;
+mod watchdog;
+mod workers;
+
 use std::sync::Arc;
 use std::fs;
 use std::path::Path;
 use std::process::Stdio;
-use tokio::net::TcpListener;
 use tokio::sync::Mutex;
 use tokio::process::Command;
 use tokio::io::AsyncWriteExt;
-use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
-use tauri::{Emitter, State};
+use tauri::{Manager, State};
 use serde::Serialize;
 
-type WsConnection = Arc<Mutex<Option<futures_util::stream::SplitSink<WebSocketStream<tokio::net::TcpStream>, Message>>>>;
+use bridge::{BlenderBridge, WsConnection};
+use errors::CommandError;
+use history::HistoryStore;
+use mirror::SceneMirror;
+use network::Session;
+use settings::SettingsStore;
 
 struct AppState {
     ws_sender: WsConnection,
+    bridge: Arc<BlenderBridge>,
+    mirror: Arc<SceneMirror>,
+    session: Arc<Session>,
 }
 
-const WS_ADDRESS: &str = "127.0.0.1:32123";
-
 #[derive(Serialize)]
 struct FileInfo {
     path: String,
@@ -46,7 +132,7 @@ fn format_size(bytes: u64) -> String {
 
 /// Ask Claude CLI for AI response (uses pipe mode)
 #[tauri::command]
-async fn ask_claude(prompt: String, system_prompt: String) -> Result<String, String> {
+async fn ask_claude(prompt: String, system_prompt: String) -> Result<String, CommandError> {
     // Build the full prompt with system context
     let full_prompt = if system_prompt.is_empty() {
         prompt
@@ -60,7 +146,7 @@ async fn ask_claude(prompt: String, system_prompt: String) -> Result<String, Str
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .map_err(|e| format!("Failed to spawn claude CLI: {}. Is it installed?", e))?;
+        .map_err(|e| CommandError::Unsupported(format!("Failed to spawn claude CLI: {}. Is it installed?", e)))?;
 
     // Write prompt to stdin
     if let Some(mut stdin) = child.stdin.take() {
@@ -78,23 +164,23 @@ async fn ask_claude(prompt: String, system_prompt: String) -> Result<String, Str
 
     if output.status.success() {
         String::from_utf8(output.stdout)
-            .map_err(|e| format!("Invalid UTF-8 in response: {}", e))
+            .map_err(|e| CommandError::Failed(format!("Invalid UTF-8 in response: {}", e)))
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Claude CLI error: {}", stderr))
+        Err(CommandError::Failed(format!("Claude CLI error: {}", stderr)))
     }
 }
 
 /// Get file metadata for display
 #[tauri::command]
-fn get_file_info(path: String) -> Result<FileInfo, String> {
+fn get_file_info(path: String) -> Result<FileInfo, CommandError> {
     let file_path = Path::new(&path);
 
     if !file_path.exists() {
-        return Err("File not found".to_string());
+        return Err(CommandError::NotFound("File not found".to_string()));
     }
 
-    let metadata = fs::metadata(&path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+    let metadata = fs::metadata(&path).map_err(|e| CommandError::Io(format!("Failed to read metadata: {}", e)))?;
     let size_bytes = metadata.len();
 
     let modified = metadata.modified().ok().map(|time| {
@@ -119,116 +205,369 @@ fn get_file_info(path: String) -> Result<FileInfo, String> {
 
 /// Send a message to Blender addon via WebSocket
 #[tauri::command]
-async fn send_to_blender(message: String, state: State<'_, AppState>) -> Result<(), String> {
-    let mut sender_guard = state.ws_sender.lock().await;
-    if let Some(sender) = sender_guard.as_mut() {
-        sender
-            .send(Message::Text(message))
-            .await
-            .map_err(|e| format!("Failed to send: {}", e))?;
-        Ok(())
-    } else {
-        Err("No WebSocket connection".to_string())
-    }
-}
-
-fn start_websocket_server<R: tauri::Runtime>(
-    app_handle: tauri::AppHandle<R>,
-    ws_sender: WsConnection,
-) {
-    tauri::async_runtime::spawn(async move {
-        let listener = match TcpListener::bind(WS_ADDRESS).await {
-            Ok(listener) => listener,
-            Err(err) => {
-                eprintln!("Failed to bind WebSocket listener on {WS_ADDRESS}: {err}");
-                return;
-            }
-        };
-
-        loop {
-            let (stream, _) = match listener.accept().await {
-                Ok(connection) => connection,
-                Err(err) => {
-                    eprintln!("WebSocket accept error: {err}");
-                    continue;
-                }
-            };
-
-            let app_handle = app_handle.clone();
-            let ws_sender = ws_sender.clone();
-
-            tauri::async_runtime::spawn(async move {
-                match accept_async(stream).await {
-                    Ok(websocket) => {
-                        let (sender, mut receiver) = websocket.split();
-
-                        // Store sender for outgoing messages
-                        {
-                            let mut sender_guard = ws_sender.lock().await;
-                            *sender_guard = Some(sender);
-                        }
-
-                        if let Err(err) = app_handle.emit("ws:status", "connected") {
-                            eprintln!("Failed to emit ws:status connected: {err}");
-                        }
-
-                        // Read incoming messages
-                        while let Some(message_result) = receiver.next().await {
-                            match message_result {
-                                Ok(Message::Text(text)) => {
-                                    if let Err(err) = app_handle.emit("ws:message", text) {
-                                        eprintln!("Failed to emit ws:message: {err}");
-                                        break;
-                                    }
-                                }
-                                Ok(Message::Close(_)) => {
-                                    break;
-                                }
-                                Ok(_) => {}
-                                Err(err) => {
-                                    eprintln!("WebSocket read error: {err}");
-                                    break;
-                                }
-                            }
-                        }
-
-                        // Clear sender on disconnect
-                        {
-                            let mut sender_guard = ws_sender.lock().await;
-                            *sender_guard = None;
-                        }
-
-                        if let Err(err) = app_handle.emit("ws:status", "disconnected") {
-                            eprintln!("Failed to emit ws:status disconnected: {err}");
-                        }
-                    }
-                    Err(err) => {
-                        eprintln!("WebSocket handshake error: {err}");
-
-                        if let Err(err) = app_handle.emit("ws:status", "disconnected") {
-                            eprintln!("Failed to emit ws:status disconnected: {err}");
-                        }
-                    }
-                }
-            });
-        }
-    });
+async fn send_to_blender(
+    message: String,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<(), CommandError> {
+    state.bridge.send_frame(message.clone()).await?;
+    history.record_message(&settings, &message);
+    Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let ws_sender: WsConnection = Arc::new(Mutex::new(None));
+    let bridge = Arc::new(BlenderBridge::new(ws_sender.clone()));
+    let mirror = Arc::new(SceneMirror::default());
+    let session = Arc::new(Session::default());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .manage(AppState {
             ws_sender: ws_sender.clone(),
+            bridge: bridge.clone(),
+            mirror: mirror.clone(),
+            session: session.clone(),
         })
+        .manage(lookdev::LookdevState::default())
+        .manage(naming::NamingState::default())
+        .manage(library::LibraryState::default())
+        .manage(caches::CacheState::default())
+        .manage(power::PowerState::default())
+        .manage(preview::PreviewState::default())
+        .manage(listeners::ListenerSupervisor::default())
+        .manage(addon::AddonDevState::default())
+        .manage(tasks::TaskManager::default())
+        .manage(search::SceneSearch::default())
+        .manage(smart::SmartCollectionState::default())
+        .manage(watch::WatchState::default())
+        .manage(triggers::TriggerState::default())
+        .manage(profiler::PlaybackProfiler::default())
+        .manage(shots::ShotState::default())
+        .manage(trackers::TrackerState::default())
+        .manage(audio::AudioState::default())
+        .manage(watchdog::Watchdog::default())
+        .manage(diskguard::DiskGuard::default())
+        .manage(shares::ShareMonitor::default())
+        .manage(snapshot::RecentEvents::default())
+        .manage(outliner::OutlinerState::default())
+        .manage(a11y::Announcer::default())
+        .manage(health::HealthMonitor::default())
+        .manage(tagging::TagState::default())
+        .manage(quick_actions::QuickActionState::default())
+        .manage(relink::RelinkState::default())
+        .manage(editor::EditorState::default())
+        .manage(delivery::DeliveryState::default())
+        .manage(console::ConsoleState::default())
+        .manage(warnings::WarningStore::default())
+        .manage(sync::SyncState::default())
+        .manage(timesync::TimeSync::default())
+        .manage(features::FeatureRegistry::default())
+        .manage(mode::ModeState::default())
         .setup(move |app| {
-            start_websocket_server(app.handle().clone(), ws_sender.clone());
+            let config_dir = app.path().app_config_dir()?;
+            let settings = SettingsStore::load(config_dir.join("settings.json"));
+            let locales = app.path().resource_dir().ok().map(|dir| dir.join("locales"));
+            i18n::init(locales.as_deref(), settings.get().locale);
+            let data_dir = app.path().app_data_dir()?;
+            let storage = storage::open(&settings.get().storage, &data_dir);
+            let shared = storage.storage.clone();
+            app.manage(settings);
+            app.manage(exr::ExrStore::new(shared.clone()));
+            app.manage(render_logs::RenderLogs::new(data_dir.join("logs"), shared.clone()));
+            app.manage(review::ReviewStore::new(shared.clone()));
+            app.manage(surfaces::SurfaceState::new(shared.clone()));
+            app.manage(references::ReferenceStore::new(data_dir.join("references")));
+            app.manage(scaffold::TemplateLibrary::new(data_dir.join("templates")));
+            app.manage(timelapse::TimelapseState::new(data_dir.join("timelapse")));
+            app.manage(history::HistoryStore::load(shared.clone(), &data_dir.join("history.json")));
+            app.manage(storage);
+            app.manage(scratch::ScratchDirs::new(app.path().app_cache_dir()?.join("scratch")));
+
+            // The core: settings, storage, command history, the stores above,
+            // which only hold paths, and the WebSocket server. Safe mode
+            // leaves out the rest.
+            command_queue::forward_acks(app.handle().clone());
+            listeners::start(app.handle().clone());
+            let safe_mode_flag = std::env::args().any(|arg| arg == safemode::SAFE_MODE_FLAG);
+            let context = safemode::StartupContext {
+                config_dir,
+                data_dir,
+                storage: shared,
+            };
+            let startup = safemode::StartupState::begin(context, safe_mode_flag);
+            startup.start_all(app.handle());
+            app.manage(startup);
+            safemode::watch_startup(app.handle().clone());
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![send_to_blender, get_file_info, ask_claude])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(placement::on_window_event)
+        // Listed by name in `manifest::COMMANDS` as well
+        .invoke_handler(tauri::generate_handler![
+            send_to_blender,
+            get_file_info,
+            ask_claude,
+            lookdev::isolate_objects,
+            lookdev::hide_collection,
+            lookdev::solo_light,
+            lookdev::restore_lookdev,
+            validation::validate_scene,
+            validation::list_validation_profiles,
+            validation::save_validation_profile,
+            naming::get_naming_convention,
+            naming::set_naming_convention,
+            naming::audit_naming,
+            naming::apply_rename_plan,
+            library::get_library_graph,
+            assets::refresh_asset_index,
+            assets::get_asset_index,
+            assets::append_asset,
+            assets::apply_pose,
+            workers::list_jobs,
+            workers::get_job,
+            workers::cancel_job,
+            workers::resume_job,
+            workers::get_worker_settings,
+            workers::set_worker_settings,
+            workers::list_blender_installs,
+            export::start_export_job,
+            upgrade::start_upgrade_job,
+            playlist::list_playlists,
+            playlist::save_playlist,
+            playlist::delete_playlist,
+            playlist::list_playlist_runs,
+            playlist::start_playlist,
+            playlist::resume_playlist_run,
+            playlist::cancel_playlist_run,
+            caches::list_caches,
+            caches::delete_cache,
+            caches::relocate_cache,
+            devices::get_render_devices,
+            devices::set_render_devices,
+            devices::apply_device_profile,
+            devices::list_device_profiles,
+            devices::save_device_profile,
+            colormgmt::inspect_color_management,
+            colormgmt::get_color_standard,
+            colormgmt::set_color_standard,
+            colormgmt::apply_color_standard,
+            render_queue::plan_render_chunks,
+            render_queue::start_render_job,
+            power::get_power_status,
+            power::get_power_settings,
+            power::set_power_settings,
+            network::get_network_settings,
+            network::set_network_settings,
+            preview::start_preview,
+            preview::stop_preview,
+            quality::get_connection_quality,
+            listeners::get_listeners,
+            listeners::set_listeners,
+            listeners::list_listeners,
+            addon::reload_addon,
+            addon::get_addon_dev_settings,
+            addon::set_addon_dev_settings,
+            addon::get_addon_dev_status,
+            conformance::run_conformance,
+            faults::set_fault_injection,
+            faults::get_fault_injection,
+            manifest::get_command_manifest,
+            tasks::list_tasks,
+            tasks::cancel_task,
+            search::search_scene,
+            smart::list_smart_collections,
+            smart::save_smart_collection,
+            smart::delete_smart_collection,
+            smart::get_smart_collection_members,
+            smart::materialize_smart_collection,
+            watch::watch_property,
+            watch::unwatch_property,
+            watch::list_property_watches,
+            triggers::list_custom_triggers,
+            projects::get_project_settings,
+            projects::save_project_profile,
+            projects::delete_project_profile,
+            projects::set_active_project,
+            projects::export_project_profile,
+            projects::import_project_profile,
+            projects::resolve_project_path,
+            benchmarks::start_benchmark_job,
+            benchmarks::list_benchmark_results,
+            benchmarks::compare_benchmark_runs,
+            benchmarks::clear_benchmark_results,
+            profiler::get_playback_profile,
+            profiler::reset_playback_profile,
+            shots::list_shots,
+            shots::save_shot,
+            shots::delete_shot,
+            shots::reorder_shots,
+            shots::jump_to_shot,
+            shots::start_shot_render,
+            secrets::set_secret,
+            secrets::delete_secret,
+            secrets::has_secret,
+            trackers::get_tracker_settings,
+            trackers::set_tracker_settings,
+            trackers::kitsu_list_shots,
+            trackers::kitsu_set_task_status,
+            trackers::kitsu_push_shots,
+            trackers::flamenco_submit_render,
+            trackers::flamenco_submit_shot,
+            trackers::flamenco_get_job,
+            audio::list_audio_strips,
+            audio::get_audio_peaks,
+            audio::set_audio_options,
+            references::list_references,
+            references::import_reference,
+            references::download_reference,
+            references::remove_reference,
+            references::push_reference,
+            ffmpeg::check_ffmpeg,
+            ffmpeg::get_ffmpeg_settings,
+            ffmpeg::set_ffmpeg_settings,
+            timelapse::start_timelapse,
+            timelapse::stop_timelapse,
+            timelapse::list_timelapses,
+            timelapse::assemble_timelapse,
+            timelapse::delete_timelapse,
+            timelapse::get_timelapse_settings,
+            timelapse::set_timelapse_settings,
+            palettes::list_palettes,
+            palettes::save_palette,
+            palettes::delete_palette,
+            palettes::convert_color,
+            palettes::extract_palette,
+            palettes::push_color,
+            units::start_unit_audit,
+            licenses::list_licenses,
+            licenses::set_license,
+            licenses::remove_license,
+            licenses::scan_license_files,
+            licenses::check_scene_licenses,
+            licenses::write_license_manifest,
+            licenses::get_license_settings,
+            licenses::set_license_settings,
+            watchdog::get_blender_activity,
+            diskguard::get_disk_status,
+            diskguard::get_disk_guard_settings,
+            diskguard::set_disk_guard_settings,
+            shares::get_share_status,
+            shares::get_share_settings,
+            shares::set_share_settings,
+            scratch::get_scratch_usage,
+            scratch::get_scratch_settings,
+            scratch::set_scratch_settings,
+            history::list_history,
+            history::repeat_last,
+            history::repeat_history,
+            history::recall_params,
+            history::list_favorites,
+            history::pin_favorite,
+            history::unpin_favorite,
+            history::run_favorite,
+            macros::list_macros,
+            macros::save_macro,
+            macros::delete_macro,
+            macros::start_macro_recording,
+            macros::get_macro_recording,
+            macros::stop_macro_recording,
+            macros::cancel_macro_recording,
+            macros::run_macro,
+            macros::get_macro_settings,
+            macros::set_macro_settings,
+            snapshot::get_app_snapshot,
+            outliner::sync_outliner,
+            outliner::get_outliner_page,
+            i18n::list_locales,
+            i18n::get_locale,
+            i18n::set_locale,
+            i18n::translate,
+            i18n::get_catalog,
+            a11y::list_announcements,
+            a11y::get_a11y_settings,
+            a11y::set_a11y_settings,
+            report::export_report,
+            storage::get_storage_status,
+            storage::get_storage_settings,
+            storage::set_storage_settings,
+            health::get_blender_health,
+            health::get_health_settings,
+            health::set_health_settings,
+            tagging::get_session_tags,
+            tagging::test_tag_patterns,
+            tagging::get_tagging_settings,
+            tagging::set_tagging_settings,
+            quick_actions::plan_quick_action,
+            quick_actions::apply_quick_action,
+            relink::plan_relink,
+            relink::apply_relink,
+            relink::start_relink_job,
+            command_queue::get_command_queue,
+            editor::open_in_editor,
+            editor::list_editor_sessions,
+            editor::close_in_editor,
+            editor::get_editor_settings,
+            editor::set_editor_settings,
+            notes::list_notes,
+            notes::save_note,
+            notes::delete_note,
+            placement::open_panel_window,
+            placement::reset_window_placement,
+            delivery::get_delivery_status,
+            delivery::get_delivery_manifest,
+            delivery::retry_delivery,
+            console::console_exec,
+            console::get_console_history,
+            console::clear_console,
+            warnings::get_warnings,
+            warnings::list_warning_sessions,
+            warnings::mark_warnings_seen,
+            scaffold::list_project_templates,
+            scaffold::save_project_template,
+            scaffold::delete_project_template,
+            scaffold::export_project_template,
+            scaffold::import_project_template,
+            scaffold::create_project_from_template,
+            sync::export_settings_bundle,
+            sync::import_settings_bundle,
+            sync::get_sync_settings,
+            sync::set_sync_settings,
+            sync::get_sync_status,
+            timesync::get_time_sync_settings,
+            timesync::set_time_sync_settings,
+            timesync::get_time_sync_metrics,
+            timesync::reset_time_sync_metrics,
+            timesync::list_midi_ports,
+            features::list_features,
+            features::set_feature_enabled,
+            mode::get_mode,
+            exr::get_render_metadata,
+            exr::read_exr_metadata,
+            exr::check_render_passes,
+            cryptomatte::list_matte_names,
+            cryptomatte::composite_matte_preview,
+            render_logs::search_render_logs,
+            render_logs::get_render_log,
+            render_logs::import_render_log,
+            review::list_review_notes,
+            review::save_review_note,
+            review::delete_review_note,
+            review::import_review_markers,
+            review::sync_review_markers,
+            surfaces::list_control_surfaces,
+            surfaces::refresh_control_surfaces,
+            surfaces::get_surface_profile,
+            surfaces::save_surface_profile,
+            surfaces::learn_surface_control,
+            surfaces::set_control_state,
+            safemode::get_startup_report,
+            safemode::enable_subsystem,
+            safemode::leave_safe_mode,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(safemode::on_run_event);
 }
@@ -0,0 +1,225 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::AppState;
+
+/// Node id of the open .blend itself
+const ROOT_ID: &str = "file";
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryStatus {
+    Ok,
+    Missing,
+    Outdated,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// `from` links datablocks out of `to`
+    Link,
+    /// `from` holds library overrides of datablocks in `to`
+    Override,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LibraryNode {
+    pub id: String,
+    pub filepath: String,
+    pub status: LibraryStatus,
+    pub issues: Vec<String>,
+    /// Linked datablock count per `bpy.data` collection
+    pub linked_ids: BTreeMap<String, u64>,
+    pub missing_ids: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LibraryEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    pub count: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LibraryGraph {
+    pub filepath: String,
+    pub nodes: Vec<LibraryNode>,
+    pub edges: Vec<LibraryEdge>,
+    pub missing: Vec<String>,
+    pub outdated: Vec<String>,
+}
+
+/// Graph cached per open file (see `SceneMirror::file_generation`).
+#[derive(Default)]
+pub struct LibraryState {
+    cached: Mutex<Option<(u64, LibraryGraph)>>,
+}
+
+impl LibraryState {
+    /// Drop the cached graph after the libraries were changed
+    pub async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+}
+
+/// Libraries the open file pulls in, directly or through other libraries
+#[tauri::command]
+pub async fn get_library_graph(
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+    library: State<'_, LibraryState>,
+) -> Result<LibraryGraph, CommandError> {
+    let generation = state.mirror.file_generation().await;
+    let mut cached = library.cached.lock().await;
+
+    if !refresh.unwrap_or(false) {
+        if let Some((cached_generation, graph)) = cached.as_ref() {
+            if *cached_generation == generation {
+                return Ok(graph.clone());
+            }
+        }
+    }
+
+    let links = state.bridge.request("library.get_links", "", json!({})).await?;
+    let graph = build_graph(&links);
+    *cached = Some((generation, graph.clone()));
+    Ok(graph)
+}
+
+fn build_graph(links: &Value) -> LibraryGraph {
+    let filepath = str_field(links, "filepath").to_string();
+    let loaded_at = links.get("loaded_at").and_then(Value::as_f64);
+    let blender_version = version(links.get("blender_version"));
+
+    let mut nodes = vec![LibraryNode {
+        id: ROOT_ID.to_string(),
+        filepath: filepath.clone(),
+        status: LibraryStatus::Ok,
+        issues: Vec::new(),
+        linked_ids: BTreeMap::new(),
+        missing_ids: 0,
+    }];
+    let mut edges = Vec::new();
+
+    for lib in array(links, "libraries") {
+        let id = str_field(lib, "name").to_string();
+        let mut issues = Vec::new();
+        let mut status = LibraryStatus::Ok;
+
+        let missing_ids = lib.get("missing_ids").and_then(Value::as_u64).unwrap_or(0);
+        if lib.get("exists").and_then(Value::as_bool) == Some(false) {
+            issues.push(format!("File not found: {}", str_field(lib, "abspath")));
+            status = LibraryStatus::Missing;
+        } else if missing_ids > 0 {
+            issues.push(format!("{} linked datablocks are missing", missing_ids));
+            status = LibraryStatus::Missing;
+        }
+
+        let mtime = lib.get("mtime").and_then(Value::as_f64);
+        if let (Some(mtime), Some(loaded_at)) = (mtime, loaded_at) {
+            if mtime > loaded_at {
+                issues.push("Changed on disk since the file was opened; reload to pick it up".to_string());
+                if status == LibraryStatus::Ok {
+                    status = LibraryStatus::Outdated;
+                }
+            }
+        }
+
+        if let (Some(lib_version), Some(current)) = (version(lib.get("version")), blender_version) {
+            if lib_version < current {
+                issues.push(format!(
+                    "Saved with Blender {}.{}, current is {}.{}",
+                    lib_version.0, lib_version.1, current.0, current.1
+                ));
+                if status == LibraryStatus::Ok {
+                    status = LibraryStatus::Outdated;
+                }
+            }
+        }
+
+        let linked_ids: BTreeMap<String, u64> = lib
+            .get("linked_ids")
+            .and_then(Value::as_object)
+            .map(|counts| {
+                counts
+                    .iter()
+                    .map(|(collection, count)| (collection.clone(), count.as_u64().unwrap_or(0)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let parent = lib
+            .get("parent")
+            .and_then(Value::as_str)
+            .unwrap_or(ROOT_ID)
+            .to_string();
+        edges.push(LibraryEdge {
+            from: parent,
+            to: id.clone(),
+            kind: EdgeKind::Link,
+            count: linked_ids.values().sum(),
+        });
+
+        nodes.push(LibraryNode {
+            id,
+            filepath: str_field(lib, "filepath").to_string(),
+            status,
+            issues,
+            linked_ids,
+            missing_ids,
+        });
+    }
+
+    let mut override_counts: BTreeMap<String, u64> = BTreeMap::new();
+    for override_entry in array(links, "overrides") {
+        if let Some(library) = override_entry.get("reference_library").and_then(Value::as_str) {
+            *override_counts.entry(library.to_string()).or_default() += 1;
+        }
+    }
+    for (library, count) in override_counts {
+        edges.push(LibraryEdge {
+            from: ROOT_ID.to_string(),
+            to: library,
+            kind: EdgeKind::Override,
+            count,
+        });
+    }
+
+    let ids_with = |wanted: LibraryStatus| {
+        nodes
+            .iter()
+            .filter(|node| node.status == wanted)
+            .map(|node| node.id.clone())
+            .collect::<Vec<_>>()
+    };
+    let missing = ids_with(LibraryStatus::Missing);
+    let outdated = ids_with(LibraryStatus::Outdated);
+
+    LibraryGraph {
+        filepath,
+        nodes,
+        edges,
+        missing,
+        outdated,
+    }
+}
+
+pub fn array<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value.get(key).and_then(Value::as_array).into_iter().flatten()
+}
+
+pub fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+/// `(major, minor)` from a `[major, minor, patch]` array
+fn version(value: Option<&Value>) -> Option<(u64, u64)> {
+    let parts = value?.as_array()?;
+    Some((parts.first()?.as_u64()?, parts.get(1)?.as_u64()?))
+}
@@ -0,0 +1,470 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::assets::AssetState;
+use crate::errors::CommandError;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Only the start of a license file is read to recognize it
+const MAX_LICENSE_BYTES: usize = 64 * 1024;
+
+/// Phrases that identify a license, checked in order. More specific
+/// licenses come before the ones their text contains.
+const KNOWN_LICENSES: &[(&str, &str)] = &[
+    ("attribution-noncommercial-noderivatives 4.0", "CC-BY-NC-ND-4.0"),
+    ("attribution-noncommercial-sharealike 4.0", "CC-BY-NC-SA-4.0"),
+    ("attribution-noncommercial 4.0", "CC-BY-NC-4.0"),
+    ("attribution-noderivatives 4.0", "CC-BY-ND-4.0"),
+    ("attribution-sharealike 4.0", "CC-BY-SA-4.0"),
+    ("attribution 4.0 international", "CC-BY-4.0"),
+    ("cc0 1.0", "CC0-1.0"),
+    ("creative commons zero", "CC0-1.0"),
+    ("sil open font license", "OFL-1.1"),
+    ("ubuntu font licence", "Ubuntu-font-1.0"),
+    ("apache license", "Apache-2.0"),
+    ("gnu general public license", "GPL-3.0-or-later"),
+    ("mit license", "MIT"),
+    ("permission is hereby granted, free of charge", "MIT"),
+];
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LicenseSettings {
+    /// Licenses accepted without review, as SPDX ids
+    pub allowed: Vec<String>,
+    /// Licenses that must not end up in delivered work
+    pub restricted: Vec<String>,
+}
+
+impl Default for LicenseSettings {
+    fn default() -> Self {
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect();
+        Self {
+            allowed: ids(&["CC0-1.0", "CC-BY-4.0", "CC-BY-SA-4.0", "MIT", "Apache-2.0", "OFL-1.1"]),
+            restricted: ids(&["CC-BY-NC-4.0", "CC-BY-NC-SA-4.0", "CC-BY-NC-ND-4.0", "CC-BY-ND-4.0"]),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseSource {
+    /// Entered by a user
+    User,
+    /// Recognized in a license file by `scan_license_files`
+    File,
+}
+
+/// License of a file, of everything below a directory, or of one asset in
+/// a .blend file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LicenseRecord {
+    pub path: String,
+    /// With `name`, limits the record to one asset of the .blend at `path`
+    #[serde(default)]
+    pub id_type: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// SPDX id where there is one, else free text
+    pub license: String,
+    #[serde(default)]
+    pub attribution: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    pub source: LicenseSource,
+    /// File the license was recognized in
+    #[serde(default)]
+    pub license_file: Option<String>,
+}
+
+impl LicenseRecord {
+    fn same_target(&self, other: &LicenseRecord) -> bool {
+        self.path == other.path && self.id_type == other.id_type && self.name == other.name
+    }
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseStatus {
+    Allowed,
+    /// Known, but neither allowed nor restricted by the settings
+    Review,
+    Restricted,
+    Unknown,
+}
+
+/// Something the open file uses and its license
+#[derive(Serialize, Debug)]
+pub struct LicenseUsage {
+    /// `asset`, `font`, `image` or `sound`
+    pub kind: String,
+    pub name: String,
+    pub filepath: String,
+    pub id_type: Option<String>,
+    pub packed: bool,
+    pub license: Option<String>,
+    pub attribution: Option<String>,
+    pub url: Option<String>,
+    pub status: LicenseStatus,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LicenseReport {
+    pub filepath: String,
+    pub items: Vec<LicenseUsage>,
+    /// Items with an unknown or restricted license
+    pub flagged: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LicenseScan {
+    pub files_read: usize,
+    pub detected: usize,
+    /// License files whose license wasn't recognized
+    pub unrecognized: Vec<String>,
+}
+
+/// License records, persisted as `licenses.json` in the app data directory
+/// after every change.
+pub struct LicenseStore {
+    path: PathBuf,
+    records: Mutex<Vec<LicenseRecord>>,
+}
+
+impl LicenseStore {
+    pub fn load(path: PathBuf) -> Self {
+        let records = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            records: Mutex::new(records),
+        }
+    }
+
+    fn update<T>(&self, change: impl FnOnce(&mut Vec<LicenseRecord>) -> T) -> Result<T, CommandError> {
+        let mut records = self.records.lock().unwrap();
+        let result = change(&mut records);
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| CommandError::Io(format!("Failed to create data directory: {}", e)))?;
+        }
+        let text =
+            serde_json::to_string_pretty(&*records).map_err(|e| format!("Failed to serialize licenses: {}", e))?;
+        fs::write(&self.path, text)
+            .map_err(|e| CommandError::Io(format!("Failed to write licenses: {}", e)))?;
+        Ok(result)
+    }
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_string()
+}
+
+/// Most specific record for a file or asset: the asset itself, then the
+/// file, then the closest directory above it
+fn lookup<'a>(
+    records: &'a [LicenseRecord],
+    filepath: &str,
+    id_type: Option<&str>,
+    name: Option<&str>,
+) -> Option<&'a LicenseRecord> {
+    let filepath = normalize(filepath);
+    let asset = records.iter().find(|r| {
+        name.is_some() && normalize(&r.path) == filepath && r.id_type.as_deref() == id_type && r.name.as_deref() == name
+    });
+    let file = || records.iter().find(|r| r.name.is_none() && normalize(&r.path) == filepath);
+    let directory = || {
+        records
+            .iter()
+            .filter(|r| r.name.is_none() && filepath.starts_with(&format!("{}/", normalize(&r.path))))
+            .max_by_key(|r| r.path.len())
+    };
+    asset.or_else(file).or_else(directory)
+}
+
+fn status(settings: &LicenseSettings, license: Option<&str>) -> LicenseStatus {
+    match license {
+        None => LicenseStatus::Unknown,
+        Some(license) if settings.restricted.iter().any(|id| id.eq_ignore_ascii_case(license)) => {
+            LicenseStatus::Restricted
+        }
+        Some(license) if settings.allowed.iter().any(|id| id.eq_ignore_ascii_case(license)) => LicenseStatus::Allowed,
+        Some(_) => LicenseStatus::Review,
+    }
+}
+
+/// SPDX id of a license text: its `SPDX-License-Identifier` line, else the
+/// first known phrase it contains
+fn detect(text: &str) -> Option<String> {
+    for line in text.lines() {
+        if let Some((_, id)) = line.split_once("SPDX-License-Identifier:") {
+            return Some(id.trim().to_string());
+        }
+    }
+    let text = text.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ");
+    KNOWN_LICENSES
+        .iter()
+        .find(|(phrase, _)| text.contains(phrase))
+        .map(|(_, id)| id.to_string())
+}
+
+/// What a license file covers: `name.ext.license` covers `name.ext`,
+/// `LICENSE*`, `LICENCE*` and `COPYING*` cover their directory
+fn license_target(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if let Some(covered) = name.strip_suffix(".license") {
+        if covered.contains('.') {
+            return Some(path.with_extension(""));
+        }
+    }
+    if ["license", "licence", "copying"].iter().any(|prefix| name.starts_with(prefix)) {
+        return path.parent().map(Path::to_path_buf);
+    }
+    None
+}
+
+/// License files below `roots`, with what each one covers
+fn find_license_files(roots: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    let mut found = Vec::new();
+    let mut pending = roots.to_vec();
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Some(target) = license_target(&path) {
+                found.push((path, target));
+            }
+        }
+    }
+    found
+}
+
+#[tauri::command]
+pub fn list_licenses(licenses: State<'_, LicenseStore>) -> Vec<LicenseRecord> {
+    licenses.records.lock().unwrap().clone()
+}
+
+/// Set the license of a file, directory or asset, replacing any record
+/// for the same target
+#[tauri::command]
+pub fn set_license(
+    mut record: LicenseRecord,
+    licenses: State<'_, LicenseStore>,
+) -> Result<Vec<LicenseRecord>, CommandError> {
+    if record.license.trim().is_empty() {
+        return Err(CommandError::InvalidInput("License is required".to_string()));
+    }
+    if record.id_type.is_some() != record.name.is_some() {
+        return Err(CommandError::InvalidInput("An asset needs both its type and name".to_string()));
+    }
+    record.path = normalize(&record.path);
+    licenses.update(|records| {
+        records.retain(|r| !r.same_target(&record));
+        records.push(record);
+        records.clone()
+    })
+}
+
+#[tauri::command]
+pub fn remove_license(
+    path: String,
+    id_type: Option<String>,
+    name: Option<String>,
+    licenses: State<'_, LicenseStore>,
+) -> Result<Vec<LicenseRecord>, CommandError> {
+    let path = normalize(&path);
+    licenses.update(|records| {
+        records.retain(|r| !(r.path == path && r.id_type == id_type && r.name == name));
+        records.clone()
+    })
+}
+
+/// Recognize license files below `directory`, or below every indexed asset
+/// library. Recognized licenses are recorded; licenses entered by a user
+/// are never replaced.
+#[tauri::command]
+pub async fn scan_license_files(
+    directory: Option<String>,
+    licenses: State<'_, LicenseStore>,
+    assets: State<'_, AssetState>,
+) -> Result<LicenseScan, CommandError> {
+    let roots: Vec<PathBuf> = match directory {
+        Some(directory) => vec![PathBuf::from(directory)],
+        None => assets.libraries().await.into_iter().map(|l| PathBuf::from(l.path)).collect(),
+    };
+    if roots.is_empty() {
+        return Err(CommandError::InvalidInput(
+            "No directory given and no asset libraries indexed".to_string(),
+        ));
+    }
+
+    let (found, unrecognized, files_read) = tauri::async_runtime::spawn_blocking(move || {
+        let files = find_license_files(&roots);
+        let mut found = Vec::new();
+        let mut unrecognized = Vec::new();
+        for (file, target) in &files {
+            let Ok(bytes) = fs::read(file) else {
+                continue;
+            };
+            let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_LICENSE_BYTES)]);
+            match detect(&text) {
+                Some(license) => found.push(LicenseRecord {
+                    path: normalize(&target.to_string_lossy()),
+                    id_type: None,
+                    name: None,
+                    license,
+                    attribution: None,
+                    url: None,
+                    source: LicenseSource::File,
+                    license_file: Some(file.to_string_lossy().into_owned()),
+                }),
+                None => unrecognized.push(file.to_string_lossy().into_owned()),
+            }
+        }
+        (found, unrecognized, files.len())
+    })
+    .await
+    .map_err(|e| format!("License scan failed: {}", e))?;
+
+    let detected = found.len();
+    licenses.update(|records| {
+        for record in found {
+            let user_set = records
+                .iter()
+                .any(|r| r.same_target(&record) && r.source == LicenseSource::User);
+            if !user_set {
+                records.retain(|r| !r.same_target(&record));
+                records.push(record);
+            }
+        }
+    })?;
+    Ok(LicenseScan {
+        files_read,
+        detected,
+        unrecognized,
+    })
+}
+
+async fn check(
+    state: &AppState,
+    settings: &SettingsStore,
+    licenses: &LicenseStore,
+) -> Result<LicenseReport, CommandError> {
+    let data = state.bridge.request("licenses.used", "", serde_json::json!({})).await?;
+    let policy = settings.get().licenses;
+    let records = licenses.records.lock().unwrap().clone();
+    let text = |value: &Value, key: &str| value.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+
+    let mut items = Vec::new();
+    let used = data.get("assets").and_then(Value::as_array).into_iter().flatten();
+    let files = data.get("files").and_then(Value::as_array).into_iter().flatten();
+    for (entry, is_asset) in used.map(|e| (e, true)).chain(files.map(|e| (e, false))) {
+        let filepath = projects::map_path(settings, &text(entry, "filepath"));
+        let name = text(entry, "name");
+        let id_type = is_asset.then(|| text(entry, "id_type"));
+        let record = lookup(&records, &filepath, id_type.as_deref(), is_asset.then_some(name.as_str()));
+        let license = record.map(|r| r.license.clone());
+        items.push(LicenseUsage {
+            kind: if is_asset { "asset".to_string() } else { text(entry, "kind") },
+            status: status(&policy, license.as_deref()),
+            name,
+            filepath,
+            id_type,
+            packed: entry.get("packed").and_then(Value::as_bool).unwrap_or(false),
+            license,
+            attribution: record.and_then(|r| r.attribution.clone()),
+            url: record.and_then(|r| r.url.clone()),
+        });
+    }
+
+    let flagged = items
+        .iter()
+        .filter(|item| matches!(item.status, LicenseStatus::Unknown | LicenseStatus::Restricted))
+        .count();
+    Ok(LicenseReport {
+        filepath: text(&data, "filepath"),
+        items,
+        flagged,
+    })
+}
+
+/// Licenses of everything the open file uses from outside itself, with
+/// unknown and restricted ones flagged
+#[tauri::command]
+pub async fn check_scene_licenses(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    licenses: State<'_, LicenseStore>,
+) -> Result<LicenseReport, CommandError> {
+    check(&state, &settings, &licenses).await
+}
+
+/// Write the license manifest of the open file, e.g. into a handoff
+/// bundle. A `.json` output gets the full report, anything else a text
+/// listing grouped by license.
+#[tauri::command]
+pub async fn write_license_manifest(
+    output: String,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    licenses: State<'_, LicenseStore>,
+) -> Result<LicenseReport, CommandError> {
+    let report = check(&state, &settings, &licenses).await?;
+
+    let text = if output.to_lowercase().ends_with(".json") {
+        serde_json::to_string_pretty(&report).map_err(|e| format!("Failed to serialize manifest: {}", e))?
+    } else {
+        let mut groups: BTreeMap<&str, Vec<&LicenseUsage>> = BTreeMap::new();
+        for item in &report.items {
+            groups.entry(item.license.as_deref().unwrap_or("Unknown")).or_default().push(item);
+        }
+        let mut text = format!("Third-party content used by {}\n", report.filepath);
+        for (license, items) in groups {
+            text.push_str(&format!("\n{}\n", license));
+            for item in items {
+                text.push_str(&format!("  - {} {} ({})", item.kind, item.name, item.filepath));
+                if let Some(attribution) = &item.attribution {
+                    text.push_str(&format!(", {}", attribution));
+                }
+                if let Some(url) = &item.url {
+                    text.push_str(&format!(", {}", url));
+                }
+                text.push('\n');
+            }
+        }
+        text
+    };
+
+    if let Some(parent) = Path::new(&output).parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| CommandError::Io(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+    fs::write(&output, text).map_err(|e| CommandError::Io(format!("Failed to write {}: {}", output, e)))?;
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn get_license_settings(settings: State<'_, SettingsStore>) -> LicenseSettings {
+    settings.get().licenses
+}
+
+#[tauri::command]
+pub fn set_license_settings(
+    licenses: LicenseSettings,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), CommandError> {
+    settings.update(|s| s.licenses = licenses)?;
+    Ok(())
+}
@@ -0,0 +1,496 @@
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{accept_hdr_async, WebSocketStream};
+
+use crate::a11y::{self, Politeness};
+use crate::errors::CommandError;
+use crate::faults::Direction;
+use crate::health::HealthMonitor;
+use crate::i18n;
+use crate::mode;
+use crate::profiler::PlaybackProfiler;
+use crate::scratch::{self, ScratchDirs};
+use crate::search::SceneSearch;
+use crate::settings::SettingsStore;
+use crate::shots;
+use crate::smart;
+use crate::snapshot::RecentEvents;
+use crate::tagging;
+use crate::triggers;
+use crate::warnings::{self, WarningStore};
+use crate::watch;
+use crate::watchdog::Watchdog;
+use crate::AppState;
+
+pub const DEFAULT_BLENDER_ADDRESS: &str = "127.0.0.1:32123";
+
+/// Who connects to a listener
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "role", rename_all = "snake_case")]
+pub enum ListenerRole {
+    /// The Blender add-on. Only one Blender listener can be enabled.
+    Blender,
+    /// External tools (Stream Deck, scripts) sending requests that are
+    /// relayed to Blender
+    Integration {
+        /// Actions clients may call; `*` or a trailing `*` (`scene.*`)
+        /// matches several
+        #[serde(default)]
+        allowed_commands: Vec<String>,
+    },
+}
+
+/// How clients prove they may connect
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthPolicy {
+    #[default]
+    None,
+    /// `Authorization: Bearer <token>` or a `?token=<token>` query in the URL
+    Token { token: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ListenerConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub role: ListenerRole,
+    pub address: String,
+    #[serde(default)]
+    pub auth: AuthPolicy,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Configured listeners; a Blender listener on loopback by default
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(transparent)]
+pub struct Listeners(pub Vec<ListenerConfig>);
+
+impl Default for Listeners {
+    fn default() -> Self {
+        Self(vec![ListenerConfig {
+            name: "blender".to_string(),
+            role: ListenerRole::Blender,
+            address: DEFAULT_BLENDER_ADDRESS.to_string(),
+            auth: AuthPolicy::None,
+            enabled: true,
+        }])
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ListenerStatus {
+    pub name: String,
+    pub address: String,
+    pub listening: bool,
+    pub clients: usize,
+    /// Why the listener isn't listening
+    pub error: Option<String>,
+}
+
+struct RunningListener {
+    /// Kept across restarts for a listener of the same name, so the
+    /// connections that survive one are still counted
+    status: Arc<std::sync::Mutex<ListenerStatus>>,
+    task: JoinHandle<()>,
+}
+
+/// Runs every enabled listener and restarts them when the configuration
+/// changes. Open connections survive a restart.
+#[derive(Default)]
+pub struct ListenerSupervisor {
+    running: Mutex<Vec<RunningListener>>,
+}
+
+impl ListenerSupervisor {
+    /// Stop all listeners and start the configured ones.
+    pub async fn restart<R: Runtime>(&self, app: &AppHandle<R>) {
+        let mut running = self.running.lock().await;
+        let mut previous = HashMap::new();
+        for listener in running.drain(..) {
+            listener.task.abort();
+            // Wait for the socket to close so its address can be bound again
+            let _ = listener.task.await;
+            let name = listener.status.lock().unwrap().name.clone();
+            previous.insert(name, listener.status);
+        }
+
+        for config in app.state::<SettingsStore>().get().listeners.0 {
+            if !config.enabled {
+                continue;
+            }
+            let status = match previous.remove(&config.name) {
+                Some(status) => {
+                    let mut current = status.lock().unwrap();
+                    current.address = config.address.clone();
+                    current.listening = false;
+                    current.error = None;
+                    drop(current);
+                    status
+                }
+                None => Arc::new(std::sync::Mutex::new(ListenerStatus {
+                    name: config.name.clone(),
+                    address: config.address.clone(),
+                    listening: false,
+                    clients: 0,
+                    error: None,
+                })),
+            };
+            let task = tauri::async_runtime::spawn(serve(app.clone(), config, status.clone()));
+            running.push(RunningListener { status, task });
+        }
+    }
+
+    pub async fn statuses(&self) -> Vec<ListenerStatus> {
+        self.running
+            .lock()
+            .await
+            .iter()
+            .map(|listener| listener.status.lock().unwrap().clone())
+            .collect()
+    }
+}
+
+/// Start the configured listeners in the background.
+pub fn start<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        app.state::<ListenerSupervisor>().restart(&app).await;
+    });
+}
+
+async fn serve<R: Runtime>(app: AppHandle<R>, config: ListenerConfig, status: Arc<std::sync::Mutex<ListenerStatus>>) {
+    let listener = match TcpListener::bind(&config.address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Failed to bind listener '{}' on {}: {err}", config.name, config.address);
+            status.lock().unwrap().error = Some(err.to_string());
+            return;
+        }
+    };
+    status.lock().unwrap().listening = true;
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(err) => {
+                eprintln!("WebSocket accept error on '{}': {err}", config.name);
+                continue;
+            }
+        };
+
+        let app = app.clone();
+        let config = config.clone();
+        let status = status.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let Some(websocket) = handshake(stream, &config).await else {
+                if matches!(config.role, ListenerRole::Blender) {
+                    if let Err(err) = app.emit("ws:status", "disconnected") {
+                        eprintln!("Failed to emit ws:status disconnected: {err}");
+                    }
+                }
+                return;
+            };
+
+            status.lock().unwrap().clients += 1;
+            match &config.role {
+                ListenerRole::Blender => serve_blender(&app, websocket, peer).await,
+                ListenerRole::Integration { allowed_commands } => {
+                    serve_integration(&app, websocket, &config.name, allowed_commands).await
+                }
+            }
+            status.lock().unwrap().clients -= 1;
+        });
+    }
+}
+
+// The error type is fixed by tungstenite's handshake callback
+#[allow(clippy::result_large_err)]
+async fn handshake(stream: TcpStream, config: &ListenerConfig) -> Option<WebSocketStream<TcpStream>> {
+    let auth = config.auth.clone();
+    let check = move |request: &Request, response: Response| {
+        if authorized(&auth, request) {
+            Ok(response)
+        } else {
+            let mut error = ErrorResponse::new(Some("Unauthorized".to_string()));
+            *error.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(error)
+        }
+    };
+
+    match accept_hdr_async(stream, check).await {
+        Ok(websocket) => Some(websocket),
+        Err(err) => {
+            eprintln!("WebSocket handshake error on '{}': {err}", config.name);
+            None
+        }
+    }
+}
+
+fn authorized(auth: &AuthPolicy, request: &Request) -> bool {
+    match auth {
+        AuthPolicy::None => true,
+        AuthPolicy::Token { token } => {
+            let bearer = request
+                .headers()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+            let query = request
+                .uri()
+                .query()
+                .unwrap_or_default()
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="));
+            bearer.is_some_and(|bearer| token_matches(token, bearer))
+                || query.is_some_and(|query| token_matches(token, query))
+        }
+    }
+}
+
+/// Compared in constant time, so the time to refuse a guess doesn't tell
+/// how much of it was right. Both sides go through HMAC first, which also
+/// keeps the token's length from showing.
+fn token_matches(token: &str, candidate: &str) -> bool {
+    let digest = |text: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(token.as_bytes()).expect("HMAC takes keys of any length");
+        mac.update(text.as_bytes());
+        mac
+    };
+    digest(candidate).verify_slice(&digest(token).finalize().into_bytes()).is_ok()
+}
+
+/// The add-on connection: events go to the mirror and the frontend,
+/// responses to backend requests back to the bridge.
+async fn serve_blender<R: Runtime>(app: &AppHandle<R>, websocket: WebSocketStream<TcpStream>, peer: SocketAddr) {
+    let state = app.state::<AppState>();
+    let (sender, mut receiver) = websocket.split();
+
+    // Store sender for outgoing messages
+    {
+        let mut sender_guard = state.ws_sender.lock().await;
+        *sender_guard = Some(sender);
+    }
+    state.session.connected(peer);
+    app.state::<Watchdog>().connected();
+    scratch::open_session(app);
+    app.state::<WarningStore>().open_session(app, peer);
+
+    mode::update(app).await;
+
+    if let Err(err) = app.emit("ws:status", "connected") {
+        eprintln!("Failed to emit ws:status connected: {err}");
+    }
+    a11y::announce(app, "connection", Politeness::Polite, i18n::t("a11y-blender-connected", &[]));
+
+    // Read incoming messages
+    'read: while let Some(message_result) = receiver.next().await {
+        match message_result {
+            Ok(Message::Text(text)) => {
+                for text in state.bridge.faults().apply(Direction::Incoming, text).await {
+                    // Responses to backend-initiated requests stay in the backend
+                    if let Ok(value) = serde_json::from_str::<Value>(&text) {
+                        state.mirror.observe(&value).await;
+                        state.session.observe(&value);
+                        app.state::<Watchdog>().observe(app, &value);
+                        app.state::<HealthMonitor>().observe(app, &value);
+                        app.state::<SceneSearch>().observe(&value);
+                        app.state::<PlaybackProfiler>().observe(&value);
+                        smart::observe(app, &value);
+                        watch::observe(app, &value);
+                        shots::observe(app, &value);
+                        tagging::observe(app, &value);
+                        warnings::observe(app, &value);
+                        if state.bridge.handle_incoming(&value).await || triggers::handle(app, &value) {
+                            continue;
+                        }
+                        app.state::<RecentEvents>().observe(&value);
+                    }
+
+                    if let Err(err) = app.emit("ws:message", text) {
+                        eprintln!("Failed to emit ws:message: {err}");
+                        break 'read;
+                    }
+                }
+            }
+            Ok(Message::Close(_)) => {
+                break;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("WebSocket read error: {err}");
+                break;
+            }
+        }
+    }
+
+    // Clear sender on disconnect
+    {
+        let mut sender_guard = state.ws_sender.lock().await;
+        *sender_guard = None;
+    }
+    state.bridge.fail_pending(&i18n::t("blender-disconnected", &[])).await;
+    state.mirror.disconnected().await;
+    state.session.disconnected();
+    app.state::<Watchdog>().disconnected(app);
+    app.state::<ScratchDirs>().close_session();
+    mode::update(app).await;
+
+    if let Err(err) = app.emit("ws:status", "disconnected") {
+        eprintln!("Failed to emit ws:status disconnected: {err}");
+    }
+    a11y::announce(app, "connection", Politeness::Assertive, i18n::t("a11y-blender-disconnected", &[]));
+}
+
+/// An external tool: each `{"id", "action", "target", "params"}` request
+/// is checked against the listener's allowed commands and relayed to
+/// Blender, and the response is sent back to the tool.
+async fn serve_integration<R: Runtime>(
+    app: &AppHandle<R>,
+    websocket: WebSocketStream<TcpStream>,
+    name: &str,
+    allowed_commands: &[String],
+) {
+    let state = app.state::<AppState>();
+    let (mut sender, mut receiver) = websocket.split();
+
+    while let Some(message_result) = receiver.next().await {
+        let text = match message_result {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(err) => {
+                eprintln!("WebSocket read error on '{}': {err}", name);
+                break;
+            }
+        };
+
+        let request: Value = serde_json::from_str(&text).unwrap_or_default();
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let action = request.get("action").and_then(Value::as_str).unwrap_or_default();
+        let target = request.get("target").and_then(Value::as_str).unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        let result = if action.is_empty() {
+            Err("Expected a request with an 'action'".to_string())
+        } else if !command_allowed(allowed_commands, action) {
+            Err(format!("Command '{}' is not allowed on listener '{}'", action, name))
+        } else {
+            state.bridge.request(action, target, params).await.map_err(String::from)
+        };
+
+        let response = match result {
+            Ok(data) => json!({"type": "response", "id": id, "action": action, "data": data}),
+            Err(error) => json!({"type": "response", "id": id, "action": action, "error": error}),
+        };
+        if let Err(err) = sender.send(Message::Text(response.to_string())).await {
+            eprintln!("Failed to reply on '{}': {err}", name);
+            break;
+        }
+    }
+}
+
+/// `action` matches one of `patterns`: an action name, `*`, or a prefix
+/// ending in `*`
+pub fn command_allowed(patterns: &[String], action: &str) -> bool {
+    patterns.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => action.starts_with(prefix),
+        None => pattern == action,
+    })
+}
+
+fn check_listeners(listeners: &Listeners) -> Result<(), CommandError> {
+    let mut names = HashSet::new();
+    for config in &listeners.0 {
+        if !names.insert(config.name.as_str()) {
+            return Err(CommandError::InvalidInput(format!(
+                "Listener name '{}' is used twice",
+                config.name
+            )));
+        }
+        if let AuthPolicy::Token { token } = &config.auth {
+            if token.is_empty() {
+                return Err(CommandError::InvalidInput(format!(
+                    "Listener '{}' needs a token",
+                    config.name
+                )));
+            }
+        }
+        // Anyone who can reach an open integration listener can drive Blender
+        if config.enabled
+            && matches!(config.role, ListenerRole::Integration { .. })
+            && matches!(config.auth, AuthPolicy::None)
+            && !is_loopback(&config.address)
+        {
+            return Err(CommandError::InvalidInput(format!(
+                "Integration listener '{}' needs a token to listen on {}",
+                config.name, config.address
+            )));
+        }
+    }
+    let blender = listeners
+        .0
+        .iter()
+        .filter(|config| config.enabled && matches!(config.role, ListenerRole::Blender))
+        .count();
+    if blender > 1 {
+        return Err(CommandError::InvalidInput(
+            "Only one Blender listener can be enabled".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `address` only accepts connections from this machine. Host
+/// names other than `localhost` may resolve anywhere, so they count as
+/// reachable from outside.
+fn is_loopback(address: &str) -> bool {
+    if let Ok(addr) = address.parse::<SocketAddr>() {
+        return addr.ip().is_loopback();
+    }
+    address
+        .rsplit_once(':')
+        .is_some_and(|(host, _)| host.eq_ignore_ascii_case("localhost"))
+}
+
+#[tauri::command]
+pub fn get_listeners(settings: State<'_, SettingsStore>) -> Listeners {
+    settings.get().listeners
+}
+
+/// Save the listener configuration and restart all listeners
+#[tauri::command]
+pub async fn set_listeners<R: Runtime>(
+    listeners: Listeners,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    supervisor: State<'_, ListenerSupervisor>,
+) -> Result<(), CommandError> {
+    check_listeners(&listeners)?;
+    settings.update(|s| s.listeners = listeners)?;
+    supervisor.restart(&app).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_listeners(supervisor: State<'_, ListenerSupervisor>) -> Result<Vec<ListenerStatus>, CommandError> {
+    Ok(supervisor.statuses().await)
+}
@@ -0,0 +1,176 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::bridge::{datablock_path, BlenderBridge, PropertyChange};
+use crate::AppState;
+
+/// Values captured before look-dev toggles, in the order they were first changed.
+#[derive(Default)]
+pub struct LookdevState {
+    restore: Mutex<Vec<PropertyChange>>,
+}
+
+#[derive(Serialize)]
+pub struct LookdevResult {
+    changed: usize,
+    restorable: usize,
+}
+
+/// Hide every object except `names` in the viewport
+#[tauri::command]
+pub async fn isolate_objects(
+    names: Vec<String>,
+    state: State<'_, AppState>,
+    lookdev: State<'_, LookdevState>,
+) -> Result<LookdevResult, CommandError> {
+    let scene = state.mirror.scene(&state.bridge).await?;
+    let keep: HashSet<&str> = names.iter().map(String::as_str).collect();
+    let objects: Vec<(&String, &Value)> = scene_objects(&scene)?.collect();
+
+    if let Some(missing) = names.iter().find(|name| !objects.iter().any(|(object, _)| *object == *name)) {
+        return Err(CommandError::NotFound(format!("Object '{}' not found", missing)));
+    }
+
+    let mut planned = Vec::new();
+    for (name, object) in objects {
+        let hidden = !keep.contains(name.as_str());
+        plan_change(&mut planned, datablock_path("objects", name), "hide_viewport", object, hidden);
+    }
+
+    apply(&state.bridge, &lookdev, planned, "Blendmate: Isolate objects").await
+}
+
+/// Hide a collection in the viewport
+#[tauri::command]
+pub async fn hide_collection(
+    name: String,
+    state: State<'_, AppState>,
+    lookdev: State<'_, LookdevState>,
+) -> Result<LookdevResult, CommandError> {
+    let scene = state.mirror.scene(&state.bridge).await?;
+    let collection = scene
+        .get("collections")
+        .and_then(|root| find_collection(root, &name))
+        .ok_or_else(|| CommandError::NotFound(format!("Collection '{}' not found", name)))?;
+    if collection.get("hide_viewport").is_none() {
+        return Err(CommandError::InvalidInput(format!("Collection '{}' cannot be hidden", name)));
+    }
+
+    let mut planned = Vec::new();
+    plan_change(&mut planned, datablock_path("collections", &name), "hide_viewport", collection, true);
+
+    apply(&state.bridge, &lookdev, planned, "Blendmate: Hide collection").await
+}
+
+/// Disable every light except `name` in the viewport and in renders
+#[tauri::command]
+pub async fn solo_light(
+    name: String,
+    state: State<'_, AppState>,
+    lookdev: State<'_, LookdevState>,
+) -> Result<LookdevResult, CommandError> {
+    let scene = state.mirror.scene(&state.bridge).await?;
+    let lights: Vec<(&String, &Value)> = scene_objects(&scene)?
+        .filter(|(_, object)| object.get("type").and_then(Value::as_str) == Some("LIGHT"))
+        .collect();
+
+    if !lights.iter().any(|(light, _)| **light == name) {
+        return Err(CommandError::NotFound(format!("Light '{}' not found", name)));
+    }
+
+    let mut planned = Vec::new();
+    for (light, object) in lights {
+        let hidden = *light != name;
+        let target = datablock_path("objects", light);
+        plan_change(&mut planned, target.clone(), "hide_viewport", object, hidden);
+        plan_change(&mut planned, target, "hide_render", object, hidden);
+    }
+
+    apply(&state.bridge, &lookdev, planned, "Blendmate: Solo light").await
+}
+
+/// Restore everything changed by look-dev toggles since the last restore
+#[tauri::command]
+pub async fn restore_lookdev(
+    state: State<'_, AppState>,
+    lookdev: State<'_, LookdevState>,
+) -> Result<LookdevResult, CommandError> {
+    let mut restore = lookdev.restore.lock().await;
+    if restore.is_empty() {
+        return Ok(LookdevResult { changed: 0, restorable: 0 });
+    }
+
+    state.bridge.execute_batch(&restore, "Blendmate: Restore look-dev").await?;
+    let changed = restore.len();
+    restore.clear();
+
+    Ok(LookdevResult { changed, restorable: 0 })
+}
+
+fn scene_objects(scene: &Value) -> Result<impl Iterator<Item = (&String, &Value)>, String> {
+    scene
+        .get("objects")
+        .and_then(Value::as_object)
+        .map(|objects| objects.iter())
+        .ok_or_else(|| "Scene data has no objects".to_string())
+}
+
+fn find_collection<'a>(collection: &'a Value, name: &str) -> Option<&'a Value> {
+    if collection.get("name").and_then(Value::as_str) == Some(name) {
+        return Some(collection);
+    }
+    collection
+        .get("children")
+        .and_then(Value::as_array)?
+        .iter()
+        .find_map(|child| find_collection(child, name))
+}
+
+/// Queue `path = value` unless the datablock already has that value.
+/// `current` is the datablock as reported by `get_scene`.
+fn plan_change(planned: &mut Vec<(PropertyChange, Value)>, target: String, path: &str, current: &Value, value: bool) {
+    let previous = current.get(path).cloned().unwrap_or(Value::Bool(!value));
+    if previous == Value::Bool(value) {
+        return;
+    }
+    planned.push((
+        PropertyChange {
+            target,
+            path: path.to_string(),
+            value: Value::Bool(value),
+        },
+        previous,
+    ));
+}
+
+async fn apply(
+    bridge: &BlenderBridge,
+    lookdev: &LookdevState,
+    planned: Vec<(PropertyChange, Value)>,
+    label: &str,
+) -> Result<LookdevResult, CommandError> {
+    let mut restore = lookdev.restore.lock().await;
+    if planned.is_empty() {
+        return Ok(LookdevResult { changed: 0, restorable: restore.len() });
+    }
+
+    let changes: Vec<PropertyChange> = planned.iter().map(|(change, _)| change.clone()).collect();
+    bridge.execute_batch(&changes, label).await?;
+
+    // Keep only the first captured value per property so a chain of toggles
+    // still restores the state from before look-dev started
+    for (change, previous) in planned {
+        let known = restore
+            .iter()
+            .any(|saved| saved.target == change.target && saved.path == change.path);
+        if !known {
+            restore.push(PropertyChange { value: previous, ..change });
+        }
+    }
+
+    Ok(LookdevResult { changed: changes.len(), restorable: restore.len() })
+}
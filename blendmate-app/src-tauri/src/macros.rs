@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::history::{self, HistoryStore};
+use crate::listeners;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MacroSettings {
+    /// Actions a macro step may call, matched like a listener's
+    /// `allowed_commands`. A macro with any other step is refused whole.
+    pub allowed_commands: Vec<String>,
+}
+
+impl Default for MacroSettings {
+    fn default() -> Self {
+        Self {
+            allowed_commands: history::RECORDED_ACTIONS.iter().map(|action| action.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MacroStep {
+    pub action: String,
+    pub target: String,
+    pub params: Value,
+}
+
+/// A value given when the macro runs. `{name}` in step targets and string
+/// parameters is replaced by it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MacroParameter {
+    pub name: String,
+    /// Used when a run doesn't give a value; the recorded value for
+    /// recorded macros
+    pub default: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+    #[serde(default)]
+    pub parameters: Vec<MacroParameter>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MacroRecording {
+    pub project: String,
+    /// Newest history entry when recording started
+    after: u64,
+    pub started_at: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MacroRun {
+    /// Response of each step that ran
+    pub results: Vec<Value>,
+    /// Index and error of the step that failed; later steps didn't run
+    pub failed: Option<(usize, String)>,
+}
+
+/// Named command sequences, persisted as `macros.json` in the app data
+/// directory after every change. A recording is the slice of the command
+/// history between its start and stop.
+pub struct MacroStore {
+    path: PathBuf,
+    macros: Mutex<Vec<Macro>>,
+    recording: Mutex<Option<MacroRecording>>,
+}
+
+impl MacroStore {
+    pub fn load(path: PathBuf) -> Self {
+        let macros = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            macros: Mutex::new(macros),
+            recording: Mutex::new(None),
+        }
+    }
+
+    fn update<T>(&self, change: impl FnOnce(&mut Vec<Macro>) -> Result<T, CommandError>) -> Result<T, CommandError> {
+        let mut macros = self.macros.lock().unwrap();
+        let result = change(&mut macros)?;
+        save(&self.path, &macros)?;
+        Ok(result)
+    }
+}
+
+fn save(path: &Path, macros: &[Macro]) -> Result<(), CommandError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| CommandError::Io(format!("Failed to create data directory: {}", e)))?;
+    }
+    let text = serde_json::to_string_pretty(macros).map_err(|e| format!("Failed to serialize macros: {}", e))?;
+    fs::write(path, text).map_err(|e| CommandError::Io(format!("Failed to write macros: {}", e)))
+}
+
+/// Replace strings equal to a parameter's recorded value with `{name}`
+fn parameterize(value: &mut Value, parameters: &[MacroParameter]) {
+    match value {
+        Value::String(text) => {
+            if let Some(parameter) = parameters.iter().find(|p| &p.default == text) {
+                *text = format!("{{{}}}", parameter.name);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| parameterize(item, parameters)),
+        Value::Object(map) => map.values_mut().for_each(|item| parameterize(item, parameters)),
+        _ => {}
+    }
+}
+
+/// Replace `{name}` with each parameter's value
+fn substitute(value: &mut Value, values: &HashMap<String, String>) {
+    match value {
+        Value::String(text) => *text = substitute_text(text, values),
+        Value::Array(items) => items.iter_mut().for_each(|item| substitute(item, values)),
+        Value::Object(map) => map.values_mut().for_each(|item| substitute(item, values)),
+        _ => {}
+    }
+}
+
+fn substitute_text(text: &str, values: &HashMap<String, String>) -> String {
+    values
+        .iter()
+        .fold(text.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+#[tauri::command]
+pub fn list_macros(macros: State<'_, MacroStore>) -> Vec<Macro> {
+    macros.macros.lock().unwrap().clone()
+}
+
+/// Add a macro, or replace the one with the same name
+#[tauri::command]
+pub fn save_macro(definition: Macro, macros: State<'_, MacroStore>) -> Result<Vec<Macro>, CommandError> {
+    if definition.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Macro name is required".to_string()));
+    }
+    macros.update(|list| {
+        list.retain(|m| m.name != definition.name);
+        list.push(definition);
+        Ok(list.clone())
+    })
+}
+
+#[tauri::command]
+pub fn delete_macro(name: String, macros: State<'_, MacroStore>) -> Result<Vec<Macro>, CommandError> {
+    macros.update(|list| {
+        let before = list.len();
+        list.retain(|m| m.name != name);
+        if list.len() == before {
+            return Err(CommandError::NotFound(format!("Unknown macro '{}'", name)));
+        }
+        Ok(list.clone())
+    })
+}
+
+/// Start capturing the commands sent to Blender from now on
+#[tauri::command]
+pub fn start_macro_recording(
+    macros: State<'_, MacroStore>,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<MacroRecording, CommandError> {
+    let mut recording = macros.recording.lock().unwrap();
+    if recording.is_some() {
+        return Err(CommandError::InvalidInput("Already recording a macro".to_string()));
+    }
+    let project = projects::key(&settings, None);
+    let started = MacroRecording {
+        after: history.last_id(&project),
+        project,
+        started_at: chrono::Local::now().to_rfc3339(),
+    };
+    *recording = Some(started.clone());
+    Ok(started)
+}
+
+#[tauri::command]
+pub fn get_macro_recording(macros: State<'_, MacroStore>) -> Option<MacroRecording> {
+    macros.recording.lock().unwrap().clone()
+}
+
+/// Stop recording and save the captured commands as `name`. `parameters`
+/// maps parameter names to a value used while recording, e.g.
+/// `{"object": "Cube"}`; every step target or string parameter equal to
+/// that value becomes `{object}`.
+#[tauri::command]
+pub fn stop_macro_recording(
+    name: String,
+    parameters: Option<HashMap<String, String>>,
+    macros: State<'_, MacroStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<Macro, CommandError> {
+    if name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Macro name is required".to_string()));
+    }
+    let recording = macros
+        .recording
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or_else(|| CommandError::InvalidInput("Not recording a macro".to_string()))?;
+
+    let parameters: Vec<MacroParameter> = parameters
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, default)| MacroParameter { name, default })
+        .collect();
+    let steps: Vec<MacroStep> = history
+        .since(&recording.project, recording.after)
+        .into_iter()
+        .map(|entry| {
+            let mut target = Value::String(entry.target);
+            let mut params = entry.params;
+            parameterize(&mut target, &parameters);
+            parameterize(&mut params, &parameters);
+            MacroStep {
+                action: entry.action,
+                target: target.as_str().unwrap_or_default().to_string(),
+                params,
+            }
+        })
+        .collect();
+    if steps.is_empty() {
+        return Err(CommandError::InvalidInput("No commands were recorded".to_string()));
+    }
+
+    let recorded = Macro { name, steps, parameters };
+    macros.update(|list| {
+        list.retain(|m| m.name != recorded.name);
+        list.push(recorded.clone());
+        Ok(())
+    })?;
+    Ok(recorded)
+}
+
+#[tauri::command]
+pub fn cancel_macro_recording(macros: State<'_, MacroStore>) {
+    macros.recording.lock().unwrap().take();
+}
+
+/// Run a macro's steps in order, stopping at the first failure. Every
+/// step must be allowed by the `macros` settings before any of them runs.
+/// Parameters without a value in `values` use their default.
+#[tauri::command]
+pub async fn run_macro(
+    name: String,
+    values: Option<HashMap<String, String>>,
+    state: State<'_, AppState>,
+    macros: State<'_, MacroStore>,
+    history: State<'_, HistoryStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<MacroRun, CommandError> {
+    let definition = macros
+        .macros
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|m| m.name == name)
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown macro '{}'", name)))?;
+
+    let allowed = settings.get().macros.allowed_commands;
+    if let Some(step) = definition
+        .steps
+        .iter()
+        .find(|step| !listeners::command_allowed(&allowed, &step.action))
+    {
+        return Err(CommandError::InvalidInput(format!(
+            "Command '{}' is not allowed in macros",
+            step.action
+        )));
+    }
+
+    let mut values = values.unwrap_or_default();
+    for parameter in &definition.parameters {
+        values.entry(parameter.name.clone()).or_insert_with(|| parameter.default.clone());
+    }
+
+    let mut results = Vec::new();
+    for (index, step) in definition.steps.into_iter().enumerate() {
+        let target = substitute_text(&step.target, &values);
+        let mut params = step.params;
+        substitute(&mut params, &values);
+        match state.bridge.request(&step.action, &target, params.clone()).await {
+            Ok(result) => {
+                history.record(&settings, &step.action, &target, &params);
+                results.push(result);
+            }
+            Err(err) => {
+                return Ok(MacroRun {
+                    results,
+                    failed: Some((index, err.to_string())),
+                })
+            }
+        }
+    }
+    Ok(MacroRun { results, failed: None })
+}
+
+#[tauri::command]
+pub fn get_macro_settings(settings: State<'_, SettingsStore>) -> MacroSettings {
+    settings.get().macros
+}
+
+#[tauri::command]
+pub fn set_macro_settings(macros: MacroSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.macros = macros)?;
+    Ok(())
+}
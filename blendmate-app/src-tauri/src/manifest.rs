@@ -0,0 +1,290 @@
+use serde::Serialize;
+
+use crate::errors::ERROR_CODES;
+
+/// Bumped when a command is removed or its arguments or result change
+/// incompatibly. Adding a command does not bump it.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Every command in `generate_handler!` in `lib.rs`, by invoke name
+const COMMANDS: &[&str] = &[
+    "send_to_blender",
+    "get_file_info",
+    "ask_claude",
+    "isolate_objects",
+    "hide_collection",
+    "solo_light",
+    "restore_lookdev",
+    "validate_scene",
+    "list_validation_profiles",
+    "save_validation_profile",
+    "get_naming_convention",
+    "set_naming_convention",
+    "audit_naming",
+    "apply_rename_plan",
+    "get_library_graph",
+    "refresh_asset_index",
+    "get_asset_index",
+    "append_asset",
+    "apply_pose",
+    "list_jobs",
+    "get_job",
+    "cancel_job",
+    "resume_job",
+    "get_worker_settings",
+    "set_worker_settings",
+    "list_blender_installs",
+    "start_export_job",
+    "start_upgrade_job",
+    "list_playlists",
+    "save_playlist",
+    "delete_playlist",
+    "list_playlist_runs",
+    "start_playlist",
+    "resume_playlist_run",
+    "cancel_playlist_run",
+    "list_caches",
+    "delete_cache",
+    "relocate_cache",
+    "get_render_devices",
+    "set_render_devices",
+    "apply_device_profile",
+    "list_device_profiles",
+    "save_device_profile",
+    "inspect_color_management",
+    "get_color_standard",
+    "set_color_standard",
+    "apply_color_standard",
+    "plan_render_chunks",
+    "start_render_job",
+    "get_power_status",
+    "get_power_settings",
+    "set_power_settings",
+    "get_network_settings",
+    "set_network_settings",
+    "start_preview",
+    "stop_preview",
+    "get_connection_quality",
+    "get_listeners",
+    "set_listeners",
+    "list_listeners",
+    "reload_addon",
+    "get_addon_dev_settings",
+    "set_addon_dev_settings",
+    "get_addon_dev_status",
+    "run_conformance",
+    "set_fault_injection",
+    "get_fault_injection",
+    "get_command_manifest",
+    "list_tasks",
+    "cancel_task",
+    "search_scene",
+    "list_smart_collections",
+    "save_smart_collection",
+    "delete_smart_collection",
+    "get_smart_collection_members",
+    "materialize_smart_collection",
+    "watch_property",
+    "unwatch_property",
+    "list_property_watches",
+    "list_custom_triggers",
+    "get_project_settings",
+    "save_project_profile",
+    "delete_project_profile",
+    "set_active_project",
+    "export_project_profile",
+    "import_project_profile",
+    "resolve_project_path",
+    "start_benchmark_job",
+    "list_benchmark_results",
+    "compare_benchmark_runs",
+    "clear_benchmark_results",
+    "get_playback_profile",
+    "reset_playback_profile",
+    "list_shots",
+    "save_shot",
+    "delete_shot",
+    "reorder_shots",
+    "jump_to_shot",
+    "start_shot_render",
+    "set_secret",
+    "delete_secret",
+    "has_secret",
+    "get_tracker_settings",
+    "set_tracker_settings",
+    "kitsu_list_shots",
+    "kitsu_set_task_status",
+    "kitsu_push_shots",
+    "flamenco_submit_render",
+    "flamenco_submit_shot",
+    "flamenco_get_job",
+    "list_audio_strips",
+    "get_audio_peaks",
+    "set_audio_options",
+    "list_references",
+    "import_reference",
+    "download_reference",
+    "remove_reference",
+    "push_reference",
+    "check_ffmpeg",
+    "get_ffmpeg_settings",
+    "set_ffmpeg_settings",
+    "start_timelapse",
+    "stop_timelapse",
+    "list_timelapses",
+    "assemble_timelapse",
+    "delete_timelapse",
+    "get_timelapse_settings",
+    "set_timelapse_settings",
+    "list_palettes",
+    "save_palette",
+    "delete_palette",
+    "convert_color",
+    "extract_palette",
+    "push_color",
+    "start_unit_audit",
+    "list_licenses",
+    "set_license",
+    "remove_license",
+    "scan_license_files",
+    "check_scene_licenses",
+    "write_license_manifest",
+    "get_license_settings",
+    "set_license_settings",
+    "get_blender_activity",
+    "get_disk_status",
+    "get_disk_guard_settings",
+    "set_disk_guard_settings",
+    "get_share_status",
+    "get_share_settings",
+    "set_share_settings",
+    "get_scratch_usage",
+    "get_scratch_settings",
+    "set_scratch_settings",
+    "list_history",
+    "repeat_last",
+    "repeat_history",
+    "recall_params",
+    "list_favorites",
+    "pin_favorite",
+    "unpin_favorite",
+    "run_favorite",
+    "list_macros",
+    "save_macro",
+    "delete_macro",
+    "start_macro_recording",
+    "get_macro_recording",
+    "stop_macro_recording",
+    "cancel_macro_recording",
+    "run_macro",
+    "get_macro_settings",
+    "set_macro_settings",
+    "get_app_snapshot",
+    "sync_outliner",
+    "get_outliner_page",
+    "list_locales",
+    "get_locale",
+    "set_locale",
+    "translate",
+    "get_catalog",
+    "list_announcements",
+    "get_a11y_settings",
+    "set_a11y_settings",
+    "export_report",
+    "get_storage_status",
+    "get_storage_settings",
+    "set_storage_settings",
+    "get_blender_health",
+    "get_health_settings",
+    "set_health_settings",
+    "get_session_tags",
+    "test_tag_patterns",
+    "get_tagging_settings",
+    "set_tagging_settings",
+    "plan_quick_action",
+    "apply_quick_action",
+    "plan_relink",
+    "apply_relink",
+    "start_relink_job",
+    "get_command_queue",
+    "open_in_editor",
+    "list_editor_sessions",
+    "close_in_editor",
+    "get_editor_settings",
+    "set_editor_settings",
+    "list_notes",
+    "save_note",
+    "delete_note",
+    "open_panel_window",
+    "reset_window_placement",
+    "get_delivery_status",
+    "get_delivery_manifest",
+    "retry_delivery",
+    "console_exec",
+    "get_console_history",
+    "clear_console",
+    "get_warnings",
+    "list_warning_sessions",
+    "mark_warnings_seen",
+    "list_project_templates",
+    "save_project_template",
+    "delete_project_template",
+    "export_project_template",
+    "import_project_template",
+    "create_project_from_template",
+    "export_settings_bundle",
+    "import_settings_bundle",
+    "get_sync_settings",
+    "set_sync_settings",
+    "get_sync_status",
+    "get_time_sync_settings",
+    "set_time_sync_settings",
+    "get_time_sync_metrics",
+    "reset_time_sync_metrics",
+    "list_midi_ports",
+    "list_features",
+    "set_feature_enabled",
+    "get_mode",
+    "get_render_metadata",
+    "read_exr_metadata",
+    "check_render_passes",
+    "list_matte_names",
+    "composite_matte_preview",
+    "search_render_logs",
+    "get_render_log",
+    "import_render_log",
+    "list_review_notes",
+    "save_review_note",
+    "delete_review_note",
+    "import_review_markers",
+    "sync_review_markers",
+    "list_control_surfaces",
+    "refresh_control_surfaces",
+    "get_surface_profile",
+    "save_surface_profile",
+    "learn_surface_control",
+    "set_control_state",
+    "get_startup_report",
+    "enable_subsystem",
+    "leave_safe_mode",
+];
+
+/// What the backend offers, so the frontend can check before it calls
+#[derive(Serialize)]
+pub struct CommandManifest {
+    pub version: u32,
+    pub app_version: &'static str,
+    pub commands: &'static [&'static str],
+    /// Codes a failed command can report
+    pub error_codes: &'static [&'static str],
+}
+
+#[tauri::command]
+pub fn get_command_manifest() -> CommandManifest {
+    CommandManifest {
+        version: MANIFEST_VERSION,
+        app_version: env!("CARGO_PKG_VERSION"),
+        commands: COMMANDS,
+        error_codes: ERROR_CODES,
+    }
+}
@@ -0,0 +1,130 @@
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::bridge::BlenderBridge;
+use crate::errors::CommandError;
+use crate::protocol;
+
+/// Events after which the cached scene no longer matches Blender.
+const INVALIDATING_EVENTS: &[&str] = &[
+    "event.scene.connected",
+    "event.scene.file_loaded",
+    "event.depsgraph.updated",
+];
+
+/// Backend copy of the last `get_scene` payload.
+///
+/// The mirror is refreshed lazily: events only mark it stale, and the next
+/// reader fetches a new snapshot. `get_scene` responses requested by the
+/// frontend are picked up as well, so the two rarely both hit Blender.
+#[derive(Default)]
+pub struct SceneMirror {
+    inner: Mutex<MirrorInner>,
+}
+
+#[derive(Default)]
+struct MirrorInner {
+    scene: Option<Arc<Value>>,
+    stale: bool,
+    /// Bumped whenever a different .blend may be open (connect, file load)
+    file_generation: u64,
+    /// When `scene` was read from Blender
+    read_at: Option<String>,
+}
+
+impl SceneMirror {
+    /// Feed every incoming add-on message through the mirror.
+    pub async fn observe(&self, message: &Value) {
+        let Some(msg_type) = protocol::message_type(message) else {
+            return;
+        };
+
+        if INVALIDATING_EVENTS.contains(&msg_type.as_str()) {
+            let mut inner = self.inner.lock().await;
+            inner.stale = true;
+            if msg_type != "event.depsgraph.updated" {
+                inner.file_generation += 1;
+            }
+            return;
+        }
+
+        if msg_type == "response"
+            && protocol::response_action(message) == Some("get_scene")
+            && protocol::response_ok(message)
+        {
+            if let Some(scene) = protocol::response_data(message) {
+                self.store(scene.clone()).await;
+            }
+        }
+    }
+
+    /// Current scene, fetching a fresh snapshot when the cache is stale.
+    pub async fn scene(&self, bridge: &BlenderBridge) -> Result<Arc<Value>, CommandError> {
+        {
+            let inner = self.inner.lock().await;
+            if let (Some(scene), false) = (&inner.scene, inner.stale) {
+                return Ok(scene.clone());
+            }
+        }
+
+        let scene = bridge.request("get_scene", "", json!({})).await?;
+        if let Some(error) = scene.get("error").and_then(Value::as_str) {
+            return Err(CommandError::Blender {
+                message: error.to_string(),
+                details: None,
+            });
+        }
+        Ok(self.store(scene).await)
+    }
+
+    /// Current scene, or the last snapshot with `true` when Blender is not
+    /// connected, for commands that serve stale data while it is away
+    pub async fn scene_or_cached(&self, bridge: &BlenderBridge) -> Result<(Arc<Value>, bool), CommandError> {
+        match self.scene(bridge).await {
+            Ok(scene) => Ok((scene, false)),
+            Err(CommandError::NotConnected(message)) => match self.cached().await {
+                Some(scene) => Ok((scene, true)),
+                None => Err(CommandError::NotConnected(message)),
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The last snapshot without fetching, even if stale
+    pub async fn cached(&self) -> Option<Arc<Value>> {
+        self.inner.lock().await.scene.clone()
+    }
+
+    /// When the last snapshot was read from Blender
+    pub async fn read_at(&self) -> Option<String> {
+        self.inner.lock().await.read_at.clone()
+    }
+
+    /// Force the next reader to fetch a fresh snapshot.
+    pub async fn invalidate(&self) {
+        self.inner.lock().await.stale = true;
+    }
+
+    /// Caches of per-file data compare this to know when to refetch.
+    pub async fn file_generation(&self) -> u64 {
+        self.inner.lock().await.file_generation
+    }
+
+    /// Keep the last snapshot for cached mode, but never as current: the
+    /// next reader fetches again, and Blender may open another file
+    pub async fn disconnected(&self) {
+        let mut inner = self.inner.lock().await;
+        inner.stale = true;
+        inner.file_generation += 1;
+    }
+
+    async fn store(&self, scene: Value) -> Arc<Value> {
+        let scene = Arc::new(scene);
+        let mut inner = self.inner.lock().await;
+        inner.scene = Some(scene.clone());
+        inner.stale = false;
+        inner.read_at = Some(chrono::Local::now().to_rfc3339());
+        scene
+    }
+}
@@ -0,0 +1,88 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::AppState;
+
+/// Commands that answer from the last scene snapshot while Blender is not
+/// connected, with `stale` set in their result. Other commands that need
+/// Blender fail fast with `not_connected`; the rest only read files and
+/// stored data and work in every mode.
+pub const SERVES_STALE: &[&str] = &["search_scene", "audit_naming"];
+
+/// How much of the backend can answer
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// Blender is connected
+    Live,
+    /// Blender is gone, but the scene it last sent is kept, along with
+    /// history, assets and everything else stored
+    Cached,
+    /// Blender hasn't connected since the app started; stored data and
+    /// files only
+    Offline,
+}
+
+/// Emitted as `mode:changed`
+#[derive(Serialize, Clone, Debug)]
+pub struct ModeStatus {
+    pub mode: Mode,
+    pub since: String,
+    /// When the kept scene was read from Blender, in cached mode
+    pub scene_read_at: Option<String>,
+    pub serves_stale: &'static [&'static str],
+}
+
+pub struct ModeState {
+    status: Mutex<ModeStatus>,
+}
+
+impl Default for ModeState {
+    fn default() -> Self {
+        Self {
+            status: Mutex::new(ModeStatus {
+                mode: Mode::Offline,
+                since: chrono::Local::now().to_rfc3339(),
+                scene_read_at: None,
+                serves_stale: SERVES_STALE,
+            }),
+        }
+    }
+}
+
+/// Work out the mode after Blender connected or disconnected, and emit
+/// `mode:changed` when it changed
+pub async fn update<R: Runtime>(app: &AppHandle<R>) -> ModeStatus {
+    let state = app.state::<AppState>();
+    let connected = state.ws_sender.lock().await.is_some();
+    let scene_read_at = state.mirror.read_at().await;
+    let mode = match (connected, &scene_read_at) {
+        (true, _) => Mode::Live,
+        (false, Some(_)) => Mode::Cached,
+        (false, None) => Mode::Offline,
+    };
+    let status = {
+        let mut current = app.state::<ModeState>().status.lock().unwrap();
+        if current.mode == mode {
+            return current.clone();
+        }
+        *current = ModeStatus {
+            mode,
+            since: chrono::Local::now().to_rfc3339(),
+            scene_read_at: scene_read_at.filter(|_| mode == Mode::Cached),
+            serves_stale: SERVES_STALE,
+        };
+        current.clone()
+    };
+    if let Err(err) = app.emit("mode:changed", &status) {
+        eprintln!("Failed to emit mode:changed: {err}");
+    }
+    status
+}
+
+/// The current mode, for panels deciding what they can show
+#[tauri::command]
+pub async fn get_mode<R: Runtime>(app: AppHandle<R>) -> ModeStatus {
+    update(&app).await
+}
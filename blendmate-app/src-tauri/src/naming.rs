@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::bridge::{datablock_path, PropertyChange};
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Blender truncates datablock names beyond 63 bytes.
+const BLENDER_MAX_NAME: usize = 63;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct NamingConvention {
+    /// Required name prefix per object type (`MESH`, `LIGHT`, ...)
+    pub prefixes: HashMap<String, String>,
+    pub allow_spaces: bool,
+    pub max_length: usize,
+}
+
+impl Default for NamingConvention {
+    fn default() -> Self {
+        let prefixes = [
+            ("MESH", "GEO_"),
+            ("CURVE", "CRV_"),
+            ("LIGHT", "LGT_"),
+            ("CAMERA", "CAM_"),
+            ("EMPTY", "EMP_"),
+            ("ARMATURE", "RIG_"),
+        ]
+        .into_iter()
+        .map(|(object_type, prefix)| (object_type.to_string(), prefix.to_string()))
+        .collect();
+
+        Self {
+            prefixes,
+            allow_spaces: false,
+            max_length: BLENDER_MAX_NAME,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum NamingRule {
+    MissingPrefix,
+    ContainsSpaces,
+    TooLong,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct NamingViolation {
+    pub object: String,
+    pub object_type: String,
+    pub rule: NamingRule,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Proposed renames; nothing is changed until the plan is applied by id.
+#[derive(Serialize, Clone, Debug)]
+pub struct RenamePlan {
+    pub id: u64,
+    pub renames: Vec<Rename>,
+}
+
+#[derive(Serialize)]
+pub struct NamingAudit {
+    pub violations: Vec<NamingViolation>,
+    pub plan: RenamePlan,
+    /// Audited the last scene snapshot because Blender is not connected;
+    /// the plan can only be applied once it is back
+    pub stale: bool,
+}
+
+#[derive(Default)]
+pub struct NamingState {
+    plan: Mutex<Option<RenamePlan>>,
+    next_plan_id: AtomicU64,
+}
+
+#[tauri::command]
+pub fn get_naming_convention(settings: State<'_, SettingsStore>) -> NamingConvention {
+    settings.get().naming
+}
+
+#[tauri::command]
+pub fn set_naming_convention(convention: NamingConvention, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    if convention.max_length == 0 || convention.max_length > BLENDER_MAX_NAME {
+        return Err(CommandError::InvalidInput(format!(
+            "max_length must be between 1 and {}",
+            BLENDER_MAX_NAME
+        )));
+    }
+    settings.update(|s| s.naming = convention)?;
+    Ok(())
+}
+
+/// Check object names against the naming convention and prepare a rename plan
+#[tauri::command]
+pub async fn audit_naming(
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    naming: State<'_, NamingState>,
+) -> Result<NamingAudit, CommandError> {
+    let convention = settings.get().naming;
+    let (scene, stale) = state.mirror.scene_or_cached(&state.bridge).await?;
+    let objects = scene
+        .get("objects")
+        .and_then(Value::as_object)
+        .ok_or_else(|| "Scene data has no objects".to_string())?;
+
+    let mut violations = Vec::new();
+    let mut renames = Vec::new();
+    let mut taken: HashSet<String> = objects.keys().cloned().collect();
+
+    let mut names: Vec<&String> = objects.keys().collect();
+    names.sort();
+
+    for name in names {
+        let object_type = objects[name].get("type").and_then(Value::as_str).unwrap_or("").to_string();
+        let prefix = convention.prefixes.get(&object_type).map(String::as_str).unwrap_or("");
+
+        let mut violation = |rule: NamingRule, message: String| {
+            violations.push(NamingViolation {
+                object: name.clone(),
+                object_type: object_type.clone(),
+                rule,
+                message,
+            });
+        };
+
+        if !prefix.is_empty() && !name.starts_with(prefix) {
+            violation(NamingRule::MissingPrefix, format!("'{}' should start with '{}'", name, prefix));
+        }
+        if !convention.allow_spaces && name.contains(' ') {
+            violation(NamingRule::ContainsSpaces, format!("'{}' contains spaces", name));
+        }
+        if name.len() > convention.max_length {
+            violation(
+                NamingRule::TooLong,
+                format!("'{}' is longer than {} bytes", name, convention.max_length),
+            );
+        }
+
+        let suggested = suggest_name(name, prefix, &convention);
+        if suggested != *name {
+            taken.remove(name);
+            let unique = unique_name(&suggested, &taken, convention.max_length);
+            taken.insert(unique.clone());
+            renames.push(Rename { from: name.clone(), to: unique });
+        }
+    }
+
+    let plan = RenamePlan {
+        id: naming.next_plan_id.fetch_add(1, Ordering::Relaxed) + 1,
+        renames,
+    };
+    *naming.plan.lock().await = Some(plan.clone());
+
+    Ok(NamingAudit {
+        violations,
+        plan,
+        stale,
+    })
+}
+
+/// Apply the rename plan returned by the last `audit_naming` call
+#[tauri::command]
+pub async fn apply_rename_plan(
+    plan_id: u64,
+    state: State<'_, AppState>,
+    naming: State<'_, NamingState>,
+) -> Result<usize, CommandError> {
+    let mut pending = naming.plan.lock().await;
+    let plan = match pending.as_ref() {
+        Some(plan) if plan.id == plan_id => plan.clone(),
+        _ => {
+            return Err(CommandError::InvalidInput(
+                "Rename plan is no longer current, run the audit again".to_string(),
+            ))
+        }
+    };
+    if plan.renames.is_empty() {
+        *pending = None;
+        return Ok(0);
+    }
+
+    // The scene may have changed since the preview
+    let scene = state.mirror.scene(&state.bridge).await?;
+    let current = scene.get("objects").and_then(Value::as_object);
+    if let Some(missing) = plan
+        .renames
+        .iter()
+        .find(|r| current.is_none_or(|objects| !objects.contains_key(&r.from)))
+    {
+        return Err(CommandError::NotFound(format!(
+            "Object '{}' no longer exists, run the audit again",
+            missing.from
+        )));
+    }
+
+    let changes: Vec<PropertyChange> = plan
+        .renames
+        .iter()
+        .map(|rename| PropertyChange {
+            target: datablock_path("objects", &rename.from),
+            path: "name".to_string(),
+            value: Value::String(rename.to.clone()),
+        })
+        .collect();
+    state.bridge.execute_batch(&changes, "Blendmate: Batch rename").await?;
+
+    *pending = None;
+    state.mirror.invalidate().await;
+    Ok(changes.len())
+}
+
+fn suggest_name(name: &str, prefix: &str, convention: &NamingConvention) -> String {
+    let mut base = if convention.allow_spaces {
+        name.to_string()
+    } else {
+        name.split_whitespace().collect::<Vec<_>>().join("_")
+    };
+    if !prefix.is_empty() && !base.starts_with(prefix) {
+        base = format!("{}{}", prefix, base);
+    }
+    truncate(&base, convention.max_length)
+}
+
+/// Append `_001`, `_002`, ... until the name is free.
+fn unique_name(name: &str, taken: &HashSet<String>, max_length: usize) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    (1..)
+        .map(|n| {
+            let suffix = format!("_{:03}", n);
+            let keep = max_length.saturating_sub(suffix.len());
+            format!("{}{}", truncate(name, keep), suffix)
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Cut to at most `max_bytes` bytes of UTF-8, at a character boundary.
+fn truncate(name: &str, max_bytes: usize) -> String {
+    let mut end = max_bytes.min(name.len());
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    name[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggest_name_adds_prefix_and_replaces_spaces() {
+        let convention = NamingConvention::default();
+        assert_eq!(suggest_name("Big Rock", "GEO_", &convention), "GEO_Big_Rock");
+        assert_eq!(suggest_name("GEO_Rock", "GEO_", &convention), "GEO_Rock");
+        assert_eq!(suggest_name("Rock", "", &convention), "Rock");
+    }
+
+    #[test]
+    fn suggest_name_keeps_spaces_when_allowed() {
+        let convention = NamingConvention {
+            allow_spaces: true,
+            ..NamingConvention::default()
+        };
+        assert_eq!(suggest_name("Big Rock", "GEO_", &convention), "GEO_Big Rock");
+    }
+
+    #[test]
+    fn suggest_name_truncates_by_bytes() {
+        let convention = NamingConvention {
+            max_length: 6,
+            ..NamingConvention::default()
+        };
+        // "á" is two bytes and would end at byte 7
+        assert_eq!(suggest_name("Kámen", "GEO_", &convention), "GEO_K");
+        assert_eq!(suggest_name("Kámen", "G_", &convention), "G_Kám");
+    }
+
+    #[test]
+    fn truncate_keeps_multi_byte_names_within_blenders_limit() {
+        let name = "Žluťoučký kůň úpěl ďábelské ódy ".repeat(3);
+        let cut = truncate(&name, BLENDER_MAX_NAME);
+        assert!(cut.len() <= BLENDER_MAX_NAME);
+        assert!(name.starts_with(&cut));
+        assert_eq!(truncate("日本語", 4), "日");
+    }
+
+    #[test]
+    fn unique_name_counts_up_until_free() {
+        let taken: HashSet<String> = ["Rock", "Rock_001"].into_iter().map(String::from).collect();
+        assert_eq!(unique_name("Stone", &taken, 63), "Stone");
+        assert_eq!(unique_name("Rock", &taken, 63), "Rock_002");
+    }
+
+    #[test]
+    fn unique_name_stays_within_max_length() {
+        let taken: HashSet<String> = ["Boulder"].into_iter().map(String::from).collect();
+        assert_eq!(unique_name("Boulder", &taken, 7), "Bou_001");
+    }
+
+    #[test]
+    fn unique_name_counts_suffix_in_bytes() {
+        let taken: HashSet<String> = ["Kámen"].into_iter().map(String::from).collect();
+        // Two bytes are left next to "_001", and "á" would need a third
+        assert_eq!(unique_name("Kámen", &taken, 6), "K_001");
+    }
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::protocol;
+use crate::quality::{ConnectionQuality, QualityTracker};
+use crate::settings::SettingsStore;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Force remote shaping on or off. By default a session is remote when
+    /// Blender connects from a non-loopback address (SSH tunnels look local).
+    pub treat_as_remote: Option<bool>,
+    /// Preview traffic cap on remote sessions, in KiB per second
+    pub remote_max_kib_per_sec: f64,
+    /// Share of the measured throughput previews may use; the rest is left
+    /// for command traffic
+    pub preview_share: f64,
+    /// Frame rate below which quality is lowered instead
+    pub min_fps: f64,
+    pub min_quality: u8,
+    pub max_quality: u8,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            treat_as_remote: None,
+            remote_max_kib_per_sec: 256.0,
+            preview_share: 0.5,
+            min_fps: 1.0,
+            min_quality: 30,
+            max_quality: 85,
+        }
+    }
+}
+
+/// The connected Blender's address and link quality
+#[derive(Default)]
+pub struct Session {
+    peer: Mutex<Option<SocketAddr>>,
+    quality: Mutex<QualityTracker>,
+}
+
+impl Session {
+    pub fn connected(&self, peer: SocketAddr) {
+        *self.peer.lock().unwrap() = Some(peer);
+        self.quality.lock().unwrap().connected();
+    }
+
+    pub fn disconnected(&self) {
+        *self.peer.lock().unwrap() = None;
+        self.quality.lock().unwrap().disconnected();
+    }
+
+    /// Track heartbeats among incoming messages
+    pub fn observe(&self, message: &Value) {
+        if protocol::message_type(message).as_deref() == Some("heartbeat") {
+            self.quality.lock().unwrap().heartbeat();
+        }
+    }
+
+    /// A backend ping's round trip, or `None` when it timed out
+    pub fn record_ping(&self, rtt: Option<Duration>) {
+        self.quality.lock().unwrap().ping(rtt);
+    }
+
+    pub fn quality(&self) -> ConnectionQuality {
+        let peer = self.peer().map(|peer| peer.to_string());
+        self.quality.lock().unwrap().snapshot(peer)
+    }
+
+    pub fn peer(&self) -> Option<SocketAddr> {
+        *self.peer.lock().unwrap()
+    }
+
+    /// The connected peer's address. `session`, when given, must still be
+    /// the connected one, so a command meant for one Blender does not reach
+    /// another.
+    pub fn check_peer(&self, session: Option<&str>) -> Result<String, CommandError> {
+        let Some(peer) = self.peer().map(|peer| peer.to_string()) else {
+            return Err(CommandError::not_connected());
+        };
+        if session.is_some_and(|session| session != peer) {
+            return Err(CommandError::NotConnected(
+                "That Blender session is no longer connected".to_string(),
+            ));
+        }
+        Ok(peer)
+    }
+
+    /// Whether streams to this session should be shaped
+    pub fn is_remote(&self, settings: &NetworkSettings) -> bool {
+        settings
+            .treat_as_remote
+            .unwrap_or_else(|| self.peer().is_some_and(|peer| !peer.ip().is_loopback()))
+    }
+}
+
+/// Picks frame rate and JPEG quality for a stream so it stays within the
+/// remote cap and a share of the throughput measured on its own transfers.
+///
+/// The frame rate drops first; once it would fall below `min_fps`, quality
+/// is lowered instead, and raised again while there is headroom.
+#[derive(Default)]
+pub struct BandwidthShaper {
+    /// Bytes per second, smoothed. Includes Blender's capture time, so it
+    /// errs low.
+    throughput: Option<f64>,
+    /// Bytes per frame, smoothed
+    frame_bytes: Option<f64>,
+    quality: Option<u8>,
+}
+
+/// Weight of the newest measurement in the running averages
+const SMOOTHING: f64 = 0.3;
+
+impl BandwidthShaper {
+    /// Record one transfer of `bytes` that took `elapsed`
+    pub fn record(&mut self, bytes: usize, elapsed: Duration) {
+        let bytes = bytes as f64;
+        let rate = bytes / elapsed.as_secs_f64().max(0.001);
+        self.throughput = Some(smooth(self.throughput, rate));
+        self.frame_bytes = Some(smooth(self.frame_bytes, bytes));
+    }
+
+    /// Frame rate and quality for the next frame
+    pub fn next(&mut self, settings: &NetworkSettings, remote: bool, max_fps: f64) -> (f64, u8) {
+        let max_quality = settings.max_quality.clamp(1, 100);
+        let min_quality = settings.min_quality.clamp(1, max_quality);
+        if !remote {
+            self.quality = None;
+            return (max_fps, max_quality);
+        }
+
+        let quality = *self.quality.get_or_insert(max_quality);
+        let Some(frame_bytes) = self.frame_bytes else {
+            // Nothing measured yet; start slow
+            return (max_fps.min(settings.min_fps.max(0.1)), quality);
+        };
+
+        let mut budget = settings.remote_max_kib_per_sec.max(1.0) * 1024.0;
+        if let Some(throughput) = self.throughput {
+            budget = budget.min(throughput * settings.preview_share.clamp(0.05, 1.0));
+        }
+        let affordable = budget / frame_bytes.max(1.0);
+
+        let quality = if affordable < settings.min_fps && quality > min_quality {
+            quality.saturating_sub(10).max(min_quality)
+        } else if affordable > max_fps * 1.5 && quality < max_quality {
+            (quality + 5).min(max_quality)
+        } else {
+            quality
+        };
+        self.quality = Some(quality);
+        (affordable.min(max_fps), quality)
+    }
+}
+
+fn smooth(average: Option<f64>, value: f64) -> f64 {
+    match average {
+        Some(average) => average + (value - average) * SMOOTHING,
+        None => value,
+    }
+}
+
+#[tauri::command]
+pub fn get_network_settings(settings: State<'_, SettingsStore>) -> NetworkSettings {
+    settings.get().network
+}
+
+#[tauri::command]
+pub fn set_network_settings(network: NetworkSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.network = network)?;
+    Ok(())
+}
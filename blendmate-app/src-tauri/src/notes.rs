@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::storage::SharedStorage;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Note {
+    pub name: String,
+    /// Markdown
+    pub text: String,
+    pub updated_at: String,
+}
+
+/// Free-form notes kept in storage as one document, by name
+pub struct NoteStore {
+    storage: SharedStorage,
+    notes: Mutex<BTreeMap<String, Note>>,
+}
+
+impl NoteStore {
+    pub fn load(storage: SharedStorage) -> Self {
+        let notes = storage.get_json("notes", "notes").unwrap_or_default();
+        Self {
+            storage,
+            notes: Mutex::new(notes),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Note> {
+        self.notes.lock().unwrap().get(name).cloned()
+    }
+
+    /// Create or replace a note
+    pub fn save(&self, name: &str, text: String) -> Result<Note, CommandError> {
+        let note = Note {
+            name: name.to_string(),
+            text,
+            updated_at: chrono::Local::now().to_rfc3339(),
+        };
+        let mut notes = self.notes.lock().unwrap();
+        notes.insert(note.name.clone(), note.clone());
+        self.storage.put_json("notes", "notes", &*notes)?;
+        Ok(note)
+    }
+}
+
+#[tauri::command]
+pub fn list_notes(notes: State<'_, NoteStore>) -> Vec<Note> {
+    notes.notes.lock().unwrap().values().cloned().collect()
+}
+
+#[tauri::command]
+pub fn save_note(name: String, text: String, notes: State<'_, NoteStore>) -> Result<Note, CommandError> {
+    if name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Note name is empty".to_string()));
+    }
+    notes.save(&name, text)
+}
+
+#[tauri::command]
+pub fn delete_note(name: String, notes: State<'_, NoteStore>) -> Result<(), CommandError> {
+    let mut all = notes.notes.lock().unwrap();
+    if all.remove(&name).is_none() {
+        return Err(CommandError::NotFound(format!("Note '{}' not found", name)));
+    }
+    notes.storage.put_json("notes", "notes", &*all)
+}
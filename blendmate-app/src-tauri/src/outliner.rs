@@ -0,0 +1,313 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::AppState;
+
+/// Versions whose changes are kept for diffs; clients further behind get
+/// the full tree
+const MAX_HISTORY: usize = 32;
+
+/// Nodes per page of a full tree
+const DEFAULT_PAGE: usize = 1000;
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Collection,
+    Object,
+}
+
+/// One row of the outliner. Objects appear under every collection they
+/// are linked to, nested under their parent object when it is in the same
+/// collection, so ids are paths such as `Scene Collection/Props/Cube`.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct OutlinerNode {
+    pub id: String,
+    pub parent: Option<String>,
+    /// Position among the parent's children
+    pub index: usize,
+    pub kind: NodeKind,
+    pub name: String,
+    /// Blender object type, e.g. `MESH`
+    pub object_type: Option<String>,
+    pub child_count: usize,
+    pub hide_viewport: bool,
+    pub hide_render: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum OutlinerChange {
+    /// A new node, or one whose fields changed
+    Upsert { node: OutlinerNode },
+    Remove { id: String },
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum OutlinerSync {
+    /// Changes since the version the client knows; empty when it is current
+    Diff { version: u64, changes: Vec<OutlinerChange> },
+    /// The tree in depth-first order, parents before children. Fetch the
+    /// rest with `get_outliner_page` while `next_offset` is set.
+    Full {
+        version: u64,
+        nodes: Vec<OutlinerNode>,
+        total: usize,
+        next_offset: Option<usize>,
+    },
+}
+
+#[derive(Default)]
+struct Tree {
+    version: u64,
+    /// Snapshot the tree was built from
+    source: Option<Arc<Value>>,
+    file_generation: u64,
+    nodes: Vec<OutlinerNode>,
+    /// Changes that led to each version, oldest first
+    history: VecDeque<(u64, Vec<OutlinerChange>)>,
+}
+
+impl Tree {
+    /// Rebuild from `scene` and record what changed as a new version
+    fn update(&mut self, scene: &Value, same_file: bool) {
+        let nodes = build(scene);
+        if !same_file {
+            self.history.clear();
+        }
+        let changes = diff(&self.nodes, &nodes);
+        if changes.is_empty() && same_file {
+            return;
+        }
+        self.version += 1;
+        self.nodes = nodes;
+        // A different file can't be reached by diffs
+        if same_file {
+            self.history.push_back((self.version, changes));
+            if self.history.len() > MAX_HISTORY {
+                self.history.pop_front();
+            }
+        }
+    }
+
+    /// Changes from `known` to the current version, if all are kept and
+    /// smaller than sending the tree again
+    fn changes_since(&self, known: u64) -> Option<Vec<OutlinerChange>> {
+        if known == self.version {
+            return Some(Vec::new());
+        }
+        let first = self.history.front()?.0;
+        if known > self.version || known + 1 < first {
+            return None;
+        }
+        // Only the last change per node matters
+        let mut latest: HashMap<String, (usize, OutlinerChange)> = HashMap::new();
+        let changes = self.history.iter().filter(|(version, _)| *version > known).flat_map(|(_, c)| c);
+        for (order, change) in changes.enumerate() {
+            let id = match change {
+                OutlinerChange::Upsert { node } => node.id.clone(),
+                OutlinerChange::Remove { id } => id.clone(),
+            };
+            latest.insert(id, (order, change.clone()));
+        }
+        if latest.len() > self.nodes.len() / 2 {
+            return None;
+        }
+        let mut merged: Vec<(usize, OutlinerChange)> = latest.into_values().collect();
+        merged.sort_by_key(|(order, _)| *order);
+        Some(merged.into_iter().map(|(_, change)| change).collect())
+    }
+
+    fn page(&self, offset: usize, limit: Option<usize>) -> OutlinerSync {
+        let limit = limit.unwrap_or(DEFAULT_PAGE).max(1);
+        let end = offset.saturating_add(limit).min(self.nodes.len());
+        OutlinerSync::Full {
+            version: self.version,
+            nodes: self.nodes.get(offset..end).map(<[_]>::to_vec).unwrap_or_default(),
+            total: self.nodes.len(),
+            next_offset: (end < self.nodes.len()).then_some(end),
+        }
+    }
+}
+
+fn flag(value: &Value, key: &str) -> bool {
+    value.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Flatten the scene's collection tree into outliner rows
+fn build(scene: &Value) -> Vec<OutlinerNode> {
+    let empty = Map::new();
+    let objects = scene.get("objects").and_then(Value::as_object).unwrap_or(&empty);
+    let mut nodes = Vec::new();
+    if let Some(root) = scene.get("collections") {
+        add_collection(root, None, 0, objects, &mut nodes);
+    }
+    nodes
+}
+
+fn add_collection(
+    collection: &Value,
+    parent: Option<&str>,
+    index: usize,
+    objects: &Map<String, Value>,
+    nodes: &mut Vec<OutlinerNode>,
+) {
+    let name = collection.get("name").and_then(Value::as_str).unwrap_or_default();
+    let id = match parent {
+        Some(parent) => format!("{}/{}", parent, name),
+        None => name.to_string(),
+    };
+    let linked: Vec<&str> = collection
+        .get("objects")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let members: HashSet<&str> = linked.iter().copied().collect();
+    // Objects whose parent is linked here are listed under the parent
+    let top: Vec<&str> = linked
+        .iter()
+        .copied()
+        .filter(|name| {
+            let parent = objects.get(*name).and_then(|o| o.get("parent")).and_then(Value::as_str);
+            parent.is_none_or(|parent| !members.contains(parent))
+        })
+        .collect();
+    let children: Vec<&Value> = collection
+        .get("children")
+        .and_then(Value::as_array)
+        .map(|children| children.iter().collect())
+        .unwrap_or_default();
+
+    nodes.push(OutlinerNode {
+        id: id.clone(),
+        parent: parent.map(str::to_string),
+        index,
+        kind: NodeKind::Collection,
+        name: name.to_string(),
+        object_type: None,
+        child_count: children.len() + top.len(),
+        hide_viewport: flag(collection, "hide_viewport"),
+        hide_render: flag(collection, "hide_render"),
+    });
+    for (index, child) in children.iter().enumerate() {
+        add_collection(child, Some(&id), index, objects, nodes);
+    }
+    for (index, name) in top.iter().enumerate() {
+        add_object(name, &id, children.len() + index, objects, &members, nodes);
+    }
+}
+
+fn add_object(
+    name: &str,
+    parent: &str,
+    index: usize,
+    objects: &Map<String, Value>,
+    members: &HashSet<&str>,
+    nodes: &mut Vec<OutlinerNode>,
+) {
+    let null = Value::Null;
+    let object = objects.get(name).unwrap_or(&null);
+    let id = format!("{}/{}", parent, name);
+    let children: Vec<&str> = object
+        .get("children")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).filter(|c| members.contains(c)).collect())
+        .unwrap_or_default();
+    nodes.push(OutlinerNode {
+        id: id.clone(),
+        parent: Some(parent.to_string()),
+        index,
+        kind: NodeKind::Object,
+        name: name.to_string(),
+        object_type: object.get("type").and_then(Value::as_str).map(str::to_string),
+        child_count: children.len(),
+        hide_viewport: flag(object, "hide_viewport"),
+        hide_render: flag(object, "hide_render"),
+    });
+    for (index, child) in children.iter().enumerate() {
+        add_object(child, &id, index, objects, members, nodes);
+    }
+}
+
+/// Removals first, then new and changed nodes in tree order
+fn diff(old: &[OutlinerNode], new: &[OutlinerNode]) -> Vec<OutlinerChange> {
+    let before: HashMap<&str, &OutlinerNode> = old.iter().map(|node| (node.id.as_str(), node)).collect();
+    let after: HashSet<&str> = new.iter().map(|node| node.id.as_str()).collect();
+    let removed = old
+        .iter()
+        .filter(|node| !after.contains(node.id.as_str()))
+        .map(|node| OutlinerChange::Remove { id: node.id.clone() });
+    let upserted = new
+        .iter()
+        .filter(|node| before.get(node.id.as_str()) != Some(node))
+        .map(|node| OutlinerChange::Upsert { node: node.clone() });
+    removed.chain(upserted).collect()
+}
+
+/// Versioned outliner tree built from the scene mirror
+#[derive(Default)]
+pub struct OutlinerState {
+    tree: Mutex<Tree>,
+}
+
+impl OutlinerState {
+    /// Bring the tree up to date with the mirror, then read it
+    async fn with_tree<T>(&self, state: &AppState, read: impl FnOnce(&Tree) -> T) -> Result<T, CommandError> {
+        let file_generation = state.mirror.file_generation().await;
+        let scene = state.mirror.scene(&state.bridge).await?;
+        let mut tree = self.tree.lock().unwrap();
+        if !tree.source.as_ref().is_some_and(|source| Arc::ptr_eq(source, &scene)) {
+            let same_file = tree.source.is_some() && tree.file_generation == file_generation;
+            tree.update(&scene, same_file);
+            tree.source = Some(scene);
+            tree.file_generation = file_generation;
+        }
+        Ok(read(&tree))
+    }
+}
+
+/// The outliner since `known_version`: a diff when the backend still has
+/// the changes since then, else the first page of the full tree
+#[tauri::command]
+pub async fn sync_outliner(
+    known_version: Option<u64>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    outliner: State<'_, OutlinerState>,
+) -> Result<OutlinerSync, CommandError> {
+    outliner
+        .with_tree(&state, |tree| {
+            match known_version.and_then(|known| tree.changes_since(known)) {
+                Some(changes) => OutlinerSync::Diff {
+                    version: tree.version,
+                    changes,
+                },
+                None => tree.page(0, limit),
+            }
+        })
+        .await
+}
+
+/// A further page of the full tree. Fails once the tree has moved past
+/// `version`; sync again from the version the client has.
+#[tauri::command]
+pub fn get_outliner_page(
+    version: u64,
+    offset: usize,
+    limit: Option<usize>,
+    outliner: State<'_, OutlinerState>,
+) -> Result<OutlinerSync, CommandError> {
+    let tree = outliner.tree.lock().unwrap();
+    if tree.version != version {
+        return Err(CommandError::NotFound(format!(
+            "Outliner version {} is gone; the current one is {}",
+            version, tree.version
+        )));
+    }
+    Ok(tree.page(offset, limit))
+}
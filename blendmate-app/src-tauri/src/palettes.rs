@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Images are scaled down to this size before clustering
+const SAMPLE_SIZE: u32 = 64;
+
+const KMEANS_ITERATIONS: usize = 12;
+
+const MAX_COLORS: u32 = 16;
+
+/// Linear Rec.709 to ACEScg (AP1), with the Bradford D65 to D60 adaptation
+const LINEAR_TO_ACESCG: [[f32; 3]; 3] = [
+    [0.613_097_4, 0.339_523_1, 0.047_379_5],
+    [0.070_193_7, 0.916_353_9, 0.013_452_4],
+    [0.020_615_6, 0.109_569_8, 0.869_814_6],
+];
+
+const ACESCG_TO_LINEAR: [[f32; 3]; 3] = [
+    [1.704_858_7, -0.621_716_0, -0.083_142_7],
+    [-0.130_076_8, 1.140_735_8, -0.010_658_9],
+    [-0.023_964_1, -0.128_975_5, 1.152_939_7],
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// Display encoded sRGB, as in hex codes and color pickers
+    Srgb,
+    /// Linear Rec.709, Blender's default scene linear space
+    LinearSrgb,
+    Acescg,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorTarget {
+    /// Base color of a material
+    Material,
+    /// Color of the current paint brush
+    Brush,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Swatch {
+    pub name: Option<String>,
+    /// Linear Rec.709
+    pub color: [f32; 3],
+    /// Share of the image, for swatches extracted from one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Palette {
+    pub name: String,
+    pub swatches: Vec<Swatch>,
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn multiply(matrix: &[[f32; 3]; 3], color: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * color[0] + row[1] * color[1] + row[2] * color[2])
+}
+
+/// Convert `color` between color spaces, going through linear Rec.709
+pub fn convert(color: [f32; 3], from: ColorSpace, to: ColorSpace) -> [f32; 3] {
+    let linear = match from {
+        ColorSpace::Srgb => color.map(srgb_to_linear),
+        ColorSpace::LinearSrgb => color,
+        ColorSpace::Acescg => multiply(&ACESCG_TO_LINEAR, color),
+    };
+    match to {
+        ColorSpace::Srgb => linear.map(linear_to_srgb),
+        ColorSpace::LinearSrgb => linear,
+        ColorSpace::Acescg => multiply(&LINEAR_TO_ACESCG, linear),
+    }
+}
+
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
+
+fn nearest(centers: &[[f32; 3]], pixel: &[f32; 3]) -> usize {
+    (0..centers.len())
+        .min_by(|&a, &b| distance(&centers[a], pixel).total_cmp(&distance(&centers[b], pixel)))
+        .unwrap_or_default()
+}
+
+/// The `count` dominant colors of `pixels` with their share, largest first.
+/// k-means, seeded with evenly spaced pixels by brightness so the result
+/// is the same on every run.
+fn dominant(pixels: &[[f32; 3]], count: usize) -> Vec<([f32; 3], f32)> {
+    if pixels.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let mut sorted = pixels.to_vec();
+    sorted.sort_by(|a, b| (a[0] + a[1] + a[2]).total_cmp(&(b[0] + b[1] + b[2])));
+    let mut centers: Vec<[f32; 3]> = (0..count)
+        .map(|i| sorted[(2 * i + 1) * sorted.len() / (2 * count)])
+        .collect();
+
+    let mut sizes = vec![0usize; count];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut sums = vec![[0.0f32; 3]; count];
+        sizes = vec![0; count];
+        for pixel in pixels {
+            let cluster = nearest(&centers, pixel);
+            sizes[cluster] += 1;
+            for (sum, value) in sums[cluster].iter_mut().zip(pixel) {
+                *sum += value;
+            }
+        }
+        for ((center, sum), size) in centers.iter_mut().zip(&sums).zip(&sizes) {
+            if *size > 0 {
+                *center = sum.map(|value| value / *size as f32);
+            }
+        }
+    }
+
+    // Identical seeds, e.g. on a flat image, leave clusters empty
+    let mut colors: Vec<([f32; 3], f32)> = centers
+        .into_iter()
+        .zip(sizes)
+        .filter(|(_, size)| *size > 0)
+        .map(|(center, size)| (center, size as f32 / pixels.len() as f32))
+        .collect();
+    colors.sort_by(|a, b| b.1.total_cmp(&a.1));
+    colors
+}
+
+/// Project palettes, persisted as `palettes.json` in the app data directory
+/// after every change.
+pub struct PaletteStore {
+    path: PathBuf,
+    palettes: Mutex<HashMap<String, Vec<Palette>>>,
+}
+
+impl PaletteStore {
+    pub fn load(path: PathBuf) -> Self {
+        let palettes = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            palettes: Mutex::new(palettes),
+        }
+    }
+
+    fn update<T>(
+        &self,
+        project: String,
+        change: impl FnOnce(&mut Vec<Palette>) -> Result<T, CommandError>,
+    ) -> Result<T, CommandError> {
+        let mut all = self.palettes.lock().unwrap();
+        let result = change(all.entry(project).or_default())?;
+        save(&self.path, &all)?;
+        Ok(result)
+    }
+}
+
+fn save(path: &Path, palettes: &HashMap<String, Vec<Palette>>) -> Result<(), CommandError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| CommandError::Io(format!("Failed to create data directory: {}", e)))?;
+    }
+    let text = serde_json::to_string_pretty(palettes).map_err(|e| format!("Failed to serialize palettes: {}", e))?;
+    fs::write(path, text).map_err(|e| CommandError::Io(format!("Failed to write palettes: {}", e)))
+}
+
+/// Palettes of a project (default: the active one)
+#[tauri::command]
+pub fn list_palettes(
+    project: Option<String>,
+    palettes: State<'_, PaletteStore>,
+    settings: State<'_, SettingsStore>,
+) -> Vec<Palette> {
+    let key = projects::key(&settings, project);
+    palettes.palettes.lock().unwrap().get(&key).cloned().unwrap_or_default()
+}
+
+/// Add a palette, or replace the one with the same name
+#[tauri::command]
+pub fn save_palette(
+    palette: Palette,
+    project: Option<String>,
+    palettes: State<'_, PaletteStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<Palette>, CommandError> {
+    if palette.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Palette name is required".to_string()));
+    }
+    palettes.update(projects::key(&settings, project), |list| {
+        match list.iter_mut().find(|p| p.name == palette.name) {
+            Some(existing) => *existing = palette,
+            None => list.push(palette),
+        }
+        Ok(list.clone())
+    })
+}
+
+#[tauri::command]
+pub fn delete_palette(
+    name: String,
+    project: Option<String>,
+    palettes: State<'_, PaletteStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<Palette>, CommandError> {
+    palettes.update(projects::key(&settings, project), |list| {
+        let before = list.len();
+        list.retain(|p| p.name != name);
+        if list.len() == before {
+            return Err(CommandError::NotFound(format!("Unknown palette '{}'", name)));
+        }
+        Ok(list.clone())
+    })
+}
+
+#[tauri::command]
+pub fn convert_color(color: [f32; 3], from: ColorSpace, to: ColorSpace) -> [f32; 3] {
+    convert(color, from, to)
+}
+
+/// Dominant colors of an image, e.g. a reference board image, largest
+/// share first. Up to `count` swatches (default 6), in linear Rec.709.
+#[tauri::command]
+pub async fn extract_palette(path: String, count: Option<u32>) -> Result<Vec<Swatch>, CommandError> {
+    let count = count.unwrap_or(6).clamp(1, MAX_COLORS) as usize;
+    tauri::async_runtime::spawn_blocking(move || {
+        let image =
+            image::open(&path).map_err(|e| CommandError::InvalidInput(format!("Can't read image {}: {}", path, e)))?;
+        let pixels: Vec<[f32; 3]> = image
+            .thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+            .to_rgb8()
+            .pixels()
+            .map(|pixel| pixel.0.map(|channel| channel as f32 / 255.0))
+            .collect();
+        // Cluster display encoded values, which are closer to how different colors look
+        Ok(dominant(&pixels, count)
+            .into_iter()
+            .map(|(color, weight)| Swatch {
+                name: None,
+                color: convert(color, ColorSpace::Srgb, ColorSpace::LinearSrgb),
+                weight: Some(weight),
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("Palette extraction failed: {}", e))?
+}
+
+/// Set a material's base color or the current paint brush's color.
+/// `color` is linear Rec.709; brushes get it display encoded. `name`
+/// defaults to the active material or brush.
+#[tauri::command]
+pub async fn push_color(
+    color: [f32; 3],
+    target: ColorTarget,
+    name: Option<String>,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<Value, CommandError> {
+    let params = json!({
+        "kind": target,
+        "linear": color,
+        "srgb": convert(color, ColorSpace::LinearSrgb, ColorSpace::Srgb),
+    });
+    let name = name.unwrap_or_default();
+    let result = state.bridge.request("palette.apply", &name, params.clone()).await?;
+    history.record(&settings, "palette.apply", &name, &params);
+    Ok(result)
+}
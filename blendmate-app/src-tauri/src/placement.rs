@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{
+    AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Runtime, State, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder, Window, WindowEvent,
+};
+
+use crate::errors::CommandError;
+
+/// How often the attached monitors are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Pixels of a window's top edge that must be on a monitor for it to count
+/// as reachable, enough to grab it by the title bar
+const GRAB_MARGIN: i32 = 48;
+
+/// Detached panels get this label prefix
+const PANEL_PREFIX: &str = "panel-";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Geometry {
+    /// Outer position and size in physical pixels
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct SavedGeometry {
+    geometry: Geometry,
+    saved_at: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct PlacementFile {
+    /// Monitor layout (see `layout_key`) to window label to geometry
+    layouts: BTreeMap<String, BTreeMap<String, SavedGeometry>>,
+}
+
+#[derive(Clone, Copy)]
+struct Area {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Area {
+    fn of(monitor: &Monitor) -> Self {
+        Self {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        }
+    }
+
+    /// Whether the top strip of `geometry` overlaps this area
+    fn can_grab(&self, geometry: &Geometry) -> bool {
+        let right = geometry.x.saturating_add(geometry.width as i32);
+        let overlap_x = right.min(self.x + self.width as i32) - geometry.x.max(self.x);
+        let top = geometry.y;
+        overlap_x >= GRAB_MARGIN && top >= self.y && top + GRAB_MARGIN <= self.y + self.height as i32
+    }
+}
+
+/// Window geometry per monitor layout, saved as `windows.json` in the app
+/// config directory. Geometry stays on this machine rather than in the
+/// storage backend, as other seats have other displays.
+pub struct PlacementStore {
+    path: PathBuf,
+    file: Mutex<PlacementFile>,
+    /// Layout the windows were last placed for
+    layout: Mutex<Option<String>>,
+}
+
+impl PlacementStore {
+    pub fn load(path: PathBuf) -> Self {
+        let file = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            file: Mutex::new(file),
+            layout: Mutex::new(None),
+        }
+    }
+
+    fn record(&self, layout: String, label: &str, geometry: Geometry) {
+        let mut file = self.file.lock().unwrap();
+        let saved = file.layouts.entry(layout).or_default();
+        if saved.get(label).is_some_and(|saved| saved.geometry == geometry) {
+            return;
+        }
+        saved.insert(
+            label.to_string(),
+            SavedGeometry {
+                geometry,
+                saved_at: chrono::Local::now().to_rfc3339(),
+            },
+        );
+        if let Err(err) = save(&self.path, &file) {
+            eprintln!("Failed to save window placement: {err}");
+        }
+    }
+
+    /// Geometry of `label` for `layout`, or the latest one saved under any
+    /// layout that is still reachable on `monitors`
+    fn lookup(&self, layout: &str, label: &str, monitors: &[Area]) -> Option<Geometry> {
+        let file = self.file.lock().unwrap();
+        if let Some(saved) = file.layouts.get(layout).and_then(|windows| windows.get(label)) {
+            return Some(saved.geometry);
+        }
+        file.layouts
+            .values()
+            .filter_map(|windows| windows.get(label))
+            .filter(|saved| monitors.iter().any(|area| area.can_grab(&saved.geometry)))
+            .max_by(|a, b| a.saved_at.cmp(&b.saved_at))
+            .map(|saved| saved.geometry)
+    }
+}
+
+fn save(path: &Path, file: &PlacementFile) -> Result<(), CommandError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| CommandError::Io(format!("Failed to create config directory: {}", e)))?;
+    }
+    let text = serde_json::to_string_pretty(file).map_err(|e| format!("Failed to serialize window placement: {}", e))?;
+    fs::write(path, text).map_err(|e| CommandError::Io(format!("Failed to write window placement: {}", e)))
+}
+
+/// Attached monitors as a stable key, e.g. `DELL U2720Q@0,0 3840x2160x1.5`
+fn layout_key(monitors: &[Monitor]) -> String {
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            format!(
+                "{}@{},{} {}x{}x{}",
+                monitor.name().map(String::as_str).unwrap_or("?"),
+                monitor.position().x,
+                monitor.position().y,
+                monitor.size().width,
+                monitor.size().height,
+                monitor.scale_factor()
+            )
+        })
+        .collect();
+    parts.sort();
+    parts.join("; ")
+}
+
+fn monitors<R: Runtime>(app: &AppHandle<R>) -> Option<(String, Vec<Area>, Option<Area>)> {
+    let monitors = app.available_monitors().ok().filter(|monitors| !monitors.is_empty())?;
+    let primary = app.primary_monitor().ok().flatten().map(|monitor| Area::of(&monitor));
+    Some((layout_key(&monitors), monitors.iter().map(Area::of).collect(), primary))
+}
+
+fn geometry_of<R: Runtime>(window: &WebviewWindow<R>) -> Option<Geometry> {
+    if window.is_minimized().unwrap_or(false) {
+        return None;
+    }
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    Some(Geometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: window.is_maximized().unwrap_or(false),
+    })
+}
+
+/// Put `window` where it was on this monitor layout. A window saved on a
+/// monitor that is gone, or never saved, is centered on the primary
+/// monitor with its size clamped to fit.
+fn place<R: Runtime>(
+    window: &WebviewWindow<R>,
+    store: &PlacementStore,
+    layout: &str,
+    areas: &[Area],
+    primary: Option<Area>,
+) {
+    let saved = store.lookup(layout, window.label(), areas);
+    let geometry = match saved.or_else(|| geometry_of(window)) {
+        Some(geometry) if areas.iter().any(|area| area.can_grab(&geometry)) => geometry,
+        Some(geometry) => {
+            let Some(area) = primary.or_else(|| areas.first().copied()) else {
+                return;
+            };
+            let width = geometry.width.min(area.width);
+            let height = geometry.height.min(area.height);
+            Geometry {
+                x: area.x + (area.width - width) as i32 / 2,
+                y: area.y + (area.height - height) as i32 / 2,
+                width,
+                height,
+                maximized: geometry.maximized,
+            }
+        }
+        None => return,
+    };
+
+    let result = window
+        .unmaximize()
+        .and_then(|()| window.set_size(PhysicalSize::new(geometry.width, geometry.height)))
+        .and_then(|()| window.set_position(PhysicalPosition::new(geometry.x, geometry.y)))
+        .and_then(|()| if geometry.maximized { window.maximize() } else { Ok(()) });
+    if let Err(err) = result {
+        eprintln!("Failed to place window {}: {err}", window.label());
+    }
+}
+
+/// Place a window created from the config, then show it; windows start
+/// hidden so they don't flash at the default position
+pub fn restore<R: Runtime>(window: &WebviewWindow<R>) {
+    let app = window.app_handle();
+    if let Some((layout, areas, primary)) = monitors(app) {
+        let store = app.state::<PlacementStore>();
+        place(window, &store, &layout, &areas, primary);
+        *store.layout.lock().unwrap() = Some(layout);
+    }
+    if let Err(err) = window.show() {
+        eprintln!("Failed to show window {}: {err}", window.label());
+    }
+}
+
+/// Remember where windows are moved and resized to, under the monitor
+/// layout at the time
+pub fn on_window_event<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_) | WindowEvent::CloseRequested { .. }) {
+        return;
+    }
+    let Some(store) = window.try_state::<PlacementStore>() else {
+        return;
+    };
+    // Nothing is recorded until the window was placed, or while the
+    // layout is changing under it
+    let Some(layout) = store.layout.lock().unwrap().clone() else {
+        return;
+    };
+    if monitors(window.app_handle()).is_none_or(|(current, _, _)| current != layout) {
+        return;
+    }
+    let Some(window) = window.get_webview_window(window.label()) else {
+        return;
+    };
+    if let Some(geometry) = geometry_of(&window) {
+        store.record(layout, window.label(), geometry);
+    }
+}
+
+/// Watch for monitors being attached or detached, and move every window to
+/// its place for the new layout, or back onto a monitor that is there
+pub fn start_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let Some((layout, areas, primary)) = monitors(&app) else {
+                continue;
+            };
+            let store = app.state::<PlacementStore>();
+            if store.layout.lock().unwrap().as_deref() == Some(layout.as_str()) {
+                continue;
+            }
+            for window in app.webview_windows().values() {
+                place(window, &store, &layout, &areas, primary);
+            }
+            *store.layout.lock().unwrap() = Some(layout);
+        }
+    });
+}
+
+/// Open a panel in a window of its own, where it was last placed on this
+/// monitor layout. The frontend renders the panel named in the `panel`
+/// query parameter. An open panel is brought to the front.
+#[tauri::command]
+pub fn open_panel_window<R: Runtime>(
+    panel: String,
+    title: Option<String>,
+    app: AppHandle<R>,
+) -> Result<(), CommandError> {
+    if panel.is_empty() || !panel.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(CommandError::InvalidInput(format!("Invalid panel name '{}'", panel)));
+    }
+    let label = format!("{}{}", PANEL_PREFIX, panel);
+    if let Some(window) = app.get_webview_window(&label) {
+        return window
+            .show()
+            .and_then(|()| window.set_focus())
+            .map_err(|e| CommandError::Failed(format!("Failed to show {}: {}", label, e)));
+    }
+    let url = WebviewUrl::App(format!("index.html?panel={}", panel).into());
+    let window = WebviewWindowBuilder::new(&app, &label, url)
+        .title(title.unwrap_or_else(|| format!("Blendmate – {}", panel)))
+        .inner_size(480.0, 640.0)
+        .visible(false)
+        .build()
+        .map_err(|e| CommandError::Failed(format!("Failed to open {}: {}", label, e)))?;
+    restore(&window);
+    Ok(())
+}
+
+/// Forget saved window placement, for every layout or the current one
+#[tauri::command]
+pub fn reset_window_placement<R: Runtime>(
+    all_layouts: Option<bool>,
+    app: AppHandle<R>,
+    store: State<'_, PlacementStore>,
+) -> Result<(), CommandError> {
+    let mut file = store.file.lock().unwrap();
+    if all_layouts.unwrap_or(false) {
+        file.layouts.clear();
+    } else if let Some((layout, _, _)) = monitors(&app) {
+        file.layouts.remove(&layout);
+    }
+    save(&store.path, &file)
+}
@@ -0,0 +1,568 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::process::Command;
+use tokio::sync::{watch, Mutex};
+
+use crate::errors::CommandError;
+use crate::export::{self, ExportRequest};
+use crate::render_queue::{self, RenderRequest};
+use crate::settings::SettingsStore;
+use crate::shares::ShareMonitor;
+use crate::upgrade::{self, UpgradeRequest};
+use crate::validation;
+use crate::workers::{self, JobStatus, WorkerPool};
+use crate::AppState;
+
+/// Finished runs kept in `playlists.json`; older ones are dropped.
+const MAX_FINISHED_RUNS: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaylistStep {
+    /// Check the open scene against a validation profile
+    Validate { profile: String },
+    Export(ExportRequest),
+    Upgrade(UpgradeRequest),
+    Render(RenderRequest),
+    /// Run an external program (encoders, upload tools, ...)
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        cwd: Option<String>,
+    },
+    /// Emit `playlist:notify` for the frontend to show
+    Notify { message: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Stop the run; it can be resumed from the failed step
+    #[default]
+    Stop,
+    /// Record the failure and go on with the next step
+    Continue,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlaylistEntry {
+    #[serde(flatten)]
+    pub step: PlaylistStep,
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+    /// Extra attempts before `on_failure` applies
+    #[serde(default)]
+    pub retries: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Playlist {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub steps: Vec<PlaylistEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+    /// The app quit while the run was going
+    Interrupted,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StepResult {
+    pub status: RunStatus,
+    pub attempts: u32,
+    pub message: Option<String>,
+    pub job_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlaylistRun {
+    pub id: String,
+    pub playlist_id: String,
+    pub name: String,
+    /// Steps as they were when the run started
+    pub steps: Vec<PlaylistEntry>,
+    pub results: Vec<StepResult>,
+    pub status: RunStatus,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct PlaylistFile {
+    playlists: Vec<Playlist>,
+    runs: Vec<PlaylistRun>,
+}
+
+/// Playlists and their runs, persisted as `playlists.json` in the app data
+/// directory after every change.
+pub struct PlaylistStore {
+    path: PathBuf,
+    data: Mutex<PlaylistFile>,
+    cancels: Mutex<HashMap<String, watch::Sender<bool>>>,
+}
+
+impl PlaylistStore {
+    /// Load playlists; runs that were going when the app quit become
+    /// `interrupted` and can be resumed. A file that can't be parsed is
+    /// renamed aside rather than overwritten by the next save, so the
+    /// playlists in it can still be recovered by hand.
+    pub fn load(path: PathBuf) -> Self {
+        let mut data: PlaylistFile = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                let stamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+                let aside = path.with_extension(format!("corrupt-{}.json", stamp));
+                eprintln!("Failed to parse playlists, keeping them as {}: {err}", aside.display());
+                if let Err(err) = fs::rename(&path, &aside) {
+                    eprintln!("Failed to move {} aside: {err}", path.display());
+                }
+                PlaylistFile::default()
+            }),
+            Err(_) => PlaylistFile::default(),
+        };
+
+        for run in &mut data.runs {
+            if run.status == RunStatus::Running {
+                run.status = RunStatus::Interrupted;
+                for result in &mut run.results {
+                    if result.status == RunStatus::Running {
+                        result.status = RunStatus::Interrupted;
+                    }
+                }
+            }
+        }
+        prune_runs(&mut data.runs);
+
+        Self {
+            path,
+            data: Mutex::new(data),
+            cancels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn save(&self, data: &PlaylistFile) -> Result<(), CommandError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| CommandError::Io(format!("Failed to create data directory: {}", e)))?;
+        }
+        let text = serde_json::to_string_pretty(data).map_err(|e| format!("Failed to serialize playlists: {}", e))?;
+        // Written aside and renamed, so a crash mid-write leaves the last
+        // complete file
+        let partial = self.path.with_extension("partial");
+        fs::write(&partial, text).map_err(|e| CommandError::Io(format!("Failed to write playlists: {}", e)))?;
+        fs::rename(&partial, &self.path).map_err(|e| CommandError::Io(format!("Failed to write playlists: {}", e)))
+    }
+
+    /// Mark a run as going and return its cancel signal; `None` when it
+    /// already is. Checked and set under one lock, so two resumes can't
+    /// both start it.
+    async fn claim(&self, run_id: &str) -> Option<watch::Receiver<bool>> {
+        let mut cancels = self.cancels.lock().await;
+        if cancels.contains_key(run_id) {
+            return None;
+        }
+        let (cancel, cancelled) = watch::channel(false);
+        cancels.insert(run_id.to_string(), cancel);
+        Some(cancelled)
+    }
+
+    /// Apply `change` to a run, persist, and return the new snapshot.
+    async fn update_run(&self, run_id: &str, change: impl FnOnce(&mut PlaylistRun)) -> Option<PlaylistRun> {
+        let mut data = self.data.lock().await;
+        let run = data.runs.iter_mut().find(|run| run.id == run_id)?;
+        change(run);
+        let snapshot = run.clone();
+        if let Err(err) = self.save(&data) {
+            eprintln!("{err}");
+        }
+        Some(snapshot)
+    }
+}
+
+/// Drop the oldest done, failed and cancelled runs beyond
+/// `MAX_FINISHED_RUNS`, leaving room for one more. Runs are kept in the
+/// order they started; interrupted ones stay until they are resumed.
+fn prune_runs(runs: &mut Vec<PlaylistRun>) {
+    let finished = |run: &PlaylistRun| matches!(run.status, RunStatus::Done | RunStatus::Failed | RunStatus::Cancelled);
+    let mut excess = (runs.iter().filter(|run| finished(run)).count() + 1).saturating_sub(MAX_FINISHED_RUNS);
+    runs.retain(|run| {
+        if excess > 0 && finished(run) {
+            excess -= 1;
+            return false;
+        }
+        true
+    });
+}
+
+#[tauri::command]
+pub async fn list_playlists(store: State<'_, PlaylistStore>) -> Result<Vec<Playlist>, CommandError> {
+    Ok(store.data.lock().await.playlists.clone())
+}
+
+/// Create or replace a playlist; returns its id
+#[tauri::command]
+pub async fn save_playlist(mut playlist: Playlist, store: State<'_, PlaylistStore>) -> Result<String, CommandError> {
+    if playlist.id.is_empty() {
+        playlist.id = format!("pl-{}", chrono::Local::now().timestamp_millis());
+    }
+    let id = playlist.id.clone();
+
+    let mut data = store.data.lock().await;
+    data.playlists.retain(|p| p.id != id);
+    data.playlists.push(playlist);
+    store.save(&data)?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn delete_playlist(id: String, store: State<'_, PlaylistStore>) -> Result<(), CommandError> {
+    let mut data = store.data.lock().await;
+    data.playlists.retain(|p| p.id != id);
+    store.save(&data)
+}
+
+#[tauri::command]
+pub async fn list_playlist_runs(store: State<'_, PlaylistStore>) -> Result<Vec<PlaylistRun>, CommandError> {
+    Ok(store.data.lock().await.runs.clone())
+}
+
+/// Start a playlist from its first step; returns the run id
+#[tauri::command]
+pub async fn start_playlist(
+    id: String,
+    app: AppHandle,
+    store: State<'_, PlaylistStore>,
+) -> Result<String, CommandError> {
+    let run_id = format!("run-{}", chrono::Local::now().timestamp_millis());
+    {
+        let mut data = store.data.lock().await;
+        let playlist = data
+            .playlists
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| CommandError::NotFound(format!("Unknown playlist '{}'", id)))?;
+
+        prune_runs(&mut data.runs);
+        data.runs.push(PlaylistRun {
+            id: run_id.clone(),
+            playlist_id: playlist.id,
+            name: playlist.name,
+            results: playlist
+                .steps
+                .iter()
+                .map(|_| StepResult {
+                    status: RunStatus::Pending,
+                    attempts: 0,
+                    message: None,
+                    job_id: None,
+                })
+                .collect(),
+            steps: playlist.steps,
+            status: RunStatus::Running,
+            started_at: chrono::Local::now().to_rfc3339(),
+            finished_at: None,
+        });
+        store.save(&data)?;
+    }
+
+    let cancelled = store
+        .claim(&run_id)
+        .await
+        .ok_or_else(|| CommandError::InvalidInput(format!("Run '{}' is already going", run_id)))?;
+    spawn_run(&app, run_id.clone(), cancelled);
+    Ok(run_id)
+}
+
+/// Continue a failed, cancelled or interrupted run from the first step
+/// that didn't finish.
+#[tauri::command]
+pub async fn resume_playlist_run(
+    run_id: String,
+    app: AppHandle,
+    store: State<'_, PlaylistStore>,
+) -> Result<(), CommandError> {
+    let cancelled = store
+        .claim(&run_id)
+        .await
+        .ok_or_else(|| CommandError::InvalidInput(format!("Run '{}' is already going", run_id)))?;
+    let resumed = store
+        .update_run(&run_id, |run| {
+            run.status = RunStatus::Running;
+            run.finished_at = None;
+            for result in &mut run.results {
+                if result.status != RunStatus::Done {
+                    result.status = RunStatus::Pending;
+                }
+            }
+        })
+        .await;
+    if resumed.is_none() {
+        store.cancels.lock().await.remove(&run_id);
+        return Err(CommandError::NotFound(format!("Unknown run '{}'", run_id)));
+    }
+
+    spawn_run(&app, run_id, cancelled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn cancel_playlist_run(run_id: String, store: State<'_, PlaylistStore>) -> Result<(), CommandError> {
+    let cancels = store.cancels.lock().await;
+    let cancel = cancels
+        .get(&run_id)
+        .ok_or_else(|| CommandError::NotFound(format!("Run '{}' is not going", run_id)))?;
+    cancel.send_replace(true);
+    Ok(())
+}
+
+/// Execute a run claimed with `PlaylistStore::claim`
+fn spawn_run(app: &AppHandle, run_id: String, cancelled: watch::Receiver<bool>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        execute(&app, &run_id, cancelled).await;
+        app.state::<PlaylistStore>().cancels.lock().await.remove(&run_id);
+    });
+}
+
+async fn execute(app: &AppHandle, run_id: &str, cancelled: watch::Receiver<bool>) {
+    let store = app.state::<PlaylistStore>();
+    let Some(run) = store.update_run(run_id, |_| {}).await else {
+        return;
+    };
+    emit(app, &run);
+
+    let mut failed = false;
+    for (index, entry) in run.steps.iter().enumerate() {
+        if run.results[index].status != RunStatus::Pending {
+            continue;
+        }
+
+        let mut attempts = 0;
+        let outcome = loop {
+            attempts += 1;
+            if let Some(run) = store
+                .update_run(run_id, |run| {
+                    run.results[index].status = RunStatus::Running;
+                    run.results[index].attempts = attempts;
+                })
+                .await
+            {
+                emit(app, &run);
+            }
+
+            // Only the first attempt picks up the job from before a restart
+            let previous = run.results[index].job_id.as_deref().filter(|_| attempts == 1);
+            let outcome = run_step(app, run_id, index, &entry.step, previous, &cancelled).await;
+            if outcome.is_ok() || *cancelled.borrow() || attempts > entry.retries {
+                break outcome;
+            }
+        };
+
+        let is_cancelled = *cancelled.borrow();
+        let status = match (&outcome, is_cancelled) {
+            (_, true) => RunStatus::Cancelled,
+            (Ok(_), _) => RunStatus::Done,
+            (Err(_), _) => RunStatus::Failed,
+        };
+        if let Some(run) = store
+            .update_run(run_id, |run| {
+                let result = &mut run.results[index];
+                result.status = status;
+                result.message = match outcome {
+                    Ok(message) => message,
+                    Err(error) => Some(error),
+                };
+            })
+            .await
+        {
+            emit(app, &run);
+        }
+
+        if is_cancelled {
+            finish(app, run_id, RunStatus::Cancelled).await;
+            return;
+        }
+        if status == RunStatus::Failed {
+            failed = true;
+            if entry.on_failure == FailurePolicy::Stop {
+                finish(app, run_id, RunStatus::Failed).await;
+                return;
+            }
+        }
+    }
+
+    finish(app, run_id, if failed { RunStatus::Failed } else { RunStatus::Done }).await;
+}
+
+async fn finish(app: &AppHandle, run_id: &str, status: RunStatus) {
+    let store = app.state::<PlaylistStore>();
+    if let Some(run) = store
+        .update_run(run_id, |run| {
+            run.status = status;
+            run.finished_at = Some(chrono::Local::now().to_rfc3339());
+        })
+        .await
+    {
+        emit(app, &run);
+    }
+}
+
+/// The step's job from before the app quit, when it was interrupted.
+/// It is requeued, so the items it finished aren't run again.
+async fn interrupted_job(app: &AppHandle, pool: &WorkerPool, previous: Option<&str>) -> Option<String> {
+    let id = previous?;
+    if pool.get(id).await?.status != JobStatus::Interrupted {
+        return None;
+    }
+    match pool.requeue(app, id).await {
+        Ok(_) => Some(id.to_string()),
+        Err(err) => {
+            eprintln!("Failed to resume job {id}, submitting it again: {err}");
+            None
+        }
+    }
+}
+
+/// Run one step to completion. `previous` is the job the step had before
+/// the run was resumed. `Ok` carries an optional status message.
+async fn run_step(
+    app: &AppHandle,
+    run_id: &str,
+    index: usize,
+    step: &PlaylistStep,
+    previous: Option<&str>,
+    cancelled: &watch::Receiver<bool>,
+) -> Result<Option<String>, String> {
+    let state = app.state::<AppState>();
+    let settings = app.state::<SettingsStore>();
+    let pool = app.state::<WorkerPool>();
+
+    match step {
+        PlaylistStep::Validate { profile } => {
+            let report = validation::run_validation(&state, &settings, profile).await?;
+            if report.passed {
+                Ok(Some(format!("{} findings", report.warnings.len())))
+            } else {
+                Err(format!("Validation profile '{}' failed", profile))
+            }
+        }
+        PlaylistStep::Export(request) => {
+            let job_id = match interrupted_job(app, &pool, previous).await {
+                Some(job_id) => job_id,
+                None => export::submit_export(&state, &settings, &pool, request).await?,
+            };
+            let result = run_job(app, run_id, index, &job_id, cancelled).await;
+            if let Some(job) = pool.get(&job_id).await {
+                export::finish_export(request, &job);
+            }
+            result
+        }
+        PlaylistStep::Upgrade(request) => {
+            let job_id = match interrupted_job(app, &pool, previous).await {
+                Some(job_id) => job_id,
+                None => upgrade::submit_upgrade(&settings, &pool, request).await?,
+            };
+            run_job(app, run_id, index, &job_id, cancelled).await
+        }
+        PlaylistStep::Render(request) => {
+            let shares = app.state::<ShareMonitor>();
+            let job_id = match interrupted_job(app, &pool, previous).await {
+                Some(job_id) => job_id,
+                None => render_queue::submit_render(&state, &settings, &pool, &shares, request).await?,
+            };
+            let result = run_job(app, run_id, index, &job_id, cancelled).await;
+            if let Some(job) = pool.get(&job_id).await {
+                pool.annotate(app, &job_id, render_queue::summarize(request, &job)).await;
+            }
+            result
+        }
+        PlaylistStep::Command { program, args, cwd } => {
+            let mut command = Command::new(program);
+            command
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+            if let Some(cwd) = cwd {
+                command.current_dir(cwd);
+            }
+            let child = command
+                .spawn()
+                .map_err(|e| format!("Failed to run '{}': {}", program, e))?;
+
+            let mut cancelled = cancelled.clone();
+            let output = tokio::select! {
+                output = child.wait_with_output() => output.map_err(|e| e.to_string())?,
+                _ = workers::cancelled(&mut cancelled) => return Err("Cancelled".to_string()),
+            };
+            if output.status.success() {
+                Ok(None)
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let last = stderr.lines().last().unwrap_or("").trim();
+                Err(format!("'{}' exited with {} {}", program, output.status, last))
+            }
+        }
+        PlaylistStep::Notify { message } => {
+            if let Err(err) = app.emit("playlist:notify", message) {
+                eprintln!("Failed to emit playlist:notify: {err}");
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Run a queued pool job, cancelling it when the run is cancelled.
+async fn run_job(
+    app: &AppHandle,
+    run_id: &str,
+    index: usize,
+    job_id: &str,
+    cancelled: &watch::Receiver<bool>,
+) -> Result<Option<String>, String> {
+    let store = app.state::<PlaylistStore>();
+    let pool = app.state::<WorkerPool>();
+    store
+        .update_run(run_id, |run| run.results[index].job_id = Some(job_id.to_string()))
+        .await;
+
+    // On cancel the job is stopped through the pool so it still finishes cleanly
+    let mut cancelled = cancelled.clone();
+    let run = pool.run(app, job_id);
+    tokio::pin!(run);
+    let job = tokio::select! {
+        job = &mut run => job,
+        _ = workers::cancelled(&mut cancelled) => {
+            let _ = pool.cancel(job_id).await;
+            run.await
+        }
+    };
+
+    let job = job.ok_or_else(|| format!("Job '{}' disappeared", job_id))?;
+    let failed = job.items.iter().filter(|item| item.status != JobStatus::Done).count();
+    match job.status {
+        JobStatus::Done => Ok(Some(format!("{} files", job.items.len()))),
+        _ => Err(format!("{} of {} files failed", failed, job.items.len())),
+    }
+}
+
+fn emit(app: &AppHandle, run: &PlaylistRun) {
+    if let Err(err) = app.emit("playlist:progress", run) {
+        eprintln!("Failed to emit playlist:progress: {err}");
+    }
+}
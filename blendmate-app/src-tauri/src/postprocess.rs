@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::errors::CommandError;
+use crate::ffmpeg;
+use crate::journal::JobRequest;
+use crate::safemode;
+use crate::settings::SettingsStore;
+use crate::tasks::{TaskHandle, TaskKind, TaskManager};
+use crate::workers::{JobStatus, WorkItem, WorkerPool};
+
+const DENOISE_SCRIPT: &str = include_str!("../scripts/denoise.py");
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+fn default_opacity() -> f64 {
+    0.8
+}
+
+fn default_margin() -> u32 {
+    24
+}
+
+/// One step of the post-process stage, run on each landed frame in order
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PostOperation {
+    /// OIDN in a headless Blender compositor, guided by the denoising
+    /// normal and albedo passes when the frame has them
+    Denoise {
+        /// Install name from the worker settings, or a path to a Blender binary
+        #[serde(default)]
+        blender: Option<String>,
+    },
+    /// Scale down to at most `width` wide
+    Resize { width: u32 },
+    /// Overlay an image, e.g. a logo with transparency, in a corner
+    Watermark {
+        image: String,
+        #[serde(default)]
+        corner: Corner,
+        /// 0 to 1
+        #[serde(default = "default_opacity")]
+        opacity: f64,
+        /// Distance from the frame's edges in pixels
+        #[serde(default = "default_margin")]
+        margin: u32,
+    },
+}
+
+impl PostOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            PostOperation::Denoise { .. } => "denoise",
+            PostOperation::Resize { .. } => "resize",
+            PostOperation::Watermark { .. } => "watermark",
+        }
+    }
+
+    /// The operation with its file paths passed through `map`, e.g. a
+    /// project's path mappings
+    pub fn with_paths(&self, map: impl Fn(&str) -> String) -> PostOperation {
+        match self {
+            PostOperation::Watermark {
+                image,
+                corner,
+                opacity,
+                margin,
+            } => PostOperation::Watermark {
+                image: map(image),
+                corner: *corner,
+                opacity: *opacity,
+                margin: *margin,
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+/// A denoise job for one frame, run on the worker pool
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DenoiseRequest {
+    pub input: String,
+    pub output: String,
+    /// Install name from the worker settings, or a path to a Blender binary
+    pub blender: Option<String>,
+}
+
+/// One item, without a .blend file
+pub fn work(request: &DenoiseRequest) -> Vec<WorkItem> {
+    vec![WorkItem {
+        label: request.input.clone(),
+        blend_file: None,
+        script: DENOISE_SCRIPT,
+        args: json!({
+            "input": request.input,
+            "output": request.output,
+        }),
+        devices: None,
+        priority: 0,
+        env: BTreeMap::new(),
+    }]
+}
+
+/// Denoise on the worker pool and wait for it; cancelling the task stops
+/// the job through the pool so it still finishes cleanly
+async fn denoise<R: Runtime>(
+    app: &AppHandle<R>,
+    task: &TaskHandle<R>,
+    blender: Option<String>,
+    input: &Path,
+    output: &Path,
+) -> Result<(), CommandError> {
+    let pool = safemode::subsystem::<WorkerPool, R>(app, "workers")?;
+    let request = DenoiseRequest {
+        input: input.to_string_lossy().into_owned(),
+        output: output.to_string_lossy().into_owned(),
+        blender,
+    };
+    let id = pool.submit(JobRequest::Denoise(request), &app.state::<SettingsStore>()).await?;
+    let run = pool.run(app, &id);
+    tokio::pin!(run);
+    let job = tokio::select! {
+        job = &mut run => job,
+        _ = task.cancelled() => {
+            let _ = pool.cancel(&id).await;
+            run.await
+        }
+    };
+    let job = job.ok_or_else(|| CommandError::NotFound(format!("Unknown job '{}'", id)))?;
+    match job.status {
+        JobStatus::Done => Ok(()),
+        JobStatus::Cancelled => Err(CommandError::Cancelled(format!("Job '{}' was cancelled", id))),
+        _ => {
+            let error = job.items.iter().find_map(|item| item.errors.last()).cloned();
+            Err(CommandError::Failed(format!(
+                "Denoising in {} failed: {}",
+                id,
+                error.unwrap_or_else(|| "no error reported".to_string())
+            )))
+        }
+    }
+}
+
+/// Overlay position as ffmpeg `overlay` expressions
+fn position(corner: Corner, margin: u32) -> (String, String) {
+    let near = margin.to_string();
+    let (far_x, far_y) = (format!("W-w-{}", margin), format!("H-h-{}", margin));
+    match corner {
+        Corner::TopLeft => (near.clone(), near),
+        Corner::TopRight => (far_x, near),
+        Corner::BottomLeft => (near, far_y),
+        Corner::BottomRight => (far_x, far_y),
+    }
+}
+
+/// Where step `index` writes, hidden next to the final frame
+fn step_path(output: &Path, index: usize) -> PathBuf {
+    let name = output.file_name().unwrap_or_default().to_string_lossy();
+    output.with_file_name(format!(".step{}.{}", index, name))
+}
+
+async fn steps<R: Runtime>(
+    app: &AppHandle<R>,
+    task: &TaskHandle<R>,
+    operations: &[PostOperation],
+    input: &Path,
+    output: &Path,
+) -> Result<(), CommandError> {
+    let settings = app.state::<SettingsStore>();
+    let mut current = input.to_path_buf();
+    for (index, operation) in operations.iter().enumerate() {
+        if task.is_cancelled() {
+            return Err(CommandError::Cancelled("Cancelled".to_string()));
+        }
+        task.progress(
+            Some(index as f32 / operations.len() as f32),
+            Some(operation.name().to_string()),
+        );
+        let next = step_path(output, index);
+        let result = match operation {
+            PostOperation::Denoise { blender } => denoise(app, task, blender.clone(), &current, &next).await,
+            PostOperation::Resize { width } => {
+                task.run(ffmpeg::extract_still(&settings, &current, None, *width, &next)).await
+            }
+            PostOperation::Watermark {
+                image,
+                corner,
+                opacity,
+                margin,
+            } => {
+                let (x, y) = position(*corner, *margin);
+                let mark = Path::new(image);
+                task.run(ffmpeg::overlay_image(&settings, &current, mark, &x, &y, *opacity, &next)).await
+            }
+        };
+        if current != input {
+            let _ = fs::remove_file(&current);
+        }
+        if let Err(err) = result {
+            let _ = fs::remove_file(&next);
+            return Err(err);
+        }
+        current = next;
+    }
+    fs::rename(&current, output)
+        .map_err(|e| CommandError::Io(format!("Failed to write {}: {}", output.display(), e)))
+}
+
+/// Run `operations` on the landed frame `input` and write the result to
+/// `output`, in the same format. Each frame is a task, emitted as
+/// `task:progress` with the operation under way.
+pub async fn process<R: Runtime>(
+    app: &AppHandle<R>,
+    source: &str,
+    operations: &[PostOperation],
+    input: &Path,
+    output: &Path,
+) -> Result<(), CommandError> {
+    let task = app
+        .state::<TaskManager>()
+        .start(app, TaskKind::PostProcess, format!("Post-process {}", source));
+    let result = steps(app, &task, operations, input, output).await;
+    let outcome = match &result {
+        Ok(()) => Ok(json!({
+            "source": source,
+            "output": output.to_string_lossy(),
+            "operations": operations.iter().map(PostOperation::name).collect::<Vec<_>>(),
+        })),
+        Err(err) => Err(err.clone()),
+    };
+    task.finish(&outcome);
+    result
+}
@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+use crate::workers::WorkerPool;
+use crate::AppState;
+
+/// The add-on's default event coalescing window, in seconds
+const DEFAULT_EVENT_INTERVAL: f64 = 0.1;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PowerSettings {
+    /// Sample power and load and react to them at all
+    pub enabled: bool,
+    /// Treat running on battery as constrained
+    pub pause_on_battery: bool,
+    /// With `pause_on_battery`, only once the charge drops below this
+    pub min_battery_percent: Option<f64>,
+    /// 1-minute load average per core. It includes the app's own workers,
+    /// so keep it above 1.0 or a single render trips it.
+    pub max_load_per_core: Option<f64>,
+    /// Hottest thermal zone, where the platform reports one
+    pub max_temperature_c: Option<f64>,
+    pub sample_interval_secs: u64,
+    /// How long conditions must stay clear before work resumes
+    pub resume_delay_secs: u64,
+    /// Multiplier for the add-on's event coalescing window while constrained
+    pub debounce_factor: f64,
+    /// Multiplier for preview streaming rates while constrained
+    pub preview_rate_factor: f64,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pause_on_battery: true,
+            min_battery_percent: None,
+            max_load_per_core: Some(1.5),
+            max_temperature_c: Some(90.0),
+            sample_interval_secs: 15,
+            resume_delay_secs: 60,
+            debounce_factor: 3.0,
+            preview_rate_factor: 0.5,
+        }
+    }
+}
+
+/// Raw readings; `None` where the platform doesn't report a value
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct PowerSample {
+    pub on_battery: Option<bool>,
+    pub battery_percent: Option<f64>,
+    pub load_per_core: Option<f64>,
+    pub temperature_c: Option<f64>,
+    /// macOS thermal CPU speed limit, 100 when not throttled
+    pub cpu_speed_limit: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PowerStatus {
+    pub sample: PowerSample,
+    /// Why work is being reduced; empty when it isn't
+    pub reasons: Vec<String>,
+    pub constrained: bool,
+    /// Headless jobs are held
+    pub jobs_paused: bool,
+    /// Event coalescing window requested from the add-on, in seconds
+    pub event_interval: f64,
+    /// Multiplier preview streams should apply to their rate
+    pub preview_rate_factor: f64,
+}
+
+impl Default for PowerStatus {
+    fn default() -> Self {
+        Self {
+            sample: PowerSample::default(),
+            reasons: Vec::new(),
+            constrained: false,
+            jobs_paused: false,
+            event_interval: DEFAULT_EVENT_INTERVAL,
+            preview_rate_factor: 1.0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PowerState {
+    status: Mutex<PowerStatus>,
+}
+
+impl PowerState {
+    pub fn current(&self) -> PowerStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// Sample power and load every `sample_interval_secs` for the life of the
+/// app. While constrained, jobs are paused, the add-on coalesces events
+/// longer and `power:state` tells the UI to slow previews.
+pub fn start_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut constrained = false;
+        let mut clear_since: Option<Instant> = None;
+        let mut sent_interval: Option<f64> = None;
+
+        loop {
+            let config = app.state::<SettingsStore>().get().power;
+            let sample = sample().await;
+            let reasons = if config.enabled { reasons(&config, &sample) } else { Vec::new() };
+
+            // Resume only after conditions have stayed clear for a while
+            let was_constrained = constrained;
+            if !reasons.is_empty() {
+                constrained = true;
+                clear_since = None;
+            } else if constrained {
+                let since = *clear_since.get_or_insert_with(Instant::now);
+                if !config.enabled || since.elapsed() >= Duration::from_secs(config.resume_delay_secs) {
+                    constrained = false;
+                    clear_since = None;
+                }
+            }
+
+            let status = PowerStatus {
+                sample,
+                reasons,
+                constrained,
+                jobs_paused: constrained,
+                event_interval: if constrained {
+                    DEFAULT_EVENT_INTERVAL * config.debounce_factor.max(1.0)
+                } else {
+                    DEFAULT_EVENT_INTERVAL
+                },
+                preview_rate_factor: if constrained { config.preview_rate_factor.clamp(0.0, 1.0) } else { 1.0 },
+            };
+
+            if constrained != was_constrained {
+                app.state::<WorkerPool>().set_paused("power", constrained);
+            }
+
+            // Re-sent after every reconnect, since a restarted add-on starts at the default
+            let state = app.state::<AppState>();
+            if state.ws_sender.lock().await.is_none() {
+                sent_interval = None;
+            } else if sent_interval != Some(status.event_interval) {
+                // Not retried on failure; older add-ons don't have the command
+                let params = json!({ "interval": status.event_interval });
+                if let Err(e) = state.bridge.request("events.set_throttle", "", params).await {
+                    eprintln!("Failed to set event throttle: {e}");
+                }
+                sent_interval = Some(status.event_interval);
+            }
+
+            *app.state::<PowerState>().status.lock().unwrap() = status.clone();
+            if let Err(err) = app.emit("power:state", &status) {
+                eprintln!("Failed to emit power:state: {err}");
+            }
+
+            tokio::time::sleep(Duration::from_secs(config.sample_interval_secs.max(1))).await;
+        }
+    });
+}
+
+fn reasons(config: &PowerSettings, sample: &PowerSample) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if config.pause_on_battery && sample.on_battery == Some(true) {
+        match (config.min_battery_percent, sample.battery_percent) {
+            (Some(min), Some(percent)) if percent >= min => {}
+            (_, Some(percent)) => reasons.push(format!("On battery ({:.0}%)", percent)),
+            (_, None) => reasons.push("On battery".to_string()),
+        }
+    }
+    if let (Some(max), Some(load)) = (config.max_load_per_core, sample.load_per_core) {
+        if load > max {
+            reasons.push(format!("System load {:.2} per core", load));
+        }
+    }
+    if let (Some(max), Some(temp)) = (config.max_temperature_c, sample.temperature_c) {
+        if temp >= max {
+            reasons.push(format!("CPU at {:.0}°C", temp));
+        }
+    }
+    if let Some(limit) = sample.cpu_speed_limit.filter(|limit| *limit < 100.0) {
+        reasons.push(format!("CPU thermally limited to {:.0}%", limit));
+    }
+    reasons
+}
+
+#[cfg(target_os = "linux")]
+async fn sample() -> PowerSample {
+    use std::fs;
+
+    let read = |path: &std::path::Path| fs::read_to_string(path).ok().map(|text| text.trim().to_string());
+
+    let mut on_mains = false;
+    let mut battery_percent = None;
+    let mut has_battery = false;
+    for entry in fs::read_dir("/sys/class/power_supply").into_iter().flatten().flatten() {
+        let path = entry.path();
+        match read(&path.join("type")).as_deref() {
+            Some("Mains") | Some("USB") => on_mains |= read(&path.join("online")).as_deref() == Some("1"),
+            Some("Battery") => {
+                has_battery = true;
+                battery_percent = read(&path.join("capacity")).and_then(|c| c.parse().ok()).or(battery_percent);
+            }
+            _ => {}
+        }
+    }
+
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f64;
+    let load_per_core = read(std::path::Path::new("/proc/loadavg"))
+        .and_then(|text| text.split_whitespace().next()?.parse::<f64>().ok())
+        .map(|load| load / cores);
+
+    let temperature_c = fs::read_dir("/sys/class/thermal")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("thermal_zone"))
+        .filter_map(|entry| read(&entry.path().join("temp"))?.parse::<f64>().ok())
+        .map(|millidegrees| millidegrees / 1000.0)
+        .reduce(f64::max);
+
+    PowerSample {
+        on_battery: has_battery.then_some(!on_mains),
+        battery_percent,
+        load_per_core,
+        temperature_c,
+        cpu_speed_limit: None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+async fn sample() -> PowerSample {
+    use tokio::process::Command;
+
+    async fn output(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program).args(args).output().await.ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    // "Now drawing from 'Battery Power'" / " -InternalBattery-0 (id=...) 87%; discharging; ..."
+    let batt = output("pmset", &["-g", "batt"]).await.unwrap_or_default();
+    let on_battery = batt.lines().next().and_then(|line| {
+        if line.contains("Battery Power") {
+            Some(true)
+        } else if line.contains("AC Power") {
+            Some(false)
+        } else {
+            None
+        }
+    });
+    let battery_percent = batt
+        .split_whitespace()
+        .find_map(|word| word.strip_suffix("%;")?.parse::<f64>().ok());
+
+    // "{ 1.23 1.45 1.67 }"
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f64;
+    let load_per_core = output("sysctl", &["-n", "vm.loadavg"])
+        .await
+        .and_then(|text| text.split_whitespace().nth(1)?.parse::<f64>().ok())
+        .map(|load| load / cores);
+
+    // "CPU_Speed_Limit \t= 100"
+    let cpu_speed_limit = output("pmset", &["-g", "therm"]).await.and_then(|text| {
+        text.lines()
+            .find(|line| line.contains("CPU_Speed_Limit"))?
+            .split('=')
+            .nth(1)?
+            .trim()
+            .parse::<f64>()
+            .ok()
+    });
+
+    PowerSample {
+        on_battery,
+        battery_percent,
+        load_per_core,
+        temperature_c: None,
+        cpu_speed_limit,
+    }
+}
+
+/// No readings on other platforms, so nothing is ever constrained
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+async fn sample() -> PowerSample {
+    PowerSample::default()
+}
+
+/// Latest sample and what the app is doing about it
+#[tauri::command]
+pub fn get_power_status(power: State<'_, PowerState>) -> PowerStatus {
+    power.current()
+}
+
+#[tauri::command]
+pub fn get_power_settings(settings: State<'_, SettingsStore>) -> PowerSettings {
+    settings.get().power
+}
+
+/// Takes effect at the next sample
+#[tauri::command]
+pub fn set_power_settings(power: PowerSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.power = power)?;
+    Ok(())
+}
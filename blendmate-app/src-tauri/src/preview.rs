@@ -0,0 +1,115 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::watch;
+
+use crate::errors::CommandError;
+use crate::network::BandwidthShaper;
+use crate::power::PowerState;
+use crate::settings::SettingsStore;
+use crate::workers;
+use crate::AppState;
+
+const DEFAULT_FPS: f64 = 10.0;
+const DEFAULT_WIDTH: u32 = 640;
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One viewport frame, emitted as `preview:frame`
+#[derive(Serialize, Clone)]
+pub struct PreviewFrame {
+    /// Base64 JPEG
+    pub image: String,
+    pub width: u64,
+    pub height: u64,
+    pub quality: u8,
+    /// Frame rate the stream is currently shaped to
+    pub fps: f64,
+    pub bytes: usize,
+    pub remote: bool,
+}
+
+/// The running viewport stream, if any
+#[derive(Default)]
+pub struct PreviewState {
+    stop: Mutex<Option<watch::Sender<bool>>>,
+}
+
+/// Stream the 3D viewport at up to `fps` frames per second, replacing a
+/// running stream. Remote sessions are shaped to the network settings and
+/// every session slows down while power is constrained.
+#[tauri::command]
+pub fn start_preview<R: Runtime>(
+    fps: Option<f64>,
+    width: Option<u32>,
+    app: AppHandle<R>,
+    preview: State<'_, PreviewState>,
+) -> Result<(), CommandError> {
+    let (stop, stopped) = watch::channel(false);
+    if let Some(previous) = preview.stop.lock().unwrap().replace(stop) {
+        previous.send_replace(true);
+    }
+    let fps = fps.unwrap_or(DEFAULT_FPS).max(0.1);
+    let width = width.unwrap_or(DEFAULT_WIDTH);
+    tauri::async_runtime::spawn(stream(app, fps, width, stopped));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_preview(preview: State<'_, PreviewState>) -> Result<(), CommandError> {
+    if let Some(stop) = preview.stop.lock().unwrap().take() {
+        stop.send_replace(true);
+    }
+    Ok(())
+}
+
+async fn stream<R: Runtime>(app: AppHandle<R>, max_fps: f64, width: u32, mut stopped: watch::Receiver<bool>) {
+    let mut shaper = BandwidthShaper::default();
+
+    while !*stopped.borrow() {
+        let started = Instant::now();
+        let settings = app.state::<SettingsStore>().get().network;
+        let state = app.state::<AppState>();
+        let remote = state.session.is_remote(&settings);
+        let rate_factor = app.state::<PowerState>().current().preview_rate_factor;
+        let (fps, quality) = shaper.next(&settings, remote, (max_fps * rate_factor).max(0.1));
+
+        // On a slow link, let outstanding commands through before the next frame
+        let delay = if remote && state.bridge.pending_count().await > 0 {
+            Duration::from_millis(50)
+        } else {
+            let params = json!({ "width": width, "quality": quality });
+            match state
+                .bridge
+                .request_with_timeout("viewport.capture", "", params, CAPTURE_TIMEOUT)
+                .await
+            {
+                Ok(data) => {
+                    let image = data.get("image").and_then(Value::as_str).unwrap_or_default().to_string();
+                    shaper.record(image.len(), started.elapsed());
+                    let frame = PreviewFrame {
+                        bytes: image.len(),
+                        image,
+                        width: data.get("width").and_then(Value::as_u64).unwrap_or_default(),
+                        height: data.get("height").and_then(Value::as_u64).unwrap_or_default(),
+                        quality,
+                        fps,
+                        remote,
+                    };
+                    if let Err(err) = app.emit("preview:frame", &frame) {
+                        eprintln!("Failed to emit preview:frame: {err}");
+                    }
+                    Duration::from_secs_f64(1.0 / fps.max(0.01)).saturating_sub(started.elapsed())
+                }
+                // Not connected or no viewport; keep trying at a slow pace
+                Err(_) => Duration::from_secs(1),
+            }
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = workers::cancelled(&mut stopped) => break,
+        }
+    }
+}
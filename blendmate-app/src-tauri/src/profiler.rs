@@ -0,0 +1,222 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::protocol;
+
+/// Slowest frames listed in a profile
+const SLOWEST_FRAMES: usize = 20;
+
+/// Evaluation cost of one frame across every time it was played
+#[derive(Serialize, Clone, Debug)]
+pub struct FrameCost {
+    pub frame: i64,
+    pub samples: u32,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    /// Objects the depsgraph re-evaluated on the last sample of this frame
+    pub objects: Vec<String>,
+    /// Objects re-evaluated in total, including ones not listed
+    pub evaluated: u32,
+}
+
+/// Evaluation time attributed to one object. A frame's time is split
+/// evenly between the objects it re-evaluated.
+#[derive(Serialize, Clone, Debug)]
+pub struct ObjectCost {
+    pub object: String,
+    pub total_ms: f64,
+    /// Frame samples the object was re-evaluated in
+    pub frames: u32,
+    /// Of those, samples over the frame budget
+    pub slow_frames: u32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PlaybackProfile {
+    pub samples: u64,
+    /// Scene frame rate during the last sample
+    pub fps: f64,
+    /// Time one frame may take at `fps` without dropping frames
+    pub budget_ms: f64,
+    pub mean_eval_ms: f64,
+    /// Samples whose evaluation alone took longer than `budget_ms`
+    pub slow_samples: u64,
+    /// Mean wall time between frames, drawing included
+    pub mean_interval_ms: Option<f64>,
+    /// Every sampled frame, in frame order
+    pub frames: Vec<FrameCost>,
+    /// The frames with the highest mean cost, slowest first
+    pub slowest_frames: Vec<i64>,
+    /// Costliest first
+    pub objects: Vec<ObjectCost>,
+}
+
+#[derive(Default)]
+struct FrameEntry {
+    samples: u32,
+    total_ms: f64,
+    max_ms: f64,
+    objects: Vec<String>,
+    evaluated: u32,
+}
+
+#[derive(Default)]
+struct ObjectEntry {
+    total_ms: f64,
+    frames: u32,
+    slow_frames: u32,
+}
+
+#[derive(Default)]
+struct Profile {
+    samples: u64,
+    fps: f64,
+    total_ms: f64,
+    slow_samples: u64,
+    intervals: u64,
+    total_interval_ms: f64,
+    frames: BTreeMap<i64, FrameEntry>,
+    objects: HashMap<String, ObjectEntry>,
+}
+
+/// Per-frame playback timings sent by the add-on as
+/// `event.playback.frame_times`, collected until reset or a file load.
+#[derive(Default)]
+pub struct PlaybackProfiler {
+    profile: Mutex<Profile>,
+}
+
+fn budget_ms(fps: f64) -> f64 {
+    if fps > 0.0 {
+        1000.0 / fps
+    } else {
+        0.0
+    }
+}
+
+impl PlaybackProfiler {
+    pub fn observe(&self, message: &Value) {
+        match protocol::message_type(message).as_deref() {
+            Some("event.playback.frame_times") => self.record(protocol::message_body(message)),
+            Some("event.scene.file_loaded") => *self.profile.lock().unwrap() = Profile::default(),
+            _ => {}
+        }
+    }
+
+    fn record(&self, body: &Value) {
+        let Some(samples) = body.get("samples").and_then(Value::as_array) else {
+            return;
+        };
+        let mut profile = self.profile.lock().unwrap();
+        if let Some(fps) = body.get("fps").and_then(Value::as_f64) {
+            profile.fps = fps;
+        }
+        let budget = budget_ms(profile.fps);
+
+        for sample in samples {
+            let (Some(frame), Some(eval_ms)) = (
+                sample.get("frame").and_then(Value::as_i64),
+                sample.get("eval_ms").and_then(Value::as_f64),
+            ) else {
+                continue;
+            };
+            let objects: Vec<String> = sample
+                .get("objects")
+                .and_then(Value::as_array)
+                .map(|names| names.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                .unwrap_or_default();
+            let evaluated = sample
+                .get("evaluated")
+                .and_then(Value::as_u64)
+                .unwrap_or(objects.len() as u64) as u32;
+            let slow = budget > 0.0 && eval_ms > budget;
+
+            profile.samples += 1;
+            profile.total_ms += eval_ms;
+            if slow {
+                profile.slow_samples += 1;
+            }
+            if let Some(interval) = sample.get("interval_ms").and_then(Value::as_f64) {
+                profile.intervals += 1;
+                profile.total_interval_ms += interval;
+            }
+
+            let share = eval_ms / evaluated.max(1) as f64;
+            for name in &objects {
+                let entry = profile.objects.entry(name.clone()).or_default();
+                entry.total_ms += share;
+                entry.frames += 1;
+                if slow {
+                    entry.slow_frames += 1;
+                }
+            }
+
+            let entry = profile.frames.entry(frame).or_default();
+            entry.samples += 1;
+            entry.total_ms += eval_ms;
+            entry.max_ms = entry.max_ms.max(eval_ms);
+            entry.objects = objects;
+            entry.evaluated = evaluated;
+        }
+    }
+
+    fn snapshot(&self) -> PlaybackProfile {
+        let profile = self.profile.lock().unwrap();
+        let frames: Vec<FrameCost> = profile
+            .frames
+            .iter()
+            .map(|(frame, entry)| FrameCost {
+                frame: *frame,
+                samples: entry.samples,
+                mean_ms: entry.total_ms / entry.samples.max(1) as f64,
+                max_ms: entry.max_ms,
+                objects: entry.objects.clone(),
+                evaluated: entry.evaluated,
+            })
+            .collect();
+
+        let mut slowest: Vec<&FrameCost> = frames.iter().collect();
+        slowest.sort_by(|a, b| b.mean_ms.total_cmp(&a.mean_ms));
+        let slowest_frames = slowest.iter().take(SLOWEST_FRAMES).map(|f| f.frame).collect();
+
+        let mut objects: Vec<ObjectCost> = profile
+            .objects
+            .iter()
+            .map(|(name, entry)| ObjectCost {
+                object: name.clone(),
+                total_ms: entry.total_ms,
+                frames: entry.frames,
+                slow_frames: entry.slow_frames,
+            })
+            .collect();
+        objects.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+
+        PlaybackProfile {
+            samples: profile.samples,
+            fps: profile.fps,
+            budget_ms: budget_ms(profile.fps),
+            mean_eval_ms: if profile.samples > 0 { profile.total_ms / profile.samples as f64 } else { 0.0 },
+            slow_samples: profile.slow_samples,
+            mean_interval_ms: (profile.intervals > 0).then(|| profile.total_interval_ms / profile.intervals as f64),
+            frames,
+            slowest_frames,
+            objects,
+        }
+    }
+}
+
+/// Per-frame evaluation cost of the playback sampled so far, to find the
+/// frames and objects that make playback stutter
+#[tauri::command]
+pub fn get_playback_profile(profiler: State<'_, PlaybackProfiler>) -> PlaybackProfile {
+    profiler.snapshot()
+}
+
+/// Forget the samples so far, e.g. before timing a change
+#[tauri::command]
+pub fn reset_playback_profile(profiler: State<'_, PlaybackProfiler>) {
+    *profiler.profile.lock().unwrap() = Profile::default();
+}
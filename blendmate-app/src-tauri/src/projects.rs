@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use tauri::State;
+
+use crate::delivery::DeliveryConfig;
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+
+/// Key of per-project data kept while no project is active
+const DEFAULT_PROJECT: &str = "default";
+
+/// Where a shared folder lives on each platform, e.g. `T:/textures` on
+/// Windows and `/mnt/textures` on Linux. Paths under any of them are
+/// rewritten to this machine's prefix.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct PathMapping {
+    pub windows: Option<String>,
+    pub linux: Option<String>,
+    pub macos: Option<String>,
+}
+
+/// Environment and path mappings of one project, meant to be shared
+/// between machines as a file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectProfile {
+    pub name: String,
+    /// Set for every Blender process launched while the project is active
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Tried in order; the first matching prefix wins
+    #[serde(default)]
+    pub path_mappings: Vec<PathMapping>,
+    /// Kitsu project this maps to, when its name differs
+    #[serde(default)]
+    pub kitsu_project: Option<String>,
+    /// Network mounts the project needs besides the local side of its
+    /// path mappings, e.g. a render output share
+    #[serde(default)]
+    pub shares: Vec<String>,
+    /// Folder whose renders are converted for web delivery
+    #[serde(default)]
+    pub delivery: Option<DeliveryConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub profiles: Vec<ProjectProfile>,
+    /// Profile applied to launches and paths; `None` applies nothing
+    pub active: Option<String>,
+}
+
+impl PathMapping {
+    fn local(&self) -> Option<&str> {
+        if cfg!(windows) {
+            self.windows.as_deref()
+        } else if cfg!(target_os = "macos") {
+            self.macos.as_deref()
+        } else {
+            self.linux.as_deref()
+        }
+    }
+}
+
+impl ProjectProfile {
+    /// Rewrite `path` to this machine's location when it starts with a
+    /// mapped prefix of any platform. Other paths are returned unchanged.
+    pub fn map_path(&self, path: &str) -> String {
+        let normalized = path.replace('\\', "/");
+        for mapping in &self.path_mappings {
+            let Some(local) = mapping.local() else {
+                continue;
+            };
+            let prefixes = [&mapping.windows, &mapping.linux, &mapping.macos];
+            for prefix in prefixes.into_iter().flatten() {
+                let prefix = prefix.replace('\\', "/");
+                let prefix = prefix.trim_end_matches('/');
+                if let Some(rest) = strip_prefix(&normalized, prefix) {
+                    let mapped = format!("{}{}", local.trim_end_matches(['/', '\\']), rest);
+                    return if cfg!(windows) { mapped.replace('/', "\\") } else { mapped };
+                }
+            }
+        }
+        path.to_string()
+    }
+
+    /// Every share the project needs on this machine
+    pub fn required_shares(&self) -> Vec<String> {
+        let mut shares: Vec<String> = Vec::new();
+        let mapped = self.path_mappings.iter().filter_map(|mapping| mapping.local());
+        for share in mapped.chain(self.shares.iter().map(String::as_str)) {
+            if !shares.iter().any(|known| known == share) {
+                shares.push(share.to_string());
+            }
+        }
+        shares
+    }
+}
+
+/// `path` without `prefix`, matching whole path components only. Drive
+/// letters and Windows paths compare case-insensitively.
+fn strip_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    if prefix.is_empty() || path.len() < prefix.len() || !path.is_char_boundary(prefix.len()) {
+        return None;
+    }
+    let (head, rest) = path.split_at(prefix.len());
+    let windows_style = prefix.as_bytes().get(1) == Some(&b':') || prefix.starts_with("//");
+    let same = if windows_style { head.eq_ignore_ascii_case(prefix) } else { head == prefix };
+    (same && (rest.is_empty() || rest.starts_with('/'))).then_some(rest)
+}
+
+/// The active project profile, if one is set and still exists
+pub fn active(settings: &SettingsStore) -> Option<ProjectProfile> {
+    let projects = settings.get().projects;
+    let name = projects.active?;
+    projects.profiles.into_iter().find(|profile| profile.name == name)
+}
+
+/// `project`, else the active project, else `default`; what per-project
+/// data such as palettes is stored under
+pub fn key(settings: &SettingsStore, project: Option<String>) -> String {
+    project
+        .or_else(|| active(settings).map(|profile| profile.name))
+        .unwrap_or_else(|| DEFAULT_PROJECT.to_string())
+}
+
+/// Map `path` through the active project, if any
+pub fn map_path(settings: &SettingsStore, path: &str) -> String {
+    match active(settings) {
+        Some(project) => project.map_path(path),
+        None => path.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_project_settings(settings: State<'_, SettingsStore>) -> ProjectSettings {
+    settings.get().projects
+}
+
+/// Create or replace a project profile
+#[tauri::command]
+pub fn save_project_profile(profile: ProjectProfile, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    if profile.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Project name is required".to_string()));
+    }
+    settings.update(|s| {
+        s.projects.profiles.retain(|p| p.name != profile.name);
+        s.projects.profiles.push(profile);
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_project_profile(name: String, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| {
+        s.projects.profiles.retain(|p| p.name != name);
+        if s.projects.active.as_deref() == Some(name.as_str()) {
+            s.projects.active = None;
+        }
+    })?;
+    Ok(())
+}
+
+/// Make `name` the active project, or clear it with `None`
+#[tauri::command]
+pub fn set_active_project(name: Option<String>, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    if let Some(name) = &name {
+        if !settings.get().projects.profiles.iter().any(|p| &p.name == name) {
+            return Err(CommandError::NotFound(format!("Unknown project '{}'", name)));
+        }
+    }
+    settings.update(|s| s.projects.active = name)?;
+    Ok(())
+}
+
+/// Write a profile to a JSON file for teammates to import
+#[tauri::command]
+pub fn export_project_profile(name: String, path: String, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    let profile = settings
+        .get()
+        .projects
+        .profiles
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown project '{}'", name)))?;
+    let text = serde_json::to_string_pretty(&profile).map_err(|e| format!("Failed to serialize project: {}", e))?;
+    fs::write(&path, text).map_err(|e| CommandError::Io(format!("Failed to write {}: {}", path, e)))
+}
+
+/// Add a profile from a file written by `export_project_profile`,
+/// replacing one with the same name
+#[tauri::command]
+pub fn import_project_profile(path: String, settings: State<'_, SettingsStore>) -> Result<ProjectProfile, CommandError> {
+    let text = fs::read_to_string(&path).map_err(|e| CommandError::Io(format!("Failed to read {}: {}", path, e)))?;
+    let profile: ProjectProfile = serde_json::from_str(&text)
+        .map_err(|e| CommandError::InvalidInput(format!("Not a project profile: {}", e)))?;
+    save_project_profile(profile.clone(), settings)?;
+    Ok(profile)
+}
+
+/// Rewrite a path for this machine through the active project
+#[tauri::command]
+pub fn resolve_project_path(path: String, settings: State<'_, SettingsStore>) -> String {
+    map_path(&settings, &path)
+}
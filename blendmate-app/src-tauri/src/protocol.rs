@@ -0,0 +1,65 @@
+use serde_json::Value;
+
+/// Legacy event names and their protocol v1 type strings
+/// (mirrors `EVENT_TYPE_MAP` in the add-on's `protocol.py`).
+const EVENT_TYPE_MAP: &[(&str, &str)] = &[
+    ("connected", "event.scene.connected"),
+    ("load_post", "event.scene.file_loaded"),
+    ("save_post", "event.scene.file_saved"),
+    ("depsgraph_update", "event.depsgraph.updated"),
+    ("frame_change", "event.timeline.frame_changed"),
+    ("context", "event.node.active_changed"),
+    ("operator_blocking", "event.operator.blocking"),
+    ("operator_report", "event.operator.report"),
+];
+
+/// Normalized message type for both envelope and legacy messages,
+/// e.g. `event.depsgraph.updated`, `heartbeat` or `response`.
+pub fn message_type(message: &Value) -> Option<String> {
+    let raw = message.get("type").and_then(Value::as_str)?;
+
+    let legacy = match raw {
+        "event" => message.get("event").and_then(Value::as_str),
+        "context" => Some("context"),
+        _ => None,
+    };
+    if let Some(legacy) = legacy {
+        let mapped = EVENT_TYPE_MAP
+            .iter()
+            .find(|(old, _)| *old == legacy)
+            .map(|(_, new)| new.to_string())
+            .unwrap_or_else(|| format!("event.legacy.{}", legacy));
+        return Some(mapped);
+    }
+
+    Some(raw.to_string())
+}
+
+/// Message payload: the envelope body, or the legacy message itself.
+pub fn message_body(message: &Value) -> &Value {
+    if message.get("v").is_some() {
+        if let Some(body) = message.get("body") {
+            return body;
+        }
+    }
+    message
+}
+
+/// Action a response answers, when the add-on included it.
+pub fn response_action(message: &Value) -> Option<&str> {
+    message_body(message).get("action").and_then(Value::as_str)
+}
+
+/// Whether a response reports success.
+pub fn response_ok(message: &Value) -> bool {
+    let body = message_body(message);
+    match body.get("ok").and_then(Value::as_bool) {
+        Some(ok) => ok && body.get("error").is_none(),
+        None => body.get("error").is_none(),
+    }
+}
+
+/// Response payload (`data`) for both envelope and legacy responses.
+pub fn response_data(message: &Value) -> Option<&Value> {
+    message_body(message).get("data")
+}
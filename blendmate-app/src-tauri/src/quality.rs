@@ -0,0 +1,200 @@
+use serde::Serialize;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::AppState;
+
+/// The add-on's heartbeat period (`_heartbeat_interval` in `connection.py`)
+const HEARTBEAT_INTERVAL: f64 = 5.0;
+
+/// How often the backend pings and emits `connection:quality`
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Samples kept for RTT, jitter and loss
+const WINDOW: usize = 20;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ConnectionQuality {
+    pub connected: bool,
+    pub peer: Option<String>,
+    /// Ping round trip, averaged over recent samples
+    pub rtt_ms: Option<f64>,
+    /// Mean difference between consecutive round trips
+    pub jitter_ms: Option<f64>,
+    /// Share of recent pings and heartbeats that never arrived, 0-1
+    pub loss: f64,
+    /// Heartbeats missed during this connection
+    pub missed_heartbeats: u32,
+    /// Connections since the app started, minus the first
+    pub reconnects: u32,
+    /// 0-100, higher is better
+    pub score: u8,
+    /// 0-4 signal bars; 0 when disconnected
+    pub bars: u8,
+}
+
+/// Outcome of one ping or expected heartbeat
+#[derive(Clone, Copy)]
+enum Sample {
+    Arrived,
+    Lost,
+}
+
+/// Per-connection measurements, fed by heartbeats and backend pings
+#[derive(Default)]
+pub struct QualityTracker {
+    connected: bool,
+    connections: u32,
+    last_heartbeat: Option<Instant>,
+    missed_heartbeats: u32,
+    rtts: VecDeque<f64>,
+    samples: VecDeque<Sample>,
+}
+
+impl QualityTracker {
+    pub fn connected(&mut self) {
+        *self = Self {
+            connected: true,
+            connections: self.connections + 1,
+            ..Self::default()
+        };
+    }
+
+    pub fn disconnected(&mut self) {
+        self.connected = false;
+        self.last_heartbeat = None;
+    }
+
+    pub fn heartbeat(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_heartbeat {
+            let missed = missed_since(last, now);
+            self.missed_heartbeats += missed;
+            for _ in 0..missed.min(WINDOW as u32) {
+                self.push_sample(Sample::Lost);
+            }
+        }
+        self.push_sample(Sample::Arrived);
+        self.last_heartbeat = Some(now);
+    }
+
+    pub fn ping(&mut self, rtt: Option<Duration>) {
+        match rtt {
+            Some(rtt) => {
+                if self.rtts.len() >= WINDOW {
+                    self.rtts.pop_front();
+                }
+                self.rtts.push_back(rtt.as_secs_f64() * 1000.0);
+                self.push_sample(Sample::Arrived);
+            }
+            None => self.push_sample(Sample::Lost),
+        }
+    }
+
+    pub fn snapshot(&self, peer: Option<String>) -> ConnectionQuality {
+        let reconnects = self.connections.saturating_sub(1);
+        if !self.connected {
+            return ConnectionQuality {
+                connected: false,
+                peer: None,
+                rtt_ms: None,
+                jitter_ms: None,
+                loss: 0.0,
+                missed_heartbeats: 0,
+                reconnects,
+                score: 0,
+                bars: 0,
+            };
+        }
+
+        // A heartbeat that is overdue right now counts before it's followed by one
+        let overdue = self.last_heartbeat.map_or(0, |last| missed_since(last, Instant::now()));
+        let lost = self.samples.iter().filter(|s| matches!(s, Sample::Lost)).count() + overdue as usize;
+        let total = self.samples.len() + overdue as usize;
+        let loss = if total == 0 { 0.0 } else { lost as f64 / total as f64 };
+
+        let rtt_ms = (!self.rtts.is_empty()).then(|| self.rtts.iter().sum::<f64>() / self.rtts.len() as f64);
+        let jitter_ms = (self.rtts.len() > 1).then(|| {
+            let diffs: f64 = self
+                .rtts
+                .iter()
+                .zip(self.rtts.iter().skip(1))
+                .map(|(a, b)| (b - a).abs())
+                .sum();
+            diffs / (self.rtts.len() - 1) as f64
+        });
+
+        let penalty = rtt_ms.map_or(0.0, |rtt| (rtt / 10.0).min(40.0))
+            + jitter_ms.map_or(0.0, |jitter| (jitter / 5.0).min(20.0))
+            + (loss * 200.0).min(60.0)
+            + (reconnects as f64 * 5.0).min(20.0);
+        let score = (100.0 - penalty).clamp(0.0, 100.0).round() as u8;
+        let bars = match score {
+            80.. => 4,
+            60..=79 => 3,
+            40..=59 => 2,
+            _ => 1,
+        };
+
+        ConnectionQuality {
+            connected: true,
+            peer,
+            rtt_ms,
+            jitter_ms,
+            loss,
+            missed_heartbeats: self.missed_heartbeats + overdue,
+            reconnects,
+            score,
+            bars,
+        }
+    }
+
+    fn push_sample(&mut self, sample: Sample) {
+        if self.samples.len() >= WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+/// Heartbeats that should have arrived between `last` and `now`
+fn missed_since(last: Instant, now: Instant) -> u32 {
+    // Half an interval of slack before a heartbeat counts as missed
+    let periods = now.duration_since(last).as_secs_f64() / HEARTBEAT_INTERVAL;
+    (periods - 0.5).floor().max(0.0) as u32
+}
+
+/// Ping the add-on and emit `connection:quality` every few seconds.
+pub fn start_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+            let state = app.state::<AppState>();
+
+            if state.ws_sender.lock().await.is_some() {
+                let started = Instant::now();
+                let rtt = state
+                    .bridge
+                    .request_with_timeout("ping", "", json!({}), PING_TIMEOUT)
+                    .await
+                    .ok()
+                    .map(|_| started.elapsed());
+                state.session.record_ping(rtt);
+            }
+
+            let quality = state.session.quality();
+            if let Err(err) = app.emit("connection:quality", &quality) {
+                eprintln!("Failed to emit connection:quality: {err}");
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_connection_quality(state: State<'_, AppState>) -> ConnectionQuality {
+    state.session.quality()
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::i18n;
+use crate::AppState;
+
+/// Converting heavy objects or applying their modifiers takes a while
+const APPLY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Object types whose transforms can be applied to their data
+const TRANSFORMABLE: &[&str] = &[
+    "MESH",
+    "CURVE",
+    "SURFACE",
+    "FONT",
+    "META",
+    "LATTICE",
+    "ARMATURE",
+    "GPENCIL",
+    "GREASEPENCIL",
+    "CURVES",
+    "POINTCLOUD",
+];
+
+/// Object types `object.convert` turns into meshes
+const CONVERTIBLE: &[&str] = &["CURVE", "SURFACE", "FONT", "META", "CURVES"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickAction {
+    ApplyTransforms,
+    ClearCustomNormals,
+    /// Also applies the modifiers of meshes
+    ConvertToMesh,
+}
+
+impl QuickAction {
+    fn id(self) -> &'static str {
+        match self {
+            QuickAction::ApplyTransforms => "apply_transforms",
+            QuickAction::ClearCustomNormals => "clear_custom_normals",
+            QuickAction::ConvertToMesh => "convert_to_mesh",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            QuickAction::ApplyTransforms => "Blendmate: Apply transforms",
+            QuickAction::ClearCustomNormals => "Blendmate: Clear custom normals",
+            QuickAction::ConvertToMesh => "Blendmate: Convert to mesh",
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PlannedObject {
+    pub name: String,
+    pub object_type: String,
+    /// What changes, e.g. the transform that is applied
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SkippedObject {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Objects a quick action would change; nothing runs until the plan is
+/// applied by id
+#[derive(Serialize, Clone, Debug)]
+pub struct QuickActionPlan {
+    pub id: u64,
+    pub action: QuickAction,
+    pub objects: Vec<PlannedObject>,
+    pub skipped: Vec<SkippedObject>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FailedObject {
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct QuickActionOutcome {
+    /// Objects changed, by their names after the action
+    pub applied: Vec<String>,
+    pub failed: Option<FailedObject>,
+    /// Objects after the failed one, left as they were
+    pub not_run: Vec<String>,
+    /// How to get back to a consistent state after a partial run
+    pub rollback: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Reply {
+    applied: Vec<String>,
+    failed: Option<FailedObject>,
+    not_run: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct QuickActionState {
+    plan: Mutex<Option<QuickActionPlan>>,
+    next_plan_id: AtomicU64,
+}
+
+fn vector(object: &Value, key: &str) -> Vec<f64> {
+    object
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|values| values.iter().filter_map(Value::as_f64).collect())
+        .unwrap_or_default()
+}
+
+fn is_near(values: &[f64], expected: f64) -> bool {
+    values.iter().all(|value| (value - expected).abs() < 1e-6)
+}
+
+fn format_vector(values: &[f64]) -> String {
+    values.iter().map(|value| format!("{:.3}", value)).collect::<Vec<_>>().join(", ")
+}
+
+/// What `action` would change on `object`, or why it is skipped
+fn plan_object(action: QuickAction, object: &Value, object_type: &str) -> Result<Option<String>, String> {
+    match action {
+        QuickAction::ApplyTransforms => {
+            if !TRANSFORMABLE.contains(&object_type) {
+                return Err(format!("Transforms can't be applied to {} objects", object_type));
+            }
+            let location = vector(object, "location");
+            let rotation = vector(object, "rotation_euler");
+            let scale = vector(object, "scale");
+            let mut changes = Vec::new();
+            if !is_near(&location, 0.0) {
+                changes.push(format!("location {}", format_vector(&location)));
+            }
+            if !is_near(&rotation, 0.0) {
+                changes.push(format!("rotation {}", format_vector(&rotation)));
+            }
+            if !is_near(&scale, 1.0) {
+                changes.push(format!("scale {}", format_vector(&scale)));
+            }
+            if changes.is_empty() {
+                return Err("Transforms are already applied".to_string());
+            }
+            Ok(Some(changes.join("; ")))
+        }
+        QuickAction::ClearCustomNormals => {
+            if object_type != "MESH" {
+                return Err("Only meshes have custom normals".to_string());
+            }
+            // Unknown with add-ons that don't report it
+            match object.pointer("/mesh/has_custom_normals").and_then(Value::as_bool) {
+                Some(false) => Err("Has no custom normals".to_string()),
+                _ => Ok(None),
+            }
+        }
+        QuickAction::ConvertToMesh => {
+            let modifiers = object.get("modifiers").and_then(Value::as_array).map_or(0, Vec::len);
+            if object_type == "MESH" {
+                if modifiers == 0 {
+                    return Err("Already a mesh without modifiers".to_string());
+                }
+                return Ok(Some(format!("applies {} modifiers", modifiers)));
+            }
+            if !CONVERTIBLE.contains(&object_type) {
+                return Err(format!("{} objects can't be converted to meshes", object_type));
+            }
+            Ok(Some(format!("{} to mesh", object_type)))
+        }
+    }
+}
+
+/// Plan `action` on `objects` (default: the selected objects) and return
+/// the preview; apply it with `apply_quick_action`
+#[tauri::command]
+pub async fn plan_quick_action(
+    action: QuickAction,
+    objects: Option<Vec<String>>,
+    state: State<'_, AppState>,
+    quick_actions: State<'_, QuickActionState>,
+) -> Result<QuickActionPlan, CommandError> {
+    let scene = state.mirror.scene(&state.bridge).await?;
+    let empty = Map::new();
+    let all = scene.get("objects").and_then(Value::as_object).unwrap_or(&empty);
+    let names = match objects {
+        Some(names) => names,
+        None => scene
+            .get("selected_objects")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default(),
+    };
+    if names.is_empty() {
+        return Err(CommandError::InvalidInput("No objects given or selected".to_string()));
+    }
+
+    let mut planned = Vec::new();
+    let mut skipped = Vec::new();
+    for name in names {
+        let Some(object) = all.get(&name) else {
+            skipped.push(SkippedObject {
+                name,
+                reason: "Not found".to_string(),
+            });
+            continue;
+        };
+        let object_type = object.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+        match plan_object(action, object, &object_type) {
+            Ok(detail) => planned.push(PlannedObject {
+                name,
+                object_type,
+                detail,
+            }),
+            Err(reason) => skipped.push(SkippedObject { name, reason }),
+        }
+    }
+
+    let plan = QuickActionPlan {
+        id: quick_actions.next_plan_id.fetch_add(1, Ordering::Relaxed) + 1,
+        action,
+        objects: planned,
+        skipped,
+    };
+    *quick_actions.plan.lock().unwrap() = Some(plan.clone());
+    Ok(plan)
+}
+
+/// Run the plan returned by the last `plan_quick_action` call as one
+/// request and one undo step. A failure stops the run; the outcome lists
+/// what was changed and how to roll it back.
+#[tauri::command]
+pub async fn apply_quick_action(
+    plan_id: u64,
+    state: State<'_, AppState>,
+    quick_actions: State<'_, QuickActionState>,
+) -> Result<QuickActionOutcome, CommandError> {
+    let plan = {
+        let mut pending = quick_actions.plan.lock().unwrap();
+        match pending.take() {
+            Some(plan) if plan.id == plan_id => plan,
+            other => {
+                *pending = other;
+                return Err(CommandError::InvalidInput(
+                    "Quick action plan is no longer current, preview it again".to_string(),
+                ));
+            }
+        }
+    };
+    if plan.objects.is_empty() {
+        return Ok(QuickActionOutcome {
+            applied: Vec::new(),
+            failed: None,
+            not_run: Vec::new(),
+            rollback: None,
+        });
+    }
+
+    let names: Vec<&str> = plan.objects.iter().map(|object| object.name.as_str()).collect();
+    let params = json!({"action": plan.action.id(), "objects": names, "label": plan.action.label()});
+    let reply = state
+        .bridge
+        .request_with_timeout("objects.quick_action", "", params, APPLY_TIMEOUT)
+        .await?;
+    let reply: Reply =
+        serde_json::from_value(reply).map_err(|e| format!("Invalid quick action response: {}", e))?;
+
+    let rollback = reply.failed.as_ref().map(|failed| {
+        i18n::t(
+            "quick-action-rollback",
+            &[
+                ("applied", reply.applied.len().into()),
+                ("name", failed.name.as_str().into()),
+                ("remaining", reply.not_run.len().into()),
+            ],
+        )
+    });
+    Ok(QuickActionOutcome {
+        applied: reply.applied,
+        failed: reply.failed,
+        not_run: reply.not_run,
+        rollback,
+    })
+}
@@ -0,0 +1,281 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::State;
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Longest edge of a thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// Largest image accepted from a URL
+const MAX_DOWNLOAD_BYTES: u64 = 50 * 1024 * 1024;
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Board used while no project is active
+const DEFAULT_BOARD: &str = "default";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReferenceImage {
+    pub id: String,
+    pub name: String,
+    /// Stored copy of the image
+    pub file: String,
+    /// JPEG thumbnail made on import
+    pub thumbnail: String,
+    /// File path or URL it was imported from
+    pub source: String,
+    pub width: u32,
+    pub height: u32,
+    pub added_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceMode {
+    /// An image empty at the 3D cursor
+    Empty,
+    /// A background image on a camera
+    CameraBackground,
+}
+
+/// Reference boards, one per project, under `references/` in the app
+/// data directory. Each board directory holds the images, their thumbnails
+/// and a `board.json` index.
+pub struct ReferenceStore {
+    root: PathBuf,
+    /// Serializes changes to the board indexes
+    lock: Mutex<()>,
+}
+
+impl ReferenceStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Board directory of `project`, or of the active project
+    fn board(&self, settings: &SettingsStore, project: Option<String>) -> PathBuf {
+        let name = project
+            .or_else(|| projects::active(settings).map(|profile| profile.name))
+            .unwrap_or_else(|| DEFAULT_BOARD.to_string());
+        let safe: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | ' ') { c } else { '_' })
+            .collect();
+        self.root.join(safe)
+    }
+}
+
+fn load(board: &Path) -> Vec<ReferenceImage> {
+    fs::read_to_string(board.join("board.json"))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save(board: &Path, images: &[ReferenceImage]) -> Result<(), CommandError> {
+    let text = serde_json::to_string_pretty(images).map_err(|e| format!("Failed to serialize references: {}", e))?;
+    fs::write(board.join("board.json"), text)
+        .map_err(|e| CommandError::Io(format!("Failed to write reference board: {}", e)))
+}
+
+/// Store `bytes` as a new image on `board` with a thumbnail
+fn store(board: &Path, bytes: &[u8], name: &str, source: &str) -> Result<ReferenceImage, CommandError> {
+    let format = image::guess_format(bytes)
+        .map_err(|_| CommandError::InvalidInput(format!("'{}' is not a supported image", name)))?;
+    let decoded = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| CommandError::InvalidInput(format!("Can't read image '{}': {}", name, e)))?;
+    let extension = format.extensions_str().first().copied().unwrap_or("img");
+
+    fs::create_dir_all(board).map_err(|e| CommandError::Io(format!("Failed to create reference board: {}", e)))?;
+    let id = format!("ref-{}", chrono::Utc::now().format("%Y%m%d%H%M%S%f"));
+    let file = board.join(format!("{}.{}", id, extension));
+    let thumbnail = board.join(format!("{}.thumb.jpg", id));
+    fs::write(&file, bytes).map_err(|e| CommandError::Io(format!("Failed to store image: {}", e)))?;
+    decoded
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgb8()
+        .save_with_format(&thumbnail, image::ImageFormat::Jpeg)
+        .map_err(|e| CommandError::Io(format!("Failed to write thumbnail: {}", e)))?;
+
+    Ok(ReferenceImage {
+        id,
+        name: name.to_string(),
+        file: file.to_string_lossy().into_owned(),
+        thumbnail: thumbnail.to_string_lossy().into_owned(),
+        source: source.to_string(),
+        width: decoded.width(),
+        height: decoded.height(),
+        added_at: chrono::Local::now().to_rfc3339(),
+    })
+}
+
+/// Decode, store and index an image without blocking the async runtime
+async fn add(
+    references: &ReferenceStore,
+    board: PathBuf,
+    bytes: Vec<u8>,
+    name: String,
+    source: String,
+) -> Result<ReferenceImage, CommandError> {
+    let _guard = references.lock.lock().await;
+    tauri::async_runtime::spawn_blocking(move || {
+        let image = store(&board, &bytes, &name, &source)?;
+        let mut images = load(&board);
+        images.push(image.clone());
+        save(&board, &images)?;
+        Ok(image)
+    })
+    .await
+    .map_err(|e| format!("Reference import failed: {}", e))?
+}
+
+/// Images on a project's board (default: the active project's), oldest first
+#[tauri::command]
+pub fn list_references(
+    project: Option<String>,
+    references: State<'_, ReferenceStore>,
+    settings: State<'_, SettingsStore>,
+) -> Vec<ReferenceImage> {
+    load(&references.board(&settings, project))
+}
+
+/// Copy an image file onto the board, e.g. one dropped on the app
+#[tauri::command]
+pub async fn import_reference(
+    path: String,
+    project: Option<String>,
+    references: State<'_, ReferenceStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<ReferenceImage, CommandError> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| CommandError::Io(format!("Failed to read {}: {}", path, e)))?;
+    let name = Path::new(&path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.clone());
+    let board = references.board(&settings, project);
+    add(&references, board, bytes, name, path).await
+}
+
+/// Download an image onto the board
+#[tauri::command]
+pub async fn download_reference(
+    url: String,
+    project: Option<String>,
+    references: State<'_, ReferenceStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<ReferenceImage, CommandError> {
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| CommandError::Failed(format!("Failed to download {}: {}", url, e)))?;
+    if response.content_length().is_some_and(|length| length > MAX_DOWNLOAD_BYTES) {
+        return Err(CommandError::InvalidInput(format!(
+            "Image is larger than {} MiB",
+            MAX_DOWNLOAD_BYTES / 1024 / 1024
+        )));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| CommandError::Failed(format!("Failed to download {}: {}", url, e)))?;
+    if bytes.len() as u64 > MAX_DOWNLOAD_BYTES {
+        return Err(CommandError::InvalidInput(format!(
+            "Image is larger than {} MiB",
+            MAX_DOWNLOAD_BYTES / 1024 / 1024
+        )));
+    }
+
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').find(|part| !part.is_empty()))
+        .map(|file| file.rsplit_once('.').map_or(file, |(stem, _)| stem).to_string())
+        .unwrap_or_else(|| "reference".to_string());
+    let board = references.board(&settings, project);
+    add(&references, board, bytes.to_vec(), name, url).await
+}
+
+#[tauri::command]
+pub async fn remove_reference(
+    id: String,
+    project: Option<String>,
+    references: State<'_, ReferenceStore>,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), CommandError> {
+    let _guard = references.lock.lock().await;
+    let board = references.board(&settings, project);
+    let mut images = load(&board);
+    let index = images
+        .iter()
+        .position(|image| image.id == id)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown reference '{}'", id)))?;
+    let image = images.remove(index);
+    save(&board, &images)?;
+    let _ = fs::remove_file(&image.file);
+    let _ = fs::remove_file(&image.thumbnail);
+    Ok(())
+}
+
+/// Put a reference into the open scene as an image empty or a camera
+/// background. Remote Blender sessions get the image data, packed into
+/// the .blend, instead of a path.
+#[tauri::command]
+pub async fn push_reference(
+    image: String,
+    mode: ReferenceMode,
+    project: Option<String>,
+    camera: Option<String>,
+    opacity: Option<f64>,
+    references: State<'_, ReferenceStore>,
+    settings: State<'_, SettingsStore>,
+    state: State<'_, AppState>,
+) -> Result<Value, CommandError> {
+    let board = references.board(&settings, project);
+    let reference = load(&board)
+        .into_iter()
+        .find(|r| r.id == image)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown reference '{}'", image)))?;
+
+    let mut params = json!({
+        "name": reference.name,
+        "mode": mode,
+        "camera": camera,
+    });
+    if let Some(opacity) = opacity {
+        params["opacity"] = json!(opacity.clamp(0.0, 1.0));
+    }
+    if state.session.is_remote(&settings.get().network) {
+        let bytes = tokio::fs::read(&reference.file)
+            .await
+            .map_err(|e| CommandError::Io(format!("Failed to read {}: {}", reference.file, e)))?;
+        let extension = Path::new(&reference.file)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        params["data"] = json!(base64::engine::general_purpose::STANDARD.encode(bytes));
+        params["extension"] = json!(extension);
+    } else {
+        params["filepath"] = json!(reference.file);
+    }
+    state.bridge.request("reference.push", "", params).await
+}
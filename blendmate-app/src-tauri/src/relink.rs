@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::journal::JobRequest;
+use crate::library::{array, str_field, LibraryState};
+use crate::settings::SettingsStore;
+use crate::units;
+use crate::workers::{self, Job, JobStatus, WorkItem, WorkerPool};
+use crate::AppState;
+
+const RELINK_SCRIPT: &str = include_str!("../scripts/relink.py");
+
+/// Reloading a library reads the whole file again
+const RELINK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A library of the open file that points at the old path
+#[derive(Serialize, Clone, Debug)]
+pub struct RelinkTarget {
+    pub library: String,
+    /// Path as stored in the file, `//` relative or absolute
+    pub filepath: String,
+    /// Linked datablock count per `bpy.data` collection
+    pub linked_ids: BTreeMap<String, u64>,
+    /// Library overrides in the open file based on its datablocks
+    pub overrides: Vec<String>,
+    /// Libraries loaded through this one; they follow it without changes
+    pub indirect: Vec<String>,
+}
+
+/// What relinking would change; nothing runs until the plan is applied by id
+#[derive(Serialize, Clone, Debug)]
+pub struct RelinkPlan {
+    pub id: u64,
+    pub old_path: String,
+    pub new_path: String,
+    pub targets: Vec<RelinkTarget>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RelinkedLibrary {
+    pub name: String,
+    #[serde(default)]
+    pub filepath: Option<String>,
+    #[serde(default)]
+    pub exists: bool,
+    #[serde(default)]
+    pub missing_ids: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RelinkOutcome {
+    pub libraries: Vec<RelinkedLibrary>,
+    /// Every relinked library was found again and resolves all its
+    /// datablocks in a fresh library graph
+    pub verified: bool,
+    pub problems: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct RelinkState {
+    plan: Mutex<Option<RelinkPlan>>,
+    next_plan_id: AtomicU64,
+}
+
+/// Paths compared the way the file system would: separators unified and,
+/// on Windows, case ignored
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/").trim_end_matches('/').to_string();
+    if cfg!(windows) {
+        path.to_lowercase()
+    } else {
+        path
+    }
+}
+
+/// Point the open file's libraries at a moved or renamed file: returns the
+/// libraries and datablocks that would change; apply it with `apply_relink`
+#[tauri::command]
+pub async fn plan_relink(
+    old_path: String,
+    new_path: String,
+    state: State<'_, AppState>,
+    relink: State<'_, RelinkState>,
+) -> Result<RelinkPlan, CommandError> {
+    if !Path::new(&new_path).is_file() {
+        return Err(CommandError::NotFound(format!("File not found: {}", new_path)));
+    }
+    let links = state.bridge.request("library.get_links", "", json!({})).await?;
+    let old = normalize(&old_path);
+    let new = normalize(&new_path);
+
+    let mut targets = Vec::new();
+    let mut warnings = Vec::new();
+    for lib in array(&links, "libraries") {
+        let abspath = normalize(str_field(lib, "abspath"));
+        let name = str_field(lib, "name");
+        if abspath == new {
+            warnings.push(format!("Library '{}' already uses the new path", name));
+        }
+        if abspath != old && normalize(str_field(lib, "filepath")) != old {
+            continue;
+        }
+        // The path of an indirect library is stored in its parent, so the
+        // change lasts only until the parent is reloaded
+        if let Some(parent) = lib.get("parent").and_then(Value::as_str) {
+            warnings.push(format!("'{}' is linked through '{}'; relink that file as well", name, parent));
+        }
+        let linked_ids = lib
+            .get("linked_ids")
+            .and_then(Value::as_object)
+            .map(|counts| {
+                counts
+                    .iter()
+                    .map(|(collection, count)| (collection.clone(), count.as_u64().unwrap_or(0)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let overrides = array(&links, "overrides")
+            .filter(|entry| entry.get("reference_library").and_then(Value::as_str) == Some(name))
+            .map(|entry| str_field(entry, "name").to_string())
+            .collect();
+        let indirect = array(&links, "libraries")
+            .filter(|child| child.get("parent").and_then(Value::as_str) == Some(name))
+            .map(|child| str_field(child, "name").to_string())
+            .collect();
+        targets.push(RelinkTarget {
+            library: name.to_string(),
+            filepath: str_field(lib, "filepath").to_string(),
+            linked_ids,
+            overrides,
+            indirect,
+        });
+    }
+    if targets.is_empty() {
+        return Err(CommandError::NotFound(format!("No library of the open file points at {}", old_path)));
+    }
+
+    let plan = RelinkPlan {
+        id: relink.next_plan_id.fetch_add(1, Ordering::Relaxed) + 1,
+        old_path,
+        new_path,
+        targets,
+        warnings,
+    };
+    *relink.plan.lock().unwrap() = Some(plan.clone());
+    Ok(plan)
+}
+
+/// Relink the libraries of the plan returned by the last `plan_relink`
+/// call, then read the library graph again to verify they resolve.
+#[tauri::command]
+pub async fn apply_relink(
+    plan_id: u64,
+    state: State<'_, AppState>,
+    relink: State<'_, RelinkState>,
+    library: State<'_, LibraryState>,
+) -> Result<RelinkOutcome, CommandError> {
+    let plan = {
+        let mut pending = relink.plan.lock().unwrap();
+        match pending.take() {
+            Some(plan) if plan.id == plan_id => plan,
+            other => {
+                *pending = other;
+                return Err(CommandError::InvalidInput(
+                    "Relink plan is no longer current, preview it again".to_string(),
+                ));
+            }
+        }
+    };
+
+    let names: Vec<&str> = plan.targets.iter().map(|target| target.library.as_str()).collect();
+    let params = json!({"libraries": names, "filepath": plan.new_path});
+    let reply = state
+        .bridge
+        .request_with_timeout("library.relink", "", params, RELINK_TIMEOUT)
+        .await;
+    library.invalidate().await;
+    let reply = reply?;
+    let libraries: Vec<RelinkedLibrary> =
+        serde_json::from_value(reply.get("libraries").cloned().unwrap_or(Value::Null))
+            .map_err(|e| format!("Invalid relink response: {}", e))?;
+
+    // Verified against a fresh graph rather than the relink reply alone
+    let links = state.bridge.request("library.get_links", "", json!({})).await?;
+    let new = normalize(&plan.new_path);
+    let mut problems = Vec::new();
+    for relinked in &libraries {
+        if let Some(error) = &relinked.error {
+            problems.push(format!("{}: {}", relinked.name, error));
+            continue;
+        }
+        let Some(lib) = array(&links, "libraries").find(|lib| str_field(lib, "name") == relinked.name) else {
+            problems.push(format!("{}: no longer in the file", relinked.name));
+            continue;
+        };
+        if normalize(str_field(lib, "abspath")) != new {
+            problems.push(format!("{}: still points at {}", relinked.name, str_field(lib, "abspath")));
+        } else if lib.get("exists").and_then(Value::as_bool) != Some(true) {
+            problems.push(format!("{}: file not found", relinked.name));
+        }
+        let missing = lib.get("missing_ids").and_then(Value::as_u64).unwrap_or(0);
+        if missing > 0 {
+            problems.push(format!("{}: {} linked datablocks are missing", relinked.name, missing));
+        }
+    }
+
+    Ok(RelinkOutcome {
+        verified: problems.is_empty(),
+        libraries,
+        problems,
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RelinkRequest {
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Relink every .blend below this directory as well
+    pub directory: Option<String>,
+    pub old_path: String,
+    pub new_path: String,
+    /// Install name from the worker settings, or a path to a Blender binary
+    pub blender: Option<String>,
+    /// Keep `<file>.bak` of each file that is saved (default true)
+    pub backup: Option<bool>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RelinkedFile {
+    pub file: String,
+    pub relinked: Vec<String>,
+    pub missing_ids: u64,
+    pub saved: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RelinkReport {
+    /// Files that linked the old path and were saved pointing at the new one
+    pub relinked: usize,
+    /// Files that didn't link the old path
+    pub unaffected: usize,
+    pub failed: usize,
+    pub files: Vec<RelinkedFile>,
+}
+
+/// One item per file
+pub fn work(request: &RelinkRequest) -> Vec<WorkItem> {
+    request
+        .files
+        .iter()
+        .map(|file| WorkItem {
+            label: file.clone(),
+            blend_file: Some(PathBuf::from(file)),
+            script: RELINK_SCRIPT,
+            args: json!({
+                "old_path": request.old_path,
+                "new_path": request.new_path,
+                "backup": request.backup.unwrap_or(true),
+            }),
+            devices: None,
+            priority: 0,
+            env: BTreeMap::new(),
+        })
+        .collect()
+}
+
+/// Sort the files into relinked, unaffected and failed; returns the report
+/// as the job summary.
+pub fn summarize(request: &RelinkRequest, job: &Job) -> Value {
+    let mut files = Vec::new();
+    for (file, item) in request.files.iter().zip(&job.items) {
+        let result = item.result.clone().unwrap_or(Value::Null);
+        let error = match item.status {
+            JobStatus::Done => item.errors.last().cloned(),
+            _ => Some(item.errors.last().cloned().unwrap_or_else(|| "Relink failed".to_string())),
+        };
+        files.push(RelinkedFile {
+            file: file.clone(),
+            relinked: serde_json::from_value(result.get("relinked").cloned().unwrap_or(Value::Null))
+                .unwrap_or_default(),
+            missing_ids: result.get("missing_ids").and_then(Value::as_u64).unwrap_or(0),
+            saved: result.get("saved").and_then(Value::as_bool).unwrap_or(false),
+            error,
+        });
+    }
+    let failed = files.iter().filter(|file| file.error.is_some()).count();
+    let relinked = files.iter().filter(|file| file.saved).count();
+    serde_json::to_value(RelinkReport {
+        relinked,
+        unaffected: files.len() - failed - relinked,
+        failed,
+        files,
+    })
+    .unwrap_or(Value::Null)
+}
+
+/// Relink a moved library across many .blend files on the worker pool.
+/// Each file is saved only when the library resolves at its new path; the
+/// report is the job's summary once it finishes.
+#[tauri::command]
+pub async fn start_relink_job<R: Runtime>(
+    mut request: RelinkRequest,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+) -> Result<String, CommandError> {
+    if !Path::new(&request.new_path).is_file() {
+        return Err(CommandError::NotFound(format!("File not found: {}", request.new_path)));
+    }
+    // The directory is expanded now so the job keeps the same items across restarts
+    if let Some(directory) = request.directory.take() {
+        if !Path::new(&directory).is_dir() {
+            return Err(CommandError::NotFound(format!("Directory not found: {}", directory)));
+        }
+        for file in units::blend_files(Path::new(&directory)) {
+            if !request.files.contains(&file) {
+                request.files.push(file);
+            }
+        }
+    }
+    if request.files.is_empty() {
+        return Err(CommandError::InvalidInput("No .blend files to relink".to_string()));
+    }
+
+    let id = pool.submit(JobRequest::Relink(request.clone()), &settings).await?;
+    let request = JobRequest::Relink(request);
+    workers::spawn_job(app, id.clone(), move |app, job| request.finished(app, job));
+    Ok(id)
+}
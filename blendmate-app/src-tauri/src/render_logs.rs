@@ -0,0 +1,439 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::storage::SharedStorage;
+use crate::workers::{JobKind, JobStatus};
+
+/// Jobs whose logs are kept; the oldest are dropped with their files
+const MAX_JOBS: usize = 100;
+
+/// Hits returned when a search doesn't set a limit
+const DEFAULT_LIMIT: usize = 200;
+
+/// Issue lines are kept up to this many characters
+const MAX_ISSUE_TEXT: usize = 500;
+
+/// Known kinds of failure, recognised line by line
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LogCategory {
+    /// An image texture that can't be found or read
+    MissingTexture,
+    /// CUDA, OptiX, HIP or Metal ran out of device memory
+    GpuOutOfMemory,
+    /// The system ran out of memory
+    OutOfMemory,
+    /// The last line of a Python traceback, with the exception
+    PythonException,
+    /// Blender crashed
+    Crash,
+}
+
+/// Lowercase fragments of each category's lines
+const PATTERNS: &[(LogCategory, &[&str])] = &[
+    (
+        LogCategory::MissingTexture,
+        &[
+            "unable to open image",
+            "cannot open image",
+            "can't open image",
+            "failed to load image",
+            "image not found",
+            "texture not found",
+            "missing texture",
+            "missing image",
+        ],
+    ),
+    (
+        LogCategory::GpuOutOfMemory,
+        &[
+            "cuda_error_out_of_memory",
+            "cuda error: out of memory",
+            "out of memory in cumemalloc",
+            "optix_error_out_of_memory",
+            "hip error: out of memory",
+            "out of gpu memory",
+            "system is out of gpu",
+            "metal: out of memory",
+        ],
+    ),
+    (
+        LogCategory::OutOfMemory,
+        &["std::bad_alloc", "memory allocation failed", "malloc returned null", "cannot allocate memory"],
+    ),
+    (
+        LogCategory::Crash,
+        &[
+            "segmentation fault",
+            "exception_access_violation",
+            "blender crashed",
+            ".crash.txt",
+            "bus error",
+            "aborted (core dumped)",
+        ],
+    ),
+];
+
+/// A classified line of a log
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogIssue {
+    pub category: LogCategory,
+    /// From 1
+    pub line: usize,
+    pub text: String,
+}
+
+/// The log of one job item, or of an imported console log
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogFile {
+    pub item: usize,
+    pub label: String,
+    /// Full text on disk, stdout and stderr interleaved as they came
+    pub path: String,
+    pub lines: usize,
+    pub issues: Vec<LogIssue>,
+    pub status: Option<JobStatus>,
+}
+
+/// Logs of one job, kept in the `render_logs` namespace of the storage
+/// backend
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RenderLog {
+    pub job: String,
+    /// `None` for imported Blender console output
+    pub kind: Option<JobKind>,
+    pub created_at: String,
+    pub items: Vec<LogFile>,
+}
+
+/// Emitted as `render_log:issues` when a finished log has issues
+#[derive(Serialize, Clone, Debug)]
+pub struct LogIssues {
+    pub job: String,
+    pub item: usize,
+    pub label: String,
+    pub issues: Vec<LogIssue>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LogHit {
+    pub job: String,
+    pub kind: Option<JobKind>,
+    pub item: usize,
+    pub label: String,
+    pub line: usize,
+    pub text: String,
+    /// Set when the line is a classified issue
+    pub category: Option<LogCategory>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LogSearchResult {
+    /// Matches before `limit` was applied
+    pub total: usize,
+    /// Newest jobs first
+    pub hits: Vec<LogHit>,
+}
+
+/// Reads lines in order and tells which are issues. A traceback is
+/// reported at its exception line, the first one after it that isn't
+/// indented.
+#[derive(Default)]
+struct Classifier {
+    in_traceback: bool,
+}
+
+impl Classifier {
+    fn classify(&mut self, line: &str) -> Option<LogCategory> {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("Traceback (most recent call last)") {
+            self.in_traceback = true;
+            return None;
+        }
+        if self.in_traceback {
+            if line.starts_with(char::is_whitespace) || trimmed.is_empty() {
+                return None;
+            }
+            self.in_traceback = false;
+            return Some(LogCategory::PythonException);
+        }
+        let lower = line.to_lowercase();
+        PATTERNS
+            .iter()
+            .find(|(_, fragments)| fragments.iter().any(|fragment| lower.contains(fragment)))
+            .map(|(category, _)| *category)
+    }
+}
+
+struct OpenLog {
+    writer: BufWriter<File>,
+    file: LogFile,
+    kind: JobKind,
+    classifier: Classifier,
+}
+
+/// Full logs of worker jobs as text files, one per item, with their
+/// classified issues in storage. Lines are written as they arrive, so a
+/// log survives its Blender crashing.
+pub struct RenderLogs {
+    dir: PathBuf,
+    storage: SharedStorage,
+    open: Mutex<HashMap<(String, usize), OpenLog>>,
+    /// Job ids with logs, oldest first
+    index: Mutex<Vec<String>>,
+}
+
+fn clip(line: &str) -> String {
+    line.chars().take(MAX_ISSUE_TEXT).collect()
+}
+
+impl RenderLogs {
+    pub fn new(dir: PathBuf, storage: SharedStorage) -> Self {
+        let index = storage.get_json("render_logs", "index").unwrap_or_default();
+        Self {
+            dir,
+            storage,
+            open: Mutex::new(HashMap::new()),
+            index: Mutex::new(index),
+        }
+    }
+
+    /// Start the log of a job item, replacing an earlier one of the same item
+    pub fn open(&self, job: &str, kind: JobKind, item: usize, label: &str) {
+        let path = self.dir.join(job).join(format!("{}.log", item));
+        let file = fs::create_dir_all(self.dir.join(job)).and_then(|()| File::create(&path));
+        let file = match file {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("Failed to create log {}: {err}", path.display());
+                return;
+            }
+        };
+        let log = OpenLog {
+            writer: BufWriter::new(file),
+            file: LogFile {
+                item,
+                label: label.to_string(),
+                path: path.to_string_lossy().into_owned(),
+                lines: 0,
+                issues: Vec::new(),
+                status: None,
+            },
+            kind,
+            classifier: Classifier::default(),
+        };
+        self.open.lock().unwrap().insert((job.to_string(), item), log);
+    }
+
+    /// Add a line Blender printed to an open log
+    pub fn append(&self, job: &str, item: usize, line: &str) {
+        let mut open = self.open.lock().unwrap();
+        let Some(log) = open.get_mut(&(job.to_string(), item)) else {
+            return;
+        };
+        log.file.lines += 1;
+        if let Some(category) = log.classifier.classify(line) {
+            log.file.issues.push(LogIssue {
+                category,
+                line: log.file.lines,
+                text: clip(line),
+            });
+        }
+        if let Err(err) = writeln!(log.writer, "{}", line) {
+            eprintln!("Failed to write log {}: {err}", log.file.path);
+        }
+    }
+
+    /// Finish the log of a job item and store it with its issues. Emits
+    /// `render_log:issues` when it has any.
+    pub fn close<R: Runtime>(&self, app: &AppHandle<R>, job: &str, item: usize, status: JobStatus) {
+        let Some(mut log) = self.open.lock().unwrap().remove(&(job.to_string(), item)) else {
+            return;
+        };
+        if let Err(err) = log.writer.flush() {
+            eprintln!("Failed to write log {}: {err}", log.file.path);
+        }
+        log.file.status = Some(status);
+        if !log.file.issues.is_empty() {
+            let issues = LogIssues {
+                job: job.to_string(),
+                item,
+                label: log.file.label.clone(),
+                issues: log.file.issues.clone(),
+            };
+            if let Err(err) = app.emit("render_log:issues", &issues) {
+                eprintln!("Failed to emit render_log:issues: {err}");
+            }
+        }
+        self.record(job, Some(log.kind), log.file);
+    }
+
+    fn record(&self, job: &str, kind: Option<JobKind>, file: LogFile) {
+        // Held throughout, so items of one job finishing together don't
+        // overwrite each other's document
+        let mut index = self.index.lock().unwrap();
+        let mut log = self.get(job).unwrap_or_else(|| RenderLog {
+            job: job.to_string(),
+            kind,
+            created_at: chrono::Local::now().to_rfc3339(),
+            items: Vec::new(),
+        });
+        log.items.retain(|known| known.item != file.item);
+        log.items.push(file);
+        log.items.sort_by_key(|file| file.item);
+        if let Err(err) = self.storage.put_json("render_logs", job, &log) {
+            eprintln!("Failed to store log of {job}: {err}");
+        }
+
+        if !index.iter().any(|known| known == job) {
+            index.push(job.to_string());
+        }
+        let excess = index.len().saturating_sub(MAX_JOBS);
+        for dropped in index.drain(..excess) {
+            if let Err(err) = self.storage.delete("render_logs", &dropped) {
+                eprintln!("Failed to drop log of {dropped}: {err}");
+            }
+            let _ = fs::remove_dir_all(self.dir.join(&dropped));
+        }
+        if let Err(err) = self.storage.put_json("render_logs", "index", &*index) {
+            eprintln!("Failed to store log index: {err}");
+        }
+    }
+
+    fn get(&self, job: &str) -> Option<RenderLog> {
+        self.storage.get_json("render_logs", job)
+    }
+
+    /// Copy a log file into the store and classify it, e.g. Blender's
+    /// console output saved from a session or a crash report
+    fn import(&self, path: &Path, label: Option<String>) -> Result<RenderLog, CommandError> {
+        let text = fs::read(path).map_err(|e| CommandError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+        let text = String::from_utf8_lossy(&text);
+        let job = format!("log-{}", chrono::Utc::now().format("%Y%m%d%H%M%S%f"));
+        let target = self.dir.join(&job).join("0.log");
+        fs::create_dir_all(self.dir.join(&job))
+            .and_then(|()| fs::write(&target, text.as_bytes()))
+            .map_err(|e| CommandError::Io(format!("Failed to store log: {}", e)))?;
+
+        let mut classifier = Classifier::default();
+        let mut file = LogFile {
+            item: 0,
+            label: label.unwrap_or_else(|| path.to_string_lossy().into_owned()),
+            path: target.to_string_lossy().into_owned(),
+            lines: 0,
+            issues: Vec::new(),
+            status: None,
+        };
+        for line in text.lines() {
+            file.lines += 1;
+            if let Some(category) = classifier.classify(line) {
+                file.issues.push(LogIssue {
+                    category,
+                    line: file.lines,
+                    text: clip(line),
+                });
+            }
+        }
+        self.record(&job, None, file);
+        self.get(&job)
+            .ok_or_else(|| CommandError::Failed(format!("Failed to store log of {}", path.display())))
+    }
+
+    /// Lines with every word of `query` in them, ignoring case. An empty
+    /// query with a category finds that category's issues.
+    fn search(
+        &self,
+        query: &str,
+        category: Option<LogCategory>,
+        job: Option<&str>,
+        limit: usize,
+    ) -> LogSearchResult {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let jobs: Vec<String> = self.index.lock().unwrap().iter().rev().cloned().collect();
+        let mut result = LogSearchResult {
+            total: 0,
+            hits: Vec::new(),
+        };
+        for log in jobs.iter().filter(|id| job.is_none_or(|job| id.as_str() == job)).filter_map(|id| self.get(id)) {
+            for file in &log.items {
+                let issues: HashMap<usize, LogCategory> =
+                    file.issues.iter().map(|issue| (issue.line, issue.category)).collect();
+                if category.is_some_and(|category| !issues.values().any(|known| *known == category)) {
+                    continue;
+                }
+                let Ok(text) = File::open(&file.path) else {
+                    continue;
+                };
+                for (number, line) in BufReader::new(text).lines().map_while(Result::ok).enumerate() {
+                    let found = issues.get(&(number + 1)).copied();
+                    if category.is_some_and(|category| found != Some(category)) {
+                        continue;
+                    }
+                    let lower = line.to_lowercase();
+                    if !terms.iter().all(|term| lower.contains(term.as_str())) {
+                        continue;
+                    }
+                    result.total += 1;
+                    if result.hits.len() < limit {
+                        result.hits.push(LogHit {
+                            job: log.job.clone(),
+                            kind: log.kind,
+                            item: file.item,
+                            label: file.label.clone(),
+                            line: number + 1,
+                            text: line,
+                            category: found,
+                        });
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Search the full logs of worker jobs and imported console logs, newest
+/// first. Every word of `query` must be on the line; `category` keeps only
+/// classified issues of that kind, e.g. `gpu_out_of_memory`.
+#[tauri::command]
+pub async fn search_render_logs<R: Runtime>(
+    query: String,
+    category: Option<LogCategory>,
+    job: Option<String>,
+    limit: Option<usize>,
+    app: AppHandle<R>,
+) -> Result<LogSearchResult, CommandError> {
+    if query.trim().is_empty() && category.is_none() {
+        return Err(CommandError::InvalidInput("Search needs a query or a category".to_string()));
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    tauri::async_runtime::spawn_blocking(move || {
+        app.state::<RenderLogs>().search(&query, category, job.as_deref(), limit)
+    })
+    .await
+    .map_err(|e| CommandError::Failed(format!("Log search failed: {}", e)))
+}
+
+/// A job's logs with their issues
+#[tauri::command]
+pub fn get_render_log(job: String, logs: State<'_, RenderLogs>) -> Result<RenderLog, CommandError> {
+    logs.get(&job)
+        .ok_or_else(|| CommandError::NotFound(format!("No logs of '{}'", job)))
+}
+
+/// Add a saved log to the searchable logs, e.g. Blender's console output
+/// from a session or a crash report
+#[tauri::command]
+pub fn import_render_log(
+    path: String,
+    label: Option<String>,
+    logs: State<'_, RenderLogs>,
+) -> Result<RenderLog, CommandError> {
+    logs.import(Path::new(&path), label)
+}
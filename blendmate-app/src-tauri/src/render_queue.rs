@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+use crate::errors::CommandError;
+use crate::devices;
+use crate::diskguard;
+use crate::journal::JobRequest;
+use crate::settings::SettingsStore;
+use crate::shares::{self, ShareMonitor};
+use crate::validation;
+use crate::workers::{self, Job, WorkItem, WorkerPool};
+use crate::AppState;
+
+const RENDER_SCRIPT: &str = include_str!("../scripts/render.py");
+
+/// Coarsest preview pass when `preview_first` doesn't give one
+const DEFAULT_PREVIEW_STEP: i64 = 10;
+
+/// How a frame range is split into worker items.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum SplitStrategy {
+    /// One worker renders the whole range
+    #[default]
+    Single,
+    /// Consecutive chunks of `chunk_size` frames
+    Chunked { chunk_size: usize },
+    /// Every `step`th frame first, then halve the step until every frame is
+    /// covered; coarser passes get higher priority
+    PreviewFirst {
+        step: Option<i64>,
+        chunk_size: Option<usize>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RenderRequest {
+    pub filepath: String,
+    pub frame_start: i64,
+    pub frame_end: i64,
+    #[serde(default = "default_step")]
+    pub frame_step: i64,
+    #[serde(default)]
+    pub split: SplitStrategy,
+    /// Base priority of the job's chunks
+    #[serde(default)]
+    pub priority: i32,
+    /// Per-chunk priority overrides, by position in the plan
+    #[serde(default)]
+    pub chunk_priorities: Vec<Option<i32>>,
+    /// Overrides the file's output path (image sequence pattern)
+    pub output: Option<String>,
+    pub device_profile: Option<String>,
+    pub validation_profile: Option<String>,
+    /// Install name or Blender path; defaults to the worker setting
+    pub blender: Option<String>,
+    /// Scene to render; defaults to the file's active scene
+    #[serde(default)]
+    pub scene: Option<String>,
+    /// Camera to render through; defaults to the scene camera
+    #[serde(default)]
+    pub camera: Option<String>,
+}
+
+fn default_step() -> i64 {
+    1
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RenderChunk {
+    pub label: String,
+    pub frames: Vec<i64>,
+    pub priority: i32,
+}
+
+/// Preview how a request would be split without queuing it
+#[tauri::command]
+pub fn plan_render_chunks(request: RenderRequest) -> Result<Vec<RenderChunk>, CommandError> {
+    plan(&request)
+}
+
+/// Queue a render job; chunks share one output sequence.
+///
+/// When it finishes, the job summary lists frames that weren't rendered.
+#[tauri::command]
+pub async fn start_render_job(
+    request: RenderRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+    shares: State<'_, ShareMonitor>,
+) -> Result<String, CommandError> {
+    let id = submit_render(&state, &settings, &pool, &shares, &request).await?;
+    workers::spawn_job(app, id.clone(), move |_, job| Some(summarize(&request, job)));
+    Ok(id)
+}
+
+/// Validate and queue a render job without running it.
+pub async fn submit_render(
+    state: &AppState,
+    settings: &SettingsStore,
+    pool: &WorkerPool,
+    shares: &ShareMonitor,
+    request: &RenderRequest,
+) -> Result<String, CommandError> {
+    // Reject a bad frame range before validating
+    plan(request)?;
+    diskguard::require_space(&settings.get().disk_guard, request)?;
+    shares::require_reachable(settings, shares).await?;
+    if let Some(profile) = &request.validation_profile {
+        validation::require_passing_if_open(state, settings, profile, &[request.filepath.as_str()]).await?;
+    }
+    pool.submit(JobRequest::Render(request.clone()), settings).await
+}
+
+/// One item per planned chunk
+pub fn work(settings: &SettingsStore, request: &RenderRequest) -> Result<Vec<WorkItem>, CommandError> {
+    let chunks = plan(request)?;
+    let devices = request
+        .device_profile
+        .as_deref()
+        .map(|name| devices::find_profile(settings, name))
+        .transpose()?;
+
+    let split = chunks.len() > 1;
+    let work = chunks
+        .into_iter()
+        .map(|chunk| WorkItem {
+            label: chunk.label,
+            blend_file: Some(PathBuf::from(&request.filepath)),
+            script: RENDER_SCRIPT,
+            args: json!({
+                "frames": chunk.frames,
+                "output": request.output,
+                "split": split,
+                "scene": request.scene,
+                "camera": request.camera,
+            }),
+            devices: devices.clone(),
+            priority: chunk.priority,
+            env: BTreeMap::new(),
+        })
+        .collect();
+    Ok(work)
+}
+
+/// Frames rendered vs. requested, from each chunk's `rendered` result
+pub fn summarize(request: &RenderRequest, job: &Job) -> Value {
+    let expected = frames(request);
+    let rendered: BTreeSet<i64> = job
+        .items
+        .iter()
+        .filter_map(|item| item.result.as_ref()?.get("rendered")?.as_array().cloned())
+        .flatten()
+        .filter_map(|frame| frame.as_i64())
+        .collect();
+    let missing: Vec<i64> = expected.iter().copied().filter(|f| !rendered.contains(f)).collect();
+
+    json!({
+        "frames_total": expected.len(),
+        "frames_rendered": rendered.len(),
+        "missing": missing,
+    })
+}
+
+/// Every frame the request renders
+pub fn frames(request: &RenderRequest) -> Vec<i64> {
+    let step = request.frame_step.max(1) as usize;
+    (request.frame_start..=request.frame_end).step_by(step).collect()
+}
+
+fn plan(request: &RenderRequest) -> Result<Vec<RenderChunk>, CommandError> {
+    if request.frame_end < request.frame_start {
+        return Err(CommandError::InvalidInput("Frame end is before frame start".to_string()));
+    }
+    let all = frames(request);
+    let base = request.priority;
+
+    let mut chunks = match &request.split {
+        SplitStrategy::Single => vec![RenderChunk {
+            label: range_label(&all),
+            frames: all,
+            priority: base,
+        }],
+        SplitStrategy::Chunked { chunk_size } => chunk(&all, *chunk_size, base)?,
+        SplitStrategy::PreviewFirst { step, chunk_size } => {
+            let mut step = step.unwrap_or(DEFAULT_PREVIEW_STEP).max(1);
+            let mut done = BTreeSet::new();
+            let mut passes = Vec::new();
+            loop {
+                let pass: Vec<i64> = all
+                    .iter()
+                    .copied()
+                    .filter(|f| (f - request.frame_start) % (step * request.frame_step.max(1)) == 0)
+                    .filter(|f| done.insert(*f))
+                    .collect();
+                if !pass.is_empty() {
+                    passes.push((step, pass));
+                }
+                if step == 1 {
+                    break;
+                }
+                step = (step / 2).max(1);
+            }
+
+            let count = passes.len() as i32;
+            let mut chunks = Vec::new();
+            for (index, (step, pass)) in passes.into_iter().enumerate() {
+                let priority = base + count - index as i32;
+                let size = chunk_size.unwrap_or(pass.len());
+                for mut piece in chunk(&pass, size, priority)? {
+                    piece.label = format!("every {} ({})", step, piece.label);
+                    chunks.push(piece);
+                }
+            }
+            chunks
+        }
+    };
+
+    for (chunk, priority) in chunks.iter_mut().zip(&request.chunk_priorities) {
+        if let Some(priority) = priority {
+            chunk.priority = *priority;
+        }
+    }
+    Ok(chunks)
+}
+
+fn chunk(frames: &[i64], size: usize, priority: i32) -> Result<Vec<RenderChunk>, CommandError> {
+    if size == 0 {
+        return Err(CommandError::InvalidInput("Chunk size must be at least 1".to_string()));
+    }
+    Ok(frames
+        .chunks(size)
+        .map(|frames| RenderChunk {
+            label: range_label(frames),
+            frames: frames.to_vec(),
+            priority,
+        })
+        .collect())
+}
+
+fn range_label(frames: &[i64]) -> String {
+    match (frames.first(), frames.last()) {
+        (Some(first), Some(last)) if first != last => format!("frames {}-{}", first, last),
+        (Some(first), _) => format!("frame {}", first),
+        _ => "no frames".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(frame_start: i64, frame_end: i64, split: SplitStrategy) -> RenderRequest {
+        RenderRequest {
+            filepath: "/shots/sh010.blend".to_string(),
+            frame_start,
+            frame_end,
+            frame_step: 1,
+            split,
+            priority: 0,
+            chunk_priorities: Vec::new(),
+            output: None,
+            device_profile: None,
+            validation_profile: None,
+            blender: None,
+            scene: None,
+            camera: None,
+        }
+    }
+
+    fn frames_of(chunks: &[RenderChunk]) -> Vec<Vec<i64>> {
+        chunks.iter().map(|chunk| chunk.frames.clone()).collect()
+    }
+
+    #[test]
+    fn single_renders_the_whole_range() {
+        let chunks = plan(&request(1, 4, SplitStrategy::Single)).unwrap();
+        assert_eq!(frames_of(&chunks), [vec![1, 2, 3, 4]]);
+        assert_eq!(chunks[0].label, "frames 1-4");
+    }
+
+    #[test]
+    fn frame_step_skips_frames() {
+        let mut request = request(1, 10, SplitStrategy::Single);
+        request.frame_step = 3;
+        assert_eq!(frames_of(&plan(&request).unwrap()), [vec![1, 4, 7, 10]]);
+    }
+
+    #[test]
+    fn chunked_splits_consecutive_frames() {
+        let chunks = plan(&request(1, 10, SplitStrategy::Chunked { chunk_size: 4 })).unwrap();
+        assert_eq!(frames_of(&chunks), [vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10]]);
+        assert_eq!(chunks[2].label, "frames 9-10");
+    }
+
+    #[test]
+    fn preview_first_renders_coarse_passes_first() {
+        let split = SplitStrategy::PreviewFirst {
+            step: Some(4),
+            chunk_size: None,
+        };
+        let chunks = plan(&request(1, 9, split)).unwrap();
+        assert_eq!(frames_of(&chunks), [vec![1, 5, 9], vec![3, 7], vec![2, 4, 6, 8]]);
+        let priorities: Vec<i32> = chunks.iter().map(|chunk| chunk.priority).collect();
+        assert_eq!(priorities, [3, 2, 1]);
+        assert_eq!(chunks[0].label, "every 4 (frames 1-9)");
+    }
+
+    #[test]
+    fn chunk_priorities_override_by_position() {
+        let mut request = request(1, 10, SplitStrategy::Chunked { chunk_size: 5 });
+        request.priority = 2;
+        request.chunk_priorities = vec![None, Some(9)];
+        let priorities: Vec<i32> = plan(&request).unwrap().iter().map(|chunk| chunk.priority).collect();
+        assert_eq!(priorities, [2, 9]);
+    }
+
+    #[test]
+    fn bad_ranges_and_sizes_are_rejected() {
+        assert!(matches!(
+            plan(&request(10, 1, SplitStrategy::Single)),
+            Err(CommandError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            plan(&request(1, 10, SplitStrategy::Chunked { chunk_size: 0 })),
+            Err(CommandError::InvalidInput(_))
+        ));
+    }
+}
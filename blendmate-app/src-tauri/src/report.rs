@@ -0,0 +1,339 @@
+use base64::Engine;
+use chrono::{DateTime, FixedOffset};
+use minijinja::Environment;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::i18n;
+use crate::projects;
+use crate::safemode;
+use crate::settings::SettingsStore;
+use crate::tagging::{TagState, Tags};
+use crate::timelapse::TimelapseState;
+use crate::validation::{self, ValidationReport};
+use crate::workers::{Job, JobKind, JobStatus, WorkerPool};
+use crate::AppState;
+
+const REPORT_TEMPLATE: &str = include_str!("../templates/report.html");
+
+/// Longest edge of an embedded thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 480;
+
+/// Render outputs and timelapse frames embedded, each
+const MAX_THUMBNAILS: usize = 8;
+
+/// Start and end as RFC 3339 timestamps; an open end is now
+#[derive(Deserialize, Clone, Default, Debug)]
+pub struct ReportRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct ActionCount {
+    action: String,
+    count: usize,
+}
+
+#[derive(Serialize, Debug)]
+struct Digest {
+    commands: usize,
+    /// Most used first
+    actions: Vec<ActionCount>,
+    first_at: Option<String>,
+    last_at: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct JobRow {
+    id: String,
+    kind: JobKind,
+    status: JobStatus,
+    created_at: String,
+    duration_secs: Option<i64>,
+    frames: usize,
+    errors: Vec<String>,
+}
+
+#[derive(Serialize, Default, Debug)]
+struct RenderStats {
+    jobs: usize,
+    done: usize,
+    failed: usize,
+    frames: usize,
+    render_secs: i64,
+    secs_per_frame: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+struct Thumbnail {
+    caption: String,
+    /// `data:image/jpeg;base64,...`
+    data_uri: String,
+}
+
+#[derive(Serialize, Debug)]
+struct Report {
+    title: String,
+    project: String,
+    /// Of the timelapse session, else of the open file
+    tags: Tags,
+    generated_at: String,
+    from: String,
+    to: String,
+    digest: Digest,
+    render: RenderStats,
+    jobs: Vec<JobRow>,
+    /// Unset while Blender is disconnected
+    validation: Option<ValidationReport>,
+    validation_error: Option<String>,
+    thumbnails: Vec<Thumbnail>,
+    timelapse: Vec<Thumbnail>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReportSummary {
+    pub path: String,
+    pub commands: usize,
+    pub jobs: usize,
+    pub thumbnails: usize,
+}
+
+fn parse(at: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(at).ok()
+}
+
+fn within(at: &str, from: &DateTime<FixedOffset>, to: &DateTime<FixedOffset>) -> bool {
+    parse(at).is_some_and(|at| at >= *from && at <= *to)
+}
+
+/// Resolve the range from the timelapse session and `range`, whose ends
+/// take precedence
+fn resolve_range(
+    session: Option<&str>,
+    range: ReportRange,
+    timelapse: &TimelapseState,
+) -> Result<(DateTime<FixedOffset>, DateTime<FixedOffset>), CommandError> {
+    let recorded = session.map(|id| timelapse.load(id)).transpose()?;
+    let from = range
+        .from
+        .or_else(|| recorded.as_ref().map(|session| session.started_at.clone()))
+        .ok_or_else(|| CommandError::InvalidInput("Give a timelapse session or a start time".to_string()))?;
+    let to = range.to.or_else(|| recorded.and_then(|session| session.stopped_at));
+    let from = parse(&from).ok_or_else(|| CommandError::InvalidInput(format!("Invalid start time '{}'", from)))?;
+    let to = match to {
+        Some(to) => parse(&to).ok_or_else(|| CommandError::InvalidInput(format!("Invalid end time '{}'", to)))?,
+        None => chrono::Local::now().fixed_offset(),
+    };
+    if to < from {
+        return Err(CommandError::InvalidInput("The range ends before it starts".to_string()));
+    }
+    Ok((from, to))
+}
+
+fn digest(history: &HistoryStore, project: &str, from: &DateTime<FixedOffset>, to: &DateTime<FixedOffset>) -> Digest {
+    let entries: Vec<_> = history
+        .entries(project)
+        .into_iter()
+        .filter(|entry| within(&entry.at, from, to))
+        .collect();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in &entries {
+        *counts.entry(entry.action.clone()).or_default() += 1;
+    }
+    let mut actions: Vec<ActionCount> = counts
+        .into_iter()
+        .map(|(action, count)| ActionCount { action, count })
+        .collect();
+    actions.sort_by(|a, b| b.count.cmp(&a.count));
+    Digest {
+        commands: entries.len(),
+        actions,
+        first_at: entries.first().map(|entry| entry.at.clone()),
+        last_at: entries.last().map(|entry| entry.at.clone()),
+    }
+}
+
+fn job_row(job: &Job) -> JobRow {
+    let duration_secs = job
+        .finished_at
+        .as_deref()
+        .and_then(parse)
+        .zip(parse(&job.created_at))
+        .map(|(finished, created)| (finished - created).num_seconds());
+    JobRow {
+        id: job.id.clone(),
+        kind: job.kind,
+        status: job.status,
+        created_at: job.created_at.clone(),
+        duration_secs,
+        frames: job.items.iter().map(|item| item.outputs.len()).sum(),
+        errors: job.items.iter().flat_map(|item| item.errors.iter().cloned()).collect(),
+    }
+}
+
+fn render_stats(rows: &[JobRow]) -> RenderStats {
+    let renders: Vec<&JobRow> = rows.iter().filter(|row| matches!(row.kind, JobKind::Render)).collect();
+    let frames = renders.iter().map(|row| row.frames).sum();
+    let render_secs = renders.iter().filter_map(|row| row.duration_secs).sum();
+    RenderStats {
+        jobs: renders.len(),
+        done: renders.iter().filter(|row| row.status == JobStatus::Done).count(),
+        failed: renders.iter().filter(|row| row.status == JobStatus::Failed).count(),
+        frames,
+        render_secs,
+        secs_per_frame: (frames > 0).then(|| render_secs as f64 / frames as f64),
+    }
+}
+
+/// A JPEG thumbnail of `path` as a data URI; `None` for formats the image
+/// crate can't read, e.g. EXR
+fn thumbnail(path: &Path, caption: String) -> Option<Thumbnail> {
+    let image = image::open(path).ok()?;
+    let mut bytes = Vec::new();
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgb8()
+        .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Jpeg)
+        .ok()?;
+    Some(Thumbnail {
+        caption,
+        data_uri: format!(
+            "data:image/jpeg;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        ),
+    })
+}
+
+/// The last output of each finished render, newest first
+fn render_thumbnails(jobs: &[Job]) -> Vec<Thumbnail> {
+    jobs.iter()
+        .rev()
+        .filter(|job| matches!(job.kind, JobKind::Render) && job.status == JobStatus::Done)
+        .filter_map(|job| {
+            let output = job.items.iter().rev().find_map(|item| item.outputs.last())?;
+            let name = Path::new(output).file_name()?.to_string_lossy().into_owned();
+            thumbnail(Path::new(output), format!("{} · {}", job.id, name))
+        })
+        .take(MAX_THUMBNAILS)
+        .collect()
+}
+
+/// Frames spread evenly over the timelapse, first and last included
+fn timelapse_thumbnails(dir: &Path) -> Vec<Thumbnail> {
+    let mut frames: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jpg"))
+        .collect();
+    frames.sort();
+    if frames.is_empty() {
+        return Vec::new();
+    }
+    let count = frames.len().min(MAX_THUMBNAILS);
+    let step = (frames.len() - 1) as f64 / (count.max(2) - 1) as f64;
+    (0..count)
+        .filter_map(|index| {
+            let path = &frames[(index as f64 * step).round() as usize];
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            thumbnail(path, name)
+        })
+        .collect()
+}
+
+fn render_html(report: &Report) -> Result<String, CommandError> {
+    let mut env = Environment::new();
+    env.add_function("t", |key: String| i18n::t(&key, &[]));
+    env.add_template("report.html", REPORT_TEMPLATE)
+        .map_err(|e| format!("Invalid report template: {}", e))?;
+    let template = env
+        .get_template("report.html")
+        .map_err(|e| format!("Invalid report template: {}", e))?;
+    template
+        .render(report)
+        .map_err(|e| CommandError::Failed(format!("Failed to render report: {}", e)))
+}
+
+/// Write a self-contained HTML report of a working session to `path`: the
+/// command digest, render statistics, a validation run of the open scene
+/// and embedded thumbnails. The session is a timelapse id, whose start and
+/// stop bound the report; `range` narrows or replaces them.
+#[tauri::command]
+pub async fn export_report(
+    session: Option<String>,
+    range: Option<ReportRange>,
+    path: String,
+    validation_profile: Option<String>,
+    app: AppHandle,
+) -> Result<ReportSummary, CommandError> {
+    let state = app.state::<AppState>();
+    let settings = app.state::<SettingsStore>();
+    let timelapse = app.state::<TimelapseState>();
+    let (from, to) = resolve_range(session.as_deref(), range.unwrap_or_default(), &timelapse)?;
+    let project = projects::key(&settings, None);
+    let tags = session
+        .as_deref()
+        .and_then(|id| timelapse.load(id).ok())
+        .map(|recorded| recorded.tags)
+        .unwrap_or_else(|| app.state::<TagState>().current().tags);
+    let digest = digest(&app.state::<HistoryStore>(), &project, &from, &to);
+
+    let jobs: Vec<Job> = safemode::subsystem::<WorkerPool, _>(&app, "workers")?
+        .list()
+        .await
+        .into_iter()
+        .filter(|job| within(&job.created_at, &from, &to))
+        .collect();
+    let rows: Vec<JobRow> = jobs.iter().map(job_row).collect();
+
+    let profile = validation_profile.unwrap_or_else(|| "default".to_string());
+    let (validation, validation_error) = if state.session.peer().is_some() {
+        match validation::run_validation(&state, &settings, &profile).await {
+            Ok(report) => (Some(report), None),
+            Err(err) => (None, Some(err.to_string())),
+        }
+    } else {
+        (None, Some(i18n::t("blender-not-connected", &[])))
+    };
+
+    let timelapse_dir = session.as_deref().map(|id| timelapse.dir(id));
+    let (thumbnails, frames) = tauri::async_runtime::spawn_blocking(move || {
+        let frames = timelapse_dir.map(|dir| timelapse_thumbnails(&dir)).unwrap_or_default();
+        (render_thumbnails(&jobs), frames)
+    })
+    .await
+    .map_err(|e| format!("Failed to make thumbnails: {}", e))?;
+
+    let report = Report {
+        title: session.unwrap_or_else(|| project.clone()),
+        project,
+        tags,
+        generated_at: chrono::Local::now().to_rfc3339(),
+        from: from.to_rfc3339(),
+        to: to.to_rfc3339(),
+        render: render_stats(&rows),
+        digest,
+        jobs: rows,
+        validation,
+        validation_error,
+        thumbnails,
+        timelapse: frames,
+    };
+    let html = render_html(&report)?;
+    fs::write(&path, html).map_err(|e| CommandError::Io(format!("Failed to write report: {}", e)))?;
+
+    Ok(ReportSummary {
+        path,
+        commands: report.digest.commands,
+        jobs: report.jobs.len(),
+        thumbnails: report.thumbnails.len() + report.timelapse.len(),
+    })
+}
@@ -0,0 +1,477 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::shots::ShotState;
+use crate::storage::SharedStorage;
+use crate::AppState;
+
+/// Blender cuts marker names at 63 bytes
+const MAX_MARKER_NAME: usize = 63;
+
+/// A timeline marker as Blender has it
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MarkerRef {
+    pub scene: String,
+    pub name: String,
+    pub frame: i64,
+}
+
+/// A review comment on one frame of a scene
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReviewNote {
+    pub id: String,
+    pub scene: String,
+    pub frame: i64,
+    /// Markdown; the marker is named from its first line
+    pub text: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// The note's marker as of the last sync; `None` until it has one
+    pub marker: Option<MarkerRef>,
+    /// Deleted in the app, kept until the marker is removed by a sync
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+impl ReviewNote {
+    /// The marker this note should have
+    fn wanted(&self) -> MarkerRef {
+        MarkerRef {
+            scene: self.scene.clone(),
+            name: marker_name(&self.text),
+            frame: self.frame,
+        }
+    }
+
+    /// Changed in the app since the last sync
+    fn changed(&self) -> bool {
+        self.deleted || self.marker.as_ref() != Some(&self.wanted())
+    }
+}
+
+/// Review notes of one .blend file, in the `review` namespace of the
+/// storage backend under the file's path
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+struct ReviewFile {
+    notes: Vec<ReviewNote>,
+    /// Every marker of the file as of the last sync, so markers changed in
+    /// Blender since can be told apart
+    known: Vec<MarkerRef>,
+}
+
+/// New note, or changed text or frame of one
+#[derive(Deserialize, Clone, Debug)]
+pub struct ReviewNoteInput {
+    /// Updates this note; a new one is added without
+    pub id: Option<String>,
+    pub scene: String,
+    pub frame: i64,
+    pub text: String,
+}
+
+/// Which side wins when a note and its marker both changed since the last
+/// sync
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    App,
+    Blender,
+}
+
+/// A note and its marker both changed; nothing was done to either
+#[derive(Serialize, Clone, Debug)]
+pub struct MarkerConflict {
+    pub note: ReviewNote,
+    /// The marker now, `None` when it was removed in Blender
+    pub marker: Option<MarkerRef>,
+}
+
+/// Emitted as `review:synced`
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct MarkerSyncReport {
+    pub file: String,
+    /// Markers created, changed or removed after notes
+    pub pushed: usize,
+    /// Notes changed or removed after markers
+    pub pulled: usize,
+    pub conflicts: Vec<MarkerConflict>,
+    pub notes: Vec<ReviewNote>,
+}
+
+/// A marker name from the first line of a note
+fn marker_name(text: &str) -> String {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("Note");
+    let mut end = line.len().min(MAX_MARKER_NAME);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[..end].to_string()
+}
+
+fn new_id() -> String {
+    format!("note-{}", chrono::Utc::now().format("%Y%m%d%H%M%S%f"))
+}
+
+/// Where a note's marker went in Blender since the last sync
+enum Found {
+    Unchanged(usize),
+    Changed(usize),
+    Removed,
+}
+
+/// Find the marker a note had at the last sync. A marker that wasn't there
+/// then, with the same name in the same scene, was moved; one on the same
+/// frame was renamed.
+fn locate(
+    synced: &MarkerRef,
+    markers: &[MarkerRef],
+    known: &HashSet<&MarkerRef>,
+    claimed: &HashSet<usize>,
+) -> Found {
+    if let Some(index) = markers.iter().position(|marker| marker == synced) {
+        return Found::Unchanged(index);
+    }
+    let new = |index: &usize| !claimed.contains(index) && !known.contains(&markers[*index]);
+    let in_scene = |index: &usize| markers[*index].scene == synced.scene;
+    let moved = (0..markers.len()).filter(new).filter(in_scene).find(|index| markers[*index].name == synced.name);
+    let renamed = || {
+        let mut candidates = (0..markers.len())
+            .filter(new)
+            .filter(in_scene)
+            .filter(|index| markers[*index].frame == synced.frame);
+        // Several new markers on the frame can't be told apart
+        candidates.next().filter(|_| candidates.next().is_none())
+    };
+    moved.or_else(renamed).map_or(Found::Removed, Found::Changed)
+}
+
+/// A marker change for the add-on's `review.apply`
+fn change(op: &str, marker: &MarkerRef, old: Option<&MarkerRef>) -> Value {
+    json!({
+        "op": op,
+        "scene": marker.scene,
+        "name": marker.name,
+        "frame": marker.frame,
+        "old_name": old.map(|old| &old.name),
+        "old_frame": old.map(|old| old.frame),
+    })
+}
+
+/// Merge notes with the markers Blender has now. Returns the marker changes
+/// to make and the report; `file.notes` is updated as if they were made.
+fn merge(
+    file: &mut ReviewFile,
+    markers: &[MarkerRef],
+    resolve: &HashMap<String, Side>,
+) -> (Vec<Value>, MarkerSyncReport) {
+    let known: HashSet<&MarkerRef> = file.known.iter().collect();
+    let mut claimed = HashSet::new();
+    let mut changes = Vec::new();
+    let mut report = MarkerSyncReport::default();
+    let mut kept = Vec::new();
+
+    for mut note in std::mem::take(&mut file.notes) {
+        let Some(synced) = note.marker.clone() else {
+            if !note.deleted {
+                let wanted = note.wanted();
+                changes.push(change("create", &wanted, None));
+                note.marker = Some(wanted);
+                report.pushed += 1;
+                kept.push(note);
+            }
+            continue;
+        };
+        let found = locate(&synced, markers, &known, &claimed);
+        let current = match found {
+            Found::Unchanged(index) | Found::Changed(index) => {
+                claimed.insert(index);
+                Some(&markers[index])
+            }
+            Found::Removed => None,
+        };
+        let in_blender = !matches!(found, Found::Unchanged(_));
+        let side = match (note.changed(), in_blender) {
+            (false, false) => {
+                kept.push(note);
+                continue;
+            }
+            // Removed on both sides
+            (true, true) if note.deleted && current.is_none() => continue,
+            (true, false) => Side::App,
+            (false, true) => Side::Blender,
+            (true, true) => match resolve.get(&note.id) {
+                Some(side) => *side,
+                None => {
+                    report.conflicts.push(MarkerConflict {
+                        note: note.clone(),
+                        marker: current.cloned(),
+                    });
+                    kept.push(note);
+                    continue;
+                }
+            },
+        };
+
+        match side {
+            Side::App => {
+                report.pushed += 1;
+                if note.deleted {
+                    if let Some(current) = current {
+                        changes.push(change("delete", current, Some(current)));
+                    }
+                    continue;
+                }
+                let wanted = note.wanted();
+                match current {
+                    Some(current) => changes.push(change("update", &wanted, Some(current))),
+                    None => changes.push(change("create", &wanted, None)),
+                }
+                note.marker = Some(wanted);
+            }
+            Side::Blender => {
+                report.pulled += 1;
+                let Some(current) = current else {
+                    continue;
+                };
+                note.deleted = false;
+                note.frame = current.frame;
+                if current.name != marker_name(&note.text) {
+                    note.text = current.name.clone();
+                }
+                note.updated_at = chrono::Local::now().to_rfc3339();
+                note.marker = Some(current.clone());
+            }
+        }
+        kept.push(note);
+    }
+    file.notes = kept;
+    (changes, report)
+}
+
+fn parse_markers(data: &Value) -> Result<Vec<MarkerRef>, CommandError> {
+    serde_json::from_value(data.get("markers").cloned().unwrap_or(Value::Null))
+        .map_err(|e| CommandError::Failed(format!("Invalid marker list from Blender: {}", e)))
+}
+
+/// Review notes per .blend file, synced with timeline markers
+pub struct ReviewStore {
+    storage: SharedStorage,
+    /// Held across a whole sync, so two syncs don't interleave
+    lock: tokio::sync::Mutex<()>,
+    files: Mutex<HashMap<String, ReviewFile>>,
+}
+
+impl ReviewStore {
+    pub fn new(storage: SharedStorage) -> Self {
+        Self {
+            storage,
+            lock: tokio::sync::Mutex::new(()),
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn load(&self, path: &str) -> ReviewFile {
+        let mut files = self.files.lock().unwrap();
+        files
+            .entry(path.to_string())
+            .or_insert_with(|| self.storage.get_json("review", path).unwrap_or_default())
+            .clone()
+    }
+
+    fn save(&self, path: &str, file: ReviewFile) -> Result<(), CommandError> {
+        self.storage.put_json("review", path, &file)?;
+        self.files.lock().unwrap().insert(path.to_string(), file);
+        Ok(())
+    }
+}
+
+fn visible(file: &ReviewFile) -> Vec<ReviewNote> {
+    let mut notes: Vec<ReviewNote> = file.notes.iter().filter(|note| !note.deleted).cloned().collect();
+    notes.sort_by(|a, b| a.scene.cmp(&b.scene).then(a.frame.cmp(&b.frame)));
+    notes
+}
+
+/// Path of the open .blend file, from the last scene Blender sent when it
+/// isn't connected
+async fn current_file(state: &AppState) -> Result<String, CommandError> {
+    let (scene, _) = state.mirror.scene_or_cached(&state.bridge).await?;
+    let path = scene.get("filepath").and_then(Value::as_str).unwrap_or_default();
+    if path.is_empty() {
+        return Err(CommandError::InvalidInput(
+            "Save the .blend file first; review notes are kept per file".to_string(),
+        ));
+    }
+    Ok(path.to_string())
+}
+
+/// Review notes of the open .blend file, by scene and frame
+#[tauri::command]
+pub async fn list_review_notes(
+    state: State<'_, AppState>,
+    review: State<'_, ReviewStore>,
+) -> Result<Vec<ReviewNote>, CommandError> {
+    let path = current_file(&state).await?;
+    Ok(visible(&review.load(&path)))
+}
+
+/// Add a note, or change one. Markers follow on the next sync.
+#[tauri::command]
+pub async fn save_review_note(
+    note: ReviewNoteInput,
+    state: State<'_, AppState>,
+    review: State<'_, ReviewStore>,
+) -> Result<ReviewNote, CommandError> {
+    if note.text.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Note text is empty".to_string()));
+    }
+    let path = current_file(&state).await?;
+    let _sync = review.lock.lock().await;
+    let mut file = review.load(&path);
+    let now = chrono::Local::now().to_rfc3339();
+    let saved = match &note.id {
+        Some(id) => {
+            let existing = file
+                .notes
+                .iter_mut()
+                .find(|known| known.id == *id && !known.deleted)
+                .ok_or_else(|| CommandError::NotFound(format!("Review note '{}' not found", id)))?;
+            existing.scene = note.scene;
+            existing.frame = note.frame;
+            existing.text = note.text;
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let created = ReviewNote {
+                id: new_id(),
+                scene: note.scene,
+                frame: note.frame,
+                text: note.text,
+                created_at: now.clone(),
+                updated_at: now,
+                marker: None,
+                deleted: false,
+            };
+            file.notes.push(created.clone());
+            created
+        }
+    };
+    review.save(&path, file)?;
+    Ok(saved)
+}
+
+/// Delete a note; its marker is removed on the next sync
+#[tauri::command]
+pub async fn delete_review_note(
+    id: String,
+    state: State<'_, AppState>,
+    review: State<'_, ReviewStore>,
+) -> Result<(), CommandError> {
+    let path = current_file(&state).await?;
+    let _sync = review.lock.lock().await;
+    let mut file = review.load(&path);
+    let index = file
+        .notes
+        .iter()
+        .position(|note| note.id == id && !note.deleted)
+        .ok_or_else(|| CommandError::NotFound(format!("Review note '{}' not found", id)))?;
+    if file.notes[index].marker.is_some() {
+        file.notes[index].deleted = true;
+        file.notes[index].updated_at = chrono::Local::now().to_rfc3339();
+    } else {
+        file.notes.remove(index);
+    }
+    review.save(&path, file)
+}
+
+/// Make notes from existing markers of a scene, linked to them from then on
+#[tauri::command]
+pub async fn import_review_markers(
+    scene: String,
+    names: Vec<String>,
+    state: State<'_, AppState>,
+    review: State<'_, ReviewStore>,
+) -> Result<Vec<ReviewNote>, CommandError> {
+    if names.is_empty() {
+        return Err(CommandError::InvalidInput("No markers selected".to_string()));
+    }
+    let data = state.bridge.request("review.list_markers", "", json!({})).await?;
+    let path = data.get("filepath").and_then(Value::as_str).unwrap_or_default().to_string();
+    if path.is_empty() {
+        return Err(CommandError::InvalidInput(
+            "Save the .blend file first; review notes are kept per file".to_string(),
+        ));
+    }
+    let markers = parse_markers(&data)?;
+    let _sync = review.lock.lock().await;
+    let mut file = review.load(&path);
+    let linked: HashSet<MarkerRef> = file.notes.iter().filter_map(|note| note.marker.clone()).collect();
+    for name in &names {
+        let marker = markers
+            .iter()
+            .find(|marker| marker.scene == scene && marker.name == *name && !linked.contains(*marker))
+            .ok_or_else(|| CommandError::NotFound(format!("No unlinked marker '{}' in scene '{}'", name, scene)))?;
+        let now = chrono::Local::now().to_rfc3339();
+        file.notes.push(ReviewNote {
+            id: new_id(),
+            scene: scene.clone(),
+            frame: marker.frame,
+            text: marker.name.clone(),
+            created_at: now.clone(),
+            updated_at: now,
+            marker: Some(marker.clone()),
+            deleted: false,
+        });
+    }
+    review.save(&path, file.clone())?;
+    Ok(visible(&file))
+}
+
+/// Sync the open file's review notes with its timeline markers, both ways.
+/// New notes get markers named from their first line. Notes follow their
+/// markers when those are moved, renamed or removed in Blender, and
+/// markers follow notes changed in the app. A note whose marker changed on
+/// both sides is a conflict and left alone, unless `resolve` says which
+/// side wins for it. Emits `review:synced`.
+#[tauri::command]
+pub async fn sync_review_markers<R: Runtime>(
+    resolve: Option<HashMap<String, Side>>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    review: State<'_, ReviewStore>,
+) -> Result<MarkerSyncReport, CommandError> {
+    let _sync = review.lock.lock().await;
+    let data = state.bridge.request("review.list_markers", "", json!({})).await?;
+    let path = data.get("filepath").and_then(Value::as_str).unwrap_or_default().to_string();
+    if path.is_empty() {
+        return Err(CommandError::InvalidInput(
+            "Save the .blend file first; review notes are kept per file".to_string(),
+        ));
+    }
+    let markers = parse_markers(&data)?;
+    let mut file = review.load(&path);
+    let (changes, mut report) = merge(&mut file, &markers, &resolve.unwrap_or_default());
+
+    let mut linked: BTreeMap<&str, Vec<(&str, i64)>> = BTreeMap::new();
+    for marker in file.notes.iter().filter_map(|note| note.marker.as_ref()) {
+        linked.entry(&marker.scene).or_default().push((&marker.name, marker.frame));
+    }
+    let params = json!({ "changes": changes, "linked": linked });
+    let applied = state.bridge.request("review.apply", "", params).await?;
+    file.known = parse_markers(&applied)?;
+    review.save(&path, file.clone())?;
+
+    report.file = path;
+    report.notes = visible(&file);
+    if let Err(err) = app.emit("review:synced", &report) {
+        eprintln!("Failed to emit review:synced: {err}");
+    }
+    // Linked markers drop out of the shot list
+    let _ = app.state::<ShotState>().refresh(&app).await;
+    Ok(report)
+}
@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, RunEvent, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::journal::JobJournal;
+use crate::settings::SettingsStore;
+use crate::storage::SharedStorage;
+use crate::workers::{self, WorkerPool};
+use crate::{
+    addon, assets, benchmarks, features, licenses, macros, notes, palettes, placement, playlist, power,
+    quality, scratch, watchdog,
+};
+
+/// Launch flag that starts in safe mode
+pub const SAFE_MODE_FLAG: &str = "--safe-mode";
+
+/// Launches in a row that ended during startup before safe mode starts on
+/// its own
+const CRASH_LIMIT: u32 = 3;
+
+/// A launch counts as started once it ran this long
+const STARTED_AFTER: Duration = Duration::from_secs(15);
+
+/// Something started at launch that safe mode leaves out
+struct Subsystem {
+    id: &'static str,
+    description: &'static str,
+    /// Started first when this one is enabled
+    requires: &'static [&'static str],
+}
+
+const SUBSYSTEMS: &[Subsystem] = &[
+    Subsystem {
+        id: "benchmarks",
+        description: "Benchmark results",
+        requires: &[],
+    },
+    Subsystem {
+        id: "workers",
+        description: "Render worker pool, with the jobs restored from the journal",
+        requires: &["benchmarks"],
+    },
+    Subsystem {
+        id: "assets",
+        description: "Asset index",
+        requires: &[],
+    },
+    Subsystem {
+        id: "playlists",
+        description: "Render playlists",
+        requires: &["workers"],
+    },
+    Subsystem {
+        id: "palettes",
+        description: "Project palettes",
+        requires: &[],
+    },
+    Subsystem {
+        id: "licenses",
+        description: "Asset licenses",
+        requires: &[],
+    },
+    Subsystem {
+        id: "notes",
+        description: "Notes",
+        requires: &[],
+    },
+    Subsystem {
+        id: "macros",
+        description: "Macros",
+        requires: &[],
+    },
+    Subsystem {
+        id: "placement",
+        description: "Window placement across monitor layouts",
+        requires: &[],
+    },
+    Subsystem {
+        id: "power",
+        description: "Pause renders on battery or under load",
+        requires: &["workers"],
+    },
+    Subsystem {
+        id: "quality",
+        description: "Connection quality pings",
+        requires: &[],
+    },
+    Subsystem {
+        id: "watchdog",
+        description: "Busy Blender detection",
+        requires: &[],
+    },
+    Subsystem {
+        id: "scratch",
+        description: "Scratch folder quotas",
+        requires: &[],
+    },
+    Subsystem {
+        id: "features",
+        description: "Background features from the feature flags",
+        requires: &["workers", "macros"],
+    },
+    Subsystem {
+        id: "addon_dev",
+        description: "Add-on dev mode",
+        requires: &[],
+    },
+];
+
+/// What the app needs to start subsystems after setup
+pub struct StartupContext {
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub storage: SharedStorage,
+}
+
+fn init<R: Runtime>(id: &str, app: &AppHandle<R>, context: &StartupContext) -> Result<(), CommandError> {
+    let (data_dir, storage) = (&context.data_dir, context.storage.clone());
+    match id {
+        "benchmarks" => {
+            app.manage(benchmarks::BenchmarkStore::load(storage, &data_dir.join("benchmarks.json")));
+        }
+        "workers" => {
+            let max_workers = app.state::<SettingsStore>().get().workers.max_workers;
+            app.manage(WorkerPool::new(max_workers, JobJournal::open(storage, &data_dir.join("jobs.json"))));
+            workers::resume_restored(app.clone());
+        }
+        "assets" => {
+            app.manage(assets::AssetState::load(storage, &data_dir.join("asset_index.json")));
+        }
+        "playlists" => {
+            app.manage(playlist::PlaylistStore::load(data_dir.join("playlists.json")));
+        }
+        "palettes" => {
+            app.manage(palettes::PaletteStore::load(data_dir.join("palettes.json")));
+        }
+        "licenses" => {
+            app.manage(licenses::LicenseStore::load(data_dir.join("licenses.json")));
+        }
+        "notes" => {
+            app.manage(notes::NoteStore::load(storage));
+        }
+        "macros" => {
+            app.manage(macros::MacroStore::load(data_dir.join("macros.json")));
+        }
+        "placement" => {
+            app.manage(placement::PlacementStore::load(context.config_dir.join("windows.json")));
+            if let Some(window) = app.get_webview_window("main") {
+                placement::restore(&window);
+            }
+            placement::start_monitor(app.clone());
+        }
+        "power" => power::start_monitor(app.clone()),
+        "quality" => quality::start_monitor(app.clone()),
+        "watchdog" => watchdog::start_monitor(app.clone()),
+        "scratch" => scratch::start_monitor(app.clone()),
+        "features" => features::start(app.clone()),
+        "addon_dev" => addon::apply_dev_settings(app)?,
+        _ => unreachable!("unknown subsystem {id}"),
+    }
+    Ok(())
+}
+
+/// `startup.json` in the config directory, written as each subsystem
+/// starts so a launch that dies leaves behind what it was doing
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct StartupRecord {
+    /// Launches in a row that ended before they counted as started
+    crashes: u32,
+    /// Subsystem being started
+    starting: Option<String>,
+    /// Whether this launch counted as started
+    started: bool,
+    /// Entered after `CRASH_LIMIT` crashes; stays until `leave_safe_mode`
+    safe_mode: bool,
+    /// Subsystem that was starting when the last launch died, if any
+    last_crash: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SubsystemStatus {
+    pub id: String,
+    pub description: String,
+    pub requires: Vec<String>,
+    pub running: bool,
+    /// Why it failed to start in this launch
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StartupReport {
+    pub safe_mode: bool,
+    /// `flag` or `crashes` while in safe mode
+    pub reason: Option<String>,
+    /// Launches in a row that ended during startup
+    pub crashes: u32,
+    /// Subsystem that was starting when the last launch died, if any
+    pub last_crash: Option<String>,
+    pub subsystems: Vec<SubsystemStatus>,
+}
+
+/// How this launch started, and the subsystems started so far
+pub struct StartupState {
+    path: PathBuf,
+    context: StartupContext,
+    record: Mutex<StartupRecord>,
+    reason: Option<&'static str>,
+    /// Outcome of each subsystem started in this launch
+    results: Mutex<HashMap<&'static str, Result<(), String>>>,
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    format!("Panicked: {}", message)
+}
+
+impl StartupState {
+    /// Read what the last launch left behind and decide whether this one
+    /// runs in safe mode
+    pub fn begin(context: StartupContext, safe_mode_flag: bool) -> Self {
+        let path = context.config_dir.join("startup.json");
+        let last: Option<StartupRecord> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok());
+        let mut record = last.clone().unwrap_or_default();
+        match last {
+            Some(last) if !last.started => {
+                record.crashes += 1;
+                record.last_crash = last.starting;
+            }
+            _ => {
+                record.crashes = 0;
+                if !record.safe_mode {
+                    record.last_crash = None;
+                }
+            }
+        }
+        if record.crashes >= CRASH_LIMIT {
+            record.safe_mode = true;
+        }
+        let reason = if safe_mode_flag {
+            Some("flag")
+        } else if record.safe_mode {
+            Some("crashes")
+        } else {
+            None
+        };
+        record.starting = None;
+        record.started = false;
+        if let Some(reason) = reason {
+            eprintln!("Starting in safe mode ({reason}), last crash in {:?}", record.last_crash);
+        }
+        let state = Self {
+            path,
+            context,
+            record: Mutex::new(record),
+            reason,
+            results: Mutex::new(HashMap::new()),
+        };
+        state.save(&state.record.lock().unwrap());
+        state
+    }
+
+    fn save(&self, record: &StartupRecord) {
+        let written = serde_json::to_string_pretty(record)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                fs::create_dir_all(&self.context.config_dir).map_err(|e| e.to_string())?;
+                fs::write(&self.path, text).map_err(|e| e.to_string())
+            });
+        if let Err(err) = written {
+            eprintln!("Failed to write {}: {err}", self.path.display());
+        }
+    }
+
+    fn mark(&self, starting: Option<&str>) {
+        let mut record = self.record.lock().unwrap();
+        record.starting = starting.map(str::to_string);
+        self.save(&record);
+    }
+
+    /// Start a subsystem and the ones it requires, unless already running.
+    /// A panic while starting it is caught and kept as its error.
+    fn start<R: Runtime>(&self, app: &AppHandle<R>, subsystem: &'static Subsystem) {
+        if self.results.lock().unwrap().get(subsystem.id).is_some_and(Result::is_ok) {
+            return;
+        }
+        for required in subsystem.requires {
+            if let Some(required) = SUBSYSTEMS.iter().find(|known| known.id == *required) {
+                self.start(app, required);
+            }
+        }
+        self.mark(Some(subsystem.id));
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| init(subsystem.id, app, &self.context))) {
+            Ok(result) => result.map_err(|err| err.to_string()),
+            Err(panic) => Err(panic_message(panic)),
+        };
+        self.mark(None);
+        if let Err(err) = &result {
+            eprintln!("Failed to start {}: {err}", subsystem.id);
+        }
+        self.results.lock().unwrap().insert(subsystem.id, result);
+    }
+
+    /// Start every subsystem, or none in safe mode
+    pub fn start_all<R: Runtime>(&self, app: &AppHandle<R>) {
+        if self.reason.is_some() {
+            return;
+        }
+        for subsystem in SUBSYSTEMS {
+            self.start(app, subsystem);
+        }
+    }
+
+    fn mark_started(&self) {
+        let mut record = self.record.lock().unwrap();
+        if !record.started {
+            record.started = true;
+            record.crashes = 0;
+            self.save(&record);
+        }
+    }
+
+    fn report(&self) -> StartupReport {
+        let record = self.record.lock().unwrap().clone();
+        let results = self.results.lock().unwrap();
+        StartupReport {
+            safe_mode: self.reason.is_some(),
+            reason: self.reason.map(str::to_string),
+            crashes: record.crashes,
+            last_crash: record.last_crash,
+            subsystems: SUBSYSTEMS
+                .iter()
+                .map(|subsystem| SubsystemStatus {
+                    id: subsystem.id.to_string(),
+                    description: subsystem.description.to_string(),
+                    requires: subsystem.requires.iter().map(|id| id.to_string()).collect(),
+                    running: results.get(subsystem.id).is_some_and(Result::is_ok),
+                    error: results.get(subsystem.id).and_then(|result| result.clone().err()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// State of `subsystem`, or an error when safe mode left it out
+pub fn subsystem<'a, T: Send + Sync + 'static, R: Runtime>(
+    app: &'a AppHandle<R>,
+    subsystem: &str,
+) -> Result<State<'a, T>, CommandError> {
+    app.try_state::<T>().ok_or_else(|| {
+        CommandError::Unsupported(format!("The {} subsystem isn't running; enable it first", subsystem))
+    })
+}
+
+/// Count the launch as started after `STARTED_AFTER`, so the next one
+/// doesn't count it as a crash
+pub fn watch_startup<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTED_AFTER).await;
+        app.state::<StartupState>().mark_started();
+    });
+}
+
+/// Count a clean exit as a started launch, however soon after launch it
+/// came, so quitting right away isn't taken for a crash
+pub fn on_run_event<R: Runtime>(app: &AppHandle<R>, event: RunEvent) {
+    if let RunEvent::Exit = event {
+        if let Some(startup) = app.try_state::<StartupState>() {
+            startup.mark_started();
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_startup_report(startup: State<'_, StartupState>) -> StartupReport {
+    startup.report()
+}
+
+/// Start a subsystem left out by safe mode, with the ones it requires.
+/// Failing to start is reported on the subsystem, not as an error.
+#[tauri::command]
+pub async fn enable_subsystem<R: Runtime>(id: String, app: AppHandle<R>) -> Result<StartupReport, CommandError> {
+    let subsystem = SUBSYSTEMS
+        .iter()
+        .find(|subsystem| subsystem.id == id)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown subsystem '{}'", id)))?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let startup = app.state::<StartupState>();
+        startup.start(&app, subsystem);
+        startup.report()
+    })
+    .await
+    .map_err(|e| CommandError::Failed(format!("Starting {} failed: {}", id, e)))
+}
+
+/// Forget the startup crashes, so the next launch starts everything unless
+/// launched with `--safe-mode`
+#[tauri::command]
+pub fn leave_safe_mode(startup: State<'_, StartupState>) -> StartupReport {
+    {
+        let mut record = startup.record.lock().unwrap();
+        record.safe_mode = false;
+        record.crashes = 0;
+        record.last_crash = None;
+        startup.save(&record);
+    }
+    startup.report()
+}
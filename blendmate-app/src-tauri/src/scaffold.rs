@@ -0,0 +1,481 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use tauri::State;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::errors::CommandError;
+use crate::naming::NamingConvention;
+
+/// Manifest of a template, next to its `files` directory
+const MANIFEST: &str = "template.json";
+
+/// Files copied into every project made from the template
+const FILES_DIR: &str = "files";
+
+/// Written to the root of each scaffolded project
+const PROJECT_FILE: &str = "blendmate.json";
+
+/// Scene settings as a Blender render preset, e.g. `render.resolution_x`
+/// to 1920. Written to `presets/render/<name>.py` in the project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RenderPreset {
+    pub name: String,
+    /// Property paths below `bpy.context.scene` to values
+    pub settings: BTreeMap<String, Value>,
+}
+
+/// How new projects of one kind start
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Folders to create, `/` separated; `{project}` stands for the project
+    /// name, here and in the names of the template's files
+    #[serde(default)]
+    pub folders: Vec<String>,
+    /// .blend among the template's files to start the project from
+    #[serde(default)]
+    pub startup_blend: Option<String>,
+    /// Where the start file goes in the project; `{project}.blend` by default
+    #[serde(default)]
+    pub startup_name: Option<String>,
+    #[serde(default)]
+    pub render_presets: Vec<RenderPreset>,
+    /// Naming convention recorded in the project's `blendmate.json`
+    #[serde(default)]
+    pub naming: Option<NamingConvention>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TemplateSummary {
+    pub template: ProjectTemplate,
+    /// The template's files, relative and `/` separated
+    pub files: Vec<String>,
+}
+
+/// `blendmate.json` of a scaffolded project
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectFile {
+    pub name: String,
+    pub template: String,
+    pub created_at: String,
+    pub startup_blend: Option<String>,
+    pub render_presets: Vec<String>,
+    pub naming: Option<NamingConvention>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ScaffoldReport {
+    pub path: String,
+    /// Everything created, relative to the project and `/` separated
+    pub folders: Vec<String>,
+    pub files: Vec<String>,
+    pub startup_blend: Option<String>,
+}
+
+/// Project templates, one directory each under `templates/` in the app
+/// data directory. A directory holds `template.json` and the `files` to
+/// copy; an exported template is the same as a zip archive.
+pub struct TemplateLibrary {
+    root: PathBuf,
+}
+
+fn io_error(action: &str, path: &Path, e: impl std::fmt::Display) -> CommandError {
+    CommandError::Io(format!("Failed to {} {}: {}", action, path.display(), e))
+}
+
+fn check_name(name: &str) -> Result<(), CommandError> {
+    let valid = !name.trim().is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' ' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(CommandError::InvalidInput(format!("Invalid template name '{}'", name)))
+    }
+}
+
+/// `path` as a relative path that stays inside the directory it is joined
+/// to, or `None`
+fn relative(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let inside = path.components().all(|component| matches!(component, Component::Normal(_)));
+    (inside && path.components().next().is_some()).then(|| path.to_path_buf())
+}
+
+fn slashed(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Files below `dir`, relative to it
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let Ok(entries) = fs::read_dir(dir.join(&relative)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = relative.join(entry.file_name());
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn copy_tree(from: &Path, to: &Path) -> Result<(), CommandError> {
+    for file in list_files(from) {
+        let target = to.join(&file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error("create", parent, e))?;
+        }
+        fs::copy(from.join(&file), &target).map_err(|e| io_error("copy", &from.join(&file), e))?;
+    }
+    Ok(())
+}
+
+/// A JSON value as a Python literal
+fn python_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "None".to_string(),
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Number(number) => number.to_string(),
+        // JSON string escapes are valid in Python strings
+        Value::String(_) => value.to_string(),
+        Value::Array(items) => {
+            let items: String = items.iter().map(|item| python_literal(item) + ", ").collect();
+            format!("({})", items)
+        }
+        Value::Object(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("{}: {}", Value::String(key.clone()), python_literal(value)))
+                .collect();
+            format!("{{{}}}", fields.join(", "))
+        }
+    }
+}
+
+/// A render preset as a Blender preset script
+fn preset_script(preset: &RenderPreset) -> Result<String, CommandError> {
+    let mut script = String::from("import bpy\nscene = bpy.context.scene\n\n");
+    for (path, value) in &preset.settings {
+        let valid = !path.is_empty()
+            && path.split('.').all(|part| {
+                part.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+        if !valid {
+            return Err(CommandError::InvalidInput(format!(
+                "Invalid setting '{}' in render preset '{}'",
+                path, preset.name
+            )));
+        }
+        script.push_str(&format!("scene.{} = {}\n", path, python_literal(value)));
+    }
+    Ok(script)
+}
+
+impl TemplateLibrary {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn dir(&self, name: &str) -> Result<PathBuf, CommandError> {
+        check_name(name)?;
+        Ok(self.root.join(name))
+    }
+
+    fn load(&self, name: &str) -> Result<ProjectTemplate, CommandError> {
+        let path = self.dir(name)?.join(MANIFEST);
+        let text = fs::read_to_string(&path)
+            .map_err(|_| CommandError::NotFound(format!("Unknown project template '{}'", name)))?;
+        serde_json::from_str(&text)
+            .map_err(|e| CommandError::InvalidInput(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    fn summary(&self, template: ProjectTemplate) -> TemplateSummary {
+        let files = list_files(&self.root.join(&template.name).join(FILES_DIR))
+            .iter()
+            .map(|file| slashed(file))
+            .collect();
+        TemplateSummary { template, files }
+    }
+}
+
+fn write_manifest(dir: &Path, template: &ProjectTemplate) -> Result<(), CommandError> {
+    fs::create_dir_all(dir).map_err(|e| io_error("create", dir, e))?;
+    let text = serde_json::to_string_pretty(template).map_err(|e| format!("Failed to serialize template: {}", e))?;
+    let path = dir.join(MANIFEST);
+    fs::write(&path, text).map_err(|e| io_error("write", &path, e))
+}
+
+/// Check what a template refers to before it is saved or used
+fn check_template(template: &ProjectTemplate, files: &[PathBuf]) -> Result<(), CommandError> {
+    check_name(&template.name)?;
+    for folder in &template.folders {
+        if relative(folder).is_none() {
+            return Err(CommandError::InvalidInput(format!("Folder '{}' is not inside the project", folder)));
+        }
+    }
+    if let Some(startup) = &template.startup_blend {
+        if !relative(startup).is_some_and(|startup| files.contains(&startup)) {
+            return Err(CommandError::InvalidInput(format!("'{}' is not one of the template's files", startup)));
+        }
+    }
+    if let Some(name) = &template.startup_name {
+        if relative(name).is_none() {
+            return Err(CommandError::InvalidInput(format!("'{}' is not inside the project", name)));
+        }
+    }
+    for preset in &template.render_presets {
+        check_name(&preset.name)?;
+        preset_script(preset)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_project_templates(library: State<'_, TemplateLibrary>) -> Vec<TemplateSummary> {
+    let Ok(entries) = fs::read_dir(&library.root) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+        .iter()
+        .filter_map(|name| library.load(name).ok())
+        .map(|template| library.summary(template))
+        .collect()
+}
+
+/// Create or replace a template. With `source_dir`, the files of that
+/// directory replace the template's files.
+#[tauri::command]
+pub fn save_project_template(
+    template: ProjectTemplate,
+    source_dir: Option<String>,
+    library: State<'_, TemplateLibrary>,
+) -> Result<TemplateSummary, CommandError> {
+    let dir = library.dir(&template.name)?;
+    let files_dir = dir.join(FILES_DIR);
+    let files = match &source_dir {
+        Some(source) if !Path::new(source).is_dir() => {
+            return Err(CommandError::NotFound(format!("Directory not found: {}", source)));
+        }
+        Some(source) => list_files(Path::new(source)),
+        None => list_files(&files_dir),
+    };
+    check_template(&template, &files)?;
+
+    if let Some(source) = &source_dir {
+        if files_dir.exists() {
+            fs::remove_dir_all(&files_dir).map_err(|e| io_error("remove", &files_dir, e))?;
+        }
+        copy_tree(Path::new(source), &files_dir)?;
+    }
+    write_manifest(&dir, &template)?;
+    Ok(library.summary(template))
+}
+
+#[tauri::command]
+pub fn delete_project_template(name: String, library: State<'_, TemplateLibrary>) -> Result<(), CommandError> {
+    let dir = library.dir(&name)?;
+    if !dir.join(MANIFEST).is_file() {
+        return Err(CommandError::NotFound(format!("Unknown project template '{}'", name)));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| io_error("remove", &dir, e))
+}
+
+/// Write a template and its files to a zip archive to share
+#[tauri::command]
+pub fn export_project_template(
+    name: String,
+    path: String,
+    library: State<'_, TemplateLibrary>,
+) -> Result<(), CommandError> {
+    let template = library.load(&name)?;
+    let dir = library.dir(&name)?;
+    let archive = Path::new(&path);
+    let file = File::create(archive).map_err(|e| io_error("create", archive, e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let failed = |e: zip::result::ZipError| io_error("write", archive, e);
+
+    zip.start_file(MANIFEST, options).map_err(failed)?;
+    let text = serde_json::to_string_pretty(&template).map_err(|e| format!("Failed to serialize template: {}", e))?;
+    zip.write_all(text.as_bytes()).map_err(|e| io_error("write", archive, e))?;
+    for file in list_files(&dir.join(FILES_DIR)) {
+        let source = dir.join(FILES_DIR).join(&file);
+        zip.start_file(format!("{}/{}", FILES_DIR, slashed(&file)), options).map_err(failed)?;
+        let mut reader = File::open(&source).map_err(|e| io_error("read", &source, e))?;
+        io::copy(&mut reader, &mut zip).map_err(|e| io_error("write", archive, e))?;
+    }
+    zip.finish().map_err(failed)?;
+    Ok(())
+}
+
+/// Extract the `files` of a template archive into `staging`; returns them
+/// relative to it. Entries that would land outside are skipped.
+fn unpack(archive: &mut ZipArchive<File>, path: &Path, staging: &Path) -> Result<Vec<PathBuf>, CommandError> {
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| io_error("read", path, e))?;
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let Ok(file) = name.strip_prefix(FILES_DIR).map(Path::to_path_buf) else {
+            continue;
+        };
+        if entry.is_dir() || file.as_os_str().is_empty() {
+            continue;
+        }
+        let target = staging.join(FILES_DIR).join(&file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error("create", parent, e))?;
+        }
+        let mut out = File::create(&target).map_err(|e| io_error("create", &target, e))?;
+        io::copy(&mut entry, &mut out).map_err(|e| io_error("write", &target, e))?;
+        files.push(file);
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Add a template from an archive written by `export_project_template`,
+/// replacing one with the same name
+#[tauri::command]
+pub fn import_project_template(
+    path: String,
+    library: State<'_, TemplateLibrary>,
+) -> Result<TemplateSummary, CommandError> {
+    let archive_path = Path::new(&path);
+    let file = File::open(archive_path).map_err(|e| io_error("open", archive_path, e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| CommandError::InvalidInput(format!("Not a template archive: {}", e)))?;
+    let template: ProjectTemplate = {
+        let manifest = archive
+            .by_name(MANIFEST)
+            .map_err(|_| CommandError::InvalidInput(format!("{} has no {}", path, MANIFEST)))?;
+        serde_json::from_reader(manifest)
+            .map_err(|e| CommandError::InvalidInput(format!("Invalid {} in {}: {}", MANIFEST, path, e)))?
+    };
+    let dir = library.dir(&template.name)?;
+
+    // Unpacked next to the library first, so a broken archive leaves the
+    // existing template alone
+    let staging = library.root.join(format!(".import-{}", template.name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|e| io_error("remove", &staging, e))?;
+    }
+    let unpacked = unpack(&mut archive, archive_path, &staging).and_then(|files| {
+        check_template(&template, &files)?;
+        write_manifest(&staging, &template)
+    });
+    if let Err(error) = unpacked {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(error);
+    }
+
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| io_error("remove", &dir, e))?;
+    }
+    fs::rename(&staging, &dir).map_err(|e| io_error("move", &staging, e))?;
+    Ok(library.summary(template))
+}
+
+/// Scaffold a project at `path` from a template: its folders, its files
+/// with the start .blend, a preset script per render preset, and
+/// `blendmate.json` with the naming convention. `path` must not exist or
+/// be empty; the project is named after its last component.
+#[tauri::command]
+pub fn create_project_from_template(
+    template: String,
+    path: String,
+    library: State<'_, TemplateLibrary>,
+) -> Result<ScaffoldReport, CommandError> {
+    let template = library.load(&template)?;
+    let source = library.dir(&template.name)?.join(FILES_DIR);
+    let root = PathBuf::from(&path);
+    let project = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| CommandError::InvalidInput(format!("'{}' has no project name", path)))?;
+    if fs::read_dir(&root).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(CommandError::InvalidInput(format!("{} is not empty", path)));
+    }
+    let files = list_files(&source);
+    check_template(&template, &files)?;
+    let expand = |text: &str| text.replace("{project}", &project);
+
+    let mut report = ScaffoldReport {
+        path: path.clone(),
+        folders: Vec::new(),
+        files: Vec::new(),
+        startup_blend: None,
+    };
+    fs::create_dir_all(&root).map_err(|e| io_error("create", &root, e))?;
+    for folder in &template.folders {
+        let folder = expand(folder);
+        let target = root.join(relative(&folder).unwrap_or_default());
+        fs::create_dir_all(&target).map_err(|e| io_error("create", &target, e))?;
+        report.folders.push(folder);
+    }
+
+    let startup = template.startup_blend.as_deref().and_then(relative);
+    for file in &files {
+        let name = if Some(file) == startup.as_ref() {
+            let name = expand(template.startup_name.as_deref().unwrap_or("{project}.blend"));
+            report.startup_blend = Some(name.clone());
+            name
+        } else {
+            expand(&slashed(file))
+        };
+        let Some(target) = relative(&name).map(|name| root.join(name)) else {
+            return Err(CommandError::InvalidInput(format!("'{}' is not inside the project", name)));
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error("create", parent, e))?;
+        }
+        fs::copy(source.join(file), &target).map_err(|e| io_error("copy", &source.join(file), e))?;
+        report.files.push(name);
+    }
+
+    for preset in &template.render_presets {
+        let name = format!("presets/render/{}.py", preset.name);
+        let target = root.join(&name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error("create", parent, e))?;
+        }
+        fs::write(&target, preset_script(preset)?).map_err(|e| io_error("write", &target, e))?;
+        report.files.push(name);
+    }
+
+    let project_file = ProjectFile {
+        name: project,
+        template: template.name.clone(),
+        created_at: chrono::Local::now().to_rfc3339(),
+        startup_blend: report.startup_blend.clone(),
+        render_presets: template.render_presets.iter().map(|preset| preset.name.clone()).collect(),
+        naming: template.naming.clone(),
+    };
+    let text = serde_json::to_string_pretty(&project_file).map_err(|e| format!("Failed to serialize project: {}", e))?;
+    fs::write(root.join(PROJECT_FILE), text).map_err(|e| io_error("write", &root.join(PROJECT_FILE), e))?;
+    report.files.push(PROJECT_FILE.to_string());
+    Ok(report)
+}
@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Worker slots handed out by priority, then in request order.
+///
+/// Unlike a semaphore, a high-priority item queued late still runs before
+/// everything already waiting with a lower priority.
+pub struct Scheduler {
+    inner: Mutex<SchedulerInner>,
+}
+
+struct SchedulerInner {
+    limit: usize,
+    running: usize,
+    /// No new slots are handed out while set
+    paused: bool,
+    waiting: BinaryHeap<Waiter>,
+    next_seq: u64,
+}
+
+struct Waiter {
+    priority: i32,
+    seq: u64,
+    wake: oneshot::Sender<Slot>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    /// Higher priority first, then older requests first
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// A running worker's slot; released on drop.
+pub struct Slot {
+    scheduler: Option<Arc<Scheduler>>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler.take() {
+            scheduler.inner.lock().unwrap().running -= 1;
+            scheduler.dispatch();
+        }
+    }
+}
+
+impl Scheduler {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(SchedulerInner {
+                limit: limit.max(1),
+                running: 0,
+                paused: false,
+                waiting: BinaryHeap::new(),
+                next_seq: 0,
+            }),
+        })
+    }
+
+    /// Wait for a free slot. Dropping the future gives up the place in line.
+    pub async fn acquire(self: &Arc<Self>, priority: i32) -> Slot {
+        let wake = {
+            let mut inner = self.inner.lock().unwrap();
+            if !inner.paused && inner.running < inner.limit && inner.waiting.is_empty() {
+                inner.running += 1;
+                return Slot {
+                    scheduler: Some(self.clone()),
+                };
+            }
+            let (wake, woken) = oneshot::channel();
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            inner.waiting.push(Waiter { priority, seq, wake });
+            woken
+        };
+
+        match wake.await {
+            Ok(slot) => slot,
+            // The scheduler owns every sender, so this never resolves
+            Err(_) => std::future::pending().await,
+        }
+    }
+
+    /// Change the number of slots. Lowering it takes effect as running
+    /// workers finish.
+    pub fn set_limit(self: &Arc<Self>, limit: usize) {
+        self.inner.lock().unwrap().limit = limit.max(1);
+        self.dispatch();
+    }
+
+    /// Stop or resume handing out slots. Running workers keep their slots.
+    pub fn set_paused(self: &Arc<Self>, paused: bool) {
+        self.inner.lock().unwrap().paused = paused;
+        self.dispatch();
+    }
+
+    /// Hand free slots to the highest-priority waiters
+    fn dispatch(self: &Arc<Self>) {
+        loop {
+            let waiter = {
+                let mut inner = self.inner.lock().unwrap();
+                if inner.paused || inner.running >= inner.limit {
+                    return;
+                }
+                let Some(waiter) = inner.waiting.pop() else {
+                    return;
+                };
+                inner.running += 1;
+                waiter
+            };
+
+            let slot = Slot {
+                scheduler: Some(self.clone()),
+            };
+            if let Err(mut slot) = waiter.wake.send(slot) {
+                // The waiter gave up; take the slot back without re-entering dispatch
+                slot.scheduler = None;
+                self.inner.lock().unwrap().running -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waiters_pop_by_priority_then_request_order() {
+        let mut heap = BinaryHeap::new();
+        for (priority, seq) in [(0, 0), (5, 1), (0, 2), (5, 3)] {
+            let (wake, _) = oneshot::channel();
+            heap.push(Waiter { priority, seq, wake });
+        }
+        let order: Vec<(i32, u64)> = std::iter::from_fn(|| heap.pop().map(|w| (w.priority, w.seq))).collect();
+        assert_eq!(order, [(5, 1), (5, 3), (0, 0), (0, 2)]);
+    }
+
+    #[tokio::test]
+    async fn high_priority_runs_before_earlier_low_priority() {
+        let scheduler = Scheduler::new(1);
+        let running = scheduler.acquire(0).await;
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for (name, priority) in [("low", 0), ("high", 5), ("later low", 0)] {
+            let (scheduler, order) = (scheduler.clone(), order.clone());
+            tasks.push(tokio::spawn(async move {
+                let _slot = scheduler.acquire(priority).await;
+                order.lock().unwrap().push(name);
+            }));
+            // Let the task take its place in line before the next one
+            tokio::task::yield_now().await;
+        }
+
+        drop(running);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), ["high", "low", "later low"]);
+    }
+
+    #[tokio::test]
+    async fn paused_scheduler_hands_out_no_slots() {
+        let scheduler = Scheduler::new(1);
+        scheduler.set_paused(true);
+        let waiting = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { drop(scheduler.acquire(0).await) }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        scheduler.set_paused(false);
+        waiting.await.unwrap();
+    }
+}
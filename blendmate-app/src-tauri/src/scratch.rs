@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+const MIB: u64 = 1024 * 1024;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScratchSettings {
+    /// Most a session's directory may hold, in MiB; the oldest files are
+    /// removed beyond it
+    pub session_quota_mb: u64,
+    /// Most a job's directory may hold, in MiB, before `scratch:over_quota`
+    pub job_quota_mb: u64,
+}
+
+impl Default for ScratchSettings {
+    fn default() -> Self {
+        Self {
+            session_quota_mb: 1024,
+            job_quota_mb: 4096,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ScratchUsage {
+    /// `session` or a job id
+    pub owner: String,
+    pub path: String,
+    pub bytes: u64,
+    pub quota_bytes: u64,
+}
+
+/// Temporary directories owned by the Blender session and by each running
+/// job, under `scratch` in the app cache directory. A session's directory
+/// is removed when Blender disconnects and a job's when the job ends;
+/// whatever a crash left behind is removed on the next start.
+pub struct ScratchDirs {
+    root: PathBuf,
+    session: Mutex<Option<PathBuf>>,
+}
+
+impl ScratchDirs {
+    pub fn new(root: PathBuf) -> Self {
+        if root.exists() {
+            if let Err(err) = fs::remove_dir_all(&root) {
+                eprintln!("Failed to clear {}: {err}", root.display());
+            }
+        }
+        Self {
+            root,
+            session: Mutex::new(None),
+        }
+    }
+
+    /// Start a session directory, removing the previous session's
+    pub fn open_session(&self) -> Option<PathBuf> {
+        self.close_session();
+        let dir = self
+            .root
+            .join(format!("session-{}", chrono::Local::now().format("%Y%m%d-%H%M%S%.3f")));
+        if let Err(err) = fs::create_dir_all(&dir) {
+            eprintln!("Failed to create {}: {err}", dir.display());
+            return None;
+        }
+        *self.session.lock().unwrap() = Some(dir.clone());
+        Some(dir)
+    }
+
+    pub fn close_session(&self) {
+        if let Some(dir) = self.session.lock().unwrap().take() {
+            remove(&dir);
+        }
+    }
+
+    pub fn session_dir(&self) -> Option<PathBuf> {
+        self.session.lock().unwrap().clone()
+    }
+
+    /// The job's directory, created on first use
+    pub fn job_dir(&self, id: &str) -> Option<PathBuf> {
+        let dir = self.root.join(id);
+        match fs::create_dir_all(&dir) {
+            Ok(()) => Some(dir),
+            Err(err) => {
+                eprintln!("Failed to create {}: {err}", dir.display());
+                None
+            }
+        }
+    }
+
+    pub fn remove_job(&self, id: &str) {
+        remove(&self.root.join(id));
+    }
+
+    /// Size of every directory, session first
+    pub fn usage(&self, settings: &ScratchSettings) -> Vec<ScratchUsage> {
+        let session = self.session_dir();
+        let mut usage: Vec<ScratchUsage> = fs::read_dir(&self.root)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .map(|path| {
+                let is_session = session.as_ref() == Some(&path);
+                let quota_mb = if is_session { settings.session_quota_mb } else { settings.job_quota_mb };
+                ScratchUsage {
+                    owner: if is_session {
+                        "session".to_string()
+                    } else {
+                        path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+                    },
+                    bytes: dir_usage(&path),
+                    quota_bytes: quota_mb * MIB,
+                    path: path.to_string_lossy().into_owned(),
+                }
+            })
+            .collect();
+        usage.sort_by_key(|entry| entry.owner != "session");
+        usage
+    }
+}
+
+fn remove(dir: &Path) {
+    if dir.exists() {
+        if let Err(err) = fs::remove_dir_all(dir) {
+            eprintln!("Failed to remove {}: {err}", dir.display());
+        }
+    }
+}
+
+/// Files below `dir` with their size and modification time
+fn files(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((entry.path(), metadata.len(), modified));
+            }
+        }
+    }
+    files
+}
+
+fn dir_usage(dir: &Path) -> u64 {
+    files(dir).iter().map(|(_, size, _)| size).sum()
+}
+
+/// Remove the oldest files until `dir` is within `quota`
+fn evict(dir: &Path, quota: u64) {
+    let mut files = files(dir);
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    files.sort_by_key(|(_, _, modified)| *modified);
+    for (path, size, _) in files {
+        if total <= quota {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+/// Create the session directory and tell the add-on to keep its temporary
+/// files there. A remote Blender can't see the directory and keeps its own.
+pub fn open_session<R: Runtime>(app: &AppHandle<R>) {
+    let Some(dir) = app.state::<ScratchDirs>().open_session() else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        // Not retried on failure; older add-ons don't have the command
+        let params = json!({ "path": dir });
+        if let Err(e) = state.bridge.request("session.set_temp_dir", "", params).await {
+            eprintln!("Failed to set session temp directory: {e}");
+        }
+    });
+}
+
+/// Check directory sizes every 30 seconds. Sessions over their quota lose
+/// their oldest files. Job directories are in use by a running Blender,
+/// so one over its quota only emits `scratch:over_quota`.
+pub fn start_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let config = app.state::<SettingsStore>().get().scratch;
+            let handle = app.clone();
+            let over = tauri::async_runtime::spawn_blocking(move || {
+                let mut over = Vec::new();
+                for usage in handle.state::<ScratchDirs>().usage(&config) {
+                    if usage.bytes <= usage.quota_bytes {
+                        continue;
+                    }
+                    if usage.owner == "session" {
+                        evict(Path::new(&usage.path), usage.quota_bytes);
+                    } else {
+                        over.push(usage);
+                    }
+                }
+                over
+            })
+            .await
+            .unwrap_or_default();
+            for usage in over {
+                if let Err(err) = app.emit("scratch:over_quota", &usage) {
+                    eprintln!("Failed to emit scratch:over_quota: {err}");
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_scratch_usage(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<ScratchUsage>, CommandError> {
+    let config = settings.get().scratch;
+    tauri::async_runtime::spawn_blocking(move || app.state::<ScratchDirs>().usage(&config))
+        .await
+        .map_err(|e| CommandError::Io(format!("Failed to measure temp directories: {}", e)))
+}
+
+#[tauri::command]
+pub fn get_scratch_settings(settings: State<'_, SettingsStore>) -> ScratchSettings {
+    settings.get().scratch
+}
+
+#[tauri::command]
+pub fn set_scratch_settings(scratch: ScratchSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.scratch = scratch)?;
+    Ok(())
+}
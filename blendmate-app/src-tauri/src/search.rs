@@ -0,0 +1,497 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::protocol;
+use crate::AppState;
+
+/// Hits returned when a search doesn't set a limit
+const DEFAULT_LIMIT: usize = 200;
+
+/// Narrows a search. Values within one list are alternatives; every list
+/// that is set must match.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct SearchFilters {
+    /// Blender object types, e.g. `MESH` or `LIGHT`
+    pub types: Vec<String>,
+    /// Objects with any of these materials in a slot
+    pub materials: Vec<String>,
+    /// Objects in any of these collections, directly or through a child
+    /// collection
+    pub collections: Vec<String>,
+    /// Custom properties the object must have
+    pub properties: Vec<PropertyFilter>,
+    /// Comparisons on fields of the object, all of which must hold
+    pub conditions: Vec<FieldCondition>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PropertyFilter {
+    pub key: String,
+    /// Required value; any value matches when unset
+    pub value: Option<Value>,
+}
+
+/// Compares a field of the object as `get_scene` reports it, e.g.
+/// `light.energy` greater than 1000 or `mesh.has_uv` equal to false.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FieldCondition {
+    /// Dotted path into the object
+    pub field: String,
+    pub op: CompareOp,
+    pub value: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl FieldCondition {
+    /// Objects without the field never match
+    fn holds(&self, object: &Value) -> bool {
+        let Some(actual) = self.field.split('.').try_fold(object, |value, key| value.get(key)) else {
+            return false;
+        };
+        let ordering = match (actual, &self.value) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (a, b) => (a == b).then_some(Ordering::Equal),
+        };
+        match self.op {
+            CompareOp::Eq => ordering == Some(Ordering::Equal),
+            CompareOp::Ne => ordering != Some(Ordering::Equal),
+            CompareOp::Gt => ordering == Some(Ordering::Greater),
+            CompareOp::Gte => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            CompareOp::Lt => ordering == Some(Ordering::Less),
+            CompareOp::Lte => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchHit {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub object_type: String,
+    /// Collections the object is linked to directly
+    pub collections: Vec<String>,
+    pub materials: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SearchResult {
+    /// Matches before `limit` was applied
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+    /// Searched the last scene snapshot because Blender is not connected
+    pub stale: bool,
+}
+
+/// One object as the indexes see it
+struct ObjectEntry {
+    name: String,
+    lower: String,
+    object_type: String,
+    materials: Vec<String>,
+    collections: Vec<String>,
+    /// `collections` plus every collection above them
+    within: Vec<String>,
+    properties: Map<String, Value>,
+}
+
+impl ObjectEntry {
+    fn new(name: &str, object: &Value, membership: Option<&Membership>) -> Self {
+        let materials = object
+            .get("materials")
+            .and_then(Value::as_array)
+            .map(|slots| slots.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default();
+        let (collections, within) = membership
+            .map(|m| (m.direct.clone(), m.within.clone()))
+            .unwrap_or_default();
+        Self {
+            name: name.to_string(),
+            lower: name.to_lowercase(),
+            object_type: object.get("type").and_then(Value::as_str).unwrap_or_default().to_string(),
+            materials,
+            collections,
+            within,
+            properties: object
+                .get("custom_properties")
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Check every condition, whatever index picked the candidate.
+    /// `object` is the object's entry in the indexed snapshot.
+    fn matches(&self, query: &str, filters: &SearchFilters, object: Option<&Value>) -> bool {
+        (query.is_empty() || self.lower.contains(query))
+            && (filters.types.is_empty()
+                || filters.types.iter().any(|t| t.eq_ignore_ascii_case(&self.object_type)))
+            && (filters.materials.is_empty() || filters.materials.iter().any(|m| self.materials.contains(m)))
+            && (filters.collections.is_empty() || filters.collections.iter().any(|c| self.within.contains(c)))
+            && filters.properties.iter().all(|filter| match (self.properties.get(&filter.key), &filter.value) {
+                (Some(_), None) => true,
+                (Some(value), Some(expected)) => value == expected,
+                (None, _) => false,
+            })
+            && (filters.conditions.is_empty()
+                || object.is_some_and(|object| filters.conditions.iter().all(|c| c.holds(object))))
+    }
+
+    fn hit(&self) -> SearchHit {
+        SearchHit {
+            name: self.name.clone(),
+            object_type: self.object_type.clone(),
+            collections: self.collections.clone(),
+            materials: self.materials.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Membership {
+    direct: Vec<String>,
+    within: Vec<String>,
+}
+
+/// Objects of a mirrored scene with lookup tables for each kind of filter
+#[derive(Default)]
+struct SceneIndex {
+    /// Snapshot the index was built from
+    source: Option<Arc<Value>>,
+    file_generation: u64,
+    objects: HashMap<String, ObjectEntry>,
+    /// Lowercase three-character windows of object names
+    trigrams: HashMap<[char; 3], HashSet<String>>,
+    by_type: HashMap<String, HashSet<String>>,
+    by_material: HashMap<String, HashSet<String>>,
+    by_collection: HashMap<String, HashSet<String>>,
+    by_property: HashMap<String, HashSet<String>>,
+}
+
+impl SceneIndex {
+    /// Bring the index up to date with `scene`. `changed` names the
+    /// objects edited since the last update; `None` rebuilds everything.
+    fn update(&mut self, scene: &Value, changed: Option<HashSet<String>>) {
+        let empty = Map::new();
+        let objects = scene.get("objects").and_then(Value::as_object).unwrap_or(&empty);
+        let mut membership = HashMap::new();
+        if let Some(root) = scene.get("collections") {
+            collect_membership(root, &mut Vec::new(), &mut membership);
+        }
+
+        let stale: Vec<String> = match changed {
+            None => {
+                *self = SceneIndex::default();
+                objects.keys().cloned().collect()
+            }
+            Some(changed) => {
+                let removed: Vec<String> = self
+                    .objects
+                    .keys()
+                    .filter(|name| !objects.contains_key(*name))
+                    .cloned()
+                    .collect();
+                for name in removed {
+                    self.remove(&name);
+                }
+                // Moving an object between collections doesn't always
+                // come with a depsgraph update for it
+                objects
+                    .keys()
+                    .filter(|name| {
+                        changed.contains(*name)
+                            || self.objects.get(*name).is_none_or(|entry| {
+                                let current = membership.get(*name).cloned().unwrap_or_default();
+                                entry.collections != current.direct || entry.within != current.within
+                            })
+                    })
+                    .cloned()
+                    .collect()
+            }
+        };
+
+        for name in stale {
+            self.remove(&name);
+            if let Some(object) = objects.get(&name) {
+                self.insert(ObjectEntry::new(&name, object, membership.get(&name)));
+            }
+        }
+    }
+
+    fn insert(&mut self, entry: ObjectEntry) {
+        let name = &entry.name;
+        for trigram in trigrams(&entry.lower) {
+            self.trigrams.entry(trigram).or_default().insert(name.clone());
+        }
+        self.by_type
+            .entry(entry.object_type.to_uppercase())
+            .or_default()
+            .insert(name.clone());
+        for material in &entry.materials {
+            self.by_material.entry(material.clone()).or_default().insert(name.clone());
+        }
+        for collection in &entry.within {
+            self.by_collection.entry(collection.clone()).or_default().insert(name.clone());
+        }
+        for key in entry.properties.keys() {
+            self.by_property.entry(key.clone()).or_default().insert(name.clone());
+        }
+        self.objects.insert(name.clone(), entry);
+    }
+
+    fn remove(&mut self, name: &str) {
+        let Some(entry) = self.objects.remove(name) else {
+            return;
+        };
+        for trigram in trigrams(&entry.lower) {
+            unlink(&mut self.trigrams, &trigram, name);
+        }
+        unlink(&mut self.by_type, &entry.object_type.to_uppercase(), name);
+        for material in &entry.materials {
+            unlink(&mut self.by_material, material, name);
+        }
+        for collection in &entry.within {
+            unlink(&mut self.by_collection, collection, name);
+        }
+        for key in entry.properties.keys() {
+            unlink(&mut self.by_property, key, name);
+        }
+    }
+
+    fn search(&self, query: &str, filters: &SearchFilters) -> SearchResult {
+        let matches = self.matching(query, filters);
+        let total = matches.len();
+        let hits = matches
+            .into_iter()
+            .take(filters.limit.unwrap_or(DEFAULT_LIMIT))
+            .map(ObjectEntry::hit)
+            .collect();
+        SearchResult {
+            total,
+            hits,
+            stale: false,
+        }
+    }
+
+    /// Every object matching, best first. `limit` is not applied.
+    fn matching(&self, query: &str, filters: &SearchFilters) -> Vec<&ObjectEntry> {
+        let query = query.trim().to_lowercase();
+        let snapshot = self.source.as_ref().and_then(|source| source.get("objects"));
+
+        // Start from the smallest candidate set any index gives, then check
+        // every condition on those objects only
+        let mut pools: Vec<HashSet<&str>> = Vec::new();
+        let query_trigrams = trigrams(&query);
+        if !query_trigrams.is_empty() {
+            let smallest = query_trigrams
+                .iter()
+                .map(|trigram| self.trigrams.get(trigram))
+                .min_by_key(|names| names.map_or(0, HashSet::len));
+            pools.push(names(smallest.flatten().into_iter()));
+        }
+        if !filters.types.is_empty() {
+            pools.push(names(filters.types.iter().filter_map(|t| self.by_type.get(&t.to_uppercase()))));
+        }
+        if !filters.materials.is_empty() {
+            pools.push(names(filters.materials.iter().filter_map(|m| self.by_material.get(m))));
+        }
+        if !filters.collections.is_empty() {
+            pools.push(names(filters.collections.iter().filter_map(|c| self.by_collection.get(c))));
+        }
+        for filter in &filters.properties {
+            pools.push(names(self.by_property.get(&filter.key).into_iter()));
+        }
+
+        let candidates: Vec<&ObjectEntry> = match pools.into_iter().min_by_key(HashSet::len) {
+            Some(pool) => pool.into_iter().filter_map(|name| self.objects.get(name)).collect(),
+            None => self.objects.values().collect(),
+        };
+        let mut matches: Vec<&ObjectEntry> = candidates
+            .into_iter()
+            .filter(|entry| entry.matches(&query, filters, snapshot.and_then(|objects| objects.get(&entry.name))))
+            .collect();
+
+        // Names starting with the query first, then alphabetical
+        matches.sort_by(|a, b| {
+            let a_prefix = !a.lower.starts_with(&query);
+            let b_prefix = !b.lower.starts_with(&query);
+            (a_prefix, &a.lower).cmp(&(b_prefix, &b.lower))
+        });
+        matches
+    }
+}
+
+/// Union of the name sets of an index
+fn names<'a>(sets: impl Iterator<Item = &'a HashSet<String>>) -> HashSet<&'a str> {
+    sets.flatten().map(String::as_str).collect()
+}
+
+/// Drop `name` from one index entry, and the entry once it is empty
+fn unlink<K, Q>(index: &mut HashMap<K, HashSet<String>>, key: &Q, name: &str)
+where
+    K: Borrow<Q> + Hash + Eq,
+    Q: Hash + Eq + ?Sized,
+{
+    if let Some(names) = index.get_mut(key) {
+        names.remove(name);
+        if names.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+fn trigrams(text: &str) -> Vec<[char; 3]> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// Walk the collection tree from `get_scene`, recording for each object
+/// the collections it is linked to and every collection above those
+fn collect_membership(collection: &Value, path: &mut Vec<String>, membership: &mut HashMap<String, Membership>) {
+    let Some(name) = collection.get("name").and_then(Value::as_str) else {
+        return;
+    };
+    path.push(name.to_string());
+    for object in collection.get("objects").and_then(Value::as_array).into_iter().flatten() {
+        let Some(object) = object.as_str() else {
+            continue;
+        };
+        let entry = membership.entry(object.to_string()).or_default();
+        if !entry.direct.iter().any(|c| c == name) {
+            entry.direct.push(name.to_string());
+        }
+        for parent in path.iter() {
+            if !entry.within.contains(parent) {
+                entry.within.push(parent.clone());
+            }
+        }
+    }
+    for child in collection.get("children").and_then(Value::as_array).into_iter().flatten() {
+        collect_membership(child, path, membership);
+    }
+    path.pop();
+}
+
+#[derive(Default)]
+struct Changes {
+    objects: HashSet<String>,
+    /// An update didn't say which objects it touched
+    unknown: bool,
+}
+
+/// Search over the scene mirror. The indexes follow the mirror lazily:
+/// depsgraph events record which objects changed, and the next search
+/// re-indexes only those once the mirror has a new snapshot.
+#[derive(Default)]
+pub struct SceneSearch {
+    index: Mutex<SceneIndex>,
+    changes: Mutex<Changes>,
+}
+
+impl SceneSearch {
+    /// Feed every incoming add-on message through the search.
+    pub fn observe(&self, message: &Value) {
+        if protocol::message_type(message).as_deref() != Some("event.depsgraph.updated") {
+            return;
+        }
+        let body = protocol::message_body(message);
+        let ids = body
+            .get("changed_object_ids")
+            .or_else(|| body.get("changed_objects"))
+            .and_then(Value::as_array);
+        let mut changes = self.changes.lock().unwrap();
+        match ids {
+            Some(ids) => changes
+                .objects
+                .extend(ids.iter().filter_map(Value::as_str).map(str::to_string)),
+            None => changes.unknown = true,
+        }
+    }
+
+    pub async fn search(&self, state: &AppState, query: &str, filters: &SearchFilters) -> Result<SearchResult, CommandError> {
+        let (mut result, stale) = self.with_index(state, |index| index.search(query, filters)).await?;
+        result.stale = stale;
+        Ok(result)
+    }
+
+    /// Names of every matching object, ignoring `limit`
+    pub async fn members(&self, state: &AppState, query: &str, filters: &SearchFilters) -> Result<Vec<String>, CommandError> {
+        self.with_index(state, |index| {
+            index
+                .matching(query, filters)
+                .into_iter()
+                .map(|entry| entry.name.clone())
+                .collect()
+        })
+        .await
+        .map(|(members, _)| members)
+    }
+
+    /// Run `read` on indexes that match the current mirror snapshot, or the
+    /// last one while Blender is away, which is reported with `true`
+    async fn with_index<T>(
+        &self,
+        state: &AppState,
+        read: impl FnOnce(&SceneIndex) -> T,
+    ) -> Result<(T, bool), CommandError> {
+        // Taken before fetching, so edits that land meanwhile are kept for
+        // the next snapshot
+        let changes = std::mem::take(&mut *self.changes.lock().unwrap());
+        let file_generation = state.mirror.file_generation().await;
+        let (scene, stale) = match state.mirror.scene_or_cached(&state.bridge).await {
+            Ok(found) => found,
+            Err(error) => {
+                self.requeue(changes);
+                return Err(error);
+            }
+        };
+
+        let mut index = self.index.lock().unwrap();
+        if index.source.as_ref().is_some_and(|source| Arc::ptr_eq(source, &scene)) {
+            self.requeue(changes);
+        } else {
+            let incremental = index.source.is_some() && index.file_generation == file_generation && !changes.unknown;
+            index.update(&scene, incremental.then_some(changes.objects));
+            index.source = Some(scene);
+            index.file_generation = file_generation;
+        }
+        Ok((read(&index), stale))
+    }
+
+    fn requeue(&self, changes: Changes) {
+        let mut pending = self.changes.lock().unwrap();
+        pending.objects.extend(changes.objects);
+        pending.unknown |= changes.unknown;
+    }
+}
+
+/// Search mirrored scene objects by name (case-insensitive substring) and
+/// filters. Fast enough to call on every keystroke.
+#[tauri::command]
+pub async fn search_scene(
+    query: String,
+    filters: Option<SearchFilters>,
+    state: State<'_, AppState>,
+    search: State<'_, SceneSearch>,
+) -> Result<SearchResult, CommandError> {
+    search.search(&state, &query, &filters.unwrap_or_default()).await
+}
@@ -0,0 +1,56 @@
+use keyring::Entry;
+
+use crate::errors::CommandError;
+
+/// Keyring service every secret is stored under
+const SERVICE: &str = "blendmate";
+
+/// Secrets the frontend may set; anything else is refused
+const KNOWN: &[&str] = &["kitsu.password", "flamenco.token", "storage.postgres_password", "sync.key"];
+
+fn entry(name: &str) -> Result<Entry, CommandError> {
+    Entry::new(SERVICE, name).map_err(|e| CommandError::Failed(format!("Keyring unavailable: {}", e)))
+}
+
+/// Read a secret from the OS keyring; `None` when it was never set
+pub fn get(name: &str) -> Result<Option<String>, CommandError> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(CommandError::Failed(format!("Failed to read secret '{}': {}", name, e))),
+    }
+}
+
+fn check(name: &str) -> Result<(), CommandError> {
+    if KNOWN.contains(&name) {
+        Ok(())
+    } else {
+        Err(CommandError::InvalidInput(format!("Unknown secret '{}'", name)))
+    }
+}
+
+/// Store a credential in the OS keyring. Secrets never go to settings.json
+/// and can't be read back by the frontend.
+#[tauri::command]
+pub fn set_secret(name: String, value: String) -> Result<(), CommandError> {
+    check(&name)?;
+    entry(&name)?
+        .set_password(&value)
+        .map_err(|e| CommandError::Failed(format!("Failed to store secret '{}': {}", name, e)))
+}
+
+#[tauri::command]
+pub fn delete_secret(name: String) -> Result<(), CommandError> {
+    check(&name)?;
+    match entry(&name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(CommandError::Failed(format!("Failed to delete secret '{}': {}", name, e))),
+    }
+}
+
+/// Whether a secret is set, without revealing it
+#[tauri::command]
+pub fn has_secret(name: String) -> Result<bool, CommandError> {
+    check(&name)?;
+    Ok(get(&name)?.is_some())
+}
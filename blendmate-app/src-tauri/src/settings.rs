@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::a11y::A11ySettings;
+use crate::addon::AddonDevSettings;
+use crate::colormgmt::ColorStandard;
+use crate::devices::DeviceProfile;
+use crate::diskguard::DiskGuardSettings;
+use crate::editor::EditorSettings;
+use crate::errors::CommandError;
+use crate::features::FeatureFlags;
+use crate::ffmpeg::FfmpegSettings;
+use crate::health::HealthSettings;
+use crate::licenses::LicenseSettings;
+use crate::listeners::Listeners;
+use crate::macros::MacroSettings;
+use crate::naming::NamingConvention;
+use crate::network::NetworkSettings;
+use crate::power::PowerSettings;
+use crate::projects::ProjectSettings;
+use crate::scratch::ScratchSettings;
+use crate::shares::ShareSettings;
+use crate::smart::SmartCollection;
+use crate::storage::StorageSettings;
+use crate::sync::SyncSettings;
+use crate::tagging::TaggingSettings;
+use crate::timelapse::TimelapseSettings;
+use crate::timesync::TimeSyncSettings;
+use crate::trackers::TrackerSettings;
+use crate::validation::ValidationProfile;
+use crate::workers::WorkerSettings;
+
+/// Backend settings persisted as `settings.json` in the app config directory.
+///
+/// Every section has a default so older files keep loading as new
+/// sections are added.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub validation_profiles: Vec<ValidationProfile>,
+    pub naming: NamingConvention,
+    pub workers: WorkerSettings,
+    pub device_profiles: Vec<DeviceProfile>,
+    pub color_standard: ColorStandard,
+    pub power: PowerSettings,
+    pub network: NetworkSettings,
+    pub listeners: Listeners,
+    pub addon_dev: AddonDevSettings,
+    pub smart_collections: Vec<SmartCollection>,
+    pub projects: ProjectSettings,
+    pub trackers: TrackerSettings,
+    pub ffmpeg: FfmpegSettings,
+    pub timelapse: TimelapseSettings,
+    pub licenses: LicenseSettings,
+    pub disk_guard: DiskGuardSettings,
+    pub shares: ShareSettings,
+    pub scratch: ScratchSettings,
+    pub macros: MacroSettings,
+    /// Locale of backend messages, e.g. `cs`; the system's when unset
+    pub locale: Option<String>,
+    pub a11y: A11ySettings,
+    /// Read at startup only
+    pub storage: StorageSettings,
+    pub health: HealthSettings,
+    pub tagging: TaggingSettings,
+    pub editor: EditorSettings,
+    pub sync: SyncSettings,
+    pub time_sync: TimeSyncSettings,
+    /// Background subsystems switched on or off; see `features.rs`
+    pub features: FeatureFlags,
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, falling back to defaults when the file is
+    /// missing or unreadable.
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                eprintln!("Failed to parse settings {}: {err}", path.display());
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+
+        Self {
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Apply `change` and write the result to disk.
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, CommandError> {
+        let mut settings = self.settings.lock().unwrap();
+        change(&mut settings);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| CommandError::Io(format!("Failed to create settings directory: {}", e)))?;
+        }
+        let text = serde_json::to_string_pretty(&*settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        fs::write(&self.path, text).map_err(|e| CommandError::Io(format!("Failed to write settings: {}", e)))?;
+
+        Ok(settings.clone())
+    }
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::a11y::{self, Politeness};
+use crate::errors::CommandError;
+use crate::i18n;
+use crate::projects;
+use crate::settings::SettingsStore;
+use crate::workers::WorkerPool;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ShareSettings {
+    /// Check the active project's shares and hold jobs while one is gone
+    pub enabled: bool,
+    pub check_interval_secs: u64,
+    /// A share that doesn't list within this long is unreachable. Hung
+    /// network mounts block instead of failing.
+    pub timeout_secs: u64,
+    /// Listing slower than this marks a share slow
+    pub slow_ms: u64,
+}
+
+impl Default for ShareSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: 30,
+            timeout_secs: 5,
+            slow_ms: 500,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareState {
+    Available,
+    Slow,
+    Unreachable,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ShareStatus {
+    pub path: String,
+    pub state: ShareState,
+    /// Time to list the share's first entry
+    pub latency_ms: Option<f64>,
+    pub error: Option<String>,
+    /// When the share entered this state
+    pub since: String,
+}
+
+/// Reachability of the active project's shares as of the last check
+#[derive(Default)]
+pub struct ShareMonitor {
+    shares: Mutex<Vec<ShareStatus>>,
+    /// Shares whose listing hasn't returned yet, so a hung mount doesn't
+    /// pile up blocked threads
+    pending: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ShareMonitor {
+    pub fn current(&self) -> Vec<ShareStatus> {
+        self.shares.lock().unwrap().clone()
+    }
+
+    /// List the first entry of `path` within the timeout
+    async fn probe(&self, config: &ShareSettings, path: &str) -> (ShareState, Option<f64>, Option<String>) {
+        if !self.pending.lock().unwrap().insert(path.to_string()) {
+            return (ShareState::Unreachable, None, Some(i18n::t("share-still-waiting", &[])));
+        }
+        let started = Instant::now();
+        let pending = self.pending.clone();
+        let owned = path.to_string();
+        let listing = tauri::async_runtime::spawn_blocking(move || {
+            let first = fs::read_dir(&owned).map(|mut entries| entries.next().is_some());
+            pending.lock().unwrap().remove(&owned);
+            first
+        });
+        let timeout = Duration::from_secs(config.timeout_secs.max(1));
+        match tokio::time::timeout(timeout, listing).await {
+            Err(_) => (
+                ShareState::Unreachable,
+                None,
+                Some(i18n::t("share-no-response", &[("secs", timeout.as_secs().into())])),
+            ),
+            Ok(Err(e)) => (ShareState::Unreachable, None, Some(e.to_string())),
+            Ok(Ok(Err(e))) => (ShareState::Unreachable, None, Some(e.to_string())),
+            // The mount point of an unmounted share is usually left empty
+            Ok(Ok(Ok(false))) => (ShareState::Unreachable, None, Some(i18n::t("share-empty", &[]))),
+            Ok(Ok(Ok(true))) => {
+                let latency = started.elapsed().as_secs_f64() * 1000.0;
+                let state = if latency > config.slow_ms as f64 {
+                    ShareState::Slow
+                } else {
+                    ShareState::Available
+                };
+                (state, Some(latency), None)
+            }
+        }
+    }
+
+    /// Probe every share; returns the new statuses and those that became
+    /// reachable or unreachable
+    async fn check(&self, config: &ShareSettings, paths: Vec<String>) -> (Vec<ShareStatus>, Vec<ShareStatus>) {
+        let previous = self.current();
+        let mut statuses = Vec::new();
+        let mut changed = Vec::new();
+        for path in paths {
+            let (state, latency_ms, error) = self.probe(config, &path).await;
+            let before = previous.iter().find(|status| status.path == path);
+            let since = match before {
+                Some(before) if before.state == state => before.since.clone(),
+                _ => chrono::Local::now().to_rfc3339(),
+            };
+            let status = ShareStatus {
+                path,
+                state,
+                latency_ms,
+                error,
+                since,
+            };
+            let was_unreachable = before.is_some_and(|before| before.state == ShareState::Unreachable);
+            if was_unreachable != (state == ShareState::Unreachable) {
+                changed.push(status.clone());
+            }
+            statuses.push(status);
+        }
+        *self.shares.lock().unwrap() = statuses.clone();
+        (statuses, changed)
+    }
+}
+
+fn required(settings: &SettingsStore) -> Vec<String> {
+    projects::active(settings)
+        .map(|project| project.required_shares())
+        .unwrap_or_default()
+}
+
+fn emit<R: Runtime>(app: &AppHandle<R>, event: &str, status: &ShareStatus) {
+    if let Err(err) = app.emit(event, status) {
+        eprintln!("Failed to emit {event}: {err}");
+    }
+}
+
+/// Refuse to queue a job while a share the active project needs is
+/// unreachable; checks the shares now rather than trusting the last check
+pub async fn require_reachable(settings: &SettingsStore, monitor: &ShareMonitor) -> Result<(), CommandError> {
+    let config = settings.get().shares;
+    if !config.enabled {
+        return Ok(());
+    }
+    let mut missing = Vec::new();
+    for path in required(settings) {
+        if let (ShareState::Unreachable, _, error) = monitor.probe(&config, &path).await {
+            missing.push(format!("{} ({})", path, error.unwrap_or_default()));
+        }
+    }
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(CommandError::InvalidInput(i18n::t(
+            "shares-unreachable",
+            &[("shares", missing.join(", ").into())],
+        )))
+    }
+}
+
+/// Check the active project's shares every `check_interval_secs`. Emits
+/// `shares:status` after every check, `share:lost` and `share:restored`
+/// when a share goes away or comes back, and holds the worker pool while
+/// any share is unreachable.
+pub fn start_monitor<R: Runtime>(app: AppHandle<R>) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let mut holding = false;
+        loop {
+            let settings = app.state::<SettingsStore>();
+            let config = settings.get().shares;
+            let paths = if config.enabled { required(&settings) } else { Vec::new() };
+            let (statuses, changed) = app.state::<ShareMonitor>().check(&config, paths).await;
+
+            for status in &changed {
+                let path = status.path.as_str().into();
+                match status.state {
+                    ShareState::Unreachable => {
+                        emit(&app, "share:lost", status);
+                        let text = i18n::t("a11y-share-lost", &[("path", path)]);
+                        a11y::announce(&app, "shares", Politeness::Assertive, text);
+                    }
+                    _ => {
+                        emit(&app, "share:restored", status);
+                        let text = i18n::t("a11y-share-restored", &[("path", path)]);
+                        a11y::announce(&app, "shares", Politeness::Polite, text);
+                    }
+                }
+            }
+
+            let unreachable = statuses.iter().any(|status| status.state == ShareState::Unreachable);
+            if unreachable != holding {
+                app.state::<WorkerPool>().set_paused("shares", unreachable);
+                holding = unreachable;
+            }
+            if let Err(err) = app.emit("shares:status", &statuses) {
+                eprintln!("Failed to emit shares:status: {err}");
+            }
+
+            tokio::time::sleep(Duration::from_secs(config.check_interval_secs.max(1))).await;
+        }
+    })
+}
+
+/// The active project's shares as of the last check
+#[tauri::command]
+pub fn get_share_status(monitor: State<'_, ShareMonitor>) -> Vec<ShareStatus> {
+    monitor.current()
+}
+
+#[tauri::command]
+pub fn get_share_settings(settings: State<'_, SettingsStore>) -> ShareSettings {
+    settings.get().shares
+}
+
+/// Takes effect at the next check
+#[tauri::command]
+pub fn set_share_settings(shares: ShareSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.shares = shares)?;
+    Ok(())
+}
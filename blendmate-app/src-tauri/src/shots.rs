@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::protocol;
+use crate::render_queue::{self, RenderRequest, SplitStrategy};
+use crate::settings::SettingsStore;
+use crate::shares::ShareMonitor;
+use crate::workers::{self, WorkerPool};
+use crate::AppState;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ShotStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Review,
+    Approved,
+    Final,
+}
+
+/// A shot: a timeline marker and the frames up to the next marker, unless
+/// an explicit end is set. Status and order are stored in the .blend file.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Shot {
+    pub scene: String,
+    pub name: String,
+    pub start: i64,
+    pub end: i64,
+    /// Camera bound to the marker
+    pub camera: Option<String>,
+    #[serde(default)]
+    pub status: ShotStatus,
+    /// Storyboard position set by `reorder_shots`
+    pub order: Option<u32>,
+}
+
+/// New or changed shot for `save_shot`
+#[derive(Deserialize, Debug)]
+pub struct ShotInput {
+    /// Defaults to the current scene
+    pub scene: Option<String>,
+    pub name: String,
+    /// Renames this shot instead of adding one
+    pub rename_from: Option<String>,
+    pub start: i64,
+    /// Defaults to the frame before the next marker
+    pub end: Option<i64>,
+    pub camera: Option<String>,
+    pub status: Option<ShotStatus>,
+}
+
+/// How to render a shot; the frame range, scene and camera come from the
+/// shot itself
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ShotRenderOptions {
+    pub split: SplitStrategy,
+    pub priority: i32,
+    /// Output path; `{shot}` is replaced with the shot name
+    pub output: Option<String>,
+    pub device_profile: Option<String>,
+    pub validation_profile: Option<String>,
+    pub blender: Option<String>,
+}
+
+/// Last shot list read from Blender. Refreshed on connect and file load
+/// and after every shot command; `shots:changed` is emitted when it differs.
+#[derive(Default)]
+pub struct ShotState {
+    shots: Mutex<Vec<Shot>>,
+}
+
+impl ShotState {
+    pub async fn refresh<R: Runtime>(&self, app: &AppHandle<R>) -> Result<Vec<Shot>, CommandError> {
+        let bridge = &app.state::<AppState>().bridge;
+        let data = bridge.request("shots.list", "", json!({})).await?;
+        let mut shots: Vec<Shot> = serde_json::from_value(data.get("shots").cloned().unwrap_or(Value::Null))
+            .map_err(|e| format!("Invalid shot list from Blender: {}", e))?;
+        shots.sort_by(|a, b| {
+            (&a.scene, a.order.unwrap_or(u32::MAX), a.start).cmp(&(&b.scene, b.order.unwrap_or(u32::MAX), b.start))
+        });
+
+        let mut current = self.shots.lock().await;
+        if *current != shots {
+            current.clone_from(&shots);
+            if let Err(err) = app.emit("shots:changed", &shots) {
+                eprintln!("Failed to emit shots:changed: {err}");
+            }
+        }
+        Ok(shots)
+    }
+
+    async fn find<R: Runtime>(&self, app: &AppHandle<R>, scene: &str, name: &str) -> Result<Shot, CommandError> {
+        self.refresh(app)
+            .await?
+            .into_iter()
+            .find(|shot| shot.scene == scene && shot.name == name)
+            .ok_or_else(|| CommandError::NotFound(format!("Shot '{}' not found in scene '{}'", name, scene)))
+    }
+}
+
+/// Re-read shots after a file load or reconnect.
+pub fn observe<R: Runtime>(app: &AppHandle<R>, message: &Value) {
+    if !matches!(
+        protocol::message_type(message).as_deref(),
+        Some("event.scene.file_loaded" | "event.scene.connected")
+    ) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Older add-ons have no shot commands; the list just stays empty
+        let _ = app.state::<ShotState>().refresh(&app).await;
+    });
+}
+
+/// Shots of every scene, by scene and storyboard order
+#[tauri::command]
+pub async fn list_shots<R: Runtime>(app: AppHandle<R>, shots: State<'_, ShotState>) -> Result<Vec<Shot>, CommandError> {
+    shots.refresh(&app).await
+}
+
+/// Create a shot, or update one with the same name (or `rename_from`)
+#[tauri::command]
+pub async fn save_shot<R: Runtime>(
+    shot: ShotInput,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    shots: State<'_, ShotState>,
+) -> Result<Vec<Shot>, CommandError> {
+    if shot.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Shot name is required".to_string()));
+    }
+    if shot.end.is_some_and(|end| end < shot.start) {
+        return Err(CommandError::InvalidInput("Shot end is before its start".to_string()));
+    }
+    state
+        .bridge
+        .request(
+            "shots.set",
+            shot.scene.as_deref().unwrap_or(""),
+            json!({
+                "name": shot.name,
+                "rename_from": shot.rename_from,
+                "start": shot.start,
+                "end": shot.end,
+                "camera": shot.camera,
+                "status": shot.status,
+            }),
+        )
+        .await?;
+    shots.refresh(&app).await
+}
+
+#[tauri::command]
+pub async fn delete_shot<R: Runtime>(
+    scene: String,
+    name: String,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    shots: State<'_, ShotState>,
+) -> Result<Vec<Shot>, CommandError> {
+    state.bridge.request("shots.delete", &scene, json!({ "name": name })).await?;
+    shots.refresh(&app).await
+}
+
+/// Set the storyboard order of a scene's shots. Markers stay where they are.
+#[tauri::command]
+pub async fn reorder_shots<R: Runtime>(
+    scene: String,
+    names: Vec<String>,
+    app: AppHandle<R>,
+    state: State<'_, AppState>,
+    shots: State<'_, ShotState>,
+) -> Result<Vec<Shot>, CommandError> {
+    state.bridge.request("shots.reorder", &scene, json!({ "names": names })).await?;
+    shots.refresh(&app).await
+}
+
+/// Switch Blender to a shot's scene, first frame and camera. With
+/// `preview_range`, playback is limited to the shot.
+#[tauri::command]
+pub async fn jump_to_shot(
+    scene: String,
+    name: String,
+    preview_range: Option<bool>,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    history: State<'_, HistoryStore>,
+) -> Result<(), CommandError> {
+    let params = json!({ "name": name, "preview_range": preview_range.unwrap_or(false) });
+    state.bridge.request("shots.jump", &scene, params.clone()).await?;
+    history.record(&settings, "shots.jump", &scene, &params);
+    Ok(())
+}
+
+/// Render request for one shot of the open file, as saved on disk
+pub async fn render_request<R: Runtime>(
+    app: &AppHandle<R>,
+    scene: &str,
+    name: &str,
+    options: ShotRenderOptions,
+) -> Result<RenderRequest, CommandError> {
+    let shot = app.state::<ShotState>().find(app, scene, name).await?;
+    let state = app.state::<AppState>();
+    let file = state.mirror.scene(&state.bridge).await?;
+    let filepath = file.get("filepath").and_then(Value::as_str).unwrap_or_default();
+    if filepath.is_empty() || filepath == "(unsaved)" {
+        return Err(CommandError::InvalidInput("Save the file before rendering a shot".to_string()));
+    }
+
+    Ok(RenderRequest {
+        filepath: filepath.to_string(),
+        frame_start: shot.start,
+        frame_end: shot.end,
+        frame_step: 1,
+        split: options.split,
+        priority: options.priority,
+        chunk_priorities: Vec::new(),
+        output: options.output.map(|output| output.replace("{shot}", &shot.name)),
+        device_profile: options.device_profile,
+        validation_profile: options.validation_profile,
+        blender: options.blender,
+        scene: Some(shot.scene),
+        camera: shot.camera,
+    })
+}
+
+/// Queue a render job for one shot of the open file, as saved on disk
+#[tauri::command]
+pub async fn start_shot_render(
+    scene: String,
+    name: String,
+    options: Option<ShotRenderOptions>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+    shares: State<'_, ShareMonitor>,
+) -> Result<String, CommandError> {
+    let request = render_request(&app, &scene, &name, options.unwrap_or_default()).await?;
+    let id = render_queue::submit_render(&state, &settings, &pool, &shares, &request).await?;
+    workers::spawn_job(app, id.clone(), move |_, job| Some(render_queue::summarize(&request, job)));
+    Ok(id)
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::protocol;
+use crate::search::{SceneSearch, SearchFilters};
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// Events after which memberships may have changed
+const REFRESH_EVENTS: &[&str] = &[
+    "event.scene.connected",
+    "event.scene.file_loaded",
+    "event.depsgraph.updated",
+];
+
+/// Quiet time after an event before memberships are evaluated, so a burst
+/// of edits costs one pass
+const REFRESH_DELAY: Duration = Duration::from_millis(500);
+
+/// A saved scene search whose members are kept up to date
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SmartCollection {
+    pub name: String,
+    /// Name substring, as in `search_scene`
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub filters: SearchFilters,
+    /// Keep a Blender collection of the same name in sync with the members
+    #[serde(default)]
+    pub materialize: bool,
+}
+
+/// Payload of `smart_collection:changed`
+#[derive(Serialize, Clone, Debug)]
+pub struct MembershipChange {
+    pub name: String,
+    pub members: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MaterializeResult {
+    /// Collection name Blender ended up using
+    pub name: String,
+    pub linked: Vec<String>,
+    pub unlinked: Vec<String>,
+    /// Members Blender no longer has
+    pub missing: Vec<String>,
+}
+
+/// Last evaluated members of each smart collection
+#[derive(Default)]
+pub struct SmartCollectionState {
+    members: Mutex<HashMap<String, Vec<String>>>,
+    refresh_pending: AtomicBool,
+}
+
+/// Schedule a refresh when `message` may have changed memberships.
+pub fn observe<R: Runtime>(app: &AppHandle<R>, message: &Value) {
+    let Some(msg_type) = protocol::message_type(message) else {
+        return;
+    };
+    if !REFRESH_EVENTS.contains(&msg_type.as_str()) {
+        return;
+    }
+    let state = app.state::<SmartCollectionState>();
+    if app.state::<SettingsStore>().get().smart_collections.is_empty()
+        || state.refresh_pending.swap(true, Ordering::SeqCst)
+    {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(REFRESH_DELAY).await;
+        app.state::<SmartCollectionState>()
+            .refresh_pending
+            .store(false, Ordering::SeqCst);
+        refresh(&app).await;
+    });
+}
+
+/// Evaluate every smart collection, emit `smart_collection:changed` for
+/// those whose members changed and sync the materialized ones.
+pub async fn refresh<R: Runtime>(app: &AppHandle<R>) {
+    let collections = app.state::<SettingsStore>().get().smart_collections;
+    let state = app.state::<AppState>();
+    let search = app.state::<SceneSearch>();
+    let smart = app.state::<SmartCollectionState>();
+
+    for collection in collections {
+        let members = match search.members(&state, &collection.query, &collection.filters).await {
+            Ok(members) => members,
+            // Not connected; try again on the next event
+            Err(_) => return,
+        };
+        let Some(change) = smart.record(&collection.name, members).await else {
+            continue;
+        };
+
+        if collection.materialize {
+            if let Err(err) = materialize(&state, &collection.name, &change.members).await {
+                eprintln!("Failed to sync collection '{}': {err}", collection.name);
+            }
+        }
+        if let Err(err) = app.emit("smart_collection:changed", &change) {
+            eprintln!("Failed to emit smart_collection:changed: {err}");
+        }
+    }
+}
+
+impl SmartCollectionState {
+    /// Store new members; returns the change when they differ from before.
+    async fn record(&self, name: &str, mut members: Vec<String>) -> Option<MembershipChange> {
+        members.sort();
+        let mut known = self.members.lock().await;
+        let previous = known.get(name);
+        if previous == Some(&members) {
+            return None;
+        }
+
+        let before: HashSet<&String> = previous.into_iter().flatten().collect();
+        let after: HashSet<&String> = members.iter().collect();
+        let mut added: Vec<String> = after.difference(&before).map(|name| name.to_string()).collect();
+        let mut removed: Vec<String> = before.difference(&after).map(|name| name.to_string()).collect();
+        added.sort();
+        removed.sort();
+
+        let change = MembershipChange {
+            name: name.to_string(),
+            members: members.clone(),
+            added,
+            removed,
+        };
+        known.insert(name.to_string(), members);
+        Some(change)
+    }
+}
+
+/// Make the Blender collection `name` hold exactly `members`
+async fn materialize(state: &AppState, name: &str, members: &[String]) -> Result<MaterializeResult, CommandError> {
+    let data = state
+        .bridge
+        .request("collection.sync", name, json!({ "objects": members }))
+        .await?;
+    serde_json::from_value(data)
+        .map_err(|e| CommandError::Failed(format!("Unexpected collection.sync response: {}", e)))
+}
+
+fn find<'a>(collections: &'a [SmartCollection], name: &str) -> Result<&'a SmartCollection, CommandError> {
+    collections
+        .iter()
+        .find(|collection| collection.name == name)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown smart collection '{}'", name)))
+}
+
+#[tauri::command]
+pub fn list_smart_collections(settings: State<'_, SettingsStore>) -> Vec<SmartCollection> {
+    settings.get().smart_collections
+}
+
+/// Create or replace a smart collection. Its members arrive as
+/// `smart_collection:changed`.
+#[tauri::command]
+pub async fn save_smart_collection<R: Runtime>(
+    collection: SmartCollection,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    smart: State<'_, SmartCollectionState>,
+) -> Result<(), CommandError> {
+    if collection.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Smart collection name is required".to_string()));
+    }
+    let name = collection.name.clone();
+    settings.update(|s| {
+        s.smart_collections.retain(|c| c.name != collection.name);
+        s.smart_collections.push(collection);
+    })?;
+
+    // Evaluate the new definition from scratch
+    smart.members.lock().await.remove(&name);
+    tauri::async_runtime::spawn(async move { refresh(&app).await });
+    Ok(())
+}
+
+/// Forget a smart collection. A materialized Blender collection is kept.
+#[tauri::command]
+pub async fn delete_smart_collection(
+    name: String,
+    settings: State<'_, SettingsStore>,
+    smart: State<'_, SmartCollectionState>,
+) -> Result<(), CommandError> {
+    find(&settings.get().smart_collections, &name)?;
+    settings.update(|s| s.smart_collections.retain(|c| c.name != name))?;
+    smart.members.lock().await.remove(&name);
+    Ok(())
+}
+
+/// Evaluate a smart collection now
+#[tauri::command]
+pub async fn get_smart_collection_members(
+    name: String,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    search: State<'_, SceneSearch>,
+) -> Result<Vec<String>, CommandError> {
+    let collections = settings.get().smart_collections;
+    let collection = find(&collections, &name)?;
+    let mut members = search.members(&state, &collection.query, &collection.filters).await?;
+    members.sort();
+    Ok(members)
+}
+
+/// Create or update a Blender collection holding the current members
+#[tauri::command]
+pub async fn materialize_smart_collection(
+    name: String,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    search: State<'_, SceneSearch>,
+) -> Result<MaterializeResult, CommandError> {
+    let collections = settings.get().smart_collections;
+    let collection = find(&collections, &name)?;
+    let members = search.members(&state, &collection.query, &collection.filters).await?;
+    materialize(&state, &collection.name, &members).await
+}
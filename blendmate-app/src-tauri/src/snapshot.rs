@@ -0,0 +1,168 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::errors::CommandError;
+use crate::listeners::{ListenerStatus, ListenerSupervisor};
+use crate::projects::{self, ProjectProfile};
+use crate::protocol;
+use crate::quality::ConnectionQuality;
+use crate::settings::SettingsStore;
+use crate::tasks::{Task, TaskManager};
+use crate::watchdog::{ActivityState, BlenderActivity, Watchdog};
+use crate::workers::{Job, WorkerPool};
+use crate::AppState;
+
+/// Add-on events kept for the snapshot
+const RECENT_EVENTS: usize = 50;
+
+/// How long the snapshot waits for a fresh scene before using the cached one
+const SCENE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RecentEvent {
+    /// Increases by one per event since the app started
+    pub seq: u64,
+    /// Normalized type, e.g. `event.scene.file_loaded`
+    pub event: String,
+    pub received_at: String,
+    pub message: Value,
+}
+
+/// The last add-on events that were passed on to the frontend
+#[derive(Default)]
+pub struct RecentEvents {
+    events: Mutex<(u64, VecDeque<RecentEvent>)>,
+}
+
+impl RecentEvents {
+    /// Feed every add-on message passed on as `ws:message`
+    pub fn observe(&self, message: &Value) {
+        let Some(event) = protocol::message_type(message).filter(|kind| kind.starts_with("event.")) else {
+            return;
+        };
+        let mut events = self.events.lock().unwrap();
+        let (seq, recent) = &mut *events;
+        *seq += 1;
+        recent.push_back(RecentEvent {
+            seq: *seq,
+            event,
+            received_at: chrono::Local::now().to_rfc3339(),
+            message: message.clone(),
+        });
+        if recent.len() > RECENT_EVENTS {
+            recent.pop_front();
+        }
+    }
+
+    fn list(&self) -> Vec<RecentEvent> {
+        self.events.lock().unwrap().1.iter().cloned().collect()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ConnectionSnapshot {
+    pub blender: ConnectionQuality,
+    pub activity: BlenderActivity,
+    pub listeners: Vec<ListenerStatus>,
+}
+
+/// The parts of `get_scene` panels need before they load their own data
+#[derive(Serialize, Debug)]
+pub struct SceneSummary {
+    pub scene: Value,
+    pub filepath: Option<String>,
+    pub active_object: Option<String>,
+    pub selected_objects: Value,
+    pub object_count: usize,
+    /// Taken from the cache because Blender was busy or didn't answer in
+    /// time; may miss the latest changes
+    pub stale: bool,
+}
+
+impl SceneSummary {
+    fn new(scene: &Value, stale: bool) -> Self {
+        Self {
+            scene: scene.get("scene").cloned().unwrap_or(Value::Null),
+            filepath: scene.get("filepath").and_then(Value::as_str).map(str::to_string),
+            active_object: scene.get("active_object").and_then(Value::as_str).map(str::to_string),
+            selected_objects: scene.get("selected_objects").cloned().unwrap_or(Value::Array(Vec::new())),
+            object_count: scene.get("objects").and_then(Value::as_object).map_or(0, |objects| objects.len()),
+            stale,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct QueueSnapshot {
+    pub jobs: Vec<Job>,
+    /// Why the worker pool is paused, e.g. `power` or `disk`; empty while
+    /// it runs
+    pub paused_by: Vec<&'static str>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AppSnapshot {
+    pub taken_at: String,
+    pub connection: ConnectionSnapshot,
+    pub project: Option<ProjectProfile>,
+    /// `None` while Blender is disconnected and nothing is cached
+    pub scene: Option<SceneSummary>,
+    pub tasks: Vec<Task>,
+    pub queue: QueueSnapshot,
+    /// Oldest first
+    pub recent_events: Vec<RecentEvent>,
+}
+
+/// Scene summary, fetched fresh unless Blender is busy or slow to answer
+async fn scene_summary(state: &AppState, activity: &BlenderActivity) -> Option<SceneSummary> {
+    if activity.state == ActivityState::Responsive {
+        if let Ok(Ok(scene)) = tokio::time::timeout(SCENE_TIMEOUT, state.mirror.scene(&state.bridge)).await {
+            return Some(SceneSummary::new(&scene, false));
+        }
+    }
+    let scene = state.mirror.cached().await?;
+    Some(SceneSummary::new(&scene, true))
+}
+
+/// Everything the panels show on load, in one call. Subscribe to events
+/// before calling and apply those that arrive meanwhile afterwards, so
+/// nothing falls between the snapshot and the live updates. In safe mode
+/// the queue is empty, as the worker pool isn't running.
+#[tauri::command]
+pub async fn get_app_snapshot(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+    watchdog: State<'_, Watchdog>,
+    supervisor: State<'_, ListenerSupervisor>,
+    tasks: State<'_, TaskManager>,
+    events: State<'_, RecentEvents>,
+) -> Result<AppSnapshot, CommandError> {
+    let taken_at = chrono::Local::now().to_rfc3339();
+    let activity = watchdog.activity();
+    let scene = scene_summary(&state, &activity).await;
+    let queue = match app.try_state::<WorkerPool>() {
+        Some(pool) => QueueSnapshot {
+            jobs: pool.list().await,
+            paused_by: pool.holds(),
+        },
+        None => QueueSnapshot::default(),
+    };
+    Ok(AppSnapshot {
+        taken_at,
+        connection: ConnectionSnapshot {
+            blender: state.session.quality(),
+            activity,
+            listeners: supervisor.statuses().await,
+        },
+        project: projects::active(&settings),
+        scene,
+        tasks: tasks.list(),
+        queue,
+        recent_events: events.list(),
+    })
+}
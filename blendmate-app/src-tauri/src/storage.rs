@@ -0,0 +1,495 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    /// `storage.sqlite` in the app data directory
+    #[default]
+    Sqlite,
+    /// A shared database, for several seats on one history. Needs a build
+    /// with the `postgres` feature.
+    Postgres,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct StorageSettings {
+    pub backend: StorageBackend,
+    /// e.g. `postgres://blendmate@db.studio.lan/blendmate`. The password
+    /// is the `storage.postgres_password` secret.
+    pub postgres_url: Option<String>,
+}
+
+/// Where history, metrics and caches are kept. Values are JSON documents
+/// under a namespace and key, e.g. `history` / the project name, so one
+/// seat writing a project's history doesn't rewrite another's.
+pub trait Storage: Send + Sync {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<String>, CommandError>;
+    fn put(&self, namespace: &str, key: &str, value: &str) -> Result<(), CommandError>;
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), CommandError>;
+    /// Replace a document with what `change` makes of its current value.
+    /// Other writers, including other seats on a shared database, wait
+    /// until it is written. `change` must not use the storage itself.
+    fn update(
+        &self,
+        namespace: &str,
+        key: &str,
+        change: Box<dyn FnOnce(Option<String>) -> Result<String, CommandError> + '_>,
+    ) -> Result<(), CommandError>;
+}
+
+pub type SharedStorage = Arc<dyn Storage>;
+
+impl dyn Storage {
+    /// Read and parse a document; unreadable ones are logged and `None`
+    pub fn get_json<T: DeserializeOwned>(&self, namespace: &str, key: &str) -> Option<T> {
+        let text = match self.get(namespace, key) {
+            Ok(text) => text?,
+            Err(err) => {
+                eprintln!("Failed to read {namespace}/{key}: {err}");
+                return None;
+            }
+        };
+        serde_json::from_str(&text)
+            .map_err(|err| eprintln!("Invalid {namespace}/{key}: {err}"))
+            .ok()
+    }
+
+    pub fn put_json<T: Serialize + ?Sized>(&self, namespace: &str, key: &str, value: &T) -> Result<(), CommandError> {
+        let text = serde_json::to_string(value).map_err(|e| format!("Failed to serialize {}: {}", namespace, e))?;
+        self.put(namespace, key, &text)
+    }
+
+    /// Change a document in place; see `Storage::update`. A missing or
+    /// unreadable document starts as the default.
+    pub fn update_json<T: Serialize + DeserializeOwned + Default, R>(
+        &self,
+        namespace: &str,
+        key: &str,
+        change: impl FnOnce(&mut T) -> Result<R, CommandError>,
+    ) -> Result<R, CommandError> {
+        let mut result = None;
+        self.update(
+            namespace,
+            key,
+            Box::new(|current: Option<String>| {
+                let mut value: T = current
+                    .and_then(|text| {
+                        serde_json::from_str(&text)
+                            .map_err(|err| eprintln!("Invalid {namespace}/{key}: {err}"))
+                            .ok()
+                    })
+                    .unwrap_or_default();
+                result = Some(change(&mut value)?);
+                serde_json::to_string(&value)
+                    .map_err(|e| CommandError::Failed(format!("Failed to serialize {}: {}", namespace, e)))
+            }),
+        )?;
+        Ok(result.expect("update ran the change"))
+    }
+
+    /// Move a JSON file from before the storage backend into `namespace` /
+    /// `key`, unless the key already exists. The file is renamed to
+    /// `*.migrated` so it is imported once.
+    pub fn import_file(&self, path: &Path, namespace: &str, key: &str) {
+        if !path.is_file() || matches!(self.get(namespace, key), Ok(Some(_))) {
+            return;
+        }
+        let result = fs::read_to_string(path)
+            .map_err(|e| CommandError::Io(format!("Failed to read {}: {}", path.display(), e)))
+            .and_then(|text| self.put(namespace, key, &text))
+            .and_then(|()| {
+                fs::rename(path, path.with_extension("json.migrated"))
+                    .map_err(|e| CommandError::Io(format!("Failed to rename {}: {}", path.display(), e)))
+            });
+        if let Err(err) = result {
+            eprintln!("Failed to import {}: {err}", path.display());
+        }
+    }
+}
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS documents (
+    namespace TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (namespace, key)
+)";
+
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> Result<Self, CommandError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| CommandError::Io(format!("Failed to create data directory: {}", e)))?;
+        }
+        let connection = Connection::open(path).map_err(sqlite_error)?;
+        Self::new(connection)
+    }
+
+    /// Kept in memory only; the last resort when no file can be opened
+    pub fn in_memory() -> Result<Self, CommandError> {
+        Self::new(Connection::open_in_memory().map_err(sqlite_error)?)
+    }
+
+    fn new(connection: Connection) -> Result<Self, CommandError> {
+        connection.execute(SCHEMA, []).map_err(sqlite_error)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+fn sqlite_error(error: rusqlite::Error) -> CommandError {
+    CommandError::Io(format!("SQLite: {}", error))
+}
+
+impl Storage for SqliteStorage {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<String>, CommandError> {
+        self.connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT value FROM documents WHERE namespace = ?1 AND key = ?2",
+                params![namespace, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &str) -> Result<(), CommandError> {
+        self.connection
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO documents (namespace, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (namespace, key) DO UPDATE SET value = ?3, updated_at = ?4",
+                params![namespace, key, value, chrono::Local::now().to_rfc3339()],
+            )
+            .map(|_| ())
+            .map_err(sqlite_error)
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), CommandError> {
+        self.connection
+            .lock()
+            .unwrap()
+            .execute(
+                "DELETE FROM documents WHERE namespace = ?1 AND key = ?2",
+                params![namespace, key],
+            )
+            .map(|_| ())
+            .map_err(sqlite_error)
+    }
+
+    fn update(
+        &self,
+        namespace: &str,
+        key: &str,
+        change: Box<dyn FnOnce(Option<String>) -> Result<String, CommandError> + '_>,
+    ) -> Result<(), CommandError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        let current = transaction
+            .query_row(
+                "SELECT value FROM documents WHERE namespace = ?1 AND key = ?2",
+                params![namespace, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)?;
+        let value = change(current)?;
+        transaction
+            .execute(
+                "INSERT INTO documents (namespace, key, value, updated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (namespace, key) DO UPDATE SET value = ?3, updated_at = ?4",
+                params![namespace, key, value, chrono::Local::now().to_rfc3339()],
+            )
+            .map_err(sqlite_error)?;
+        transaction.commit().map_err(sqlite_error)
+    }
+}
+
+#[cfg(feature = "postgres")]
+mod postgres_backend {
+    use postgres::{Client, NoTls};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::{Storage, SCHEMA};
+    use crate::errors::CommandError;
+
+    type Job = Box<dyn FnOnce(&mut Client) + Send>;
+
+    /// Storage is opened during startup, so an unreachable server must not
+    /// hold the window back for long
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// The blocking client runs its own runtime, which can't start on a
+    /// Tokio worker thread, so every query runs on a thread of its own.
+    pub struct PostgresStorage {
+        jobs: mpsc::Sender<Job>,
+    }
+
+    fn postgres_error(error: postgres::Error) -> CommandError {
+        CommandError::Io(format!("PostgreSQL: {}", error))
+    }
+
+    impl PostgresStorage {
+        pub fn connect(url: &str, password: Option<String>) -> Result<Self, CommandError> {
+            let mut config: postgres::Config = url
+                .parse()
+                .map_err(|e| CommandError::InvalidInput(format!("Invalid PostgreSQL URL: {}", e)))?;
+            if let Some(password) = password {
+                config.password(password);
+            }
+            if config.get_connect_timeout().is_none() {
+                config.connect_timeout(CONNECT_TIMEOUT);
+            }
+            let (jobs, receiver) = mpsc::channel::<Job>();
+            let (ready, connected) = mpsc::channel();
+            std::thread::spawn(move || {
+                let mut client = match config.connect(NoTls).and_then(|mut client| {
+                    client.batch_execute(SCHEMA)?;
+                    Ok(client)
+                }) {
+                    Ok(client) => client,
+                    Err(err) => {
+                        let _ = ready.send(Err(postgres_error(err)));
+                        return;
+                    }
+                };
+                let _ = ready.send(Ok(()));
+                for job in receiver {
+                    job(&mut client);
+                }
+            });
+            connected
+                .recv()
+                .map_err(|_| CommandError::Failed("PostgreSQL connection thread stopped".to_string()))??;
+            Ok(Self { jobs })
+        }
+
+        fn run<T: Send + 'static>(
+            &self,
+            query: impl FnOnce(&mut Client) -> Result<T, postgres::Error> + Send + 'static,
+        ) -> Result<T, CommandError> {
+            let (reply, result) = mpsc::channel();
+            self.jobs
+                .send(Box::new(move |client| {
+                    let _ = reply.send(query(client));
+                }))
+                .map_err(|_| CommandError::Failed("PostgreSQL connection thread stopped".to_string()))?;
+            result
+                .recv()
+                .map_err(|_| CommandError::Failed("PostgreSQL connection thread stopped".to_string()))?
+                .map_err(postgres_error)
+        }
+    }
+
+    impl Storage for PostgresStorage {
+        fn get(&self, namespace: &str, key: &str) -> Result<Option<String>, CommandError> {
+            let (namespace, key) = (namespace.to_string(), key.to_string());
+            self.run(move |client| {
+                let row = client.query_opt(
+                    "SELECT value FROM documents WHERE namespace = $1 AND key = $2",
+                    &[&namespace, &key],
+                )?;
+                Ok(row.map(|row| row.get(0)))
+            })
+        }
+
+        fn put(&self, namespace: &str, key: &str, value: &str) -> Result<(), CommandError> {
+            let (namespace, key, value) = (namespace.to_string(), key.to_string(), value.to_string());
+            let updated_at = chrono::Local::now().to_rfc3339();
+            self.run(move |client| {
+                client.execute(
+                    "INSERT INTO documents (namespace, key, value, updated_at) VALUES ($1, $2, $3, $4)
+                     ON CONFLICT (namespace, key) DO UPDATE SET value = $3, updated_at = $4",
+                    &[&namespace, &key, &value, &updated_at],
+                )?;
+                Ok(())
+            })
+        }
+
+        fn delete(&self, namespace: &str, key: &str) -> Result<(), CommandError> {
+            let (namespace, key) = (namespace.to_string(), key.to_string());
+            self.run(move |client| {
+                client.execute(
+                    "DELETE FROM documents WHERE namespace = $1 AND key = $2",
+                    &[&namespace, &key],
+                )?;
+                Ok(())
+            })
+        }
+
+        /// The row is locked with `SELECT ... FOR UPDATE` while `change` runs
+        /// on the calling thread, so two seats can't both read the same
+        /// version and drop each other's entries.
+        fn update(
+            &self,
+            namespace: &str,
+            key: &str,
+            change: Box<dyn FnOnce(Option<String>) -> Result<String, CommandError> + '_>,
+        ) -> Result<(), CommandError> {
+            let (namespace, key) = (namespace.to_string(), key.to_string());
+            let updated_at = chrono::Local::now().to_rfc3339();
+            let (send_current, current) = mpsc::channel();
+            let (send_next, next) = mpsc::channel::<Option<String>>();
+            let (send_done, done) = mpsc::channel();
+            self.jobs
+                .send(Box::new(move |client| {
+                    let result = (|| -> Result<(), postgres::Error> {
+                        let mut transaction = client.transaction()?;
+                        // A missing document gets an empty row first, so there
+                        // is a row to lock; it is never committed empty
+                        transaction.execute(
+                            "INSERT INTO documents (namespace, key, value, updated_at) VALUES ($1, $2, '', $3)
+                             ON CONFLICT (namespace, key) DO NOTHING",
+                            &[&namespace, &key, &updated_at],
+                        )?;
+                        let value: String = transaction
+                            .query_one(
+                                "SELECT value FROM documents WHERE namespace = $1 AND key = $2 FOR UPDATE",
+                                &[&namespace, &key],
+                            )?
+                            .get(0);
+                        let _ = send_current.send(Some(value).filter(|value| !value.is_empty()));
+                        // No new value means `change` failed; dropping the
+                        // transaction rolls it back
+                        let Ok(Some(value)) = next.recv() else {
+                            return Ok(());
+                        };
+                        transaction.execute(
+                            "UPDATE documents SET value = $3, updated_at = $4 WHERE namespace = $1 AND key = $2",
+                            &[&namespace, &key, &value, &updated_at],
+                        )?;
+                        transaction.commit()
+                    })();
+                    let _ = send_done.send(result);
+                }))
+                .map_err(|_| CommandError::Failed("PostgreSQL connection thread stopped".to_string()))?;
+            // Closed without a value when the transaction failed to start
+            if let Ok(value) = current.recv() {
+                match change(value) {
+                    Ok(value) => {
+                        let _ = send_next.send(Some(value));
+                    }
+                    Err(err) => {
+                        let _ = send_next.send(None);
+                        let _ = done.recv();
+                        return Err(err);
+                    }
+                }
+            }
+            done.recv()
+                .map_err(|_| CommandError::Failed("PostgreSQL connection thread stopped".to_string()))?
+                .map_err(postgres_error)
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StorageStatus {
+    /// Backend in use
+    pub backend: StorageBackend,
+    /// Backend in the settings; differs from `backend` after a fallback
+    pub configured: StorageBackend,
+    /// Why the configured backend isn't used
+    pub error: Option<String>,
+}
+
+/// The storage opened at startup
+pub struct StorageState {
+    pub storage: SharedStorage,
+    pub status: StorageStatus,
+}
+
+fn open_postgres(config: &StorageSettings) -> Result<SharedStorage, CommandError> {
+    #[cfg(feature = "postgres")]
+    {
+        let url = config
+            .postgres_url
+            .as_deref()
+            .ok_or_else(|| CommandError::InvalidInput("No PostgreSQL URL is set".to_string()))?;
+        let password = crate::secrets::get("storage.postgres_password")?;
+        Ok(Arc::new(postgres_backend::PostgresStorage::connect(url, password)?))
+    }
+    #[cfg(not(feature = "postgres"))]
+    {
+        let _ = config;
+        Err(CommandError::Unsupported(
+            "This build has no PostgreSQL support".to_string(),
+        ))
+    }
+}
+
+/// Open the configured backend, falling back to SQLite in `data_dir` when
+/// it can't be used, and to memory when not even that opens
+pub fn open(config: &StorageSettings, data_dir: &Path) -> StorageState {
+    let mut error = None;
+    if config.backend == StorageBackend::Postgres {
+        match open_postgres(config) {
+            Ok(storage) => {
+                return StorageState {
+                    storage,
+                    status: StorageStatus {
+                        backend: StorageBackend::Postgres,
+                        configured: config.backend,
+                        error: None,
+                    },
+                }
+            }
+            Err(err) => {
+                eprintln!("Failed to open PostgreSQL storage, using SQLite: {err}");
+                error = Some(err.to_string());
+            }
+        }
+    }
+    let storage: SharedStorage = match SqliteStorage::open(&data_dir.join("storage.sqlite")) {
+        Ok(storage) => Arc::new(storage),
+        Err(err) => {
+            eprintln!("Failed to open storage, keeping it in memory: {err}");
+            error = Some(err.to_string());
+            Arc::new(SqliteStorage::in_memory().expect("in-memory SQLite"))
+        }
+    };
+    StorageState {
+        storage,
+        status: StorageStatus {
+            backend: StorageBackend::Sqlite,
+            configured: config.backend,
+            error,
+        },
+    }
+}
+
+#[tauri::command]
+pub fn get_storage_status(storage: State<'_, StorageState>) -> StorageStatus {
+    storage.status.clone()
+}
+
+#[tauri::command]
+pub fn get_storage_settings(settings: State<'_, SettingsStore>) -> StorageSettings {
+    settings.get().storage
+}
+
+/// Takes effect when the app restarts
+#[tauri::command]
+pub fn set_storage_settings(storage: StorageSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.storage = storage)?;
+    Ok(())
+}
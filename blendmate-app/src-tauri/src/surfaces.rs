@@ -0,0 +1,887 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::settings::SettingsStore;
+use crate::storage::SharedStorage;
+use crate::{listeners, macros, projects, AppState};
+
+/// How often devices are looked for, so plugged in ones connect and
+/// unplugged ones are dropped
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long learning waits for a control to be touched
+const LEARN_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A control pressed, released or moved on a device
+#[derive(Serialize, Clone, Debug)]
+pub struct ControlInput {
+    /// e.g. `midi:nanoKONTROL2`, `streamdeck:<serial>` or `keyboard`
+    pub device: String,
+    /// e.g. `note/1/60`, `cc/1/7`, `key/3` or `ctrl+shift+r`
+    pub control: String,
+    /// 0 to 1; buttons are 1 while held
+    pub value: f64,
+    pub pressed: bool,
+}
+
+/// What a control shows, on devices that can
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default)]
+pub struct ControlState {
+    pub lit: bool,
+    /// Key color on devices with screens or RGB LEDs; dimmed while unlit
+    pub color: Option<[u8; 3]>,
+    /// Fader, ring or LED level, 0 to 1; `lit` sets it full or off otherwise
+    pub value: Option<f64>,
+}
+
+/// What a bound control does
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SurfaceAction {
+    /// An add-on command, allowed by the `macros` settings like a macro
+    /// step. `"{value}"` in the parameters is replaced by the control's
+    /// value.
+    Command {
+        action: String,
+        #[serde(default)]
+        target: String,
+        #[serde(default)]
+        params: Value,
+    },
+    Macro { name: String },
+    /// Handled by the frontend, emitted as `surface:action`
+    App { name: String },
+}
+
+/// A physical control mapped to an action
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ControlBinding {
+    /// Device id, or `*` for the control on any device
+    pub device: String,
+    pub control: String,
+    pub action: SurfaceAction,
+    /// Run on every change instead of on press, for faders and knobs
+    #[serde(default)]
+    pub continuous: bool,
+    /// Shown on the control while the profile is active
+    #[serde(default)]
+    pub state: ControlState,
+}
+
+impl ControlBinding {
+    fn on(&self, device: &str) -> bool {
+        self.device == "*" || self.device == device
+    }
+}
+
+/// Bindings of one project, in the `surfaces` namespace of the storage
+/// backend under the project key
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct SurfaceProfile {
+    pub bindings: Vec<ControlBinding>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SurfaceDevice {
+    pub id: String,
+    /// `midi`, `streamdeck` or `keyboard`
+    pub kind: String,
+    pub name: String,
+    pub connected: bool,
+    pub error: Option<String>,
+}
+
+type Inputs = UnboundedSender<ControlInput>;
+
+/// A kind of control surface. A new device type is a driver and its
+/// connection, listed in `drivers`.
+trait SurfaceDriver: Send + Sync {
+    fn kind(&self) -> &'static str;
+
+    /// Devices present now, as id and name
+    fn discover(&self) -> Vec<(String, String)>;
+
+    /// Connect to a device; its input goes to `inputs` until the connection
+    /// is dropped
+    fn open(&self, id: &str, inputs: Inputs) -> Result<Box<dyn SurfaceConnection>, String>;
+}
+
+trait SurfaceConnection: Send {
+    /// Show `state` on `control`; controls without feedback ignore it
+    fn show(&mut self, control: &str, state: &ControlState) -> Result<(), String>;
+
+    /// The controls bound on this device, for devices that have to claim
+    /// them, such as global hotkeys
+    fn watch(&mut self, _controls: &[String]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn drivers<R: Runtime>(app: &AppHandle<R>) -> Vec<Arc<dyn SurfaceDriver>> {
+    let mut drivers = hardware::drivers();
+    drivers.push(Arc::new(KeyboardDriver { app: app.clone() }));
+    drivers
+}
+
+/// Builds without the `control-surfaces` feature have global hotkeys only,
+/// and need none of the MIDI and HID system libraries
+#[cfg(not(feature = "control-surfaces"))]
+mod hardware {
+    use std::sync::Arc;
+
+    use super::SurfaceDriver;
+
+    pub fn drivers() -> Vec<Arc<dyn SurfaceDriver>> {
+        Vec::new()
+    }
+}
+
+#[cfg(feature = "control-surfaces")]
+mod hardware {
+    use elgato_streamdeck::{StreamDeck, StreamDeckInput};
+    use midir::{Ignore, MidiInput, MidiOutput};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{ControlInput, ControlState, Inputs, SurfaceConnection, SurfaceDriver};
+
+    /// How long a Stream Deck read blocks before its key images can be drawn
+    const STREAMDECK_POLL: Duration = Duration::from_millis(50);
+
+    /// Key images are drawn at this size and scaled to the model's keys
+    const KEY_SIZE: u32 = 96;
+
+    pub fn drivers() -> Vec<Arc<dyn SurfaceDriver>> {
+        vec![Arc::new(MidiDriver), Arc::new(StreamDeckDriver::default())]
+    }
+
+    struct MidiDriver;
+
+    /// Control and value of a note, controller or pitch bend message
+    fn midi_input(message: &[u8]) -> Option<(String, f64, bool)> {
+        let (&status, data) = message.split_first()?;
+        let channel = (status & 0x0f) + 1;
+        match (status & 0xf0, data) {
+            (0x90, [note, velocity]) => Some((
+                format!("note/{}/{}", channel, note),
+                f64::from(*velocity) / 127.0,
+                *velocity > 0,
+            )),
+            (0x80, [note, _]) => Some((format!("note/{}/{}", channel, note), 0.0, false)),
+            (0xb0, [number, value]) => Some((
+                format!("cc/{}/{}", channel, number),
+                f64::from(*value) / 127.0,
+                *value >= 64,
+            )),
+            (0xe0, [lsb, msb]) => {
+                let bend = (u16::from(*msb) << 7) | u16::from(*lsb);
+                Some((format!("pitch/{}", channel), f64::from(bend) / 16383.0, bend > 8192))
+            }
+            _ => None,
+        }
+    }
+
+    /// A note or controller message that lights an LED or moves a motor fader
+    fn midi_feedback(control: &str, state: &ControlState) -> Option<Vec<u8>> {
+        let mut parts = control.split('/');
+        let kind = parts.next()?;
+        let channel = parts.next()?.parse::<u8>().ok()?.checked_sub(1).filter(|channel| *channel < 16)?;
+        let number = parts.next()?.parse::<u8>().ok().filter(|number| *number < 128)?;
+        let level = match state.value {
+            Some(value) => (value.clamp(0.0, 1.0) * 127.0).round() as u8,
+            None if state.lit => 127,
+            None => 0,
+        };
+        match kind {
+            "note" => Some(vec![0x90 | channel, number, level]),
+            "cc" => Some(vec![0xb0 | channel, number, level]),
+            _ => None,
+        }
+    }
+
+    impl SurfaceDriver for MidiDriver {
+        fn kind(&self) -> &'static str {
+            "midi"
+        }
+
+        fn discover(&self) -> Vec<(String, String)> {
+            let Ok(input) = MidiInput::new("Blendmate") else {
+                return Vec::new();
+            };
+            input
+                .ports()
+                .iter()
+                .filter_map(|port| input.port_name(port).ok())
+                .map(|name| (format!("midi:{}", name), name))
+                .collect()
+        }
+
+        /// Opens the input port on a thread that holds it, and the output port
+        /// of the same name for feedback when there is one
+        fn open(&self, id: &str, inputs: Inputs) -> Result<Box<dyn SurfaceConnection>, String> {
+            let name = id.strip_prefix("midi:").unwrap_or(id).to_string();
+            let device = id.to_string();
+            let (ready_tx, ready_rx) = mpsc::channel();
+            let (feedback_tx, feedback_rx) = mpsc::channel::<Vec<u8>>();
+            std::thread::spawn(move || {
+                let connect = || -> Result<_, String> {
+                    let mut input = MidiInput::new("Blendmate").map_err(|e| e.to_string())?;
+                    input.ignore(Ignore::All);
+                    let port = input
+                        .ports()
+                        .into_iter()
+                        .find(|port| input.port_name(port).is_ok_and(|port_name| port_name == name))
+                        .ok_or_else(|| format!("MIDI port '{}' not found", name))?;
+                    let receive = move |_: u64, message: &[u8], _: &mut ()| {
+                        if let Some((control, value, pressed)) = midi_input(message) {
+                            let _ = inputs.send(ControlInput {
+                                device: device.clone(),
+                                control,
+                                value,
+                                pressed,
+                            });
+                        }
+                    };
+                    let input = input.connect(&port, "blendmate-surface", receive, ()).map_err(|e| e.to_string())?;
+                    let output = MidiOutput::new("Blendmate").ok().and_then(|output| {
+                        let port = output
+                            .ports()
+                            .into_iter()
+                            .find(|port| output.port_name(port).is_ok_and(|port_name| port_name == name))?;
+                        output.connect(&port, "blendmate-surface").ok()
+                    });
+                    Ok((input, output))
+                };
+                match connect() {
+                    Ok((input, mut output)) => {
+                        let _ = ready_tx.send(Ok(()));
+                        while let Ok(message) = feedback_rx.recv() {
+                            if let Some(output) = output.as_mut() {
+                                let _ = output.send(&message);
+                            }
+                        }
+                        drop(input);
+                    }
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err));
+                    }
+                }
+            });
+            match ready_rx.recv() {
+                Ok(Ok(())) => Ok(Box::new(MidiConnection { feedback: feedback_tx })),
+                Ok(Err(err)) => Err(err),
+                Err(_) => Err("MIDI thread ended".to_string()),
+            }
+        }
+    }
+
+    /// Closes its ports when dropped, as the thread's channel closes
+    struct MidiConnection {
+        feedback: mpsc::Sender<Vec<u8>>,
+    }
+
+    impl SurfaceConnection for MidiConnection {
+        fn show(&mut self, control: &str, state: &ControlState) -> Result<(), String> {
+            match midi_feedback(control, state) {
+                Some(message) => self.feedback.send(message).map_err(|_| "MIDI thread ended".to_string()),
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// One HID context for every Stream Deck, as hidapi wants
+    #[derive(Default)]
+    struct StreamDeckDriver {
+        hid: Mutex<Option<hidapi::HidApi>>,
+    }
+
+    impl SurfaceDriver for StreamDeckDriver {
+        fn kind(&self) -> &'static str {
+            "streamdeck"
+        }
+
+        fn discover(&self) -> Vec<(String, String)> {
+            let mut hid = self.hid.lock().unwrap();
+            match hid.as_mut() {
+                Some(hid) => {
+                    let _ = hid.refresh_devices();
+                }
+                None => *hid = elgato_streamdeck::new_hidapi().ok(),
+            }
+            let Some(hid) = hid.as_ref() else {
+                return Vec::new();
+            };
+            elgato_streamdeck::list_devices(hid)
+                .into_iter()
+                .map(|(kind, serial)| (format!("streamdeck:{}", serial), format!("Stream Deck {:?}", kind)))
+                .collect()
+        }
+
+        /// Reads keys on a thread, holding the device briefly between reads so
+        /// key images can be drawn
+        fn open(&self, id: &str, inputs: Inputs) -> Result<Box<dyn SurfaceConnection>, String> {
+            let serial = id.strip_prefix("streamdeck:").unwrap_or(id);
+            let hid = self.hid.lock().unwrap();
+            let hid = hid.as_ref().ok_or("HID unavailable")?;
+            let (kind, _) = elgato_streamdeck::list_devices(hid)
+                .into_iter()
+                .find(|(_, found)| found == serial)
+                .ok_or_else(|| format!("Stream Deck '{}' not found", serial))?;
+            let deck = StreamDeck::connect(hid, kind, serial).map_err(|e| e.to_string())?;
+            let _ = deck.clear_all_button_images();
+            let deck = Arc::new(Mutex::new(deck));
+            let closed = Arc::new(AtomicBool::new(false));
+
+            let (reader, stop, device) = (deck.clone(), closed.clone(), id.to_string());
+            std::thread::spawn(move || {
+                let mut held: Vec<bool> = Vec::new();
+                while !stop.load(Ordering::Relaxed) {
+                    let read = reader.lock().unwrap().read_input(Some(STREAMDECK_POLL));
+                    let keys = match read {
+                        Ok(StreamDeckInput::ButtonStateChange(keys)) => keys,
+                        Ok(_) => continue,
+                        Err(_) => break,
+                    };
+                    for (index, pressed) in keys.iter().enumerate() {
+                        if held.get(index).copied().unwrap_or(false) != *pressed {
+                            let _ = inputs.send(ControlInput {
+                                device: device.clone(),
+                                control: format!("key/{}", index),
+                                value: if *pressed { 1.0 } else { 0.0 },
+                                pressed: *pressed,
+                            });
+                        }
+                    }
+                    held = keys;
+                }
+            });
+            Ok(Box::new(StreamDeckConnection { deck, closed }))
+        }
+    }
+
+    struct StreamDeckConnection {
+        deck: Arc<Mutex<StreamDeck>>,
+        closed: Arc<AtomicBool>,
+    }
+
+    impl SurfaceConnection for StreamDeckConnection {
+        /// Fills the key with its color, full while lit and dimmed otherwise
+        fn show(&mut self, control: &str, state: &ControlState) -> Result<(), String> {
+            let Some(key) = control.strip_prefix("key/").and_then(|key| key.parse::<u8>().ok()) else {
+                return Ok(());
+            };
+            let [r, g, b] = state.color.unwrap_or([255, 255, 255]);
+            let level = state.value.unwrap_or(if state.lit { 1.0 } else { 0.25 }).clamp(0.0, 1.0);
+            let dim = |channel: u8| (f64::from(channel) * level).round() as u8;
+            let fill = image::RgbImage::from_pixel(KEY_SIZE, KEY_SIZE, image::Rgb([dim(r), dim(g), dim(b)]));
+            let deck = self.deck.lock().unwrap();
+            deck.set_button_image(key, image::DynamicImage::ImageRgb8(fill)).map_err(|e| e.to_string())?;
+            deck.flush().map_err(|e| e.to_string())
+        }
+    }
+
+    impl Drop for StreamDeckConnection {
+        fn drop(&mut self) {
+            self.closed.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Global hotkeys, one device whose controls are shortcuts such as
+/// `ctrl+shift+r`. They work while Blender has focus.
+struct KeyboardDriver<R: Runtime> {
+    app: AppHandle<R>,
+}
+
+impl<R: Runtime> SurfaceDriver for KeyboardDriver<R> {
+    fn kind(&self) -> &'static str {
+        "keyboard"
+    }
+
+    fn discover(&self) -> Vec<(String, String)> {
+        vec![("keyboard".to_string(), "Keyboard hotkeys".to_string())]
+    }
+
+    fn open(&self, _id: &str, inputs: Inputs) -> Result<Box<dyn SurfaceConnection>, String> {
+        Ok(Box::new(KeyboardConnection {
+            app: self.app.clone(),
+            inputs,
+            registered: Vec::new(),
+        }))
+    }
+}
+
+struct KeyboardConnection<R: Runtime> {
+    app: AppHandle<R>,
+    inputs: Inputs,
+    registered: Vec<Shortcut>,
+}
+
+impl<R: Runtime> KeyboardConnection<R> {
+    fn release(&mut self) {
+        for shortcut in self.registered.drain(..) {
+            let _ = self.app.global_shortcut().unregister(shortcut);
+        }
+    }
+}
+
+impl<R: Runtime> SurfaceConnection for KeyboardConnection<R> {
+    fn show(&mut self, _control: &str, _state: &ControlState) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Registers the bound shortcuts and releases the rest; a shortcut
+    /// another app holds is reported and the others still register
+    fn watch(&mut self, controls: &[String]) -> Result<(), String> {
+        self.release();
+        let mut errors = Vec::new();
+        for control in controls {
+            let shortcut = match control.parse::<Shortcut>() {
+                Ok(shortcut) => shortcut,
+                Err(err) => {
+                    errors.push(format!("'{}': {}", control, err));
+                    continue;
+                }
+            };
+            let (inputs, control) = (self.inputs.clone(), control.clone());
+            let registered = self.app.global_shortcut().on_shortcut(shortcut, move |_, _, event| {
+                let pressed = event.state() == ShortcutState::Pressed;
+                let _ = inputs.send(ControlInput {
+                    device: "keyboard".to_string(),
+                    control: control.clone(),
+                    value: if pressed { 1.0 } else { 0.0 },
+                    pressed,
+                });
+            });
+            match registered {
+                Ok(()) => self.registered.push(shortcut),
+                Err(err) => errors.push(format!("'{}': {}", control, err)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Hotkeys not registered: {}", errors.join(", ")))
+        }
+    }
+}
+
+impl<R: Runtime> Drop for KeyboardConnection<R> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+struct OpenSurface {
+    kind: &'static str,
+    name: String,
+    connection: Option<Box<dyn SurfaceConnection>>,
+    error: Option<String>,
+}
+
+/// Connected control surfaces and the active project's bindings. Devices
+/// are only connected while the `control_surfaces` feature runs.
+pub struct SurfaceState {
+    storage: SharedStorage,
+    drivers: Mutex<Vec<Arc<dyn SurfaceDriver>>>,
+    /// Where connections send input; `None` while the feature is off
+    inputs: Mutex<Option<Inputs>>,
+    devices: Mutex<HashMap<String, OpenSurface>>,
+    /// Project key of the loaded profile, and the profile
+    profile: Mutex<Option<(String, SurfaceProfile)>>,
+    /// States set with `set_control_state`, by device and control; shown
+    /// over the bindings' own
+    states: Mutex<HashMap<(String, String), ControlState>>,
+    learning: Mutex<Option<oneshot::Sender<ControlInput>>>,
+}
+
+impl SurfaceState {
+    pub fn new(storage: SharedStorage) -> Self {
+        Self {
+            storage,
+            drivers: Mutex::new(Vec::new()),
+            inputs: Mutex::new(None),
+            devices: Mutex::new(HashMap::new()),
+            profile: Mutex::new(None),
+            states: Mutex::new(HashMap::new()),
+            learning: Mutex::new(None),
+        }
+    }
+
+    fn load(&self, project: &str) -> SurfaceProfile {
+        self.storage.get_json("surfaces", project).unwrap_or_default()
+    }
+
+    /// Claim the bound controls of one device and show their states
+    fn apply(&self, id: &str, surface: &mut OpenSurface, profile: &SurfaceProfile) {
+        let Some(connection) = surface.connection.as_mut() else {
+            return;
+        };
+        let bindings: Vec<&ControlBinding> = profile.bindings.iter().filter(|binding| binding.on(id)).collect();
+        let controls: Vec<String> = bindings.iter().map(|binding| binding.control.clone()).collect();
+        let mut shown: HashMap<String, ControlState> = bindings
+            .iter()
+            .map(|binding| (binding.control.clone(), binding.state.clone()))
+            .collect();
+        for ((device, control), state) in self.states.lock().unwrap().iter() {
+            if device == id {
+                shown.insert(control.clone(), state.clone());
+            }
+        }
+        let mut errors = Vec::new();
+        if let Err(err) = connection.watch(&controls) {
+            errors.push(err);
+        }
+        for (control, state) in &shown {
+            if let Err(err) = connection.show(control, state) {
+                errors.push(err);
+            }
+        }
+        surface.error = (!errors.is_empty()).then(|| errors.join("; "));
+    }
+
+    /// Connect devices that appeared, drop those that are gone and load the
+    /// active project's profile when it changed
+    fn scan(&self, settings: &SettingsStore) {
+        let Some(inputs) = self.inputs.lock().unwrap().clone() else {
+            return;
+        };
+        let project = projects::key(settings, None);
+        let mut profile = self.profile.lock().unwrap();
+        let switched = profile.as_ref().is_none_or(|(loaded, _)| *loaded != project);
+        if switched {
+            *profile = Some((project.clone(), self.load(&project)));
+        }
+        let bindings = profile.as_ref().map(|(_, profile)| profile).cloned().unwrap_or_default();
+
+        let drivers = self.drivers.lock().unwrap().clone();
+        let present: Vec<(&'static str, String, String)> = drivers
+            .iter()
+            .flat_map(|driver| {
+                let kind = driver.kind();
+                driver.discover().into_iter().map(move |(id, name)| (kind, id, name))
+            })
+            .collect();
+        let mut devices = self.devices.lock().unwrap();
+        devices.retain(|id, _| present.iter().any(|(_, found, _)| found == id));
+        for (kind, id, name) in present {
+            let fresh = devices.get(&id).is_none_or(|surface| surface.connection.is_none());
+            if !fresh && !switched {
+                continue;
+            }
+            let surface = devices.entry(id.clone()).or_insert_with(|| OpenSurface {
+                kind,
+                name,
+                connection: None,
+                error: None,
+            });
+            if surface.connection.is_none() {
+                let driver = drivers.iter().find(|driver| driver.kind() == kind);
+                match driver.map(|driver| driver.open(&id, inputs.clone())) {
+                    Some(Ok(connection)) => surface.connection = Some(connection),
+                    Some(Err(err)) => {
+                        surface.error = Some(err);
+                        continue;
+                    }
+                    None => continue,
+                }
+            }
+            self.apply(&id, surface, &bindings);
+        }
+    }
+
+    /// Show the active profile on every connected device again
+    fn reapply(&self) {
+        let profile = self.profile.lock().unwrap().as_ref().map(|(_, profile)| profile.clone());
+        let mut devices = self.devices.lock().unwrap();
+        for (id, surface) in devices.iter_mut() {
+            self.apply(id, surface, profile.as_ref().unwrap_or(&SurfaceProfile::default()));
+        }
+    }
+
+    fn list(&self) -> Vec<SurfaceDevice> {
+        let mut devices: Vec<SurfaceDevice> = self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, surface)| SurfaceDevice {
+                id: id.clone(),
+                kind: surface.kind.to_string(),
+                name: surface.name.clone(),
+                connected: surface.connection.is_some(),
+                error: surface.error.clone(),
+            })
+            .collect();
+        devices.sort_by(|a, b| a.id.cmp(&b.id));
+        devices
+    }
+
+    /// Bindings `input` runs
+    fn bound(&self, input: &ControlInput) -> Vec<ControlBinding> {
+        let profile = self.profile.lock().unwrap();
+        let Some((_, profile)) = profile.as_ref() else {
+            return Vec::new();
+        };
+        profile
+            .bindings
+            .iter()
+            .filter(|binding| binding.on(&input.device) && binding.control == input.control)
+            .filter(|binding| binding.continuous || input.pressed)
+            .cloned()
+            .collect()
+    }
+
+    /// Disconnect every device, e.g. when the feature is switched off
+    pub fn close(&self) {
+        *self.inputs.lock().unwrap() = None;
+        self.devices.lock().unwrap().clear();
+        self.learning.lock().unwrap().take();
+    }
+}
+
+/// Replace `"{value}"` strings with the control's value
+fn with_value(params: &mut Value, value: f64) {
+    match params {
+        Value::String(text) if text == "{value}" => *params = json!(value),
+        Value::Array(items) => items.iter_mut().for_each(|item| with_value(item, value)),
+        Value::Object(map) => map.values_mut().for_each(|item| with_value(item, value)),
+        _ => {}
+    }
+}
+
+async fn run<R: Runtime>(app: &AppHandle<R>, action: &SurfaceAction, input: &ControlInput) -> Result<(), CommandError> {
+    match action {
+        SurfaceAction::Command { action, target, params } => {
+            let settings = app.state::<SettingsStore>();
+            if !listeners::command_allowed(&settings.get().macros.allowed_commands, action) {
+                return Err(CommandError::InvalidInput(format!(
+                    "Command '{}' is not allowed on control surfaces",
+                    action
+                )));
+            }
+            let mut params = params.clone();
+            with_value(&mut params, input.value);
+            app.state::<AppState>().bridge.request(action, target, params.clone()).await?;
+            app.state::<HistoryStore>().record(&settings, action, target, &params);
+            Ok(())
+        }
+        SurfaceAction::Macro { name } => {
+            let run = macros::run_macro(
+                name.clone(),
+                None,
+                app.state(),
+                app.state(),
+                app.state(),
+                app.state(),
+            )
+            .await?;
+            match run.failed {
+                Some((index, error)) => Err(CommandError::Failed(format!(
+                    "Macro '{}' failed at step {}: {}",
+                    name,
+                    index + 1,
+                    error
+                ))),
+                None => Ok(()),
+            }
+        }
+        SurfaceAction::App { name } => {
+            let payload = json!({
+                "name": name,
+                "device": input.device,
+                "control": input.control,
+                "value": input.value,
+            });
+            app.emit("surface:action", payload).map_err(|e| CommandError::Failed(e.to_string()))
+        }
+    }
+}
+
+/// Hand `input` to a waiting `learn_surface_control`, or run what it is
+/// bound to. Each action runs on its own, so a slow command doesn't hold
+/// up the controls.
+fn handle<R: Runtime>(app: &AppHandle<R>, input: ControlInput) {
+    let surfaces = app.state::<SurfaceState>();
+    if let Some(learn) = surfaces.learning.lock().unwrap().take() {
+        let _ = learn.send(input);
+        return;
+    }
+    if let Err(err) = app.emit("surface:input", &input) {
+        eprintln!("Failed to emit surface:input: {err}");
+    }
+    for binding in surfaces.bound(&input) {
+        let (app, input) = (app.clone(), input.clone());
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = run(&app, &binding.action, &input).await {
+                let payload = json!({ "device": input.device, "control": input.control, "error": err.to_string() });
+                if let Err(err) = app.emit("surface:error", payload) {
+                    eprintln!("Failed to emit surface:error: {err}");
+                }
+            }
+        });
+    }
+}
+
+/// Connect control surfaces and run their bindings until the task is
+/// aborted. Devices are looked for every `SCAN_INTERVAL`.
+pub fn start<R: Runtime>(app: AppHandle<R>) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let (inputs, mut received) = tokio::sync::mpsc::unbounded_channel();
+        {
+            let surfaces = app.state::<SurfaceState>();
+            *surfaces.drivers.lock().unwrap() = drivers(&app);
+            *surfaces.inputs.lock().unwrap() = Some(inputs);
+            *surfaces.profile.lock().unwrap() = None;
+        }
+        let mut scan = tokio::time::interval(SCAN_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = scan.tick() => {
+                    let app = app.clone();
+                    let scanned = tauri::async_runtime::spawn_blocking(move || {
+                        app.state::<SurfaceState>().scan(&app.state::<SettingsStore>());
+                    });
+                    let _ = scanned.await;
+                }
+                Some(input) = received.recv() => handle(&app, input),
+            }
+        }
+    })
+}
+
+fn running(surfaces: &SurfaceState) -> Result<(), CommandError> {
+    if surfaces.inputs.lock().unwrap().is_none() {
+        return Err(CommandError::Unsupported(
+            "Control surfaces are switched off; enable the control_surfaces feature".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Devices found at the last scan, connected or not
+#[tauri::command]
+pub fn list_control_surfaces(surfaces: State<'_, SurfaceState>) -> Vec<SurfaceDevice> {
+    surfaces.list()
+}
+
+/// Look for devices now instead of at the next scan
+#[tauri::command]
+pub async fn refresh_control_surfaces<R: Runtime>(app: AppHandle<R>) -> Result<Vec<SurfaceDevice>, CommandError> {
+    running(&app.state::<SurfaceState>())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let surfaces = app.state::<SurfaceState>();
+        surfaces.scan(&app.state::<SettingsStore>());
+        surfaces.list()
+    })
+    .await
+    .map_err(|e| CommandError::Failed(format!("Device scan failed: {}", e)))
+}
+
+/// Bindings of `project`, the active project when not given
+#[tauri::command]
+pub fn get_surface_profile(
+    project: Option<String>,
+    settings: State<'_, SettingsStore>,
+    surfaces: State<'_, SurfaceState>,
+) -> SurfaceProfile {
+    surfaces.load(&projects::key(&settings, project))
+}
+
+/// Replace the bindings of `project`. The active project's take effect on
+/// connected devices right away.
+#[tauri::command]
+pub fn save_surface_profile(
+    project: Option<String>,
+    profile: SurfaceProfile,
+    settings: State<'_, SettingsStore>,
+    surfaces: State<'_, SurfaceState>,
+) -> Result<(), CommandError> {
+    if let Some(binding) = profile.bindings.iter().find(|binding| binding.control.trim().is_empty()) {
+        return Err(CommandError::InvalidInput(format!(
+            "A binding on '{}' has no control",
+            binding.device
+        )));
+    }
+    let key = projects::key(&settings, project);
+    surfaces.storage.put_json("surfaces", &key, &profile)?;
+    if key == projects::key(&settings, None) {
+        *surfaces.profile.lock().unwrap() = Some((key, profile));
+        surfaces.reapply();
+    }
+    Ok(())
+}
+
+/// Live remap: wait for the next control touched on any device and return
+/// it instead of running its binding. With `binding`, that binding of the
+/// active profile moves to the control and the profile is saved.
+#[tauri::command]
+pub async fn learn_surface_control(
+    binding: Option<usize>,
+    settings: State<'_, SettingsStore>,
+    surfaces: State<'_, SurfaceState>,
+) -> Result<ControlInput, CommandError> {
+    running(&surfaces)?;
+    let key = projects::key(&settings, None);
+    let mut profile = surfaces.load(&key);
+    if let Some(index) = binding.filter(|index| *index >= profile.bindings.len()) {
+        return Err(CommandError::NotFound(format!("No binding {} in the active profile", index)));
+    }
+    let (learned_tx, learned_rx) = oneshot::channel();
+    // A learn already waiting is cancelled
+    *surfaces.learning.lock().unwrap() = Some(learned_tx);
+    let input = match tokio::time::timeout(LEARN_TIMEOUT, learned_rx).await {
+        Ok(Ok(input)) => input,
+        Ok(Err(_)) => return Err(CommandError::Cancelled("Learning was cancelled".to_string())),
+        Err(_) => {
+            surfaces.learning.lock().unwrap().take();
+            return Err(CommandError::Cancelled("No control was touched".to_string()));
+        }
+    };
+    if let Some(index) = binding {
+        profile.bindings[index].device = input.device.clone();
+        profile.bindings[index].control = input.control.clone();
+        surfaces.storage.put_json("surfaces", &key, &profile)?;
+        *surfaces.profile.lock().unwrap() = Some((key, profile));
+        surfaces.reapply();
+    }
+    Ok(input)
+}
+
+/// Show a state on a control, e.g. a toggle that changed in Blender. It
+/// stays over the binding's own state until cleared with `None`.
+#[tauri::command]
+pub fn set_control_state(
+    device: String,
+    control: String,
+    state: Option<ControlState>,
+    surfaces: State<'_, SurfaceState>,
+) -> Result<(), CommandError> {
+    let key = (device.clone(), control.clone());
+    match state {
+        Some(state) => surfaces.states.lock().unwrap().insert(key, state),
+        None => surfaces.states.lock().unwrap().remove(&key),
+    };
+    let profile = surfaces.profile.lock().unwrap().as_ref().map(|(_, profile)| profile.clone());
+    let mut devices = surfaces.devices.lock().unwrap();
+    let surface = devices
+        .get_mut(&device)
+        .ok_or_else(|| CommandError::NotFound(format!("Control surface '{}' is not connected", device)))?;
+    surfaces.apply(&device, surface, profile.as_ref().unwrap_or(&SurfaceProfile::default()));
+    match &surface.error {
+        Some(err) => Err(CommandError::Failed(err.clone())),
+        None => Ok(()),
+    }
+}
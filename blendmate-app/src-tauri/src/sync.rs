@@ -0,0 +1,428 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::features::FeatureRegistry;
+use crate::i18n;
+use crate::secrets;
+use crate::settings::{Settings, SettingsStore};
+
+/// How often the synced file and the local settings are compared
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const BUNDLE_FORMAT: u32 = 1;
+
+/// Keyring secret bundles are signed with; the same on every machine
+const KEY_SECRET: &str = "sync.key";
+
+/// Categories that stay on this machine whatever the settings say:
+/// listeners carry access tokens, storage is only read at startup, and
+/// sync settings name this machine's own path
+const NEVER_SYNCED: &[&str] = &["sync", "listeners", "storage"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SyncSettings {
+    /// Keep `path` and these settings in step while the app runs
+    pub enabled: bool,
+    /// Synced bundle, e.g. `blendmate-settings.json` in a shared drive or
+    /// cloud folder
+    pub path: Option<String>,
+    /// Categories, the top-level keys of `settings.json`, that this
+    /// machine neither sends nor takes
+    pub excluded: Vec<String>,
+    /// Name of this machine in bundles; the host name when unset
+    pub machine: Option<String>,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            // Paths to tools and folders differ between machines
+            excluded: ["addon_dev", "editor", "ffmpeg", "scratch", "workers"]
+                .map(String::from)
+                .to_vec(),
+            machine: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Payload {
+    format: u32,
+    machine: String,
+    exported_at: String,
+    categories: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    #[serde(flatten)]
+    payload: Payload,
+    /// Hex HMAC-SHA256 of the payload under the sync key
+    signature: String,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SyncStatus {
+    pub enabled: bool,
+    pub path: Option<String>,
+    pub last_pushed: Option<String>,
+    pub last_pulled: Option<String>,
+    /// Machine that wrote the bundle last pulled
+    pub pulled_from: Option<String>,
+    /// Why the last attempt failed; cleared by the next success
+    pub error: Option<String>,
+}
+
+/// Emitted as `settings:synced` after settings were taken from another
+/// machine, so the frontend reloads them
+#[derive(Serialize, Clone, Debug)]
+pub struct SyncedSettings {
+    pub machine: String,
+    pub exported_at: String,
+    pub categories: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct SyncState {
+    status: Mutex<SyncStatus>,
+}
+
+fn machine_name(config: &SyncSettings) -> String {
+    config
+        .machine
+        .clone()
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn is_synced(config: &SyncSettings, category: &str) -> bool {
+    !NEVER_SYNCED.contains(&category) && !config.excluded.iter().any(|excluded| excluded == category)
+}
+
+/// Settings categories this machine shares
+fn synced_categories(settings: &Settings) -> Result<Map<String, Value>, CommandError> {
+    let Value::Object(all) =
+        serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?
+    else {
+        return Err(CommandError::Failed("Settings are not an object".to_string()));
+    };
+    Ok(all.into_iter().filter(|(category, _)| is_synced(&settings.sync, category)).collect())
+}
+
+fn key() -> Result<Vec<u8>, CommandError> {
+    secrets::get(KEY_SECRET)?
+        .filter(|key| !key.is_empty())
+        .map(String::into_bytes)
+        .ok_or_else(|| {
+            CommandError::InvalidInput(format!("Set the '{}' secret first, the same on every machine", KEY_SECRET))
+        })
+}
+
+fn mac(key: &[u8], payload: &Payload) -> Result<Hmac<Sha256>, CommandError> {
+    let bytes = serde_json::to_vec(payload).map_err(|e| format!("Failed to serialize settings bundle: {}", e))?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|e| CommandError::Failed(e.to_string()))?;
+    mac.update(&bytes);
+    Ok(mac)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Read `path` and check its signature
+fn read_bundle(path: &Path, key: &[u8]) -> Result<Payload, CommandError> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| CommandError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+    let bundle: Bundle = serde_json::from_str(&text)
+        .map_err(|e| CommandError::InvalidInput(format!("Invalid settings bundle {}: {}", path.display(), e)))?;
+    let signature = from_hex(&bundle.signature).unwrap_or_default();
+    if mac(key, &bundle.payload)?.verify_slice(&signature).is_err() {
+        return Err(CommandError::InvalidInput(format!(
+            "Settings bundle {} is not signed with this machine's sync key",
+            path.display()
+        )));
+    }
+    if bundle.payload.format != BUNDLE_FORMAT {
+        return Err(CommandError::Unsupported(format!(
+            "Settings bundle format {} is not supported",
+            bundle.payload.format
+        )));
+    }
+    Ok(bundle.payload)
+}
+
+/// Sign and write the synced categories to `path`. Categories already in
+/// the file that this machine doesn't share are kept for the others.
+fn write_bundle(path: &Path, settings: &Settings, key: &[u8]) -> Result<Vec<String>, CommandError> {
+    let mut categories = match read_bundle(path, key) {
+        Ok(existing) => existing.categories,
+        Err(_) => Map::new(),
+    };
+    categories.retain(|category, _| !NEVER_SYNCED.contains(&category.as_str()));
+    let ours = synced_categories(settings)?;
+    let names = ours.keys().cloned().collect();
+    categories.extend(ours);
+    let payload = Payload {
+        format: BUNDLE_FORMAT,
+        machine: machine_name(&settings.sync),
+        exported_at: chrono::Local::now().to_rfc3339(),
+        categories,
+    };
+    let signature = to_hex(&mac(key, &payload)?.finalize().into_bytes());
+    let text = serde_json::to_string_pretty(&Bundle { payload, signature })
+        .map_err(|e| format!("Failed to serialize settings bundle: {}", e))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| CommandError::Io(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+    // Written aside and renamed, so other machines never read half a file
+    let partial = path.with_extension("partial");
+    fs::write(&partial, text).map_err(|e| CommandError::Io(format!("Failed to write {}: {}", partial.display(), e)))?;
+    fs::rename(&partial, path).map_err(|e| CommandError::Io(format!("Failed to write {}: {}", path.display(), e)))?;
+    Ok(names)
+}
+
+/// Take the categories of `payload` this machine syncs. Returns the ones
+/// that changed.
+fn apply<R: Runtime>(app: &AppHandle<R>, payload: &Payload) -> Result<Vec<String>, CommandError> {
+    let store = app.state::<SettingsStore>();
+    let mut changed = Vec::new();
+    let mut failed = None;
+    let updated = store.update(|settings| {
+        let Ok(Value::Object(mut all)) = serde_json::to_value(&*settings) else {
+            return;
+        };
+        for (category, value) in &payload.categories {
+            if is_synced(&settings.sync, category) && all.get(category) != Some(value) {
+                all.insert(category.clone(), value.clone());
+                changed.push(category.clone());
+            }
+        }
+        if changed.is_empty() {
+            return;
+        }
+        match serde_json::from_value(Value::Object(all)) {
+            Ok(merged) => *settings = merged,
+            Err(e) => failed = Some(CommandError::InvalidInput(format!("Invalid synced settings: {}", e))),
+        }
+    })?;
+    if let Some(err) = failed {
+        return Err(err);
+    }
+    if changed.is_empty() {
+        return Ok(changed);
+    }
+    if changed.iter().any(|category| category == "locale") {
+        i18n::use_locale(updated.locale);
+    }
+    if changed.iter().any(|category| category == "features") {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move { app.state::<FeatureRegistry>().apply(&app).await });
+    }
+    let synced = SyncedSettings {
+        machine: payload.machine.clone(),
+        exported_at: payload.exported_at.clone(),
+        categories: changed.clone(),
+    };
+    if let Err(err) = app.emit("settings:synced", &synced) {
+        eprintln!("Failed to emit settings:synced: {err}");
+    }
+    Ok(changed)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// What the watcher last saw, so its own writes don't come back to it
+#[derive(Default)]
+struct Seen {
+    path: Option<PathBuf>,
+    /// Modification time of the bundle when last read or written
+    file: Option<SystemTime>,
+    /// Synced categories when last pushed or pulled
+    local: Option<Map<String, Value>>,
+}
+
+/// One round: take the file when another machine changed it, else write
+/// it when settings changed here. The file wins when both changed, as on
+/// the first round after startup.
+fn sync_once<R: Runtime>(app: &AppHandle<R>, path: &Path, seen: &mut Seen) -> Result<(), CommandError> {
+    let key = key()?;
+    let state = app.state::<SyncState>();
+    let store = app.state::<SettingsStore>();
+    let file = modified(path);
+    if file.is_some() && file != seen.file {
+        let payload = read_bundle(path, &key)?;
+        seen.file = file;
+        let changed = apply(app, &payload)?;
+        seen.local = Some(synced_categories(&store.get())?);
+        if !changed.is_empty() {
+            let mut status = state.status.lock().unwrap();
+            status.last_pulled = Some(chrono::Local::now().to_rfc3339());
+            status.pulled_from = Some(payload.machine);
+        }
+        return Ok(());
+    }
+    let settings = store.get();
+    let local = synced_categories(&settings)?;
+    if seen.local.as_ref() == Some(&local) {
+        return Ok(());
+    }
+    write_bundle(path, &settings, &key)?;
+    seen.file = modified(path);
+    seen.local = Some(local);
+    state.status.lock().unwrap().last_pushed = Some(chrono::Local::now().to_rfc3339());
+    Ok(())
+}
+
+fn set_error<R: Runtime>(app: &AppHandle<R>, error: Option<String>) {
+    let status = {
+        let mut status = app.state::<SyncState>().status.lock().unwrap();
+        if status.error == error {
+            return;
+        }
+        status.error = error;
+        status.clone()
+    };
+    if let Err(err) = app.emit("sync:status", &status) {
+        eprintln!("Failed to emit sync:status: {err}");
+    }
+}
+
+/// Keep the synced file and these settings in step while sync is enabled
+pub fn start_watcher<R: Runtime>(app: AppHandle<R>) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let mut seen = Seen::default();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let config = app.state::<SettingsStore>().get().sync;
+            let path = config
+                .path
+                .filter(|path| config.enabled && !path.is_empty())
+                .map(PathBuf::from);
+            if path != seen.path {
+                seen = Seen {
+                    path: path.clone(),
+                    ..Seen::default()
+                };
+            }
+            let Some(path) = path else {
+                continue;
+            };
+            let error = sync_once(&app, &path, &mut seen).err().map(|err| err.to_string());
+            set_error(&app, error);
+        }
+    })
+}
+
+/// Write the shared settings to `path` as a signed bundle, e.g. to carry
+/// them over by hand. Returns the categories written.
+#[tauri::command]
+pub fn export_settings_bundle(path: String, settings: State<'_, SettingsStore>) -> Result<Vec<String>, CommandError> {
+    write_bundle(Path::new(&path), &settings.get(), &key()?)
+}
+
+/// Take the settings of a signed bundle, except the categories this
+/// machine keeps to itself. Returns the categories that changed.
+#[tauri::command]
+pub fn import_settings_bundle<R: Runtime>(path: String, app: AppHandle<R>) -> Result<Vec<String>, CommandError> {
+    let payload = read_bundle(Path::new(&path), &key()?)?;
+    apply(&app, &payload)
+}
+
+#[tauri::command]
+pub fn get_sync_settings(settings: State<'_, SettingsStore>) -> SyncSettings {
+    settings.get().sync
+}
+
+#[tauri::command]
+pub fn set_sync_settings(sync: SyncSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    settings.update(|s| s.sync = sync)?;
+    Ok(())
+}
+
+/// When settings were last pushed to and pulled from the synced file
+#[tauri::command]
+pub fn get_sync_status(settings: State<'_, SettingsStore>, state: State<'_, SyncState>) -> SyncStatus {
+    let config = settings.get().sync;
+    SyncStatus {
+        enabled: config.enabled,
+        path: config.path,
+        ..state.status.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle_path(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blendmate-sync-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("settings.json")
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let bytes = [0x00, 0x0f, 0xa5, 0xff];
+        assert_eq!(to_hex(&bytes), "000fa5ff");
+        assert_eq!(from_hex("000fa5ff").unwrap(), bytes);
+        assert_eq!(from_hex("000FA5FF").unwrap(), bytes);
+    }
+
+    #[test]
+    fn from_hex_rejects_bad_input() {
+        assert_eq!(from_hex("abc"), None);
+        assert_eq!(from_hex("zz"), None);
+        assert_eq!(from_hex("éé"), None);
+    }
+
+    #[test]
+    fn signed_bundle_reads_back() {
+        let path = bundle_path("signed");
+        let names = write_bundle(&path, &Settings::default(), b"studio key").unwrap();
+        let payload = read_bundle(&path, b"studio key").unwrap();
+        assert_eq!(payload.categories.keys().cloned().collect::<Vec<_>>(), names);
+        assert!(NEVER_SYNCED.iter().all(|category| !payload.categories.contains_key(*category)));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn wrong_key_or_changed_bundle_is_refused() {
+        let path = bundle_path("refused");
+        write_bundle(&path, &Settings::default(), b"studio key").unwrap();
+        assert!(matches!(read_bundle(&path, b"other key"), Err(CommandError::InvalidInput(_))));
+
+        let text = fs::read_to_string(&path).unwrap();
+        let mut bundle: Value = serde_json::from_str(&text).unwrap();
+        bundle["machine"] = Value::String("intruder".to_string());
+        fs::write(&path, bundle.to_string()).unwrap();
+        assert!(matches!(read_bundle(&path, b"studio key"), Err(CommandError::InvalidInput(_))));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
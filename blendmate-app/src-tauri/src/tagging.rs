@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::history::HistoryStore;
+use crate::protocol;
+use crate::settings::SettingsStore;
+
+/// Structured metadata from a file path, e.g. `show` → `brisk`,
+/// `shot` → `sh010`
+pub type Tags = BTreeMap<String, String>;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TaggingSettings {
+    /// Tried in order; the first that matches the end of the path wins.
+    /// `{name}` matches one or more characters within a folder or file
+    /// name and becomes the tag `name`. A field used twice must match the
+    /// same text both times.
+    pub patterns: Vec<String>,
+}
+
+impl Default for TaggingSettings {
+    fn default() -> Self {
+        Self {
+            patterns: vec![
+                "{show}/{sequence}/{shot}/{shot}_v{version}.blend".to_string(),
+                "{sequence}_{shot}_v{version}.blend".to_string(),
+            ],
+        }
+    }
+}
+
+enum Token {
+    Literal(String),
+    Field(String),
+}
+
+fn parse(pattern: &str) -> Result<Vec<Token>, CommandError> {
+    let invalid = |reason: &str| CommandError::InvalidInput(format!("Invalid pattern '{}': {}", pattern, reason));
+    let mut tokens = Vec::new();
+    let mut rest = pattern;
+    while !rest.is_empty() {
+        match rest.find(['{', '}']) {
+            Some(index) if rest[index..].starts_with('}') => return Err(invalid("unmatched '}'")),
+            Some(index) => {
+                if index > 0 {
+                    tokens.push(Token::Literal(rest[..index].to_string()));
+                }
+                let end = rest[index..].find('}').ok_or_else(|| invalid("unclosed '{'"))? + index;
+                let name = &rest[index + 1..end];
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(invalid("field names are letters, digits and underscores"));
+                }
+                if matches!(tokens.last(), Some(Token::Field(_))) {
+                    return Err(invalid("two fields need text between them"));
+                }
+                tokens.push(Token::Field(name.to_string()));
+                rest = &rest[end + 1..];
+            }
+            None => {
+                tokens.push(Token::Literal(rest.to_string()));
+                rest = "";
+            }
+        }
+    }
+    if !tokens.iter().any(|token| matches!(token, Token::Field(_))) {
+        return Err(invalid("it has no fields"));
+    }
+    Ok(tokens)
+}
+
+fn match_tokens(tokens: &[Token], text: &str, tags: &mut Tags) -> bool {
+    let Some((first, rest)) = tokens.split_first() else {
+        return text.is_empty();
+    };
+    match first {
+        Token::Literal(literal) => text
+            .strip_prefix(literal.as_str())
+            .is_some_and(|text| match_tokens(rest, text, tags)),
+        Token::Field(name) => {
+            if let Some(value) = tags.get(name).cloned() {
+                return text.strip_prefix(value.as_str()).is_some_and(|text| match_tokens(rest, text, tags));
+            }
+            // Shortest value first, never across folders
+            let ends = text.char_indices().map(|(index, _)| index).skip(1).chain([text.len()]);
+            for end in ends.filter(|end| *end > 0) {
+                let value = &text[..end];
+                if value.contains('/') {
+                    break;
+                }
+                tags.insert(name.clone(), value.to_string());
+                if match_tokens(rest, &text[end..], tags) {
+                    return true;
+                }
+                tags.remove(name);
+            }
+            false
+        }
+    }
+}
+
+/// Tags from the end of `path` under `pattern`
+fn match_pattern(tokens: &[Token], path: &str) -> Option<Tags> {
+    let path = path.replace('\\', "/");
+    let starts = [0].into_iter().chain(path.match_indices('/').map(|(index, _)| index + 1));
+    starts.find_map(|start| {
+        let mut tags = Tags::new();
+        match_tokens(tokens, &path[start..], &mut tags).then_some(tags)
+    })
+}
+
+/// Tags of `path` under the first pattern that matches it, and that pattern
+pub fn tags_for(patterns: &[String], path: &str) -> Option<(Tags, String)> {
+    patterns.iter().find_map(|pattern| {
+        let tokens = parse(pattern).ok()?;
+        Some((match_pattern(&tokens, path)?, pattern.clone()))
+    })
+}
+
+/// Whether `tags` has every tag in `filter`
+pub fn matches(tags: &Tags, filter: Option<&Tags>) -> bool {
+    filter.is_none_or(|filter| filter.iter().all(|(name, value)| tags.get(name) == Some(value)))
+}
+
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct SessionTags {
+    /// File open in Blender; unset while it is unsaved
+    pub filepath: Option<String>,
+    pub tags: Tags,
+    /// Pattern the tags come from
+    pub pattern: Option<String>,
+}
+
+/// Tags of the file open in Blender, emitted as `session:tags` when they
+/// change
+#[derive(Default)]
+pub struct TagState {
+    current: Mutex<SessionTags>,
+}
+
+impl TagState {
+    pub fn current(&self) -> SessionTags {
+        self.current.lock().unwrap().clone()
+    }
+}
+
+/// Follow the open file from heartbeats and file events
+pub fn observe<R: Runtime>(app: &AppHandle<R>, message: &Value) {
+    if !matches!(
+        protocol::message_type(message).as_deref(),
+        Some("heartbeat" | "event.scene.file_loaded" | "event.scene.file_saved")
+    ) {
+        return;
+    }
+    let Some(filepath) = protocol::message_body(message).get("filepath").and_then(Value::as_str) else {
+        return;
+    };
+    let filepath = (!filepath.is_empty() && filepath != "(unsaved)").then(|| filepath.to_string());
+    let state = app.state::<TagState>();
+    if state.current.lock().unwrap().filepath == filepath {
+        return;
+    }
+    let patterns = app.state::<SettingsStore>().get().tagging.patterns;
+    let (tags, pattern) = filepath
+        .as_deref()
+        .and_then(|path| tags_for(&patterns, path))
+        .map_or((Tags::new(), None), |(tags, pattern)| (tags, Some(pattern)));
+    let session = SessionTags {
+        filepath,
+        tags,
+        pattern,
+    };
+    *state.current.lock().unwrap() = session.clone();
+    app.state::<HistoryStore>().set_tags(session.tags.clone());
+    if let Err(err) = app.emit("session:tags", &session) {
+        eprintln!("Failed to emit session:tags: {err}");
+    }
+}
+
+#[tauri::command]
+pub fn get_session_tags(tags: State<'_, TagState>) -> SessionTags {
+    tags.current()
+}
+
+/// Tags `path` gets under `patterns` (default: the configured ones), to
+/// try patterns out before saving them
+#[tauri::command]
+pub fn test_tag_patterns(
+    path: String,
+    patterns: Option<Vec<String>>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Option<Tags>, CommandError> {
+    let patterns = patterns.unwrap_or_else(|| settings.get().tagging.patterns);
+    for pattern in &patterns {
+        parse(pattern)?;
+    }
+    Ok(tags_for(&patterns, &path).map(|(tags, _)| tags))
+}
+
+#[tauri::command]
+pub fn get_tagging_settings(settings: State<'_, SettingsStore>) -> TaggingSettings {
+    settings.get().tagging
+}
+
+/// Takes effect when the next file is opened
+#[tauri::command]
+pub fn set_tagging_settings(tagging: TaggingSettings, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    for pattern in &tagging.patterns {
+        parse(pattern)?;
+    }
+    settings.update(|s| s.tagging = tagging)?;
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::watch;
+
+use crate::errors::CommandError;
+use crate::workers;
+
+/// Finished tasks kept for `list_tasks`; older ones are dropped.
+const MAX_FINISHED: usize = 100;
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    AssetIndex,
+    /// A worker pool job (export, upgrade or render)
+    Job,
+    Diagnostics,
+    /// Encoding a session timelapse with ffmpeg
+    Timelapse,
+    /// Post-processing a landed frame before delivery
+    PostProcess,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+/// A long-running backend operation, emitted as `task:progress` whenever
+/// it changes
+#[derive(Serialize, Clone, Debug)]
+pub struct Task {
+    pub id: String,
+    pub kind: TaskKind,
+    pub label: String,
+    pub status: TaskStatus,
+    /// 0 to 1; `None` while the operation can't tell
+    pub progress: Option<f32>,
+    pub message: Option<String>,
+    pub result: Option<Value>,
+    pub error: Option<CommandError>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+struct TaskEntry {
+    task: Task,
+    cancel: watch::Sender<bool>,
+}
+
+/// Every long-running operation registers here, so the frontend has one
+/// progress and cancel model for all of them.
+#[derive(Default)]
+pub struct TaskManager {
+    tasks: Mutex<HashMap<String, TaskEntry>>,
+    next_id: AtomicU64,
+}
+
+impl TaskManager {
+    /// Register a running task. The caller reports progress and the outcome
+    /// through the returned handle.
+    pub fn start<R: Runtime>(&self, app: &AppHandle<R>, kind: TaskKind, label: impl Into<String>) -> TaskHandle<R> {
+        let id = format!("task-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let (cancel, cancelled) = watch::channel(false);
+        let task = Task {
+            id: id.clone(),
+            kind,
+            label: label.into(),
+            status: TaskStatus::Running,
+            progress: None,
+            message: None,
+            result: None,
+            error: None,
+            started_at: chrono::Local::now().to_rfc3339(),
+            finished_at: None,
+        };
+
+        {
+            let mut tasks = self.tasks.lock().unwrap();
+            prune(&mut tasks);
+            tasks.insert(id.clone(), TaskEntry { task: task.clone(), cancel });
+        }
+        emit(app, &task);
+
+        TaskHandle {
+            app: app.clone(),
+            id,
+            cancelled,
+        }
+    }
+
+    pub fn list(&self) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self.tasks.lock().unwrap().values().map(|entry| entry.task.clone()).collect();
+        tasks.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        tasks
+    }
+
+    /// Ask a task to stop. It is marked cancelled once its work has wound down.
+    pub fn cancel(&self, id: &str) -> Result<(), CommandError> {
+        let tasks = self.tasks.lock().unwrap();
+        let entry = tasks
+            .get(id)
+            .ok_or_else(|| CommandError::NotFound(format!("Unknown task '{}'", id)))?;
+        if entry.task.status != TaskStatus::Running {
+            return Err(CommandError::InvalidInput(format!("Task '{}' already finished", id)));
+        }
+        entry.cancel.send_replace(true);
+        Ok(())
+    }
+
+    /// Report progress of a running task
+    pub fn progress<R: Runtime>(&self, app: &AppHandle<R>, id: &str, progress: Option<f32>, message: Option<String>) {
+        self.update(app, id, |task| {
+            task.progress = progress.map(|p| p.clamp(0.0, 1.0));
+            task.message = message;
+        });
+    }
+
+    fn update<R: Runtime>(&self, app: &AppHandle<R>, id: &str, change: impl FnOnce(&mut Task)) {
+        let task = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let Some(entry) = tasks.get_mut(id) else {
+                return;
+            };
+            change(&mut entry.task);
+            entry.task.clone()
+        };
+        emit(app, &task);
+    }
+}
+
+/// Drop the oldest finished tasks beyond `MAX_FINISHED`
+fn prune(tasks: &mut HashMap<String, TaskEntry>) {
+    let mut finished: Vec<(String, String)> = tasks
+        .values()
+        .filter(|entry| entry.task.status != TaskStatus::Running)
+        .map(|entry| (entry.task.started_at.clone(), entry.task.id.clone()))
+        .collect();
+    if finished.len() < MAX_FINISHED {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() + 1 - MAX_FINISHED) {
+        tasks.remove(id);
+    }
+}
+
+fn emit<R: Runtime>(app: &AppHandle<R>, task: &Task) {
+    if let Err(err) = app.emit("task:progress", task) {
+        eprintln!("Failed to emit task:progress: {err}");
+    }
+}
+
+/// Held by the code doing a task's work
+pub struct TaskHandle<R: Runtime> {
+    app: AppHandle<R>,
+    id: String,
+    cancelled: watch::Receiver<bool>,
+}
+
+impl<R: Runtime> TaskHandle<R> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Resolves once `cancel_task` is called for this task
+    pub async fn cancelled(&self) {
+        workers::cancelled(&mut self.cancelled.clone()).await
+    }
+
+    pub fn progress(&self, progress: Option<f32>, message: Option<String>) {
+        self.app.state::<TaskManager>().progress(&self.app, &self.id, progress, message);
+    }
+
+    /// Run `work` until it finishes, or fail with `cancelled` as soon as
+    /// the task is cancelled. `work` is dropped on cancel.
+    pub async fn run<T>(&self, work: impl Future<Output = Result<T, CommandError>>) -> Result<T, CommandError> {
+        tokio::select! {
+            result = work => result,
+            _ = self.cancelled() => Err(CommandError::Cancelled("Cancelled".to_string())),
+        }
+    }
+
+    /// Record the outcome. A task that was asked to stop finishes as
+    /// cancelled whatever its work returned.
+    pub fn finish<T: Serialize>(self, result: &Result<T, CommandError>) {
+        let status = match result {
+            _ if self.is_cancelled() => TaskStatus::Cancelled,
+            Err(CommandError::Cancelled(_)) => TaskStatus::Cancelled,
+            Ok(_) => TaskStatus::Done,
+            Err(_) => TaskStatus::Failed,
+        };
+        self.app.state::<TaskManager>().update(&self.app, &self.id, |task| {
+            task.status = status;
+            if status == TaskStatus::Done {
+                task.progress = Some(1.0);
+            }
+            match result {
+                Ok(value) => task.result = serde_json::to_value(value).ok(),
+                Err(error) => task.error = Some(error.clone()),
+            }
+            task.finished_at = Some(chrono::Local::now().to_rfc3339());
+        });
+    }
+}
+
+#[tauri::command]
+pub fn list_tasks(tasks: State<'_, TaskManager>) -> Vec<Task> {
+    tasks.list()
+}
+
+#[tauri::command]
+pub fn cancel_task(id: String, tasks: State<'_, TaskManager>) -> Result<(), CommandError> {
+    tasks.cancel(&id)
+}
@@ -0,0 +1,374 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::{watch, Mutex};
+
+use crate::errors::CommandError;
+use crate::ffmpeg;
+use crate::settings::SettingsStore;
+use crate::tagging::{self, TagState, Tags};
+use crate::tasks::{TaskKind, TaskManager};
+use crate::workers;
+use crate::AppState;
+
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait before retrying while Blender is unreachable
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+const MIN_INTERVAL_SECS: f64 = 1.0;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TimelapseSettings {
+    /// Seconds between captures
+    pub interval_secs: f64,
+    /// Width of captured frames; height follows the viewport
+    pub width: u32,
+    pub quality: u8,
+    /// Frame rate of assembled videos
+    pub fps: f64,
+    /// Assemble the video when a timelapse is stopped
+    pub assemble_on_stop: bool,
+}
+
+impl Default for TimelapseSettings {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30.0,
+            width: 1280,
+            quality: 85,
+            fps: 24.0,
+            assemble_on_stop: true,
+        }
+    }
+}
+
+/// A recorded timelapse, stored as `session.json` next to its frames
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimelapseSession {
+    pub id: String,
+    pub started_at: String,
+    /// Unset while recording, or if the app quit without stopping
+    pub stopped_at: Option<String>,
+    pub interval_secs: f64,
+    /// Frames written to disk
+    pub frames: u32,
+    /// Captures skipped because nothing changed since the previous frame
+    pub duplicates: u32,
+    /// Size of the first frame; the video is made at this size
+    pub width: u32,
+    pub height: u32,
+    /// Last assembled video
+    pub video: Option<String>,
+    /// Tags of the file open when recording started
+    #[serde(default)]
+    pub tags: Tags,
+}
+
+struct Recording {
+    id: String,
+    stop: watch::Sender<bool>,
+    /// Capture loop; returns the session once stopped
+    capture: JoinHandle<TimelapseSession>,
+}
+
+/// Timelapses under `timelapse/` in the app data directory, one directory
+/// of numbered JPEG frames per session, and the one being recorded.
+pub struct TimelapseState {
+    root: PathBuf,
+    recording: Mutex<Option<Recording>>,
+}
+
+impl TimelapseState {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            recording: Mutex::new(None),
+        }
+    }
+
+    pub fn dir(&self, id: &str) -> PathBuf {
+        self.root.join(id)
+    }
+
+    pub fn load(&self, id: &str) -> Result<TimelapseSession, CommandError> {
+        let text = fs::read_to_string(self.dir(id).join("session.json"))
+            .map_err(|_| CommandError::NotFound(format!("Unknown timelapse '{}'", id)))?;
+        serde_json::from_str(&text).map_err(|e| CommandError::Failed(format!("Invalid timelapse '{}': {}", id, e)))
+    }
+
+    async fn is_recording(&self, id: &str) -> bool {
+        self.recording.lock().await.as_ref().is_some_and(|recording| recording.id == id)
+    }
+}
+
+fn save(dir: &Path, session: &TimelapseSession) -> Result<(), CommandError> {
+    let text = serde_json::to_string_pretty(session).map_err(|e| format!("Failed to serialize timelapse: {}", e))?;
+    fs::write(dir.join("session.json"), text)
+        .map_err(|e| CommandError::Io(format!("Failed to write timelapse session: {}", e)))
+}
+
+/// Capture the viewport every `interval_secs` until stopped. A frame equal
+/// to the previous one is counted but not stored, so idle time costs nothing.
+async fn capture<R: Runtime>(
+    app: AppHandle<R>,
+    dir: PathBuf,
+    mut session: TimelapseSession,
+    width: u32,
+    quality: u8,
+    mut stopped: watch::Receiver<bool>,
+) -> TimelapseSession {
+    let interval = Duration::from_secs_f64(session.interval_secs);
+    let mut previous: Option<u64> = None;
+
+    while !*stopped.borrow() {
+        let params = json!({ "width": width, "quality": quality });
+        let result = app
+            .state::<AppState>()
+            .bridge
+            .request_with_timeout("viewport.capture", "", params, CAPTURE_TIMEOUT)
+            .await;
+        let delay = match result {
+            Ok(data) => {
+                if store_frame(&dir, &mut session, &mut previous, &data).await {
+                    if let Err(err) = save(&dir, &session) {
+                        eprintln!("{}", err);
+                    }
+                    if let Err(err) = app.emit("timelapse:changed", &session) {
+                        eprintln!("Failed to emit timelapse:changed: {err}");
+                    }
+                }
+                interval
+            }
+            // Not connected or no viewport; try again soon
+            Err(_) => RETRY_DELAY.min(interval),
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = workers::cancelled(&mut stopped) => break,
+        }
+    }
+    session
+}
+
+/// Write a captured frame unless it equals the previous one. Returns
+/// whether the session changed.
+async fn store_frame(dir: &Path, session: &mut TimelapseSession, previous: &mut Option<u64>, data: &Value) -> bool {
+    let image = data.get("image").and_then(Value::as_str).unwrap_or_default();
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(image) else {
+        return false;
+    };
+    if bytes.is_empty() {
+        return false;
+    }
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let hash = hasher.finish();
+    if *previous == Some(hash) {
+        session.duplicates += 1;
+        return true;
+    }
+
+    let path = dir.join(format!("frame_{:06}.jpg", session.frames));
+    if let Err(err) = tokio::fs::write(&path, &bytes).await {
+        eprintln!("Failed to write timelapse frame {}: {err}", path.display());
+        return false;
+    }
+    if session.frames == 0 {
+        session.width = data.get("width").and_then(Value::as_u64).unwrap_or_default() as u32;
+        session.height = data.get("height").and_then(Value::as_u64).unwrap_or_default() as u32;
+    }
+    session.frames += 1;
+    *previous = Some(hash);
+    true
+}
+
+/// Encode a session's frames into a video, tracked as a task. The video
+/// goes to `output`, or into the session directory.
+async fn assemble<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    fps: Option<f64>,
+    output: Option<String>,
+) -> Result<String, CommandError> {
+    let timelapse = app.state::<TimelapseState>();
+    if timelapse.is_recording(id).await {
+        return Err(CommandError::InvalidInput("Stop the timelapse before assembling it".to_string()));
+    }
+    let mut session = timelapse.load(id)?;
+    if session.frames == 0 {
+        return Err(CommandError::InvalidInput(format!("Timelapse '{}' has no frames", id)));
+    }
+    let dir = timelapse.dir(id);
+    let output = output.map(PathBuf::from).unwrap_or_else(|| dir.join("timelapse.mp4"));
+    let settings = app.state::<SettingsStore>();
+    let fps = fps.unwrap_or(settings.get().timelapse.fps).max(0.1);
+
+    let task = app
+        .state::<TaskManager>()
+        .start(app, TaskKind::Timelapse, format!("Assemble timelapse {}", id));
+    task.progress(None, Some(format!("Encoding {} frames", session.frames)));
+    let result = task
+        .run(ffmpeg::encode_sequence(
+            &settings,
+            &dir.join("frame_%06d.jpg"),
+            fps,
+            session.width,
+            session.height,
+            &output,
+        ))
+        .await
+        .and_then(|()| {
+            session.video = Some(output.to_string_lossy().into_owned());
+            save(&dir, &session)?;
+            Ok(output.to_string_lossy().into_owned())
+        });
+    task.finish(&result);
+    result
+}
+
+/// Start recording a timelapse of the viewport. `interval_secs` and
+/// `width` default to the timelapse settings.
+#[tauri::command]
+pub async fn start_timelapse<R: Runtime>(
+    interval_secs: Option<f64>,
+    width: Option<u32>,
+    app: AppHandle<R>,
+    timelapse: State<'_, TimelapseState>,
+    settings: State<'_, SettingsStore>,
+) -> Result<TimelapseSession, CommandError> {
+    let mut recording = timelapse.recording.lock().await;
+    if let Some(current) = recording.as_ref() {
+        return Err(CommandError::InvalidInput(format!("Timelapse '{}' is already recording", current.id)));
+    }
+    let defaults = settings.get().timelapse;
+    let interval_secs = interval_secs.unwrap_or(defaults.interval_secs);
+    if interval_secs < MIN_INTERVAL_SECS {
+        return Err(CommandError::InvalidInput(format!(
+            "Interval must be at least {} second",
+            MIN_INTERVAL_SECS
+        )));
+    }
+
+    let now = chrono::Local::now();
+    let session = TimelapseSession {
+        id: format!("timelapse-{}", now.format("%Y%m%d-%H%M%S")),
+        started_at: now.to_rfc3339(),
+        stopped_at: None,
+        interval_secs,
+        frames: 0,
+        duplicates: 0,
+        width: 0,
+        height: 0,
+        video: None,
+        tags: app.state::<TagState>().current().tags,
+    };
+    let dir = timelapse.dir(&session.id);
+    fs::create_dir_all(&dir).map_err(|e| CommandError::Io(format!("Failed to create timelapse directory: {}", e)))?;
+    save(&dir, &session)?;
+
+    let (stop, stopped) = watch::channel(false);
+    let width = width.unwrap_or(defaults.width);
+    let capture = tauri::async_runtime::spawn(capture(app, dir, session.clone(), width, defaults.quality, stopped));
+    *recording = Some(Recording {
+        id: session.id.clone(),
+        stop,
+        capture,
+    });
+    Ok(session)
+}
+
+/// Stop recording. With `assemble` (default: the `assemble_on_stop`
+/// setting) the video is encoded in the background as a task.
+#[tauri::command]
+pub async fn stop_timelapse<R: Runtime>(
+    assemble: Option<bool>,
+    app: AppHandle<R>,
+    timelapse: State<'_, TimelapseState>,
+    settings: State<'_, SettingsStore>,
+) -> Result<TimelapseSession, CommandError> {
+    let recording = timelapse
+        .recording
+        .lock()
+        .await
+        .take()
+        .ok_or_else(|| CommandError::InvalidInput("No timelapse is recording".to_string()))?;
+    recording.stop.send_replace(true);
+    let mut session = recording
+        .capture
+        .await
+        .map_err(|e| format!("Timelapse capture failed: {}", e))?;
+    session.stopped_at = Some(chrono::Local::now().to_rfc3339());
+    save(&timelapse.dir(&session.id), &session)?;
+    if let Err(err) = app.emit("timelapse:changed", &session) {
+        eprintln!("Failed to emit timelapse:changed: {err}");
+    }
+
+    if assemble.unwrap_or(settings.get().timelapse.assemble_on_stop) && session.frames > 0 {
+        let id = session.id.clone();
+        tauri::async_runtime::spawn(async move {
+            // Failures are recorded on the task
+            let _ = self::assemble(&app, &id, None, None).await;
+        });
+    }
+    Ok(session)
+}
+
+/// Recorded timelapses, newest first; only those with all of `tags` when
+/// given
+#[tauri::command]
+pub fn list_timelapses(tags: Option<Tags>, timelapse: State<'_, TimelapseState>) -> Vec<TimelapseSession> {
+    let Ok(entries) = fs::read_dir(&timelapse.root) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<TimelapseSession> = entries
+        .flatten()
+        .filter_map(|entry| timelapse.load(&entry.file_name().to_string_lossy()).ok())
+        .filter(|session| tagging::matches(&session.tags, tags.as_ref()))
+        .collect();
+    sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    sessions
+}
+
+/// Encode a stopped timelapse into a video; returns the video path
+#[tauri::command]
+pub async fn assemble_timelapse<R: Runtime>(
+    id: String,
+    fps: Option<f64>,
+    output: Option<String>,
+    app: AppHandle<R>,
+) -> Result<String, CommandError> {
+    assemble(&app, &id, fps, output).await
+}
+
+#[tauri::command]
+pub async fn delete_timelapse(id: String, timelapse: State<'_, TimelapseState>) -> Result<(), CommandError> {
+    if timelapse.is_recording(&id).await {
+        return Err(CommandError::InvalidInput("Stop the timelapse before deleting it".to_string()));
+    }
+    timelapse.load(&id)?;
+    fs::remove_dir_all(timelapse.dir(&id)).map_err(|e| CommandError::Io(format!("Failed to delete timelapse: {}", e)))
+}
+
+#[tauri::command]
+pub fn get_timelapse_settings(settings: State<'_, SettingsStore>) -> TimelapseSettings {
+    settings.get().timelapse
+}
+
+#[tauri::command]
+pub fn set_timelapse_settings(
+    timelapse: TimelapseSettings,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), CommandError> {
+    settings.update(|s| s.timelapse = timelapse)?;
+    Ok(())
+}
@@ -0,0 +1,527 @@
+use chrono::Timelike;
+#[cfg(feature = "midi")]
+use midir::{Ignore, MidiInput};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::net::UdpSocket;
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+/// How often Blender's playhead is compared with the clock
+const TICK: Duration = Duration::from_millis(250);
+
+/// How often the NTP server is asked, and asked again after a failure
+const NTP_INTERVAL: Duration = Duration::from_secs(64);
+const NTP_RETRY: Duration = Duration::from_secs(10);
+const NTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Seconds from the NTP epoch (1900) to the Unix epoch
+const NTP_UNIX_OFFSET: f64 = 2_208_988_800.0;
+
+/// Timecode counts as stopped when no quarter frame came for this long
+const MTC_STOPPED: Duration = Duration::from_millis(250);
+
+/// How often a MIDI port that failed to open is tried again
+const MTC_RETRY: Duration = Duration::from_secs(5);
+
+/// MTC rate codes; 29.97 drop-frame counts in its nominal 30 fps labels
+const MTC_RATES: [f64; 4] = [24.0, 25.0, 30.0, 30.0];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClockSource {
+    /// MIDI timecode from an input port, e.g. from an LTC-to-MTC converter
+    Mtc { port: String },
+    /// Time of day from this machine's clock, corrected by an NTP server
+    Ntp { server: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TimeSyncSettings {
+    pub enabled: bool,
+    pub source: ClockSource,
+    /// Clock time at which the first frame of the scene plays,
+    /// `HH:MM:SS:FF` at the scene's frame rate
+    pub start_timecode: String,
+    /// Wrap around the frame range, for installations that loop; otherwise
+    /// playback holds on the last frame
+    pub loop_range: bool,
+    /// Drift tolerated before Blender's playhead is moved
+    pub tolerance_frames: f64,
+}
+
+impl Default for TimeSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source: ClockSource::Ntp {
+                server: "pool.ntp.org".to_string(),
+            },
+            start_timecode: "00:00:00:00".to_string(),
+            loop_range: true,
+            tolerance_frames: 1.0,
+        }
+    }
+}
+
+/// Emitted as `timesync:drift` on every comparison while sync is enabled
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DriftMetrics {
+    /// Whether the clock is known, i.e. timecode was received or the NTP
+    /// server answered
+    pub locked: bool,
+    /// Whether the clock runs; MIDI timecode stops with its source
+    pub running: bool,
+    /// Clock time as `HH:MM:SS:FF` at the scene's frame rate
+    pub clock: Option<String>,
+    pub target_frame: Option<f64>,
+    pub blender_frame: Option<f64>,
+    /// Frames Blender is ahead of the clock, behind when negative
+    pub drift_frames: Option<f64>,
+    pub mean_abs_drift: f64,
+    pub max_abs_drift: f64,
+    pub samples: u64,
+    /// Times the playhead was moved or playback started or stopped
+    pub corrections: u64,
+    pub last_correction: Option<String>,
+    /// How far this machine's clock is behind the NTP server
+    pub ntp_offset_ms: Option<f64>,
+    pub ntp_delay_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+struct ClockReading {
+    /// Seconds since midnight, or of timecode
+    secs: f64,
+    running: bool,
+}
+
+#[derive(Clone, Copy)]
+struct NtpSample {
+    /// Seconds to add to the local clock
+    offset: f64,
+    delay: f64,
+}
+
+/// Decodes MIDI timecode quarter frames and full-frame messages
+#[derive(Default)]
+struct MtcClock {
+    pieces: [u8; 8],
+    /// Quarter frame pieces received since the last full timecode
+    seen: u8,
+    /// Latest timecode in seconds, and when it was current
+    position: Option<(f64, Instant)>,
+    quarter_at: Option<Instant>,
+}
+
+fn timecode_secs(hours: u8, minutes: u8, seconds: u8, frames: f64, rate: f64) -> f64 {
+    f64::from(hours) * 3600.0 + f64::from(minutes) * 60.0 + f64::from(seconds) + frames / rate
+}
+
+impl MtcClock {
+    fn receive(&mut self, message: &[u8]) {
+        match *message {
+            [0xF1, data] => {
+                let piece = usize::from((data >> 4) & 0x07);
+                let now = Instant::now();
+                self.pieces[piece] = data & 0x0F;
+                self.seen |= 1 << piece;
+                self.quarter_at = Some(now);
+                if piece == 7 && self.seen == 0xFF {
+                    self.seen = 0;
+                    let p = self.pieces;
+                    let rate = MTC_RATES[usize::from((p[7] >> 1) & 0x03)];
+                    let frames = p[0] | ((p[1] & 0x01) << 4);
+                    let seconds = p[2] | ((p[3] & 0x03) << 4);
+                    let minutes = p[4] | ((p[5] & 0x03) << 4);
+                    let hours = p[6] | ((p[7] & 0x01) << 4);
+                    // The eight quarter frames take two frames to send
+                    let secs = timecode_secs(hours, minutes, seconds, f64::from(frames) + 2.0, rate);
+                    self.position = Some((secs, now));
+                }
+            }
+            // Full frame, sent when the source locates rather than runs
+            [0xF0, 0x7F, _, 0x01, 0x01, hours, minutes, seconds, frames, 0xF7] => {
+                let rate = MTC_RATES[usize::from((hours >> 5) & 0x03)];
+                let secs = timecode_secs(hours & 0x1F, minutes, seconds, f64::from(frames), rate);
+                self.position = Some((secs, Instant::now()));
+                self.quarter_at = None;
+                self.seen = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn reading(&self) -> Option<ClockReading> {
+        let (secs, at) = self.position?;
+        let running = self.quarter_at.is_some_and(|at| at.elapsed() < MTC_STOPPED);
+        Some(ClockReading {
+            secs: if running { secs + at.elapsed().as_secs_f64() } else { secs },
+            running,
+        })
+    }
+}
+
+struct MtcPort {
+    name: String,
+    /// Dropping it closes the port; unset when the port failed to open
+    stop: Option<mpsc::Sender<()>>,
+    error: Option<CommandError>,
+    opened_at: Instant,
+}
+
+#[derive(Default)]
+pub struct TimeSync {
+    mtc: Arc<Mutex<MtcClock>>,
+    port: Mutex<Option<MtcPort>>,
+    ntp: Mutex<Option<(String, NtpSample)>>,
+    metrics: Mutex<DriftMetrics>,
+}
+
+/// Open `name` on a thread that keeps the connection until the returned
+/// sender is dropped
+fn connect_mtc(name: &str, clock: Arc<Mutex<MtcClock>>) -> Result<mpsc::Sender<()>, CommandError> {
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let name = name.to_string();
+    std::thread::spawn(move || {
+        match open_midi_input(&name, move |message: &[u8]| clock.lock().unwrap().receive(message)) {
+            // Held until stopped; dropping it closes the port
+            Ok(_connection) => {
+                let _ = ready_tx.send(Ok(()));
+                let _ = stop_rx.recv();
+            }
+            Err(err) => {
+                let _ = ready_tx.send(Err(err));
+            }
+        }
+    });
+    match ready_rx.recv() {
+        Ok(Ok(())) => Ok(stop_tx),
+        Ok(Err(err)) => Err(CommandError::Failed(err)),
+        Err(_) => Err(CommandError::Failed("MIDI thread ended".to_string())),
+    }
+}
+
+/// Connect to the MIDI input `name` and pass it every message, timecode
+/// included, until the connection is dropped
+#[cfg(feature = "midi")]
+fn open_midi_input(name: &str, mut receive: impl FnMut(&[u8]) + Send + 'static) -> Result<impl Sized, String> {
+    let mut input = MidiInput::new("Blendmate").map_err(|e| e.to_string())?;
+    // Timecode is a system message, which is ignored by default
+    input.ignore(Ignore::None);
+    let port = input
+        .ports()
+        .into_iter()
+        .find(|port| input.port_name(port).is_ok_and(|port_name| port_name == name))
+        .ok_or_else(|| format!("MIDI port '{}' not found", name))?;
+    input
+        .connect(&port, "blendmate-mtc", move |_, message, _| receive(message), ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "midi"))]
+fn open_midi_input(name: &str, receive: impl FnMut(&[u8]) + Send + 'static) -> Result<(), String> {
+    let _ = (name, receive);
+    Err("This build has no MIDI support".to_string())
+}
+
+impl TimeSync {
+    /// Have the MIDI port of `config` open, and no other
+    fn keep_port(&self, config: &TimeSyncSettings) -> Result<(), CommandError> {
+        let wanted = match &config.source {
+            ClockSource::Mtc { port } if config.enabled => Some(port.as_str()),
+            _ => None,
+        };
+        let mut current = self.port.lock().unwrap();
+        if let Some(port) = current.as_ref() {
+            let retry = port.stop.is_none() && port.opened_at.elapsed() >= MTC_RETRY;
+            if Some(port.name.as_str()) == wanted && !retry {
+                return port.error.clone().map_or(Ok(()), Err);
+            }
+        }
+        *current = None;
+        *self.mtc.lock().unwrap() = MtcClock::default();
+        let Some(name) = wanted else {
+            return Ok(());
+        };
+        let (stop, error) = match connect_mtc(name, self.mtc.clone()) {
+            Ok(stop) => (Some(stop), None),
+            Err(err) => (None, Some(err)),
+        };
+        *current = Some(MtcPort {
+            name: name.to_string(),
+            stop,
+            error: error.clone(),
+            opened_at: Instant::now(),
+        });
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Close the MIDI port, e.g. when time sync is switched off
+    pub fn close(&self) {
+        *self.port.lock().unwrap() = None;
+        *self.mtc.lock().unwrap() = MtcClock::default();
+    }
+
+    fn reading(&self, config: &TimeSyncSettings) -> Option<ClockReading> {
+        match &config.source {
+            ClockSource::Mtc { .. } => self.mtc.lock().unwrap().reading(),
+            ClockSource::Ntp { server } => {
+                let ntp = self.ntp.lock().unwrap();
+                let (_, sample) = ntp.as_ref().filter(|(queried, _)| queried == server)?;
+                let now = chrono::Local::now() + chrono::Duration::microseconds((sample.offset * 1e6) as i64);
+                Some(ClockReading {
+                    secs: f64::from(now.num_seconds_from_midnight()) + f64::from(now.nanosecond()) / 1e9,
+                    running: true,
+                })
+            }
+        }
+    }
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs_f64())
+        .unwrap_or_default()
+}
+
+fn ntp_timestamp(bytes: &[u8]) -> f64 {
+    let secs = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    f64::from(secs) + f64::from(fraction) / 4_294_967_296.0 - NTP_UNIX_OFFSET
+}
+
+/// One SNTP exchange with `server`
+async fn query_ntp(server: &str) -> Result<NtpSample, CommandError> {
+    let address = if server.contains(':') { server.to_string() } else { format!("{}:123", server) };
+    let io = |e: std::io::Error| CommandError::Io(format!("NTP server {}: {}", server, e));
+    let socket = UdpSocket::bind("0.0.0.0:0").await.map_err(io)?;
+    socket.connect(&address).await.map_err(io)?;
+    // Version 3, client mode
+    let mut request = [0u8; 48];
+    request[0] = 0x1B;
+    let sent = unix_now();
+    socket.send(&request).await.map_err(io)?;
+    let mut reply = [0u8; 48];
+    let received = tokio::time::timeout(NTP_TIMEOUT, socket.recv(&mut reply))
+        .await
+        .map_err(|_| CommandError::Failed(format!("NTP server {} did not answer", server)))?
+        .map_err(io)?;
+    let arrived = unix_now();
+    // Server mode, and not a kiss-of-death
+    if received < 48 || reply[0] & 0x07 != 4 || reply[1] == 0 {
+        return Err(CommandError::Failed(format!("Invalid answer from NTP server {}", server)));
+    }
+    let server_received = ntp_timestamp(&reply[32..40]);
+    let server_sent = ntp_timestamp(&reply[40..48]);
+    Ok(NtpSample {
+        offset: ((server_received - sent) + (server_sent - arrived)) / 2.0,
+        delay: (arrived - sent) - (server_sent - server_received),
+    })
+}
+
+/// `HH:MM:SS:FF` or `HH:MM:SS` in seconds
+fn parse_timecode(text: &str, fps: f64) -> Result<f64, CommandError> {
+    let invalid = || CommandError::InvalidInput(format!("Invalid timecode '{}', expected HH:MM:SS:FF", text));
+    let parts: Vec<f64> = text
+        .split([':', ';'])
+        .map(|part| part.trim().parse::<f64>().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    match parts[..] {
+        [hours, minutes, seconds] => Ok(hours * 3600.0 + minutes * 60.0 + seconds),
+        [hours, minutes, seconds, frames] => Ok(hours * 3600.0 + minutes * 60.0 + seconds + frames / fps),
+        _ => Err(invalid()),
+    }
+}
+
+fn format_timecode(secs: f64, fps: f64) -> String {
+    let frames = (secs * fps).floor().max(0.0) as u64;
+    let rate = (fps.round() as u64).max(1);
+    let whole = frames / rate;
+    format!("{:02}:{:02}:{:02}:{:02}", whole / 3600, whole / 60 % 60, whole % 60, frames % rate)
+}
+
+#[derive(Deserialize)]
+struct Timeline {
+    frame: i64,
+    #[serde(default)]
+    subframe: f64,
+    fps: f64,
+    frame_start: i64,
+    frame_end: i64,
+    playing: bool,
+}
+
+/// Where Blender should be at clock time `clock`, and whether it should play
+fn target(config: &TimeSyncSettings, timeline: &Timeline, clock: ClockReading) -> Result<(f64, bool), CommandError> {
+    let start = timeline.frame_start as f64;
+    let length = (timeline.frame_end - timeline.frame_start + 1).max(1) as f64;
+    let elapsed = (clock.secs - parse_timecode(&config.start_timecode, timeline.fps)?) * timeline.fps;
+    Ok(if elapsed < 0.0 {
+        (start, false)
+    } else if config.loop_range {
+        (start + elapsed % length, clock.running)
+    } else if elapsed >= length {
+        (timeline.frame_end as f64, false)
+    } else {
+        (start + elapsed, clock.running)
+    })
+}
+
+/// Compare Blender's playhead with the clock and correct it when it drifted
+/// too far
+async fn discipline<R: Runtime>(app: &AppHandle<R>, config: &TimeSyncSettings) -> Result<(), CommandError> {
+    let sync = app.state::<TimeSync>();
+    let bridge = &app.state::<AppState>().bridge;
+    let asked = Instant::now();
+    let state = bridge.request("timeline.get_state", "", json!({})).await?;
+    let timeline: Timeline =
+        serde_json::from_value(state).map_err(|e| format!("Invalid timeline state: {}", e))?;
+    // Blender read its playhead about halfway through the round trip
+    let latency = asked.elapsed().as_secs_f64() / 2.0;
+    let Some(mut clock) = sync.reading(config) else {
+        let mut metrics = sync.metrics.lock().unwrap();
+        metrics.locked = false;
+        metrics.running = false;
+        return Ok(());
+    };
+    if clock.running {
+        clock.secs -= latency;
+    }
+    let (target_frame, play) = target(config, &timeline, clock)?;
+    let blender_frame = timeline.frame as f64 + timeline.subframe;
+    let mut drift = blender_frame - target_frame;
+    if config.loop_range {
+        // Just before the loop point is close to just after it
+        let length = (timeline.frame_end - timeline.frame_start + 1).max(1) as f64;
+        drift = (drift + length / 2.0).rem_euclid(length) - length / 2.0;
+    }
+
+    {
+        let mut metrics = sync.metrics.lock().unwrap();
+        metrics.locked = true;
+        metrics.running = clock.running;
+        metrics.clock = Some(format_timecode(clock.secs, timeline.fps));
+        metrics.target_frame = Some(target_frame);
+        metrics.blender_frame = Some(blender_frame);
+        metrics.drift_frames = Some(drift);
+        metrics.mean_abs_drift =
+            (metrics.mean_abs_drift * metrics.samples as f64 + drift.abs()) / (metrics.samples + 1) as f64;
+        metrics.max_abs_drift = metrics.max_abs_drift.max(drift.abs());
+        metrics.samples += 1;
+    }
+
+    if play == timeline.playing && drift.abs() <= config.tolerance_frames {
+        return Ok(());
+    }
+    // Aim where the clock will be when the request arrives
+    let lead = if play { latency * timeline.fps } else { 0.0 };
+    bridge
+        .request("timeline.set", "", json!({ "frame": target_frame + lead, "play": play }))
+        .await?;
+    let mut metrics = sync.metrics.lock().unwrap();
+    metrics.corrections += 1;
+    metrics.last_correction = Some(chrono::Local::now().to_rfc3339());
+    Ok(())
+}
+
+/// Keep Blender's playback on the configured clock while time sync is
+/// enabled, and emit how far it drifts
+pub fn start<R: Runtime>(app: AppHandle<R>) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let mut next_ntp = Instant::now();
+        loop {
+            tokio::time::sleep(TICK).await;
+            let config = app.state::<SettingsStore>().get().time_sync;
+            let sync = app.state::<TimeSync>();
+            let mut error = sync.keep_port(&config).err();
+            if !config.enabled {
+                continue;
+            }
+            if let ClockSource::Ntp { server } = &config.source {
+                let stale = sync.ntp.lock().unwrap().as_ref().is_none_or(|(queried, _)| queried != server);
+                if stale || Instant::now() >= next_ntp {
+                    match query_ntp(server).await {
+                        Ok(sample) => {
+                            *sync.ntp.lock().unwrap() = Some((server.clone(), sample));
+                            next_ntp = Instant::now() + NTP_INTERVAL;
+                        }
+                        Err(err) => {
+                            error = Some(err);
+                            next_ntp = Instant::now() + NTP_RETRY;
+                        }
+                    }
+                }
+            }
+            if let Err(err) = discipline(&app, &config).await {
+                error = Some(err);
+            }
+            let metrics = {
+                let ntp = sync.ntp.lock().unwrap().as_ref().map(|(_, sample)| *sample);
+                let mut metrics = sync.metrics.lock().unwrap();
+                metrics.ntp_offset_ms = ntp.map(|sample| sample.offset * 1000.0);
+                metrics.ntp_delay_ms = ntp.map(|sample| sample.delay * 1000.0);
+                metrics.error = error.map(|err| err.to_string());
+                metrics.clone()
+            };
+            if let Err(err) = app.emit("timesync:drift", &metrics) {
+                eprintln!("Failed to emit timesync:drift: {err}");
+            }
+        }
+    })
+}
+
+#[tauri::command]
+pub fn get_time_sync_settings(settings: State<'_, SettingsStore>) -> TimeSyncSettings {
+    settings.get().time_sync
+}
+
+#[tauri::command]
+pub fn set_time_sync_settings(
+    time_sync: TimeSyncSettings,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), CommandError> {
+    parse_timecode(&time_sync.start_timecode, 30.0)?;
+    settings.update(|s| s.time_sync = time_sync)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_time_sync_metrics(sync: State<'_, TimeSync>) -> DriftMetrics {
+    sync.metrics.lock().unwrap().clone()
+}
+
+/// Start the drift statistics over, e.g. after changing the tolerance
+#[tauri::command]
+pub fn reset_time_sync_metrics(sync: State<'_, TimeSync>) {
+    *sync.metrics.lock().unwrap() = DriftMetrics::default();
+}
+
+/// MIDI input ports timecode can come from
+#[tauri::command]
+pub fn list_midi_ports() -> Result<Vec<String>, CommandError> {
+    #[cfg(feature = "midi")]
+    {
+        let input =
+            MidiInput::new("Blendmate").map_err(|e| CommandError::Failed(format!("MIDI unavailable: {}", e)))?;
+        Ok(input
+            .ports()
+            .iter()
+            .filter_map(|port| input.port_name(port).ok())
+            .collect())
+    }
+    #[cfg(not(feature = "midi"))]
+    {
+        Err(CommandError::Unsupported("This build has no MIDI support".to_string()))
+    }
+}
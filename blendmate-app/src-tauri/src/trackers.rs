@@ -0,0 +1,533 @@
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime, State};
+use tokio::sync::Mutex;
+
+use crate::errors::CommandError;
+use crate::projects;
+use crate::render_queue::{RenderRequest, SplitStrategy};
+use crate::secrets;
+use crate::settings::SettingsStore;
+use crate::shots::{self, ShotRenderOptions, ShotState};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Kitsu (CGWire) server used for shot metadata and task status
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct KitsuSettings {
+    /// Server root, e.g. `https://kitsu.studio.lan`; unset disables Kitsu
+    pub url: Option<String>,
+    pub email: String,
+    /// Kitsu project; the active project profile's `kitsu_project` wins
+    pub project: Option<String>,
+    /// Task type whose status `kitsu_set_task_status` changes by default
+    pub task_type: Option<String>,
+}
+
+/// Flamenco Manager that takes render jobs
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct FlamencoSettings {
+    /// Manager root, e.g. `http://flamenco.studio.lan:8080`; unset disables Flamenco
+    pub url: Option<String>,
+    pub job_type: String,
+    pub priority: i32,
+    /// Frames per Flamenco task when the request isn't chunked
+    pub chunk_size: usize,
+    /// Job settings added to every submission, e.g. `format` and
+    /// `image_file_extension`; they override the ones blendmate fills in
+    pub settings: Map<String, Value>,
+}
+
+impl Default for FlamencoSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            job_type: "simple-blender-render".to_string(),
+            priority: 50,
+            chunk_size: 3,
+            settings: Map::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct TrackerSettings {
+    pub kitsu: KitsuSettings,
+    pub flamenco: FlamencoSettings,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct KitsuTask {
+    pub id: String,
+    pub task_type: String,
+    pub status: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct KitsuShot {
+    pub id: String,
+    pub name: String,
+    pub sequence: Option<String>,
+    pub frame_in: Option<i64>,
+    pub frame_out: Option<i64>,
+    pub nb_frames: Option<i64>,
+    pub tasks: Vec<KitsuTask>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KitsuPushResult {
+    /// Shots whose frame range was written to Kitsu
+    pub updated: Vec<String>,
+    /// Shots with no Kitsu shot of the same name
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FlamencoJob {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub priority: Option<i64>,
+}
+
+/// Login token of the Kitsu server it was issued by
+#[derive(Default)]
+pub struct TrackerState {
+    kitsu_token: Mutex<Option<(String, String)>>,
+}
+
+fn http() -> Result<Client, CommandError> {
+    Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| CommandError::Failed(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Send a request; fails only when there was no response
+async fn send_raw(service: &str, request: RequestBuilder) -> Result<(StatusCode, String), CommandError> {
+    let response = request.send().await.map_err(|e| {
+        if e.is_timeout() {
+            CommandError::Timeout(format!("{} did not answer in time", service))
+        } else {
+            CommandError::Failed(format!("{} unreachable: {}", service, e))
+        }
+    })?;
+    let status = response.status();
+    Ok((status, response.text().await.unwrap_or_default()))
+}
+
+/// JSON body of a response, or the command error its status maps to
+fn parse(service: &str, status: StatusCode, text: &str) -> Result<Value, CommandError> {
+    if status.is_success() {
+        return Ok(serde_json::from_str(text).unwrap_or(Value::Null));
+    }
+    let message = serde_json::from_str::<Value>(text)
+        .ok()
+        .and_then(|body| ["message", "msg"].iter().find_map(|key| str_field(&body, key)))
+        .unwrap_or_else(|| status.to_string());
+    Err(match status {
+        StatusCode::NOT_FOUND => CommandError::NotFound(format!("{}: {}", service, message)),
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+            CommandError::InvalidInput(format!("{} refused the request: {}", service, message))
+        }
+        _ => CommandError::Failed(format!("{} error: {}", service, message)),
+    })
+}
+
+async fn send(service: &str, request: RequestBuilder) -> Result<Value, CommandError> {
+    let (status, text) = send_raw(service, request).await?;
+    parse(service, status, &text)
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// Calls to the configured Kitsu server. The login token is kept in
+/// `TrackerState` and renewed when the server rejects it.
+struct Kitsu<'a> {
+    http: Client,
+    base: String,
+    settings: &'a SettingsStore,
+    state: &'a TrackerState,
+}
+
+impl<'a> Kitsu<'a> {
+    fn new(settings: &'a SettingsStore, state: &'a TrackerState) -> Result<Self, CommandError> {
+        let base = settings
+            .get()
+            .trackers
+            .kitsu
+            .url
+            .map(|url| url.trim_end_matches('/').to_string())
+            .ok_or_else(|| CommandError::Unsupported("Kitsu is not configured".to_string()))?;
+        Ok(Self {
+            http: http()?,
+            base,
+            settings,
+            state,
+        })
+    }
+
+    /// Token for this server, logging in when there is none or `renew`
+    async fn token(&self, renew: bool) -> Result<String, CommandError> {
+        let mut token = self.state.kitsu_token.lock().await;
+        if let Some((url, stored)) = token.as_ref() {
+            if !renew && *url == self.base {
+                return Ok(stored.clone());
+            }
+        }
+
+        let password = secrets::get("kitsu.password")?
+            .ok_or_else(|| CommandError::InvalidInput("Set the Kitsu password first".to_string()))?;
+        let email = self.settings.get().trackers.kitsu.email;
+        let request = self
+            .http
+            .post(format!("{}/api/auth/login", self.base))
+            .json(&json!({ "email": email, "password": password }));
+        let (status, text) = send_raw("Kitsu", request).await?;
+        if status.is_client_error() {
+            return Err(CommandError::InvalidInput("Kitsu login failed; check the email and password".to_string()));
+        }
+        let access = str_field(&parse("Kitsu", status, &text)?, "access_token")
+            .ok_or_else(|| CommandError::Failed("Kitsu login returned no token".to_string()))?;
+        *token = Some((self.base.clone(), access.clone()));
+        Ok(access)
+    }
+
+    async fn call(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value, CommandError> {
+        let build = |token: &str| {
+            let request = self
+                .http
+                .request(method.clone(), format!("{}/api{}", self.base, path))
+                .bearer_auth(token);
+            match &body {
+                Some(body) => request.json(body),
+                None => request,
+            }
+        };
+        let (mut status, mut text) = send_raw("Kitsu", build(&self.token(false).await?)).await?;
+        if status == StatusCode::UNAUTHORIZED {
+            (status, text) = send_raw("Kitsu", build(&self.token(true).await?)).await?;
+        }
+        parse("Kitsu", status, &text)
+    }
+
+    async fn list(&self, path: &str) -> Result<Vec<Value>, CommandError> {
+        match self.call(Method::GET, path, None).await? {
+            Value::Array(items) => Ok(items),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Id of the Kitsu project: the active project profile's
+    /// `kitsu_project`, else the configured one, else the profile's name
+    async fn project_id(&self) -> Result<String, CommandError> {
+        let active = projects::active(self.settings);
+        let name = active
+            .as_ref()
+            .and_then(|profile| profile.kitsu_project.clone())
+            .or(self.settings.get().trackers.kitsu.project)
+            .or(active.map(|profile| profile.name))
+            .ok_or_else(|| CommandError::InvalidInput("No Kitsu project set".to_string()))?;
+        self.list("/data/projects")
+            .await?
+            .iter()
+            .find(|project| project.get("name").and_then(Value::as_str) == Some(name.as_str()))
+            .and_then(|project| str_field(project, "id"))
+            .ok_or_else(|| CommandError::NotFound(format!("Kitsu project '{}' not found", name)))
+    }
+
+    /// Names by id of a `/data/...` listing, e.g. task types
+    async fn names(&self, path: &str) -> Result<HashMap<String, String>, CommandError> {
+        Ok(self
+            .list(path)
+            .await?
+            .iter()
+            .filter_map(|item| Some((str_field(item, "id")?, str_field(item, "name")?)))
+            .collect())
+    }
+
+    async fn shots(&self) -> Result<Vec<KitsuShot>, CommandError> {
+        let project = self.project_id().await?;
+        let sequences = self.names(&format!("/data/sequences?project_id={}", project)).await?;
+        let task_types = self.names("/data/task-types").await?;
+        let statuses = self.names("/data/task-status").await?;
+
+        let mut tasks: HashMap<String, Vec<KitsuTask>> = HashMap::new();
+        for task in self.list(&format!("/data/tasks?project_id={}", project)).await? {
+            let (Some(id), Some(entity)) = (str_field(&task, "id"), str_field(&task, "entity_id")) else {
+                continue;
+            };
+            let lookup = |key: &str, names: &HashMap<String, String>| {
+                str_field(&task, key).and_then(|id| names.get(&id).cloned()).unwrap_or_default()
+            };
+            tasks.entry(entity).or_default().push(KitsuTask {
+                id,
+                task_type: lookup("task_type_id", &task_types),
+                status: lookup("task_status_id", &statuses),
+            });
+        }
+
+        let mut shots: Vec<KitsuShot> = self
+            .list(&format!("/data/projects/{}/shots", project))
+            .await?
+            .iter()
+            .filter_map(|shot| {
+                let id = str_field(shot, "id")?;
+                let data = shot.get("data").cloned().unwrap_or(Value::Null);
+                let frame = |key: &str| data.get(key).and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()));
+                Some(KitsuShot {
+                    name: str_field(shot, "name")?,
+                    sequence: str_field(shot, "parent_id").and_then(|id| sequences.get(&id).cloned()),
+                    frame_in: frame("frame_in"),
+                    frame_out: frame("frame_out"),
+                    nb_frames: shot.get("nb_frames").and_then(Value::as_i64),
+                    tasks: tasks.remove(&id).unwrap_or_default(),
+                    id,
+                })
+            })
+            .collect();
+        shots.sort_by(|a, b| (&a.sequence, &a.name).cmp(&(&b.sequence, &b.name)));
+        Ok(shots)
+    }
+}
+
+#[tauri::command]
+pub fn get_tracker_settings(settings: State<'_, SettingsStore>) -> TrackerSettings {
+    settings.get().trackers
+}
+
+#[tauri::command]
+pub async fn set_tracker_settings(
+    trackers: TrackerSettings,
+    settings: State<'_, SettingsStore>,
+    state: State<'_, TrackerState>,
+) -> Result<(), CommandError> {
+    settings.update(|s| s.trackers = trackers)?;
+    *state.kitsu_token.lock().await = None;
+    Ok(())
+}
+
+/// Shots of the Kitsu project with their task statuses
+#[tauri::command]
+pub async fn kitsu_list_shots(
+    settings: State<'_, SettingsStore>,
+    state: State<'_, TrackerState>,
+) -> Result<Vec<KitsuShot>, CommandError> {
+    Kitsu::new(&settings, &state)?.shots().await
+}
+
+/// Set the status of a shot's task by status name or short name, with an
+/// optional comment. `task_type` defaults to the configured one.
+#[tauri::command]
+pub async fn kitsu_set_task_status(
+    shot: String,
+    status: String,
+    task_type: Option<String>,
+    comment: Option<String>,
+    settings: State<'_, SettingsStore>,
+    state: State<'_, TrackerState>,
+) -> Result<(), CommandError> {
+    let task_type = task_type
+        .or(settings.get().trackers.kitsu.task_type)
+        .ok_or_else(|| CommandError::InvalidInput("No task type given".to_string()))?;
+    let kitsu = Kitsu::new(&settings, &state)?;
+    let task = kitsu
+        .shots()
+        .await?
+        .into_iter()
+        .find(|s| s.name == shot)
+        .ok_or_else(|| CommandError::NotFound(format!("Kitsu shot '{}' not found", shot)))?
+        .tasks
+        .into_iter()
+        .find(|t| t.task_type == task_type)
+        .ok_or_else(|| CommandError::NotFound(format!("Shot '{}' has no {} task", shot, task_type)))?;
+    let status_id = kitsu
+        .list("/data/task-status")
+        .await?
+        .iter()
+        .find(|s| {
+            [str_field(s, "name"), str_field(s, "short_name")]
+                .into_iter()
+                .flatten()
+                .any(|name| name.eq_ignore_ascii_case(&status))
+        })
+        .and_then(|s| str_field(s, "id"))
+        .ok_or_else(|| CommandError::InvalidInput(format!("Unknown Kitsu status '{}'", status)))?;
+    kitsu
+        .call(
+            Method::POST,
+            &format!("/actions/tasks/{}/comment", task.id),
+            Some(json!({ "task_status_id": status_id, "comment": comment.unwrap_or_default() })),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Write the frame ranges of blendmate shots to the Kitsu shots with the
+/// same names
+#[tauri::command]
+pub async fn kitsu_push_shots<R: Runtime>(
+    scene: Option<String>,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    state: State<'_, TrackerState>,
+    shot_state: State<'_, ShotState>,
+) -> Result<KitsuPushResult, CommandError> {
+    let kitsu = Kitsu::new(&settings, &state)?;
+    let local = shot_state.refresh(&app).await?;
+    let remote = kitsu.shots().await?;
+
+    let mut result = KitsuPushResult {
+        updated: Vec::new(),
+        missing: Vec::new(),
+    };
+    for shot in local.iter().filter(|shot| scene.as_ref().is_none_or(|scene| &shot.scene == scene)) {
+        let Some(target) = remote.iter().find(|r| r.name == shot.name) else {
+            result.missing.push(shot.name.clone());
+            continue;
+        };
+        let path = format!("/data/entities/{}", target.id);
+        let current = kitsu.call(Method::GET, &path, None).await?;
+        let mut data = current.get("data").and_then(Value::as_object).cloned().unwrap_or_default();
+        data.insert("frame_in".to_string(), json!(shot.start));
+        data.insert("frame_out".to_string(), json!(shot.end));
+        kitsu
+            .call(
+                Method::PUT,
+                &path,
+                Some(json!({ "data": data, "nb_frames": shot.end - shot.start + 1 })),
+            )
+            .await?;
+        result.updated.push(shot.name.clone());
+    }
+    Ok(result)
+}
+
+fn flamenco_job(value: &Value) -> FlamencoJob {
+    FlamencoJob {
+        id: str_field(value, "id").unwrap_or_default(),
+        name: str_field(value, "name").unwrap_or_default(),
+        status: str_field(value, "status").unwrap_or_default(),
+        priority: value.get("priority").and_then(Value::as_i64),
+    }
+}
+
+fn flamenco_base(settings: &FlamencoSettings) -> Result<String, CommandError> {
+    settings
+        .url
+        .as_ref()
+        .map(|url| url.trim_end_matches('/').to_string())
+        .ok_or_else(|| CommandError::Unsupported("Flamenco is not configured".to_string()))
+}
+
+fn flamenco_auth(request: RequestBuilder) -> Result<RequestBuilder, CommandError> {
+    Ok(match secrets::get("flamenco.token")? {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    })
+}
+
+/// Submit a render request to Flamenco instead of the local worker pool.
+/// Local device and validation profiles don't apply there.
+async fn submit_flamenco(
+    settings: &SettingsStore,
+    request: &RenderRequest,
+    name: Option<String>,
+    metadata: Map<String, Value>,
+) -> Result<FlamencoJob, CommandError> {
+    let flamenco = settings.get().trackers.flamenco;
+    let base = flamenco_base(&flamenco)?;
+    let output = request
+        .output
+        .clone()
+        .ok_or_else(|| CommandError::InvalidInput("Flamenco jobs need an output path".to_string()))?;
+    if request.frame_end < request.frame_start {
+        return Err(CommandError::InvalidInput("Frame end is before frame start".to_string()));
+    }
+    let chunk_size = match &request.split {
+        SplitStrategy::Chunked { chunk_size } => *chunk_size,
+        _ => flamenco.chunk_size,
+    };
+    let frames = if request.frame_step > 1 {
+        format!("{}-{}x{}", request.frame_start, request.frame_end, request.frame_step)
+    } else {
+        format!("{}-{}", request.frame_start, request.frame_end)
+    };
+
+    let mut job_settings = Map::new();
+    job_settings.insert("blendfile".to_string(), json!(projects::map_path(settings, &request.filepath)));
+    job_settings.insert("frames".to_string(), json!(frames));
+    job_settings.insert("chunk_size".to_string(), json!(chunk_size.max(1)));
+    job_settings.insert("render_output_path".to_string(), json!(output));
+    job_settings.insert("format".to_string(), json!("PNG"));
+    job_settings.insert("image_file_extension".to_string(), json!(".png"));
+    job_settings.extend(flamenco.settings);
+
+    let name = name.unwrap_or_else(|| {
+        let file = request.filepath.rsplit(['/', '\\']).next().unwrap_or(&request.filepath);
+        format!("{} {}", file, frames)
+    });
+    let platform = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    let body = json!({
+        "name": name,
+        "type": flamenco.job_type,
+        "priority": flamenco.priority + request.priority,
+        "submitter_platform": platform,
+        "settings": job_settings,
+        "metadata": metadata,
+    });
+    let job = send("Flamenco", flamenco_auth(http()?.post(format!("{}/api/v3/jobs", base)).json(&body))?).await?;
+    Ok(flamenco_job(&job))
+}
+
+/// Submit a render request to the Flamenco Manager
+#[tauri::command]
+pub async fn flamenco_submit_render(
+    request: RenderRequest,
+    name: Option<String>,
+    settings: State<'_, SettingsStore>,
+) -> Result<FlamencoJob, CommandError> {
+    let mut metadata = Map::new();
+    if let Some(project) = projects::active(&settings) {
+        metadata.insert("project".to_string(), json!(project.name));
+    }
+    submit_flamenco(&settings, &request, name, metadata).await
+}
+
+/// Submit one shot of the open file to the Flamenco Manager
+#[tauri::command]
+pub async fn flamenco_submit_shot<R: Runtime>(
+    scene: String,
+    name: String,
+    options: Option<ShotRenderOptions>,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+) -> Result<FlamencoJob, CommandError> {
+    let request = shots::render_request(&app, &scene, &name, options.unwrap_or_default()).await?;
+    let mut metadata = Map::new();
+    metadata.insert("shot".to_string(), json!(name));
+    metadata.insert("scene".to_string(), json!(scene));
+    if let Some(project) = projects::active(&settings) {
+        metadata.insert("project".to_string(), json!(project.name));
+    }
+    submit_flamenco(&settings, &request, Some(name.clone()), metadata).await
+}
+
+/// Current state of a Flamenco job
+#[tauri::command]
+pub async fn flamenco_get_job(id: String, settings: State<'_, SettingsStore>) -> Result<FlamencoJob, CommandError> {
+    let base = flamenco_base(&settings.get().trackers.flamenco)?;
+    let job = send("Flamenco", flamenco_auth(http()?.get(format!("{}/api/v3/jobs/{}", base, id)))?).await?;
+    Ok(flamenco_job(&job))
+}
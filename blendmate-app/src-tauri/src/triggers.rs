@@ -0,0 +1,146 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::protocol;
+
+/// Longest accepted trigger name
+const MAX_NAME_LEN: usize = 64;
+
+/// Largest accepted payload, serialized
+const MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+
+/// Each trigger name may fire this often per `RATE_WINDOW`; the rest are
+/// dropped
+const RATE_LIMIT: usize = 20;
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Payload of `trigger:fired`
+#[derive(Serialize, Clone, Debug)]
+pub struct TriggerFired {
+    pub name: String,
+    pub payload: Value,
+    pub fired_at: String,
+}
+
+/// What the backend has seen of one trigger name
+#[derive(Serialize, Clone, Debug)]
+pub struct TriggerStats {
+    pub name: String,
+    pub fired: u64,
+    /// Dropped by the rate limit
+    pub dropped: u64,
+    pub last_payload: Value,
+    pub last_fired_at: Option<String>,
+}
+
+struct TriggerEntry {
+    stats: TriggerStats,
+    recent: VecDeque<Instant>,
+}
+
+/// `custom.trigger` events fired by user scripts in Blender. Every name
+/// seen is registered here.
+#[derive(Default)]
+pub struct TriggerState {
+    triggers: Mutex<HashMap<String, TriggerEntry>>,
+    /// Triggers refused for a bad name or payload
+    rejected: Mutex<u64>,
+}
+
+/// Route a `custom.trigger` event: valid ones within the rate limit are
+/// emitted as `trigger:fired`. Returns true when `message` was a trigger,
+/// so it is not forwarded to the frontend as a raw message.
+pub fn handle<R: Runtime>(app: &AppHandle<R>, message: &Value) -> bool {
+    if protocol::message_type(message).as_deref() != Some("custom.trigger") {
+        return false;
+    }
+    let body = protocol::message_body(message);
+    let name = body.get("name").and_then(Value::as_str).unwrap_or_default();
+    let payload = body.get("payload").cloned().unwrap_or(Value::Null);
+    let state = app.state::<TriggerState>();
+
+    if let Err(reason) = check(name, &payload) {
+        *state.rejected.lock().unwrap() += 1;
+        eprintln!("Dropped custom trigger: {reason}");
+        return true;
+    }
+
+    let fired = {
+        let mut triggers = state.triggers.lock().unwrap();
+        let entry = triggers.entry(name.to_string()).or_insert_with(|| TriggerEntry {
+            stats: TriggerStats {
+                name: name.to_string(),
+                fired: 0,
+                dropped: 0,
+                last_payload: Value::Null,
+                last_fired_at: None,
+            },
+            recent: VecDeque::new(),
+        });
+
+        let now = Instant::now();
+        while entry.recent.front().is_some_and(|at| now.duration_since(*at) > RATE_WINDOW) {
+            entry.recent.pop_front();
+        }
+        if entry.recent.len() >= RATE_LIMIT {
+            entry.stats.dropped += 1;
+            return true;
+        }
+        entry.recent.push_back(now);
+
+        let fired_at = chrono::Local::now().to_rfc3339();
+        entry.stats.fired += 1;
+        entry.stats.last_payload = payload.clone();
+        entry.stats.last_fired_at = Some(fired_at.clone());
+        TriggerFired {
+            name: name.to_string(),
+            payload,
+            fired_at,
+        }
+    };
+
+    if let Err(err) = app.emit("trigger:fired", &fired) {
+        eprintln!("Failed to emit trigger:fired: {err}");
+    }
+    true
+}
+
+fn check(name: &str, payload: &Value) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(format!("name must be 1 to {} characters", MAX_NAME_LEN));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return Err(format!("'{}' may only use letters, digits, '_', '-' and '.'", name));
+    }
+    if payload.to_string().len() > MAX_PAYLOAD_BYTES {
+        return Err(format!("payload of '{}' is over {} KiB", name, MAX_PAYLOAD_BYTES / 1024));
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct TriggerReport {
+    pub triggers: Vec<TriggerStats>,
+    pub rejected: u64,
+}
+
+/// Trigger names fired since the app started, with counts
+#[tauri::command]
+pub fn list_custom_triggers(state: State<'_, TriggerState>) -> TriggerReport {
+    let mut triggers: Vec<TriggerStats> = state
+        .triggers
+        .lock()
+        .unwrap()
+        .values()
+        .map(|entry| entry.stats.clone())
+        .collect();
+    triggers.sort_by(|a, b| a.name.cmp(&b.name));
+    TriggerReport {
+        triggers,
+        rejected: *state.rejected.lock().unwrap(),
+    }
+}
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::journal::JobRequest;
+use crate::settings::SettingsStore;
+use crate::workers::{self, Job, JobStatus, WorkItem, WorkerPool};
+
+const UNITS_SCRIPT: &str = include_str!("../scripts/units.py");
+
+/// Scene unit settings compared across files
+const COMPARED_FIELDS: [&str; 4] = ["system", "scale_length", "length_unit", "mass_unit"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UnitAuditRequest {
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Audit every .blend below this directory as well
+    pub directory: Option<String>,
+    /// Install name from the worker settings, or a path to a Blender binary
+    pub blender: Option<String>,
+    /// Flag objects whose largest scale axis is more than this many times
+    /// their smallest (default 10)
+    pub max_scale_ratio: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SceneUnits {
+    pub name: String,
+    /// `NONE`, `METRIC` or `IMPERIAL`
+    pub system: String,
+    pub scale_length: f64,
+    pub length_unit: String,
+    pub mass_unit: String,
+    pub system_rotation: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FileUnits {
+    pub file: String,
+    pub scenes: Vec<SceneUnits>,
+    /// More objects had scale issues than were reported
+    pub truncated: bool,
+    pub error: Option<String>,
+}
+
+/// One value of a mismatched field and the scenes using it
+#[derive(Serialize, Debug)]
+pub struct UnitValue {
+    pub value: Value,
+    /// `file` and `scene` of each scene with this value
+    pub scenes: Vec<Value>,
+}
+
+/// A unit setting that differs between files; values are most used first,
+/// so everything after the first one is an outlier
+#[derive(Serialize, Debug)]
+pub struct UnitMismatch {
+    pub field: String,
+    pub values: Vec<UnitValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScaleIssue {
+    #[serde(default)]
+    pub file: String,
+    #[serde(rename(deserialize = "name"))]
+    pub object: String,
+    #[serde(rename(deserialize = "type"))]
+    pub object_type: String,
+    pub scenes: Vec<String>,
+    pub scale: [f64; 3],
+    /// Largest scale axis over the smallest
+    pub ratio: Option<f64>,
+    /// `non_uniform`, `negative` or `zero`
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UnitAuditReport {
+    pub files: Vec<FileUnits>,
+    pub mismatches: Vec<UnitMismatch>,
+    pub scale_issues: Vec<ScaleIssue>,
+}
+
+/// Every .blend file below `directory`, sorted; backups like `.blend1` are skipped
+pub fn blend_files(directory: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut pending = vec![directory.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "blend") {
+                files.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// One item per file
+pub fn work(request: &UnitAuditRequest) -> Vec<WorkItem> {
+    request
+        .files
+        .iter()
+        .map(|file| WorkItem {
+            label: file.clone(),
+            blend_file: Some(PathBuf::from(file)),
+            script: UNITS_SCRIPT,
+            args: json!({ "max_scale_ratio": request.max_scale_ratio.unwrap_or(10.0) }),
+            devices: None,
+            priority: 0,
+            env: BTreeMap::new(),
+        })
+        .collect()
+}
+
+/// Compare the unit settings reported by each item and collect scale
+/// issues; returns the report as the job summary.
+pub fn summarize(request: &UnitAuditRequest, job: &Job) -> Value {
+    let mut files = Vec::new();
+    let mut scale_issues = Vec::new();
+    for (file, item) in request.files.iter().zip(&job.items) {
+        let result = item.result.clone().unwrap_or(Value::Null);
+        let error = match item.status {
+            JobStatus::Done => None,
+            _ => Some(item.errors.last().cloned().unwrap_or_else(|| "Audit failed".to_string())),
+        };
+        let scenes = serde_json::from_value(result.get("scenes").cloned().unwrap_or(Value::Null)).unwrap_or_default();
+        let objects: Vec<ScaleIssue> =
+            serde_json::from_value(result.get("objects").cloned().unwrap_or(Value::Null)).unwrap_or_default();
+        scale_issues.extend(objects.into_iter().map(|issue| ScaleIssue {
+            file: file.clone(),
+            ..issue
+        }));
+        files.push(FileUnits {
+            file: file.clone(),
+            scenes,
+            truncated: result.get("truncated").and_then(Value::as_bool).unwrap_or(false),
+            error,
+        });
+    }
+
+    let mismatches = COMPARED_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let mut values: Vec<UnitValue> = Vec::new();
+            for file in &files {
+                for scene in &file.scenes {
+                    let value = match *field {
+                        "system" => json!(scene.system),
+                        // Rounded so float noise isn't a mismatch
+                        "scale_length" => json!((scene.scale_length * 1e6).round() / 1e6),
+                        "length_unit" => json!(scene.length_unit),
+                        _ => json!(scene.mass_unit),
+                    };
+                    let at = json!({ "file": file.file, "scene": scene.name });
+                    match values.iter_mut().find(|v| v.value == value) {
+                        Some(existing) => existing.scenes.push(at),
+                        None => values.push(UnitValue {
+                            value,
+                            scenes: vec![at],
+                        }),
+                    }
+                }
+            }
+            if values.len() < 2 {
+                return None;
+            }
+            values.sort_by(|a, b| b.scenes.len().cmp(&a.scenes.len()));
+            Some(UnitMismatch {
+                field: field.to_string(),
+                values,
+            })
+        })
+        .collect();
+
+    serde_json::to_value(UnitAuditReport {
+        files,
+        mismatches,
+        scale_issues,
+    })
+    .unwrap_or(Value::Null)
+}
+
+/// Audit scene units and object scales across .blend files on the worker
+/// pool. The report is the job's summary once it finishes.
+#[tauri::command]
+pub async fn start_unit_audit<R: Runtime>(
+    mut request: UnitAuditRequest,
+    app: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+) -> Result<String, CommandError> {
+    // The directory is expanded now so the job keeps the same items across restarts
+    if let Some(directory) = request.directory.take() {
+        if !Path::new(&directory).is_dir() {
+            return Err(CommandError::NotFound(format!("Directory not found: {}", directory)));
+        }
+        for file in blend_files(Path::new(&directory)) {
+            if !request.files.contains(&file) {
+                request.files.push(file);
+            }
+        }
+    }
+    if request.files.is_empty() {
+        return Err(CommandError::InvalidInput("No .blend files to audit".to_string()));
+    }
+    if request.max_scale_ratio.is_some_and(|ratio| ratio <= 1.0) {
+        return Err(CommandError::InvalidInput("Scale ratio must be greater than 1".to_string()));
+    }
+
+    let id = pool.submit(JobRequest::UnitAudit(request.clone()), &settings).await?;
+    let request = JobRequest::UnitAudit(request);
+    workers::spawn_job(app, id.clone(), move |app, job| request.finished(app, job));
+    Ok(id)
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+use crate::errors::CommandError;
+use crate::journal::JobRequest;
+use crate::settings::SettingsStore;
+use crate::workers::{self, WorkItem, WorkerPool};
+
+const UPGRADE_SCRIPT: &str = include_str!("../scripts/upgrade.py");
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UpgradeRequest {
+    pub files: Vec<String>,
+    /// Install name from the worker settings, or a path to a Blender binary
+    pub blender: Option<String>,
+    /// Python file run in each opened file before it is saved
+    pub fixup_script: Option<String>,
+    /// Write the re-saved copies here instead of overwriting the originals
+    pub output_dir: Option<String>,
+    /// Keep `<file>.bak` when saving in place (default true)
+    pub backup: Option<bool>,
+    #[serde(default)]
+    pub compress: bool,
+}
+
+/// Re-save .blend files with a chosen Blender version on the worker pool.
+///
+/// Each item reports `{file_version, saved_version}` as its result.
+#[tauri::command]
+pub async fn start_upgrade_job(
+    request: UpgradeRequest,
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+) -> Result<String, CommandError> {
+    let id = submit_upgrade(&settings, &pool, &request).await?;
+    workers::spawn_job(app, id.clone(), |_, _| None);
+    Ok(id)
+}
+
+/// Queue an upgrade job without running it.
+pub async fn submit_upgrade(
+    settings: &SettingsStore,
+    pool: &WorkerPool,
+    request: &UpgradeRequest,
+) -> Result<String, CommandError> {
+    if request.files.is_empty() {
+        return Err(CommandError::InvalidInput("No files selected".to_string()));
+    }
+    if let Some(script) = &request.fixup_script {
+        if !PathBuf::from(script).is_file() {
+            return Err(CommandError::NotFound(format!("Fix-up script not found: {}", script)));
+        }
+    }
+
+    pool.submit(JobRequest::Upgrade(request.clone()), settings).await
+}
+
+/// One item per file
+pub fn work(request: &UpgradeRequest) -> Vec<WorkItem> {
+    request
+        .files
+        .iter()
+        .map(|file| WorkItem {
+            label: file.clone(),
+            blend_file: Some(PathBuf::from(file)),
+            script: UPGRADE_SCRIPT,
+            args: json!({
+                "fixup_script": request.fixup_script,
+                "output_dir": request.output_dir,
+                "backup": request.backup.unwrap_or(true),
+                "compress": request.compress,
+            }),
+            devices: None,
+            priority: 0,
+            env: BTreeMap::new(),
+        })
+        .collect()
+}
@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use tauri::State;
+
+use crate::errors::CommandError;
+use crate::settings::SettingsStore;
+use crate::AppState;
+
+const SCALE_EPSILON: f64 = 1e-4;
+
+/// Object types whose scale affects geometry (and therefore exports/sims).
+const GEOMETRY_TYPES: &[&str] = &["MESH", "CURVE", "SURFACE", "FONT", "META"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    UnappliedScale,
+    MissingUvs,
+    OutputPathNotSet,
+    PlaceholderMaterials,
+    RenderBorder,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CheckConfig {
+    pub check: Check,
+    pub severity: Severity,
+}
+
+/// Named set of checks. User profiles are stored in settings and take
+/// precedence over built-in profiles with the same name.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValidationProfile {
+    pub name: String,
+    pub checks: Vec<CheckConfig>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ValidationWarning {
+    pub check: Check,
+    pub severity: Severity,
+    pub message: String,
+    pub object: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ValidationReport {
+    pub profile: String,
+    pub warnings: Vec<ValidationWarning>,
+    /// False when any finding has `error` severity
+    pub passed: bool,
+}
+
+fn builtin_profiles() -> Vec<ValidationProfile> {
+    let all = |severity_of: fn(Check) -> Severity| {
+        [
+            Check::UnappliedScale,
+            Check::MissingUvs,
+            Check::OutputPathNotSet,
+            Check::PlaceholderMaterials,
+            Check::RenderBorder,
+        ]
+        .into_iter()
+        .map(|check| CheckConfig { check, severity: severity_of(check) })
+        .collect()
+    };
+
+    vec![
+        ValidationProfile {
+            name: "default".to_string(),
+            checks: all(|_| Severity::Warning),
+        },
+        ValidationProfile {
+            name: "final".to_string(),
+            checks: all(|check| match check {
+                Check::OutputPathNotSet | Check::RenderBorder | Check::PlaceholderMaterials => Severity::Error,
+                _ => Severity::Warning,
+            }),
+        },
+    ]
+}
+
+fn all_profiles(settings: &SettingsStore) -> Vec<ValidationProfile> {
+    let mut profiles = settings.get().validation_profiles;
+    for builtin in builtin_profiles() {
+        if !profiles.iter().any(|p| p.name == builtin.name) {
+            profiles.push(builtin);
+        }
+    }
+    profiles
+}
+
+/// Run the checks of `profile` (defaults to `"default"`) against the scene
+#[tauri::command]
+pub async fn validate_scene(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsStore>,
+) -> Result<ValidationReport, CommandError> {
+    run_validation(&state, &settings, profile.as_deref().unwrap_or("default")).await
+}
+
+/// List built-in and user validation profiles
+#[tauri::command]
+pub fn list_validation_profiles(settings: State<'_, SettingsStore>) -> Vec<ValidationProfile> {
+    all_profiles(&settings)
+}
+
+/// Create or replace a user validation profile
+#[tauri::command]
+pub fn save_validation_profile(profile: ValidationProfile, settings: State<'_, SettingsStore>) -> Result<(), CommandError> {
+    if profile.name.trim().is_empty() {
+        return Err(CommandError::InvalidInput("Profile name is required".to_string()));
+    }
+    settings.update(|s| {
+        s.validation_profiles.retain(|p| p.name != profile.name);
+        s.validation_profiles.push(profile);
+    })?;
+    Ok(())
+}
+
+/// Headless jobs read saved files, so only the file open in Blender can be
+/// checked against the live scene. Fails when it is among `files` and
+/// doesn't pass `profile`. Without a Blender attached no file is open, and
+/// the jobs run as they are.
+pub async fn require_passing_if_open(
+    state: &AppState,
+    settings: &SettingsStore,
+    profile: &str,
+    files: &[&str],
+) -> Result<(), CommandError> {
+    let scene = match state.mirror.scene(&state.bridge).await {
+        Ok(scene) => scene,
+        Err(CommandError::NotConnected(_)) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let open_file = scene.get("filepath").and_then(Value::as_str).unwrap_or("");
+    if !files.iter().any(|f| Path::new(f) == Path::new(open_file)) {
+        return Ok(());
+    }
+
+    let report = run_validation(state, settings, profile).await?;
+    if report.passed {
+        Ok(())
+    } else {
+        Err(CommandError::Failed(format!(
+            "Validation profile '{}' failed with {} findings",
+            profile,
+            report.warnings.len()
+        )))
+    }
+}
+
+pub async fn run_validation(
+    state: &AppState,
+    settings: &SettingsStore,
+    profile: &str,
+) -> Result<ValidationReport, CommandError> {
+    let profile = all_profiles(settings)
+        .into_iter()
+        .find(|p| p.name == profile)
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown validation profile '{}'", profile)))?;
+
+    let scene = state.mirror.scene(&state.bridge).await?;
+
+    let needs_render = profile
+        .checks
+        .iter()
+        .any(|c| matches!(c.check, Check::OutputPathNotSet | Check::RenderBorder));
+    let render = if needs_render {
+        Some(state.bridge.request("render.get_settings", "", json!({})).await?)
+    } else {
+        None
+    };
+
+    let mut warnings = Vec::new();
+    for config in &profile.checks {
+        let mut push = |message: String, object: Option<&str>| {
+            warnings.push(ValidationWarning {
+                check: config.check,
+                severity: config.severity,
+                message,
+                object: object.map(str::to_string),
+            });
+        };
+
+        match config.check {
+            Check::UnappliedScale => {
+                for (name, object) in objects(&scene) {
+                    if !GEOMETRY_TYPES.contains(&object_type(object)) {
+                        continue;
+                    }
+                    if let Some(scale) = vector(object.get("scale")) {
+                        if scale.iter().any(|s| (s - 1.0).abs() > SCALE_EPSILON) {
+                            push(format!("'{}' has unapplied scale {:?}", name, scale), Some(name));
+                        }
+                    }
+                }
+            }
+            Check::MissingUvs => {
+                for (name, object) in objects(&scene) {
+                    // Older add-ons do not report `has_uv`; skip rather than guess
+                    if object.pointer("/mesh/has_uv").and_then(Value::as_bool) == Some(false) {
+                        push(format!("'{}' has no UV map", name), Some(name));
+                    }
+                }
+            }
+            Check::PlaceholderMaterials => {
+                for (name, object) in objects(&scene) {
+                    let Some(slots) = object.get("materials").and_then(Value::as_array) else {
+                        continue;
+                    };
+                    for slot in slots {
+                        match slot.as_str() {
+                            None => push(format!("'{}' has an empty material slot", name), Some(name)),
+                            Some(material) if is_placeholder_material(material) => push(
+                                format!("'{}' uses placeholder material '{}'", name, material),
+                                Some(name),
+                            ),
+                            Some(_) => {}
+                        }
+                    }
+                }
+            }
+            Check::OutputPathNotSet => {
+                let filepath = render
+                    .as_ref()
+                    .and_then(|r| r.get("filepath"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                // Blender's default output path points at the system temp dir
+                if filepath.trim().is_empty() || filepath == "/tmp/" || filepath == "/tmp\\" {
+                    push("Render output path is not set".to_string(), None);
+                }
+            }
+            Check::RenderBorder => {
+                let use_border = render
+                    .as_ref()
+                    .and_then(|r| r.get("use_border"))
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                if use_border {
+                    push("Render border is enabled".to_string(), None);
+                }
+            }
+        }
+    }
+
+    let passed = !warnings.iter().any(|w| w.severity == Severity::Error);
+    Ok(ValidationReport {
+        profile: profile.name,
+        warnings,
+        passed,
+    })
+}
+
+fn objects(scene: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    scene
+        .get("objects")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|objects| objects.iter())
+}
+
+fn object_type(object: &Value) -> &str {
+    object.get("type").and_then(Value::as_str).unwrap_or("")
+}
+
+fn vector(value: Option<&Value>) -> Option<Vec<f64>> {
+    value?.as_array()?.iter().map(Value::as_f64).collect()
+}
+
+/// `Material`, `Material.001`, ... are what Blender creates by default.
+fn is_placeholder_material(name: &str) -> bool {
+    match name.strip_prefix("Material") {
+        Some("") => true,
+        Some(suffix) => suffix
+            .strip_prefix('.')
+            .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())),
+        None => false,
+    }
+}
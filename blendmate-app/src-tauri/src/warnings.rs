@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::errors::CommandError;
+use crate::protocol;
+
+/// Connection sessions kept; the oldest is dropped
+const MAX_SESSIONS: usize = 10;
+
+/// Distinct warnings kept per session; the least recent is dropped
+const MAX_GROUPS: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningLevel {
+    Warning,
+    Error,
+}
+
+#[derive(Deserialize)]
+struct Report {
+    level: WarningLevel,
+    #[serde(default, rename = "type")]
+    kind: String,
+    message: String,
+}
+
+/// Reports that differ only in names and numbers, counted as one
+#[derive(Serialize, Clone, Debug)]
+pub struct WarningGroup {
+    pub level: WarningLevel,
+    /// Blender's report type, e.g. `Invalid Context Error`
+    pub kind: String,
+    /// The latest message of the group
+    pub message: String,
+    /// Operator or command that raised it, e.g. `object.modifier_apply`
+    pub source: String,
+    pub count: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+    /// Raised again since `mark_warnings_seen`
+    pub unseen: bool,
+    #[serde(skip)]
+    key: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WarningDigest {
+    pub session: String,
+    pub peer: String,
+    pub started_at: String,
+    /// Most recent first
+    pub groups: Vec<WarningGroup>,
+}
+
+/// Emitted as `warnings:count` whenever the unseen counts of the current
+/// session change, for a badge
+#[derive(Serialize, Clone, Debug)]
+pub struct WarningCount {
+    pub session: String,
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct WarningSession {
+    pub session: String,
+    pub peer: String,
+    pub started_at: String,
+    pub warnings: usize,
+    pub errors: usize,
+}
+
+impl WarningDigest {
+    fn count(&self) -> WarningCount {
+        let unseen = |level| self.groups.iter().filter(|g| g.unseen && g.level == level).count();
+        WarningCount {
+            session: self.session.clone(),
+            warnings: unseen(WarningLevel::Warning),
+            errors: unseen(WarningLevel::Error),
+        }
+    }
+}
+
+/// Blender's warning and error reports per connection session, newest
+/// session last
+#[derive(Default)]
+pub struct WarningStore {
+    sessions: Mutex<VecDeque<WarningDigest>>,
+}
+
+/// Message with quoted names and numbers blanked out, so `Modifier "A" not
+/// found` and `Modifier "B" not found` count as one warning
+fn normalize(message: &str) -> String {
+    let mut key = String::with_capacity(message.len());
+    let mut quote: Option<char> = None;
+    let mut previous: Option<char> = None;
+    for c in message.trim().trim_end_matches('.').chars() {
+        let previous_digit = previous.is_some_and(|p| p.is_ascii_digit());
+        // An apostrophe inside a word, as in `can't`, opens no quote
+        let opens_quote = c == '"' || c == '“' || (c == '\'' && !previous.is_some_and(char::is_alphanumeric));
+        previous = Some(c);
+        if let Some(open) = quote {
+            if c == open || (open == '“' && c == '”') {
+                key.push(c);
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            _ if opens_quote => {
+                key.push(c);
+                key.push('…');
+                quote = Some(c);
+            }
+            c if c.is_ascii_digit() => {
+                if !previous_digit {
+                    key.push('#');
+                }
+            }
+            c if c.is_whitespace() => {
+                if !key.ends_with(' ') {
+                    key.push(' ');
+                }
+            }
+            c => key.push(c),
+        }
+    }
+    key
+}
+
+impl WarningStore {
+    /// Start a session for a new connection
+    pub fn open_session<R: Runtime>(&self, app: &AppHandle<R>, peer: SocketAddr) {
+        let now = chrono::Local::now();
+        let digest = WarningDigest {
+            session: format!("session-{}", now.format("%Y%m%d-%H%M%S%.3f")),
+            peer: peer.to_string(),
+            started_at: now.to_rfc3339(),
+            groups: Vec::new(),
+        };
+        let count = digest.count();
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            sessions.push_back(digest);
+            while sessions.len() > MAX_SESSIONS {
+                sessions.pop_front();
+            }
+        }
+        emit_count(app, &count);
+    }
+
+    fn record(&self, source: &str, reports: Vec<Report>) -> Option<WarningCount> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let digest = sessions.back_mut()?;
+        let before = digest.count();
+        let now = chrono::Local::now().to_rfc3339();
+        for report in reports {
+            let key = format!("{:?}|{}|{}", report.level, source, normalize(&report.message));
+            match digest.groups.iter().position(|group| group.key == key) {
+                Some(index) => {
+                    let mut group = digest.groups.remove(index);
+                    group.message = report.message;
+                    group.count += 1;
+                    group.last_seen = now.clone();
+                    group.unseen = true;
+                    digest.groups.insert(0, group);
+                }
+                None => {
+                    digest.groups.insert(
+                        0,
+                        WarningGroup {
+                            level: report.level,
+                            kind: report.kind,
+                            message: report.message,
+                            source: source.to_string(),
+                            count: 1,
+                            first_seen: now.clone(),
+                            last_seen: now.clone(),
+                            unseen: true,
+                            key,
+                        },
+                    );
+                    digest.groups.truncate(MAX_GROUPS);
+                }
+            }
+        }
+        let after = digest.count();
+        (after.warnings != before.warnings || after.errors != before.errors).then_some(after)
+    }
+}
+
+fn emit_count<R: Runtime>(app: &AppHandle<R>, count: &WarningCount) {
+    if let Err(err) = app.emit("warnings:count", count) {
+        eprintln!("Failed to emit warnings:count: {err}");
+    }
+}
+
+/// Record the reports of an `event.operator.report` from the add-on
+pub fn observe<R: Runtime>(app: &AppHandle<R>, message: &Value) {
+    if protocol::message_type(message).as_deref() != Some("event.operator.report") {
+        return;
+    }
+    let body = protocol::message_body(message);
+    let reports: Vec<Report> = match serde_json::from_value(body.get("reports").cloned().unwrap_or(Value::Null)) {
+        Ok(reports) => reports,
+        Err(err) => {
+            eprintln!("Invalid operator reports: {err}");
+            return;
+        }
+    };
+    // Operator calls name the operator as their target
+    let action = body.get("action").and_then(Value::as_str).unwrap_or_default();
+    let target = body.get("target").and_then(Value::as_str).unwrap_or_default();
+    let source = if action == "operator.call" && !target.is_empty() { target } else { action };
+    if let Some(count) = app.state::<WarningStore>().record(source, reports) {
+        emit_count(app, &count);
+    }
+}
+
+/// Warnings of a connection session, by default the current one
+#[tauri::command]
+pub fn get_warnings(session: Option<String>, store: State<'_, WarningStore>) -> Result<WarningDigest, CommandError> {
+    let sessions = store.sessions.lock().unwrap();
+    let digest = match &session {
+        Some(id) => sessions.iter().find(|digest| &digest.session == id),
+        None => sessions.back(),
+    };
+    digest
+        .cloned()
+        .ok_or_else(|| CommandError::NotFound(format!("No warnings session {}", session.unwrap_or_default())))
+}
+
+/// Sessions kept, oldest first, with how many distinct warnings and
+/// errors each had
+#[tauri::command]
+pub fn list_warning_sessions(store: State<'_, WarningStore>) -> Vec<WarningSession> {
+    store
+        .sessions
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|digest| {
+            let total = |level| digest.groups.iter().filter(|g| g.level == level).count();
+            WarningSession {
+                session: digest.session.clone(),
+                peer: digest.peer.clone(),
+                started_at: digest.started_at.clone(),
+                warnings: total(WarningLevel::Warning),
+                errors: total(WarningLevel::Error),
+            }
+        })
+        .collect()
+}
+
+/// Clear the unseen flags of the current session, e.g. when the warnings
+/// panel is opened
+#[tauri::command]
+pub fn mark_warnings_seen<R: Runtime>(app: AppHandle<R>, store: State<'_, WarningStore>) {
+    let count = {
+        let mut sessions = store.sessions.lock().unwrap();
+        let Some(digest) = sessions.back_mut() else {
+            return;
+        };
+        for group in &mut digest.groups {
+            group.unseen = false;
+        }
+        digest.count()
+    };
+    emit_count(&app, &count);
+}
@@ -0,0 +1,221 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::sync::Mutex;
+
+use crate::bridge::datablock_path;
+use crate::errors::CommandError;
+use crate::protocol;
+use crate::AppState;
+
+/// A property value the frontend subscribed to
+#[derive(Serialize, Clone, Debug)]
+pub struct PropertyWatch {
+    pub id: String,
+    pub object: String,
+    pub data_path: String,
+    /// Last value read; `None` before the first read or while unreadable
+    pub value: Option<Value>,
+    /// Why the last read failed, e.g. the object was deleted
+    pub error: Option<String>,
+}
+
+/// Payload of `property:changed`
+#[derive(Serialize, Clone, Debug)]
+pub struct PropertyChanged {
+    pub id: String,
+    pub object: String,
+    pub data_path: String,
+    pub value: Option<Value>,
+    pub previous: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// Objects whose watches need reading again
+#[derive(Default)]
+struct Pending {
+    objects: HashSet<String>,
+    all: bool,
+    /// A reader task is running and will pick up new entries
+    scheduled: bool,
+}
+
+/// Watched properties. Depsgraph events name the objects that changed, and
+/// only watches on those are read again, in one `property.get_batch`
+/// request. Frame changes, file loads and reconnects re-read every watch.
+#[derive(Default)]
+pub struct WatchState {
+    watches: Mutex<HashMap<String, PropertyWatch>>,
+    pending: std::sync::Mutex<Pending>,
+    next_id: AtomicU64,
+}
+
+/// Schedule reads for the watches `message` may have changed.
+pub fn observe<R: Runtime>(app: &AppHandle<R>, message: &Value) {
+    let Some(msg_type) = protocol::message_type(message) else {
+        return;
+    };
+    let state = app.state::<WatchState>();
+    {
+        let mut pending = state.pending.lock().unwrap();
+        match msg_type.as_str() {
+            "event.depsgraph.updated" => {
+                let body = protocol::message_body(message);
+                match body
+                    .get("changed_object_ids")
+                    .or_else(|| body.get("changed_objects"))
+                    .and_then(Value::as_array)
+                {
+                    Some(ids) => pending
+                        .objects
+                        .extend(ids.iter().filter_map(Value::as_str).map(str::to_string)),
+                    None => pending.all = true,
+                }
+            }
+            "event.timeline.frame_changed" | "event.scene.file_loaded" | "event.scene.connected" => pending.all = true,
+            _ => return,
+        }
+        if pending.scheduled {
+            return;
+        }
+        pending.scheduled = true;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<WatchState>();
+        loop {
+            let (objects, all) = {
+                let mut pending = state.pending.lock().unwrap();
+                if pending.objects.is_empty() && !pending.all {
+                    pending.scheduled = false;
+                    return;
+                }
+                (std::mem::take(&mut pending.objects), std::mem::take(&mut pending.all))
+            };
+            state.read(&app, |watch| all || objects.contains(&watch.object)).await;
+        }
+    });
+}
+
+impl WatchState {
+    /// Read the watches `select` picks and emit `property:changed` for
+    /// each value that differs from the last one.
+    async fn read<R: Runtime>(&self, app: &AppHandle<R>, select: impl Fn(&PropertyWatch) -> bool) {
+        let selected: Vec<PropertyWatch> = self.watches.lock().await.values().filter(|w| select(w)).cloned().collect();
+        if selected.is_empty() {
+            return;
+        }
+
+        let reads: Vec<Value> = selected
+            .iter()
+            .map(|watch| json!({ "target": datablock_path("objects", &watch.object), "path": watch.data_path }))
+            .collect();
+        let bridge = &app.state::<AppState>().bridge;
+        let results = match bridge.request("property.get_batch", "", json!({ "reads": reads })).await {
+            Ok(Value::Array(results)) => results,
+            // Disconnected or an old add-on; the next event tries again
+            _ => return,
+        };
+
+        let mut changes = Vec::new();
+        {
+            let mut watches = self.watches.lock().await;
+            for (read, result) in selected.iter().zip(results) {
+                // Unwatched while the request was out
+                let Some(watch) = watches.get_mut(&read.id) else {
+                    continue;
+                };
+                let value = result.get("value").cloned();
+                let error = result.get("error").and_then(Value::as_str).map(str::to_string);
+                if watch.value == value && watch.error == error {
+                    continue;
+                }
+                changes.push(PropertyChanged {
+                    id: watch.id.clone(),
+                    object: watch.object.clone(),
+                    data_path: watch.data_path.clone(),
+                    value: value.clone(),
+                    previous: watch.value.take(),
+                    error: error.clone(),
+                });
+                watch.value = value;
+                watch.error = error;
+            }
+        }
+
+        for change in changes {
+            if let Err(err) = app.emit("property:changed", &change) {
+                eprintln!("Failed to emit property:changed: {err}");
+            }
+        }
+    }
+}
+
+/// Subscribe to `object`'s `data_path` (e.g. `location` or
+/// `modifiers[0].show_viewport`). `property:changed` is emitted whenever
+/// the value changes. Watching the same property twice returns the
+/// existing watch.
+#[tauri::command]
+pub async fn watch_property(
+    object: String,
+    data_path: String,
+    state: State<'_, AppState>,
+    watch: State<'_, WatchState>,
+) -> Result<PropertyWatch, CommandError> {
+    if object.is_empty() || data_path.is_empty() {
+        return Err(CommandError::InvalidInput("Object and data path are required".to_string()));
+    }
+    if let Some(existing) = watch
+        .watches
+        .lock()
+        .await
+        .values()
+        .find(|w| w.object == object && w.data_path == data_path)
+    {
+        return Ok(existing.clone());
+    }
+
+    // Read the starting value; a watch made while disconnected is read on
+    // connect
+    let value = match state
+        .bridge
+        .request("property.get", &datablock_path("objects", &object), json!({ "path": data_path }))
+        .await
+    {
+        Ok(value) => Some(value),
+        Err(CommandError::NotConnected(_)) => None,
+        Err(CommandError::Blender { message, .. }) => return Err(CommandError::InvalidInput(message)),
+        Err(error) => return Err(error),
+    };
+
+    let new = PropertyWatch {
+        id: format!("watch-{}", watch.next_id.fetch_add(1, Ordering::Relaxed) + 1),
+        object,
+        data_path,
+        value,
+        error: None,
+    };
+    watch.watches.lock().await.insert(new.id.clone(), new.clone());
+    Ok(new)
+}
+
+#[tauri::command]
+pub async fn unwatch_property(id: String, watch: State<'_, WatchState>) -> Result<(), CommandError> {
+    watch
+        .watches
+        .lock()
+        .await
+        .remove(&id)
+        .map(|_| ())
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown watch '{}'", id)))
+}
+
+#[tauri::command]
+pub async fn list_property_watches(watch: State<'_, WatchState>) -> Result<Vec<PropertyWatch>, CommandError> {
+    let mut watches: Vec<PropertyWatch> = watch.watches.lock().await.values().cloned().collect();
+    watches.sort_by(|a, b| (&a.object, &a.data_path).cmp(&(&b.object, &b.data_path)));
+    Ok(watches)
+}
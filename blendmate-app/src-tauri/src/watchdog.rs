@@ -0,0 +1,200 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::protocol;
+
+/// Silence after which a connected Blender counts as busy: two missed
+/// heartbeats (see `HEARTBEAT_INTERVAL` in `quality.rs`) and some slack
+const BUSY_AFTER: Duration = Duration::from_secs(12);
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often `blender:busy` is repeated while Blender stays busy
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityState {
+    Disconnected,
+    Responsive,
+    /// Connected, but the main thread stopped sending heartbeats and events
+    Busy,
+}
+
+/// What the connected Blender is doing, as far as the backend can tell
+#[derive(Serialize, Clone, Debug)]
+pub struct BlenderActivity {
+    pub state: ActivityState,
+    /// Time since Blender last sent anything
+    pub silent_secs: Option<f64>,
+    pub busy_since: Option<String>,
+    pub elapsed_secs: Option<f64>,
+    /// Last operator Blender finished, from its heartbeats
+    pub operator: Option<String>,
+    /// Modal operators running at the last heartbeat
+    pub modal: Vec<String>,
+    /// Blocking operation the add-on announced, e.g. `object.bake`
+    pub blocking: Option<String>,
+    /// The connection dropped while Blender was busy, e.g. it was killed
+    /// while hanging, rather than crashing or quitting while responsive
+    pub lost_while_busy: bool,
+}
+
+#[derive(Default)]
+struct Watch {
+    connected: bool,
+    last_message: Option<Instant>,
+    busy_since: Option<(Instant, String)>,
+    operator: Option<String>,
+    modal: Vec<String>,
+    blocking: Option<String>,
+    lost_while_busy: bool,
+    last_report: Option<Instant>,
+}
+
+impl Watch {
+    fn snapshot(&self) -> BlenderActivity {
+        let state = match (self.connected, &self.busy_since) {
+            (false, _) => ActivityState::Disconnected,
+            (true, Some(_)) => ActivityState::Busy,
+            (true, None) => ActivityState::Responsive,
+        };
+        BlenderActivity {
+            state,
+            silent_secs: self
+                .last_message
+                .filter(|_| self.connected)
+                .map(|last| last.elapsed().as_secs_f64()),
+            busy_since: self.busy_since.as_ref().map(|(_, at)| at.clone()),
+            elapsed_secs: self.busy_since.as_ref().map(|(since, _)| since.elapsed().as_secs_f64()),
+            operator: self.operator.clone(),
+            modal: self.modal.clone(),
+            blocking: self.blocking.clone(),
+            lost_while_busy: self.lost_while_busy,
+        }
+    }
+}
+
+/// Tells a Blender stuck in a long modal operator or blocking bake from
+/// one that crashed. A crash or quit closes the connection. A busy main
+/// thread keeps the socket open but stops the heartbeat and event timers.
+#[derive(Default)]
+pub struct Watchdog {
+    watch: Mutex<Watch>,
+}
+
+impl Watchdog {
+    pub fn connected(&self) {
+        *self.watch.lock().unwrap() = Watch {
+            connected: true,
+            last_message: Some(Instant::now()),
+            ..Watch::default()
+        };
+    }
+
+    pub fn disconnected<R: Runtime>(&self, app: &AppHandle<R>) {
+        let activity = {
+            let mut watch = self.watch.lock().unwrap();
+            let was_busy = watch.busy_since.is_some();
+            watch.connected = false;
+            watch.lost_while_busy = was_busy;
+            if !was_busy {
+                return;
+            }
+            let activity = watch.snapshot();
+            watch.busy_since = None;
+            activity
+        };
+        emit(app, &activity);
+    }
+
+    /// Any message shows the main thread is running; heartbeats also carry
+    /// the last operator.
+    pub fn observe<R: Runtime>(&self, app: &AppHandle<R>, message: &Value) {
+        let recovered = {
+            let mut watch = self.watch.lock().unwrap();
+            watch.last_message = Some(Instant::now());
+            let body = protocol::message_body(message);
+            let message_type = protocol::message_type(message);
+            match message_type.as_deref() {
+                Some("heartbeat") => {
+                    watch.operator = body.get("operator").and_then(Value::as_str).map(str::to_string);
+                    watch.modal = body
+                        .get("modal")
+                        .and_then(Value::as_array)
+                        .map(|ops| ops.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                        .unwrap_or_default();
+                }
+                Some("event.operator.blocking") => {
+                    watch.blocking = body.get("operator").and_then(Value::as_str).map(str::to_string);
+                }
+                _ => {}
+            }
+            let recovered = watch.busy_since.is_some().then(|| {
+                let mut activity = watch.snapshot();
+                activity.state = ActivityState::Responsive;
+                watch.busy_since = None;
+                activity
+            });
+            // Heartbeats come from the main thread, so an announced blocking operation is over
+            if recovered.is_some() || message_type.as_deref() == Some("heartbeat") {
+                watch.blocking = None;
+            }
+            recovered
+        };
+        if let Some(activity) = recovered {
+            emit(app, &activity);
+        }
+    }
+
+    /// Mark Blender busy once it has been silent too long; returns the
+    /// activity to report, if any
+    fn check(&self) -> Option<BlenderActivity> {
+        let mut watch = self.watch.lock().unwrap();
+        let Some(last_message) = watch.last_message.filter(|last| watch.connected && last.elapsed() >= BUSY_AFTER)
+        else {
+            return None;
+        };
+        let now = Instant::now();
+        if watch.busy_since.is_none() {
+            // Busy since the last sign of life
+            let silent = chrono::Duration::from_std(last_message.elapsed()).unwrap_or_default();
+            watch.busy_since = Some((last_message, (chrono::Local::now() - silent).to_rfc3339()));
+        } else if watch.last_report.is_some_and(|last| now.duration_since(last) < REPORT_INTERVAL) {
+            return None;
+        }
+        watch.last_report = Some(now);
+        Some(watch.snapshot())
+    }
+
+    pub fn activity(&self) -> BlenderActivity {
+        self.watch.lock().unwrap().snapshot()
+    }
+}
+
+fn emit<R: Runtime>(app: &AppHandle<R>, activity: &BlenderActivity) {
+    if let Err(err) = app.emit("blender:busy", activity) {
+        eprintln!("Failed to emit blender:busy: {err}");
+    }
+}
+
+/// Check for a silent Blender every second and emit `blender:busy` when it
+/// becomes busy and every few seconds while it stays busy.
+pub fn start_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            if let Some(activity) = app.state::<Watchdog>().check() {
+                emit(&app, &activity);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_blender_activity(watchdog: State<'_, Watchdog>) -> BlenderActivity {
+    watchdog.activity()
+}
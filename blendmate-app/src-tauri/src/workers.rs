@@ -0,0 +1,941 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{watch, Mutex};
+
+use crate::a11y::{self, Politeness};
+use crate::errors::CommandError;
+use crate::devices::DeviceProfile;
+use crate::exr;
+use crate::i18n;
+use crate::journal::{JobJournal, JobRequest, JournalEntry};
+use crate::render_logs::RenderLogs;
+use crate::scheduler::Scheduler;
+use crate::scratch::ScratchDirs;
+use crate::settings::SettingsStore;
+use crate::tagging::{self, Tags};
+use crate::tasks::{TaskKind, TaskManager};
+
+/// Applies `WorkItem::devices` before the job script runs
+const DEVICES_SCRIPT: &str = include_str!("../scripts/devices.py");
+
+/// Prefix of structured lines printed by worker scripts, followed by JSON
+/// (`{"progress": 0.5}`, `{"output": "/path"}`, `{"error": "..."}`,
+/// `{"result": {...}}`).
+const REPORT_PREFIX: &str = "BLENDMATE:";
+
+/// Log lines kept per item; older lines are dropped.
+const MAX_LOG_LINES: usize = 500;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WorkerSettings {
+    /// Blender executable used when a job doesn't pick one
+    pub blender_path: String,
+    /// Headless Blender processes allowed to run at once
+    pub max_workers: usize,
+    /// Additional Blender versions jobs can pick by name
+    pub installs: Vec<BlenderInstall>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BlenderInstall {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Serialize)]
+pub struct BlenderInstallInfo {
+    pub name: String,
+    pub path: String,
+    /// First line of `blender --version`; `None` when it could not be run
+    pub version: Option<String>,
+}
+
+impl Default for WorkerSettings {
+    fn default() -> Self {
+        Self {
+            blender_path: "blender".to_string(),
+            max_workers: 2,
+            installs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Export,
+    Upgrade,
+    Render,
+    Benchmark,
+    UnitAudit,
+    Relink,
+    /// A landed frame denoised for delivery
+    Denoise,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+    /// Was queued or running when the app quit; see `resume_job`
+    Interrupted,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Done | JobStatus::Failed | JobStatus::Cancelled | JobStatus::Interrupted
+        )
+    }
+}
+
+/// One headless Blender run: open `blend_file` (if any) and run `script`
+/// with `args` passed as JSON after `--`.
+pub struct WorkItem {
+    pub label: String,
+    pub blend_file: Option<PathBuf>,
+    pub script: &'static str,
+    pub args: Value,
+    /// Cycles devices to use, for rendering jobs
+    pub devices: Option<DeviceProfile>,
+    /// Higher runs first when workers are busy
+    pub priority: i32,
+    /// Extra environment for the Blender process, from the active project
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobItem {
+    pub label: String,
+    pub status: JobStatus,
+    pub progress: f32,
+    pub outputs: Vec<String>,
+    pub errors: Vec<String>,
+    /// Job specific data reported by the script
+    pub result: Option<Value>,
+    pub log: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Job {
+    pub id: String,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub progress: f32,
+    pub blender: String,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub items: Vec<JobItem>,
+    /// Job specific outcome added when the job finishes
+    pub summary: Option<Value>,
+    /// Tags of the first item's .blend file, so outputs can be found by shot
+    #[serde(default)]
+    pub tags: Tags,
+}
+
+struct JobEntry {
+    job: Job,
+    /// Empty for jobs restored from the journal until they are resumed
+    work: Vec<Arc<WorkItem>>,
+    request: JobRequest,
+    cancel: watch::Sender<bool>,
+    /// Task that mirrors this job's progress
+    task: Option<String>,
+    /// Started on its own rather than as a playlist step
+    detached: bool,
+}
+
+/// Running worker processes, suspended while the pool is paused
+#[derive(Default)]
+struct Processes {
+    /// Why the pool is paused, e.g. `power`; empty while it runs
+    holds: BTreeSet<&'static str>,
+    pids: HashSet<u32>,
+}
+
+/// Runs jobs as headless Blender processes, at most `max_workers` at a time
+/// across all jobs, highest item priority first. Job snapshots are emitted
+/// as `job:progress`, and every status change is written to the journal.
+pub struct WorkerPool {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    scheduler: Arc<Scheduler>,
+    processes: std::sync::Mutex<Processes>,
+    next_id: AtomicU64,
+    journal: JobJournal,
+    /// Jobs from the last session that never started, for `resume_restored`
+    restored: std::sync::Mutex<Vec<String>>,
+}
+
+impl WorkerPool {
+    /// Load jobs from the journal. Jobs that were queued or running when
+    /// the app quit become `interrupted`.
+    pub fn new(max_workers: usize, journal: JobJournal) -> Self {
+        let mut jobs = HashMap::new();
+        let mut restored = Vec::new();
+        let mut last_id = 0;
+        for JournalEntry {
+            mut job,
+            request,
+            detached,
+        } in journal.load()
+        {
+            if let Some(number) = job.id.strip_prefix("job-").and_then(|n| n.parse::<u64>().ok()) {
+                last_id = last_id.max(number);
+            }
+            if !job.status.is_finished() {
+                if job.status == JobStatus::Queued && detached {
+                    restored.push(job.id.clone());
+                }
+                job.status = JobStatus::Interrupted;
+                for item in &mut job.items {
+                    if !item.status.is_finished() {
+                        item.status = JobStatus::Interrupted;
+                    }
+                }
+            }
+            let (cancel, _) = watch::channel(false);
+            jobs.insert(
+                job.id.clone(),
+                JobEntry {
+                    job,
+                    work: Vec::new(),
+                    request,
+                    cancel,
+                    task: None,
+                    detached,
+                },
+            );
+        }
+
+        Self {
+            jobs: Mutex::new(jobs),
+            scheduler: Scheduler::new(max_workers),
+            processes: std::sync::Mutex::new(Processes::default()),
+            next_id: AtomicU64::new(last_id + 1),
+            journal,
+            restored: std::sync::Mutex::new(restored),
+        }
+    }
+
+    /// Grow or shrink the number of concurrent workers. Shrinking takes
+    /// effect as running workers finish.
+    pub fn set_max_workers(&self, max_workers: usize) {
+        self.scheduler.set_limit(max_workers);
+    }
+
+    /// Hold queued items and suspend running workers (on Unix) for
+    /// `reason`, or release that hold. Work continues once no reason
+    /// holds it.
+    pub fn set_paused(&self, reason: &'static str, paused: bool) {
+        let mut processes = self.processes.lock().unwrap();
+        let was_paused = !processes.holds.is_empty();
+        if paused {
+            processes.holds.insert(reason);
+        } else {
+            processes.holds.remove(reason);
+        }
+        let paused = !processes.holds.is_empty();
+        if paused == was_paused {
+            return;
+        }
+        for pid in &processes.pids {
+            suspend_process(*pid, paused);
+        }
+        self.scheduler.set_paused(paused);
+    }
+
+    /// Reasons the pool is currently paused for, empty while it runs
+    pub fn holds(&self) -> Vec<&'static str> {
+        self.processes.lock().unwrap().holds.iter().copied().collect()
+    }
+
+    /// Register a queued job. Call `run` to execute it.
+    pub async fn submit(&self, request: JobRequest, settings: &SettingsStore) -> Result<String, CommandError> {
+        let (blender, work) = request.work(settings)?;
+        let id = format!("job-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let patterns = settings.get().tagging.patterns;
+        let tags = work
+            .iter()
+            .find_map(|item| item.blend_file.as_ref())
+            .and_then(|file| tagging::tags_for(&patterns, &file.to_string_lossy()))
+            .map(|(tags, _)| tags)
+            .unwrap_or_default();
+        let items = work
+            .iter()
+            .map(|item| JobItem {
+                label: item.label.clone(),
+                status: JobStatus::Queued,
+                progress: 0.0,
+                outputs: Vec::new(),
+                errors: Vec::new(),
+                result: None,
+                log: Vec::new(),
+            })
+            .collect();
+
+        let job = Job {
+            id: id.clone(),
+            kind: request.kind(),
+            status: JobStatus::Queued,
+            progress: 0.0,
+            blender,
+            created_at: chrono::Local::now().to_rfc3339(),
+            finished_at: None,
+            items,
+            summary: None,
+            tags,
+        };
+        let (cancel, _) = watch::channel(false);
+        let mut jobs = self.jobs.lock().await;
+        jobs.insert(
+            id.clone(),
+            JobEntry {
+                job,
+                work: work.into_iter().map(Arc::new).collect(),
+                request,
+                cancel,
+                task: None,
+                detached: false,
+            },
+        );
+        self.persist(&jobs, &id);
+        Ok(id)
+    }
+
+    /// Requeue an interrupted job, skipping the items that finished
+    /// before, and run it in the background.
+    pub async fn resume<R: Runtime>(&self, app: &AppHandle<R>, id: &str) -> Result<(), CommandError> {
+        let request = self.requeue(app, id).await?;
+        spawn_job(app.clone(), id.to_string(), move |app, job| request.finished(app, job));
+        Ok(())
+    }
+
+    /// Requeue an interrupted job without running it, for callers that run
+    /// it themselves, such as a resumed playlist. Returns its request.
+    pub async fn requeue<R: Runtime>(&self, app: &AppHandle<R>, id: &str) -> Result<JobRequest, CommandError> {
+        let request = {
+            let jobs = self.jobs.lock().await;
+            let entry = jobs
+                .get(id)
+                .ok_or_else(|| CommandError::NotFound(format!("Unknown job '{}'", id)))?;
+            if entry.job.status != JobStatus::Interrupted {
+                return Err(CommandError::InvalidInput(format!("Job '{}' was not interrupted", id)));
+            }
+            entry.request.clone()
+        };
+        let (blender, work) = request.work(&app.state::<SettingsStore>())?;
+
+        {
+            let mut jobs = self.jobs.lock().await;
+            let entry = jobs
+                .get_mut(id)
+                .ok_or_else(|| CommandError::NotFound(format!("Unknown job '{}'", id)))?;
+            // Checked again, as another resume may have requeued it while
+            // the work was planned
+            if entry.job.status != JobStatus::Interrupted {
+                return Err(CommandError::InvalidInput(format!("Job '{}' was not interrupted", id)));
+            }
+            if work.len() != entry.job.items.len() {
+                return Err(CommandError::Failed(format!(
+                    "Job '{}' no longer plans the same items and can't be resumed",
+                    id
+                )));
+            }
+            entry.job.blender = blender;
+            entry.job.status = JobStatus::Queued;
+            entry.job.finished_at = None;
+            for item in &mut entry.job.items {
+                if item.status != JobStatus::Done {
+                    item.status = JobStatus::Queued;
+                    item.progress = 0.0;
+                }
+            }
+            entry.work = work.into_iter().map(Arc::new).collect();
+            entry.cancel = watch::channel(false).0;
+            self.persist(&jobs, id);
+        }
+        self.emit(app, id).await;
+        Ok(request)
+    }
+
+    /// Queue job `id` to be written to the journal. Called with the jobs
+    /// lock held, so writes are queued in the order the changes were made;
+    /// the write itself happens on the journal's thread.
+    fn persist(&self, jobs: &HashMap<String, JobEntry>, id: &str) {
+        if let Some(entry) = jobs.get(id) {
+            self.journal.write(JournalEntry {
+                job: entry.job.clone(),
+                request: entry.request.clone(),
+                detached: entry.detached,
+            });
+        }
+    }
+
+    /// Run every item of a job and return its final state.
+    /// Items already done, from before the job was resumed, are skipped.
+    pub async fn run<R: Runtime>(&self, app: &AppHandle<R>, id: &str) -> Option<Job> {
+        let (blender, work, cancel) = {
+            let mut jobs = self.jobs.lock().await;
+            let entry = jobs.get_mut(id)?;
+            entry.job.status = JobStatus::Running;
+            let work: Vec<(usize, Arc<WorkItem>)> = entry
+                .work
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| entry.job.items[*index].status != JobStatus::Done)
+                .map(|(index, item)| (index, item.clone()))
+                .collect();
+            let snapshot = (entry.job.blender.clone(), work, entry.cancel.subscribe());
+            self.persist(&jobs, id);
+            snapshot
+        };
+        self.emit(app, id).await;
+
+        let runs = work
+            .iter()
+            .map(|(index, item)| self.run_item(app, id, *index, &blender, item, cancel.clone()));
+        futures_util::future::join_all(runs).await;
+        app.state::<ScratchDirs>().remove_job(id);
+
+        let job = {
+            let mut jobs = self.jobs.lock().await;
+            let entry = jobs.get_mut(id)?;
+            let job = &mut entry.job;
+            job.status = if *cancel.borrow() {
+                JobStatus::Cancelled
+            } else if job.items.iter().all(|item| item.status == JobStatus::Done) {
+                JobStatus::Done
+            } else {
+                JobStatus::Failed
+            };
+            job.progress = 1.0;
+            job.finished_at = Some(chrono::Local::now().to_rfc3339());
+            let job = job.clone();
+            self.persist(&jobs, id);
+            job
+        };
+        self.emit(app, id).await;
+        announce_finished(app, &job);
+        Some(job)
+    }
+
+    /// Attach a summary to a job and emit the update.
+    pub async fn annotate<R: Runtime>(&self, app: &AppHandle<R>, id: &str, summary: Value) {
+        {
+            let mut jobs = self.jobs.lock().await;
+            if let Some(entry) = jobs.get_mut(id) {
+                entry.job.summary = Some(summary);
+            }
+            self.persist(&jobs, id);
+        }
+        self.emit(app, id).await;
+    }
+
+    /// Report this job's progress on `task` from now on. Jobs with a task
+    /// run on their own, so they are resumed automatically after a restart.
+    pub async fn link_task(&self, id: &str, task: &str) {
+        let mut jobs = self.jobs.lock().await;
+        if let Some(entry) = jobs.get_mut(id) {
+            entry.task = Some(task.to_string());
+            entry.detached = true;
+        }
+        self.persist(&jobs, id);
+    }
+
+    pub async fn list(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self.jobs.lock().await.values().map(|entry| entry.job.clone()).collect();
+        jobs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        jobs
+    }
+
+    /// Unfinished jobs with the request each was submitted with
+    pub async fn active(&self) -> Vec<(Job, JobRequest)> {
+        let jobs = self.jobs.lock().await;
+        jobs.values()
+            .filter(|entry| !entry.job.status.is_finished())
+            .map(|entry| (entry.job.clone(), entry.request.clone()))
+            .collect()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Job> {
+        self.jobs.lock().await.get(id).map(|entry| entry.job.clone())
+    }
+
+    /// Stop a job: queued items are skipped and running workers are killed.
+    pub async fn cancel(&self, id: &str) -> Result<(), CommandError> {
+        let jobs = self.jobs.lock().await;
+        let entry = jobs
+            .get(id)
+            .ok_or_else(|| CommandError::NotFound(format!("Unknown job '{}'", id)))?;
+        if entry.job.status.is_finished() {
+            return Err(CommandError::InvalidInput(format!("Job '{}' already finished", id)));
+        }
+        entry.cancel.send_replace(true);
+        Ok(())
+    }
+
+    async fn run_item<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        id: &str,
+        index: usize,
+        blender: &str,
+        item: &WorkItem,
+        mut cancel: watch::Receiver<bool>,
+    ) {
+        let _slot = tokio::select! {
+            slot = self.scheduler.acquire(item.priority) => slot,
+            _ = cancelled(&mut cancel) => {
+                self.finish_item(app, id, index, JobStatus::Cancelled, None).await;
+                return;
+            }
+        };
+        let is_cancelled = *cancel.borrow();
+        if is_cancelled {
+            self.finish_item(app, id, index, JobStatus::Cancelled, None).await;
+            return;
+        }
+
+        self.update_item(id, index, |entry| entry.status = JobStatus::Running).await;
+        self.emit(app, id).await;
+        let logs = app.state::<RenderLogs>();
+        if let Some(job) = self.get(id).await {
+            logs.open(id, job.kind, index, &item.label);
+        }
+
+        let mut command = Command::new(blender);
+        command.envs(&item.env).arg("--background").arg("--factory-startup");
+        // Blender and Python put their temporary files in the job's directory
+        if let Some(temp) = app.state::<ScratchDirs>().job_dir(id) {
+            command.env("TMPDIR", &temp).env("TEMP", &temp).env("TMP", &temp);
+        }
+        if let Some(blend_file) = &item.blend_file {
+            command.arg(blend_file);
+        }
+        command.args(["--python-exit-code", "1"]);
+
+        let mut args = item.args.clone();
+        if let (Some(devices), Some(map)) = (&item.devices, args.as_object_mut()) {
+            map.insert("devices".to_string(), serde_json::to_value(devices).unwrap_or_default());
+            command.args(["--python-expr", DEVICES_SCRIPT]);
+        }
+        command
+            .args(["--python-expr", item.script, "--"])
+            .arg(args.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let error = format!("Failed to start Blender '{}': {}", blender, e);
+                self.finish_item(app, id, index, JobStatus::Failed, Some(error)).await;
+                return;
+            }
+        };
+
+        let pid = child.id();
+        if let Some(pid) = pid {
+            let mut processes = self.processes.lock().unwrap();
+            processes.pids.insert(pid);
+            // Started just as the pool was paused
+            if !processes.holds.is_empty() {
+                suspend_process(pid, true);
+            }
+        }
+
+        let mut stdout = child.stdout.take().map(BufReader::new);
+        let mut stderr = child.stderr.take().map(BufReader::new);
+        let mut was_cancelled = false;
+
+        while stdout.is_some() || stderr.is_some() {
+            tokio::select! {
+                line = async { read_line(stdout.as_mut()?).await }, if stdout.is_some() => match line {
+                    Some(line) => {
+                        logs.append(id, index, &line);
+                        self.handle_line(app, id, index, line).await
+                    }
+                    None => stdout = None,
+                },
+                line = async { read_line(stderr.as_mut()?).await }, if stderr.is_some() => match line {
+                    Some(line) => {
+                        logs.append(id, index, &line);
+                        self.update_item(id, index, |entry| push_log(entry, line)).await
+                    }
+                    None => stderr = None,
+                },
+                _ = cancelled(&mut cancel) => {
+                    was_cancelled = true;
+                    break;
+                }
+            }
+        }
+
+        if let Some(pid) = pid {
+            self.processes.lock().unwrap().pids.remove(&pid);
+        }
+
+        if was_cancelled {
+            let _ = child.kill().await;
+            self.finish_item(app, id, index, JobStatus::Cancelled, None).await;
+            return;
+        }
+
+        match child.wait().await {
+            Ok(status) if status.success() => {
+                let failed = {
+                    let jobs = self.jobs.lock().await;
+                    jobs.get(id).is_some_and(|entry| !entry.job.items[index].errors.is_empty())
+                };
+                let status = if failed { JobStatus::Failed } else { JobStatus::Done };
+                self.finish_item(app, id, index, status, None).await;
+            }
+            Ok(status) => {
+                let error = format!("Blender exited with {}", status);
+                self.finish_item(app, id, index, JobStatus::Failed, Some(error)).await;
+            }
+            Err(e) => {
+                let error = format!("Failed to wait for Blender: {}", e);
+                self.finish_item(app, id, index, JobStatus::Failed, Some(error)).await;
+            }
+        }
+    }
+
+    async fn handle_line<R: Runtime>(&self, app: &AppHandle<R>, id: &str, index: usize, line: String) {
+        let Some(report) = line
+            .strip_prefix(REPORT_PREFIX)
+            .and_then(|json| serde_json::from_str::<Value>(json).ok())
+        else {
+            self.update_item(id, index, |entry| push_log(entry, line)).await;
+            return;
+        };
+
+        self.update_item(id, index, |entry| {
+            if let Some(progress) = report.get("progress").and_then(Value::as_f64) {
+                entry.progress = progress.clamp(0.0, 1.0) as f32;
+            }
+            if let Some(output) = report.get("output").and_then(Value::as_str) {
+                entry.outputs.push(output.to_string());
+            }
+            if let Some(error) = report.get("error").and_then(Value::as_str) {
+                entry.errors.push(error.to_string());
+            }
+            if let Some(result) = report.get("result") {
+                entry.result = Some(result.clone());
+            }
+        })
+        .await;
+        if let Some(output) = report.get("output").and_then(Value::as_str) {
+            exr::ingest(app, id, output);
+        }
+        self.emit(app, id).await;
+    }
+
+    async fn finish_item<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        id: &str,
+        index: usize,
+        status: JobStatus,
+        error: Option<String>,
+    ) {
+        let logs = app.state::<RenderLogs>();
+        if let Some(error) = &error {
+            logs.append(id, index, error);
+        }
+        logs.close(app, id, index, status);
+        self.update_item(id, index, |entry| {
+            entry.status = status;
+            if status == JobStatus::Done {
+                entry.progress = 1.0;
+            }
+            entry.errors.extend(error);
+        })
+        .await;
+        self.persist(&*self.jobs.lock().await, id);
+        self.emit(app, id).await;
+    }
+
+    async fn update_item(&self, id: &str, index: usize, change: impl FnOnce(&mut JobItem)) {
+        let mut jobs = self.jobs.lock().await;
+        let Some(entry) = jobs.get_mut(id) else {
+            return;
+        };
+        let job = &mut entry.job;
+        if let Some(item) = job.items.get_mut(index) {
+            change(item);
+        }
+        let total = job.items.len().max(1) as f32;
+        job.progress = job
+            .items
+            .iter()
+            .map(|item| if item.status.is_finished() { 1.0 } else { item.progress })
+            .sum::<f32>()
+            / total;
+    }
+
+    async fn emit<R: Runtime>(&self, app: &AppHandle<R>, id: &str) {
+        let snapshot = self
+            .jobs
+            .lock()
+            .await
+            .get(id)
+            .map(|entry| (entry.job.clone(), entry.task.clone()));
+        let Some((job, task)) = snapshot else {
+            return;
+        };
+        if let Err(err) = app.emit("job:progress", &job) {
+            eprintln!("Failed to emit job:progress: {err}");
+        }
+
+        if let Some(task) = task {
+            let finished = job.items.iter().filter(|item| item.status.is_finished()).count();
+            let message = format!("{} of {} items finished", finished, job.items.len());
+            app.state::<TaskManager>().progress(app, &task, Some(job.progress), Some(message));
+        }
+    }
+}
+
+/// Blender binary for a job: a configured install by name, an explicit
+/// path, or the default executable.
+pub fn resolve_blender(settings: &WorkerSettings, choice: Option<&str>) -> String {
+    match choice {
+        Some(choice) => settings
+            .installs
+            .iter()
+            .find(|install| install.name == choice)
+            .map(|install| install.path.clone())
+            .unwrap_or_else(|| choice.to_string()),
+        None => settings.blender_path.clone(),
+    }
+}
+
+/// First line of `blender --version`
+pub async fn blender_version(path: &str) -> Option<String> {
+    let output = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|line| line.trim().to_string())
+}
+
+/// Next line of worker output, without its line ending; `None` at the end.
+/// Bytes that aren't UTF-8, e.g. a file name in a legacy encoding, are
+/// replaced rather than ending the stream.
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> Option<String> {
+    let mut line = Vec::new();
+    match reader.read_until(b'\n', &mut line).await {
+        Ok(0) | Err(_) => return None,
+        Ok(_) => {}
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    }
+    Some(String::from_utf8_lossy(&line).into_owned())
+}
+
+/// Resolves once the job is cancelled
+pub async fn cancelled(cancel: &mut watch::Receiver<bool>) {
+    if cancel.wait_for(|cancelled| *cancelled).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Stop (`SIGSTOP`) or continue (`SIGCONT`) a worker process
+#[cfg(unix)]
+fn suspend_process(pid: u32, suspend: bool) {
+    let signal = if suspend { "-STOP" } else { "-CONT" };
+    if let Err(e) = std::process::Command::new("kill").arg(signal).arg(pid.to_string()).status() {
+        eprintln!("Failed to signal worker {}: {}", pid, e);
+    }
+}
+
+/// Windows has no process suspension without extra APIs; running workers
+/// finish and only queued items wait.
+#[cfg(not(unix))]
+fn suspend_process(_pid: u32, _suspend: bool) {}
+
+/// Tell screen readers how a job ended; failures interrupt
+fn announce_finished<R: Runtime>(app: &AppHandle<R>, job: &Job) {
+    // Denoising runs once per frame; its delivery is announced instead
+    if matches!(job.kind, JobKind::Denoise) {
+        return;
+    }
+    let (key, politeness) = match job.status {
+        JobStatus::Done => ("a11y-job-done", Politeness::Polite),
+        JobStatus::Failed => ("a11y-job-failed", Politeness::Assertive),
+        JobStatus::Cancelled => ("a11y-job-cancelled", Politeness::Polite),
+        _ => return,
+    };
+    let kind = match job.kind {
+        JobKind::Export => "export",
+        JobKind::Upgrade => "upgrade",
+        JobKind::Render => "render",
+        JobKind::Benchmark => "benchmark",
+        JobKind::UnitAudit => "unit_audit",
+        JobKind::Relink => "relink",
+        JobKind::Denoise => "denoise",
+    };
+    a11y::announce(app, "job", politeness, i18n::t(key, &[("kind", kind.into())]));
+}
+
+fn push_log(item: &mut JobItem, line: String) {
+    if item.log.len() >= MAX_LOG_LINES {
+        item.log.remove(0);
+    }
+    item.log.push(line);
+}
+
+/// Resume the jobs from the last session that were queued but never
+/// started. Jobs that were mid-run stay interrupted until `resume_job`.
+pub fn resume_restored<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let pool = app.state::<WorkerPool>();
+        let restored = std::mem::take(&mut *pool.restored.lock().unwrap());
+        for id in restored {
+            if let Err(err) = pool.resume(&app, &id).await {
+                eprintln!("Failed to resume {id}: {err}");
+            }
+        }
+    });
+}
+
+/// Run a submitted job in the background as a task. `finished` gets its
+/// final state and may return a summary to attach to the job.
+pub fn spawn_job<R: Runtime, F>(app: AppHandle<R>, id: String, finished: F)
+where
+    F: FnOnce(&AppHandle<R>, &Job) -> Option<Value> + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let pool = app.state::<WorkerPool>();
+        let Some(kind) = pool.get(&id).await.map(|job| job.kind) else {
+            return;
+        };
+        let label = match kind {
+            JobKind::Export => format!("Export {}", id),
+            JobKind::Upgrade => format!("Re-save {}", id),
+            JobKind::Render => format!("Render {}", id),
+            JobKind::Benchmark => format!("Benchmark {}", id),
+            JobKind::UnitAudit => format!("Unit audit {}", id),
+            JobKind::Relink => format!("Relink {}", id),
+            JobKind::Denoise => format!("Denoise {}", id),
+        };
+        let task = app.state::<TaskManager>().start(&app, TaskKind::Job, label);
+        pool.link_task(&id, task.id()).await;
+
+        // Cancelling the task stops the job through the pool so it still finishes cleanly
+        let run = pool.run(&app, &id);
+        tokio::pin!(run);
+        let job = tokio::select! {
+            job = &mut run => job,
+            _ = task.cancelled() => {
+                let _ = pool.cancel(&id).await;
+                run.await
+            }
+        };
+
+        let Some(job) = job else {
+            task.finish::<()>(&Err(CommandError::NotFound(format!("Unknown job '{}'", id))));
+            return;
+        };
+        let summary = finished(&app, &job);
+        if let Some(summary) = &summary {
+            pool.annotate(&app, &id, summary.clone()).await;
+        }
+        let failed = job.items.iter().filter(|item| item.status == JobStatus::Failed).count();
+        let result = match job.status {
+            JobStatus::Done => Ok(json!({ "job": job.id, "summary": summary })),
+            JobStatus::Cancelled => Err(CommandError::Cancelled(format!("Job '{}' was cancelled", id))),
+            _ => Err(CommandError::Failed(format!("{} of {} items failed", failed, job.items.len()))),
+        };
+        task.finish(&result);
+    });
+}
+
+/// Jobs, only those with all of `tags` when given
+#[tauri::command]
+pub async fn list_jobs(tags: Option<Tags>, pool: State<'_, WorkerPool>) -> Result<Vec<Job>, CommandError> {
+    let jobs = pool.list().await;
+    Ok(jobs.into_iter().filter(|job| tagging::matches(&job.tags, tags.as_ref())).collect())
+}
+
+#[tauri::command]
+pub async fn get_job(id: String, pool: State<'_, WorkerPool>) -> Result<Job, CommandError> {
+    pool.get(&id)
+        .await
+        .ok_or_else(|| CommandError::NotFound(format!("Unknown job '{}'", id)))
+}
+
+#[tauri::command]
+pub async fn cancel_job(id: String, pool: State<'_, WorkerPool>) -> Result<(), CommandError> {
+    pool.cancel(&id).await
+}
+
+/// Run an interrupted job again; items that finished before are kept.
+#[tauri::command]
+pub async fn resume_job<R: Runtime>(
+    id: String,
+    app: AppHandle<R>,
+    pool: State<'_, WorkerPool>,
+) -> Result<(), CommandError> {
+    pool.resume(&app, &id).await
+}
+
+#[tauri::command]
+pub fn get_worker_settings(settings: State<'_, SettingsStore>) -> WorkerSettings {
+    settings.get().workers
+}
+
+#[tauri::command]
+pub fn set_worker_settings(
+    workers: WorkerSettings,
+    settings: State<'_, SettingsStore>,
+    pool: State<'_, WorkerPool>,
+) -> Result<(), CommandError> {
+    if workers.max_workers == 0 {
+        return Err(CommandError::InvalidInput("At least one worker is needed".to_string()));
+    }
+    pool.set_max_workers(workers.max_workers);
+    settings.update(|s| s.workers = workers)?;
+    Ok(())
+}
+
+/// Configured Blender installs with their reported versions
+#[tauri::command]
+pub async fn list_blender_installs(
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<BlenderInstallInfo>, CommandError> {
+    let workers = settings.get().workers;
+    let mut installs = Vec::new();
+    for install in workers.installs {
+        let version = blender_version(&install.path).await;
+        installs.push(BlenderInstallInfo {
+            name: install.name,
+            path: install.path,
+            version,
+        });
+    }
+    Ok(installs)
+}
@@ -11,6 +11,7 @@ import { useState, useRef, useEffect, useCallback } from 'react';
 import { Send, Loader2, Bot, User, Wrench, Zap } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
 import { useBlenderStore, type BlenderSceneData, type BlenderCapabilities } from '@/stores/blenderStore';
+import { isCommandError } from '@/types/commands';
 
 type Message = {
   id: string;
@@ -421,7 +422,7 @@ export default function Mate({ sceneData, selectedObjectName }: MateProps) {
         {
           id: `error-${Date.now()}`,
           role: 'assistant',
-          content: `Chyba: ${error instanceof Error ? error.message : 'Nepodařilo se spojit s AI'}`,
+          content: `Chyba: ${isCommandError(error) || error instanceof Error ? error.message : 'Nepodařilo se spojit s AI'}`,
         },
       ]);
     } finally {
@@ -2,6 +2,7 @@ import { create } from 'zustand';
 import { subscribeWithSelector } from 'zustand/middleware';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
+import { isCommandError } from '@/types/commands';
 
 // Types
 export type BlenderModifier = {
@@ -22,6 +23,7 @@ export type BlenderMeshData = {
   edges: number;
   polygons: number;
   materials: number;
+  has_uv?: boolean;
 };
 
 export type BlenderGeometry = {
@@ -497,7 +499,7 @@ export const useBlenderStore = create<BlenderState>()(
             // Clean up on send failure
             clearTimeout(timeout);
             state._pendingCommands.delete(id);
-            resolve({ success: false, error: `Failed to send: ${e}` });
+            resolve({ success: false, error: `Failed to send: ${isCommandError(e) ? e.message : String(e)}` });
           });
       });
     },
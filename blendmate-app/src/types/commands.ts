@@ -0,0 +1,34 @@
+/**
+ * Types for the Rust backend's Tauri commands
+ */
+
+export type CommandErrorCode =
+  | 'not_connected'
+  | 'timeout'
+  | 'blender'
+  | 'invalid_input'
+  | 'not_found'
+  | 'io'
+  | 'unsupported'
+  | 'cancelled'
+  | 'failed';
+
+/** What every backend command rejects with */
+export interface CommandError {
+  code: CommandErrorCode;
+  message: string;
+  details: unknown | null;
+  // Safe to call again unchanged, e.g. once Blender reconnects
+  retryable: boolean;
+}
+
+export interface CommandManifest {
+  version: number;
+  app_version: string;
+  commands: string[];
+  error_codes: CommandErrorCode[];
+}
+
+export function isCommandError(error: unknown): error is CommandError {
+  return typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+}
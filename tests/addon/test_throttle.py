@@ -23,7 +23,7 @@ class TestThrottle(unittest.TestCase):
         """Reset throttle state before each test."""
         throttle._pending_events.clear()
         throttle._dirty_reasons.clear()
-        throttle._throttle_interval = 0.1
+        throttle._throttle_interval = throttle.DEFAULT_INTERVAL
         
         # Mock the send function
         self.mock_send = MagicMock()
@@ -37,10 +37,16 @@ class TestThrottle(unittest.TestCase):
         throttle._dirty_reasons.clear()
         throttle._send_function = None
 
-    def test_throttle_interval_is_fixed(self):
-        """Test that throttle interval is fixed at 100ms."""
+    def test_throttle_interval_defaults_to_100ms(self):
+        """Test that throttle interval starts at 100ms."""
         self.assertEqual(throttle._throttle_interval, 0.1)
 
+    def test_set_interval_clamps_to_minimum(self):
+        """Test that the interval can be lengthened but not set below 10ms."""
+        self.assertEqual(throttle.set_interval(0.3), 0.3)
+        self.assertEqual(throttle._throttle_interval, 0.3)
+        self.assertEqual(throttle.set_interval(0), 0.01)
+
     def test_throttle_event_queues_event(self):
         """Test that throttle_event queues an event."""
         event_data = {"type": "event", "event": "test_event"}